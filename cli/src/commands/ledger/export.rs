@@ -0,0 +1,60 @@
+use std::{fs::File, io::Write, path::PathBuf};
+
+use node::rpc::{RpcLedgerExportKind, RpcLedgerExportResponse};
+use reqwest::Url;
+
+use super::LedgerFormat;
+
+#[derive(Debug, clap::Args)]
+/// Export a ledger held by a running node, together with its hash.
+pub struct Export {
+    /// Http server of the node.
+    #[arg(long, default_value = "http://127.0.0.1:3000")]
+    pub node: Url,
+
+    /// Ledger to export: staking-epoch, next-epoch, root-snarked or
+    /// best-tip-staged.
+    #[arg(long, short)]
+    pub ledger: RpcLedgerExportKind,
+
+    #[arg(long, short, value_enum, default_value_t = LedgerFormat::Json)]
+    pub format: LedgerFormat,
+
+    /// File to write the ledger to. Standard output if not set.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl Export {
+    pub fn run(self) -> anyhow::Result<()> {
+        let mut url = self.node.join("ledger/export")?;
+        url.query_pairs_mut()
+            .append_pair("ledger", &self.ledger.to_string());
+
+        // Exporting a big ledger takes a while.
+        let client = reqwest::blocking::Client::builder().timeout(None).build()?;
+        let response: RpcLedgerExportResponse = client.get(url).send()?.json()?;
+        let export = response.map_err(|err| anyhow::anyhow!("node error: {err}"))?;
+
+        let mut writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(File::create(path)?),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        match self.format {
+            LedgerFormat::Json => {
+                let ledger = export.to_daemon_json()?;
+                serde_json::to_writer_pretty(&mut writer, &ledger)?;
+            }
+            LedgerFormat::Binprot => export.store(&mut writer)?,
+        }
+        writer.flush()?;
+
+        eprintln!(
+            "exported {} ledger {} with {} accounts",
+            self.ledger,
+            export.ledger_hash,
+            export.accounts.len()
+        );
+        Ok(())
+    }
+}
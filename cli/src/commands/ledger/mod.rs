@@ -0,0 +1,35 @@
+pub mod export;
+pub use export::Export;
+
+pub mod prebuilt_genesis;
+pub use prebuilt_genesis::PrebuiltGenesis;
+
+#[derive(Debug, clap::Args)]
+pub struct Ledger {
+    #[command(subcommand)]
+    pub command: LedgerCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum LedgerCommand {
+    Export(Export),
+    PrebuiltGenesis(PrebuiltGenesis),
+}
+
+impl Ledger {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            LedgerCommand::Export(v) => v.run(),
+            LedgerCommand::PrebuiltGenesis(v) => v.run(),
+        }
+    }
+}
+
+/// Format of the exported ledger file.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum LedgerFormat {
+    /// The `ledger` section of the daemon.json, as produced by
+    /// `mina ledger export`.
+    Json,
+    Binprot,
+}
@@ -0,0 +1,80 @@
+use std::{fs::File, io::BufReader, path::Path, path::PathBuf};
+
+use mina_p2p_messages::v2::{self, PROTOCOL_CONSTANTS};
+use node::daemon_json;
+use node::ledger::LedgerExport;
+use node::transition_frontier::genesis::PrebuiltGenesisConfig;
+
+use super::LedgerFormat;
+
+#[derive(Debug, clap::Args)]
+/// Build a prebuilt genesis config from exported ledgers, e.g. to start a
+/// test network from the state of a real one.
+///
+/// The result can be used as `GenesisConfig::Prebuilt`.
+pub struct PrebuiltGenesis {
+    /// Exported ledger to use as the genesis ledger.
+    #[arg(long, short)]
+    pub ledger: PathBuf,
+
+    /// Exported staking epoch ledger. Genesis ledger if not set.
+    #[arg(long)]
+    pub staking_epoch_ledger: Option<PathBuf>,
+
+    /// Exported next epoch ledger. Genesis ledger if not set.
+    #[arg(long)]
+    pub next_epoch_ledger: Option<PathBuf>,
+
+    /// Format of the exported ledgers.
+    #[arg(long, short, value_enum, default_value_t = LedgerFormat::Json)]
+    pub format: LedgerFormat,
+
+    /// Genesis timestamp in milliseconds. Default protocol constants are
+    /// used otherwise.
+    #[arg(long)]
+    pub genesis_timestamp_ms: Option<u64>,
+
+    #[arg(long, short)]
+    pub output: PathBuf,
+}
+
+impl PrebuiltGenesis {
+    pub fn run(self) -> anyhow::Result<()> {
+        let read = |path: &Path| read_ledger_export(path, self.format);
+        let genesis_ledger = read(&self.ledger)?;
+        let staking_epoch_ledger = self.staking_epoch_ledger.as_deref().map(read).transpose()?;
+        let next_epoch_ledger = self.next_epoch_ledger.as_deref().map(read).transpose()?;
+
+        let mut constants = PROTOCOL_CONSTANTS;
+        if let Some(timestamp_ms) = self.genesis_timestamp_ms {
+            constants.genesis_state_timestamp = v2::BlockTimeTimeStableV1(
+                v2::UnsignedExtendedUInt64Int64ForVersionTagsStableV1(timestamp_ms.into()),
+            );
+        }
+
+        let config = PrebuiltGenesisConfig::from_ledger_exports(
+            constants,
+            genesis_ledger,
+            staking_epoch_ledger,
+            next_epoch_ledger,
+        )?;
+        config.store(File::create(&self.output)?)?;
+
+        eprintln!(
+            "prebuilt genesis config written to {}",
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+fn read_ledger_export(path: &Path, format: LedgerFormat) -> anyhow::Result<LedgerExport> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(match format {
+        LedgerFormat::Json => {
+            let ledger: daemon_json::Ledger = serde_json::from_reader(reader)?;
+            LedgerExport::from_daemon_json(&ledger)?
+        }
+        LedgerFormat::Binprot => LedgerExport::read(reader)?,
+    })
+}
@@ -1,7 +1,12 @@
+use std::path::PathBuf;
+
 pub mod build_info;
+pub mod ledger;
 pub mod misc;
 pub mod node;
 pub mod replay;
+#[cfg(unix)]
+pub mod signer;
 pub mod snark;
 
 #[derive(Debug, clap::Parser)]
@@ -17,6 +22,19 @@ pub struct OpenminaCli {
     /// Select the network (devnet or mainnet)
     pub network: Network,
 
+    /// Network config JSON file, for networks other than devnet and mainnet.
+    ///
+    /// Describes network's name, id, signature prefix, default peers,
+    /// constraint constants, circuit blobs directory and optionally the
+    /// daemon.json with the genesis.
+    #[arg(
+        global = true,
+        long,
+        env = "OPENMINA_NETWORK_CONFIG",
+        conflicts_with = "network"
+    )]
+    pub network_config: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -36,6 +54,12 @@ pub enum Command {
     /// Miscilaneous utilities.
     Misc(misc::Misc),
     Replay(replay::Replay),
+    /// Ledger export and conversion utilities.
+    Ledger(ledger::Ledger),
+    /// Block producer key signer, used by the node started with
+    /// `--producer-signer-socket` or `--producer-signer-http`.
+    #[cfg(unix)]
+    Signer(signer::Signer),
     BuildInfo(build_info::Command),
 }
 
@@ -46,6 +70,9 @@ impl Command {
             Self::Node(v) => v.run(),
             Self::Misc(v) => v.run(),
             Self::Replay(v) => v.run(),
+            Self::Ledger(v) => v.run(),
+            #[cfg(unix)]
+            Self::Signer(v) => v.run(),
             Self::BuildInfo(v) => v.run(),
         }
     }
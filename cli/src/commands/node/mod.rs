@@ -1,9 +1,10 @@
-use std::{fs::File, path::PathBuf, sync::Arc};
+use std::{fs::File, net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 
 use anyhow::Context;
-use ledger::proofs::provers::BlockProver;
+use ledger::proofs::provers::{BlockProver, TransactionProver, ZkappProver};
 use node::{
     account::AccountSecretKey,
+    ledger::ArchiveTarget,
     snark::{BlockVerifier, TransactionVerifier},
     transition_frontier::genesis::GenesisConfig,
 };
@@ -15,8 +16,10 @@ use node::core::log::inner::Level;
 use node::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
 use node::p2p::identity::SecretKey;
 use node::service::Recorder;
-use node::SnarkerStrategy;
+use node::{SnarkPoolConfig, SnarkerPricing, SnarkerStrategy};
 
+#[cfg(unix)]
+use openmina_node_native::block_producer::RemoteSignerAddr;
 use openmina_node_native::{tracing, NodeBuilder};
 
 /// Openmina node
@@ -77,10 +80,19 @@ pub struct Node {
 
     /// Run Snark Worker.
     ///
-    /// Pass snarker private key as an argument.
+    /// Pass snarker private key as an argument. By default snark work is
+    /// proven in-process, see `--snark-worker`.
     #[arg(long, env, group = "snarker")]
     pub run_snarker: Option<AccountSecretKey>,
 
+    /// Which snark worker proves the snark work.
+    #[arg(long, env, default_value = "native", requires = "snarker")]
+    pub snark_worker: SnarkWorkerKind,
+
+    /// Path to the `mina` executable, used by the external snark worker.
+    #[arg(long, env = "MINA_EXE_PATH")]
+    pub snark_worker_exe: Option<PathBuf>,
+
     /// Snark fee, in Mina
     #[arg(long, env, default_value_t = 1_000_000, requires = "snarker")]
     pub snarker_fee: u64,
@@ -88,6 +100,34 @@ pub struct Node {
     #[arg(long, env, default_value = "seq", requires = "snarker")]
     pub snarker_strategy: SnarkerStrategy,
 
+    /// Additional snark fee for each account update of a transaction
+    /// job beyond the first one, in nanomina.
+    #[arg(long, env, default_value_t = 0, requires = "snarker")]
+    pub snarker_account_update_fee: u64,
+
+    /// Snark fee for merge jobs, in nanomina. Defaults to `--snarker-fee`.
+    #[arg(long, env, requires = "snarker")]
+    pub snarker_merge_fee: Option<u64>,
+
+    /// Number of the oldest jobs, which are charged with
+    /// `--snarker-priority-premium`.
+    #[arg(long, env, default_value_t = 0, requires = "snarker")]
+    pub snarker_priority_jobs: usize,
+
+    /// Premium, in percent, added to the fee of the oldest jobs.
+    #[arg(long, env, default_value_t = 0, requires = "snarker")]
+    pub snarker_priority_premium: u64,
+
+    /// Take over jobs claimed by other snarkers at a higher fee, by
+    /// bidding this much (in nanomina) below them. If 0, claimed jobs
+    /// are skipped.
+    #[arg(long, env, default_value_t = 0, requires = "snarker")]
+    pub snarker_undercut: u64,
+
+    /// Maximum snark fee for any job, in nanomina.
+    #[arg(long, env, requires = "snarker")]
+    pub snarker_max_fee: Option<u64>,
+
     /// Enable block producer with this key file
     ///
     /// MINA_PRIVKEY_PASS must be set to decrypt the keyfile
@@ -96,6 +136,24 @@ pub struct Node {
     #[arg(env = "MINA_PRIVKEY_PASS")]
     pub producer_key_password: Option<String>,
 
+    /// Enable block producer with the key held by the signer process
+    /// listening on this Unix socket (see `openmina signer`), instead
+    /// of loading the key into the node.
+    #[cfg(unix)]
+    #[arg(long, env, group = "producer")]
+    pub producer_signer_socket: Option<PathBuf>,
+
+    /// Enable block producer with the key held by the signer process
+    /// listening for HTTP requests on this address (see `openmina signer`).
+    #[cfg(unix)]
+    #[arg(long, env, group = "producer", requires = "producer_signer_token")]
+    pub producer_signer_http: Option<SocketAddr>,
+
+    /// Token sent with the HTTP requests to the signer.
+    #[cfg(unix)]
+    #[arg(long, env = "OPENMINA_SIGNER_TOKEN", hide_env_values = true)]
+    pub producer_signer_token: Option<String>,
+
     /// Address to send coinbase rewards to (if this node is producing blocks).
     /// If not provided, coinbase rewards will be sent to the producer
     /// of a block.
@@ -105,6 +163,15 @@ pub struct Node {
     #[arg(long, requires = "producer")]
     pub coinbase_receiver: Option<AccountPublicKey>,
 
+    /// Produce blocks with an additional key file, on top of `--producer-key`.
+    ///
+    /// Optionally followed by a comma and the address to send coinbase
+    /// rewards for this key's blocks to, e.g. `./key2,B62q...`.
+    /// Can be passed multiple times. Key files are decrypted with
+    /// MINA_PRIVKEY_PASS.
+    #[arg(long, requires = "producer")]
+    pub additional_producer_key: Vec<AdditionalProducerKey>,
+
     #[arg(long, default_value = "none", env)]
     pub record: String,
 
@@ -112,7 +179,62 @@ pub struct Node {
     #[arg(long)]
     pub no_peers_discovery: bool,
 
+    /// Relay webrtc signaling through connected peers.
+    ///
+    /// Peers running older versions don't support it.
+    #[arg(long, env)]
+    pub p2p_signaling_relay: bool,
+
+    /// Do not persist transition frontier and ledgers in the work dir.
+    ///
+    /// Node will have to bootstrap from scratch on each restart.
+    #[arg(long, env)]
+    pub no_ledger_storage: bool,
+
+    /// Store snarked ledgers on disk in the work dir instead of memory.
+    ///
+    /// Lowers memory usage at the cost of slower ledger access.
+    #[arg(long, env)]
+    pub ledger_on_disk: bool,
+
+    /// Maximum number of completed snarks kept in the snark pool. Snarks
+    /// with the highest fee are evicted first.
+    #[arg(long, env, default_value_t = 4096)]
+    pub snark_pool_max_snarks: usize,
+
+    /// Maximum number of snarks received from peers and waiting to be
+    /// verified. Ones with the highest fee are evicted first.
+    #[arg(long, env, default_value_t = 8192)]
+    pub snark_pool_max_candidates: usize,
+
+    /// Seconds after which snarks received from peers, but stuck before
+    /// getting verified, are dropped.
+    #[arg(long, env, default_value_t = 600)]
+    pub snark_pool_candidate_timeout: u64,
+
+    /// Do not persist the snark pool in the work dir.
+    #[arg(long, env)]
+    pub no_snark_pool_storage: bool,
+
+    /// Write blocks added to the best chain into this directory as
+    /// precomputed blocks, which can be imported into the archive database
+    /// with `mina-archive-blocks --precomputed`.
+    #[arg(long, env, group = "archive")]
+    pub archive_precomputed_dir: Option<PathBuf>,
+
+    /// Send blocks added to the best chain to the archive process listening
+    /// on this address (its `--server-port`).
+    #[arg(long, env, group = "archive")]
+    pub archive_address: Option<SocketAddr>,
+
+    /// Archive the whole best chain on startup, not just blocks added after.
+    #[arg(long, env, requires = "archive")]
+    pub archive_backfill: bool,
+
     /// Config JSON file to load at startup.
+    ///
+    /// Defaults to the genesis config of the network, when it is described
+    /// by a network config file.
     // TODO: make this argument required.
     #[arg(short = 'c', long, env)]
     pub config: Option<PathBuf>,
@@ -128,7 +250,12 @@ impl Node {
             .build_global()
             .context("failed to initialize threadpool")?;
 
-        let (daemon_conf, genesis_conf) = match self.config {
+        let config = self.config.or_else(|| {
+            openmina_core::NetworkConfig::global()
+                .genesis_config
+                .clone()
+        });
+        let (daemon_conf, genesis_conf) = match config {
             Some(config) => {
                 let reader = File::open(config).context("config file {config:?}")?;
                 let config: node::daemon_json::DaemonJson =
@@ -194,6 +321,8 @@ impl Node {
         self.seed.then(|| node_builder.p2p_seed_node());
         self.no_peers_discovery
             .then(|| node_builder.p2p_no_discovery());
+        self.p2p_signaling_relay
+            .then(|| node_builder.p2p_signaling_relay());
 
         node_builder.initial_peers(self.peers);
         if let Some(path) = self.peer_list_file {
@@ -209,28 +338,134 @@ impl Node {
             .block_verifier_index(block_verifier_index.clone())
             .work_verifier_index(work_verifier_index.clone());
 
+        let make_block_provers = || {
+            node::core::info!(node::core::log::system_time(); summary = "loading provers index");
+            let provers = BlockProver::make(
+                Some(block_verifier_index.clone()),
+                Some(work_verifier_index.clone()),
+            );
+            node::core::info!(node::core::log::system_time(); summary = "loaded provers index");
+            provers
+        };
+        let mut is_producer = false;
         if let (Some(producer_key_path), Some(pasword)) =
             (self.producer_key, &self.producer_key_password)
         {
-            node::core::info!(node::core::log::system_time(); summary = "loading provers index");
-            let provers = BlockProver::make(Some(block_verifier_index), Some(work_verifier_index));
-            node::core::info!(node::core::log::system_time(); summary = "loaded provers index");
-            node_builder.block_producer_from_file(provers, producer_key_path, pasword)?;
+            node_builder.block_producer_from_file(
+                make_block_provers(),
+                producer_key_path,
+                pasword,
+            )?;
+            is_producer = true;
+        }
+        #[cfg(unix)]
+        if let Some(socket_path) = self.producer_signer_socket {
+            node_builder.block_producer_with_remote_signer(
+                make_block_provers(),
+                RemoteSignerAddr::Unix(socket_path),
+            )?;
+            is_producer = true;
+        }
+        #[cfg(unix)]
+        if let (Some(addr), Some(token)) = (self.producer_signer_http, self.producer_signer_token) {
+            node_builder.block_producer_with_remote_signer(
+                make_block_provers(),
+                RemoteSignerAddr::Http { addr, token },
+            )?;
+            is_producer = true;
+        }
 
+        if is_producer {
             if let Some(pub_key) = self.coinbase_receiver {
                 node_builder
                     .custom_coinbase_receiver(pub_key.into())
                     .unwrap();
             }
+
+            if !self.additional_producer_key.is_empty() {
+                let pasword = self.producer_key_password.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "MINA_PRIVKEY_PASS must be set to decrypt additional producer keys"
+                    )
+                })?;
+                for key in self.additional_producer_key {
+                    node_builder.additional_block_producer_from_file(
+                        key.path,
+                        pasword,
+                        key.coinbase_receiver.map(Into::into),
+                    )?;
+                }
+            }
         }
 
         if let Some(sec_key) = self.run_snarker {
-            node_builder.snarker(sec_key, self.snarker_fee, self.snarker_strategy);
+            match self.snark_worker {
+                SnarkWorkerKind::Native => {
+                    node::core::info!(node::core::log::system_time(); summary = "loading snark worker provers index");
+                    let tx_prover = TransactionProver::make(Some(work_verifier_index.clone()));
+                    let zkapp_prover = ZkappProver::make(Some(work_verifier_index));
+                    node::core::info!(node::core::log::system_time(); summary = "loaded snark worker provers index");
+                    node_builder.snarker(
+                        tx_prover,
+                        zkapp_prover,
+                        sec_key,
+                        self.snarker_fee,
+                        self.snarker_strategy,
+                    );
+                }
+                SnarkWorkerKind::External => {
+                    let exe_path = self.snark_worker_exe.context(
+                        "`--snark-worker-exe` or MINA_EXE_PATH must be set for the external snark worker",
+                    )?;
+                    node_builder.snarker_with_external_worker(
+                        exe_path,
+                        sec_key,
+                        self.snarker_fee,
+                        self.snarker_strategy,
+                    );
+                }
+            }
+            node_builder.snarker_pricing(SnarkerPricing {
+                account_update_fee: self.snarker_account_update_fee,
+                merge_fee: self.snarker_merge_fee,
+                priority_jobs: self.snarker_priority_jobs,
+                priority_premium_percent: self.snarker_priority_premium,
+                undercut: self.snarker_undercut,
+                max_fee: self.snarker_max_fee,
+            })?;
         }
 
         let work_dir = shellexpand::full(&self.work_dir).unwrap().into_owned();
         openmina_core::set_work_dir(work_dir.clone().into());
 
+        node_builder.address_book(PathBuf::from(&work_dir).join("peers.json"));
+
+        if !self.no_ledger_storage {
+            node_builder.ledger_storage(PathBuf::from(&work_dir).join("ledger"));
+        }
+        if self.ledger_on_disk {
+            node_builder.ledger_on_disk(PathBuf::from(&work_dir).join("ledgers"));
+        }
+
+        node_builder.snark_pool_config(SnarkPoolConfig {
+            max_snarks: self.snark_pool_max_snarks,
+            max_candidates: self.snark_pool_max_candidates,
+            candidate_timeout: Duration::from_secs(self.snark_pool_candidate_timeout),
+            ..Default::default()
+        });
+        if !self.no_snark_pool_storage {
+            node_builder.snark_pool_storage(PathBuf::from(&work_dir).join("snark_pool.bin"));
+        }
+
+        if let Some(dir) = self.archive_precomputed_dir {
+            node_builder.archive(
+                ArchiveTarget::PrecomputedBlocksDir(dir),
+                self.archive_backfill,
+            );
+        } else if let Some(addr) = self.archive_address {
+            node_builder.archive(ArchiveTarget::ArchiveProcess(addr), self.archive_backfill);
+        }
+
         node_builder
             .http_server(self.port)
             .gather_stats()
@@ -253,3 +488,80 @@ impl Node {
         Ok(())
     }
 }
+
+/// Snark worker used by the snarker.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SnarkWorkerKind {
+    /// Prove snark work in-process.
+    Native,
+    /// Prove snark work with the `mina` executable's snark worker, as
+    /// set by `--snark-worker-exe`.
+    External,
+}
+
+/// Additional block producer key file with an optional coinbase receiver,
+/// in the `<PATH>[,<COINBASE_RECEIVER>]` format. If the text after the last
+/// comma isn't a valid public key, the whole text is taken as the path.
+#[derive(Debug, Clone)]
+pub struct AdditionalProducerKey {
+    pub path: PathBuf,
+    pub coinbase_receiver: Option<AccountPublicKey>,
+}
+
+impl FromStr for AdditionalProducerKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Path may contain commas itself, so the text after the last one is
+        // only taken as the coinbase receiver if it is a valid public key.
+        let (path, coinbase_receiver) = s
+            .rsplit_once(',')
+            .and_then(|(path, receiver)| Some((path, Some(receiver.parse().ok()?))))
+            .unwrap_or((s, None));
+        if path.is_empty() {
+            return Err("producer key path is empty".to_owned());
+        }
+        Ok(Self {
+            path: path.into(),
+            coinbase_receiver,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECEIVER: &str = "B62qiy32p8kAKnny8ZFwoMhYpBppM1DWVCqAPBYNcXnsAHhnfAAuXgg";
+
+    #[test]
+    fn test_additional_producer_key_path() {
+        let key = AdditionalProducerKey::from_str("/keys/producer-2").unwrap();
+        assert_eq!(key.path, PathBuf::from("/keys/producer-2"));
+        assert!(key.coinbase_receiver.is_none());
+    }
+
+    #[test]
+    fn test_additional_producer_key_with_coinbase_receiver() {
+        let key =
+            AdditionalProducerKey::from_str(&format!("/keys/a,b/producer,{RECEIVER}")).unwrap();
+        assert_eq!(key.path, PathBuf::from("/keys/a,b/producer"));
+        assert_eq!(
+            key.coinbase_receiver,
+            Some(AccountPublicKey::from_str(RECEIVER).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_additional_producer_key_path_with_comma() {
+        let key = AdditionalProducerKey::from_str("/keys/producer,backup").unwrap();
+        assert_eq!(key.path, PathBuf::from("/keys/producer,backup"));
+        assert!(key.coinbase_receiver.is_none());
+    }
+
+    #[test]
+    fn test_additional_producer_key_invalid() {
+        assert!(AdditionalProducerKey::from_str("").is_err());
+        assert!(AdditionalProducerKey::from_str(&format!(",{RECEIVER}")).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    net::{SocketAddr, TcpListener},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use ledger::proofs::provers::BlockProver;
+use node::{account::AccountSecretKey, core::log::inner::Level};
+use openmina_node_native::{
+    block_producer::{
+        remote_signer_bind, remote_signer_serve, remote_signer_serve_http, BlockProductionGuard,
+        LocalKeyBackend,
+    },
+    tracing,
+};
+
+/// Holds the block producer key, computes vrf outputs and proves blocks
+/// for the node started with `--producer-signer-socket` or
+/// `--producer-signer-http`.
+#[derive(Debug, clap::Args)]
+#[command(group(clap::ArgGroup::new("listen").required(true)))]
+pub struct Signer {
+    #[arg(
+        long,
+        short = 'd',
+        default_value = "~/.openmina",
+        env = "OPENMINA_HOME"
+    )]
+    pub work_dir: String,
+
+    /// Block producer key file.
+    ///
+    /// MINA_PRIVKEY_PASS must be set to decrypt the keyfile
+    #[arg(long, env)]
+    pub producer_key: PathBuf,
+    #[arg(env = "MINA_PRIVKEY_PASS")]
+    pub producer_key_password: String,
+
+    /// Unix socket to listen on for requests from the node. Only the
+    /// user running the signer may connect to it.
+    #[arg(long, env = "OPENMINA_SIGNER_SOCKET", group = "listen")]
+    pub socket: Option<PathBuf>,
+
+    /// Address to listen on for HTTP requests from the node, which must
+    /// be authenticated with `--token`.
+    ///
+    /// The token is sent in plaintext, so only loopback addresses are
+    /// accepted, unless `--http-allow-remote` is set.
+    #[arg(
+        long,
+        env = "OPENMINA_SIGNER_HTTP",
+        group = "listen",
+        requires = "token"
+    )]
+    pub http: Option<SocketAddr>,
+
+    /// Allow listening for HTTP requests on a non-loopback address. The
+    /// network between the node and the signer must be trusted, as the
+    /// token and the requests aren't encrypted.
+    #[arg(long, env = "OPENMINA_SIGNER_HTTP_ALLOW_REMOTE", requires = "http")]
+    pub http_allow_remote: bool,
+
+    /// Token which the node must send with HTTP requests.
+    #[arg(long, env = "OPENMINA_SIGNER_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// File where the slots, for which blocks were proven, are recorded.
+    /// The signer refuses to prove two different blocks for one slot.
+    ///
+    /// Defaults to `signer-proven-slots.json` in the work dir.
+    #[arg(long, env)]
+    pub proven_slots_file: Option<PathBuf>,
+
+    /// Verbosity level
+    #[arg(long, short, env, default_value = "info")]
+    pub verbosity: Level,
+}
+
+impl Signer {
+    pub fn run(self) -> anyhow::Result<()> {
+        tracing::initialize(self.verbosity);
+
+        if let Some(addr) = self.http.filter(|addr| !addr.ip().is_loopback()) {
+            if !self.http_allow_remote {
+                anyhow::bail!(
+                    "Refusing to listen for HTTP requests on non-loopback address {addr}, the token would be sent in plaintext. Use `--http-allow-remote` to allow it"
+                );
+            }
+            node::core::warn!(node::core::log::system_time();
+                summary = "signer listening on a non-loopback address, the token is sent in plaintext",
+                http = addr.to_string());
+        }
+
+        let key =
+            AccountSecretKey::from_encrypted_file(&self.producer_key, &self.producer_key_password)
+                .context("Failed to decrypt secret key file")?;
+
+        let work_dir = shellexpand::full(&self.work_dir).unwrap().into_owned();
+        let proven_slots_file = self
+            .proven_slots_file
+            .unwrap_or_else(|| PathBuf::from(&work_dir).join("signer-proven-slots.json"));
+        if let Some(dir) = proven_slots_file.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create the work dir")?;
+        }
+        let guard = BlockProductionGuard::load(&proven_slots_file)
+            .with_context(|| format!("Failed to load {}", proven_slots_file.display()))?;
+
+        node::core::info!(node::core::log::system_time(); summary = "loading provers index");
+        let provers = BlockProver::make(None, None);
+        node::core::info!(node::core::log::system_time(); summary = "loaded provers index");
+
+        let producer = key.public_key().to_string();
+        let backend = LocalKeyBackend::with_guard(key, guard);
+        if let (Some(addr), Some(token)) = (self.http, self.token) {
+            let listener =
+                TcpListener::bind(addr).with_context(|| format!("Failed to bind {addr}"))?;
+            node::core::info!(node::core::log::system_time();
+                summary = "signer listening",
+                http = addr.to_string(),
+                producer = producer);
+            remote_signer_serve_http(listener, token, backend, provers)?;
+        } else if let Some(socket) = self.socket {
+            let listener = remote_signer_bind(&socket)
+                .with_context(|| format!("Failed to bind {}", socket.display()))?;
+            node::core::info!(node::core::log::system_time();
+                summary = "signer listening",
+                socket = socket.display().to_string(),
+                producer = producer);
+            remote_signer_serve(listener, backend, provers)?;
+        }
+        Ok(())
+    }
+}
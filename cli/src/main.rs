@@ -43,9 +43,10 @@ fn main() -> anyhow::Result<()> {
     unsafe_signal_handlers::setup();
     let app = commands::OpenminaCli::parse();
 
-    let network_init_result = match app.network {
-        commands::Network::Devnet => openmina_core::NetworkConfig::init("devnet"),
-        commands::Network::Mainnet => openmina_core::NetworkConfig::init("mainnet"),
+    let network_init_result = match (&app.network_config, app.network) {
+        (Some(path), _) => openmina_core::NetworkConfig::init_from_file(path),
+        (None, commands::Network::Devnet) => openmina_core::NetworkConfig::init("devnet"),
+        (None, commands::Network::Mainnet) => openmina_core::NetworkConfig::init("mainnet"),
     };
 
     network_init_result.expect("Failed to initialize network configuration");
@@ -1,6 +1,7 @@
 use binprot_derive::BinProtWrite;
 use mina_hasher::Fp;
 use mina_p2p_messages::{bigint, number, v2};
+use serde::{Deserialize, Deserializer};
 
 pub const GENESIS_PRODUCER_SK: &str = "EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw";
 
@@ -14,14 +15,15 @@ pub fn constraint_constants() -> &'static ConstraintConstants {
     NetworkConfig::global().constraint_constants
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct ForkConstants {
+    #[serde(deserialize_with = "deserialize_state_hash")]
     pub state_hash: Fp,
     pub blockchain_length: u32,
     pub global_slot_since_genesis: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct ConstraintConstants {
     pub sub_windows_per_window: u64,
     pub ledger_depth: u64,
@@ -34,6 +36,15 @@ pub struct ConstraintConstants {
     pub account_creation_fee: u64,
     pub fork: Option<ForkConstants>,
 }
+
+/// Fork state hash is given in its base58check encoding.
+fn deserialize_state_hash<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fp, D::Error> {
+    let state_hash = v2::StateHash::deserialize(deserializer)?;
+    state_hash
+        .to_field()
+        .map_err(|err| serde::de::Error::custom(format!("invalid state hash: {err:?}")))
+}
+
 #[derive(Clone, Debug, BinProtWrite)]
 pub struct ForkConstantsUnversioned {
     previous_state_hash: bigint::BigInt,
@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::thread;
+
+/// Periodically persisted file, written in the background thread at most
+/// once per `interval`, with the value encoded by `encode`.
+pub struct PeriodicFileWriter<T> {
+    path: PathBuf,
+    /// What the file holds, used in the name of the writing thread and
+    /// in the logs.
+    name: &'static str,
+    interval: Duration,
+    encode: fn(&T) -> io::Result<Vec<u8>>,
+    last_written: Option<Instant>,
+}
+
+impl<T: Send + 'static> PeriodicFileWriter<T> {
+    pub fn new(
+        path: impl Into<PathBuf>,
+        name: &'static str,
+        interval: Duration,
+        encode: fn(&T) -> io::Result<Vec<u8>>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            name,
+            interval,
+            encode,
+            last_written: None,
+        }
+    }
+
+    /// Reads the file written by the previous run, `None` if there is
+    /// no such file.
+    pub fn read(&self) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the value in the background thread, unless the file was
+    /// written less than `interval` ago. The value is only taken if it's
+    /// going to be written.
+    pub fn write_if_due(&mut self, value: impl FnOnce() -> T) {
+        let now = Instant::now();
+        if self
+            .last_written
+            .map_or(false, |t| now.duration_since(t) < self.interval)
+        {
+            return;
+        }
+        self.last_written = Some(now);
+
+        let value = value();
+        let (path, name, encode) = (self.path.clone(), self.name, self.encode);
+        let _ = thread::Builder::new()
+            .name(format!("{name}-save"))
+            .spawn(move || {
+                if let Err(err) = Self::write_to(&path, encode, &value) {
+                    crate::warn!(
+                        crate::log::system_time();
+                        summary = "failed to save file",
+                        file = name,
+                        path = display(path.display()),
+                        error = display(&err)
+                    );
+                }
+            });
+    }
+
+    /// Writes the value right away.
+    pub fn write(&self, value: &T) -> io::Result<()> {
+        Self::write_to(&self.path, self.encode, value)
+    }
+
+    /// Writes to a temporary file first, so that the crash in the middle
+    /// of the write doesn't corrupt the previously written file.
+    fn write_to(path: &Path, encode: fn(&T) -> io::Result<Vec<u8>>, value: &T) -> io::Result<()> {
+        let bytes = encode(value)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read() {
+        let dir = std::env::temp_dir().join(format!("file-writer-{}", std::process::id()));
+        let path = dir.join("values.json");
+        let mut writer =
+            PeriodicFileWriter::new(&path, "values", Duration::from_secs(60), |v: &Vec<u32>| {
+                Ok(serde_json::to_vec(v)?)
+            });
+        assert!(writer.read().unwrap().is_none());
+
+        writer.write(&vec![1u32, 2]).unwrap();
+        assert_eq!(writer.read().unwrap().unwrap(), b"[1,2]");
+        assert!(!dir.join("values.json.tmp").exists());
+
+        // Not due yet, so the value isn't even taken.
+        writer.last_written = Some(Instant::now());
+        writer.write_if_due(|| panic!("value shouldn't be taken"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
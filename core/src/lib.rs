@@ -5,6 +5,8 @@ pub mod requests;
 pub mod channels;
 pub mod thread;
 
+pub mod file_writer;
+
 pub mod constants;
 pub mod dummy;
 
@@ -1,9 +1,13 @@
+use std::path::{Path, PathBuf};
+
 use once_cell::sync::OnceCell;
+use serde::Deserialize;
 
 use crate::constants::ConstraintConstants;
 
 // From mina-signer, to avoid dependency
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NetworkId {
     /// Id for all testnets
     TESTNET = 0x00,
@@ -22,9 +26,12 @@ pub struct NetworkConfig {
     pub default_peers: Vec<&'static str>,
     pub circuits_config: &'static CircuitsConfig,
     pub constraint_constants: &'static ConstraintConstants,
+    /// Daemon JSON file with genesis ledger and constants of the network.
+    /// Built-in networks ship their genesis with the node.
+    pub genesis_config: Option<PathBuf>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CircuitsConfig {
     pub directory_name: &'static str,
 
@@ -38,7 +45,45 @@ pub struct CircuitsConfig {
     pub step_transaction_proved_gates: &'static str,
 }
 
+/// Network described by a JSON file, for networks other than the
+/// built-in devnet and mainnet.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomNetworkConfig {
+    pub name: String,
+    /// Built-in network (`devnet` or `mainnet`) whose circuits are used.
+    /// Gates and constraint system digests are taken from it, as they must
+    /// match the circuit blobs.
+    pub base: String,
+    pub network_id: NetworkId,
+    pub signature_prefix: String,
+    pub default_peers: Vec<String>,
+    pub constraint_constants: ConstraintConstants,
+    /// Directory with circuit blobs, relative to the circuit blobs base
+    /// directory.
+    pub circuits_directory: String,
+    /// Daemon JSON file with the genesis ledger of the network, relative
+    /// paths are resolved against the directory of the network config file.
+    pub genesis_config: PathBuf,
+}
+
 static CONFIG: OnceCell<NetworkConfig> = OnceCell::new();
+static CUSTOM_CONFIG: OnceCell<CustomNetworkConfig> = OnceCell::new();
+static CUSTOM_CIRCUITS_CONFIG: OnceCell<CircuitsConfig> = OnceCell::new();
+
+impl CustomNetworkConfig {
+    /// Reads the config from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|err| format!("Cannot open network config {}: {err}", path.display()))?;
+        let mut custom: Self = serde_json::from_reader(file)
+            .map_err(|err| format!("Invalid network config {}: {err}", path.display()))?;
+        if let Some(dir) = path.parent() {
+            custom.genesis_config = dir.join(&custom.genesis_config);
+        }
+        Ok(custom)
+    }
+}
 
 impl NetworkConfig {
     pub fn global() -> &'static Self {
@@ -53,12 +98,29 @@ impl NetworkConfig {
     }
 
     pub fn init(network_name: &str) -> Result<(), String> {
-        let config = match network_name {
-            "devnet" => Self::devnet_config(),
-            "mainnet" => Self::mainnet_config(),
-            other => Err(format!("Unknown network {other}"))?,
-        };
+        let config = Self::builtin_config(network_name)?;
+        Self::set(config)
+    }
+
+    /// Initializes global config from a [`CustomNetworkConfig`] JSON file.
+    ///
+    /// The config is validated before any of the statics are set, so that
+    /// the initialization can be retried on error.
+    pub fn init_from_file(path: &Path) -> Result<(), String> {
+        let custom = CustomNetworkConfig::load(path)?;
+        let base = Self::builtin_config(&custom.base)?;
+        if CONFIG.get().is_some() {
+            return Err("Double network configuration initialization".to_owned());
+        }
+        CUSTOM_CONFIG
+            .set(custom)
+            .map_err(|_| "Double network configuration initialization".to_owned())?;
+        let custom = CUSTOM_CONFIG.get().expect("just initialized");
+        let config = Self::custom_config(custom, base, &CUSTOM_CIRCUITS_CONFIG);
+        Self::set(config)
+    }
 
+    fn set(config: Self) -> Result<(), String> {
         CONFIG
             .set(config)
             .map_err(|_| "Double network configuration initialization".to_owned())?;
@@ -66,6 +128,46 @@ impl NetworkConfig {
         Ok(())
     }
 
+    fn builtin_config(network_name: &str) -> Result<Self, String> {
+        match network_name {
+            "devnet" => Ok(Self::devnet_config()),
+            "mainnet" => Ok(Self::mainnet_config()),
+            other => Err(format!("Unknown network {other}")),
+        }
+    }
+
+    /// Config lives for the whole duration of the process, so it borrows
+    /// the values read from the file, which are kept in a static, same as
+    /// the constants of built-in networks.
+    ///
+    /// `base` is the built-in network named by `custom.base`.
+    fn custom_config(
+        custom: &'static CustomNetworkConfig,
+        base: Self,
+        circuits_config: &'static OnceCell<CircuitsConfig>,
+    ) -> Self {
+        let account_update_hash_param = match custom.network_id {
+            NetworkId::TESTNET => devnet::ACCOUNT_UPDATE_HASH_PARAM,
+            NetworkId::MAINNET => mainnet::ACCOUNT_UPDATE_HASH_PARAM,
+        };
+        let circuits_config = circuits_config.get_or_init(|| CircuitsConfig {
+            directory_name: &custom.circuits_directory,
+            ..base.circuits_config.clone()
+        });
+
+        Self {
+            name: &custom.name,
+            network_id: custom.network_id.clone(),
+            signature_prefix: &custom.signature_prefix,
+            account_update_hash_param,
+            constraint_system_digests: base.constraint_system_digests,
+            default_peers: custom.default_peers.iter().map(String::as_str).collect(),
+            circuits_config,
+            constraint_constants: &custom.constraint_constants,
+            genesis_config: Some(custom.genesis_config.clone()),
+        }
+    }
+
     fn default_config() -> Self {
         Self::devnet_config()
     }
@@ -80,6 +182,7 @@ impl NetworkConfig {
             default_peers: mainnet::default_peers(),
             circuits_config: &mainnet::CIRCUITS_CONFIG,
             constraint_constants: &mainnet::CONSTRAINT_CONSTANTS,
+            genesis_config: None,
         }
     }
 
@@ -93,6 +196,7 @@ impl NetworkConfig {
             default_peers: devnet::default_peers(),
             circuits_config: &devnet::CIRCUITS_CONFIG,
             constraint_constants: &devnet::CONSTRAINT_CONSTANTS,
+            genesis_config: None,
         }
     }
 }
@@ -292,3 +396,108 @@ pub mod mainnet {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_JSON: &str = r#"{
+        "name": "testnet",
+        "base": "devnet",
+        "network_id": "testnet",
+        "signature_prefix": "TestSignature",
+        "default_peers": ["/ip4/127.0.0.1/tcp/8302/p2p/12D3KooWNyExDzG8T1BYXHpXQS66kaw3zi6qi5Pg9KD3GEyHW5FF"],
+        "constraint_constants": {
+            "sub_windows_per_window": 11,
+            "ledger_depth": 35,
+            "work_delay": 2,
+            "block_window_duration_ms": 60000,
+            "transaction_capacity_log_2": 7,
+            "pending_coinbase_depth": 5,
+            "coinbase_amount": 720000000000,
+            "supercharged_coinbase_factor": 1,
+            "account_creation_fee": 1000000000,
+            "fork": null
+        },
+        "circuits_directory": "testnet",
+        "genesis_config": "daemon.json"
+    }"#;
+
+    fn write_config(name: &str, json: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("network-config-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("network.json");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_custom_config() {
+        let path = write_config("load", CONFIG_JSON);
+        let custom = CustomNetworkConfig::load(&path).unwrap();
+        assert_eq!(custom.name, "testnet");
+        assert_eq!(custom.constraint_constants.block_window_duration_ms, 60000);
+        // Relative to the network config file.
+        assert_eq!(
+            custom.genesis_config,
+            path.parent().unwrap().join("daemon.json")
+        );
+
+        let custom = Box::leak(Box::new(custom));
+        let circuits_config = Box::leak(Box::new(OnceCell::new()));
+        let base = NetworkConfig::builtin_config(&custom.base).unwrap();
+        let config = NetworkConfig::custom_config(custom, base, circuits_config);
+        assert_eq!(config.name, "testnet");
+        assert_eq!(config.signature_prefix, "TestSignature");
+        assert_eq!(
+            config.account_update_hash_param,
+            devnet::ACCOUNT_UPDATE_HASH_PARAM
+        );
+        assert_eq!(config.default_peers.len(), 1);
+        assert_eq!(config.circuits_config.directory_name, "testnet");
+        assert_eq!(
+            config.circuits_config.step_blockchain_gates,
+            devnet::CIRCUITS_CONFIG.step_blockchain_gates
+        );
+        assert_eq!(
+            config.constraint_system_digests,
+            &devnet::CONSTRAINT_SYSTEM_DIGESTS
+        );
+        assert_eq!(config.constraint_constants.block_window_duration_ms, 60000);
+    }
+
+    #[test]
+    fn load_custom_config_invalid() {
+        let path = write_config("missing", "{}");
+        assert!(CustomNetworkConfig::load(&path).is_err());
+        assert!(CustomNetworkConfig::load(&path.with_file_name("missing.json")).is_err());
+
+        let json = CONFIG_JSON.replace(
+            r#""name": "testnet","#,
+            r#""name": "testnet", "unknown": 1,"#,
+        );
+        let path = write_config("unknown-field", &json);
+        assert!(CustomNetworkConfig::load(&path).is_err());
+
+        // Custom networks can't boot from the genesis of a built-in one.
+        let json = CONFIG_JSON.replace(
+            r#",
+        "genesis_config": "daemon.json""#,
+            "",
+        );
+        let path = write_config("no-genesis", &json);
+        assert!(CustomNetworkConfig::load(&path).is_err());
+    }
+
+    #[test]
+    fn custom_config_unknown_base() {
+        let json = CONFIG_JSON.replace(r#""base": "devnet""#, r#""base": "testnet""#);
+        let path = write_config("unknown-base", &json);
+        let err = NetworkConfig::init_from_file(&path).unwrap_err();
+        assert_eq!(err, "Unknown network testnet");
+        // Nothing is initialized, so that the initialization can be retried.
+        assert!(CUSTOM_CONFIG.get().is_none());
+        assert!(CUSTOM_CIRCUITS_CONFIG.get().is_none());
+    }
+}
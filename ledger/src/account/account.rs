@@ -33,7 +33,7 @@ use crate::{
         transaction_logic::account_min_balance_at_slot,
     },
     zkapps::snark::FlaggedOption,
-    MerklePath, MyCow, ToInputs,
+    AccountIndex, MerklePath, MyCow, ToInputs,
 };
 
 use super::common::*;
@@ -1697,7 +1697,9 @@ impl ToInputs for Account {
     }
 }
 
-fn verify_merkle_path(account: &Account, merkle_path: &[MerklePath]) -> Fp {
+/// Root hash of the ledger implied by the `account` and its `merkle_path`,
+/// which goes from the leaf up to the root.
+pub fn verify_merkle_path(account: &Account, merkle_path: &[MerklePath]) -> Fp {
     let account_hash = account.hash();
     let mut param = String::with_capacity(16);
 
@@ -1717,6 +1719,31 @@ fn verify_merkle_path(account: &Account, merkle_path: &[MerklePath]) -> Fp {
         })
 }
 
+/// Checks that `merkle_path` proves the `account` to be at the `index` in
+/// the ledger with the `root` hash.
+///
+/// Directions of the path have to match the index, otherwise the same
+/// path could be used to prove the account at a different position.
+pub fn verify_account_merkle_proof(
+    account: &Account,
+    index: AccountIndex,
+    merkle_path: &[MerklePath],
+    root: Fp,
+) -> bool {
+    if merkle_path.len() >= u64::BITS as usize || index.0 >> merkle_path.len() != 0 {
+        return false;
+    }
+    let directions_match = merkle_path
+        .iter()
+        .enumerate()
+        .all(|(depth, path)| match path {
+            MerklePath::Left(_) => index.0 >> depth & 1 == 0,
+            MerklePath::Right(_) => index.0 >> depth & 1 == 1,
+        });
+
+    directions_match && verify_merkle_path(account, merkle_path) == root
+}
+
 /// `implied_root` in OCaml
 pub fn checked_verify_merkle_path(
     account: &Account,
@@ -1857,6 +1884,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verify_account_merkle_proof() {
+        use crate::{BaseLedger, Database};
+
+        let mut db = Database::create(10);
+        let accounts = (0..5u64)
+            .map(|index| {
+                let mut account = Account::rand();
+                account.token_id = TokenId::from(index);
+                account
+            })
+            .collect::<Vec<_>>();
+        for account in &accounts {
+            db.get_or_create_account(account.id(), account.clone())
+                .unwrap();
+        }
+        let root = db.merkle_root();
+
+        for account in &accounts {
+            let index = db.index_of_account(account.id()).unwrap();
+            let path = db.merkle_path_at_index(index);
+            assert!(verify_account_merkle_proof(account, index, &path, root));
+
+            let wrong_index = AccountIndex(index.0 ^ 1);
+            assert!(!verify_account_merkle_proof(
+                account,
+                wrong_index,
+                &path,
+                root
+            ));
+            assert!(!verify_account_merkle_proof(
+                &accounts[(index.0 as usize + 1) % accounts.len()],
+                index,
+                &path,
+                root
+            ));
+        }
+    }
+
     #[test]
     fn test_dummy_sideloaded_verification_key() {
         assert_eq!(
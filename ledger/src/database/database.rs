@@ -1,6 +1,6 @@
 use std::{
     collections::HashSet,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
 
@@ -9,7 +9,7 @@ use mina_signer::CompressedPubKey;
 
 use crate::{
     account::{Account, AccountId, TokenId},
-    address::Address,
+    address::{Address, AddressIterator},
     base::{AccountIndex, BaseLedger, GetOrCreated, MerklePath, Uuid},
     // tree::{Database, DatabaseError},
     tree_version::V2,
@@ -18,7 +18,10 @@ use crate::{
 
 use crate::HashesMatrix;
 
-use super::database_impl::DatabaseImpl;
+use super::{
+    database_impl::DatabaseImpl,
+    ondisk_database_impl::{OnDiskDatabaseImpl, DEFAULT_CACHE_CAPACITY},
+};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum DatabaseError {
@@ -28,7 +31,24 @@ pub enum DatabaseError {
 #[derive(Clone, Debug)]
 pub struct Database<T: TreeVersion> {
     // Using a mutex for now but this can be replaced with a RefCell
-    pub inner: Arc<Mutex<DatabaseImpl<T>>>,
+    inner: Arc<Mutex<DatabaseInner<T>>>,
+}
+
+#[derive(Debug)]
+enum DatabaseInner<T: TreeVersion> {
+    InMemory(DatabaseImpl<T>),
+    OnDisk(OnDiskDatabaseImpl),
+}
+
+/// Calls the same method on either of the database implementations.
+macro_rules! with_db {
+    ($self:ident, $this:ident => $body:expr) => {{
+        let mut inner = $self.inner.try_lock().expect("lock failed");
+        match &mut *inner {
+            DatabaseInner::InMemory($this) => $body,
+            DatabaseInner::OnDisk($this) => $body,
+        }
+    }};
 }
 
 // #[derive(Debug)]
@@ -39,75 +59,186 @@ pub struct Database<T: TreeVersion> {
 // }
 
 impl Database<V2> {
-    pub fn with<F, R>(&self, fun: F) -> R
+    /// Calls `fun` with the in-memory database.
+    ///
+    /// Returns an error if the database is stored on disk.
+    pub fn with<F, R>(&self, fun: F) -> std::io::Result<R>
     where
         F: FnOnce(&mut DatabaseImpl<V2>) -> R,
     {
         let mut inner = self.inner.try_lock().expect("lock failed");
-        fun(&mut inner)
+        match &mut *inner {
+            DatabaseInner::InMemory(db) => Ok(fun(db)),
+            DatabaseInner::OnDisk(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "database is stored on disk",
+            )),
+        }
+    }
+
+    fn from_inner(inner: DatabaseInner<V2>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+        }
     }
 }
 
 impl Database<V2> {
     pub fn create_with_dir(depth: u8, dir_name: Option<PathBuf>) -> Self {
         let db = DatabaseImpl::<V2>::create_with_dir(depth, dir_name);
-
-        Self {
-            inner: Arc::new(Mutex::new(db)),
-        }
+        Self::from_inner(DatabaseInner::InMemory(db))
     }
 
     pub fn create(depth: u8) -> Self {
         Self::create_with_dir(depth, None)
     }
 
+    /// Opens the database stored in `directory`, or creates an empty one.
+    ///
+    /// Accounts and hashes are kept on disk, only the recently used ones
+    /// are cached in memory.
+    pub fn create_on_disk(depth: u8, directory: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::create_on_disk_with_cache_capacity(depth, directory, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Same as [`Self::create_on_disk`], with the number of accounts to
+    /// cache in memory.
+    pub fn create_on_disk_with_cache_capacity(
+        depth: u8,
+        directory: impl AsRef<Path>,
+        cache_capacity: usize,
+    ) -> std::io::Result<Self> {
+        let db = OnDiskDatabaseImpl::create(depth, directory, cache_capacity)?;
+        Ok(Self::from_inner(DatabaseInner::OnDisk(db)))
+    }
+
+    /// Creates an empty database stored in `directory`, which is removed
+    /// from disk once the database is dropped.
+    pub fn create_on_disk_temporary(
+        depth: u8,
+        directory: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        let db = OnDiskDatabaseImpl::create_temporary(depth, directory, DEFAULT_CACHE_CAPACITY)?;
+        Ok(Self::from_inner(DatabaseInner::OnDisk(db)))
+    }
+
+    pub fn is_on_disk(&self) -> bool {
+        let inner = self.inner.try_lock().expect("lock failed");
+        matches!(&*inner, DatabaseInner::OnDisk(_))
+    }
+
+    /// Writes pending changes of the database stored on disk. No-op for
+    /// the in-memory database.
+    ///
+    /// As `BaseLedger` methods can't return errors, this also returns the
+    /// io error the database stored on disk failed with earlier, if any.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseInner::InMemory(_) => Ok(()),
+            DatabaseInner::OnDisk(db) => db.flush(),
+        }
+    }
+
     pub fn root_hash(&mut self) -> Fp {
-        self.with(|this| this.root_hash())
+        with_db!(self, this => this.root_hash())
     }
 
     // Do not use
     pub fn naccounts(&self) -> usize {
-        self.with(|this| this.naccounts())
+        with_db!(self, this => this.naccounts())
     }
 
-    pub fn create_checkpoint(&self, directory_name: String) {
-        self.with(|this| this.create_checkpoint(directory_name))
+    pub fn create_checkpoint(&self, directory_name: String) -> std::io::Result<()> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseInner::InMemory(db) => {
+                db.create_checkpoint(directory_name);
+                Ok(())
+            }
+            DatabaseInner::OnDisk(db) => db.create_checkpoint(directory_name),
+        }
     }
 
-    pub fn make_checkpoint(&self, directory_name: String) {
-        self.with(|this| this.make_checkpoint(directory_name))
+    pub fn make_checkpoint(&self, directory_name: String) -> std::io::Result<()> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseInner::InMemory(db) => {
+                db.make_checkpoint(directory_name);
+                Ok(())
+            }
+            DatabaseInner::OnDisk(db) => db.make_checkpoint(directory_name),
+        }
     }
 
-    pub fn clone_db(&self, directory_name: PathBuf) -> Self {
-        let db = self.with(|this| this.clone_db(directory_name));
-        Self {
-            inner: Arc::new(Mutex::new(db)),
-        }
+    pub fn clone_db(&self, directory_name: PathBuf) -> std::io::Result<Self> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        let db = match &*inner {
+            DatabaseInner::InMemory(db) => DatabaseInner::InMemory(db.clone_db(directory_name)),
+            DatabaseInner::OnDisk(db) => DatabaseInner::OnDisk(db.clone_db(directory_name)?),
+        };
+        Ok(Self::from_inner(db))
     }
 
     pub fn get_cached_hash(&self, addr: &Address) -> Option<Fp> {
-        self.with(|this| this.get_cached_hash(addr))
+        with_db!(self, this => this.get_cached_hash(addr))
     }
 
     pub fn set_cached_hash(&mut self, addr: &Address, hash: Fp) {
-        self.with(|this| this.set_cached_hash(addr, hash))
+        with_db!(self, this => this.set_cached_hash(addr, hash))
     }
 
     pub fn empty_hash_at_height(&mut self, height: usize) -> Fp {
-        self.with(|this| this.empty_hash_at_height(height))
+        with_db!(self, this => this.empty_hash_at_height(height))
     }
 
     pub fn invalidate_hashes(&mut self, account_index: AccountIndex) {
-        self.with(|this| this.invalidate_hashes(account_index))
+        with_db!(self, this => this.invalidate_hashes(account_index))
     }
 
     pub fn transfert_hashes(&mut self, hashes: HashesMatrix) {
-        self.with(|this| this.transfert_hashes(hashes))
+        with_db!(self, this => this.transfert_hashes(hashes))
+    }
+
+    pub fn emulate_tree_recursive(&mut self, addr: Address, last_account: &Address) -> Fp {
+        with_db!(self, this => this.emulate_tree_recursive(addr, last_account))
+    }
+
+    pub fn emulate_tree_to_get_path(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        path: &mut AddressIterator,
+        merkle_path: &mut Vec<MerklePath>,
+    ) -> Fp {
+        with_db!(self, this => {
+            this.emulate_tree_to_get_path(addr, last_account, path, merkle_path)
+        })
+    }
+
+    pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseInner::InMemory(db) => db.hashes_matrix.get_raw_inner_hashes(),
+            DatabaseInner::OnDisk(db) => db.get_raw_inner_hashes(),
+        }
+    }
+
+    pub fn set_raw_inner_hashes(&self, raw_hashes: Vec<(u64, Fp)>) {
+        let mut inner = self.inner.try_lock().expect("lock failed");
+        match &mut *inner {
+            DatabaseInner::InMemory(db) => db.hashes_matrix.set_raw_inner_hashes(raw_hashes),
+            DatabaseInner::OnDisk(db) => db.set_raw_inner_hashes(raw_hashes),
+        }
     }
 
     #[cfg(test)]
     pub fn test_matrix(&self) -> HashesMatrix {
-        self.with(|this| this.hashes_matrix.clone())
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseInner::InMemory(db) => db.hashes_matrix.clone(),
+            DatabaseInner::OnDisk(db) => db.hashes_matrix(),
+        }
         // match self {
         //     Root { database, .. } => database,
         //     Unattached { hashes, .. } | Attached { hashes, .. } => hashes.clone(),
@@ -117,62 +248,62 @@ impl Database<V2> {
 
 impl BaseLedger for Database<V2> {
     fn to_list(&self) -> Vec<Account> {
-        self.with(|this| this.to_list())
+        with_db!(self, this => this.to_list())
     }
 
     fn iter<F>(&self, fun: F)
     where
         F: FnMut(&Account),
     {
-        self.with(|this| this.iter(fun))
+        with_db!(self, this => this.iter(fun))
     }
 
     fn fold<B, F>(&self, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> B,
     {
-        self.with(|this| this.fold(init, fun))
+        with_db!(self, this => this.fold(init, fun))
     }
 
     fn fold_with_ignored_accounts<B, F>(&self, ignoreds: HashSet<AccountId>, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> B,
     {
-        self.with(|this| this.fold_with_ignored_accounts(ignoreds, init, fun))
+        with_db!(self, this => this.fold_with_ignored_accounts(ignoreds, init, fun))
     }
 
     fn fold_until<B, F>(&self, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> std::ops::ControlFlow<B, B>,
     {
-        self.with(|this| this.fold_until(init, fun))
+        with_db!(self, this => this.fold_until(init, fun))
     }
 
     fn accounts(&self) -> HashSet<AccountId> {
-        self.with(|this| this.accounts())
+        with_db!(self, this => this.accounts())
     }
 
     fn token_owner(&self, token_id: TokenId) -> Option<AccountId> {
-        self.with(|this| this.token_owner(token_id))
+        with_db!(self, this => this.token_owner(token_id))
     }
 
     fn token_owners(&self) -> HashSet<AccountId> {
-        self.with(|this| this.token_owners())
+        with_db!(self, this => this.token_owners())
     }
 
     fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
-        self.with(|this| this.tokens(public_key))
+        with_db!(self, this => this.tokens(public_key))
     }
 
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
-        self.with(|this| this.location_of_account(account_id))
+        with_db!(self, this => this.location_of_account(account_id))
     }
 
     fn location_of_account_batch(
         &self,
         account_ids: &[AccountId],
     ) -> Vec<(AccountId, Option<Address>)> {
-        self.with(|this| this.location_of_account_batch(account_ids))
+        with_db!(self, this => this.location_of_account_batch(account_ids))
     }
 
     fn get_or_create_account(
@@ -180,7 +311,7 @@ impl BaseLedger for Database<V2> {
         account_id: AccountId,
         account: Account,
     ) -> Result<GetOrCreated, DatabaseError> {
-        self.with(|this| this.get_or_create_account(account_id, account))
+        with_db!(self, this => this.get_or_create_account(account_id, account))
     }
 
     fn close(&self) {
@@ -188,87 +319,87 @@ impl BaseLedger for Database<V2> {
     }
 
     fn last_filled(&self) -> Option<Address> {
-        self.with(|this| this.last_filled())
+        with_db!(self, this => this.last_filled())
     }
 
     fn get_uuid(&self) -> Uuid {
-        self.with(|this| this.get_uuid())
+        with_db!(self, this => this.get_uuid())
     }
 
     fn get_directory(&self) -> Option<PathBuf> {
-        self.with(|this| this.get_directory())
+        with_db!(self, this => this.get_directory())
     }
 
     fn get_account_hash(&mut self, account_index: AccountIndex) -> Option<Fp> {
-        self.with(|this| this.get_account_hash(account_index))
+        with_db!(self, this => this.get_account_hash(account_index))
     }
 
     fn get(&self, addr: Address) -> Option<Box<Account>> {
-        self.with(|this| this.get(addr))
+        with_db!(self, this => this.get(addr))
     }
 
     fn get_batch(&self, addr: &[Address]) -> Vec<(Address, Option<Box<Account>>)> {
-        self.with(|this| this.get_batch(addr))
+        with_db!(self, this => this.get_batch(addr))
     }
 
     fn set(&mut self, addr: Address, account: Box<Account>) {
-        self.with(|this| this.set(addr, account))
+        with_db!(self, this => this.set(addr, account))
     }
 
     fn set_batch(&mut self, list: &[(Address, Box<Account>)]) {
-        self.with(|this| this.set_batch(list))
+        with_db!(self, this => this.set_batch(list))
     }
 
     fn get_at_index(&self, index: AccountIndex) -> Option<Box<Account>> {
-        self.with(|this| this.get_at_index(index))
+        with_db!(self, this => this.get_at_index(index))
     }
 
     fn set_at_index(&mut self, index: AccountIndex, account: Box<Account>) -> Result<(), ()> {
-        self.with(|this| this.set_at_index(index, account))
+        with_db!(self, this => this.set_at_index(index, account))
     }
 
     fn index_of_account(&self, account_id: AccountId) -> Option<AccountIndex> {
-        self.with(|this| this.index_of_account(account_id))
+        with_db!(self, this => this.index_of_account(account_id))
     }
 
     fn merkle_root(&mut self) -> Fp {
-        self.with(|this| this.merkle_root())
+        with_db!(self, this => this.merkle_root())
     }
 
     fn merkle_path(&mut self, addr: Address) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path(addr))
+        with_db!(self, this => this.merkle_path(addr))
     }
 
     fn merkle_path_at_index(&mut self, index: AccountIndex) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path_at_index(index))
+        with_db!(self, this => this.merkle_path_at_index(index))
     }
 
     fn remove_accounts(&mut self, ids: &[AccountId]) {
-        self.with(|this| this.remove_accounts(ids))
+        with_db!(self, this => this.remove_accounts(ids))
     }
 
     fn detached_signal(&mut self) {
-        self.with(|this| this.detached_signal())
+        with_db!(self, this => this.detached_signal())
     }
 
     fn depth(&self) -> u8 {
-        self.with(|this| this.depth())
+        with_db!(self, this => this.depth())
     }
 
     fn num_accounts(&self) -> usize {
-        self.with(|this| this.num_accounts())
+        with_db!(self, this => this.num_accounts())
     }
 
     fn merkle_path_at_addr(&mut self, addr: Address) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path_at_addr(addr))
+        with_db!(self, this => this.merkle_path_at_addr(addr))
     }
 
     fn get_inner_hash_at_addr(&mut self, addr: Address) -> Result<Fp, String> {
-        self.with(|this| this.get_inner_hash_at_addr(addr))
+        with_db!(self, this => this.get_inner_hash_at_addr(addr))
     }
 
     fn set_inner_hash_at_addr(&mut self, addr: Address, hash: Fp) -> Result<(), ()> {
-        self.with(|this| this.set_inner_hash_at_addr(addr, hash))
+        with_db!(self, this => this.set_inner_hash_at_addr(addr, hash))
     }
 
     fn set_all_accounts_rooted_at(
@@ -276,15 +407,15 @@ impl BaseLedger for Database<V2> {
         addr: Address,
         accounts: &[Box<Account>],
     ) -> Result<(), ()> {
-        self.with(|this| this.set_all_accounts_rooted_at(addr, accounts))
+        with_db!(self, this => this.set_all_accounts_rooted_at(addr, accounts))
     }
 
     fn get_all_accounts_rooted_at(&self, addr: Address) -> Option<Vec<(Address, Box<Account>)>> {
-        self.with(|this| this.get_all_accounts_rooted_at(addr))
+        with_db!(self, this => this.get_all_accounts_rooted_at(addr))
     }
 
     fn make_space_for(&mut self, space: usize) {
-        self.with(|this| this.make_space_for(space))
+        with_db!(self, this => this.make_space_for(space))
     }
 
     fn commit(&mut self) {
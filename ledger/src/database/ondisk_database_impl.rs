@@ -0,0 +1,1331 @@
+//! Merkle ledger persisted in [`crate::ondisk::Database`].
+//!
+//! Unlike [`super::database_impl::DatabaseImpl`], which keeps all accounts
+//! and hashes in memory, accounts, account ids, token owners and the
+//! hashes of the tree are stored on disk. Only the recently used ones are
+//! kept in memory, in LRU caches, so the ledger can be used as the root
+//! of a [`crate::Mask`] without holding a full copy of the accounts.
+//!
+//! Writes are kept in memory and written to the disk in batches, once
+//! there are enough of them or when [`OnDiskDatabaseImpl::flush`] is
+//! called.
+//!
+//! `BaseLedger` methods can't return errors. The first io error is logged
+//! and kept, the ledger can't be trusted after it, and the error is
+//! returned by [`OnDiskDatabaseImpl::flush`].
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hash,
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use mina_hasher::Fp;
+use mina_p2p_messages::binprot::{BinProtRead, BinProtWrite};
+use mina_signer::CompressedPubKey;
+use o1_utils::FieldHelpers;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    next_uuid,
+    ondisk::{self, Batch},
+    Account, AccountId, AccountIndex, Address, AddressIterator, BaseLedger, Direction,
+    GetOrCreated, HashesMatrix, MerklePath, TokenId, TreeVersion, Uuid, V2,
+};
+
+use super::DatabaseError;
+
+/// Default number of cached accounts. Hashes and account ids caches are
+/// a few times larger, as they are much smaller than accounts.
+pub const DEFAULT_CACHE_CAPACITY: usize = 16 * 1024;
+
+/// Number of not yet written changes after which they are written to disk.
+const MAX_PENDING_WRITES: usize = 64 * 1024;
+
+/// Number of overwritten or removed entries after which the database
+/// file gets compacted.
+const GC_THRESHOLD: usize = 1024 * 1024;
+
+const METADATA_KEY: &[u8] = b"metadata";
+const ACCOUNTS_PREFIX: u8 = b'a';
+const HASHES_PREFIX: u8 = b'h';
+const IDS_PREFIX: u8 = b'i';
+const TOKENS_PREFIX: u8 = b't';
+
+fn invalid_data(err: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// `io::Error` isn't `Clone`, the recorded error is returned as a new
+/// error of the same kind.
+fn copy_error(err: &io::Error) -> io::Error {
+    io::Error::new(err.kind(), err.to_string())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Metadata {
+    depth: u8,
+    last_index: Option<u64>,
+    naccounts: u64,
+}
+
+trait Codec: Sized {
+    fn encode(&self, buf: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+impl Codec for u64 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let bytes = bytes.try_into().map_err(invalid_data)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+impl Codec for Fp {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        Fp::from_bytes(bytes).map_err(invalid_data)
+    }
+}
+
+macro_rules! impl_binprot_codec {
+    ($($ty:ty),*) => {
+        $(impl Codec for $ty {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                self.binprot_write(buf).expect("writing to vec can't fail");
+            }
+
+            fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+                <$ty>::binprot_read(&mut bytes).map_err(invalid_data)
+            }
+        })*
+    };
+}
+
+impl_binprot_codec!(AccountId, TokenId);
+
+impl Codec for Box<Account> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.binprot_write(buf).expect("writing to vec can't fail");
+    }
+
+    fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        Account::binprot_read(&mut bytes)
+            .map(Box::new)
+            .map_err(invalid_data)
+    }
+}
+
+struct LruCache<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by the tick they were last used at.
+    recency: BTreeMap<u64, K>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let (value, tick) = self.entries.get_mut(key)?;
+        self.recency.remove(&*tick);
+        self.tick += 1;
+        *tick = self.tick;
+        self.recency.insert(self.tick, key.clone());
+        Some(value)
+    }
+
+    /// Same as [`Self::get`], but doesn't mark the entry as used.
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if let Some((_, tick)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.recency.remove(&tick);
+        }
+        self.recency.insert(self.tick, key);
+
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some((_, tick)) = self.entries.remove(key) {
+            self.recency.remove(&tick);
+        }
+    }
+}
+
+/// Entries of one kind, stored under keys starting with `prefix`.
+struct Table<K, V> {
+    prefix: u8,
+    cache: LruCache<K, V>,
+    /// Changes which aren't written to disk yet, `None` for removals.
+    pending: HashMap<K, Option<V>>,
+}
+
+impl<K: Codec + Hash + Eq + Clone, V: Codec + Clone> Table<K, V> {
+    fn new(prefix: u8, cache_capacity: usize) -> Self {
+        Self {
+            prefix,
+            cache: LruCache::new(cache_capacity),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn db_key(&self, key: &K) -> Box<[u8]> {
+        let mut buf = vec![self.prefix];
+        key.encode(&mut buf);
+        buf.into()
+    }
+
+    fn decode_db_key(&self, db_key: &[u8]) -> Option<io::Result<K>> {
+        let (prefix, key) = db_key.split_first()?;
+        (*prefix == self.prefix).then(|| K::decode(key))
+    }
+
+    fn read(&self, db: &mut ondisk::Database, key: &K) -> io::Result<Option<V>> {
+        match self.pending.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => db
+                .get(&self.db_key(key))?
+                .map(|bytes| V::decode(&bytes))
+                .transpose(),
+        }
+    }
+
+    fn get(&mut self, db: &mut ondisk::Database, key: &K) -> io::Result<Option<V>> {
+        if let Some(value) = self.cache.get(key) {
+            return Ok(Some(value.clone()));
+        }
+        let Some(value) = self.read(db, key)? else {
+            return Ok(None);
+        };
+        self.cache.insert(key.clone(), value.clone());
+        Ok(Some(value))
+    }
+
+    /// Same as [`Self::get`], but doesn't put the value into the cache,
+    /// used when iterating over the whole ledger.
+    fn peek(&self, db: &mut ondisk::Database, key: &K) -> io::Result<Option<V>> {
+        match self.cache.peek(key) {
+            Some(value) => Ok(Some(value.clone())),
+            None => self.read(db, key),
+        }
+    }
+
+    fn set(&mut self, key: K, value: V) {
+        self.cache.insert(key.clone(), value.clone());
+        self.pending.insert(key, Some(value));
+    }
+
+    fn remove(&mut self, db: &mut ondisk::Database, key: &K) -> io::Result<()> {
+        self.cache.remove(key);
+        if db.contains_key(&self.db_key(key))? {
+            self.pending.insert(key.clone(), None);
+        } else {
+            self.pending.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Moves pending changes into the `batch`. Returns the number of
+    /// entries on disk which become garbage.
+    fn take_pending(&mut self, db: &mut ondisk::Database, batch: &mut Batch) -> io::Result<usize> {
+        let mut garbage = 0;
+        for (key, value) in self.pending.drain() {
+            let mut db_key = vec![self.prefix];
+            key.encode(&mut db_key);
+            let db_key: Box<[u8]> = db_key.into();
+
+            if db.contains_key(&db_key)? {
+                garbage += 1;
+            }
+            match value {
+                Some(value) => {
+                    let mut bytes = Vec::new();
+                    value.encode(&mut bytes);
+                    batch.set(db_key, bytes.into());
+                }
+                None => batch.remove(db_key),
+            }
+        }
+        Ok(garbage)
+    }
+
+    /// All keys of this table, which are either on disk or pending.
+    fn keys(&self, db: &mut ondisk::Database) -> io::Result<Vec<K>> {
+        let mut keys = db
+            .keys()?
+            .iter()
+            .filter_map(|db_key| self.decode_db_key(db_key))
+            .filter(|key| {
+                key.as_ref()
+                    .map_or(true, |key| !self.pending.contains_key(key))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        keys.extend(
+            self.pending
+                .iter()
+                .filter(|(_, value)| value.is_some())
+                .map(|(key, _)| key.clone()),
+        );
+        Ok(keys)
+    }
+}
+
+struct Store {
+    db: ondisk::Database,
+    accounts: Table<u64, Box<Account>>,
+    /// Hashes of the tree nodes (accounts included), keyed by
+    /// [`Address::to_linear_index`].
+    hashes: Table<u64, Fp>,
+    ids: Table<AccountId, u64>,
+    tokens: Table<TokenId, AccountId>,
+    /// Number of entries overwritten or removed since the last compaction.
+    garbage: usize,
+    /// First io error the store failed with.
+    error: Option<io::Error>,
+}
+
+impl Store {
+    /// Keeps the first error, logged with `context` so that it's clear
+    /// which ledger and operation failed.
+    fn record<T>(&mut self, res: io::Result<T>, context: impl FnOnce() -> String) -> Option<T> {
+        match res {
+            Ok(value) => Some(value),
+            Err(err) => {
+                if self.error.is_none() {
+                    elog!("on-disk ledger io error, {}: {err}", context());
+                    self.error = Some(err);
+                }
+                None
+            }
+        }
+    }
+
+    fn npending(&self) -> usize {
+        self.accounts.pending.len()
+            + self.hashes.pending.len()
+            + self.ids.pending.len()
+            + self.tokens.pending.len()
+    }
+
+    fn flush(&mut self, metadata: Metadata) -> io::Result<()> {
+        if let Some(err) = self.error.as_ref() {
+            return Err(copy_error(err));
+        }
+
+        let mut batch = Batch::new();
+        self.garbage += self.accounts.take_pending(&mut self.db, &mut batch)?;
+        self.garbage += self.hashes.take_pending(&mut self.db, &mut batch)?;
+        self.garbage += self.ids.take_pending(&mut self.db, &mut batch)?;
+        self.garbage += self.tokens.take_pending(&mut self.db, &mut batch)?;
+        let metadata = postcard::to_stdvec(&metadata).map_err(invalid_data)?;
+        batch.set(METADATA_KEY.into(), metadata.into());
+        self.db.run_batch(&mut batch)?;
+
+        if self.garbage >= GC_THRESHOLD {
+            self.db.gc()?;
+            self.garbage = 0;
+        }
+        Ok(())
+    }
+}
+
+pub struct OnDiskDatabaseImpl {
+    store: RefCell<Store>,
+    depth: u8,
+    last_location: Option<Address>,
+    naccounts: usize,
+    uuid: Uuid,
+    directory: PathBuf,
+    cache_capacity: usize,
+    /// Set for copies made by [`Self::clone_db`] into a directory picked
+    /// by us, they are removed from disk when dropped.
+    is_temporary: bool,
+}
+
+impl std::fmt::Debug for OnDiskDatabaseImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnDiskDatabase")
+            .field("depth", &self.depth)
+            .field("naccounts", &self.naccounts)
+            .field("uuid", &self.uuid)
+            .field("directory", &self.directory)
+            .finish()
+    }
+}
+
+impl Drop for OnDiskDatabaseImpl {
+    fn drop(&mut self) {
+        if self.is_temporary {
+            let _ = std::fs::remove_dir_all(&self.directory);
+        } else if let Err(err) = self.flush() {
+            elog!("failed to flush on-disk ledger {:?}: {err}", self.directory);
+        }
+    }
+}
+
+impl OnDiskDatabaseImpl {
+    /// Opens the ledger stored in `directory`, or creates an empty one
+    /// if there is none.
+    pub fn create(
+        depth: u8,
+        directory: impl AsRef<Path>,
+        cache_capacity: usize,
+    ) -> io::Result<Self> {
+        assert!((1..0xfe).contains(&depth));
+
+        let directory = directory.as_ref().to_owned();
+        let db = ondisk::Database::create(&directory)?;
+        Self::from_db(depth, db, directory, cache_capacity, false)
+    }
+
+    /// Creates an empty ledger in `directory`, which is removed from disk
+    /// once the ledger is dropped. Anything left in `directory` is removed.
+    pub fn create_temporary(
+        depth: u8,
+        directory: impl AsRef<Path>,
+        cache_capacity: usize,
+    ) -> io::Result<Self> {
+        assert!((1..0xfe).contains(&depth));
+
+        let directory = directory.as_ref().to_owned();
+        if directory.try_exists()? {
+            std::fs::remove_dir_all(&directory)?;
+        }
+        let db = ondisk::Database::create(&directory)?;
+        Self::from_db(depth, db, directory, cache_capacity, true)
+    }
+
+    fn from_db(
+        depth: u8,
+        mut db: ondisk::Database,
+        directory: PathBuf,
+        cache_capacity: usize,
+        is_temporary: bool,
+    ) -> io::Result<Self> {
+        let metadata = db
+            .get(METADATA_KEY)?
+            .map(|bytes| postcard::from_bytes::<Metadata>(&bytes).map_err(invalid_data))
+            .transpose()?;
+        let (last_location, naccounts) = match metadata {
+            None => (None, 0),
+            Some(metadata) if metadata.depth != depth => {
+                return Err(invalid_data(format!(
+                    "ledger in {directory:?} has depth {}, expected {depth}",
+                    metadata.depth
+                )));
+            }
+            Some(metadata) => (
+                metadata
+                    .last_index
+                    .map(|index| Address::from_index(AccountIndex(index), depth as usize)),
+                metadata.naccounts as usize,
+            ),
+        };
+
+        let store = Store {
+            db,
+            accounts: Table::new(ACCOUNTS_PREFIX, cache_capacity),
+            hashes: Table::new(HASHES_PREFIX, 4 * cache_capacity),
+            ids: Table::new(IDS_PREFIX, 4 * cache_capacity),
+            tokens: Table::new(TOKENS_PREFIX, cache_capacity),
+            garbage: 0,
+            error: None,
+        };
+
+        Ok(Self {
+            store: RefCell::new(store),
+            depth,
+            last_location,
+            naccounts,
+            uuid: next_uuid(),
+            directory,
+            cache_capacity,
+            is_temporary,
+        })
+    }
+
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            depth: self.depth,
+            last_index: self.last_location.as_ref().map(|addr| addr.to_index().0),
+            naccounts: self.naccounts as u64,
+        }
+    }
+
+    /// Writes all pending changes to disk.
+    ///
+    /// Returns the error the ledger failed with earlier, if any.
+    pub fn flush(&self) -> io::Result<()> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.flush(self.metadata());
+        if let Err(err) = res.as_ref() {
+            store.record(Err::<(), _>(copy_error(err)), || self.context("flushing"));
+        }
+        res
+    }
+
+    fn flush_if_needed(&self) {
+        let npending = self.store.borrow().npending();
+        if npending >= MAX_PENDING_WRITES {
+            // Error is kept and returned by the next explicit flush.
+            let _ = self.flush();
+        }
+    }
+
+    /// Copies the ledger into `new_directory`. When `new_directory` is
+    /// our own directory, the copy is made into a new directory next to
+    /// it, which gets removed once the copy is dropped.
+    pub fn clone_db(&self, new_directory: PathBuf) -> io::Result<Self> {
+        let (new_directory, is_temporary) = if new_directory == self.directory {
+            let mut name = self.directory.file_name().unwrap_or_default().to_owned();
+            name.push(format!("-{}", next_uuid()));
+            (self.directory.with_file_name(name), true)
+        } else {
+            (new_directory, false)
+        };
+
+        self.flush()?;
+        let db = self
+            .store
+            .borrow_mut()
+            .db
+            .create_checkpoint(&new_directory)?;
+        Self::from_db(
+            self.depth,
+            db,
+            new_directory,
+            self.cache_capacity,
+            is_temporary,
+        )
+    }
+
+    pub fn create_checkpoint(&self, directory_name: String) -> io::Result<()> {
+        self.make_checkpoint(directory_name)
+    }
+
+    pub fn make_checkpoint(&self, directory_name: String) -> io::Result<()> {
+        self.flush()?;
+        self.store.borrow_mut().db.make_checkpoint(directory_name)
+    }
+
+    fn context(&self, operation: &str) -> String {
+        format!("{operation}, ledger {:?}", self.directory)
+    }
+
+    fn account_at(&self, index: u64) -> Option<Box<Account>> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.accounts.get(&mut store.db, &index);
+        store.record(res, || self.context("reading account"))?
+    }
+
+    fn index_of(&self, account_id: &AccountId) -> Option<u64> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.ids.get(&mut store.db, account_id);
+        store.record(res, || self.context("reading account index"))?
+    }
+
+    fn get_hash(&self, addr: &Address) -> Option<Fp> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.hashes.get(&mut store.db, &addr.to_linear_index());
+        store.record(res, || self.context("reading hash"))?
+    }
+
+    fn set_hash(&self, addr: &Address, hash: Fp) {
+        self.store
+            .borrow_mut()
+            .hashes
+            .set(addr.to_linear_index(), hash);
+    }
+
+    /// Iterates over the accounts without polluting the caches.
+    fn try_for_each_account<F>(&self, mut fun: F)
+    where
+        F: FnMut(&Account) -> ControlFlow<()>,
+    {
+        let Some(last) = self.last_location.as_ref() else {
+            return;
+        };
+        for index in 0..=last.to_index().0 {
+            let account = {
+                let store = &mut *self.store.borrow_mut();
+                let res = store.accounts.peek(&mut store.db, &index);
+                match store.record(res, || self.context("iterating accounts")) {
+                    Some(account) => account,
+                    None => return,
+                }
+            };
+            if let Some(account) = account {
+                if fun(&account).is_break() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn for_each_account<F>(&self, mut fun: F)
+    where
+        F: FnMut(&Account),
+    {
+        self.try_for_each_account(|account| {
+            fun(account);
+            ControlFlow::Continue(())
+        })
+    }
+
+    fn remove_account_ids(&self, account: &Account) {
+        let id = account.id();
+        let store = &mut *self.store.borrow_mut();
+        let res = store
+            .ids
+            .remove(&mut store.db, &id)
+            .and_then(|()| store.tokens.remove(&mut store.db, &id.token_id));
+        store.record(res, || self.context("removing account"));
+    }
+
+    fn create_account(
+        &mut self,
+        account_id: AccountId,
+        account: Account,
+    ) -> Result<GetOrCreated, DatabaseError> {
+        if let Some(index) = self.index_of(&account_id) {
+            let addr = Address::from_index(AccountIndex(index), self.depth as usize);
+            return Ok(GetOrCreated::Existed(addr));
+        }
+
+        let location = match self.last_location.as_ref() {
+            Some(last) => last.next().ok_or(DatabaseError::OutOfLeaves)?,
+            None => Address::first(self.depth as usize),
+        };
+        let index = location.to_index().0;
+
+        {
+            let store = self.store.get_mut();
+            store
+                .tokens
+                .set(account.token_id.clone(), account_id.clone());
+            store.ids.set(account_id, index);
+            store.accounts.set(index, Box::new(account));
+        }
+
+        self.last_location = Some(location.clone());
+        self.naccounts += 1;
+
+        Ok(GetOrCreated::Added(location))
+    }
+
+    fn emulate_tree_to_get_hash_at(&mut self, addr: Address) -> Fp {
+        if let Some(hash) = self.get_hash(&addr) {
+            return hash;
+        };
+
+        let last_account = self
+            .last_filled()
+            .unwrap_or_else(|| Address::first(self.depth as usize));
+
+        self.emulate_tree_recursive(addr, &last_account)
+    }
+
+    pub fn emulate_tree_recursive(&mut self, addr: Address, last_account: &Address) -> Fp {
+        let tree_depth = self.depth as usize;
+        let current_depth = tree_depth - addr.length();
+
+        if current_depth == 0 {
+            return self
+                .get_account_hash(addr.to_index())
+                .unwrap_or_else(|| self.empty_hash_at_height(0));
+        }
+
+        let mut get_child_hash = |addr: Address| {
+            if let Some(hash) = self.get_hash(&addr) {
+                hash
+            } else if addr.is_before(last_account) {
+                self.emulate_tree_recursive(addr, last_account)
+            } else {
+                self.empty_hash_at_height(current_depth - 1)
+            }
+        };
+
+        let left_hash = get_child_hash(addr.child_left());
+        let right_hash = get_child_hash(addr.child_right());
+
+        match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None => {
+                let hash = V2::hash_node(current_depth - 1, left_hash, right_hash);
+                self.set_hash(&addr, hash);
+                hash
+            }
+        }
+    }
+
+    pub fn emulate_tree_to_get_path(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        path: &mut AddressIterator,
+        merkle_path: &mut Vec<MerklePath>,
+    ) -> Fp {
+        let tree_depth = self.depth as usize;
+
+        if addr.length() == self.depth as usize {
+            return self
+                .get_account_hash(addr.to_index())
+                .unwrap_or_else(|| self.empty_hash_at_height(0));
+        }
+
+        let next_direction = path.next();
+
+        // We go until the end of the path
+        if let Some(direction) = next_direction.as_ref() {
+            let child = match direction {
+                Direction::Left => addr.child_left(),
+                Direction::Right => addr.child_right(),
+            };
+            self.emulate_tree_to_get_path(child, last_account, path, merkle_path);
+        };
+
+        let depth_in_tree = tree_depth - addr.length();
+
+        let mut get_child_hash = |addr: Address| match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None if addr.is_before(last_account) => {
+                self.emulate_tree_to_get_path(addr, last_account, path, merkle_path)
+            }
+            None => self.empty_hash_at_height(depth_in_tree - 1),
+        };
+
+        let left = get_child_hash(addr.child_left());
+        let right = get_child_hash(addr.child_right());
+
+        if let Some(direction) = next_direction {
+            let hash = match direction {
+                Direction::Left => MerklePath::Left(right),
+                Direction::Right => MerklePath::Right(left),
+            };
+            merkle_path.push(hash);
+        };
+
+        match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None => {
+                let hash = V2::hash_node(depth_in_tree - 1, left, right);
+                self.set_hash(&addr, hash);
+                hash
+            }
+        }
+    }
+
+    pub fn get_cached_hash(&self, addr: &Address) -> Option<Fp> {
+        self.get_hash(addr)
+    }
+
+    pub fn set_cached_hash(&mut self, addr: &Address, hash: Fp) {
+        self.set_hash(addr, hash);
+    }
+
+    pub fn empty_hash_at_height(&self, height: usize) -> Fp {
+        crate::tree::empty_hash_at_height(height)
+    }
+
+    pub fn invalidate_hashes(&mut self, account_index: AccountIndex) {
+        let store = self.store.get_mut();
+        let mut addr = Address::from_index(account_index, self.depth as usize);
+
+        loop {
+            let res = store.hashes.remove(&mut store.db, &addr.to_linear_index());
+            if store
+                .record(res, || {
+                    format!("invalidating hashes, ledger {:?}", self.directory)
+                })
+                .is_none()
+            {
+                break;
+            }
+            addr = match addr.parent() {
+                Some(addr) => addr,
+                None => break,
+            }
+        }
+    }
+
+    pub fn transfert_hashes(&mut self, hashes: HashesMatrix) {
+        self.set_raw_inner_hashes(hashes.get_raw_inner_hashes());
+    }
+
+    pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.hashes.keys(&mut store.db).and_then(|indexes| {
+            indexes
+                .into_iter()
+                .filter_map(|index| {
+                    let hash = store.hashes.peek(&mut store.db, &index).transpose()?;
+                    Some(hash.map(|hash| (index, hash)))
+                })
+                .collect::<io::Result<Vec<_>>>()
+        });
+        let mut hashes = store
+            .record(res, || self.context("listing hashes"))
+            .unwrap_or_default();
+        hashes.sort_by_key(|(index, _)| *index);
+        hashes
+    }
+
+    pub fn set_raw_inner_hashes(&mut self, hashes: Vec<(u64, Fp)>) {
+        let store = self.store.get_mut();
+        for (index, hash) in hashes {
+            store.hashes.set(index, hash);
+        }
+        self.flush_if_needed();
+    }
+
+    pub fn hashes_matrix(&self) -> HashesMatrix {
+        let mut matrix = HashesMatrix::new(self.depth as usize);
+        matrix.set_raw_inner_hashes(self.get_raw_inner_hashes());
+        matrix
+    }
+
+    pub fn root_hash(&mut self) -> Fp {
+        self.emulate_tree_to_get_hash_at(Address::root())
+    }
+
+    pub fn naccounts(&self) -> usize {
+        self.naccounts
+    }
+}
+
+impl BaseLedger for OnDiskDatabaseImpl {
+    fn to_list(&self) -> Vec<Account> {
+        let mut accounts = Vec::with_capacity(self.naccounts);
+        self.for_each_account(|account| accounts.push(account.clone()));
+        accounts
+    }
+
+    fn iter<F>(&self, fun: F)
+    where
+        F: FnMut(&Account),
+    {
+        self.for_each_account(fun)
+    }
+
+    fn fold<B, F>(&self, init: B, mut fun: F) -> B
+    where
+        F: FnMut(B, &Account) -> B,
+    {
+        let mut accum = Some(init);
+        self.for_each_account(|account| {
+            accum = Some(fun(accum.take().unwrap(), account));
+        });
+        accum.unwrap()
+    }
+
+    fn fold_with_ignored_accounts<B, F>(
+        &self,
+        ignoreds: HashSet<AccountId>,
+        init: B,
+        mut fun: F,
+    ) -> B
+    where
+        F: FnMut(B, &Account) -> B,
+    {
+        self.fold(init, |accum, account| {
+            if ignoreds.contains(&account.id()) {
+                accum
+            } else {
+                fun(accum, account)
+            }
+        })
+    }
+
+    fn fold_until<B, F>(&self, init: B, mut fun: F) -> B
+    where
+        F: FnMut(B, &Account) -> ControlFlow<B, B>,
+    {
+        let mut accum = Some(init);
+        self.try_for_each_account(|account| match fun(accum.take().unwrap(), account) {
+            ControlFlow::Continue(v) => {
+                accum = Some(v);
+                ControlFlow::Continue(())
+            }
+            ControlFlow::Break(v) => {
+                accum = Some(v);
+                ControlFlow::Break(())
+            }
+        });
+        accum.unwrap()
+    }
+
+    fn accounts(&self) -> HashSet<AccountId> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.ids.keys(&mut store.db);
+        store
+            .record(res, || self.context("listing accounts"))
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    }
+
+    fn token_owner(&self, token_id: TokenId) -> Option<AccountId> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.tokens.get(&mut store.db, &token_id);
+        store.record(res, || self.context("reading token owner"))?
+    }
+
+    fn token_owners(&self) -> HashSet<AccountId> {
+        let store = &mut *self.store.borrow_mut();
+        let res = store.tokens.keys(&mut store.db).and_then(|token_ids| {
+            token_ids
+                .into_iter()
+                .filter_map(|token_id| store.tokens.peek(&mut store.db, &token_id).transpose())
+                .collect::<io::Result<HashSet<_>>>()
+        });
+        store
+            .record(res, || self.context("listing token owners"))
+            .unwrap_or_default()
+    }
+
+    fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
+        let mut set = HashSet::with_capacity(100);
+        self.for_each_account(|account| {
+            if account.public_key == public_key {
+                set.insert(account.token_id.clone());
+            }
+        });
+        set
+    }
+
+    fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
+        let index = self.index_of(account_id)?;
+        Some(Address::from_index(
+            AccountIndex(index),
+            self.depth as usize,
+        ))
+    }
+
+    fn location_of_account_batch(
+        &self,
+        account_ids: &[AccountId],
+    ) -> Vec<(AccountId, Option<Address>)> {
+        account_ids
+            .iter()
+            .map(|account_id| (account_id.clone(), self.location_of_account(account_id)))
+            .collect()
+    }
+
+    fn get_or_create_account(
+        &mut self,
+        account_id: AccountId,
+        account: Account,
+    ) -> Result<GetOrCreated, DatabaseError> {
+        let result = self.create_account(account_id, account);
+
+        if let Ok(GetOrCreated::Added(addr)) = result.as_ref() {
+            self.invalidate_hashes(addr.to_index());
+            self.flush_if_needed();
+        };
+
+        result
+    }
+
+    fn close(&self) {
+        // Drop
+    }
+
+    fn last_filled(&self) -> Option<Address> {
+        self.last_location.clone()
+    }
+
+    fn get_uuid(&self) -> Uuid {
+        self.uuid.clone()
+    }
+
+    fn get_directory(&self) -> Option<PathBuf> {
+        Some(self.directory.clone())
+    }
+
+    fn get_account_hash(&mut self, account_index: AccountIndex) -> Option<Fp> {
+        let addr = Address::from_index(account_index, self.depth as usize);
+
+        if let Some(hash) = self.get_hash(&addr) {
+            return Some(hash);
+        }
+
+        let hash = self.account_at(account_index.0)?.hash();
+        self.set_hash(&addr, hash);
+
+        Some(hash)
+    }
+
+    fn get(&self, addr: Address) -> Option<Box<Account>> {
+        self.account_at(addr.to_index().0)
+    }
+
+    fn get_batch(&self, addr: &[Address]) -> Vec<(Address, Option<Box<Account>>)> {
+        addr.iter()
+            .map(|addr| (addr.clone(), self.get(addr.clone())))
+            .collect()
+    }
+
+    fn set(&mut self, addr: Address, account: Box<Account>) {
+        let index = addr.to_index();
+
+        self.invalidate_hashes(index);
+
+        // Remove account at the address and it's index
+        match self.account_at(index.0) {
+            Some(old) => self.remove_account_ids(&old),
+            None => self.naccounts += 1,
+        }
+
+        let id = account.id();
+        {
+            let store = self.store.get_mut();
+            store.tokens.set(account.token_id.clone(), id.clone());
+            store.ids.set(id, index.0);
+            store.accounts.set(index.0, account);
+        }
+
+        if self
+            .last_location
+            .as_ref()
+            .map(|l| l.to_index() < addr.to_index())
+            .unwrap_or(true)
+        {
+            self.last_location = Some(addr);
+        }
+
+        self.flush_if_needed();
+    }
+
+    fn set_batch(&mut self, list: &[(Address, Box<Account>)]) {
+        for (addr, account) in list {
+            assert_eq!(addr.length(), self.depth as usize, "addr={:?}", addr);
+            self.set(addr.clone(), account.clone());
+        }
+    }
+
+    fn get_at_index(&self, index: AccountIndex) -> Option<Box<Account>> {
+        self.account_at(index.0)
+    }
+
+    fn set_at_index(&mut self, index: AccountIndex, account: Box<Account>) -> Result<(), ()> {
+        let addr = Address::from_index(index, self.depth as usize);
+        self.set(addr, account);
+        Ok(())
+    }
+
+    fn index_of_account(&self, account_id: AccountId) -> Option<AccountIndex> {
+        self.index_of(&account_id).map(AccountIndex)
+    }
+
+    fn merkle_root(&mut self) -> Fp {
+        self.root_hash()
+    }
+
+    fn merkle_path(&mut self, addr: Address) -> Vec<MerklePath> {
+        let mut merkle_path = Vec::with_capacity(addr.length());
+        let mut path = addr.into_iter();
+        let addr = Address::root();
+
+        let last_account = self
+            .last_filled()
+            .unwrap_or_else(|| Address::first(self.depth as usize));
+
+        self.emulate_tree_to_get_path(addr, &last_account, &mut path, &mut merkle_path);
+
+        merkle_path
+    }
+
+    fn merkle_path_at_index(&mut self, index: AccountIndex) -> Vec<MerklePath> {
+        let addr = Address::from_index(index, self.depth as usize);
+        self.merkle_path(addr)
+    }
+
+    fn remove_accounts(&mut self, ids: &[AccountId]) {
+        let mut indexes = ids
+            .iter()
+            .map(|account_id| self.index_of(account_id).unwrap())
+            .collect::<Vec<_>>();
+        indexes.sort();
+
+        for index in indexes.into_iter().rev() {
+            self.invalidate_hashes(AccountIndex(index));
+
+            let account = match self.account_at(index) {
+                Some(account) => account,
+                None => continue,
+            };
+            self.remove_account_ids(&account);
+            {
+                let store = self.store.get_mut();
+                let res = store.accounts.remove(&mut store.db, &index);
+                store.record(res, || {
+                    format!("removing account, ledger {:?}", self.directory)
+                });
+            }
+
+            self.naccounts = self
+                .naccounts
+                .checked_sub(1)
+                .expect("invalid naccounts counter");
+
+            let addr = Address::from_index(AccountIndex(index), self.depth as usize);
+            if self
+                .last_location
+                .as_ref()
+                .map(|last| last == &addr)
+                .unwrap_or(false)
+            {
+                self.last_location = addr.prev();
+            }
+        }
+
+        self.flush_if_needed();
+    }
+
+    fn detached_signal(&mut self) {
+        // no-op
+    }
+
+    fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    fn num_accounts(&self) -> usize {
+        self.naccounts
+    }
+
+    fn merkle_path_at_addr(&mut self, addr: Address) -> Vec<MerklePath> {
+        self.merkle_path(addr)
+    }
+
+    fn get_inner_hash_at_addr(&mut self, addr: Address) -> Result<Fp, String> {
+        Ok(self.emulate_tree_to_get_hash_at(addr))
+    }
+
+    fn set_inner_hash_at_addr(&mut self, _addr: Address, _hash: Fp) -> Result<(), ()> {
+        // No-op, same as the in-memory database, hashes are computed
+        // from the accounts.
+        Ok(())
+    }
+
+    fn set_all_accounts_rooted_at(
+        &mut self,
+        addr: Address,
+        accounts: &[Box<Account>],
+    ) -> Result<(), ()> {
+        if addr.length() > self.depth as usize {
+            return Err(());
+        }
+
+        for (child_addr, account) in addr.iter_children(self.depth as usize).zip(accounts) {
+            self.set(child_addr, account.clone());
+        }
+
+        Ok(())
+    }
+
+    fn get_all_accounts_rooted_at(&self, addr: Address) -> Option<Vec<(Address, Box<Account>)>> {
+        if addr.length() > self.depth as usize {
+            return None;
+        }
+
+        let children = addr.iter_children(self.depth as usize);
+        let mut accounts = Vec::with_capacity(children.len());
+
+        for child_addr in children {
+            let account = match self.get(child_addr.clone()) {
+                Some(account) => account,
+                None => continue,
+            };
+            accounts.push((child_addr, account));
+        }
+
+        if accounts.is_empty() {
+            None
+        } else {
+            Some(accounts)
+        }
+    }
+
+    fn make_space_for(&mut self, _space: usize) {
+        // No op
+    }
+
+    fn commit(&mut self) {
+        // no-op
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Database, Mask};
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("ondisk-ledger-{}", next_uuid()))
+    }
+
+    fn accounts(n: usize) -> Vec<Account> {
+        (0..n).map(|_| Account::rand()).collect()
+    }
+
+    fn fill(db: &mut impl BaseLedger, accounts: &[Account]) {
+        for account in accounts {
+            db.get_or_create_account(account.id(), account.clone())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_same_hashes_as_in_memory() {
+        const DEPTH: u8 = 10;
+        let dir = temp_dir();
+        let accounts = accounts(300);
+
+        let mut in_memory = Database::create(DEPTH);
+        // Small cache, so that entries get evicted and read back from disk.
+        let mut on_disk = Database::create_on_disk_with_cache_capacity(DEPTH, &dir, 8).unwrap();
+        fill(&mut in_memory, &accounts);
+        fill(&mut on_disk, &accounts);
+
+        assert_eq!(in_memory.merkle_root(), on_disk.merkle_root());
+        for index in [0, 1, 150, 299] {
+            let index = AccountIndex(index);
+            assert_eq!(
+                in_memory.merkle_path_at_index(index),
+                on_disk.merkle_path_at_index(index)
+            );
+        }
+
+        let mut updated = accounts[42].clone();
+        updated.balance = updated
+            .balance
+            .add_amount(crate::scan_state::currency::Amount::from_u64(1))
+            .unwrap();
+        let addr = in_memory.location_of_account(&updated.id()).unwrap();
+        in_memory.set(addr.clone(), Box::new(updated.clone()));
+        on_disk.set(addr.clone(), Box::new(updated.clone()));
+        assert_eq!(on_disk.get(addr).as_deref(), Some(&updated));
+
+        in_memory.remove_accounts(&[accounts[299].id()]);
+        on_disk.remove_accounts(&[accounts[299].id()]);
+        assert_eq!(in_memory.num_accounts(), on_disk.num_accounts());
+        assert_eq!(in_memory.merkle_root(), on_disk.merkle_root());
+        assert_eq!(in_memory.accounts(), on_disk.accounts());
+
+        drop(on_disk);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_reopen() {
+        const DEPTH: u8 = 10;
+        let dir = temp_dir();
+        let accounts = accounts(100);
+
+        let mut db = Database::create_on_disk(DEPTH, &dir).unwrap();
+        fill(&mut db, &accounts);
+        let root = db.merkle_root();
+        drop(db);
+
+        let mut db = Database::create_on_disk(DEPTH, &dir).unwrap();
+        assert_eq!(db.num_accounts(), accounts.len());
+        assert_eq!(db.merkle_root(), root);
+        let id = accounts[10].id();
+        assert_eq!(
+            db.location_of_account(&id),
+            Some(Address::from_index(AccountIndex(10), DEPTH as usize))
+        );
+        drop(db);
+
+        assert!(Database::create_on_disk(DEPTH + 1, &dir).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_temporary_removed_on_drop() {
+        const DEPTH: u8 = 10;
+        let dir = temp_dir();
+
+        let mut db = Database::create_on_disk(DEPTH, &dir).unwrap();
+        fill(&mut db, &accounts(10));
+        drop(db);
+
+        // Leftovers in the directory are removed.
+        let mut db = Database::create_on_disk_temporary(DEPTH, &dir).unwrap();
+        assert_eq!(db.num_accounts(), 0);
+        fill(&mut db, &accounts(10));
+        db.flush().unwrap();
+        let copy = db.clone_db(dir.clone()).unwrap();
+        drop(db);
+        assert!(!dir.exists());
+
+        let copy_dir = copy.get_directory().unwrap();
+        assert!(copy_dir.exists());
+        drop(copy);
+        assert!(!copy_dir.exists());
+    }
+
+    #[test]
+    fn test_with_in_memory_only() {
+        let dir = temp_dir();
+
+        let on_disk = Database::create_on_disk_temporary(10, &dir).unwrap();
+        assert!(on_disk.with(|_| ()).is_err());
+        assert!(Database::create(10).with(|_| ()).is_ok());
+    }
+
+    #[test]
+    fn test_mask_on_top() {
+        const DEPTH: u8 = 10;
+        let dir = temp_dir();
+        let accounts = accounts(50);
+
+        let in_memory = Mask::new_root(Database::create(DEPTH));
+        let on_disk = Mask::new_root(Database::create_on_disk(DEPTH, &dir).unwrap());
+
+        for root in [&in_memory, &on_disk] {
+            let mut child = root.make_child();
+            fill(&mut child, &accounts);
+            child.commit();
+        }
+
+        assert_eq!(
+            in_memory.clone().merkle_root(),
+            on_disk.clone().merkle_root()
+        );
+        assert_eq!(on_disk.num_accounts(), accounts.len());
+
+        drop(on_disk);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
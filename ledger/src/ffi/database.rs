@@ -195,13 +195,13 @@ ocaml_export! {
             {
                 let mut db = db.0.borrow_mut();
                 let db = db.as_mut().unwrap();
-                db.create_checkpoint(directory_name.clone());
+                db.create_checkpoint(directory_name.clone()).unwrap();
             }
 
             let directory_name = PathBuf::from(directory_name);
 
             let db: Ref<Option<Database<V2>>> = (*db.0).borrow();
-            let db_clone = db.as_ref().unwrap().clone_db(directory_name);
+            let db_clone = db.as_ref().unwrap().clone_db(directory_name).unwrap();
 
             DatabaseFFI(Rc::new(RefCell::new(Some(db_clone))))
         };
@@ -222,13 +222,13 @@ ocaml_export! {
         {
             let mut db = db.0.borrow_mut();
             let db = db.as_mut().unwrap();
-            db.make_checkpoint(directory_name.clone());
+            db.make_checkpoint(directory_name.clone()).unwrap();
         }
 
         let directory_name = PathBuf::from(directory_name);
 
         let db: Ref<Option<Database<V2>>> = (*db.0).borrow();
-        let db_clone = db.as_ref().unwrap().clone_db(directory_name.clone());
+        let db_clone = db.as_ref().unwrap().clone_db(directory_name.clone()).unwrap();
 
         let mut closed_dbs = DB_CLOSED.try_lock().unwrap();
         closed_dbs.insert(directory_name, db_clone);
@@ -1,5 +1,4 @@
 use std::{
-    collections::HashMap,
     fs::{File, OpenOptions},
     io::{BufReader, BufWriter, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
@@ -10,6 +9,7 @@ use std::io::ErrorKind::{InvalidData, Other, UnexpectedEof};
 use super::{
     batch::Batch,
     compression::{compress, decompress, MaybeCompressed},
+    index::{hash_key, DiskIndex},
     lock::LockedFile,
 };
 
@@ -28,10 +28,17 @@ const BUFFER_DEFAULT_CAPACITY: usize = 4096;
 const DATABASE_VERSION: u64 = 1;
 const DATABASE_VERSION_NBYTES: usize = 8;
 
+/// Number of entries read from the database file before they are added to
+/// the index, when the index is caught up on reload.
+const REINDEX_CHUNK_LEN: usize = 64 * 1024;
+
 pub struct Database {
     uuid: Uuid,
-    /// Index of keys to their values offset
-    index: HashMap<Key, Offset>,
+    /// Index of keys to their values offset, stored on disk
+    index: DiskIndex,
+    /// Entries written since the last flush, which aren't in the index yet.
+    /// `None` for removed keys.
+    unindexed: Vec<(Key, Option<Offset>)>,
     /// Points to end of file
     current_file_offset: Offset,
     file: BufWriter<LockedFile>,
@@ -195,7 +202,7 @@ fn next_uuid() -> Uuid {
     uuid::Uuid::new_v4().to_string()
 }
 
-fn read_u64(slice: &[u8]) -> std::io::Result<u64> {
+pub(super) fn read_u64(slice: &[u8]) -> std::io::Result<u64> {
     slice
         .get(..8)
         .and_then(|slice: &[u8]| slice.try_into().ok())
@@ -226,20 +233,80 @@ fn ensure_buffer_length(buffer: &mut Vec<u8>, length: usize) {
 }
 
 #[cfg(unix)]
-fn read_exact_at(file: &mut File, buffer: &mut [u8], offset: Offset) -> std::io::Result<()> {
+pub(super) fn read_exact_at(
+    file: &mut File,
+    buffer: &mut [u8],
+    offset: Offset,
+) -> std::io::Result<()> {
     use std::os::unix::prelude::FileExt;
 
     file.read_exact_at(buffer, offset)
 }
 
 #[cfg(not(unix))]
-fn read_exact_at(file: &mut File, buffer: &mut [u8], offset: Offset) -> std::io::Result<()> {
+pub(super) fn read_exact_at(
+    file: &mut File,
+    buffer: &mut [u8],
+    offset: Offset,
+) -> std::io::Result<()> {
     use std::io::Read;
 
     file.seek(SeekFrom::Start(offset))?;
     file.read_exact(buffer)
 }
 
+/// Whether the key of the entry at `header_offset` is `key`.
+fn is_key_at(
+    file: &mut File,
+    buffer: &mut Vec<u8>,
+    header_offset: Offset,
+    key: &[u8],
+) -> std::io::Result<bool> {
+    let key_bytes = read_key_bytes(file, buffer, header_offset)?;
+    Ok(&*key_bytes == key)
+}
+
+fn read_key_bytes(
+    file: &mut File,
+    buffer: &mut Vec<u8>,
+    header_offset: Offset,
+) -> std::io::Result<Key> {
+    ensure_buffer_length(buffer, EntryHeader::NBYTES);
+    read_exact_at(file, &mut buffer[..EntryHeader::NBYTES], header_offset)?;
+    let header = EntryHeader::read(buffer)?;
+
+    let key_length = header.key_length as usize;
+    ensure_buffer_length(buffer, key_length);
+    read_exact_at(
+        file,
+        &mut buffer[..key_length],
+        header_offset + EntryHeader::NBYTES as u64,
+    )?;
+    decompress(&buffer[..key_length], header.key_is_compressed)
+}
+
+/// Adds the entry of `key` at `header_offset` (or its removal) to the index.
+fn index_entry(
+    index: &mut DiskIndex,
+    file: &mut File,
+    buffer: &mut Vec<u8>,
+    key: &[u8],
+    header_offset: Option<Offset>,
+) -> std::io::Result<()> {
+    let hash = hash_key(key);
+    let matches = |offset| is_key_at(file, buffer, offset, key);
+    match header_offset {
+        Some(header_offset) => index.insert(hash, header_offset, matches),
+        None => index.remove(hash, matches),
+    }
+}
+
+fn index_filename(filename: &Path) -> PathBuf {
+    let mut index_filename = filename.to_owned().into_os_string();
+    index_filename.push("_index");
+    index_filename.into()
+}
+
 enum CreateMode {
     Regular,
     Temporary,
@@ -302,9 +369,13 @@ impl Database {
 
         file.write_all(&DATABASE_VERSION.to_le_bytes())?;
 
+        let mut index = DiskIndex::create(index_filename(&filename))?;
+        index.commit(DATABASE_VERSION_NBYTES as u64)?;
+
         Ok(Self {
             uuid: next_uuid(),
-            index: HashMap::with_capacity(128),
+            index,
+            unindexed: Vec::new(),
             current_file_offset: DATABASE_VERSION_NBYTES as u64,
             file: BufWriter::with_capacity(4 * 1024 * 1024, file), // 4 MB
             buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
@@ -313,6 +384,9 @@ impl Database {
     }
 
     /// Reload the database at the specified path
+    ///
+    /// Entries written after the index was last updated are added to it,
+    /// the index is rebuilt from scratch if it is missing.
     fn reload(filename: PathBuf) -> std::io::Result<Self> {
         use std::io::Read;
 
@@ -343,7 +417,14 @@ impl Database {
             current_offset += DATABASE_VERSION_NBYTES as u64;
         }
 
-        let mut index = HashMap::with_capacity(256);
+        let mut index = DiskIndex::open(index_filename(&filename), eof)?;
+        if index.indexed_up_to() > current_offset {
+            current_offset = index.indexed_up_to();
+            reader.seek(SeekFrom::Start(current_offset))?;
+        }
+
+        let mut unindexed = Vec::new();
+        let mut buffer = Vec::with_capacity(BUFFER_DEFAULT_CAPACITY);
 
         while current_offset < eof {
             let header_offset = current_offset;
@@ -364,26 +445,38 @@ impl Database {
             header.verify_checksum(key_bytes, value_bytes)?;
 
             let key = decompress(key_bytes, header.key_is_compressed)?;
-
-            if header.is_removed {
-                index.remove(&key);
-            } else {
-                index.insert(key, header_offset);
-            }
+            unindexed.push((key, (!header.is_removed).then_some(header_offset)));
 
             current_offset += (EntryHeader::NBYTES + entry_length) as u64;
+
+            if unindexed.len() >= REINDEX_CHUNK_LEN || current_offset >= eof {
+                // Keys are compared by reading them from the file, outside
+                // of the reader's buffer.
+                for (key, header_offset) in unindexed.drain(..) {
+                    index_entry(
+                        &mut index,
+                        reader.get_mut(),
+                        &mut buffer,
+                        &key,
+                        header_offset,
+                    )?;
+                }
+                reader.seek(SeekFrom::Start(current_offset))?;
+            }
         }
 
         if eof != current_offset {
             return Err(UnexpectedEof.into());
         }
+        index.commit(eof)?;
 
         Ok(Self {
             uuid: next_uuid(),
             index,
+            unindexed,
             current_file_offset: eof,
             file: BufWriter::with_capacity(4 * 1024 * 1024, reader.into_inner()), // 4 MB
-            buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
+            buffer,
             filename,
         })
     }
@@ -435,7 +528,7 @@ impl Database {
     pub fn get(&mut self, key: &[u8]) -> std::io::Result<Option<Value>> {
         // Note: `&mut self` is required for `File::seek`
 
-        let header_offset = match self.index.get(key).copied() {
+        let header_offset = match self.find(key)? {
             Some(header_offset) => header_offset,
             None => return Ok(None),
         };
@@ -452,6 +545,56 @@ impl Database {
         decompress(value, header.value_is_compressed).map(Some)
     }
 
+    /// Returns the offset of the entry header of `key`.
+    fn find(&mut self, key: &[u8]) -> std::io::Result<Option<Offset>> {
+        if !self.unindexed.is_empty() {
+            self.flush()?;
+        }
+
+        let Self {
+            index,
+            file,
+            buffer,
+            ..
+        } = self;
+        index.get(hash_key(key), |offset| {
+            is_key_at(file.get_mut(), buffer, offset, key)
+        })
+    }
+
+    /// Checks if the key exists, without reading the value from disk.
+    pub fn contains_key(&mut self, key: &[u8]) -> std::io::Result<bool> {
+        Ok(self.find(key)?.is_some())
+    }
+
+    /// Calls `fun` with the offsets of the entry headers of all keys.
+    fn for_each_offset<F>(&mut self, mut fun: F) -> std::io::Result<()>
+    where
+        F: FnMut(&mut File, &mut Vec<u8>, Offset) -> std::io::Result<()>,
+    {
+        if !self.unindexed.is_empty() {
+            self.flush()?;
+        }
+
+        let Self {
+            index,
+            file,
+            buffer,
+            ..
+        } = self;
+        index.for_each_offset(|offset| fun(file.get_mut(), buffer, offset))
+    }
+
+    /// Returns all keys in the database, in no particular order.
+    pub fn keys(&mut self) -> std::io::Result<Vec<Key>> {
+        let mut keys = Vec::new();
+        self.for_each_offset(|file, buffer, offset| {
+            keys.push(read_key_bytes(file, buffer, offset)?);
+            Ok(())
+        })?;
+        Ok(keys)
+    }
+
     fn set_impl(&mut self, key: Key, value: Option<Value>) -> std::io::Result<()> {
         let is_removed = value.is_none();
 
@@ -473,12 +616,9 @@ impl Database {
         let buffer_len = EntryHeader::NBYTES as u64 + header.entry_length()?;
         self.current_file_offset += buffer_len;
 
-        // Update index
-        if is_removed {
-            self.index.remove(&key);
-        } else {
-            self.index.insert(key, header_offset);
-        }
+        // Index is updated once the entry is flushed
+        self.unindexed
+            .push((key, (!is_removed).then_some(header_offset)));
 
         Ok(())
     }
@@ -574,7 +714,7 @@ impl Database {
     pub fn create_checkpoint(&mut self, directory: impl AsRef<Path>) -> std::io::Result<Self> {
         let mut checkpoint = Self::create(directory.as_ref())?;
 
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
+        let keys = self.keys()?;
 
         for key in keys {
             let value = self.get(&key)?;
@@ -586,10 +726,24 @@ impl Database {
         Ok(checkpoint)
     }
 
-    /// Flush writes buffer to fs and call `fsync`
+    /// Flush writes buffer to fs and call `fsync`, then adds the written
+    /// entries to the index
     fn flush(&mut self) -> std::io::Result<()> {
         self.file.flush()?;
-        self.file.get_ref().sync_all()
+        self.file.get_ref().sync_all()?;
+
+        let current_file_offset = self.current_file_offset;
+        let Self {
+            index,
+            unindexed,
+            file,
+            buffer,
+            ..
+        } = self;
+        for (key, header_offset) in unindexed.drain(..) {
+            index_entry(index, file.get_mut(), buffer, &key, header_offset)?;
+        }
+        index.commit(current_file_offset)
     }
 
     fn remove_impl(&mut self, key: Key) -> std::io::Result<()> {
@@ -618,7 +772,7 @@ impl Database {
     /// * `Result<Vec<(Box<[u8]>, Box<[u8]>)>>` - Returns a vector containing
     ///   all key-value pairs as boxed byte arrays. Returns an error if retrieval fails.
     pub fn to_alist(&mut self) -> std::io::Result<Vec<(Key, Value)>> {
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
+        let keys = self.keys()?;
 
         keys.into_iter()
             .map(|key| {
@@ -665,7 +819,7 @@ impl Database {
         let directory = self.filename.parent().unwrap();
         let mut new_db = Self::create_impl(directory, CreateMode::Temporary)?;
 
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
+        let keys = self.keys()?;
 
         for key in keys {
             let value = self.get(&key)?;
@@ -674,7 +828,11 @@ impl Database {
 
         new_db.flush()?;
 
+        // The old index is removed first, if we crash before the new one
+        // is in place, it is rebuilt from the new database file on reload.
+        std::fs::remove_file(index_filename(&self.filename))?;
         exchange_file_atomically(&self.filename, &new_db.filename)?;
+        new_db.index.rename(index_filename(&self.filename))?;
 
         new_db.filename.clone_from(&self.filename);
         new_db.uuid.clone_from(&self.uuid);
@@ -683,6 +841,23 @@ impl Database {
 
         Ok(())
     }
+
+    /// Returns the number of bytes taken in the database file by
+    /// overwritten or removed entries, which [`Self::gc`] would free.
+    ///
+    /// Only the entry headers of the current values are read.
+    pub fn garbage_bytes(&mut self) -> std::io::Result<u64> {
+        let mut live_bytes = DATABASE_VERSION_NBYTES as u64;
+        self.for_each_offset(|file, buffer, header_offset| {
+            ensure_buffer_length(buffer, EntryHeader::NBYTES);
+            read_exact_at(file, &mut buffer[..EntryHeader::NBYTES], header_offset)?;
+            let header = EntryHeader::read(buffer)?;
+            live_bytes += EntryHeader::NBYTES as u64 + header.entry_length()?;
+            Ok(())
+        })?;
+
+        Ok(self.current_file_offset.saturating_sub(live_bytes))
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -727,7 +902,10 @@ fn exchange_file_atomically(db_path: &Path, tmp_path: &Path) -> std::io::Result<
 #[cfg(test)]
 mod tests {
     use rand::{Fill, Rng};
-    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    };
 
     use super::*;
 
@@ -939,6 +1117,24 @@ mod tests {
         assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("b"));
     }
 
+    #[test]
+    fn test_garbage_bytes() {
+        let db_dir = TempDir::new();
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set(key("a"), value("a")).unwrap();
+        db.set(key("b"), value("b")).unwrap();
+        assert_eq!(db.garbage_bytes().unwrap(), 0);
+
+        db.set(key("a"), value("c")).unwrap();
+        db.remove(key("b")).unwrap();
+        assert!(db.garbage_bytes().unwrap() > 0);
+
+        db.gc().unwrap();
+        assert_eq!(db.garbage_bytes().unwrap(), 0);
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("c"));
+    }
+
     #[test]
     fn test_to_alist() {
         let db_dir = TempDir::new();
@@ -996,4 +1192,37 @@ mod tests {
         assert_eq!(db_sorted, db_alist);
         assert_eq!(cp_sorted, cp_alist);
     }
+
+    #[test]
+    fn test_reindex_on_reload() {
+        let db_dir = TempDir::new();
+        let index_path = index_filename(&db_dir.as_path().join("db"));
+
+        let mut rng = rand::thread_rng();
+        let nkeys: usize = rng.gen_range(1000..2000);
+        let sorted = make_random_key_values(nkeys);
+        let (first, second) = sorted.split_at(nkeys / 2);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set_batch(first.to_vec(), []).unwrap();
+        let stale_index = std::fs::read(&index_path).unwrap();
+        db.set_batch(second.to_vec(), []).unwrap();
+        db.remove(first[0].0.clone()).unwrap();
+        let expected = sorted_vec(db.to_alist().unwrap());
+        drop(db);
+
+        // Index lagging behind the database file is caught up.
+        std::fs::write(&index_path, stale_index).unwrap();
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(sorted_vec(db.to_alist().unwrap()), expected);
+        assert!(!db.contains_key(&first[0].0).unwrap());
+        assert!(db.contains_key(&second[0].0).unwrap());
+        drop(db);
+
+        // Missing index is rebuilt.
+        std::fs::remove_file(&index_path).unwrap();
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(sorted_vec(db.to_alist().unwrap()), expected);
+        assert_eq!(db.get(&second[1].0).unwrap().as_ref(), Some(&second[1].1));
+    }
 }
@@ -0,0 +1,384 @@
+//! On-disk index of the database keys to the offsets of their entries.
+//!
+//! The index is a hash table with open addressing (linear probing), stored
+//! in its own file next to the database file:
+//!
+//! ```ignored
+//! +-----------+------------+-----------+------------+---------------+----------+
+//! |  VERSION  |  CAPACITY  |    LEN    |    USED    | INDEXED_UP_TO |  SLOTS   |
+//! | (8 bytes) | (8 bytes)  | (8 bytes) | (8 bytes)  |   (8 bytes)   |   ...    |
+//! +-----------+------------+-----------+------------+---------------+----------+
+//! ```
+//!
+//! Each slot is the hash of the key (8 bytes, zero for an empty slot) and
+//! the offset of the entry header in the database file (8 bytes,
+//! [`TOMBSTONE`] for removed keys). Only hashes are stored, the key itself
+//! is compared by reading it from the database file.
+//!
+//! `INDEXED_UP_TO` is the offset in the database file up to which the
+//! entries are reflected in the index. Entries past it are added to the
+//! index when the database is opened, so the index is consistent with the
+//! database file even if the process is killed while updating it.
+
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use std::io::ErrorKind::InvalidData;
+
+use super::database::{read_exact_at, read_u64, Offset};
+
+const INDEX_VERSION: u64 = 1;
+const HEADER_NBYTES: u64 = 40;
+const SLOT_NBYTES: u64 = 16;
+const MIN_CAPACITY: u64 = 1024;
+/// Number of slots read at once when the whole table is scanned.
+const SCAN_CHUNK_SLOTS: u64 = 4096;
+
+/// Offset of a removed key, the slot can't be marked as empty as that
+/// would break the probing sequence of the keys after it.
+const TOMBSTONE: Offset = Offset::MAX;
+
+/// FNV-1a, it has to be stable across builds as hashes are stored on disk.
+pub(super) fn hash_key(key: &[u8]) -> u64 {
+    let hash = key.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    // Zero marks an empty slot.
+    hash.max(1)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    hash: u64,
+    offset: Offset,
+}
+
+impl Slot {
+    fn is_empty(&self) -> bool {
+        self.hash == 0
+    }
+
+    fn is_live(&self) -> bool {
+        !self.is_empty() && self.offset != TOMBSTONE
+    }
+
+    fn read(bytes: &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            hash: read_u64(bytes)?,
+            offset: read_u64(&bytes[8..])?,
+        })
+    }
+
+    fn to_bytes(self) -> [u8; SLOT_NBYTES as usize] {
+        let mut bytes = [0; SLOT_NBYTES as usize];
+        bytes[..8].copy_from_slice(&self.hash.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.offset.to_le_bytes());
+        bytes
+    }
+}
+
+pub(super) struct DiskIndex {
+    file: File,
+    filename: PathBuf,
+    /// Number of slots, power of two.
+    capacity: u64,
+    /// Number of live keys.
+    len: u64,
+    /// Number of non-empty slots, tombstones included.
+    used: u64,
+    indexed_up_to: Offset,
+}
+
+impl DiskIndex {
+    /// Opens the index stored in `filename`. A missing index, or one which
+    /// can't be trusted (`indexed_up_to` past `data_eof`), is replaced with
+    /// an empty one indexing nothing.
+    pub(super) fn open(filename: PathBuf, data_eof: Offset) -> std::io::Result<Self> {
+        match Self::try_reopen(&filename, data_eof)? {
+            Some(index) => Ok(index),
+            None => Self::create(filename),
+        }
+    }
+
+    fn try_reopen(filename: &Path, data_eof: Offset) -> std::io::Result<Option<Self>> {
+        let mut file = match OpenOptions::new().read(true).write(true).open(filename) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let mut header = [0; HEADER_NBYTES as usize];
+        if read_exact_at(&mut file, &mut header, 0).is_err() {
+            return Ok(None);
+        }
+        let version = read_u64(&header)?;
+        let capacity = read_u64(&header[8..])?;
+        let len = read_u64(&header[16..])?;
+        let used = read_u64(&header[24..])?;
+        let indexed_up_to = read_u64(&header[32..])?;
+
+        let file_len = file.metadata()?.len();
+        let is_valid = version == INDEX_VERSION
+            && capacity.is_power_of_two()
+            && file_len == HEADER_NBYTES + capacity * SLOT_NBYTES
+            && indexed_up_to <= data_eof;
+        if !is_valid {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            file,
+            filename: filename.to_owned(),
+            capacity,
+            len,
+            used,
+            indexed_up_to,
+        }))
+    }
+
+    /// Creates an empty index in `filename`, replacing the existing one.
+    pub(super) fn create(filename: PathBuf) -> std::io::Result<Self> {
+        Self::create_with_capacity(filename, MIN_CAPACITY)
+    }
+
+    fn create_with_capacity(filename: PathBuf, capacity: u64) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&filename)?;
+        // Slots are zeroed, i.e. empty.
+        file.set_len(HEADER_NBYTES + capacity * SLOT_NBYTES)?;
+
+        let mut index = Self {
+            file,
+            filename,
+            capacity,
+            len: 0,
+            used: 0,
+            indexed_up_to: 0,
+        };
+        index.write_header()?;
+        Ok(index)
+    }
+
+    /// Moves the index file to `filename`.
+    pub(super) fn rename(&mut self, filename: PathBuf) -> std::io::Result<()> {
+        std::fs::rename(&self.filename, &filename)?;
+        self.filename = filename;
+        Ok(())
+    }
+
+    /// Offset in the database file up to which the entries are indexed.
+    pub(super) fn indexed_up_to(&self) -> Offset {
+        self.indexed_up_to
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        let mut header = [0; HEADER_NBYTES as usize];
+        for (i, value) in [
+            INDEX_VERSION,
+            self.capacity,
+            self.len,
+            self.used,
+            self.indexed_up_to,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            header[i * 8..(i + 1) * 8].copy_from_slice(&value.to_le_bytes());
+        }
+        write_all_at(&mut self.file, &header, 0)
+    }
+
+    /// Records that the entries up to `offset` are indexed, and syncs the
+    /// index to disk.
+    pub(super) fn commit(&mut self, offset: Offset) -> std::io::Result<()> {
+        self.indexed_up_to = offset;
+        self.write_header()?;
+        self.file.sync_data()
+    }
+
+    fn slot_offset(&self, position: u64) -> u64 {
+        HEADER_NBYTES + position * SLOT_NBYTES
+    }
+
+    fn read_slot(&mut self, position: u64) -> std::io::Result<Slot> {
+        let mut bytes = [0; SLOT_NBYTES as usize];
+        read_exact_at(&mut self.file, &mut bytes, self.slot_offset(position))?;
+        Slot::read(&bytes)
+    }
+
+    fn write_slot(&mut self, position: u64, slot: Slot) -> std::io::Result<()> {
+        let offset = self.slot_offset(position);
+        write_all_at(&mut self.file, &slot.to_bytes(), offset)
+    }
+
+    /// Finds the slot of the key with `hash`. `is_key_at` tells whether
+    /// the entry at the offset is the key we look for.
+    ///
+    /// Returns the position of the key, if found, and the position of the
+    /// first free slot (tombstone or empty) in its probing sequence.
+    fn find<F>(&mut self, hash: u64, mut is_key_at: F) -> std::io::Result<(Option<u64>, u64)>
+    where
+        F: FnMut(Offset) -> std::io::Result<bool>,
+    {
+        let mask = self.capacity - 1;
+        let mut position = hash & mask;
+        let mut free = None;
+
+        for _ in 0..self.capacity {
+            let slot = self.read_slot(position)?;
+            if slot.is_empty() {
+                return Ok((None, free.unwrap_or(position)));
+            }
+            if slot.offset == TOMBSTONE {
+                free.get_or_insert(position);
+            } else if slot.hash == hash && is_key_at(slot.offset)? {
+                return Ok((Some(position), free.unwrap_or(position)));
+            }
+            position = (position + 1) & mask;
+        }
+
+        // Table is never full, it grows before that.
+        free.map(|free| (None, free))
+            .ok_or_else(|| std::io::Error::new(InvalidData, "index is full"))
+    }
+
+    pub(super) fn get<F>(&mut self, hash: u64, is_key_at: F) -> std::io::Result<Option<Offset>>
+    where
+        F: FnMut(Offset) -> std::io::Result<bool>,
+    {
+        let (found, _) = self.find(hash, is_key_at)?;
+        match found {
+            Some(position) => Ok(Some(self.read_slot(position)?.offset)),
+            None => Ok(None),
+        }
+    }
+
+    pub(super) fn insert<F>(
+        &mut self,
+        hash: u64,
+        offset: Offset,
+        is_key_at: F,
+    ) -> std::io::Result<()>
+    where
+        F: FnMut(Offset) -> std::io::Result<bool>,
+    {
+        if (self.used + 1) * 2 > self.capacity {
+            self.grow()?;
+        }
+
+        let (found, free) = self.find(hash, is_key_at)?;
+        let position = match found {
+            Some(position) => position,
+            None => {
+                if self.read_slot(free)?.is_empty() {
+                    self.used += 1;
+                }
+                self.len += 1;
+                free
+            }
+        };
+        self.write_slot(position, Slot { hash, offset })
+    }
+
+    pub(super) fn remove<F>(&mut self, hash: u64, is_key_at: F) -> std::io::Result<()>
+    where
+        F: FnMut(Offset) -> std::io::Result<bool>,
+    {
+        let (Some(position), _) = self.find(hash, is_key_at)? else {
+            return Ok(());
+        };
+        self.len -= 1;
+        self.write_slot(
+            position,
+            Slot {
+                hash,
+                offset: TOMBSTONE,
+            },
+        )
+    }
+
+    /// Calls `fun` with the offsets of all indexed entries, in no
+    /// particular order.
+    pub(super) fn for_each_offset<F>(&mut self, mut fun: F) -> std::io::Result<()>
+    where
+        F: FnMut(Offset) -> std::io::Result<()>,
+    {
+        let mut bytes = vec![0; (SCAN_CHUNK_SLOTS * SLOT_NBYTES) as usize];
+        let mut position = 0;
+        while position < self.capacity {
+            let nslots = SCAN_CHUNK_SLOTS.min(self.capacity - position);
+            let bytes = &mut bytes[..(nslots * SLOT_NBYTES) as usize];
+            read_exact_at(&mut self.file, bytes, self.slot_offset(position))?;
+
+            for slot in bytes.chunks_exact(SLOT_NBYTES as usize) {
+                let slot = Slot::read(slot)?;
+                if slot.is_live() {
+                    fun(slot.offset)?;
+                }
+            }
+            position += nslots;
+        }
+        Ok(())
+    }
+
+    /// Doubles the capacity (unless most of the used slots are tombstones)
+    /// and moves the live slots into the new table.
+    fn grow(&mut self) -> std::io::Result<()> {
+        let mut capacity = self.capacity;
+        while (self.len + 1) * 4 > capacity {
+            capacity *= 2;
+        }
+
+        let mut tmp_filename = self.filename.clone().into_os_string();
+        tmp_filename.push("_tmp");
+        let tmp_filename = PathBuf::from(tmp_filename);
+
+        let mut new = Self::create_with_capacity(tmp_filename, capacity)?;
+        let mut bytes = vec![0; (SCAN_CHUNK_SLOTS * SLOT_NBYTES) as usize];
+        let mut position = 0;
+        while position < self.capacity {
+            let nslots = SCAN_CHUNK_SLOTS.min(self.capacity - position);
+            let bytes = &mut bytes[..(nslots * SLOT_NBYTES) as usize];
+            read_exact_at(&mut self.file, bytes, self.slot_offset(position))?;
+
+            for slot in bytes.chunks_exact(SLOT_NBYTES as usize) {
+                let slot = Slot::read(slot)?;
+                if slot.is_live() {
+                    // Keys are unique, no need to compare them.
+                    new.insert(slot.hash, slot.offset, |_| Ok(false))?;
+                }
+            }
+            position += nslots;
+        }
+
+        new.indexed_up_to = self.indexed_up_to;
+        new.write_header()?;
+        new.file.sync_all()?;
+        std::fs::rename(&new.filename, &self.filename)?;
+        new.filename.clone_from(&self.filename);
+
+        *self = new;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &mut File, buffer: &[u8], offset: Offset) -> std::io::Result<()> {
+    use std::os::unix::prelude::FileExt;
+
+    file.write_all_at(buffer, offset)
+}
+
+#[cfg(not(unix))]
+fn write_all_at(file: &mut File, buffer: &[u8], offset: Offset) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(buffer)
+}
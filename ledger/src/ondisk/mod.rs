@@ -33,6 +33,9 @@
 //! - `KEY`: The key data
 //! - `VALUE`: The value data
 //!
+//! Offsets of the entries are looked up in an index stored in a separate file,
+//! see `index.rs` for its format, so keys aren't kept in memory.
+//!
 //! ## Example Usage
 //!
 //! Create an instance of MyDatabase:
@@ -57,6 +60,7 @@
 pub mod batch;
 mod compression;
 mod database;
+mod index;
 mod lock;
 
 pub use batch::Batch;
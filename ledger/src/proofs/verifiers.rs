@@ -6,6 +6,7 @@ use std::{
 
 use anyhow::Context;
 use once_cell::sync::OnceCell;
+use openmina_core::network::{devnet, mainnet};
 use openmina_core::{info, log::system_time, warn};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -187,11 +188,17 @@ impl BlockVerifier {
     }
 
     fn src_json() -> &'static str {
-        let network_name = openmina_core::NetworkConfig::global().name;
-        match network_name {
-            "mainnet" => include_str!("data/mainnet_blockchain_verifier_index.json"),
-            "devnet" => include_str!("data/devnet_blockchain_verifier_index.json"),
-            other => panic!("get_verifier_index: unknown network '{other}'"),
+        // Custom networks reuse circuits of one of the built-in networks, so
+        // the index is selected by the blockchain-step circuit digest.
+        let config = openmina_core::NetworkConfig::global();
+        match config.constraint_system_digests[2] {
+            digest if digest == mainnet::CONSTRAINT_SYSTEM_DIGESTS[2] => {
+                include_str!("data/mainnet_blockchain_verifier_index.json")
+            }
+            digest if digest == devnet::CONSTRAINT_SYSTEM_DIGESTS[2] => {
+                include_str!("data/devnet_blockchain_verifier_index.json")
+            }
+            _ => panic!("get_verifier_index: unknown network '{}'", config.name),
         }
     }
 }
@@ -202,11 +209,17 @@ impl TransactionVerifier {
     }
 
     fn src_json() -> &'static str {
-        let network_name = openmina_core::NetworkConfig::global().name;
-        match network_name {
-            "mainnet" => include_str!("data/mainnet_transaction_verifier_index.json"),
-            "devnet" => include_str!("data/devnet_transaction_verifier_index.json"),
-            other => panic!("get_verifier_index: unknown network '{other}'"),
+        // Selected by the transaction-base circuit digest, see
+        // `BlockVerifier::src_json`.
+        let config = openmina_core::NetworkConfig::global();
+        match config.constraint_system_digests[1] {
+            digest if digest == mainnet::CONSTRAINT_SYSTEM_DIGESTS[1] => {
+                include_str!("data/mainnet_transaction_verifier_index.json")
+            }
+            digest if digest == devnet::CONSTRAINT_SYSTEM_DIGESTS[1] => {
+                include_str!("data/devnet_transaction_verifier_index.json")
+            }
+            _ => panic!("get_verifier_index: unknown network '{}'", config.name),
         }
     }
 
@@ -1,3 +1,8 @@
+use mina_p2p_messages::v2::TransactionHash;
+use mina_signer::CompressedPubKey;
+use openmina_core::snark::SnarkJobId;
+use serde::{Deserialize, Serialize};
+
 use crate::scan_state::currency::{Fee, Magnitude};
 use crate::{
     scan_state::{
@@ -5,6 +10,7 @@ use crate::{
         transaction_logic::{valid, CoinbaseFeeTransfer, GenericCommand},
     },
     staged_ledger::diff::AtMostTwo,
+    transaction_pool::transaction_hash::command_hash,
 };
 
 use self::detail::Detail;
@@ -18,7 +24,7 @@ fn add_fee(fee1: FeeSummable, fee2: FeeSummable) -> FeeSummable {
     fee1.checked_add(&fee2).unwrap()
 }
 
-#[derive(Copy, Debug, Clone)]
+#[derive(Copy, Debug, Clone, Serialize, Deserialize)]
 pub enum Reason {
     NoWork,
     NoSpace,
@@ -28,13 +34,44 @@ pub enum Reason {
     End,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Partition {
     First,
     Second,
 }
 
-mod summary {
+/// Command or completed work removed from the diff by a [`detail::Line`].
+/// Only what identifies it is kept, not the command or the proofs.
+#[derive(Clone, Debug)]
+pub enum Discarded {
+    Command {
+        hash: TransactionHash,
+    },
+    CompletedWork {
+        job_id: SnarkJobId,
+        fee: Fee,
+        prover: CompressedPubKey,
+    },
+}
+
+impl Discarded {
+    fn command(command: &valid::UserCommand) -> Self {
+        let hash = command_hash(command);
+        Self::Command {
+            hash: TransactionHash::from(hash.as_ref()),
+        }
+    }
+
+    fn completed_work(work: &work::Unchecked) -> Self {
+        Self::CompletedWork {
+            job_id: SnarkJobId::from(&work.statement()),
+            fee: work.fee,
+            prover: work.prover.clone(),
+        }
+    }
+}
+
+pub mod summary {
 
     use super::*;
 
@@ -47,25 +84,25 @@ mod summary {
 
     #[derive(Clone, Debug)]
     pub struct CommandConstraints {
-        insufficient_work: u64,
-        insufficient_space: u64,
+        pub insufficient_work: u64,
+        pub insufficient_space: u64,
     }
 
     #[derive(Clone, Debug)]
     pub struct CompletedWorkConstraints {
-        insufficient_fees: u64,
-        extra_work: u64,
+        pub insufficient_fees: u64,
+        pub extra_work: u64,
     }
 
     #[derive(Clone, Debug)]
     pub struct Summary {
-        partition: Partition,
-        start_resources: Resources,
-        available_slots: u64,
-        required_work_count: u64,
-        discarded_commands: CommandConstraints,
-        discarded_completed_work: CompletedWorkConstraints,
-        end_resources: Resources,
+        pub partition: Partition,
+        pub start_resources: Resources,
+        pub available_slots: u64,
+        pub required_work_count: u64,
+        pub discarded_commands: CommandConstraints,
+        pub discarded_completed_work: CompletedWorkConstraints,
+        pub end_resources: Resources,
     }
 
     pub fn coinbase_fees(coinbase: &AtMostTwo<CoinbaseFeeTransfer>) -> AtMostTwo<FeeSummable> {
@@ -183,15 +220,16 @@ mod summary {
     }
 }
 
-mod detail {
+pub mod detail {
     use super::*;
 
     #[derive(Debug, Clone)]
-    struct Line {
-        reason: Reason,
-        commands: CountAndFee,
-        completed_work: CountAndFee,
-        coinbase: AtMostTwo<Fee>,
+    pub struct Line {
+        pub reason: Reason,
+        pub commands: CountAndFee,
+        pub completed_work: CountAndFee,
+        pub coinbase: AtMostTwo<Fee>,
+        pub discarded: Option<Discarded>,
     }
 
     #[derive(Clone, Debug)]
@@ -211,11 +249,16 @@ mod detail {
                 commands: init.commands,
                 completed_work: init.completed_work,
                 coinbase: init.coinbase_work_fees,
+                discarded: None,
             });
 
             Self(lines)
         }
 
+        pub fn lines(&self) -> &[Line] {
+            &self.0
+        }
+
         pub fn discard_command(&mut self, why: Reason, command: &valid::UserCommand) {
             assert!(!self.0.is_empty());
 
@@ -227,7 +270,9 @@ mod detail {
                     last.commands.0 - 1,
                     last.commands.1.checked_sub(&command.fee()).unwrap(),
                 ),
-                ..last.clone()
+                completed_work: last.completed_work,
+                coinbase: last.coinbase.clone(),
+                discarded: Some(Discarded::command(command)),
             };
 
             self.0.push(new_line);
@@ -247,7 +292,9 @@ mod detail {
                         .checked_sub(&completed_work.fee)
                         .unwrap(),
                 ),
-                ..last.clone()
+                commands: last.commands,
+                coinbase: last.coinbase.clone(),
+                discarded: Some(Discarded::completed_work(completed_work)),
             };
 
             self.0.push(new_line);
@@ -261,8 +308,10 @@ mod detail {
             // Because coinbase could be updated ooutside of the check_constraints_and_update function
             let new_line = Line {
                 reason: Reason::End,
+                commands: last.commands,
+                completed_work: last.completed_work,
                 coinbase: summary::coinbase_fees(coinbase),
-                ..last.clone()
+                discarded: None,
             };
 
             self.0.push(new_line);
@@ -309,7 +358,7 @@ impl DiffCreationLog {
 
     pub fn discard_completed_work(&mut self, why: Reason, completed_work: &work::Unchecked) {
         self.detail.discard_completed_work(why, completed_work);
-        self.summary.discard_command(why);
+        self.summary.discard_completed_work(why);
     }
 
     pub fn end_log(
@@ -322,3 +371,68 @@ impl DiffCreationLog {
         self.detail.end_log(coinbase);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::{
+        binprot::BinProtRead, gossip::GossipNetMessageV2,
+        v2::NetworkPoolSnarkPoolDiffVersionedStableV2,
+    };
+    use openmina_core::snark::Snark;
+
+    use super::*;
+
+    /// Work from the recorded gossip message, with the `fee` replaced.
+    fn completed_work(fee: u64) -> work::Checked {
+        let mut bytes: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin");
+        let GossipNetMessageV2::SnarkPoolDiff {
+            message: NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(diff),
+            ..
+        } = GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+        else {
+            panic!("expected a snark pool diff with a solved work");
+        };
+        let mut work = work::Checked::try_from(&Snark::from(diff.1)).unwrap();
+        work.fee = Fee::from_u64(fee);
+        work
+    }
+
+    #[test]
+    fn discard_completed_work() {
+        let works = [completed_work(10), completed_work(20), completed_work(30)];
+        let mut log = DiffCreationLog::init(&works, &[], &AtMostTwo::Zero, Partition::First, 4, 2);
+        log.discard_completed_work(Reason::ExtraWork, &works[2]);
+        log.discard_completed_work(Reason::InsufficientFees, &works[1]);
+        log.discard_completed_work(Reason::InsufficientFees, &works[0]);
+        log.end_log(&[], &[], &AtMostTwo::Zero);
+
+        let summary = &log.summary;
+        assert_eq!(summary.discarded_completed_work.extra_work, 1);
+        assert_eq!(summary.discarded_completed_work.insufficient_fees, 2);
+        assert_eq!(summary.discarded_commands.insufficient_work, 0);
+        assert_eq!(summary.discarded_commands.insufficient_space, 0);
+        assert_eq!(
+            summary.start_resources.completed_work,
+            (3, Fee::from_u64(60))
+        );
+        assert_eq!(summary.end_resources.completed_work, (0, Fee::zero()));
+
+        let lines = log.detail.lines();
+        assert_eq!(lines.len(), 5);
+        assert!(matches!(lines[0].reason, Reason::Init));
+        assert!(lines[0].discarded.is_none());
+        assert!(matches!(lines[1].reason, Reason::ExtraWork));
+        assert_eq!(lines[1].completed_work, (2, Fee::from_u64(30)));
+        assert!(matches!(
+            &lines[1].discarded,
+            Some(Discarded::CompletedWork { job_id, fee, prover })
+                if job_id == &SnarkJobId::from(&works[2].statement())
+                    && *fee == Fee::from_u64(30)
+                    && prover == &works[2].prover
+        ));
+        assert_eq!(lines[3].completed_work, (0, Fee::zero()));
+        assert!(matches!(lines[4].reason, Reason::End));
+        assert!(lines[4].discarded.is_none());
+    }
+}
@@ -245,6 +245,25 @@ impl StagedLedger {
         })
     }
 
+    /// Staged ledger from its parts, as they were after applying a block,
+    /// without checking the scan state invariants.
+    ///
+    /// Used to restore staged ledgers persisted by the node. Caller must
+    /// compare [`Self::hash`] with the expected staged ledger hash.
+    pub fn of_parts_unchecked(
+        constraint_constants: &ConstraintConstants,
+        ledger: Mask,
+        scan_state: ScanState,
+        pending_coinbase_collection: PendingCoinbase,
+    ) -> Self {
+        Self {
+            scan_state,
+            ledger,
+            constraint_constants: constraint_constants.clone(),
+            pending_coinbase_collection,
+        }
+    }
+
     /// https://github.com/MinaProtocol/mina/blob/436023ba41c43a50458a551b7ef7a9ae61670b25/src/lib/staged_ledger/staged_ledger.ml#L353
     fn of_scan_state_pending_coinbases_and_snarked_ledger_prime<F, G>(
         constraint_constants: &ConstraintConstants,
@@ -1787,6 +1806,44 @@ impl StagedLedger {
         ),
         PreDiffError,
     >
+    where
+        F: Fn(&work::Statement) -> Option<work::Checked>,
+    {
+        self.create_diff_with_log(
+            constraint_constants,
+            global_slot,
+            log_block_creation,
+            coinbase_receiver,
+            logger,
+            current_state_view,
+            transactions_by_fee,
+            get_completed_work,
+            supercharge_coinbase,
+        )
+        .map(|(diff, invalid_txns, _log)| (diff, invalid_txns))
+    }
+
+    /// Same as [`StagedLedger::create_diff`], but also returns the diff
+    /// creation log, one entry per partition.
+    pub fn create_diff_with_log<F>(
+        &self,
+        constraint_constants: &ConstraintConstants,
+        global_slot: Slot,
+        log_block_creation: Option<bool>,
+        coinbase_receiver: CompressedPubKey,
+        logger: (),
+        current_state_view: &ProtocolStateView,
+        transactions_by_fee: Vec<valid::UserCommand>,
+        get_completed_work: F,
+        supercharge_coinbase: bool,
+    ) -> Result<
+        (
+            with_valid_signatures_and_proofs::Diff,
+            Vec<(valid::UserCommand, String)>,
+            Vec<DiffCreationLog>,
+        ),
+        PreDiffError,
+    >
     where
         F: Fn(&work::Statement) -> Option<work::Checked>,
     {
@@ -1887,7 +1944,7 @@ impl StagedLedger {
 
             let _valid_on_this_ledger_len = valid_on_this_ledger.len();
 
-            let (diff, _log) = Self::generate(
+            let (diff, log) = Self::generate(
                 constraint_constants,
                 logger,
                 completed_works_seq,
@@ -1942,7 +1999,7 @@ impl StagedLedger {
 
             let diff = with_valid_signatures_and_proofs::Diff { diff };
 
-            Ok((diff, invalid_on_this_ledger))
+            Ok((diff, invalid_on_this_ledger, log))
         })
     }
 
@@ -1549,6 +1549,13 @@ pub mod transaction_hash {
     use super::*;
 
     pub fn hash_command(cmd: valid::UserCommand) -> ValidCommandWithHash {
+        let hash = command_hash(&cmd);
+        WithHash { data: cmd, hash }
+    }
+
+    /// Hash of the command, same as the one of [`hash_command`], without
+    /// taking the command.
+    pub fn command_hash(cmd: &valid::UserCommand) -> BlakeHash {
         use mina_p2p_messages::binprot::BinProtWrite;
 
         fn to_binprot<T: Into<V>, V: BinProtWrite>(v: T) -> Vec<u8> {
@@ -1558,7 +1565,7 @@ pub mod transaction_hash {
             buffer
         }
 
-        let buffer: Vec<u8> = match &cmd {
+        let buffer: Vec<u8> = match cmd {
             valid::UserCommand::SignedCommand(cmd) => {
                 let mut cmd: SignedCommand = (**cmd).clone();
                 cmd.signature = Signature::dummy();
@@ -1581,15 +1588,11 @@ pub mod transaction_hash {
         let mut hasher = Blake2bVar::new(32).expect("Invalid Blake2bVar output size");
         hasher.update(&buffer);
 
-        let hash: Arc<[u8; 32]> = {
-            let mut buffer = [0; 32];
-            hasher
-                .finalize_variable(&mut buffer)
-                .expect("Invalid buffer size"); // Never occur
-            Arc::from(buffer)
-        };
-
-        WithHash { data: cmd, hash }
+        let mut buffer = [0; 32];
+        hasher
+            .finalize_variable(&mut buffer)
+            .expect("Invalid buffer size"); // Never occur
+        Arc::from(buffer)
     }
 }
 
@@ -211,6 +211,11 @@ impl HashesMatrix {
     }
 }
 
+/// Hash of an empty subtree of the given height.
+pub(crate) fn empty_hash_at_height(height: usize) -> Fp {
+    HASH_EMPTIES.lock().unwrap()[height]
+}
+
 static HASH_EMPTIES: Lazy<Mutex<Vec<Fp>>> = Lazy::new(|| {
     /// This value needs to be changed when the tree's height change
     const RANGE_HEIGHT: std::ops::Range<usize> = 0..36;
@@ -64,8 +64,19 @@ impl TryFrom<SexpString> for Info {
 /// Represents error processing an RPC request.
 pub type Error = Info;
 
+/// Time as seconds since Unix epoch.
 // TODO
-#[derive(Clone, Debug, Serialize, Deserialize, BinProtRead, BinProtWrite, PartialEq)]
+#[derive(
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    BinProtRead,
+    BinProtWrite,
+    PartialEq,
+    derive_more::From,
+    derive_more::Into,
+)]
 pub struct Time(f64);
 
 pub type InetAddrV1Versioned = Versioned<InetAddrV1, 1>;
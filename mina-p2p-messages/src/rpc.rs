@@ -156,20 +156,20 @@ mina_rpc!(GetBestTipV2, "get_best_tip", 2, (), GetBestTipV2Response);
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, BinProtRead, BinProtWrite)]
 pub struct NodeStatusV2 {
-    node_ip_addr: InetAddrV1Versioned,
-    node_peer_id: v2::NetworkPeerPeerIdStableV1,
-    sync_status: v2::SyncStatusTStableV1,
-    peers: List<v2::NetworkPeerPeerIdStableV1>,
-    block_producers: List<v2::NonZeroCurvePoint>,
-    protocol_state_hash: v2::StateHash,
-    ban_statuses: List<(
+    pub node_ip_addr: InetAddrV1Versioned,
+    pub node_peer_id: v2::NetworkPeerPeerIdStableV1,
+    pub sync_status: v2::SyncStatusTStableV1,
+    pub peers: List<v2::NetworkPeerPeerIdStableV1>,
+    pub block_producers: List<v2::NonZeroCurvePoint>,
+    pub protocol_state_hash: v2::StateHash,
+    pub ban_statuses: List<(
         v2::NetworkPeerPeerIdStableV1,
         v2::TrustSystemPeerStatusStableV1,
     )>,
-    k_block_hashes_and_timestamps: List<(v2::StateHash, CharString)>,
-    git_commit: CharString,
-    uptime_minutes: i32,
-    block_height_opt: Option<i32>,
+    pub k_block_hashes_and_timestamps: List<(v2::StateHash, CharString)>,
+    pub git_commit: CharString,
+    pub uptime_minutes: i32,
+    pub block_height_opt: Option<i32>,
 }
 mina_rpc!(GetNodeStatusV2, "get_node_status", 2, (), RpcResult<NodeStatusV2, core::Error>);
 
@@ -0,0 +1,280 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use ledger::{proofs::provers::BlockProver, AccountIndex};
+use mina_p2p_messages::v2::{
+    EpochSeed, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash,
+};
+use mina_signer::Keypair;
+use node::account::{AccountPublicKey, AccountSecretKey};
+use vrf::output::VrfOutput;
+
+/// Holds the block producer's secret key and performs the operations
+/// which need it.
+///
+/// The secret key is needed for the vrf evaluation and as a witness for
+/// the stake proof inside of the block proof, so the backend evaluates
+/// vrf outputs and creates block proofs.
+pub trait BlockProducerKeyBackend: Send + Sync {
+    fn public_key(&self) -> &AccountPublicKey;
+
+    /// Calculates the vrf outputs for the slot, one for each of the
+    /// delegators, in the same order.
+    fn vrf_outputs(
+        &self,
+        global_slot: u32,
+        epoch_seed: &EpochSeed,
+        delegator_indexes: &[AccountIndex],
+    ) -> Result<Vec<VrfOutput>, String>;
+
+    /// Creates a block proof, using `provers` if the proof is created
+    /// in the node process.
+    ///
+    /// Must refuse to prove a block for a slot, for which a different
+    /// block was already proven.
+    fn prove_block(
+        &self,
+        provers: &BlockProver,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    ) -> Result<Box<MinaBaseProofStableV2>, String>;
+
+    /// Secret key, if it lives in the node process.
+    fn secret_key(&self) -> Option<&AccountSecretKey> {
+        None
+    }
+}
+
+/// Backend with the secret key living in the node process.
+pub struct LocalKeyBackend {
+    public_key: AccountPublicKey,
+    secret_key: AccountSecretKey,
+    keypair: Keypair,
+    guard: Mutex<BlockProductionGuard>,
+}
+
+impl LocalKeyBackend {
+    pub fn new(secret_key: AccountSecretKey) -> Self {
+        Self::with_guard(secret_key, BlockProductionGuard::default())
+    }
+
+    pub fn with_guard(secret_key: AccountSecretKey, guard: BlockProductionGuard) -> Self {
+        Self {
+            public_key: secret_key.public_key(),
+            keypair: secret_key.clone().into(),
+            secret_key,
+            guard: Mutex::new(guard),
+        }
+    }
+}
+
+impl BlockProducerKeyBackend for LocalKeyBackend {
+    fn public_key(&self) -> &AccountPublicKey {
+        &self.public_key
+    }
+
+    fn vrf_outputs(
+        &self,
+        global_slot: u32,
+        epoch_seed: &EpochSeed,
+        delegator_indexes: &[AccountIndex],
+    ) -> Result<Vec<VrfOutput>, String> {
+        delegator_indexes
+            .iter()
+            .map(|delegator_index| {
+                vrf::calculate_vrf(
+                    &self.keypair,
+                    epoch_seed.clone(),
+                    global_slot,
+                    delegator_index,
+                )
+                .map_err(|err| err.to_string())
+            })
+            .collect()
+    }
+
+    fn prove_block(
+        &self,
+        provers: &BlockProver,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    ) -> Result<Box<MinaBaseProofStableV2>, String> {
+        self.guard
+            .lock()
+            .map_err(|_| "block production guard poisoned".to_owned())?
+            .check_and_record(&input)?;
+
+        super::prove(provers.clone(), input, self.secret_key.clone(), false)
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn secret_key(&self) -> Option<&AccountSecretKey> {
+        Some(&self.secret_key)
+    }
+}
+
+/// Keeps track of the blocks proven for each global slot and refuses
+/// to prove a different block for the same slot, so that we never
+/// produce two competing blocks for one slot.
+#[derive(Debug, Default)]
+pub struct BlockProductionGuard {
+    /// File where proven slots are persisted, so that the protection
+    /// survives restarts.
+    path: Option<PathBuf>,
+    proven: BTreeMap<u32, StateHash>,
+}
+
+impl BlockProductionGuard {
+    /// How many slots behind the latest proven slot to remember.
+    const RETENTION_SLOTS: u32 = 2 * 7140;
+
+    /// Loads the guard state from the `path`, if it exists.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let proven = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path: Some(path),
+            proven,
+        })
+    }
+
+    /// Checks that no other block was proven for the block's slot and
+    /// records the block.
+    pub fn check_and_record(
+        &mut self,
+        input: &ProverExtendBlockchainInputStableV2,
+    ) -> Result<(), String> {
+        let global_slot = input
+            .next_state
+            .body
+            .consensus_state
+            .curr_global_slot_since_hard_fork
+            .slot_number
+            .as_u32();
+        let block_hash = input
+            .next_state
+            .try_hash()
+            .map_err(|err| format!("failed to hash the block: {err}"))?;
+        self.check_and_record_slot(global_slot, block_hash)
+    }
+
+    fn check_and_record_slot(
+        &mut self,
+        global_slot: u32,
+        block_hash: StateHash,
+    ) -> Result<(), String> {
+        match self.proven.get(&global_slot) {
+            Some(proven) if proven == &block_hash => return Ok(()),
+            Some(proven) => {
+                return Err(format!(
+                    "refusing to prove block {block_hash} for slot {global_slot}, block {proven} was already proven for it"
+                ));
+            }
+            None => {}
+        }
+
+        // Slot is recorded in memory only once it's persisted, otherwise
+        // the block could be proven again after a restart.
+        let mut proven = self.proven.clone();
+        proven.insert(global_slot, block_hash);
+        let cutoff = global_slot.saturating_sub(Self::RETENTION_SLOTS);
+        proven.retain(|slot, _| *slot >= cutoff);
+        self.persist(&proven)
+            .map_err(|err| format!("failed to persist proven slots: {err}"))?;
+        self.proven = proven;
+        Ok(())
+    }
+
+    fn persist(&self, proven: &BTreeMap<u32, StateHash>) -> io::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let tmp_path = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(proven)?)?;
+        file.sync_all()?;
+        std::fs::rename(tmp_path, path)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn block_hash(n: u8) -> StateHash {
+        let hash = match n {
+            1 => "3NK2tkzqqK5spR2sZ7tujjqPksL45M3UUrcA4WhCkeiPtnugyE2x",
+            _ => "3NKxUSAJE3wqJkrtBhMYhwzrMq3B5sKjPJQRyXz1YrPWA7761opD",
+        };
+        StateHash::from_str(hash).unwrap()
+    }
+
+    #[test]
+    fn test_guard_same_block_same_slot() {
+        let mut guard = BlockProductionGuard::default();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+    }
+
+    #[test]
+    fn test_guard_different_block_same_slot() {
+        let mut guard = BlockProductionGuard::default();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+        assert!(guard.check_and_record_slot(10, block_hash(2)).is_err());
+        guard.check_and_record_slot(11, block_hash(2)).unwrap();
+    }
+
+    #[test]
+    fn test_guard_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "openmina-block-production-guard-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut guard = BlockProductionGuard::load(&path).unwrap();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+        drop(guard);
+
+        let mut guard = BlockProductionGuard::load(&path).unwrap();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+        assert!(guard.check_and_record_slot(10, block_hash(2)).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_guard_persist_failure() {
+        let path = std::env::temp_dir()
+            .join(format!("openmina-missing-dir-{}", std::process::id()))
+            .join("guard.json");
+        let mut guard = BlockProductionGuard::load(&path).unwrap();
+        assert!(guard.check_and_record_slot(10, block_hash(1)).is_err());
+        // Nothing was persisted, so the slot isn't recorded either.
+        assert!(guard.proven.is_empty());
+        assert!(guard.check_and_record_slot(10, block_hash(1)).is_err());
+    }
+
+    #[test]
+    fn test_guard_retention() {
+        let mut guard = BlockProductionGuard::default();
+        guard.check_and_record_slot(10, block_hash(1)).unwrap();
+        let later_slot = 11 + BlockProductionGuard::RETENTION_SLOTS;
+        guard
+            .check_and_record_slot(later_slot, block_hash(2))
+            .unwrap();
+        assert!(!guard.proven.contains_key(&10));
+    }
+}
@@ -1,5 +1,15 @@
+mod key_backend;
+#[cfg(unix)]
+mod remote_signer;
 mod vrf_evaluator;
 
+pub use key_backend::*;
+#[cfg(unix)]
+pub use remote_signer::*;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use ledger::proofs::{
     block::BlockParams, generate_block_proof, provers::BlockProver, transaction::ProofError,
 };
@@ -8,7 +18,7 @@ use mina_p2p_messages::{
     v2::{MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash},
 };
 use node::{
-    account::AccountSecretKey,
+    account::{AccountPublicKey, AccountSecretKey},
     block_producer::{vrf_evaluator::VrfEvaluatorInput, BlockProducerEvent},
     core::{channels::mpsc, constants::constraint_constants, thread},
 };
@@ -17,19 +27,23 @@ use crate::EventSender;
 
 pub struct BlockProducerService {
     provers: BlockProver,
-    keypair: AccountSecretKey,
-    vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
+    keys: BlockProducerKeys,
+    vrf_evaluation_sender: mpsc::UnboundedSender<(VrfEvaluatorInput, BlockProducerKeys)>,
 }
 
+/// Key backends of the producers run by this node, keyed by public key.
+pub type BlockProducerKeys = Arc<BTreeMap<AccountPublicKey, Arc<dyn BlockProducerKeyBackend>>>;
+
 impl BlockProducerService {
     pub fn new(
         provers: BlockProver,
-        keypair: AccountSecretKey,
-        vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
+        key: Arc<dyn BlockProducerKeyBackend>,
+        vrf_evaluation_sender: mpsc::UnboundedSender<(VrfEvaluatorInput, BlockProducerKeys)>,
     ) -> Self {
+        let keys = [(key.public_key().clone(), key)].into_iter().collect();
         Self {
             provers,
-            keypair,
+            keys: Arc::new(keys),
             vrf_evaluation_sender,
         }
     }
@@ -37,28 +51,48 @@ impl BlockProducerService {
     pub fn start(
         provers: BlockProver,
         event_sender: EventSender,
-        keypair: AccountSecretKey,
+        key: Arc<dyn BlockProducerKeyBackend>,
     ) -> Self {
-        let (vrf_evaluation_sender, vrf_evaluation_receiver) =
-            mpsc::unbounded_channel::<VrfEvaluatorInput>();
+        let (vrf_evaluation_sender, vrf_evaluation_receiver) = mpsc::unbounded_channel();
 
-        let producer_keypair = keypair.clone();
         thread::Builder::new()
             .name("openmina_vrf_evaluator".to_owned())
             .spawn(move || {
-                vrf_evaluator::vrf_evaluator(
-                    event_sender,
-                    vrf_evaluation_receiver,
-                    producer_keypair.into(),
-                );
+                vrf_evaluator::vrf_evaluator(event_sender, vrf_evaluation_receiver);
             })
             .unwrap();
 
-        BlockProducerService::new(provers, keypair, vrf_evaluation_sender)
+        BlockProducerService::new(provers, key, vrf_evaluation_sender)
+    }
+
+    /// Adds an additional producer key.
+    pub fn add_key_backend(&mut self, key: Arc<dyn BlockProducerKeyBackend>) {
+        Arc::make_mut(&mut self.keys).insert(key.public_key().clone(), key);
+    }
+
+    pub fn key_backend(
+        &self,
+        producer: &AccountPublicKey,
+    ) -> Option<Arc<dyn BlockProducerKeyBackend>> {
+        self.keys.get(producer).cloned()
+    }
+
+    /// Key backend of the block creator of the block that is being proven.
+    pub fn key_backend_for_prove(
+        &self,
+        input: &ProverExtendBlockchainInputStableV2,
+    ) -> Option<Arc<dyn BlockProducerKeyBackend>> {
+        let producer = &input.next_state.body.consensus_state.block_creator;
+        self.key_backend(&producer.clone().into())
     }
 
-    pub fn keypair(&self) -> AccountSecretKey {
-        self.keypair.clone()
+    /// Secret key of the block creator of the block that is being proven,
+    /// if the key lives in the node process.
+    pub fn keypair_for_prove(
+        &self,
+        input: &ProverExtendBlockchainInputStableV2,
+    ) -> Option<AccountSecretKey> {
+        self.key_backend_for_prove(input)?.secret_key().cloned()
     }
 }
 
@@ -105,17 +139,35 @@ impl node::service::BlockProducerService for crate::NodeService {
             .clone()
     }
 
-    fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>) {
+    fn prove(
+        &mut self,
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+        dry_run: bool,
+    ) {
         if self.replayer.is_some() {
             return;
         }
         let provers = self.provers();
-        let keypair = self.block_producer.as_ref().unwrap().keypair();
-
         let tx = self.event_sender().clone();
+        let Some(key) = self
+            .block_producer
+            .as_ref()
+            .unwrap()
+            .key_backend_for_prove(&input)
+        else {
+            let block_creator = &input.next_state.body.consensus_state.block_creator;
+            let error = format!("block creator {block_creator} isn't one of our producer keys");
+            let _ = tx.send(BlockProducerEvent::BlockProve(block_hash, Err(error)).into());
+            return;
+        };
+
         thread::spawn(move || {
-            let res =
-                prove(provers, input.clone(), keypair, false).map_err(|err| format!("{err:?}"));
+            let res = if dry_run {
+                prove_dry_run(&*key, provers, input.clone())
+            } else {
+                key.prove_block(&provers, input.clone())
+            };
             if res.is_err() {
                 // IMPORTANT: Make sure that `input` here is a copy from before `prove` is called, we don't
                 // want to leak the private key.
@@ -128,6 +180,24 @@ impl node::service::BlockProducerService for crate::NodeService {
     }
 }
 
+/// Proves a block of a dry run without recording it in the block
+/// production guard, as it's never broadcast and recording it would
+/// stop us from proving the actual block for the slot.
+///
+/// Only possible if the secret key lives in the node process. Remote
+/// signers check and record every block they prove, so that no client
+/// can get them to prove two different blocks for the same slot.
+fn prove_dry_run(
+    key: &dyn BlockProducerKeyBackend,
+    provers: BlockProver,
+    input: Box<ProverExtendBlockchainInputStableV2>,
+) -> Result<Box<MinaBaseProofStableV2>, String> {
+    let secret_key = key
+        .secret_key()
+        .ok_or_else(|| "dry run block proving isn't supported with a remote signer".to_owned())?;
+    prove(provers, input, secret_key.clone(), false).map_err(|err| format!("{err:?}"))
+}
+
 fn dump_failed_block_proof_input(
     block_hash: StateHash,
     input: Box<ProverExtendBlockchainInputStableV2>,
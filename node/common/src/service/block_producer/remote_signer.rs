@@ -0,0 +1,651 @@
+//! Block producer key backend, which keeps the secret key in a separate
+//! signer process and talks to it over a Unix socket or HTTP.
+//!
+//! Over the Unix socket, each request is a single line of json sent over
+//! a new connection, followed by a single line of json response. Only
+//! the user running the signer may connect to the socket.
+//!
+//! Over HTTP, each request is a json `POST` over a new connection,
+//! authenticated with a bearer token shared by the node and the signer.
+//! The response is the json body of the `200` answer.
+//!
+//! Vrf outputs are requested for all delegators of a producer at once, so
+//! there is a single round trip per slot.
+
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ledger::{proofs::provers::BlockProver, AccountIndex};
+use mina_p2p_messages::v2::{
+    EpochSeed, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2,
+};
+use node::account::AccountPublicKey;
+use serde::{Deserialize, Serialize};
+use vrf::output::VrfOutput;
+
+use super::{BlockProducerKeyBackend, LocalKeyBackend};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RemoteSignerRequest {
+    PublicKey,
+    VrfOutputs {
+        global_slot: u32,
+        epoch_seed: EpochSeed,
+        delegator_indexes: Vec<AccountIndex>,
+    },
+    ProveBlock {
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RemoteSignerResponse {
+    PublicKey(AccountPublicKey),
+    VrfOutputs(Vec<VrfOutput>),
+    BlockProof(Box<MinaBaseProofStableV2>),
+    Error(String),
+}
+
+/// Where the signer process listens for requests.
+#[derive(Debug, Clone)]
+pub enum RemoteSignerAddr {
+    Unix(PathBuf),
+    Http { addr: SocketAddr, token: String },
+}
+
+impl fmt::Display for RemoteSignerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Http { addr, .. } => write!(f, "http://{addr}"),
+        }
+    }
+}
+
+/// How long the signer waits for a request, and either side waits for
+/// a quick response (public key, vrf outputs). Block proving isn't
+/// limited, as it takes a while.
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum length of a line of the HTTP head.
+const HTTP_MAX_LINE_LEN: u64 = 8 * 1024;
+/// Maximum number of lines of the HTTP head.
+const HTTP_MAX_HEAD_LINES: usize = 64;
+/// Maximum length of an HTTP body. Block prover inputs take a few MiB.
+const HTTP_MAX_BODY_LEN: usize = 64 * 1024 * 1024;
+/// How much of the body of a rejected HTTP request is read before the
+/// connection is closed.
+const HTTP_MAX_REJECTED_BODY_LEN: u64 = 64 * 1024;
+/// Maximum number of connections the signer serves at once. Connections
+/// above the limit are closed right away.
+const MAX_CONNECTIONS: usize = 8;
+
+/// Key backend talking to the signer process.
+pub struct RemoteSignerKeyBackend {
+    addr: RemoteSignerAddr,
+    public_key: AccountPublicKey,
+}
+
+impl RemoteSignerKeyBackend {
+    /// Connects to the signer and retrieves the producer's public key.
+    pub fn connect(addr: RemoteSignerAddr) -> Result<Self, String> {
+        match request(&addr, &RemoteSignerRequest::PublicKey, Some(IO_TIMEOUT))? {
+            RemoteSignerResponse::PublicKey(public_key) => Ok(Self { addr, public_key }),
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+}
+
+impl BlockProducerKeyBackend for RemoteSignerKeyBackend {
+    fn public_key(&self) -> &AccountPublicKey {
+        &self.public_key
+    }
+
+    fn vrf_outputs(
+        &self,
+        global_slot: u32,
+        epoch_seed: &EpochSeed,
+        delegator_indexes: &[AccountIndex],
+    ) -> Result<Vec<VrfOutput>, String> {
+        let req = RemoteSignerRequest::VrfOutputs {
+            global_slot,
+            epoch_seed: epoch_seed.clone(),
+            delegator_indexes: delegator_indexes.to_vec(),
+        };
+        match request(&self.addr, &req, Some(IO_TIMEOUT))? {
+            RemoteSignerResponse::VrfOutputs(outputs)
+                if outputs.len() == delegator_indexes.len() =>
+            {
+                Ok(outputs)
+            }
+            RemoteSignerResponse::VrfOutputs(outputs) => Err(format!(
+                "remote signer returned {} vrf outputs for {} delegators",
+                outputs.len(),
+                delegator_indexes.len()
+            )),
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+
+    fn prove_block(
+        &self,
+        _provers: &BlockProver,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    ) -> Result<Box<MinaBaseProofStableV2>, String> {
+        match request(&self.addr, &RemoteSignerRequest::ProveBlock { input }, None)? {
+            RemoteSignerResponse::BlockProof(proof) => Ok(proof),
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+}
+
+fn request(
+    addr: &RemoteSignerAddr,
+    req: &RemoteSignerRequest,
+    read_timeout: Option<Duration>,
+) -> Result<RemoteSignerResponse, String> {
+    let send = || -> io::Result<RemoteSignerResponse> {
+        match addr {
+            RemoteSignerAddr::Unix(socket_path) => {
+                let mut stream = UnixStream::connect(socket_path)?;
+                stream.set_write_timeout(Some(IO_TIMEOUT))?;
+                stream.set_read_timeout(read_timeout)?;
+                write_line(&mut stream, req)?;
+                read_line(&mut BufReader::new(stream))
+            }
+            RemoteSignerAddr::Http { addr, token } => {
+                let stream = TcpStream::connect_timeout(addr, IO_TIMEOUT)?;
+                stream.set_write_timeout(Some(IO_TIMEOUT))?;
+                stream.set_read_timeout(read_timeout)?;
+                http_request(stream, token, req)
+            }
+        }
+    };
+    send().map_err(|err| format!("remote signer request to {addr} failed: {err}"))
+}
+
+fn unexpected_response(resp: RemoteSignerResponse) -> String {
+    match resp {
+        RemoteSignerResponse::Error(err) => format!("remote signer error: {err}"),
+        resp => format!("unexpected remote signer response: {resp:?}"),
+    }
+}
+
+fn write_line<T: Serialize>(stream: &mut impl Write, value: &T) -> io::Result<()> {
+    let mut bytes = serde_json::to_vec(value)?;
+    bytes.push(b'\n');
+    stream.write_all(&bytes)?;
+    stream.flush()
+}
+
+fn read_line<T: for<'de> Deserialize<'de>>(reader: &mut impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+fn http_request(
+    mut stream: TcpStream,
+    token: &str,
+    req: &RemoteSignerRequest,
+) -> io::Result<RemoteSignerResponse> {
+    let body = serde_json::to_vec(req)?;
+    let head = format!(
+        "POST / HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {token}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        stream.peer_addr()?,
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&body)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let head = HttpHead::read(&mut reader)?;
+    let body = head.read_body(&mut reader)?;
+    match head.start_line.split(' ').nth(1) {
+        Some("200") => Ok(serde_json::from_slice(&body)?),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "{}: {}",
+                head.start_line,
+                String::from_utf8_lossy(&body).trim()
+            ),
+        )),
+    }
+}
+
+/// Start line and headers of an HTTP request or response.
+struct HttpHead {
+    start_line: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpHead {
+    fn read(reader: &mut impl BufRead) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_owned());
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader
+                .by_ref()
+                .take(HTTP_MAX_LINE_LEN)
+                .read_line(&mut line)?;
+            if !line.ends_with('\n') {
+                return Err(match line.len() as u64 {
+                    HTTP_MAX_LINE_LEN => invalid("http head line too long"),
+                    _ => io::ErrorKind::UnexpectedEof.into(),
+                });
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                break;
+            }
+            if lines.len() == HTTP_MAX_HEAD_LINES {
+                return Err(invalid("too many http headers"));
+            }
+            lines.push(line.to_owned());
+        }
+
+        let mut lines = lines.into_iter();
+        let start_line = lines.next().ok_or_else(|| invalid("empty http head"))?;
+        let headers = lines
+            .map(|line| {
+                let (name, value) = line
+                    .split_once(':')
+                    .ok_or_else(|| invalid("invalid http header"))?;
+                Ok((name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            start_line,
+            headers,
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn read_body(&self, reader: &mut impl Read) -> io::Result<Vec<u8>> {
+        let len = self
+            .header("content-length")
+            .and_then(|len| len.parse::<usize>().ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "missing http content length")
+            })?;
+        if len > HTTP_MAX_BODY_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "http body too long",
+            ));
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body)?;
+        Ok(body)
+    }
+}
+
+fn http_respond(stream: &mut TcpStream, status: &str, body: &[u8]) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Compares the tokens in constant time, so that the token can't be
+/// guessed from response times.
+fn token_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Binds the signer's Unix socket, accessible only to the user running
+/// the signer, replacing the socket left behind by the previous run.
+///
+/// The socket is bound at a temporary path and moved into place once its
+/// permissions are restricted, so that no one can connect in between.
+pub fn remote_signer_bind(socket_path: &Path) -> io::Result<UnixListener> {
+    let mut tmp_path = socket_path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_path);
+    let _ = std::fs::remove_file(&tmp_path);
+
+    let listener = UnixListener::bind(&tmp_path)?;
+    let res = std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))
+        .and_then(|_| std::fs::rename(&tmp_path, socket_path));
+    if let Err(err) = res {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    Ok(listener)
+}
+
+/// Serves signer requests from the node over the Unix socket, using the
+/// `backend` holding the secret key. Each connection is handled on its
+/// own thread, so that a stalled client doesn't block the others, up to
+/// [`MAX_CONNECTIONS`] at once.
+pub fn remote_signer_serve(
+    listener: UnixListener,
+    backend: LocalKeyBackend,
+    provers: BlockProver,
+) -> io::Result<()> {
+    let backend = Arc::new(backend);
+    serve_incoming(listener.incoming(), move |stream| {
+        serve_unix_connection(stream, |req| handle_request(&backend, &provers, req))
+    });
+    Ok(())
+}
+
+/// Serves signer requests from the node over HTTP, accepting only the
+/// requests authenticated with the `token`.
+pub fn remote_signer_serve_http(
+    listener: TcpListener,
+    token: String,
+    backend: LocalKeyBackend,
+    provers: BlockProver,
+) -> io::Result<()> {
+    let backend = Arc::new(backend);
+    serve_incoming(listener.incoming(), move |stream| {
+        serve_http_connection(stream, &token, |req| {
+            handle_request(&backend, &provers, req)
+        })
+    });
+    Ok(())
+}
+
+fn serve_incoming<S: Send + 'static>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    serve_connection: impl Fn(S) -> io::Result<()> + Send + Sync + 'static,
+) {
+    let serve_connection = Arc::new(serve_connection);
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in incoming {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                openmina_core::log::warn!(openmina_core::log::system_time();
+                    summary = "remote signer: failed to accept connection",
+                    error = err.to_string());
+                continue;
+            }
+        };
+        let Some(connection) = ConnectionSlot::acquire(&connections) else {
+            openmina_core::log::warn!(openmina_core::log::system_time();
+                summary = "remote signer: too many connections, closing the new one");
+            continue;
+        };
+        let serve_connection = serve_connection.clone();
+        openmina_core::thread::spawn(move || {
+            let _connection = connection;
+            if let Err(err) = serve_connection(stream) {
+                openmina_core::log::warn!(openmina_core::log::system_time();
+                    summary = "remote signer: request failed",
+                    error = err.to_string());
+            }
+        });
+    }
+}
+
+/// One of the [`MAX_CONNECTIONS`] connections being served, released
+/// when dropped.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(connections: &Arc<AtomicUsize>) -> Option<Self> {
+        connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()?;
+        Some(Self(connections.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn serve_unix_connection(
+    mut stream: UnixStream,
+    handle: impl FnOnce(RemoteSignerRequest) -> RemoteSignerResponse,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let req = read_line::<RemoteSignerRequest>(&mut BufReader::new(&stream))?;
+    write_line(&mut stream, &handle(req))
+}
+
+fn serve_http_connection(
+    mut stream: TcpStream,
+    token: &str,
+    handle: impl FnOnce(RemoteSignerRequest) -> RemoteSignerResponse,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let head = HttpHead::read(&mut reader)?;
+
+    // Rejected before the body is read, so that unauthenticated clients
+    // can't make the signer allocate it.
+    let rejection = if !head.start_line.starts_with("POST ") {
+        Some("405 Method Not Allowed")
+    } else if !head
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map_or(false, |value| token_eq(value, token))
+    {
+        Some("401 Unauthorized")
+    } else {
+        None
+    };
+    if let Some(status) = rejection {
+        // Closing the connection with unread data would reset it before
+        // the client reads the response, so a small body is drained.
+        let len = head
+            .header("content-length")
+            .and_then(|len| len.parse::<u64>().ok())
+            .unwrap_or(0);
+        let _ = io::copy(
+            &mut reader.take(len.min(HTTP_MAX_REJECTED_BODY_LEN)),
+            &mut io::sink(),
+        );
+        return http_respond(&mut stream, status, b"");
+    }
+
+    let body = head.read_body(&mut reader)?;
+    let req = match serde_json::from_slice::<RemoteSignerRequest>(&body) {
+        Ok(req) => req,
+        Err(err) => {
+            return http_respond(&mut stream, "400 Bad Request", err.to_string().as_bytes())
+        }
+    };
+    let resp = serde_json::to_vec(&handle(req))?;
+    http_respond(&mut stream, "200 OK", &resp)
+}
+
+fn handle_request(
+    backend: &LocalKeyBackend,
+    provers: &BlockProver,
+    req: RemoteSignerRequest,
+) -> RemoteSignerResponse {
+    match req {
+        RemoteSignerRequest::PublicKey => {
+            RemoteSignerResponse::PublicKey(backend.public_key().clone())
+        }
+        RemoteSignerRequest::VrfOutputs {
+            global_slot,
+            epoch_seed,
+            delegator_indexes,
+        } => backend
+            .vrf_outputs(global_slot, &epoch_seed, &delegator_indexes)
+            .map_or_else(
+                RemoteSignerResponse::Error,
+                RemoteSignerResponse::VrfOutputs,
+            ),
+        RemoteSignerRequest::ProveBlock { input } => {
+            openmina_core::log::info!(openmina_core::log::system_time();
+                summary = "remote signer: proving block",
+                global_slot = input
+                    .next_state
+                    .body
+                    .consensus_state
+                    .curr_global_slot_since_hard_fork
+                    .slot_number
+                    .as_u32());
+            backend.prove_block(provers, input).map_or_else(
+                RemoteSignerResponse::Error,
+                RemoteSignerResponse::BlockProof,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::FileTypeExt;
+
+    use node::account::AccountSecretKey;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "openmina-remote-signer-{name}-{}",
+            std::process::id()
+        ))
+    }
+
+    /// Serves a single connection of the `listener` with the handler
+    /// answering every request with the public key.
+    fn serve_one_http(listener: TcpListener, token: &'static str) -> AccountPublicKey {
+        let public_key = AccountSecretKey::rand().public_key();
+        let resp = RemoteSignerResponse::PublicKey(public_key.clone());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_http_connection(stream, token, |_| resp).unwrap();
+        });
+        public_key
+    }
+
+    #[test]
+    fn test_bind_socket_only_accessible_to_owner() {
+        let socket_path = temp_path("socket");
+        // Left behind by the previous run.
+        std::fs::write(&socket_path, b"").unwrap();
+
+        let listener = remote_signer_bind(&socket_path).unwrap();
+        let metadata = std::fs::metadata(&socket_path).unwrap();
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+        let public_key = AccountSecretKey::rand().public_key();
+        let resp = RemoteSignerResponse::PublicKey(public_key.clone());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_unix_connection(stream, |_| resp).unwrap();
+        });
+        let backend =
+            RemoteSignerKeyBackend::connect(RemoteSignerAddr::Unix(socket_path.clone())).unwrap();
+        assert_eq!(backend.public_key(), &public_key);
+        server.join().unwrap();
+        std::fs::remove_file(&socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_http_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let public_key = serve_one_http(listener, "secret");
+
+        let backend = RemoteSignerKeyBackend::connect(RemoteSignerAddr::Http {
+            addr,
+            token: "secret".to_owned(),
+        })
+        .unwrap();
+        assert_eq!(backend.public_key(), &public_key);
+    }
+
+    #[test]
+    fn test_http_request_with_wrong_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_one_http(listener, "secret");
+
+        let err = RemoteSignerKeyBackend::connect(RemoteSignerAddr::Http {
+            addr,
+            token: "secreT".to_owned(),
+        })
+        .err()
+        .unwrap();
+        assert!(err.contains("401 Unauthorized"), "{err}");
+    }
+
+    #[test]
+    fn test_http_request_rejected_before_reading_body() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_one_http(listener, "secret");
+
+        // Only the start of the body is sent, the rejection doesn't wait
+        // for the rest of it.
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let head = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {HTTP_MAX_BODY_LEN}\r\nConnection: close\r\n\r\n"
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream
+            .write_all(&[0; HTTP_MAX_REJECTED_BODY_LEN as usize])
+            .unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let head = HttpHead::read(&mut BufReader::new(stream)).unwrap();
+        assert_eq!(head.start_line, "HTTP/1.1 401 Unauthorized");
+    }
+
+    #[test]
+    fn test_connection_slots() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let slots = (0..MAX_CONNECTIONS)
+            .map(|_| ConnectionSlot::acquire(&connections).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ConnectionSlot::acquire(&connections).is_none());
+        drop(slots);
+        assert!(ConnectionSlot::acquire(&connections).is_some());
+    }
+
+    #[test]
+    fn test_token_eq() {
+        assert!(token_eq("secret", "secret"));
+        assert!(!token_eq("secret", "secreT"));
+        assert!(!token_eq("secret", "secret2"));
+        assert!(!token_eq("", "secret"));
+    }
+}
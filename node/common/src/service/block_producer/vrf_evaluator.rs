@@ -1,4 +1,3 @@
-use mina_signer::Keypair;
 use node::{
     block_producer::BlockProducerVrfEvaluatorEvent,
     block_producer::{
@@ -8,53 +7,212 @@ use node::{
     core::channels::mpsc::{UnboundedReceiver, UnboundedSender},
     event_source::Event,
 };
-use vrf::{VrfEvaluationInput, VrfEvaluationOutput};
+use vrf::VrfEvaluationOutput;
 
+use super::BlockProducerKeys;
 use crate::NodeService;
 
 pub fn vrf_evaluator(
     event_sender: UnboundedSender<Event>,
-    mut vrf_evaluation_receiver: UnboundedReceiver<VrfEvaluatorInput>,
-    keypair: Keypair,
+    mut vrf_evaluation_receiver: UnboundedReceiver<(VrfEvaluatorInput, BlockProducerKeys)>,
 ) {
-    while let Some(vrf_evaluator_input) = vrf_evaluation_receiver.blocking_recv() {
-        let mut vrf_result = VrfEvaluationOutput::SlotLost(vrf_evaluator_input.global_slot);
+    while let Some((vrf_evaluator_input, keys)) = vrf_evaluation_receiver.blocking_recv() {
+        let event = evaluate_slot(&vrf_evaluator_input, &keys);
+        // send the result back to the state machine
+        let _ = event_sender.send(BlockProducerEvent::VrfEvaluator(event).into());
+    }
+}
+
+/// Evaluates the slot for each of our producers, until one of them wins.
+fn evaluate_slot(
+    vrf_evaluator_input: &VrfEvaluatorInput,
+    keys: &BlockProducerKeys,
+) -> BlockProducerVrfEvaluatorEvent {
+    let global_slot = vrf_evaluator_input.global_slot;
+    let mut vrf_result = VrfEvaluationOutput::SlotLost(global_slot);
+    let mut errors = Vec::new();
+
+    'producers: for (producer, delegator_table) in vrf_evaluator_input.delegator_table.iter() {
+        let Some(key) = keys.get(producer) else {
+            continue;
+        };
 
-        for (index, (pub_key, stake)) in vrf_evaluator_input.delegator_table.iter() {
-            let vrf_input = VrfEvaluationInput::new(
-                keypair.clone(),
-                vrf_evaluator_input.epoch_seed.clone(),
+        let delegator_indexes = delegator_table.keys().copied().collect::<Vec<_>>();
+        let vrf_outputs = match key.vrf_outputs(
+            global_slot,
+            &vrf_evaluator_input.epoch_seed,
+            &delegator_indexes,
+        ) {
+            Ok(vrf_outputs) => vrf_outputs,
+            Err(error) => {
+                openmina_core::log::error!(openmina_core::log::system_time();
+                    summary = "failed to calculate vrf outputs",
+                    producer = producer.to_string(),
+                    global_slot = global_slot,
+                    error = error.clone());
+                errors.push(format!("{producer}: {error}"));
+                continue;
+            }
+        };
+
+        for ((index, (pub_key, stake)), vrf_output) in delegator_table.iter().zip(vrf_outputs) {
+            vrf_result = vrf::evaluate_vrf_output(
+                producer.clone(),
+                vrf_output,
                 pub_key.clone(),
-                vrf_evaluator_input.global_slot,
+                global_slot,
                 *index,
                 (*stake).into(),
                 vrf_evaluator_input.total_currency.into(),
             );
-            vrf_result = vrf::evaluate_vrf(vrf_input).unwrap();
 
             // the first delegate that won the slot
             if let VrfEvaluationOutput::SlotWon(_) = vrf_result {
-                break;
+                break 'producers;
+            }
+        }
+    }
+
+    match vrf_result {
+        // we might have lost the slot only because the key backend
+        // failed, so don't report it as lost.
+        VrfEvaluationOutput::SlotLost(_) if !errors.is_empty() => {
+            BlockProducerVrfEvaluatorEvent::EvaluationFailed {
+                global_slot,
+                staking_ledger_hash: vrf_evaluator_input.staking_ledger_hash.clone(),
+                error: errors.join("; "),
             }
         }
-        let vrf_result_with_hash = VrfEvaluationOutputWithHash::new(
+        vrf_result => BlockProducerVrfEvaluatorEvent::Evaluated(VrfEvaluationOutputWithHash::new(
             vrf_result,
             vrf_evaluator_input.staking_ledger_hash.clone(),
-        );
-        // send the result back to the state machine
-        let _ = event_sender.send(
-            BlockProducerEvent::VrfEvaluator(BlockProducerVrfEvaluatorEvent::Evaluated(
-                vrf_result_with_hash,
-            ))
-            .into(),
-        );
+        )),
     }
 }
 
 impl node::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorService for NodeService {
     fn evaluate(&mut self, data: VrfEvaluatorInput) {
         if let Some(bp) = self.block_producer.as_mut() {
-            let _ = bp.vrf_evaluation_sender.send(data);
+            let _ = bp.vrf_evaluation_sender.send((data, bp.keys.clone()));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+    use ledger::{proofs::provers::BlockProver, AccountIndex};
+    use mina_p2p_messages::v2::{
+        EpochSeed, LedgerHash, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2,
+    };
+    use node::account::{AccountPublicKey, AccountSecretKey};
+    use vrf::output::VrfOutput;
+
+    use super::*;
+    use crate::service::block_producer::{BlockProducerKeyBackend, LocalKeyBackend};
+
+    /// Key backend, which can't reach its signer.
+    struct FailingKeyBackend(AccountPublicKey);
+
+    impl BlockProducerKeyBackend for FailingKeyBackend {
+        fn public_key(&self) -> &AccountPublicKey {
+            &self.0
+        }
+
+        fn vrf_outputs(
+            &self,
+            _global_slot: u32,
+            _epoch_seed: &EpochSeed,
+            _delegator_indexes: &[AccountIndex],
+        ) -> Result<Vec<VrfOutput>, String> {
+            Err("signer unreachable".to_owned())
+        }
+
+        fn prove_block(
+            &self,
+            _provers: &BlockProver,
+            _input: Box<ProverExtendBlockchainInputStableV2>,
+        ) -> Result<Box<MinaBaseProofStableV2>, String> {
+            Err("signer unreachable".to_owned())
+        }
+    }
+
+    fn winning_key() -> Arc<dyn BlockProducerKeyBackend> {
+        let key =
+            AccountSecretKey::from_str("EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9")
+                .unwrap();
+        Arc::new(LocalKeyBackend::new(key))
+    }
+
+    fn failing_key() -> Arc<dyn BlockProducerKeyBackend> {
+        Arc::new(FailingKeyBackend(
+            AccountSecretKey::genesis_producer().public_key(),
+        ))
+    }
+
+    fn evaluate(
+        global_slot: u32,
+        keys: Vec<Arc<dyn BlockProducerKeyBackend>>,
+    ) -> BlockProducerVrfEvaluatorEvent {
+        let delegator_table = keys
+            .iter()
+            .map(|key| {
+                let delegators = [(
+                    AccountIndex(2),
+                    (key.public_key().clone(), 1_000_000_000_000_000),
+                )]
+                .into_iter()
+                .collect();
+                (key.public_key().clone(), delegators)
+            })
+            .collect();
+        let keys = keys
+            .into_iter()
+            .map(|key| (key.public_key().clone(), key))
+            .collect::<BTreeMap<_, _>>();
+        let input = VrfEvaluatorInput::new(
+            EpochSeed::from_str("2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA").unwrap(),
+            Arc::new(delegator_table),
+            global_slot,
+            6_000_000_000_001_000,
+            LedgerHash::from_str("jxTAZfKKDxoX4vtt68pQCWooXoVLjnfBpusaMwewrcZxsL3uWp6").unwrap(),
+        );
+        evaluate_slot(&input, &Arc::new(keys))
+    }
+
+    #[test]
+    fn test_won_slot_with_multiple_keys() {
+        let winner = winning_key();
+        let event = evaluate(6, vec![failing_key(), winner.clone()]);
+        let BlockProducerVrfEvaluatorEvent::Evaluated(output) = event else {
+            panic!("unexpected event: {event}");
+        };
+        let VrfEvaluationOutput::SlotWon(won_slot) = output.evaluation_result else {
+            panic!("slot should have been won");
+        };
+        assert_eq!(&won_slot.producer, winner.public_key());
+    }
+
+    #[test]
+    fn test_lost_slot() {
+        let event = evaluate(518, vec![winning_key()]);
+        let BlockProducerVrfEvaluatorEvent::Evaluated(output) = event else {
+            panic!("unexpected event: {event}");
+        };
+        assert_eq!(output.evaluation_result, VrfEvaluationOutput::SlotLost(518));
+    }
+
+    #[test]
+    fn test_failed_slot_isnt_reported_as_lost() {
+        let event = evaluate(518, vec![failing_key(), winning_key()]);
+        let BlockProducerVrfEvaluatorEvent::EvaluationFailed {
+            global_slot, error, ..
+        } = event
+        else {
+            panic!("unexpected event: {event}");
+        };
+        assert_eq!(global_slot, 518);
+        assert!(error.contains("signer unreachable"));
+    }
+}
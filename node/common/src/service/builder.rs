@@ -1,8 +1,12 @@
-use ledger::proofs::provers::BlockProver;
+use std::{path::PathBuf, sync::Arc};
+
+use ledger::proofs::provers::{BlockProver, TransactionProver, ZkappProver};
+#[cfg(not(target_arch = "wasm32"))]
+use node::p2p::service_impl::address_book::P2pAddressBookStorage;
 use node::{
     account::AccountSecretKey,
     core::channels::mpsc,
-    ledger::{LedgerCtx, LedgerManager},
+    ledger::{LedgerArchive, LedgerCtx, LedgerManager, LedgerStorage},
     p2p::{
         identity::SecretKey as P2pSecretKey,
         service_impl::{
@@ -10,6 +14,7 @@ use node::{
             TaskSpawner,
         },
     },
+    snark_pool::SnarkPoolStorage,
     stats::Stats,
 };
 use rand::{rngs::StdRng, SeedableRng};
@@ -23,7 +28,10 @@ use crate::{
     EventReceiver, EventSender, NodeService,
 };
 
-use super::block_producer::BlockProducerService;
+use super::{
+    block_producer::{BlockProducerKeyBackend, BlockProducerService, LocalKeyBackend},
+    snark_worker::{SnarkWorker, SnarkWorkerBackend},
+};
 
 pub struct NodeServiceCommonBuilder {
     rng_seed: [u8; 32],
@@ -34,7 +42,11 @@ pub struct NodeServiceCommonBuilder {
     event_receiver: EventReceiver,
     ledger_manager: Option<LedgerManager>,
     block_producer: Option<BlockProducerService>,
+    snark_worker: Option<Box<dyn SnarkWorkerBackend>>,
+    snark_pool_storage: Option<SnarkPoolStorage>,
     p2p: Option<P2pServiceCtx>,
+    #[cfg(not(target_arch = "wasm32"))]
+    p2p_address_book: Option<P2pAddressBookStorage>,
     gather_stats: bool,
     rpc: RpcService,
 }
@@ -57,7 +69,11 @@ impl NodeServiceCommonBuilder {
             event_receiver: event_receiver.into(),
             ledger_manager: None,
             block_producer: None,
+            snark_worker: None,
+            snark_pool_storage: None,
             p2p: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            p2p_address_book: None,
             rpc: RpcService::new(),
             gather_stats: false,
         }
@@ -72,8 +88,31 @@ impl NodeServiceCommonBuilder {
     }
 
     pub fn ledger_init(&mut self) -> &mut Self {
+        self.ledger_init_with(None, None, None)
+    }
+
+    /// Initialize ledger, persisting transition frontier into the
+    /// `storage` (if provided), so that it can be restored after restart,
+    /// and sending best chain blocks to the `archive` (if provided).
+    /// Snarked ledgers are stored on disk in `on_disk_ledgers_dir` (if
+    /// provided), instead of memory.
+    pub fn ledger_init_with(
+        &mut self,
+        storage: Option<LedgerStorage>,
+        archive: Option<LedgerArchive>,
+        on_disk_ledgers_dir: Option<PathBuf>,
+    ) -> &mut Self {
         let mut ctx = LedgerCtx::default();
         ctx.set_event_sender(self.event_sender.clone());
+        if let Some(storage) = storage {
+            ctx.set_storage(storage);
+        }
+        if let Some(archive) = archive {
+            ctx.set_archive(archive);
+        }
+        if let Some(dir) = on_disk_ledgers_dir {
+            ctx.set_on_disk_ledgers_dir(dir);
+        }
         self.ledger_manager = Some(LedgerManager::spawn(ctx));
         self
     }
@@ -82,15 +121,59 @@ impl NodeServiceCommonBuilder {
         &mut self,
         provers: BlockProver,
         keypair: AccountSecretKey,
+    ) -> &mut Self {
+        self.block_producer_init_with_backend(provers, Arc::new(LocalKeyBackend::new(keypair)))
+    }
+
+    /// Initializes the block producer with the producer key held by
+    /// the `key` backend, e.g. a remote signer.
+    pub fn block_producer_init_with_backend(
+        &mut self,
+        provers: BlockProver,
+        key: Arc<dyn BlockProducerKeyBackend>,
     ) -> &mut Self {
         self.block_producer = Some(BlockProducerService::start(
             provers,
             self.event_sender.clone(),
-            keypair,
+            key,
         ));
         self
     }
 
+    /// Adds an additional producer key to the block producer initialized
+    /// with [Self::block_producer_init].
+    pub fn block_producer_add_key(&mut self, keypair: AccountSecretKey) -> &mut Self {
+        self.block_producer_add_key_backend(Arc::new(LocalKeyBackend::new(keypair)))
+    }
+
+    pub fn block_producer_add_key_backend(
+        &mut self,
+        key: Arc<dyn BlockProducerKeyBackend>,
+    ) -> &mut Self {
+        if let Some(block_producer) = self.block_producer.as_mut() {
+            block_producer.add_key_backend(key);
+        }
+        self
+    }
+
+    pub fn snark_worker_init(
+        &mut self,
+        tx_prover: TransactionProver,
+        zkapp_prover: ZkappProver,
+    ) -> &mut Self {
+        self.snark_worker_init_with_backend(Box::new(SnarkWorker::new(tx_prover, zkapp_prover)))
+    }
+
+    /// Initializes the snark worker with the `worker` backend, e.g. an
+    /// external snark worker process.
+    pub fn snark_worker_init_with_backend(
+        &mut self,
+        worker: Box<dyn SnarkWorkerBackend>,
+    ) -> &mut Self {
+        self.snark_worker = Some(worker);
+        self
+    }
+
     pub fn p2p_init<S: TaskSpawner>(
         &mut self,
         secret_key: P2pSecretKey,
@@ -103,6 +186,19 @@ impl NodeServiceCommonBuilder {
         self
     }
 
+    /// Periodically persist p2p address book in the `storage`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn p2p_address_book(&mut self, storage: P2pAddressBookStorage) -> &mut Self {
+        self.p2p_address_book = Some(storage);
+        self
+    }
+
+    /// Periodically persist completed snarks of the pool in the `storage`.
+    pub fn snark_pool_storage(&mut self, storage: SnarkPoolStorage) -> &mut Self {
+        self.snark_pool_storage = Some(storage);
+        self
+    }
+
     pub fn gather_stats(&mut self) -> &mut Self {
         self.gather_stats = true;
         self
@@ -113,6 +209,11 @@ impl NodeServiceCommonBuilder {
             .ledger_manager
             .ok_or(NodeServiceCommonBuildError::LedgerNotInit)?;
         let p2p = self.p2p.ok_or(NodeServiceCommonBuildError::P2pNotInit)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let p2p = P2pServiceCtx {
+            address_book: self.p2p_address_book,
+            ..p2p
+        };
 
         Ok(NodeService {
             rng_seed: self.rng_seed,
@@ -129,6 +230,8 @@ impl NodeServiceCommonBuilder {
             event_receiver: self.event_receiver,
             ledger_manager,
             block_producer: self.block_producer,
+            snark_worker: self.snark_worker,
+            snark_pool_storage: self.snark_pool_storage,
             p2p,
             stats: self.gather_stats.then(Stats::new),
             rpc: self.rpc,
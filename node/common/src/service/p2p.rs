@@ -41,6 +41,11 @@ impl webrtc_with_libp2p::P2pServiceWebrtcWithLibp2p for NodeService {
     fn mio(&mut self) -> &mut mio::MioService {
         &mut self.p2p.mio
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn address_book_storage(&mut self) -> Option<&mut address_book::P2pAddressBookStorage> {
+        self.p2p.address_book.as_mut()
+    }
 }
 
 #[cfg(feature = "p2p-libp2p")]
@@ -5,16 +5,21 @@ pub mod state;
 pub mod stats;
 
 use node::rpc::{
-    RpcBestChainResponse, RpcBlockProducerStatsGetResponse, RpcConsensusConstantsGetResponse,
+    RpcBestChainResponse, RpcBlockProducerDryRunResponse, RpcBlockProducerScheduleGetResponse,
+    RpcBlockProducerStatsGetResponse, RpcConsensusConstantsGetResponse,
     RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse,
-    RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse,
-    RpcPeersGetResponse, RpcReadinessCheckResponse, RpcRequest, RpcStateGetError,
-    RpcStatusGetResponse, RpcTransactionInjectResponse, RpcTransactionPoolResponse,
-    RpcTransactionStatusGetResponse, RpcTransitionFrontierUserCommandsResponse,
+    RpcLedgerAccountProofGetResponse, RpcLedgerAccountsResponse, RpcLedgerExportResponse,
+    RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse, RpcP2pBanResponse,
+    RpcP2pBansGetResponse, RpcP2pUnbanResponse, RpcPeersGetResponse, RpcReadinessCheckResponse,
+    RpcRequest, RpcStateGetError, RpcStatusGetResponse, RpcSubscriptionEvent,
+    RpcTransactionInjectResponse, RpcTransactionPoolResponse, RpcTransactionSimulateResponse,
+    RpcTransactionStatusGetResponse, RpcTransitionFrontierTipsGetResponse,
+    RpcTransitionFrontierUserCommandsResponse, RpcWatchedAccountsAddResponse,
+    RpcWatchedAccountsGetResponse, RpcWatchedAccountsRemoveResponse,
 };
 use serde::{Deserialize, Serialize};
 
-use node::core::channels::{mpsc, oneshot};
+use node::core::channels::{broadcast, mpsc, oneshot};
 use node::core::requests::PendingRequests;
 use node::p2p::connection::P2pConnectionResponse;
 pub use node::rpc::{
@@ -46,6 +51,8 @@ pub struct RpcService {
 
     req_sender: mpsc::Sender<NodeRpcRequest>,
     req_receiver: mpsc::Receiver<NodeRpcRequest>,
+
+    events: broadcast::Sender<RpcSubscriptionEvent>,
 }
 
 impl Default for RpcService {
@@ -57,16 +64,18 @@ impl Default for RpcService {
 impl RpcService {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::channel(8);
+        let (events, _) = broadcast::channel(64);
         Self {
             pending: Default::default(),
             req_sender: tx,
             req_receiver: rx,
+            events,
         }
     }
 
     /// Channel for sending the rpc request to state machine.
     pub fn req_sender(&self) -> RpcSender {
-        RpcSender::new(self.req_sender.clone())
+        RpcSender::new(self.req_sender.clone(), self.events.clone())
     }
 
     /// Channel for receiving rpc requests in state machine.
@@ -216,11 +225,22 @@ impl node::rpc::RpcService for NodeService {
         respond_block_producer_stats_get,
         RpcBlockProducerStatsGetResponse
     );
+    rpc_service_impl!(
+        respond_block_producer_schedule_get,
+        RpcBlockProducerScheduleGetResponse
+    );
+    rpc_service_impl!(
+        respond_block_producer_dry_run,
+        RpcBlockProducerDryRunResponse
+    );
     rpc_service_impl!(
         respond_message_progress_stats_get,
         RpcMessageProgressResponse
     );
     rpc_service_impl!(respond_peers_get, RpcPeersGetResponse);
+    rpc_service_impl!(respond_p2p_bans_get, RpcP2pBansGetResponse);
+    rpc_service_impl!(respond_p2p_ban, RpcP2pBanResponse);
+    rpc_service_impl!(respond_p2p_unban, RpcP2pUnbanResponse);
     rpc_service_impl!(
         respond_p2p_connection_outgoing,
         RpcP2pConnectionOutgoingResponse
@@ -268,6 +288,7 @@ impl node::rpc::RpcService for NodeService {
         respond_snarker_job_spec,
         node::rpc::RpcSnarkerJobSpecResponse
     );
+    rpc_service_impl!(respond_snarker_jobs, node::rpc::RpcSnarkerJobsResponse);
     rpc_service_impl!(
         respond_snarker_workers,
         node::rpc::RpcSnarkerWorkersResponse
@@ -289,17 +310,38 @@ impl node::rpc::RpcService for NodeService {
     rpc_service_impl!(respond_transaction_pool, RpcTransactionPoolResponse);
     rpc_service_impl!(respond_ledger_slim_accounts, RpcLedgerSlimAccountsResponse);
     rpc_service_impl!(respond_ledger_accounts, RpcLedgerAccountsResponse);
+    rpc_service_impl!(
+        respond_ledger_account_proof_get,
+        RpcLedgerAccountProofGetResponse
+    );
     rpc_service_impl!(respond_transaction_inject, RpcTransactionInjectResponse);
     rpc_service_impl!(
         respond_transition_frontier_commands,
         RpcTransitionFrontierUserCommandsResponse
     );
+    rpc_service_impl!(
+        respond_transition_frontier_tips,
+        RpcTransitionFrontierTipsGetResponse
+    );
     rpc_service_impl!(respond_best_chain, RpcBestChainResponse);
     rpc_service_impl!(
         respond_consensus_constants,
         RpcConsensusConstantsGetResponse
     );
     rpc_service_impl!(respond_transaction_status, RpcTransactionStatusGetResponse);
+    rpc_service_impl!(respond_ledger_export, RpcLedgerExportResponse);
+    rpc_service_impl!(respond_transaction_simulate, RpcTransactionSimulateResponse);
+    rpc_service_impl!(respond_watched_accounts_add, RpcWatchedAccountsAddResponse);
+    rpc_service_impl!(
+        respond_watched_accounts_remove,
+        RpcWatchedAccountsRemoveResponse
+    );
+    rpc_service_impl!(respond_watched_accounts_get, RpcWatchedAccountsGetResponse);
+
+    fn publish_subscription_event(&mut self, event: RpcSubscriptionEvent) {
+        // Fails only if there are no subscribers.
+        let _ = self.rpc.events.send(event);
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,7 @@ use node::{
     ledger::LedgerManager,
     p2p::identity::SecretKey as P2pSecretKey,
     service::Recorder,
+    snark_pool::SnarkPoolStorage,
     stats::Stats,
     transition_frontier::genesis::GenesisConfig,
 };
@@ -22,6 +23,7 @@ use super::{
     p2p::webrtc_with_libp2p::P2pServiceCtx,
     replay::ReplayerState,
     rpc::{RpcSender, RpcService},
+    snark_worker::SnarkWorkerBackend,
     EventReceiver, EventSender,
 };
 
@@ -38,6 +40,8 @@ pub struct NodeService {
 
     pub ledger_manager: LedgerManager,
     pub block_producer: Option<BlockProducerService>,
+    pub snark_worker: Option<Box<dyn SnarkWorkerBackend>>,
+    pub snark_pool_storage: Option<SnarkPoolStorage>,
     pub p2p: P2pServiceCtx,
 
     pub stats: Option<Stats>,
@@ -107,6 +111,8 @@ impl NodeService {
             event_receiver: mpsc::unbounded_channel().1.into(),
             ledger_manager: LedgerManager::spawn(Default::default()),
             block_producer: None,
+            snark_worker: None,
+            snark_pool_storage: None,
             p2p: P2pServiceCtx::mocked(p2p_sec_key),
             stats: Some(Stats::new()),
             rpc: RpcService::new(),
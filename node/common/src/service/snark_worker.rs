@@ -1,40 +1,370 @@
-use mina_p2p_messages::v2;
-use node::external_snark_worker::{ExternalSnarkWorkerError, SnarkWorkSpec};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
-use crate::NodeService;
+use ledger::proofs::{
+    generate_merge_proof, generate_tx_proof, generate_zkapp_proof,
+    merge::MergeParams,
+    provers::{TransactionProver, ZkappProver},
+    transaction::{ProofError, TransactionParams},
+    zkapp::{LedgerProof, ZkappParams},
+};
+use ledger::scan_state::{
+    currency::Fee,
+    scan_state::transaction_snark::{SokMessage, Statement},
+};
+use mina_p2p_messages::v2::{
+    self, LedgerProofProdStableV2, SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Instances,
+    SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single, TransactionSnarkWorkTStableV2Proofs,
+};
+use node::external_snark_worker::{
+    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerWorkError, SnarkWorkSpec,
+};
 
-pub struct SnarkWorker {}
+use crate::{EventSender, NodeService};
+
+/// Proves the snark work jobs of the snarker, reporting the progress as
+/// [`ExternalSnarkWorkerEvent`]s sent with the `event_sender`.
+pub trait SnarkWorkerBackend: Send {
+    fn start(
+        &mut self,
+        public_key: v2::NonZeroCurvePoint,
+        fee: v2::CurrencyFeeStableV1,
+        event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError>;
+
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: v2::CurrencyFeeStableV1,
+        event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError>;
+
+    fn cancel(&mut self, event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError>;
+
+    fn kill(&mut self, event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError>;
+}
+
+/// In-process snark worker, proving [`SnarkWorkSpec`] jobs with the rust
+/// provers from the `ledger` crate instead of an external `mina` process.
+pub struct SnarkWorker {
+    tx_prover: TransactionProver,
+    zkapp_prover: ZkappProver,
+    pool: Arc<rayon::ThreadPool>,
+    /// Set when the worker is started, `None` means it isn't running.
+    message: Option<SokMessage>,
+    jobs: SnarkWorkJobs,
+}
+
+/// Incremented on each submit/cancel/kill, so that only the last
+/// submitted job is proven and reported.
+#[derive(Debug, Default)]
+struct SnarkWorkJobs(Arc<AtomicU64>);
+
+impl SnarkWorkJobs {
+    /// Creates a new job, cancelling the previous ones.
+    fn next(&self) -> SnarkWorkJob {
+        SnarkWorkJob {
+            generation: self.0.fetch_add(1, Ordering::SeqCst) + 1,
+            job_generation: self.0.clone(),
+        }
+    }
+
+    fn cancel(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Handle of the submitted job, used to check whether it was cancelled.
+///
+/// Proving can't be interrupted once it started, so cancellation only
+/// takes effect before the job (or one of its instances) starts to be
+/// proven. A proof already in progress keeps the threads busy until it
+/// finishes, then its result is discarded.
+#[derive(Debug)]
+struct SnarkWorkJob {
+    generation: u64,
+    job_generation: Arc<AtomicU64>,
+}
+
+impl SnarkWorkJob {
+    fn is_cancelled(&self) -> bool {
+        self.job_generation.load(Ordering::SeqCst) != self.generation
+    }
+}
+
+impl SnarkWorker {
+    pub fn new(tx_prover: TransactionProver, zkapp_prover: ZkappProver) -> Self {
+        let num_threads = std::thread::available_parallelism()
+            .map_or(2, |v| v.get())
+            .max(2);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("openmina_snark_worker_{i}"))
+            .build()
+            .expect("failed to build snark worker thread pool");
+        Self {
+            tx_prover,
+            zkapp_prover,
+            pool: Arc::new(pool),
+            message: None,
+            jobs: Default::default(),
+        }
+    }
+}
+
+impl SnarkWorkerBackend for SnarkWorker {
+    fn start(
+        &mut self,
+        public_key: v2::NonZeroCurvePoint,
+        fee: v2::CurrencyFeeStableV1,
+        event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError> {
+        let prover = (&public_key)
+            .try_into()
+            .map_err(|_| ExternalSnarkWorkerError::Error("invalid public key".to_owned()))?;
+        self.message = Some(SokMessage {
+            fee: Fee::from_u64(fee.as_u64()),
+            prover,
+        });
+        let _ = event_sender.send(ExternalSnarkWorkerEvent::Started.into());
+        Ok(())
+    }
+
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: v2::CurrencyFeeStableV1,
+        event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError> {
+        let message = SokMessage {
+            fee: Fee::from_u64(fee.as_u64()),
+            ..self
+                .message
+                .clone()
+                .ok_or(ExternalSnarkWorkerError::NotRunning)?
+        };
+        let job = self.jobs.next();
+        let tx_prover = self.tx_prover.clone();
+        let zkapp_prover = self.zkapp_prover.clone();
+        let event_sender = event_sender.clone();
+
+        self.pool.spawn(move || {
+            let is_cancelled = || job.is_cancelled();
+            let res = prove_spec(&tx_prover, &zkapp_prover, spec, &message, &is_cancelled);
+            if job.is_cancelled() {
+                // job was cancelled while we were proving it.
+                return;
+            }
+            let event = match res {
+                Ok(Some(proofs)) => ExternalSnarkWorkerEvent::WorkResult(Arc::new(proofs)),
+                Ok(None) => return,
+                Err(err) => ExternalSnarkWorkerWorkError::Error(format!("{err:?}")).into(),
+            };
+            let _ = event_sender.send(event.into());
+        });
+        Ok(())
+    }
+
+    fn cancel(&mut self, event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError> {
+        if self.message.is_none() {
+            return Err(ExternalSnarkWorkerError::NotRunning);
+        }
+        // Proving which already started can't be interrupted, so we just
+        // make sure that the result of the current job is never reported.
+        self.jobs.cancel();
+        let _ = event_sender.send(ExternalSnarkWorkerEvent::WorkCancelled.into());
+        Ok(())
+    }
+
+    fn kill(&mut self, event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError> {
+        self.message
+            .take()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?;
+        self.jobs.cancel();
+        let _ = event_sender.send(ExternalSnarkWorkerEvent::Killed.into());
+        Ok(())
+    }
+}
+
+/// Proves every instance of the `spec`. If the spec has two instances,
+/// they are proven in parallel.
+///
+/// `is_cancelled` is checked before each instance starts to be proven,
+/// returns `None` if the job was cancelled.
+pub fn prove_spec(
+    tx_prover: &TransactionProver,
+    zkapp_prover: &ZkappProver,
+    spec: SnarkWorkSpec,
+    message: &SokMessage,
+    is_cancelled: &(dyn Fn() -> bool + Sync),
+) -> Result<Option<TransactionSnarkWorkTStableV2Proofs>, ProofError> {
+    let prove = |single| match is_cancelled() {
+        true => Ok(None),
+        false => prove_single(tx_prover, zkapp_prover, single, message).map(Some),
+    };
+    match spec {
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Instances::One(single) => {
+            let proof = prove(single)?;
+            Ok(proof.map(TransactionSnarkWorkTStableV2Proofs::One))
+        }
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Instances::Two((first, second)) => {
+            let (first, second) = rayon::join(|| prove(first), || prove(second));
+            Ok(first?
+                .zip(second?)
+                .map(TransactionSnarkWorkTStableV2Proofs::Two))
+        }
+    }
+}
+
+fn prove_single(
+    tx_prover: &TransactionProver,
+    zkapp_prover: &ZkappProver,
+    single: SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single,
+    message: &SokMessage,
+) -> Result<LedgerProofProdStableV2, ProofError> {
+    let sok_digest = message.digest();
+    match single {
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Transition(
+            statement,
+            tx_witness,
+        ) => {
+            let is_zkapp = matches!(
+                &tx_witness.transaction,
+                v2::MinaTransactionTransactionStableV2::Command(cmd)
+                    if matches!(&**cmd, v2::MinaBaseUserCommandStableV2::ZkappCommand(_))
+            );
+            if is_zkapp {
+                let proof = generate_zkapp_proof(ZkappParams {
+                    statement: &statement,
+                    tx_witness: &tx_witness,
+                    message,
+                    step_opt_signed_opt_signed_prover: &zkapp_prover
+                        .step_opt_signed_opt_signed_prover,
+                    step_opt_signed_prover: &zkapp_prover.step_opt_signed_prover,
+                    step_proof_prover: &zkapp_prover.step_proof_prover,
+                    merge_step_prover: &zkapp_prover.merge_step_prover,
+                    tx_wrap_prover: &zkapp_prover.tx_wrap_prover,
+                    opt_signed_path: None,
+                    proved_path: None,
+                })?;
+                return Ok((&proof).into());
+            }
+
+            let proof = generate_tx_proof(TransactionParams {
+                statement: &statement,
+                tx_witness: &tx_witness,
+                message,
+                tx_step_prover: &tx_prover.tx_step_prover,
+                tx_wrap_prover: &tx_prover.tx_wrap_prover,
+                only_verify_constraints: false,
+                expected_step_proof: None,
+                ocaml_wrap_witness: None,
+            })?;
+            let statement: Statement<()> = (&*statement).try_into()?;
+            let proof = LedgerProof {
+                statement: statement.with_digest(sok_digest),
+                proof,
+            };
+            Ok((&proof).into())
+        }
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Merge(merge) => {
+            let (statement, p1, p2) = *merge;
+            let statement: Statement<()> = (&*statement).try_into()?;
+            let proof = generate_merge_proof(MergeParams {
+                statement: statement.clone(),
+                proofs: &[p1, p2],
+                message,
+                step_prover: &tx_prover.merge_step_prover,
+                wrap_prover: &tx_prover.tx_wrap_prover,
+                only_verify_constraints: false,
+                expected_step_proof: None,
+                ocaml_wrap_witness: None,
+            })?;
+            let proof = LedgerProof {
+                statement: statement.with_digest(sok_digest),
+                proof,
+            };
+            Ok((&proof).into())
+        }
+    }
+}
 
 impl node::service::ExternalSnarkWorkerService for NodeService {
     fn start(
         &mut self,
-        _public_key: v2::NonZeroCurvePoint,
-        _fee: v2::CurrencyFeeStableV1,
+        public_key: v2::NonZeroCurvePoint,
+        fee: v2::CurrencyFeeStableV1,
     ) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .start(public_key, fee, &self.event_sender)
     }
 
     fn kill(&mut self) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .kill(&self.event_sender)
     }
 
-    fn submit(&mut self, _spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError> {
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: v2::CurrencyFeeStableV1,
+    ) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .submit(spec, fee, &self.event_sender)
     }
 
     fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .cancel(&self.event_sender)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_job_cancels_previous() {
+        let jobs = SnarkWorkJobs::default();
+        let first = jobs.next();
+        assert!(!first.is_cancelled());
+
+        let second = jobs.next();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+    }
+
+    #[test]
+    fn cancel() {
+        let jobs = SnarkWorkJobs::default();
+        let job = jobs.next();
+        jobs.cancel();
+        assert!(job.is_cancelled());
+
+        // Jobs submitted after the cancel aren't affected.
+        let job = jobs.next();
+        assert!(!job.is_cancelled());
     }
 }
@@ -8,11 +8,13 @@ use node::{
         snark::{Snark, SnarkJobId},
         thread,
     },
+    event_source::Event,
     snark::{
         block_verify::{SnarkBlockVerifyError, SnarkBlockVerifyId, VerifiableBlockWithHash},
         work_verify::{SnarkWorkVerifyError, SnarkWorkVerifyId},
         BlockVerifier, SnarkEvent, TransactionVerifier, VerifierSRS,
     },
+    snark_pool::SnarkPoolState,
 };
 use rand::prelude::*;
 
@@ -126,4 +128,34 @@ impl node::service::SnarkPoolService for NodeService {
             .cloned()
             .collect()
     }
+
+    fn snark_pool_save(&mut self, pool: &SnarkPoolState) {
+        if self.replayer.is_some() {
+            return;
+        }
+        if let Some(storage) = self.snark_pool_storage.as_mut() {
+            storage.save_if_due(pool);
+        }
+    }
+
+    fn snark_pool_restore(&mut self) {
+        if self.replayer.is_some() {
+            return;
+        }
+        let Some(storage) = self.snark_pool_storage.as_ref() else {
+            return;
+        };
+        match storage.load() {
+            Ok(snarks) if snarks.is_empty() => {}
+            Ok(snarks) => {
+                let _ = self.event_sender.send(Event::SnarkPoolRestored(snarks));
+            }
+            Err(err) => {
+                openmina_core::log::warn!(openmina_core::log::system_time();
+                    summary = "failed to load snark pool",
+                    error = display(&err)
+                );
+            }
+        }
+    }
 }
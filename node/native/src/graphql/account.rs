@@ -3,10 +3,14 @@ use ledger::FpExt;
 use mina_p2p_messages::{
     string::{TokenSymbol, ZkAppUri},
     v2::{
-        MinaBaseAccountUpdateUpdateTimingInfoStableV1, MinaBaseVerificationKeyWireStableV1,
-        ReceiptChainHash, TokenIdKeyHash,
+        MinaBaseAccountUpdateUpdateTimingInfoStableV1, MinaBaseTransactionStatusStableV2,
+        MinaBaseVerificationKeyWireStableV1, ReceiptChainHash, TokenIdKeyHash,
     },
 };
+use node::rpc::{
+    RpcLedgerAccountProof, RpcMerklePathItem, RpcWatchedAccount, RpcWatchedAccountChange,
+};
+use node::watched_accounts::{Transaction, WatchedAccountBlockState};
 
 use super::ConversionError;
 
@@ -241,3 +245,154 @@ impl TryFrom<ledger::Account> for GraphQLAccount {
         })
     }
 }
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Account with the Merkle path proving it against the ledger hash")]
+pub struct GraphQLAccountProof {
+    pub ledger_hash: String,
+    /// Position of the account in the ledger
+    pub index: String,
+    pub account: GraphQLAccount,
+    /// Sibling hashes, from the account up to the root
+    pub merkle_path: Vec<GraphQLMerklePathItem>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLMerklePathItem {
+    /// Hash of the right sibling, set if the node on the path is the left child
+    pub left: Option<String>,
+    /// Hash of the left sibling, set if the node on the path is the right child
+    pub right: Option<String>,
+}
+
+impl TryFrom<RpcLedgerAccountProof> for GraphQLAccountProof {
+    type Error = ConversionError;
+
+    fn try_from(value: RpcLedgerAccountProof) -> Result<Self, Self::Error> {
+        Ok(Self {
+            ledger_hash: value.ledger_hash.to_string(),
+            index: value.index.to_string(),
+            account: value.account.try_into()?,
+            merkle_path: value.merkle_path.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl From<RpcMerklePathItem> for GraphQLMerklePathItem {
+    fn from(value: RpcMerklePathItem) -> Self {
+        match value {
+            RpcMerklePathItem::Left(hash) => Self {
+                left: Some(hash.to_string()),
+                right: None,
+            },
+            RpcMerklePathItem::Right(hash) => Self {
+                left: None,
+                right: Some(hash.to_string()),
+            },
+        }
+    }
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Account watched by the node")]
+pub struct GraphQLWatchedAccount {
+    pub public_key: String,
+    /// Latest known state of the account, `null` if not fetched yet or if
+    /// the account doesn't exist
+    pub account: Option<GraphQLAccount>,
+    /// Blocks with transactions affecting the account since it started
+    /// being watched, from oldest to newest
+    pub blocks: Vec<GraphQLWatchedAccountBlock>,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Change of an account watched by the node")]
+pub struct GraphQLWatchedAccountChange {
+    pub public_key: String,
+    /// Latest known state of the account after the change
+    pub account: Option<GraphQLAccount>,
+    /// Block with transactions affecting the account that was added or
+    /// updated, if any
+    pub block: Option<GraphQLWatchedAccountBlock>,
+    /// State hashes of the blocks which are no longer part of the best
+    /// chain
+    pub orphaned_blocks: Vec<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLWatchedAccountBlock {
+    pub state_hash: String,
+    pub height: i32,
+    pub transactions: Vec<GraphQLWatchedAccountTransaction>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLWatchedAccountTransaction {
+    pub hash: Option<String>,
+    pub failed: bool,
+}
+
+impl TryFrom<RpcWatchedAccount> for GraphQLWatchedAccount {
+    type Error = ConversionError;
+
+    fn try_from(value: RpcWatchedAccount) -> Result<Self, Self::Error> {
+        let account = value
+            .account
+            .map(|account| {
+                ledger::Account::try_from(account).map_err(|_| ConversionError::InvalidBigInt)
+            })
+            .transpose()?
+            .map(GraphQLAccount::try_from)
+            .transpose()?;
+        Ok(Self {
+            public_key: value.public_key.to_string(),
+            account,
+            blocks: value.blocks.iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl TryFrom<RpcWatchedAccountChange> for GraphQLWatchedAccountChange {
+    type Error = ConversionError;
+
+    fn try_from(value: RpcWatchedAccountChange) -> Result<Self, Self::Error> {
+        let account = value
+            .account
+            .map(|account| {
+                ledger::Account::try_from(account).map_err(|_| ConversionError::InvalidBigInt)
+            })
+            .transpose()?
+            .map(GraphQLAccount::try_from)
+            .transpose()?;
+        Ok(Self {
+            public_key: value.public_key.to_string(),
+            account,
+            block: value.block.as_ref().map(Into::into),
+            orphaned_blocks: value
+                .orphaned_blocks
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        })
+    }
+}
+
+impl From<&WatchedAccountBlockState> for GraphQLWatchedAccountBlock {
+    fn from(value: &WatchedAccountBlockState) -> Self {
+        let block = value.block();
+        Self {
+            state_hash: block.hash.to_string(),
+            height: block.level as i32,
+            transactions: value.transactions().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&Transaction> for GraphQLWatchedAccountTransaction {
+    fn from(value: &Transaction) -> Self {
+        Self {
+            hash: value.hash.as_ref().map(ToString::to_string),
+            failed: matches!(value.status, MinaBaseTransactionStatusStableV2::Failed(_)),
+        }
+    }
+}
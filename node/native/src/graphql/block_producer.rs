@@ -0,0 +1,103 @@
+use juniper::GraphQLObject;
+use node::rpc::{RpcBlockProducerDryRun, RpcBlockProducerSchedule, RpcBlockProducerScheduleEpoch};
+use node::stats::block_producer::BlockProductionAttemptWonSlot;
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Won slots of the block producer in the current and the next epoch")]
+pub struct GraphQLBlockProductionSchedule {
+    pub current_global_slot: Option<i32>,
+    pub epochs: Vec<GraphQLBlockProductionScheduleEpoch>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLBlockProductionScheduleEpoch {
+    pub epoch: i32,
+    pub start_slot: i32,
+    pub end_slot: i32,
+    /// Whether the vrf evaluation for the epoch is finished.
+    pub evaluated: bool,
+    pub won_slots: Vec<GraphQLWonSlot>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLWonSlot {
+    pub global_slot: i32,
+    pub epoch: i32,
+    /// Slot start time, in milliseconds since the unix epoch.
+    pub slot_time: String,
+    pub producer: String,
+    pub delegator: String,
+    pub delegator_index: String,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Block produced on demand, without broadcasting it")]
+pub struct GraphQLBlockProductionDryRun {
+    pub won_slot: GraphQLWonSlot,
+    pub state_hash: String,
+    pub previous_state_hash: String,
+    pub block_height: i32,
+    pub user_commands: i32,
+    pub completed_works: i32,
+    pub proven: bool,
+    /// Time it took to produce the block, in milliseconds.
+    pub duration_ms: String,
+}
+
+impl From<RpcBlockProducerSchedule> for GraphQLBlockProductionSchedule {
+    fn from(value: RpcBlockProducerSchedule) -> Self {
+        Self {
+            current_global_slot: value.current_global_slot.map(|slot| slot as i32),
+            epochs: value.epochs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<RpcBlockProducerScheduleEpoch> for GraphQLBlockProductionScheduleEpoch {
+    fn from(value: RpcBlockProducerScheduleEpoch) -> Self {
+        Self {
+            epoch: value.epoch as i32,
+            start_slot: value.start_slot as i32,
+            end_slot: value.end_slot as i32,
+            evaluated: value.evaluated,
+            won_slots: value.won_slots.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<BlockProductionAttemptWonSlot> for GraphQLWonSlot {
+    fn from(value: BlockProductionAttemptWonSlot) -> Self {
+        Self {
+            global_slot: value.global_slot as i32,
+            epoch: value.epoch as i32,
+            slot_time: (u64::from(value.slot_time) / 1_000_000).to_string(),
+            producer: value.producer.to_string(),
+            delegator: value.delegator.0.to_string(),
+            delegator_index: value.delegator.1 .0.to_string(),
+        }
+    }
+}
+
+impl From<RpcBlockProducerDryRun> for GraphQLBlockProductionDryRun {
+    fn from(value: RpcBlockProducerDryRun) -> Self {
+        let duration = value.finished_at.checked_sub(value.started_at);
+        Self {
+            won_slot: value.won_slot.into(),
+            state_hash: value.block_hash.to_string(),
+            previous_state_hash: value.block.protocol_state.previous_state_hash.to_string(),
+            block_height: value
+                .block
+                .protocol_state
+                .body
+                .consensus_state
+                .blockchain_length
+                .as_u32() as i32,
+            user_commands: value.block.body.commands_iter().count() as i32,
+            completed_works: value.block.body.completed_works_count() as i32,
+            proven: value.proof.is_some(),
+            duration_ms: duration
+                .map_or(0, |duration| duration.as_millis())
+                .to_string(),
+        }
+    }
+}
@@ -1,19 +1,40 @@
+use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use juniper::futures::{stream, Stream};
 use juniper::{graphql_value, FieldError};
-use juniper::{EmptySubscription, GraphQLEnum, RootNode};
+use juniper::{GraphQLEnum, RootNode};
+use juniper_graphql_ws::ConnectionConfig;
+use ledger::scan_state::currency::Nonce;
 use ledger::Account;
+use mina_p2p_messages::v2::LedgerHash;
 use mina_p2p_messages::v2::MinaBaseSignedCommandStableV2;
 use mina_p2p_messages::v2::MinaBaseUserCommandStableV2;
 use mina_p2p_messages::v2::MinaBaseZkappCommandTStableV1WireStableV1;
 use mina_p2p_messages::v2::TokenIdKeyHash;
+use mina_signer::CompressedPubKey;
+use node::core::channels::broadcast;
+use node::rpc::RpcBlockProducerDryRunQuery;
+use node::rpc::RpcBlockProducerDryRunResponse;
+use node::rpc::RpcBlockProducerScheduleGetResponse;
+use node::rpc::RpcLedgerAccountProofGetResponse;
+use node::rpc::RpcLedgerAccountProofQuery;
+use node::rpc::RpcLedgerAccountsResponse;
+use node::rpc::RpcSubscriptionEvent;
 use node::rpc::RpcTransactionInjectResponse;
+use node::rpc::RpcTransactionInjectSuccess;
 use node::rpc::RpcTransactionInjectedCommand;
+use node::rpc::RpcTransactionPoolResponse;
+use node::rpc::RpcTransactionSimulateQuery;
+use node::rpc::RpcTransactionSimulateResponse;
 use node::rpc::RpcTransactionStatusGetResponse;
+use node::rpc::RpcWatchedAccountsAddResponse;
+use node::rpc::RpcWatchedAccountsGetResponse;
+use node::rpc::RpcWatchedAccountsRemoveResponse;
 use node::{
     account::AccountPublicKey,
     rpc::{AccountQuery, RpcRequest, RpcSyncStatsGetResponse, SyncStatsQuery},
-    stats::sync::SyncKind,
 };
 use openmina_core::block::AppliedBlock;
 use openmina_core::consensus::ConsensusConstants;
@@ -23,7 +44,9 @@ use warp::{Filter, Rejection, Reply};
 
 pub mod account;
 pub mod block;
+pub mod block_producer;
 pub mod constants;
+pub mod user_command;
 pub mod zkapp;
 
 #[derive(Debug, thiserror::Error)]
@@ -66,6 +89,7 @@ pub enum ConversionError {
     Custom(String),
 }
 
+#[derive(Clone)]
 struct Context(RpcSender);
 
 impl juniper::Context for Context {}
@@ -81,6 +105,22 @@ enum SyncStatus {
     CATCHUP,
 }
 
+impl From<node::stats::sync::SyncStatus> for SyncStatus {
+    fn from(value: node::stats::sync::SyncStatus) -> Self {
+        match value {
+            node::stats::sync::SyncStatus::Bootstrap => Self::BOOTSTRAP,
+            node::stats::sync::SyncStatus::Catchup => Self::CATCHUP,
+            node::stats::sync::SyncStatus::Synced => Self::SYNCED,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, GraphQLEnum)]
+#[allow(clippy::upper_case_acronyms)]
+enum ChainReorganizationStatus {
+    CHANGED,
+}
+
 #[derive(Clone, Debug)]
 struct ProtocolState {
     consensus_state: ConsensusState,
@@ -166,6 +206,50 @@ impl Query {
             .try_into()?)
     }
 
+    /// Account with the Merkle path, proving it against the best tip
+    /// staged ledger, or the given snarked ledger.
+    async fn account_proof(
+        public_key: String,
+        token: Option<String>,
+        snarked_ledger_hash: Option<String>,
+        context: &Context,
+    ) -> juniper::FieldResult<account::GraphQLAccountProof> {
+        let query = RpcLedgerAccountProofQuery {
+            public_key: AccountPublicKey::from_str(&public_key)?,
+            token_id: token.as_deref().map(TokenIdKeyHash::from_str).transpose()?,
+            snarked_ledger_hash: snarked_ledger_hash
+                .as_deref()
+                .map(LedgerHash::from_str)
+                .transpose()?,
+        };
+        let res: RpcLedgerAccountProofGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::LedgerAccountProofGet(query))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        Ok(res.map_err(Error::Custom)?.try_into()?)
+    }
+
+    /// Accounts watched by the node, or just the one with `public_key`.
+    async fn watched_accounts(
+        public_key: Option<String>,
+        context: &Context,
+    ) -> juniper::FieldResult<Vec<account::GraphQLWatchedAccount>> {
+        let public_key = public_key
+            .as_deref()
+            .map(AccountPublicKey::from_str)
+            .transpose()?;
+        let res: RpcWatchedAccountsGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::WatchedAccountsGet(public_key))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        Ok(res
+            .into_iter()
+            .map(account::GraphQLWatchedAccount::try_from)
+            .collect::<Result<_, _>>()?)
+    }
+
     async fn sync_status(context: &Context) -> juniper::FieldResult<SyncStatus> {
         let state: RpcSyncStatsGetResponse = context
             .0
@@ -174,14 +258,7 @@ impl Query {
             .ok_or(Error::StateMachineEmptyResponse)?;
 
         if let Some(state) = state.as_ref().and_then(|s| s.first()) {
-            if state.synced.is_some() {
-                Ok(SyncStatus::SYNCED)
-            } else {
-                match &state.kind {
-                    SyncKind::Bootstrap => Ok(SyncStatus::BOOTSTRAP),
-                    SyncKind::Catchup => Ok(SyncStatus::CATCHUP),
-                }
-            }
+            Ok(state.status().into())
         } else {
             Ok(SyncStatus::LISTENING)
         }
@@ -236,27 +313,7 @@ impl Query {
         zkapp_transaction: Option<String>,
         context: &Context,
     ) -> juniper::FieldResult<String> {
-        if payment.is_some() && zkapp_transaction.is_some() {
-            return Err(Error::Custom(
-                "Cannot provide both payment and zkapp transaction".to_string(),
-            )
-            .into());
-        }
-
-        let tx = if let Some(payment) = payment {
-            MinaBaseUserCommandStableV2::SignedCommand(MinaBaseSignedCommandStableV2::from_base64(
-                &payment,
-            )?)
-        } else if let Some(zkapp_transaction) = zkapp_transaction {
-            MinaBaseUserCommandStableV2::ZkappCommand(
-                MinaBaseZkappCommandTStableV1WireStableV1::from_base64(&zkapp_transaction)?,
-            )
-        } else {
-            return Err(Error::Custom(
-                "Must provide either payment or zkapp transaction".to_string(),
-            )
-            .into());
-        };
+        let tx = decode_user_command(payment, zkapp_transaction)?;
         let res: RpcTransactionStatusGetResponse = context
             .0
             .oneshot_request(RpcRequest::TransactionStatusGet(tx))
@@ -264,6 +321,41 @@ impl Query {
             .ok_or(Error::StateMachineEmptyResponse)?;
         Ok(res.to_string())
     }
+
+    /// Applies the command on top of the best tip ledger without changing
+    /// it, or broadcasting the command.
+    async fn simulate_transaction(
+        payment: Option<String>,
+        zkapp_transaction: Option<String>,
+        verify: Option<bool>,
+        context: &Context,
+    ) -> juniper::FieldResult<user_command::GraphQLTransactionSimulation> {
+        let query = RpcTransactionSimulateQuery {
+            command: decode_user_command(payment, zkapp_transaction)?,
+            verify: verify.unwrap_or(false),
+        };
+        let res: RpcTransactionSimulateResponse = context
+            .0
+            .oneshot_request(RpcRequest::TransactionSimulate(query))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        Ok(res.map_err(Error::Custom)?.into())
+    }
+
+    /// Won slots of the block producer in the current and the next epoch.
+    async fn block_production_schedule(
+        context: &Context,
+    ) -> juniper::FieldResult<block_producer::GraphQLBlockProductionSchedule> {
+        let schedule: RpcBlockProducerScheduleGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::BlockProducerScheduleGet)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        let schedule = schedule.ok_or_else(|| {
+            Error::Custom("block producer is not enabled or node isn't synced".to_owned())
+        })?;
+        Ok(schedule.into())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -275,56 +367,319 @@ impl Mutation {
         input: zkapp::SendZkappInput,
         context: &Context,
     ) -> juniper::FieldResult<zkapp::GraphQLSendZkappResponse> {
-        let res: RpcTransactionInjectResponse = context
+        let res = inject_transaction(context, input.try_into()?).await?;
+        let zkapp_cmd: MinaBaseUserCommandStableV2 = match res.first().cloned() {
+            Some(RpcTransactionInjectedCommand::Zkapp(zkapp_cmd)) => zkapp_cmd.into(),
+            _ => unreachable!(),
+        };
+        Ok(zkapp_cmd.try_into()?)
+    }
+
+    async fn send_payment(
+        input: user_command::SendPaymentInput,
+        signature: Option<user_command::SignatureInput>,
+        context: &Context,
+    ) -> juniper::FieldResult<user_command::GraphQLSendPaymentResponse> {
+        let signature = signature.ok_or(Error::Custom(
+            "Signature is required, node does not hold any account keys".to_owned(),
+        ))?;
+        let nonce = infer_nonce(context, &input.from, input.nonce.as_deref()).await?;
+        let command = input.create_signed_command(nonce, signature)?;
+        inject_transaction(
+            context,
+            MinaBaseUserCommandStableV2::SignedCommand(command.clone().into()),
+        )
+        .await?;
+        Ok(user_command::GraphQLSendPaymentResponse {
+            payment: command.try_into()?,
+        })
+    }
+
+    async fn send_delegation(
+        input: user_command::SendDelegationInput,
+        signature: Option<user_command::SignatureInput>,
+        context: &Context,
+    ) -> juniper::FieldResult<user_command::GraphQLSendDelegationResponse> {
+        let signature = signature.ok_or(Error::Custom(
+            "Signature is required, node does not hold any account keys".to_owned(),
+        ))?;
+        let nonce = infer_nonce(context, &input.from, input.nonce.as_deref()).await?;
+        let command = input.create_signed_command(nonce, signature)?;
+        inject_transaction(
+            context,
+            MinaBaseUserCommandStableV2::SignedCommand(command.clone().into()),
+        )
+        .await?;
+        Ok(user_command::GraphQLSendDelegationResponse {
+            delegation: command.try_into()?,
+        })
+    }
+
+    /// Starts tracking the account and transactions affecting it.
+    async fn watch_account(public_key: String, context: &Context) -> juniper::FieldResult<bool> {
+        let public_key = AccountPublicKey::from_str(&public_key)?;
+        let res: RpcWatchedAccountsAddResponse = context
+            .0
+            .oneshot_request(RpcRequest::WatchedAccountsAdd(public_key))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        res.map_err(Error::Custom)?;
+        Ok(true)
+    }
+
+    /// Stops tracking the account, dropping its collected history.
+    async fn unwatch_account(public_key: String, context: &Context) -> juniper::FieldResult<bool> {
+        let public_key = AccountPublicKey::from_str(&public_key)?;
+        let res: RpcWatchedAccountsRemoveResponse = context
+            .0
+            .oneshot_request(RpcRequest::WatchedAccountsRemove(public_key))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        res.map_err(Error::Custom)?;
+        Ok(true)
+    }
+
+    /// Produces, and optionally proves, a block for one of our future won
+    /// slots right away. The block isn't applied or broadcasted.
+    async fn block_production_dry_run(
+        global_slot: Option<i32>,
+        prove: Option<bool>,
+        context: &Context,
+    ) -> juniper::FieldResult<block_producer::GraphQLBlockProductionDryRun> {
+        let query = RpcBlockProducerDryRunQuery {
+            global_slot: global_slot.map(u32::try_from).transpose()?,
+            prove: prove.unwrap_or(false),
+        };
+        let res: RpcBlockProducerDryRunResponse = context
             .0
-            .oneshot_request(RpcRequest::TransactionInject(vec![input.try_into()?]))
+            .oneshot_request(RpcRequest::BlockProducerDryRun(query))
             .await
             .ok_or(Error::StateMachineEmptyResponse)?;
+        Ok(res.map_err(Error::Custom)?.into())
+    }
+}
+
+type GraphQLStream<T> = Pin<Box<dyn Stream<Item = juniper::FieldResult<T>> + Send>>;
+
+#[derive(Clone, Copy, Debug)]
+struct Subscription;
+
+#[juniper::graphql_subscription(context = Context)]
+impl Subscription {
+    /// Event that triggers when the best tip changes.
+    async fn new_block(context: &Context) -> GraphQLStream<block::GraphQLBestChainBlock> {
+        subscription_stream(context, |event| match event {
+            RpcSubscriptionEvent::NewBestTip(block) => {
+                Some(block::GraphQLBestChainBlock::try_from(block).map_err(FieldError::from))
+            }
+            _ => None,
+        })
+    }
+
+    /// Event that triggers when the best tip switches to a block, which is not
+    /// a descendant of the previous best tip.
+    async fn chain_reorganization(context: &Context) -> GraphQLStream<ChainReorganizationStatus> {
+        subscription_stream(context, |event| match event {
+            RpcSubscriptionEvent::ChainReorganization { .. } => {
+                Some(Ok(ChainReorganizationStatus::CHANGED))
+            }
+            _ => None,
+        })
+    }
 
-        match res {
-            RpcTransactionInjectResponse::Success(res) => {
-                let zkapp_cmd: MinaBaseUserCommandStableV2 = match res.first().cloned() {
-                    Some(RpcTransactionInjectedCommand::Zkapp(zkapp_cmd)) => zkapp_cmd.into(),
-                    _ => unreachable!(),
-                };
-                Ok(zkapp_cmd.try_into()?)
+    /// Event that triggers when the sync status changes.
+    async fn sync_status_changed(context: &Context) -> GraphQLStream<SyncStatus> {
+        let mut last_status = None;
+        subscription_stream(context, move |event| match event {
+            RpcSubscriptionEvent::SyncStatus(status) if last_status != Some(status) => {
+                last_status = Some(status);
+                Some(Ok(status.into()))
             }
-            RpcTransactionInjectResponse::Rejected(rejected) => {
-                let error_list = rejected
-                    .into_iter()
-                    .map(|(_, err)| graphql_value!({ "message": err.to_string() }))
-                    .collect::<Vec<_>>();
-
-                Err(FieldError::new(
-                    "Transaction rejected",
-                    graphql_value!(juniper::Value::List(error_list)),
-                ))
+            _ => None,
+        })
+    }
+
+    /// Event that triggers when the state of a watched account, or the
+    /// list of transactions affecting it, changes. Only the change is sent.
+    async fn watched_account_changed(
+        public_key: Option<String>,
+        context: &Context,
+    ) -> juniper::FieldResult<GraphQLStream<account::GraphQLWatchedAccountChange>> {
+        let public_key = public_key
+            .as_deref()
+            .map(AccountPublicKey::from_str)
+            .transpose()?;
+        Ok(subscription_stream(context, move |event| match event {
+            RpcSubscriptionEvent::WatchedAccountChanged(change)
+                if public_key
+                    .as_ref()
+                    .map_or(true, |pk| pk == &change.public_key) =>
+            {
+                Some(
+                    account::GraphQLWatchedAccountChange::try_from(*change)
+                        .map_err(FieldError::from),
+                )
             }
-            RpcTransactionInjectResponse::Failure(failure) => {
-                let error_list = failure
-                    .into_iter()
-                    .map(|err| graphql_value!({ "message": err.to_string() }))
-                    .collect::<Vec<_>>();
-
-                Err(FieldError::new(
-                    "Transaction failed",
-                    graphql_value!(juniper::Value::List(error_list)),
-                ))
+            _ => None,
+        }))
+    }
+}
+
+/// Stream of the state machine events, mapped (and filtered) by `f`.
+///
+/// Events missed because the subscriber was lagging behind are skipped.
+fn subscription_stream<T, F>(context: &Context, f: F) -> GraphQLStream<T>
+where
+    T: 'static + Send,
+    F: 'static + Send + FnMut(RpcSubscriptionEvent) -> Option<juniper::FieldResult<T>>,
+{
+    let rx = context.0.subscribe();
+    Box::pin(stream::unfold((rx, f), |(mut rx, mut f)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Some(item) = f(event) {
+                        return Some((item, (rx, f)));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
             }
         }
+    }))
+}
+
+/// Decodes base64 encoded command, exactly one of the arguments must be set.
+fn decode_user_command(
+    payment: Option<String>,
+    zkapp_transaction: Option<String>,
+) -> juniper::FieldResult<MinaBaseUserCommandStableV2> {
+    if payment.is_some() && zkapp_transaction.is_some() {
+        return Err(
+            Error::Custom("Cannot provide both payment and zkapp transaction".to_string()).into(),
+        );
+    }
+
+    if let Some(payment) = payment {
+        Ok(MinaBaseUserCommandStableV2::SignedCommand(
+            MinaBaseSignedCommandStableV2::from_base64(&payment)?,
+        ))
+    } else if let Some(zkapp_transaction) = zkapp_transaction {
+        Ok(MinaBaseUserCommandStableV2::ZkappCommand(
+            MinaBaseZkappCommandTStableV1WireStableV1::from_base64(&zkapp_transaction)?,
+        ))
+    } else {
+        Err(Error::Custom("Must provide either payment or zkapp transaction".to_string()).into())
+    }
+}
+
+/// Injects the transaction into the transaction pool, turning rejections and
+/// verification failures into graphql errors.
+async fn inject_transaction(
+    context: &Context,
+    command: MinaBaseUserCommandStableV2,
+) -> juniper::FieldResult<RpcTransactionInjectSuccess> {
+    let res: RpcTransactionInjectResponse = context
+        .0
+        .oneshot_request(RpcRequest::TransactionInject(vec![command]))
+        .await
+        .ok_or(Error::StateMachineEmptyResponse)?;
+
+    match res {
+        RpcTransactionInjectResponse::Success(res) => Ok(res),
+        RpcTransactionInjectResponse::Rejected(rejected) => {
+            let error_list = rejected
+                .into_iter()
+                .map(|(_, err)| graphql_value!({ "message": err.to_string() }))
+                .collect::<Vec<_>>();
+
+            Err(FieldError::new(
+                "Transaction rejected",
+                graphql_value!(juniper::Value::List(error_list)),
+            ))
+        }
+        RpcTransactionInjectResponse::Failure(failure) => {
+            let error_list = failure
+                .into_iter()
+                .map(|err| graphql_value!({ "message": err.to_string() }))
+                .collect::<Vec<_>>();
+
+            Err(FieldError::new(
+                "Transaction failed",
+                graphql_value!(juniper::Value::List(error_list)),
+            ))
+        }
+    }
+}
+
+/// Nonce for the next transaction of the `sender`.
+///
+/// If `nonce` is provided, it is only checked not to be lower than the
+/// account's nonce in the best tip ledger. Otherwise it is inferred from the
+/// ledger and the sender's transactions already in the transaction pool.
+async fn infer_nonce(context: &Context, sender: &str, nonce: Option<&str>) -> Result<Nonce, Error> {
+    let public_key = AccountPublicKey::from_str(sender)
+        .map_err(|err| Error::Conversion(ConversionError::Base58Check(err)))?;
+    let accounts: RpcLedgerAccountsResponse = context
+        .0
+        .oneshot_request(RpcRequest::LedgerAccountsGet(
+            AccountQuery::PubKeyWithTokenId(public_key.clone(), TokenIdKeyHash::default()),
+        ))
+        .await
+        .ok_or(Error::StateMachineEmptyResponse)?;
+    let account_nonce = accounts.first().map(|account| account.nonce);
+
+    if let Some(nonce) = nonce {
+        let nonce = user_command::parse_nonce(nonce).map_err(Error::Conversion)?;
+        return match account_nonce {
+            Some(account_nonce) if nonce < account_nonce => Err(Error::Custom(format!(
+                "Nonce {} is lower than the account nonce {}",
+                nonce.as_u32(),
+                account_nonce.as_u32()
+            ))),
+            _ => Ok(nonce),
+        };
     }
+
+    let pool: RpcTransactionPoolResponse = context
+        .0
+        .oneshot_request(RpcRequest::TransactionPoolGet)
+        .await
+        .ok_or(Error::StateMachineEmptyResponse)?;
+    let public_key = CompressedPubKey::try_from(public_key)
+        .map_err(|_| Error::Conversion(ConversionError::InvalidBigInt))?;
+    let pool_nonce = pool
+        .iter()
+        .filter(|cmd| cmd.data.fee_payer().public_key == public_key)
+        .map(|cmd| cmd.data.forget_check().expected_target_nonce())
+        .max();
+
+    account_nonce.max(pool_nonce).ok_or_else(|| {
+        Error::Custom(
+            "Couldn't infer nonce for transaction from specified `sender` since `sender` \
+             is not in the ledger or sent a transaction in transaction pool."
+                .to_owned(),
+        )
+    })
 }
 
 pub fn routes(
     rpc_sernder: RpcSender,
 ) -> impl Filter<Error = Rejection, Extract = impl Reply> + Clone {
-    let state = warp::any().map(move || Context(rpc_sernder.clone()));
-    let schema = RootNode::new(Query, Mutation, EmptySubscription::<Context>::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema, state.boxed());
-    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", None);
-    let playground_filter = juniper_warp::playground_filter("/graphql", None);
+    let context = Context(rpc_sernder);
+    let state = {
+        let context = context.clone();
+        warp::any().map(move || context.clone())
+    };
+    let schema = Arc::new(RootNode::new(Query, Mutation, Subscription));
+    let graphql_filter = juniper_warp::make_graphql_filter(schema.clone(), state.boxed());
+    let subscriptions_filter =
+        juniper_warp::subscriptions::make_ws_filter(schema, ConnectionConfig::new(context));
+    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", Some("/subscriptions"));
+    let playground_filter = juniper_warp::playground_filter("/graphql", Some("/subscriptions"));
 
     (warp::post().and(warp::path("graphql")).and(graphql_filter))
+        .or(warp::path("subscriptions").and(subscriptions_filter))
         .or(warp::get()
             .and(warp::path("playground"))
             .and(playground_filter))
@@ -0,0 +1,411 @@
+use std::str::FromStr;
+
+use juniper::{GraphQLInputObject, GraphQLObject};
+use ledger::scan_state::currency::{Amount, Fee, Magnitude, MinMax, Nonce, Slot};
+use ledger::scan_state::transaction_logic::signed_command::{
+    self, PaymentPayload, SignedCommand, SignedCommandPayload, StakeDelegationPayload,
+};
+use ledger::scan_state::transaction_logic::{verifiable, Memo};
+use mina_p2p_messages::bigint::BigInt;
+use mina_p2p_messages::v2::MinaBaseSignedCommandStableV2;
+use mina_signer::CompressedPubKey;
+use node::account::AccountPublicKey;
+use node::rpc::{
+    RpcTransactionSimulation, RpcTransactionSimulationAccount, RpcTransactionSimulationFailure,
+};
+
+use super::ConversionError;
+
+/// Maximum length of the memo in bytes.
+const MAX_MEMO_LENGTH: usize = 32;
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct SendPaymentInput {
+    /// Should only be set when cancelling transactions, otherwise a nonce is
+    /// determined automatically
+    pub nonce: Option<String>,
+    /// Short arbitrary message provided by the sender
+    pub memo: Option<String>,
+    /// The global slot since genesis after which this transaction cannot be
+    /// applied
+    pub valid_until: Option<String>,
+    /// Fee amount in order to send payment
+    pub fee: String,
+    /// Amount of MINA to send to receiver
+    pub amount: String,
+    /// Public key of recipient of payment
+    pub to: String,
+    /// Public key of sender of payment
+    pub from: String,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct SendDelegationInput {
+    /// Should only be set when cancelling transactions, otherwise a nonce is
+    /// determined automatically
+    pub nonce: Option<String>,
+    /// Short arbitrary message provided by the sender
+    pub memo: Option<String>,
+    /// The global slot since genesis after which this transaction cannot be
+    /// applied
+    pub valid_until: Option<String>,
+    /// Fee amount in order to send a stake delegation
+    pub fee: String,
+    /// Public key of the account being delegated to
+    pub to: String,
+    /// Public key of sender of a stake delegation
+    pub from: String,
+}
+
+/// A cryptographic signature. Either `rawSignature` or both `field` and
+/// `scalar` must be provided.
+#[derive(GraphQLInputObject, Debug)]
+pub struct SignatureInput {
+    /// Field component of signature, as decimal string
+    pub field: Option<String>,
+    /// Scalar component of signature, as decimal string
+    pub scalar: Option<String>,
+    /// Raw encoded signature: hex encoded field and scalar, both 32 bytes
+    /// little-endian
+    pub raw_signature: Option<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLSendPaymentResponse {
+    pub payment: GraphQLUserCommand,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLSendDelegationResponse {
+    pub delegation: GraphQLUserCommand,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLUserCommand {
+    /// Signed command represented as base64 string
+    pub id: String,
+    pub hash: String,
+    /// Either `PAYMENT` or `STAKE_DELEGATION`
+    pub kind: String,
+    pub nonce: i32,
+    pub from: String,
+    /// Receiver of the payment, or the new delegate
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    /// Memo represented as base58check string
+    pub memo: String,
+    pub is_delegation: bool,
+    pub valid_until: String,
+    pub failure_reason: Option<String>,
+}
+
+impl SendPaymentInput {
+    /// Builds signed payment with the nonce resolved by the caller.
+    pub fn create_signed_command(
+        &self,
+        nonce: Nonce,
+        signature: SignatureInput,
+    ) -> Result<SignedCommand, ConversionError> {
+        let body = signed_command::Body::Payment(PaymentPayload {
+            receiver_pk: parse_public_key(&self.to)?,
+            amount: Amount::from_u64(self.amount.parse()?),
+        });
+        create_signed_command(
+            &self.from,
+            &self.fee,
+            nonce,
+            self.valid_until.as_deref(),
+            self.memo.as_deref(),
+            body,
+            signature,
+        )
+    }
+}
+
+impl SendDelegationInput {
+    /// Builds signed stake delegation with the nonce resolved by the caller.
+    pub fn create_signed_command(
+        &self,
+        nonce: Nonce,
+        signature: SignatureInput,
+    ) -> Result<SignedCommand, ConversionError> {
+        let body = signed_command::Body::StakeDelegation(StakeDelegationPayload::SetDelegate {
+            new_delegate: parse_public_key(&self.to)?,
+        });
+        create_signed_command(
+            &self.from,
+            &self.fee,
+            nonce,
+            self.valid_until.as_deref(),
+            self.memo.as_deref(),
+            body,
+            signature,
+        )
+    }
+}
+
+fn create_signed_command(
+    from: &str,
+    fee: &str,
+    nonce: Nonce,
+    valid_until: Option<&str>,
+    memo: Option<&str>,
+    body: signed_command::Body,
+    signature: SignatureInput,
+) -> Result<SignedCommand, ConversionError> {
+    let from = parse_public_key(from)?;
+    let valid_until = match valid_until {
+        Some(valid_until) => Slot::from_u32(valid_until.parse()?),
+        None => Slot::max(),
+    };
+    let memo = memo.unwrap_or_default();
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(ConversionError::Custom(format!(
+            "Memo is longer than {MAX_MEMO_LENGTH} bytes"
+        )));
+    }
+
+    let command = SignedCommand {
+        payload: SignedCommandPayload::create(
+            Fee::from_u64(fee.parse()?),
+            from.clone(),
+            nonce,
+            Some(valid_until),
+            Memo::from_str(memo).map_err(|_| ConversionError::Custom("Invalid memo".into()))?,
+            body,
+        ),
+        signer: from,
+        signature: signature.try_into()?,
+    };
+
+    if !command.check_valid_keys() {
+        return Err(ConversionError::Custom("Invalid public key".to_owned()));
+    }
+    verifiable::check_only_for_signature(Box::new(command.clone()))
+        .map_err(|_| ConversionError::Custom("Invalid signature".to_owned()))?;
+
+    Ok(command)
+}
+
+pub fn parse_nonce(nonce: &str) -> Result<Nonce, ConversionError> {
+    Ok(Nonce::from_u32(nonce.parse()?))
+}
+
+fn parse_public_key(public_key: &str) -> Result<CompressedPubKey, ConversionError> {
+    AccountPublicKey::from_str(public_key)?
+        .try_into()
+        .map_err(|_| ConversionError::InvalidBigInt)
+}
+
+impl TryFrom<SignatureInput> for mina_signer::Signature {
+    type Error = ConversionError;
+    fn try_from(value: SignatureInput) -> Result<Self, Self::Error> {
+        let (field, scalar) = match value {
+            SignatureInput {
+                raw_signature: Some(raw),
+                ..
+            } => {
+                let bytes = hex::decode(raw)
+                    .map_err(|err| ConversionError::Custom(format!("Invalid signature: {err}")))?;
+                if bytes.len() != 64 {
+                    return Err(ConversionError::InvalidLength);
+                }
+                let (field, scalar) = bytes.split_at(32);
+                (
+                    // Cannot fail, lengths checked above
+                    BigInt::from_bytes(field.try_into().unwrap()),
+                    BigInt::from_bytes(scalar.try_into().unwrap()),
+                )
+            }
+            SignatureInput {
+                field: Some(field),
+                scalar: Some(scalar),
+                ..
+            } => (
+                BigInt::from_decimal(&field)?,
+                BigInt::from_decimal(&scalar)?,
+            ),
+            _ => {
+                return Err(ConversionError::MissingField(
+                    "either rawSignature or field and scalar".to_owned(),
+                ))
+            }
+        };
+
+        Ok(mina_signer::Signature {
+            rx: field
+                .try_into()
+                .map_err(|_| ConversionError::InvalidBigInt)?,
+            s: scalar
+                .try_into()
+                .map_err(|_| ConversionError::InvalidBigInt)?,
+        })
+    }
+}
+
+impl TryFrom<SignedCommand> for GraphQLUserCommand {
+    type Error = ConversionError;
+    fn try_from(value: SignedCommand) -> Result<Self, Self::Error> {
+        let (kind, to, amount, is_delegation) = match &value.payload.body {
+            signed_command::Body::Payment(payment) => {
+                ("PAYMENT", &payment.receiver_pk, payment.amount, false)
+            }
+            signed_command::Body::StakeDelegation(delegation) => (
+                "STAKE_DELEGATION",
+                delegation.receiver_pk(),
+                Amount::zero(),
+                true,
+            ),
+        };
+        let command = MinaBaseSignedCommandStableV2::from(&value);
+
+        Ok(Self {
+            id: command.to_base64()?,
+            hash: command.hash()?.to_string(),
+            kind: kind.to_owned(),
+            nonce: value.nonce().as_u32() as i32,
+            from: AccountPublicKey::from(value.fee_payer_pk().clone()).to_string(),
+            to: AccountPublicKey::from(to.clone()).to_string(),
+            amount: amount.as_u64().to_string(),
+            fee: value.fee().as_u64().to_string(),
+            memo: command.payload.common.memo.to_base58check(),
+            is_delegation,
+            valid_until: value.valid_until().as_u32().to_string(),
+            failure_reason: None,
+        })
+    }
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Result of applying the command on top of the best tip ledger")]
+pub struct GraphQLTransactionSimulation {
+    /// Block on top of which the command was applied
+    pub best_tip: String,
+    pub global_slot: i32,
+    pub fee: String,
+    pub applied: bool,
+    pub failures: Vec<GraphQLTransactionSimulationFailure>,
+    pub accounts: Vec<GraphQLTransactionSimulationAccount>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLTransactionSimulationFailure {
+    /// For zkApp commands, index 0 is the fee payer and account updates
+    /// start from 1
+    pub index: i32,
+    pub failures: Vec<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLTransactionSimulationAccount {
+    pub public_key: String,
+    pub token_id: String,
+    pub created: bool,
+    pub balance_before: Option<String>,
+    pub balance_after: String,
+    pub nonce_before: Option<String>,
+    pub nonce_after: String,
+}
+
+impl From<RpcTransactionSimulation> for GraphQLTransactionSimulation {
+    fn from(value: RpcTransactionSimulation) -> Self {
+        Self {
+            best_tip: value.best_tip.to_string(),
+            global_slot: value.global_slot as i32,
+            fee: value.fee.as_u64().to_string(),
+            applied: value.applied,
+            failures: value.failures.into_iter().map(Into::into).collect(),
+            accounts: value.accounts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<RpcTransactionSimulationFailure> for GraphQLTransactionSimulationFailure {
+    fn from(value: RpcTransactionSimulationFailure) -> Self {
+        Self {
+            index: value.index as i32,
+            failures: value.failures.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl From<RpcTransactionSimulationAccount> for GraphQLTransactionSimulationAccount {
+    fn from(value: RpcTransactionSimulationAccount) -> Self {
+        Self {
+            public_key: value.public_key.to_string(),
+            token_id: value.token_id.to_string(),
+            created: value.created,
+            balance_before: value.balance_before.map(|v| v.as_u64().to_string()),
+            balance_after: value.balance_after.as_u64().to_string(),
+            nonce_before: value.nonce_before.map(|v| v.as_u32().to_string()),
+            nonce_after: value.nonce_after.as_u32().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signature_input_field_and_scalar() {
+        let signature = mina_signer::Signature::try_from(SignatureInput {
+            field: Some("1".to_owned()),
+            scalar: Some("2".to_owned()),
+            raw_signature: None,
+        })
+        .unwrap();
+        assert_eq!(signature.rx, 1u64.into());
+        assert_eq!(signature.s, 2u64.into());
+    }
+
+    #[test]
+    fn test_signature_input_raw_signature() {
+        let raw = format!("01{}02{}", "00".repeat(31), "00".repeat(31));
+        let from_raw = mina_signer::Signature::try_from(SignatureInput {
+            field: None,
+            scalar: None,
+            raw_signature: Some(raw),
+        })
+        .unwrap();
+        let from_decimal = mina_signer::Signature::try_from(SignatureInput {
+            field: Some("1".to_owned()),
+            scalar: Some("2".to_owned()),
+            raw_signature: None,
+        })
+        .unwrap();
+        assert_eq!(from_raw.rx, from_decimal.rx);
+        assert_eq!(from_raw.s, from_decimal.s);
+    }
+
+    #[test]
+    fn test_signature_input_invalid() {
+        let short = SignatureInput {
+            field: None,
+            scalar: None,
+            raw_signature: Some("00".repeat(63)),
+        };
+        assert!(matches!(
+            mina_signer::Signature::try_from(short),
+            Err(ConversionError::InvalidLength)
+        ));
+
+        let not_hex = SignatureInput {
+            field: None,
+            scalar: None,
+            raw_signature: Some("zz".repeat(64)),
+        };
+        assert!(mina_signer::Signature::try_from(not_hex).is_err());
+
+        let missing_scalar = SignatureInput {
+            field: Some("1".to_owned()),
+            scalar: None,
+            raw_signature: None,
+        };
+        assert!(matches!(
+            mina_signer::Signature::try_from(missing_scalar),
+            Err(ConversionError::MissingField(_))
+        ));
+    }
+}
@@ -6,10 +6,14 @@ use warp::{
     http::HeaderValue,
     hyper::{header::CONTENT_TYPE, Response, StatusCode},
     reply::with_status,
-    Filter, Rejection, Reply,
+    sse, Filter, Rejection, Reply,
 };
 
+use juniper::futures::stream;
+use node::account::AccountPublicKey;
+use node::core::channels::broadcast;
 use node::core::snark::SnarkJobId;
+use node::p2p::PeerId;
 use node::rpc::*;
 
 use openmina_node_common::rpc::{
@@ -157,6 +161,73 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let peer_bans_get = warp::path!("state" / "peers" / "bans")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let result = rpc_sender_clone
+                    .oneshot_request::<RpcP2pBansGetResponse>(RpcRequest::P2pBansGet)
+                    .await;
+
+                with_json_reply(&result, StatusCode::OK)
+            }
+        });
+
+    // TODO(binier): make endpoint only accessible locally.
+    let rpc_sender_clone = rpc_sender.clone();
+    let peer_ban = warp::path!("state" / "peers" / "bans")
+        .and(warp::post())
+        .and(warp::filters::body::json())
+        .then(move |query: RpcP2pBanQuery| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let res: Option<RpcP2pBanResponse> = rpc_sender_clone
+                    .oneshot_request(RpcRequest::P2pBan(query))
+                    .await;
+                match res {
+                    None => with_json_reply(
+                        &"response channel dropped",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    ),
+                    Some(resp) => {
+                        let status = match &resp {
+                            Ok(_) => StatusCode::OK,
+                            Err(_) => StatusCode::BAD_REQUEST,
+                        };
+                        with_json_reply(&resp, status)
+                    }
+                }
+            }
+        });
+
+    // TODO(binier): make endpoint only accessible locally.
+    let rpc_sender_clone = rpc_sender.clone();
+    let peer_unban = warp::path!("state" / "peers" / "bans" / PeerId)
+        .and(warp::delete())
+        .then(move |peer_id: PeerId| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let res: Option<RpcP2pUnbanResponse> = rpc_sender_clone
+                    .oneshot_request(RpcRequest::P2pUnban(peer_id))
+                    .await;
+                match res {
+                    None => with_json_reply(
+                        &"response channel dropped",
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    ),
+                    Some(resp) => {
+                        let status = match &resp {
+                            Ok(_) => StatusCode::OK,
+                            Err(_) => StatusCode::NOT_FOUND,
+                        };
+                        with_json_reply(&resp, status)
+                    }
+                }
+            }
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let message_progress_get = warp::path!("state" / "message-progress")
         .and(warp::get())
@@ -358,7 +429,7 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
                     ),
                     Some(resp) => {
                         let status = match &resp {
-                            RpcSnarkerJobCommitResponse::Ok => StatusCode::CREATED,
+                            RpcSnarkerJobCommitResponse::Ok { .. } => StatusCode::CREATED,
                             _ => StatusCode::BAD_REQUEST,
                         };
                         with_json_reply(&resp, status)
@@ -428,6 +499,24 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let snarker_jobs = warp::path!("snarker" / "jobs")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::SnarkerJobs)
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: node::rpc::RpcSnarkerJobsResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let snarker_config = warp::path!("snarker" / "config")
         .and(warp::get())
@@ -446,6 +535,48 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let block_producer_schedule = warp::path!("block-producer" / "schedule")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::BlockProducerScheduleGet)
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcBlockProducerScheduleGetResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
+    // TODO(binier): make endpoint only accessible locally.
+    let rpc_sender_clone = rpc_sender.clone();
+    let block_producer_dry_run = warp::path!("block-producer" / "dry-run")
+        .and(warp::post())
+        .and(warp::filters::body::json())
+        .then(move |query: RpcBlockProducerDryRunQuery| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::BlockProducerDryRun(query))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcBlockProducerDryRunResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::BAD_REQUEST,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let transaction_pool = warp::path!("transaction-pool")
         .and(warp::get())
@@ -481,6 +612,139 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         }
     });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let account_proof = warp::path!("account" / "proof")
+        .and(warp::get())
+        .and(warp::query())
+        .then(move |query: RpcLedgerAccountProofQuery| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::LedgerAccountProofGet(query))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcLedgerAccountProofGetResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::BAD_REQUEST,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let ledger_export = warp::path!("ledger" / "export")
+        .and(warp::get())
+        .and(warp::query())
+        .then(move |query: RpcLedgerExportQuery| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::LedgerExport(query))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcLedgerExportResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::BAD_REQUEST,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let watched_accounts_get = warp::path!("watched-accounts")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::WatchedAccountsGet(None))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcWatchedAccountsGetResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let watched_account_add = warp::path!("watched-accounts" / AccountPublicKey)
+        .and(warp::post())
+        .then(move |public_key: AccountPublicKey| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::WatchedAccountsAdd(public_key))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcWatchedAccountsAddResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::CONFLICT,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let watched_account_remove = warp::path!("watched-accounts" / AccountPublicKey)
+        .and(warp::delete())
+        .then(move |public_key: AccountPublicKey| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::WatchedAccountsRemove(public_key))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcWatchedAccountsRemoveResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::NOT_FOUND,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
+    // Server-sent events with each change of a watched account.
+    let rpc_sender_clone = rpc_sender.clone();
+    let watched_accounts_events = warp::path!("watched-accounts" / "events")
+        .and(warp::get())
+        .map(move || {
+            let rx = rpc_sender_clone.subscribe();
+            let events = stream::unfold(rx, |mut rx| async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(RpcSubscriptionEvent::WatchedAccountChanged(change)) => {
+                            let event = sse::Event::default()
+                                .event("watched_account_changed")
+                                .json_data(&change);
+                            if let Ok(event) = event {
+                                return Some((Ok::<_, Infallible>(event), rx));
+                            }
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            });
+            sse::reply(sse::keep_alive().stream(events))
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let transaction_post = warp::path("send-payment")
         .and(warp::post())
@@ -505,6 +769,29 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let transaction_simulate = warp::path!("transaction" / "simulate")
+        .and(warp::post())
+        .and(warp::filters::body::json())
+        .then(move |query: RpcTransactionSimulateQuery| {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::TransactionSimulate(query))
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcTransactionSimulateResponse| {
+                            let status = match &reply {
+                                Ok(_) => StatusCode::OK,
+                                Err(_) => StatusCode::BAD_REQUEST,
+                            };
+                            with_json_reply(&reply, status)
+                        },
+                    )
+            }
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let transition_frontier_user_commands = warp::path("best-chain-user-commands")
         .and(warp::get())
@@ -524,6 +811,25 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let transition_frontier_tips = warp::path!("transition-frontier" / "tips")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::TransitionFrontierTipsGet)
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: node::rpc::RpcTransitionFrontierTipsGetResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
     let cors = warp::cors()
         .allow_any_origin()
         .allow_methods(["GET", "POST", "PUT", "DELETE", "OPTIONS"])
@@ -544,23 +850,38 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         routes,
         status,
         peers_get,
+        peer_bans_get,
+        peer_ban,
+        peer_unban,
         message_progress_get,
         stats,
+        block_producer_schedule,
+        block_producer_dry_run,
         scan_state_summary_get,
         snark_pool_jobs_get,
         snark_pool_job_get,
         snarker_config,
         snarker_job_commit,
         snarker_job_spec,
+        snarker_jobs,
         snark_workers,
         transaction_pool,
         accounts,
+        account_proof,
+        ledger_export,
+        watched_accounts_get,
+        watched_accounts_events,
+        watched_account_add,
+        watched_account_remove,
         transaction_post,
+        transaction_simulate,
         transition_frontier_user_commands,
+        transition_frontier_tips,
         healthcheck(rpc_sender.clone()),
         readiness(rpc_sender.clone()),
         discovery::routing_table(rpc_sender.clone()),
         discovery::bootstrap_stats(rpc_sender.clone()),
+        metrics::prometheus(rpc_sender.clone()),
         super::graphql::routes(rpc_sender),
     );
 
@@ -669,6 +990,354 @@ mod discovery {
     }
 }
 
+mod metrics {
+    use std::fmt::{Display, Write};
+
+    use node::rpc::{
+        ActionStatsQuery, ActionStatsResponse, PeerConnectionStatus, RpcActionStatsGetResponse,
+        RpcBlockProducerStats, RpcBlockProducerStatsGetResponse, RpcNodeStatus, RpcRequest,
+        RpcStatusGetResponse,
+    };
+    use node::stats::actions::ActionStatsSnapshot;
+    use node::stats::block_producer::BLOCK_PROOF_DURATION_BUCKETS;
+    use node::ActionKind;
+    use openmina_node_common::rpc::RpcSender;
+    use warp::hyper::StatusCode;
+    use warp::Filter;
+
+    use super::DROPPED_CHANNEL;
+
+    pub fn prometheus(
+        rpc_sender: RpcSender,
+    ) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path!("metrics").and(warp::get()).then(move || {
+            let rpc_sender = rpc_sender.clone();
+            async move {
+                let Some(status) = rpc_sender
+                    .oneshot_request::<RpcStatusGetResponse>(RpcRequest::StatusGet)
+                    .await
+                else {
+                    return warp::reply::with_status(
+                        String::from(DROPPED_CHANNEL),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    );
+                };
+                let action_stats = rpc_sender
+                    .oneshot_request::<RpcActionStatsGetResponse>(RpcRequest::ActionStatsGet(
+                        ActionStatsQuery::SinceStart,
+                    ))
+                    .await
+                    .flatten()
+                    .and_then(|resp| match resp {
+                        ActionStatsResponse::SinceStart { stats } => Some(stats),
+                        ActionStatsResponse::ForBlock(_) => None,
+                    });
+                let block_producer = rpc_sender
+                    .oneshot_request::<RpcBlockProducerStatsGetResponse>(
+                        RpcRequest::BlockProducerStatsGet,
+                    )
+                    .await
+                    .flatten();
+
+                let mut encoder = Encoder::default();
+                if let Some(status) = &status {
+                    encode_status(&mut encoder, status);
+                }
+                if let Some(stats) = &action_stats {
+                    encode_action_stats(&mut encoder, stats);
+                }
+                if let Some(stats) = &block_producer {
+                    encode_block_producer_stats(&mut encoder, stats);
+                }
+                warp::reply::with_status(encoder.0, StatusCode::OK)
+            }
+        })
+    }
+
+    fn encode_status(encoder: &mut Encoder, status: &RpcNodeStatus) {
+        if let Some(best_tip) = &status.transition_frontier.best_tip {
+            encoder
+                .header("best_tip_height", "gauge", "Height of the best tip.")
+                .sample("best_tip_height", &[], best_tip.height)
+                .header(
+                    "best_tip_global_slot",
+                    "gauge",
+                    "Global slot since genesis of the best tip.",
+                )
+                .sample("best_tip_global_slot", &[], best_tip.global_slot);
+        }
+
+        let (mut connecting, mut connected, mut disconnected) = (0, 0, 0);
+        for peer in &status.peers {
+            match peer.connection_status {
+                PeerConnectionStatus::Connecting => connecting += 1,
+                PeerConnectionStatus::Connected => connected += 1,
+                PeerConnectionStatus::Disconnected => disconnected += 1,
+            }
+        }
+        encoder
+            .header(
+                "peers",
+                "gauge",
+                "Number of known peers by connection state.",
+            )
+            .sample("peers", &[("state", "connecting")], connecting)
+            .sample("peers", &[("state", "connected")], connected)
+            .sample("peers", &[("state", "disconnected")], disconnected);
+
+        let pending_outgoing_bytes = status
+            .peers
+            .iter()
+            .map(|peer| peer.pending_outgoing_bytes)
+            .sum::<usize>();
+        encoder
+            .header(
+                "p2p_pending_outgoing_bytes",
+                "gauge",
+                "Outgoing data buffered until peers accept it.",
+            )
+            .sample("p2p_pending_outgoing_bytes", &[], pending_outgoing_bytes);
+
+        encoder
+            .header(
+                "transaction_pool_size",
+                "gauge",
+                "Number of transactions in the transaction pool.",
+            )
+            .sample(
+                "transaction_pool_size",
+                &[],
+                status.transaction_pool.transactions,
+            )
+            .header(
+                "snark_pool_jobs",
+                "gauge",
+                "Number of snark jobs in the snark pool.",
+            )
+            .sample("snark_pool_jobs", &[], status.snark_pool.total_jobs)
+            .header(
+                "snark_pool_snarks",
+                "gauge",
+                "Number of snark jobs in the snark pool which have a snark.",
+            )
+            .sample("snark_pool_snarks", &[], status.snark_pool.snarks);
+    }
+
+    fn encode_action_stats(encoder: &mut Encoder, stats: &ActionStatsSnapshot) {
+        const NAME: &str = "action_duration_seconds";
+        encoder.header(
+            NAME,
+            "histogram",
+            "Duration from the action till the next one, by action kind.",
+        );
+        let mut vrf_evaluations = 0;
+        for (kind, ranges) in stats.iter() {
+            let ranges = ranges.ranges();
+            let count = ranges.iter().map(|(_, r)| r.total_calls).sum::<u64>();
+            if count == 0 {
+                // Keep the output small by skipping actions, which have
+                // never been called.
+                continue;
+            }
+            if kind == ActionKind::BlockProducerVrfEvaluatorProcessSlotEvaluationSuccess {
+                vrf_evaluations = count;
+            }
+            let sum = ranges.iter().map(|(_, r)| r.total_duration).sum::<u64>();
+
+            let kind = kind.to_string();
+            let mut cumulative_count = 0;
+            for (bound, range) in ranges {
+                cumulative_count += range.total_calls;
+                let le = bound.map_or_else(|| "+Inf".to_owned(), |v| nanos_to_secs(v).to_string());
+                encoder.sample_with_suffix(
+                    NAME,
+                    "_bucket",
+                    &[("kind", &kind), ("le", &le)],
+                    cumulative_count,
+                );
+            }
+            encoder
+                .sample_with_suffix(NAME, "_sum", &[("kind", &kind)], nanos_to_secs(sum))
+                .sample_with_suffix(NAME, "_count", &[("kind", &kind)], count);
+        }
+
+        encoder
+            .header(
+                "vrf_evaluations_total",
+                "counter",
+                "Number of slots evaluated by the vrf evaluator.",
+            )
+            .sample("vrf_evaluations_total", &[], vrf_evaluations);
+    }
+
+    fn encode_block_producer_stats(encoder: &mut Encoder, stats: &RpcBlockProducerStats) {
+        encoder
+            .header(
+                "vrf_won_slots",
+                "gauge",
+                "Number of won slots, which are still to be produced.",
+            )
+            .sample("vrf_won_slots", &[], stats.future_won_slots.len());
+
+        const NAME: &str = "block_proof_duration_seconds";
+        let durations = &stats.proof_durations;
+        encoder.header(NAME, "histogram", "Duration of the block proof creation.");
+        for (bound, count) in BLOCK_PROOF_DURATION_BUCKETS.iter().zip(durations.buckets) {
+            let le = bound.as_secs().to_string();
+            encoder.sample_with_suffix(NAME, "_bucket", &[("le", &le)], count);
+        }
+        encoder
+            .sample_with_suffix(NAME, "_bucket", &[("le", "+Inf")], durations.count)
+            .sample_with_suffix(NAME, "_sum", &[], durations.sum.as_secs_f64())
+            .sample_with_suffix(NAME, "_count", &[], durations.count);
+    }
+
+    fn nanos_to_secs(nanos: u64) -> f64 {
+        nanos as f64 / 1_000_000_000.0
+    }
+
+    /// Writes metrics in the Prometheus text exposition format.
+    #[derive(Default)]
+    struct Encoder(String);
+
+    impl Encoder {
+        const PREFIX: &'static str = "openmina_";
+
+        fn header(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+            let prefix = Self::PREFIX;
+            let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+            let _ = writeln!(self.0, "# HELP {prefix}{name} {help}");
+            let _ = writeln!(self.0, "# TYPE {prefix}{name} {kind}");
+            self
+        }
+
+        fn sample(
+            &mut self,
+            name: &str,
+            labels: &[(&str, &str)],
+            value: impl Display,
+        ) -> &mut Self {
+            self.sample_with_suffix(name, "", labels, value)
+        }
+
+        fn sample_with_suffix(
+            &mut self,
+            name: &str,
+            suffix: &str,
+            labels: &[(&str, &str)],
+            value: impl Display,
+        ) -> &mut Self {
+            let _ = write!(self.0, "{}{name}{suffix}", Self::PREFIX);
+            if !labels.is_empty() {
+                let labels = labels
+                    .iter()
+                    .map(|(k, v)| format!("{k}=\"{}\"", escape_label_value(v)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let _ = write!(self.0, "{{{labels}}}");
+            }
+            let _ = writeln!(self.0, " {value}");
+            self
+        }
+    }
+
+    fn escape_label_value(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+
+        use node::stats::block_producer::BlockProofDurations;
+
+        use super::*;
+
+        fn block_producer_stats(proof_durations: BlockProofDurations) -> RpcBlockProducerStats {
+            RpcBlockProducerStats {
+                current_time: redux::Timestamp::ZERO,
+                current_global_slot: None,
+                epoch_start: None,
+                epoch_end: None,
+                attempts: vec![],
+                future_won_slots: vec![],
+                producers: vec![],
+                proof_durations,
+            }
+        }
+
+        #[test]
+        fn test_encode_block_producer_stats() {
+            let mut proof_durations = BlockProofDurations::default();
+            proof_durations.add(Duration::from_secs(8));
+            proof_durations.add(Duration::from_secs(25));
+            proof_durations.add(Duration::from_secs(300));
+
+            let mut encoder = Encoder::default();
+            encode_block_producer_stats(&mut encoder, &block_producer_stats(proof_durations));
+            assert_eq!(
+                encoder.0,
+                "\
+# HELP openmina_vrf_won_slots Number of won slots, which are still to be produced.
+# TYPE openmina_vrf_won_slots gauge
+openmina_vrf_won_slots 0
+# HELP openmina_block_proof_duration_seconds Duration of the block proof creation.
+# TYPE openmina_block_proof_duration_seconds histogram
+openmina_block_proof_duration_seconds_bucket{le=\"5\"} 0
+openmina_block_proof_duration_seconds_bucket{le=\"10\"} 1
+openmina_block_proof_duration_seconds_bucket{le=\"20\"} 1
+openmina_block_proof_duration_seconds_bucket{le=\"30\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"45\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"60\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"90\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"120\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"180\"} 2
+openmina_block_proof_duration_seconds_bucket{le=\"+Inf\"} 3
+openmina_block_proof_duration_seconds_sum 333
+openmina_block_proof_duration_seconds_count 3
+"
+            );
+        }
+
+        #[test]
+        fn test_encode_action_stats_without_calls() {
+            let mut encoder = Encoder::default();
+            encode_action_stats(&mut encoder, &ActionStatsSnapshot::default());
+            assert_eq!(
+                encoder.0,
+                "\
+# HELP openmina_action_duration_seconds Duration from the action till the next one, by action kind.
+# TYPE openmina_action_duration_seconds histogram
+# HELP openmina_vrf_evaluations_total Number of slots evaluated by the vrf evaluator.
+# TYPE openmina_vrf_evaluations_total counter
+openmina_vrf_evaluations_total 0
+"
+            );
+        }
+
+        #[test]
+        fn test_encoder_escaping() {
+            let mut encoder = Encoder::default();
+            encoder
+                .header("test_total", "counter", "Back\\slash and\nnewline.")
+                .sample("test_total", &[("a", "x\"y"), ("b", "c\\d\ne")], 7)
+                .sample("test_total", &[], 1);
+            assert_eq!(
+                encoder.0,
+                "\
+# HELP openmina_test_total Back\\\\slash and\\nnewline.
+# TYPE openmina_test_total counter
+openmina_test_total{a=\"x\\\"y\",b=\"c\\\\d\\ne\"} 7
+openmina_test_total 1
+"
+            );
+        }
+    }
+}
+
 fn with_rpc_sender(
     rpc_sender: RpcSender,
 ) -> impl warp::Filter<Extract = (RpcSender,), Error = Infallible> + Clone {
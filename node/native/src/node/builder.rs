@@ -1,32 +1,36 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
 
 use anyhow::Context;
-use ledger::proofs::provers::BlockProver;
+use ledger::proofs::provers::{BlockProver, TransactionProver, ZkappProver};
 use mina_p2p_messages::v2::{self, NonZeroCurvePoint};
 use node::{
     account::AccountSecretKey,
     daemon_json::Daemon,
+    ledger::{ArchiveTarget, LedgerArchive, LedgerStorage},
     p2p::{
         channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts,
-        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pTimeouts,
+        identity::SecretKey as P2pSecretKey, service_impl::address_book::P2pAddressBookStorage,
+        P2pLimits, P2pMeshsubConfig, P2pTimeouts,
     },
     service::Recorder,
     snark::{get_srs, BlockVerifier, TransactionVerifier, VerifierSRS},
+    snark_pool::SnarkPoolStorage,
     transition_frontier::genesis::GenesisConfig,
-    BlockProducerConfig, GlobalConfig, LedgerConfig, P2pConfig, SnarkConfig, SnarkerConfig,
-    SnarkerStrategy, TransitionFrontierConfig,
+    BlockProducerConfig, BlockProducerKeyConfig, GlobalConfig, LedgerConfig, P2pConfig,
+    SnarkConfig, SnarkPoolConfig, SnarkerConfig, SnarkerPricing, SnarkerStrategy,
+    TransitionFrontierConfig,
 };
 use openmina_core::{consensus::ConsensusConstants, constants::constraint_constants};
 use openmina_node_common::p2p::TaskSpawner;
 use rand::Rng;
 
-use crate::NodeServiceBuilder;
+use crate::{ExternalSnarkWorker, NodeServiceBuilder};
 
 use super::Node;
 
@@ -38,15 +42,22 @@ pub struct NodeBuilder {
     p2p_libp2p_port: Option<u16>,
     p2p_is_seed: bool,
     p2p_no_discovery: bool,
+    p2p_signaling_relay: bool,
     p2p_is_started: bool,
     initial_peers: Vec<P2pConnectionOutgoingInitOpts>,
     block_producer: Option<BlockProducerConfig>,
     snarker: Option<SnarkerConfig>,
+    snark_pool: SnarkPoolConfig,
     service: NodeServiceBuilder,
     verifier_srs: Option<Arc<VerifierSRS>>,
     block_verifier_index: Option<BlockVerifier>,
     work_verifier_index: Option<TransactionVerifier>,
     http_port: Option<u16>,
+    ledger_storage_dir: Option<PathBuf>,
+    on_disk_ledgers_dir: Option<PathBuf>,
+    address_book_path: Option<PathBuf>,
+    snark_pool_path: Option<PathBuf>,
+    archive: Option<(ArchiveTarget, bool)>,
     daemon_conf: Daemon,
 }
 
@@ -71,15 +82,22 @@ impl NodeBuilder {
             p2p_libp2p_port: None,
             p2p_is_seed: false,
             p2p_no_discovery: false,
+            p2p_signaling_relay: false,
             p2p_is_started: false,
             initial_peers: Vec::new(),
             block_producer: None,
             snarker: None,
+            snark_pool: SnarkPoolConfig::default(),
             service: NodeServiceBuilder::new(rng_seed),
             verifier_srs: None,
             block_verifier_index: None,
             work_verifier_index: None,
             http_port: None,
+            ledger_storage_dir: None,
+            on_disk_ledgers_dir: None,
+            address_book_path: None,
+            snark_pool_path: None,
+            archive: None,
             daemon_conf,
         }
     }
@@ -112,6 +130,13 @@ impl NodeBuilder {
         self
     }
 
+    /// Enable relaying of webrtc signaling through connected peers.
+    /// Peers running older versions don't support it.
+    pub fn p2p_signaling_relay(&mut self) -> &mut Self {
+        self.p2p_signaling_relay = true;
+        self
+    }
+
     /// Extend p2p initial peers from an iterable.
     pub fn initial_peers(
         &mut self,
@@ -167,11 +192,7 @@ impl NodeBuilder {
 
     /// Set up block producer.
     pub fn block_producer(&mut self, provers: BlockProver, key: AccountSecretKey) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
         self.service.block_producer_init(provers, key);
         self
@@ -189,6 +210,28 @@ impl NodeBuilder {
         Ok(self.block_producer(provers, key))
     }
 
+    /// Set up block producer, with the producer key held by the signer
+    /// process listening on `addr`.
+    #[cfg(unix)]
+    pub fn block_producer_with_remote_signer(
+        &mut self,
+        provers: BlockProver,
+        addr: openmina_node_common::block_producer::RemoteSignerAddr,
+    ) -> anyhow::Result<&mut Self> {
+        use openmina_node_common::block_producer::{
+            BlockProducerKeyBackend, RemoteSignerKeyBackend,
+        };
+
+        let key = RemoteSignerKeyBackend::connect(addr)
+            .map_err(|err| anyhow::anyhow!(err))
+            .context("Failed to connect to the remote signer")?;
+        let config = BlockProducerConfig::new(key.public_key().clone().into());
+        self.block_producer = Some(config);
+        self.service
+            .block_producer_init_with_backend(provers, Arc::new(key));
+        Ok(self)
+    }
+
     /// Receive block producer's coinbase reward to another account.
     pub fn custom_coinbase_receiver(
         &mut self,
@@ -203,6 +246,37 @@ impl NodeBuilder {
         Ok(self)
     }
 
+    /// Produce blocks with an additional key, on top of the one set up
+    /// with `block_producer`. Coinbase rewards for blocks produced with
+    /// this key go to `custom_coinbase_receiver`, if provided.
+    pub fn additional_block_producer(
+        &mut self,
+        key: AccountSecretKey,
+        custom_coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let bp = self.block_producer.as_mut().ok_or_else(|| {
+            anyhow::anyhow!("can't add block producer key when block producer is not initialized.")
+        })?;
+        bp.add_producer_key(BlockProducerKeyConfig {
+            pub_key: key.public_key().into(),
+            custom_coinbase_receiver,
+        });
+        self.service.block_producer_add_key(key);
+        Ok(self)
+    }
+
+    /// Produce blocks with an additional key loaded from file.
+    pub fn additional_block_producer_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        password: &str,
+        custom_coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let key = AccountSecretKey::from_encrypted_file(path, password)
+            .context("Failed to decrypt secret key file")?;
+        self.additional_block_producer(key, custom_coinbase_receiver)
+    }
+
     pub fn custom_block_producer_config(
         &mut self,
         config: BlockProducerConfig,
@@ -213,12 +287,37 @@ impl NodeBuilder {
         Ok(self)
     }
 
+    /// Set up snarker with an in-process snark worker, which proves jobs
+    /// using the given provers.
     pub fn snarker(
         &mut self,
+        tx_prover: TransactionProver,
+        zkapp_prover: ZkappProver,
         sec_key: AccountSecretKey,
         fee: u64,
         strategy: SnarkerStrategy,
     ) -> &mut Self {
+        self.snarker_config(sec_key, fee, strategy);
+        self.service.snark_worker_init(tx_prover, zkapp_prover);
+        self
+    }
+
+    /// Same as [`Self::snarker`], but snark work is proven by the
+    /// external `mina` snark worker executable at `exe_path`.
+    pub fn snarker_with_external_worker(
+        &mut self,
+        exe_path: PathBuf,
+        sec_key: AccountSecretKey,
+        fee: u64,
+        strategy: SnarkerStrategy,
+    ) -> &mut Self {
+        self.snarker_config(sec_key, fee, strategy);
+        self.service
+            .snark_worker_init_with_backend(Box::new(ExternalSnarkWorker::new(exe_path)));
+        self
+    }
+
+    fn snarker_config(&mut self, sec_key: AccountSecretKey, fee: u64, strategy: SnarkerStrategy) {
         let config = SnarkerConfig {
             public_key: sec_key.public_key(),
             fee: v2::CurrencyFeeStableV1(v2::UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
@@ -226,8 +325,26 @@ impl NodeBuilder {
             )),
             strategy,
             auto_commit: true,
+            pricing: Default::default(),
         };
         self.snarker = Some(config);
+    }
+
+    /// Set the policy for pricing snark jobs, on top of the fee passed
+    /// to [`Self::snarker`].
+    pub fn snarker_pricing(&mut self, pricing: SnarkerPricing) -> anyhow::Result<&mut Self> {
+        self.snarker
+            .as_mut()
+            .ok_or_else(|| {
+                anyhow::anyhow!("snarker not initialized! Call `snarker` function first.")
+            })?
+            .pricing = pricing;
+        Ok(self)
+    }
+
+    /// Set size limits of the snark pool.
+    pub fn snark_pool_config(&mut self, config: SnarkPoolConfig) -> &mut Self {
+        self.snark_pool = config;
         self
     }
 
@@ -263,6 +380,43 @@ impl NodeBuilder {
         self
     }
 
+    /// Persist transition frontier and ledgers in the `dir`, so that the
+    /// node can resume from them after restart instead of re-syncing.
+    pub fn ledger_storage(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.ledger_storage_dir = Some(dir.into());
+        self
+    }
+
+    /// Store snarked ledgers on disk in `dir` instead of memory, so that
+    /// memory usage doesn't grow with the number of ledgers held.
+    pub fn ledger_on_disk(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.on_disk_ledgers_dir = Some(dir.into());
+        self
+    }
+
+    /// Persist the p2p address book in the file at `path` and seed the
+    /// node with peers saved there by the previous run.
+    pub fn address_book(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.address_book_path = Some(path.into());
+        self
+    }
+
+    /// Persist completed snarks of the snark pool in the file at `path`.
+    /// Snarks saved there by the previous run are added back to the pool
+    /// if they are still relevant for the current scan state.
+    pub fn snark_pool_storage(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.snark_pool_path = Some(path.into());
+        self
+    }
+
+    /// Send blocks added to the best chain to the archive `target`. With
+    /// `backfill`, the whole best chain is archived on startup, otherwise
+    /// only blocks added after it.
+    pub fn archive(&mut self, target: ArchiveTarget, backfill: bool) -> &mut Self {
+        self.archive = Some((target, backfill));
+        self
+    }
+
     pub fn build(self) -> anyhow::Result<Node> {
         let p2p_sec_key = self.p2p_sec_key.unwrap_or_else(P2pSecretKey::rand);
         let initial_peers = if self.initial_peers.is_empty() && !self.p2p_is_seed {
@@ -283,6 +437,19 @@ impl NodeBuilder {
             .custom_initial_time
             .unwrap_or_else(redux::Timestamp::global_now);
 
+        let (address_book, address_book_entries) = match self.address_book_path {
+            Some(path) => {
+                let storage = P2pAddressBookStorage::new(&path);
+                let entries = storage
+                    .load()
+                    .with_context(|| anyhow::anyhow!("loading address book {path:?}"))?;
+                (Some(storage), entries)
+            }
+            None => (None, Vec::new()),
+        };
+
+        let snark_pool_storage = self.snark_pool_path.map(SnarkPoolStorage::new);
+
         let protocol_constants = self.genesis_config.protocol_constants()?;
         let consensus_consts =
             ConsensusConstants::create(constraint_constants(), &protocol_constants);
@@ -301,16 +468,23 @@ impl NodeBuilder {
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
                 ask_initial_peers_interval: Duration::from_secs(3600),
-                enabled_channels: ChannelId::iter_all().collect(),
+                enabled_channels: ChannelId::iter_default()
+                    .chain(
+                        self.p2p_signaling_relay
+                            .then_some(ChannelId::SignalingRelay),
+                    )
+                    .collect(),
                 peer_discovery: !self.p2p_no_discovery,
                 meshsub: P2pMeshsubConfig {
                     initial_time: initial_time
                         .checked_sub(redux::Timestamp::ZERO)
                         .unwrap_or_default(),
                     ..Default::default()
-                },
+                }
+                .into(),
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                address_book: address_book_entries,
             },
             ledger: LedgerConfig {},
             snark: SnarkConfig {
@@ -319,6 +493,7 @@ impl NodeBuilder {
                 work_verifier_index,
                 work_verifier_srs: srs,
             },
+            snark_pool: self.snark_pool,
             transition_frontier: TransitionFrontierConfig::new(self.genesis_config),
             block_producer: self.block_producer,
             tx_pool: ledger::transaction_pool::Config {
@@ -330,11 +505,31 @@ impl NodeBuilder {
 
         // build service
         let mut service = self.service;
-        service.ledger_init();
+        let ledger_storage = self
+            .ledger_storage_dir
+            .map(|dir| {
+                LedgerStorage::open(&dir)
+                    .with_context(|| anyhow::anyhow!("opening ledger storage {dir:?}"))
+            })
+            .transpose()?;
+        let archive = self
+            .archive
+            .map(|(target, backfill)| {
+                LedgerArchive::new(target.clone(), backfill)
+                    .with_context(|| anyhow::anyhow!("initializing archive {target:?}"))
+            })
+            .transpose()?;
+        service.ledger_init_with(ledger_storage, archive, self.on_disk_ledgers_dir);
 
         if !self.p2p_is_started {
             service.p2p_init(p2p_sec_key);
         }
+        if let Some(storage) = address_book {
+            service.p2p_address_book(storage);
+        }
+        if let Some(storage) = snark_pool_storage {
+            service.snark_pool_storage(storage);
+        }
 
         let service = service.build()?;
         let state = node::State::new(node_config, &consensus_consts, initial_time);
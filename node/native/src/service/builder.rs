@@ -1,11 +1,18 @@
-use ledger::proofs::provers::BlockProver;
+use std::{path::PathBuf, sync::Arc};
+
+use ledger::proofs::provers::{BlockProver, TransactionProver, ZkappProver};
 use node::{
-    account::AccountSecretKey, core::thread, p2p::identity::SecretKey as P2pSecretKey,
+    account::AccountSecretKey,
+    core::thread,
+    ledger::{LedgerArchive, LedgerStorage},
+    p2p::{identity::SecretKey as P2pSecretKey, service_impl::address_book::P2pAddressBookStorage},
     service::Recorder,
+    snark_pool::SnarkPoolStorage,
 };
 pub use openmina_node_common::NodeServiceCommonBuildError;
 use openmina_node_common::{
-    p2p::TaskSpawner, rpc::RpcSender, EventSender, NodeServiceCommonBuilder,
+    block_producer::BlockProducerKeyBackend, p2p::TaskSpawner, rpc::RpcSender,
+    snark_worker::SnarkWorkerBackend, EventSender, NodeServiceCommonBuilder,
 };
 
 use crate::{http_server, NodeService, P2pTaskSpawner};
@@ -44,6 +51,17 @@ impl NodeServiceBuilder {
         self
     }
 
+    pub fn ledger_init_with(
+        &mut self,
+        storage: Option<LedgerStorage>,
+        archive: Option<LedgerArchive>,
+        on_disk_ledgers_dir: Option<PathBuf>,
+    ) -> &mut Self {
+        self.common
+            .ledger_init_with(storage, archive, on_disk_ledgers_dir);
+        self
+    }
+
     pub fn block_producer_init(
         &mut self,
         provers: BlockProver,
@@ -53,6 +71,45 @@ impl NodeServiceBuilder {
         self
     }
 
+    pub fn block_producer_add_key(&mut self, keypair: AccountSecretKey) -> &mut Self {
+        self.common.block_producer_add_key(keypair);
+        self
+    }
+
+    pub fn block_producer_init_with_backend(
+        &mut self,
+        provers: BlockProver,
+        key: Arc<dyn BlockProducerKeyBackend>,
+    ) -> &mut Self {
+        self.common.block_producer_init_with_backend(provers, key);
+        self
+    }
+
+    pub fn block_producer_add_key_backend(
+        &mut self,
+        key: Arc<dyn BlockProducerKeyBackend>,
+    ) -> &mut Self {
+        self.common.block_producer_add_key_backend(key);
+        self
+    }
+
+    pub fn snark_worker_init(
+        &mut self,
+        tx_prover: TransactionProver,
+        zkapp_prover: ZkappProver,
+    ) -> &mut Self {
+        self.common.snark_worker_init(tx_prover, zkapp_prover);
+        self
+    }
+
+    pub fn snark_worker_init_with_backend(
+        &mut self,
+        worker: Box<dyn SnarkWorkerBackend>,
+    ) -> &mut Self {
+        self.common.snark_worker_init_with_backend(worker);
+        self
+    }
+
     pub fn p2p_init(&mut self, secret_key: P2pSecretKey) -> &mut Self {
         self.common.p2p_init(secret_key, P2pTaskSpawner {});
         self
@@ -67,6 +124,16 @@ impl NodeServiceBuilder {
         self
     }
 
+    pub fn p2p_address_book(&mut self, storage: P2pAddressBookStorage) -> &mut Self {
+        self.common.p2p_address_book(storage);
+        self
+    }
+
+    pub fn snark_pool_storage(&mut self, storage: SnarkPoolStorage) -> &mut Self {
+        self.common.snark_pool_storage(storage);
+        self
+    }
+
     pub fn gather_stats(&mut self) -> &mut Self {
         self.common.gather_stats();
         self
@@ -1,6 +1,6 @@
 use std::io;
 use std::mem::size_of;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 
@@ -20,15 +20,13 @@ use mina_p2p_messages::{
 use node::core::channels::{mpsc, oneshot};
 use node::event_source::Event;
 use node::external_snark_worker::{
-    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerService,
-    ExternalSnarkWorkerWorkError, SnarkWorkSpec,
+    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerWorkError, SnarkWorkSpec,
 };
+use openmina_node_common::{snark_worker::SnarkWorkerBackend, EventSender};
 
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
-use super::NodeService;
-
 /// Error generated by external snarker controller.
 #[derive(Debug, thiserror::Error)]
 enum SnarkerError {
@@ -111,7 +109,7 @@ where
 
 /// Facade for external worker process.
 pub struct ExternalSnarkWorkerFacade {
-    data_chan: mpsc::Sender<SnarkWorkSpec>,
+    data_chan: mpsc::Sender<(SnarkWorkSpec, CurrencyFeeStableV1)>,
     cancel_chan: mpsc::Sender<()>,
     kill_chan: oneshot::Sender<()>,
 }
@@ -201,21 +199,15 @@ macro_rules! send_event {
 
 impl ExternalSnarkWorkerFacade {
     fn start(
+        path: &Path,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
         event_sender: mpsc::UnboundedSender<Event>,
     ) -> Result<Self, SnarkerError> {
-        let path = std::env::var_os("MINA_EXE_PATH")
-            .or_else(|| {
-                std::env::var_os("CARGO_MANIFEST_DIR")
-                    .map(|dir| Path::new(&dir).join("bin/snark-worker").into_os_string())
-            })
-            .unwrap();
         let (data_chan, mut data_rx) = mpsc::channel(1);
         let (cancel_chan, mut cancel_rx) = mpsc::channel(1);
         let (kill_chan, kill_rx) = oneshot::channel();
 
-        let metadata = std::fs::File::open(&path)?.metadata()?;
+        let metadata = std::fs::File::open(path)?.metadata()?;
         if !metadata.is_file() {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file").into());
         }
@@ -282,13 +274,13 @@ impl ExternalSnarkWorkerFacade {
                             }
 
                             loop {
-                                let Some(spec) = data_rx.recv().await else {
+                                let Some((spec, fee)) = data_rx.recv().await else {
                                     return;
                                 };
                                 let request = ExternalSnarkWorkerRequest::perform_job(
                                     spec,
                                     public_key.clone(),
-                                    fee.clone(),
+                                    fee,
                                 );
                                 if let Err(err) = write_binprot(request, &mut child_stdin).await {
                                     send_event!(event_sender_clone, err.into());
@@ -329,7 +321,7 @@ impl ExternalSnarkWorkerFacade {
                                 if cancel_rx.recv().await.is_none() {
                                     return;
                                 }
-                                println!("sending cancel signal to {pid}...");
+                                node::core::log::debug!(node::core::log::system_time(); "sending cancel signal to {pid}...");
                                 if let Err(err) =
                                     nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGINT)
                                 {
@@ -375,9 +367,13 @@ impl ExternalSnarkWorkerFacade {
             .map_err(|_| SnarkerError::Broken("already cancelled".into()))
     }
 
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), SnarkerError> {
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
+    ) -> Result<(), SnarkerError> {
         self.data_chan
-            .try_send(spec)
+            .try_send((spec, fee))
             .map_err(|_| SnarkerError::Busy)
     }
 
@@ -388,60 +384,65 @@ impl ExternalSnarkWorkerFacade {
     }
 }
 
-impl ExternalSnarkWorkerService for NodeService {
+/// Snark worker running the `mina` snark worker executable as a child
+/// process, which is spawned when the worker is started.
+pub struct ExternalSnarkWorker {
+    path: PathBuf,
+    facade: Option<ExternalSnarkWorkerFacade>,
+}
+
+impl ExternalSnarkWorker {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, facade: None }
+    }
+}
+
+impl SnarkWorkerBackend for ExternalSnarkWorker {
     fn start(
         &mut self,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
-    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        if self.common.replayer.is_some() {
-            return Ok(());
-        }
-        let cmd_sender =
-            ExternalSnarkWorkerFacade::start(public_key, fee, self.common.event_sender().clone())?;
-        self.snark_worker_sender = Some(cmd_sender);
+        _fee: CurrencyFeeStableV1,
+        event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError> {
+        let facade =
+            ExternalSnarkWorkerFacade::start(&self.path, public_key, event_sender.clone())?;
+        self.facade = Some(facade);
         Ok(())
     }
 
     fn submit(
         &mut self,
         spec: SnarkWorkSpec,
-    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        if self.common.replayer.is_some() {
-            return Ok(());
-        }
-        self.snark_worker_sender
+        fee: CurrencyFeeStableV1,
+        _event_sender: &EventSender,
+    ) -> Result<(), ExternalSnarkWorkerError> {
+        self.facade
             .as_mut()
             .ok_or(SnarkerError::NotRunning)
-            .and_then(|sender| sender.submit(spec))?;
+            .and_then(|facade| facade.submit(spec, fee))?;
         Ok(())
     }
 
-    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
-        if self.common.replayer.is_some() {
-            return Ok(());
-        }
-        self.snark_worker_sender
+    fn cancel(&mut self, _event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError> {
+        self.facade
             .as_mut()
             .ok_or(SnarkerError::NotRunning)
-            .and_then(|sender| sender.cancel())?;
+            .and_then(|facade| facade.cancel())?;
         Ok(())
     }
 
-    fn kill(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        if self.common.replayer.is_some() {
-            return Ok(());
-        }
-        self.snark_worker_sender
+    fn kill(&mut self, _event_sender: &EventSender) -> Result<(), ExternalSnarkWorkerError> {
+        self.facade
             .take()
             .ok_or(SnarkerError::NotRunning)
-            .and_then(|sender| sender.kill())?;
+            .and_then(|facade| facade.kill())?;
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::{Path, PathBuf};
     use std::time::Duration;
 
     use mina_p2p_messages::binprot::BinProtRead;
@@ -459,6 +460,17 @@ mod tests {
 
     use super::ExternalSnarkWorkerFacade;
 
+    /// Path to the `mina` snark worker executable.
+    fn exe_path() -> PathBuf {
+        std::env::var_os("MINA_EXE_PATH")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("CARGO_MANIFEST_DIR")
+                    .map(|dir| Path::new(&dir).join("bin/snark-worker"))
+            })
+            .unwrap()
+    }
+
     macro_rules! expect_event {
         ($source:expr, $event:pat) => {
             let result = $source.recv().await.expect("failed to receive an event");
@@ -474,16 +486,9 @@ mod tests {
     #[tokio::test]
     async fn test_kill() {
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let cmd_sender = ExternalSnarkWorkerFacade::start(
-            NonZeroCurvePoint::default(),
-            CurrencyFeeStableV1(
-                mina_p2p_messages::v2::UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
-                    10_u64.into(),
-                ),
-            ),
-            event_tx,
-        )
-        .unwrap();
+        let cmd_sender =
+            ExternalSnarkWorkerFacade::start(&exe_path(), NonZeroCurvePoint::default(), event_tx)
+                .unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
@@ -514,11 +519,12 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender =
+            ExternalSnarkWorkerFacade::start(&exe_path(), public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -532,11 +538,12 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender =
+            ExternalSnarkWorkerFacade::start(&exe_path(), public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -548,7 +555,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -562,11 +569,12 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender =
+            ExternalSnarkWorkerFacade::start(&exe_path(), public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -578,7 +586,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -590,7 +598,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -1,6 +1,9 @@
 mod builder;
 pub use builder::*;
 
+mod ext_snark_worker;
+pub use ext_snark_worker::ExternalSnarkWorker;
+
 use openmina_node_common::p2p::TaskSpawner;
 pub use openmina_node_common::NodeService;
 
@@ -27,6 +27,8 @@ use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::channels::best_tip_effectful::P2pChannelsBestTipEffectfulAction;
 use crate::p2p::channels::rpc::P2pChannelsRpcAction;
 use crate::p2p::channels::rpc_effectful::P2pChannelsRpcEffectfulAction;
+use crate::p2p::channels::signaling_relay::P2pChannelsSignalingRelayAction;
+use crate::p2p::channels::signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction;
 use crate::p2p::channels::snark::P2pChannelsSnarkAction;
 use crate::p2p::channels::snark_effectful::P2pChannelsSnarkEffectfulAction;
 use crate::p2p::channels::snark_job_commitment::P2pChannelsSnarkJobCommitmentAction;
@@ -65,6 +67,7 @@ use crate::p2p::network::select::P2pNetworkSelectAction;
 use crate::p2p::network::yamux::P2pNetworkYamuxAction;
 use crate::p2p::network::P2pNetworkAction;
 use crate::p2p::peer::P2pPeerAction;
+use crate::p2p::reputation::P2pReputationAction;
 use crate::p2p::{P2pAction, P2pInitializeAction};
 use crate::rpc::RpcAction;
 use crate::snark::block_verify::SnarkBlockVerifyAction;
@@ -108,10 +111,13 @@ pub enum ActionKind {
     BlockProducerBlockInject,
     BlockProducerBlockInjected,
     BlockProducerBlockProduced,
+    BlockProducerBlockProveError,
     BlockProducerBlockProveInit,
     BlockProducerBlockProvePending,
     BlockProducerBlockProveSuccess,
     BlockProducerBlockUnprovenBuild,
+    BlockProducerDryRunFinish,
+    BlockProducerDryRunInit,
     BlockProducerStagedLedgerDiffCreateInit,
     BlockProducerStagedLedgerDiffCreatePending,
     BlockProducerStagedLedgerDiffCreateSuccess,
@@ -135,6 +141,7 @@ pub enum ActionKind {
     BlockProducerVrfEvaluatorInitializeEpochEvaluation,
     BlockProducerVrfEvaluatorInitializeEvaluator,
     BlockProducerVrfEvaluatorInterruptEpochEvaluation,
+    BlockProducerVrfEvaluatorProcessSlotEvaluationError,
     BlockProducerVrfEvaluatorProcessSlotEvaluationSuccess,
     BlockProducerVrfEvaluatorSelectInitialSlot,
     BlockProducerVrfEvaluatorWaitForNextEvaluation,
@@ -197,6 +204,16 @@ pub enum ActionKind {
     P2pChannelsRpcEffectfulInit,
     P2pChannelsRpcEffectfulRequestSend,
     P2pChannelsRpcEffectfulResponseSend,
+    P2pChannelsSignalingRelayAnswerReceived,
+    P2pChannelsSignalingRelayAnswerSend,
+    P2pChannelsSignalingRelayInit,
+    P2pChannelsSignalingRelayOfferReceived,
+    P2pChannelsSignalingRelayOfferSend,
+    P2pChannelsSignalingRelayPending,
+    P2pChannelsSignalingRelayReady,
+    P2pChannelsSignalingRelayEffectfulAnswerSend,
+    P2pChannelsSignalingRelayEffectfulInit,
+    P2pChannelsSignalingRelayEffectfulOfferSend,
     P2pChannelsSnarkInit,
     P2pChannelsSnarkLibp2pBroadcast,
     P2pChannelsSnarkLibp2pReceived,
@@ -347,6 +364,7 @@ pub enum ActionKind {
     P2pNetworkPubsubBroadcast,
     P2pNetworkPubsubBroadcastSigned,
     P2pNetworkPubsubGraft,
+    P2pNetworkPubsubHeartbeat,
     P2pNetworkPubsubIncomingData,
     P2pNetworkPubsubIncomingMessage,
     P2pNetworkPubsubNewStream,
@@ -356,6 +374,7 @@ pub enum ActionKind {
     P2pNetworkPubsubPrune,
     P2pNetworkPubsubSign,
     P2pNetworkPubsubSignError,
+    P2pNetworkPubsubValidateIncomingMessage,
     P2pNetworkPubsubEffectfulIncomingData,
     P2pNetworkPubsubEffectfulSign,
     P2pNetworkRpcHeartbeatSend,
@@ -414,8 +433,16 @@ pub enum ActionKind {
     P2pPeerDiscovered,
     P2pPeerReady,
     P2pPeerRemove,
+    P2pReputationBan,
+    P2pReputationBanNotifyFinish,
+    P2pReputationPenalize,
+    P2pReputationUnban,
     RpcActionStatsGet,
     RpcBestChain,
+    RpcBlockProducerDryRunError,
+    RpcBlockProducerDryRunInit,
+    RpcBlockProducerDryRunSuccess,
+    RpcBlockProducerScheduleGet,
     RpcBlockProducerStatsGet,
     RpcConsensusConstantsGet,
     RpcDiscoveryBoostrapStats,
@@ -423,10 +450,18 @@ pub enum ActionKind {
     RpcFinish,
     RpcGlobalStateGet,
     RpcHealthCheck,
+    RpcLedgerAccountProofGetInit,
+    RpcLedgerAccountProofGetPending,
+    RpcLedgerAccountProofGetSuccess,
     RpcLedgerAccountsGetInit,
     RpcLedgerAccountsGetPending,
     RpcLedgerAccountsGetSuccess,
+    RpcLedgerExportInit,
+    RpcLedgerExportPending,
+    RpcLedgerExportSuccess,
     RpcMessageProgressGet,
+    RpcP2pBan,
+    RpcP2pBansGet,
     RpcP2pConnectionIncomingError,
     RpcP2pConnectionIncomingInit,
     RpcP2pConnectionIncomingPending,
@@ -436,6 +471,7 @@ pub enum ActionKind {
     RpcP2pConnectionOutgoingInit,
     RpcP2pConnectionOutgoingPending,
     RpcP2pConnectionOutgoingSuccess,
+    RpcP2pUnban,
     RpcPeersGet,
     RpcReadinessCheck,
     RpcScanStateSummaryGetInit,
@@ -447,6 +483,7 @@ pub enum ActionKind {
     RpcSnarkerConfigGet,
     RpcSnarkerJobCommit,
     RpcSnarkerJobSpec,
+    RpcSnarkerJobsGet,
     RpcSnarkerWorkersGet,
     RpcStatusGet,
     RpcSyncStatsGet,
@@ -456,8 +493,15 @@ pub enum ActionKind {
     RpcTransactionInjectRejected,
     RpcTransactionInjectSuccess,
     RpcTransactionPool,
+    RpcTransactionSimulateInit,
+    RpcTransactionSimulatePending,
+    RpcTransactionSimulateSuccess,
     RpcTransactionStatusGet,
+    RpcTransitionFrontierTipsGet,
     RpcTransitionFrontierUserCommandsGet,
+    RpcWatchedAccountsAdd,
+    RpcWatchedAccountsGet,
+    RpcWatchedAccountsRemove,
     SnarkBlockVerifyError,
     SnarkBlockVerifyFinish,
     SnarkBlockVerifyInit,
@@ -485,6 +529,7 @@ pub enum ActionKind {
     SnarkPoolCandidateWorkVerifyPending,
     SnarkPoolCandidateWorkVerifySuccess,
     SnarkPoolEffectfulSnarkPoolJobsRandomChoose,
+    SnarkPoolEffectfulSnarkPoolRestore,
     SnarkUserCommandVerifyError,
     SnarkUserCommandVerifyFinish,
     SnarkUserCommandVerifyInit,
@@ -509,7 +554,15 @@ pub enum ActionKind {
     TransactionPoolStartVerifyWithAccounts,
     TransactionPoolVerifyError,
     TransactionPoolEffectfulFetchAccounts,
+    TransitionFrontierForkApplyError,
+    TransitionFrontierForkApplyInit,
+    TransitionFrontierForkApplyPending,
+    TransitionFrontierForkApplySuccess,
     TransitionFrontierGenesisInject,
+    TransitionFrontierRestoreError,
+    TransitionFrontierRestoreInit,
+    TransitionFrontierRestorePending,
+    TransitionFrontierRestoreSuccess,
     TransitionFrontierSyncFailed,
     TransitionFrontierSynced,
     TransitionFrontierGenesisLedgerLoadInit,
@@ -587,6 +640,7 @@ pub enum ActionKind {
     TransitionFrontierSyncLedgerStagedReconstructSuccess,
     TransitionFrontierSyncLedgerStagedSuccess,
     WatchedAccountsAdd,
+    WatchedAccountsBestChainUpdate,
     WatchedAccountsBlockLedgerQueryInit,
     WatchedAccountsBlockLedgerQueryPending,
     WatchedAccountsBlockLedgerQuerySuccess,
@@ -595,11 +649,12 @@ pub enum ActionKind {
     WatchedAccountsLedgerInitialStateGetPending,
     WatchedAccountsLedgerInitialStateGetRetry,
     WatchedAccountsLedgerInitialStateGetSuccess,
+    WatchedAccountsRemove,
     WatchedAccountsTransactionsIncludedInBlock,
 }
 
 impl ActionKind {
-    pub const COUNT: u16 = 493;
+    pub const COUNT: u16 = 545;
 }
 
 impl std::fmt::Display for ActionKind {
@@ -659,6 +714,7 @@ impl ActionKindGet for P2pAction {
             Self::Channels(a) => a.kind(),
             Self::ChannelsEffectful(a) => a.kind(),
             Self::Peer(a) => a.kind(),
+            Self::Reputation(a) => a.kind(),
             Self::Network(a) => a.kind(),
         }
     }
@@ -710,8 +766,16 @@ impl ActionKindGet for TransitionFrontierAction {
             Self::GenesisEffect(a) => a.kind(),
             Self::Sync(a) => a.kind(),
             Self::GenesisInject => ActionKind::TransitionFrontierGenesisInject,
+            Self::RestoreInit => ActionKind::TransitionFrontierRestoreInit,
+            Self::RestorePending => ActionKind::TransitionFrontierRestorePending,
+            Self::RestoreSuccess { .. } => ActionKind::TransitionFrontierRestoreSuccess,
+            Self::RestoreError { .. } => ActionKind::TransitionFrontierRestoreError,
             Self::Synced { .. } => ActionKind::TransitionFrontierSynced,
             Self::SyncFailed { .. } => ActionKind::TransitionFrontierSyncFailed,
+            Self::ForkApplyInit { .. } => ActionKind::TransitionFrontierForkApplyInit,
+            Self::ForkApplyPending { .. } => ActionKind::TransitionFrontierForkApplyPending,
+            Self::ForkApplySuccess { .. } => ActionKind::TransitionFrontierForkApplySuccess,
+            Self::ForkApplyError { .. } => ActionKind::TransitionFrontierForkApplyError,
         }
     }
 }
@@ -740,6 +804,7 @@ impl ActionKindGet for SnarkPoolEffectfulAction {
             Self::SnarkPoolJobsRandomChoose { .. } => {
                 ActionKind::SnarkPoolEffectfulSnarkPoolJobsRandomChoose
             }
+            Self::SnarkPoolRestore => ActionKind::SnarkPoolEffectfulSnarkPoolRestore,
         }
     }
 }
@@ -825,9 +890,12 @@ impl ActionKindGet for BlockProducerAction {
             Self::BlockProveInit => ActionKind::BlockProducerBlockProveInit,
             Self::BlockProvePending => ActionKind::BlockProducerBlockProvePending,
             Self::BlockProveSuccess { .. } => ActionKind::BlockProducerBlockProveSuccess,
+            Self::BlockProveError { .. } => ActionKind::BlockProducerBlockProveError,
             Self::BlockProduced => ActionKind::BlockProducerBlockProduced,
             Self::BlockInject => ActionKind::BlockProducerBlockInject,
             Self::BlockInjected => ActionKind::BlockProducerBlockInjected,
+            Self::DryRunInit { .. } => ActionKind::BlockProducerDryRunInit,
+            Self::DryRunFinish { .. } => ActionKind::BlockProducerDryRunFinish,
         }
     }
 }
@@ -840,8 +908,15 @@ impl ActionKindGet for RpcAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcBlockProducerStatsGet,
+            Self::BlockProducerScheduleGet { .. } => ActionKind::RpcBlockProducerScheduleGet,
+            Self::BlockProducerDryRunInit { .. } => ActionKind::RpcBlockProducerDryRunInit,
+            Self::BlockProducerDryRunSuccess { .. } => ActionKind::RpcBlockProducerDryRunSuccess,
+            Self::BlockProducerDryRunError { .. } => ActionKind::RpcBlockProducerDryRunError,
             Self::MessageProgressGet { .. } => ActionKind::RpcMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcPeersGet,
+            Self::P2pBansGet { .. } => ActionKind::RpcP2pBansGet,
+            Self::P2pBan { .. } => ActionKind::RpcP2pBan,
+            Self::P2pUnban { .. } => ActionKind::RpcP2pUnban,
             Self::P2pConnectionOutgoingInit { .. } => ActionKind::RpcP2pConnectionOutgoingInit,
             Self::P2pConnectionOutgoingPending { .. } => {
                 ActionKind::RpcP2pConnectionOutgoingPending
@@ -872,6 +947,7 @@ impl ActionKindGet for RpcAction {
             Self::SnarkerConfigGet { .. } => ActionKind::RpcSnarkerConfigGet,
             Self::SnarkerJobCommit { .. } => ActionKind::RpcSnarkerJobCommit,
             Self::SnarkerJobSpec { .. } => ActionKind::RpcSnarkerJobSpec,
+            Self::SnarkerJobsGet { .. } => ActionKind::RpcSnarkerJobsGet,
             Self::SnarkerWorkersGet { .. } => ActionKind::RpcSnarkerWorkersGet,
             Self::HealthCheck { .. } => ActionKind::RpcHealthCheck,
             Self::ReadinessCheck { .. } => ActionKind::RpcReadinessCheck,
@@ -881,6 +957,16 @@ impl ActionKindGet for RpcAction {
             Self::LedgerAccountsGetInit { .. } => ActionKind::RpcLedgerAccountsGetInit,
             Self::LedgerAccountsGetPending { .. } => ActionKind::RpcLedgerAccountsGetPending,
             Self::LedgerAccountsGetSuccess { .. } => ActionKind::RpcLedgerAccountsGetSuccess,
+            Self::LedgerAccountProofGetInit { .. } => ActionKind::RpcLedgerAccountProofGetInit,
+            Self::LedgerAccountProofGetPending { .. } => {
+                ActionKind::RpcLedgerAccountProofGetPending
+            }
+            Self::LedgerAccountProofGetSuccess { .. } => {
+                ActionKind::RpcLedgerAccountProofGetSuccess
+            }
+            Self::LedgerExportInit { .. } => ActionKind::RpcLedgerExportInit,
+            Self::LedgerExportPending { .. } => ActionKind::RpcLedgerExportPending,
+            Self::LedgerExportSuccess { .. } => ActionKind::RpcLedgerExportSuccess,
             Self::TransactionInjectInit { .. } => ActionKind::RpcTransactionInjectInit,
             Self::TransactionInjectPending { .. } => ActionKind::RpcTransactionInjectPending,
             Self::TransactionInjectSuccess { .. } => ActionKind::RpcTransactionInjectSuccess,
@@ -889,9 +975,16 @@ impl ActionKindGet for RpcAction {
             Self::TransitionFrontierUserCommandsGet { .. } => {
                 ActionKind::RpcTransitionFrontierUserCommandsGet
             }
+            Self::TransitionFrontierTipsGet { .. } => ActionKind::RpcTransitionFrontierTipsGet,
             Self::BestChain { .. } => ActionKind::RpcBestChain,
             Self::ConsensusConstantsGet { .. } => ActionKind::RpcConsensusConstantsGet,
             Self::TransactionStatusGet { .. } => ActionKind::RpcTransactionStatusGet,
+            Self::TransactionSimulateInit { .. } => ActionKind::RpcTransactionSimulateInit,
+            Self::TransactionSimulatePending { .. } => ActionKind::RpcTransactionSimulatePending,
+            Self::TransactionSimulateSuccess { .. } => ActionKind::RpcTransactionSimulateSuccess,
+            Self::WatchedAccountsAdd { .. } => ActionKind::RpcWatchedAccountsAdd,
+            Self::WatchedAccountsRemove { .. } => ActionKind::RpcWatchedAccountsRemove,
+            Self::WatchedAccountsGet { .. } => ActionKind::RpcWatchedAccountsGet,
             Self::Finish { .. } => ActionKind::RpcFinish,
         }
     }
@@ -901,6 +994,7 @@ impl ActionKindGet for WatchedAccountsAction {
     fn kind(&self) -> ActionKind {
         match self {
             Self::Add { .. } => ActionKind::WatchedAccountsAdd,
+            Self::Remove { .. } => ActionKind::WatchedAccountsRemove,
             Self::LedgerInitialStateGetInit { .. } => {
                 ActionKind::WatchedAccountsLedgerInitialStateGetInit
             }
@@ -919,6 +1013,7 @@ impl ActionKindGet for WatchedAccountsAction {
             Self::TransactionsIncludedInBlock { .. } => {
                 ActionKind::WatchedAccountsTransactionsIncludedInBlock
             }
+            Self::BestChainUpdate { .. } => ActionKind::WatchedAccountsBestChainUpdate,
             Self::BlockLedgerQueryInit { .. } => ActionKind::WatchedAccountsBlockLedgerQueryInit,
             Self::BlockLedgerQueryPending { .. } => {
                 ActionKind::WatchedAccountsBlockLedgerQueryPending
@@ -990,6 +1085,7 @@ impl ActionKindGet for P2pChannelsAction {
             Self::Transaction(a) => a.kind(),
             Self::Snark(a) => a.kind(),
             Self::SnarkJobCommitment(a) => a.kind(),
+            Self::SignalingRelay(a) => a.kind(),
             Self::Rpc(a) => a.kind(),
             Self::StreamingRpc(a) => a.kind(),
         }
@@ -1003,6 +1099,7 @@ impl ActionKindGet for P2pChannelsEffectfulAction {
             Self::Rpc(a) => a.kind(),
             Self::Snark(a) => a.kind(),
             Self::SnarkJobCommitment(a) => a.kind(),
+            Self::SignalingRelay(a) => a.kind(),
             Self::StreamingRpc(a) => a.kind(),
             Self::Transaction(a) => a.kind(),
         }
@@ -1020,6 +1117,17 @@ impl ActionKindGet for P2pPeerAction {
     }
 }
 
+impl ActionKindGet for P2pReputationAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Penalize { .. } => ActionKind::P2pReputationPenalize,
+            Self::Ban { .. } => ActionKind::P2pReputationBan,
+            Self::BanNotifyFinish { .. } => ActionKind::P2pReputationBanNotifyFinish,
+            Self::Unban { .. } => ActionKind::P2pReputationUnban,
+        }
+    }
+}
+
 impl ActionKindGet for P2pNetworkAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1224,6 +1332,9 @@ impl ActionKindGet for BlockProducerVrfEvaluatorAction {
             Self::ProcessSlotEvaluationSuccess { .. } => {
                 ActionKind::BlockProducerVrfEvaluatorProcessSlotEvaluationSuccess
             }
+            Self::ProcessSlotEvaluationError { .. } => {
+                ActionKind::BlockProducerVrfEvaluatorProcessSlotEvaluationError
+            }
             Self::InitializeEvaluator { .. } => {
                 ActionKind::BlockProducerVrfEvaluatorInitializeEvaluator
             }
@@ -1461,6 +1572,20 @@ impl ActionKindGet for P2pChannelsStreamingRpcAction {
     }
 }
 
+impl ActionKindGet for P2pChannelsSignalingRelayAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Init { .. } => ActionKind::P2pChannelsSignalingRelayInit,
+            Self::Pending { .. } => ActionKind::P2pChannelsSignalingRelayPending,
+            Self::Ready { .. } => ActionKind::P2pChannelsSignalingRelayReady,
+            Self::OfferSend { .. } => ActionKind::P2pChannelsSignalingRelayOfferSend,
+            Self::OfferReceived { .. } => ActionKind::P2pChannelsSignalingRelayOfferReceived,
+            Self::AnswerSend { .. } => ActionKind::P2pChannelsSignalingRelayAnswerSend,
+            Self::AnswerReceived { .. } => ActionKind::P2pChannelsSignalingRelayAnswerReceived,
+        }
+    }
+}
+
 impl ActionKindGet for P2pChannelsBestTipEffectfulAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1481,6 +1606,16 @@ impl ActionKindGet for P2pChannelsRpcEffectfulAction {
     }
 }
 
+impl ActionKindGet for P2pChannelsSignalingRelayEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Init { .. } => ActionKind::P2pChannelsSignalingRelayEffectfulInit,
+            Self::OfferSend { .. } => ActionKind::P2pChannelsSignalingRelayEffectfulOfferSend,
+            Self::AnswerSend { .. } => ActionKind::P2pChannelsSignalingRelayEffectfulAnswerSend,
+        }
+    }
+}
+
 impl ActionKindGet for P2pChannelsSnarkEffectfulAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1681,6 +1816,10 @@ impl ActionKindGet for P2pNetworkPubsubAction {
             Self::NewStream { .. } => ActionKind::P2pNetworkPubsubNewStream,
             Self::IncomingData { .. } => ActionKind::P2pNetworkPubsubIncomingData,
             Self::IncomingMessage { .. } => ActionKind::P2pNetworkPubsubIncomingMessage,
+            Self::ValidateIncomingMessage { .. } => {
+                ActionKind::P2pNetworkPubsubValidateIncomingMessage
+            }
+            Self::Heartbeat => ActionKind::P2pNetworkPubsubHeartbeat,
             Self::Graft { .. } => ActionKind::P2pNetworkPubsubGraft,
             Self::Prune { .. } => ActionKind::P2pNetworkPubsubPrune,
             Self::Broadcast { .. } => ActionKind::P2pNetworkPubsubBroadcast,
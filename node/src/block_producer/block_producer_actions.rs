@@ -4,6 +4,8 @@ use openmina_core::block::ArcBlockWithHash;
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
+use crate::rpc::{RpcBlockProducerDryRun, RpcId};
+
 use super::vrf_evaluator::BlockProducerVrfEvaluatorAction;
 use super::{
     BlockProducerCurrentState, BlockProducerWonSlot, BlockProducerWonSlotDiscardReason,
@@ -25,6 +27,7 @@ pub enum BlockProducerAction {
         level = info,
         fields(
             slot = won_slot.global_slot.slot_number.as_u32(),
+            producer = display(&won_slot.producer),
             slot_time = openmina_core::log::to_rfc_3339(won_slot.slot_time)
                 .unwrap_or_else(|_| "<error>".to_owned()),
             current_time = openmina_core::log::to_rfc_3339(context.timestamp())
@@ -60,10 +63,34 @@ pub enum BlockProducerAction {
     BlockProveSuccess {
         proof: Box<MinaBaseProofStableV2>,
     },
+    #[action_event(level = warn, fields(error))]
+    BlockProveError {
+        error: String,
+    },
     BlockProduced,
     #[action_event(level = trace)]
     BlockInject,
     BlockInjected,
+    /// Produce a block for the `won_slot` right away, without injecting
+    /// or broadcasting it.
+    #[action_event(
+        level = info,
+        fields(
+            slot = won_slot.global_slot.slot_number.as_u32(),
+            producer = display(&won_slot.producer),
+            prove,
+        )
+    )]
+    DryRunInit {
+        rpc_id: RpcId,
+        won_slot: BlockProducerWonSlot,
+        prove: bool,
+    },
+    #[action_event(level = info, fields(success = result.is_ok()))]
+    DryRunFinish {
+        rpc_id: RpcId,
+        result: Result<Box<RpcBlockProducerDryRun>, String>,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for BlockProducerAction {
@@ -82,7 +109,7 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                     }
                     let best_tip = state.transition_frontier.best_tip()?;
                     let cur_global_slot = state.cur_global_slot()?;
-                    let next = this.vrf_evaluator.next_won_slot(cur_global_slot, best_tip);
+                    let next = this.next_won_slot(cur_global_slot, best_tip);
                     Some(next.is_some())
                 })
                 .is_some_and(|v| v),
@@ -95,6 +122,7 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                 }
 
                 this.current.won_slot_should_search()
+                    && this.config.has_producer_key(&won_slot.producer)
                     && Some(won_slot.global_slot()) >= state.cur_global_slot()
                     && won_slot > best_tip
             }),
@@ -170,6 +198,14 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                     )
                 })
             }
+            BlockProducerAction::BlockProveError { .. } => {
+                state.block_producer.with(false, |this| {
+                    matches!(
+                        this.current,
+                        BlockProducerCurrentState::BlockProvePending { .. }
+                    )
+                })
+            }
             BlockProducerAction::BlockProduced => state.block_producer.with(false, |this| {
                 matches!(
                     this.current,
@@ -183,6 +219,22 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
             BlockProducerAction::BlockInjected => state.block_producer.with(false, |this| {
                 matches!(this.current, BlockProducerCurrentState::Produced { .. })
             }),
+            BlockProducerAction::DryRunInit { won_slot, .. } => {
+                state.block_producer.with(false, |this| {
+                    let Some(best_tip) = state.transition_frontier.best_tip() else {
+                        return false;
+                    };
+                    this.dry_run.is_none()
+                        && this.current.can_start_dry_run(time)
+                        && this.config.has_producer_key(&won_slot.producer)
+                        && won_slot > best_tip
+                })
+            }
+            BlockProducerAction::DryRunFinish { rpc_id, .. } => {
+                state.block_producer.with(false, |this| {
+                    this.dry_run.as_ref().map(|v| v.rpc_id) == Some(*rpc_id)
+                })
+            }
             BlockProducerAction::WonSlotDiscard { reason } => {
                 let current_reason = state.block_producer.with(None, |bp| {
                     let best_tip = state.transition_frontier.best_tip()?;
@@ -6,6 +6,16 @@ pub struct BlockProducerConfig {
     pub pub_key: NonZeroCurvePoint,
     pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
     pub proposed_protocol_version: Option<ProtocolVersionStableV2>,
+    /// Producer keys that this node produces blocks for, in addition
+    /// to `pub_key`.
+    #[serde(default)]
+    pub additional_keys: Vec<BlockProducerKeyConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerKeyConfig {
+    pub pub_key: NonZeroCurvePoint,
+    pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
 }
 
 impl BlockProducerConfig {
@@ -14,6 +24,7 @@ impl BlockProducerConfig {
             pub_key,
             custom_coinbase_receiver: None,
             proposed_protocol_version: None,
+            additional_keys: Vec::new(),
         }
     }
 
@@ -22,4 +33,82 @@ impl BlockProducerConfig {
             .as_ref()
             .unwrap_or(&self.pub_key)
     }
+
+    /// All producer keys, starting with the primary `pub_key`.
+    pub fn producer_keys(&self) -> impl Iterator<Item = &NonZeroCurvePoint> {
+        std::iter::once(&self.pub_key).chain(self.additional_keys.iter().map(|key| &key.pub_key))
+    }
+
+    pub fn has_producer_key(&self, pub_key: &NonZeroCurvePoint) -> bool {
+        self.producer_keys().any(|key| key == pub_key)
+    }
+
+    /// Coinbase receiver for the block produced with the `producer` key.
+    pub fn coinbase_receiver_of(&self, producer: &NonZeroCurvePoint) -> Option<&NonZeroCurvePoint> {
+        if producer == &self.pub_key {
+            return Some(self.coinbase_receiver());
+        }
+        self.additional_keys
+            .iter()
+            .find(|key| &key.pub_key == producer)
+            .map(BlockProducerKeyConfig::coinbase_receiver)
+    }
+
+    /// Adds an additional producer key. Does nothing if the key is
+    /// already configured.
+    pub fn add_producer_key(&mut self, key: BlockProducerKeyConfig) {
+        if !self.has_producer_key(&key.pub_key) {
+            self.additional_keys.push(key);
+        }
+    }
+}
+
+impl BlockProducerKeyConfig {
+    pub fn new(pub_key: NonZeroCurvePoint) -> Self {
+        Self {
+            pub_key,
+            custom_coinbase_receiver: None,
+        }
+    }
+
+    pub fn coinbase_receiver(&self) -> &NonZeroCurvePoint {
+        self.custom_coinbase_receiver
+            .as_ref()
+            .unwrap_or(&self.pub_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn key(s: &str) -> NonZeroCurvePoint {
+        NonZeroCurvePoint::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_coinbase_receiver_of_producer_keys() {
+        let primary = key("B62qiy32p8kAKnny8ZFwoMhYpBppM1DWVCqAPBYNcXnsAHhnfAAuXgg");
+        let additional = key("B62qnLVz8wM7MfJsuYbjFf4UWbwrUBEL5ZdawExxxFhnGXB6siqokyM");
+        let receiver = key("B62qnJcRzJpdaXvi6ok3iH7BbP3R6oZtT1C9qTyUr9hNHWRf3eUAJxC");
+
+        let mut config = BlockProducerConfig::new(primary.clone());
+        config.add_producer_key(BlockProducerKeyConfig {
+            pub_key: additional.clone(),
+            custom_coinbase_receiver: Some(receiver.clone()),
+        });
+        // already configured keys are ignored
+        config.add_producer_key(BlockProducerKeyConfig::new(additional.clone()));
+
+        assert_eq!(config.producer_keys().count(), 2);
+        assert!(config.has_producer_key(&primary));
+        assert!(config.has_producer_key(&additional));
+        assert!(!config.has_producer_key(&receiver));
+
+        assert_eq!(config.coinbase_receiver_of(&primary), Some(&primary));
+        assert_eq!(config.coinbase_receiver_of(&additional), Some(&receiver));
+        assert_eq!(config.coinbase_receiver_of(&receiver), None);
+    }
 }
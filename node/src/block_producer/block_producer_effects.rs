@@ -6,13 +6,14 @@ use openmina_core::bug_condition;
 
 use crate::account::AccountSecretKey;
 use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
+use crate::rpc::{RpcAction, RpcBlockProducerDryRun};
 use crate::transition_frontier::sync::TransitionFrontierSyncAction;
 use crate::{Store, TransactionPoolAction};
 
 use super::vrf_evaluator::{BlockProducerVrfEvaluatorAction, InterruptReason};
 use super::{
     next_epoch_first_slot, to_epoch_and_slot, BlockProducerAction, BlockProducerActionWithMeta,
-    BlockProducerCurrentState,
+    BlockProducerCurrentState, BlockProducerWonSlotDiscardReason,
 };
 
 pub fn block_producer_effects<S: crate::Service>(
@@ -102,7 +103,7 @@ pub fn block_producer_effects<S: crate::Service>(
             if let Some(won_slot) = store.state().block_producer.with(None, |bp| {
                 let best_tip = store.state().transition_frontier.best_tip()?;
                 let cur_global_slot = store.state().cur_global_slot()?;
-                bp.vrf_evaluator.next_won_slot(cur_global_slot, best_tip)
+                bp.next_won_slot(cur_global_slot, best_tip)
             }) {
                 store.dispatch(BlockProducerAction::WonSlot { won_slot });
             }
@@ -126,7 +127,8 @@ pub fn block_producer_effects<S: crate::Service>(
             store.dispatch(BlockProducerAction::StagedLedgerDiffCreateInit);
         }
         BlockProducerAction::StagedLedgerDiffCreateInit => {
-            if let Some(stats) = store.service.stats() {
+            let is_dry_run = store.state().block_producer.is_dry_run();
+            if let Some(stats) = store.service.stats().filter(|_| !is_dry_run) {
                 stats
                     .block_producer()
                     .staged_ledger_diff_create_start(meta.time());
@@ -136,11 +138,12 @@ pub fn block_producer_effects<S: crate::Service>(
                 let pred_block = state.block_producer.current_parent_chain()?.last()?;
                 let won_slot = state.block_producer.current_won_slot()?;
                 let config = state.block_producer.config()?;
+                let producer = &won_slot.producer;
                 Some((
                     won_slot,
                     pred_block,
-                    &config.pub_key,
-                    config.coinbase_receiver(),
+                    producer,
+                    config.coinbase_receiver_of(producer)?,
                 ))
             }) else {
                 return;
@@ -187,16 +190,27 @@ pub fn block_producer_effects<S: crate::Service>(
             });
         }
         BlockProducerAction::StagedLedgerDiffCreatePending => {}
-        BlockProducerAction::StagedLedgerDiffCreateSuccess { .. } => {
-            if let Some(stats) = store.service.stats() {
+        BlockProducerAction::StagedLedgerDiffCreateSuccess { output } => {
+            let is_dry_run = store.state().block_producer.is_dry_run();
+            if let Some(stats) = store.service.stats().filter(|_| !is_dry_run) {
                 stats
                     .block_producer()
-                    .staged_ledger_diff_create_end(meta.time());
+                    .staged_ledger_diff_create_end(meta.time(), &output.diff_create_log);
             }
             store.dispatch(BlockProducerAction::BlockUnprovenBuild);
         }
         BlockProducerAction::BlockUnprovenBuild => {
-            if let Some(stats) = store.service.stats() {
+            let dry_run = store
+                .state()
+                .block_producer
+                .with(None, |bp| bp.dry_run.as_ref().map(|v| (v.rpc_id, v.prove)));
+            if let Some((rpc_id, prove)) = dry_run {
+                if !prove {
+                    let result = dry_run_result(store.state(), meta.time());
+                    store.dispatch(BlockProducerAction::DryRunFinish { rpc_id, result });
+                    return;
+                }
+            } else if let Some(stats) = store.service.stats() {
                 let bp = &store.state.get().block_producer;
                 if let Some((block_hash, block)) = bp.with(None, |bp| match &bp.current {
                     BlockProducerCurrentState::BlockUnprovenBuilt {
@@ -213,9 +227,10 @@ pub fn block_producer_effects<S: crate::Service>(
             store.dispatch(BlockProducerAction::BlockProveInit);
         }
         BlockProducerAction::BlockProveInit => {
+            let is_dry_run = store.state.get().block_producer.is_dry_run();
             let service = &mut store.service;
 
-            if let Some(stats) = service.stats() {
+            if let Some(stats) = service.stats().filter(|_| !is_dry_run) {
                 stats.block_producer().proof_create_start(meta.time());
             }
             let Some((block_hash, input)) = store.state.get().block_producer.with(None, |bp| {
@@ -281,16 +296,43 @@ pub fn block_producer_effects<S: crate::Service>(
             }) else {
                 return;
             };
-            service.prove(block_hash, input);
+            service.prove(block_hash, input, is_dry_run);
             store.dispatch(BlockProducerAction::BlockProvePending);
         }
         BlockProducerAction::BlockProvePending => {}
         BlockProducerAction::BlockProveSuccess { .. } => {
+            if let Some(rpc_id) = store
+                .state()
+                .block_producer
+                .with(None, |bp| bp.dry_run.as_ref().map(|v| v.rpc_id))
+            {
+                let result = dry_run_result(store.state(), meta.time());
+                store.dispatch(BlockProducerAction::DryRunFinish { rpc_id, result });
+                return;
+            }
             if let Some(stats) = store.service.stats() {
                 stats.block_producer().proof_create_end(meta.time());
             }
             store.dispatch(BlockProducerAction::BlockProduced);
         }
+        BlockProducerAction::BlockProveError { error } => {
+            if let Some(rpc_id) = store
+                .state()
+                .block_producer
+                .with(None, |bp| bp.dry_run.as_ref().map(|v| v.rpc_id))
+            {
+                let result = Err(format!("block proving failed: {error}"));
+                store.dispatch(BlockProducerAction::DryRunFinish { rpc_id, result });
+                return;
+            }
+            if let Some(stats) = store.service.stats() {
+                stats.block_producer().discarded(
+                    meta.time(),
+                    BlockProducerWonSlotDiscardReason::BlockProveFailed,
+                );
+            }
+            store.dispatch(BlockProducerAction::WonSlotSearch);
+        }
         BlockProducerAction::BlockProduced => {
             store.dispatch(BlockProducerAction::BlockInject);
         }
@@ -324,10 +366,85 @@ pub fn block_producer_effects<S: crate::Service>(
             store.dispatch(BlockProducerAction::WonSlotSearch);
         }
         BlockProducerAction::WonSlotDiscard { reason } => {
+            if let Some(rpc_id) = store
+                .state()
+                .block_producer
+                .with(None, |bp| bp.dry_run.as_ref().map(|v| v.rpc_id))
+            {
+                let result = Err(format!("won slot discarded: {reason:?}"));
+                store.dispatch(BlockProducerAction::DryRunFinish { rpc_id, result });
+                return;
+            }
             if let Some(stats) = store.service.stats() {
                 stats.block_producer().discarded(meta.time(), reason);
             }
             store.dispatch(BlockProducerAction::WonSlotSearch);
         }
+        BlockProducerAction::DryRunInit { .. } => {
+            store.dispatch(BlockProducerAction::WonSlotTransactionsGet);
+        }
+        BlockProducerAction::DryRunFinish { rpc_id, result } => {
+            match result {
+                Ok(output) => {
+                    store.dispatch(RpcAction::BlockProducerDryRunSuccess { rpc_id, output })
+                }
+                Err(error) => store.dispatch(RpcAction::BlockProducerDryRunError { rpc_id, error }),
+            };
+
+            // Resume with the won slot we were waiting for before the dry run,
+            // unless the best tip changed in the meantime in a way that invalidates it.
+            if let Some(reason) = store.state().block_producer.with(None, |bp| {
+                if !matches!(
+                    bp.current,
+                    BlockProducerCurrentState::WonSlot { .. }
+                        | BlockProducerCurrentState::WonSlotWait { .. }
+                ) {
+                    return None;
+                }
+                let best_tip = store.state().transition_frontier.best_tip()?;
+                bp.current.won_slot_should_discard(best_tip)
+            }) {
+                store.dispatch(BlockProducerAction::WonSlotDiscard { reason });
+            } else {
+                store.dispatch(BlockProducerAction::WonSlotSearch);
+            }
+        }
     }
 }
+
+/// Block produced during the dry run, taken from the current state.
+fn dry_run_result(
+    state: &crate::State,
+    now: redux::Timestamp,
+) -> Result<Box<RpcBlockProducerDryRun>, String> {
+    state
+        .block_producer
+        .with(None, |bp| {
+            let dry_run = bp.dry_run.as_ref()?;
+            let (won_slot, block, block_hash, proof) = match &bp.current {
+                BlockProducerCurrentState::BlockUnprovenBuilt {
+                    won_slot,
+                    block,
+                    block_hash,
+                    ..
+                } => (won_slot, block, block_hash, None),
+                BlockProducerCurrentState::BlockProveSuccess {
+                    won_slot,
+                    block,
+                    block_hash,
+                    proof,
+                    ..
+                } => (won_slot, block, block_hash, Some(proof.clone())),
+                _ => return None,
+            };
+            Some(Box::new(RpcBlockProducerDryRun {
+                won_slot: won_slot.into(),
+                started_at: dry_run.started_at,
+                finished_at: now,
+                block_hash: block_hash.clone(),
+                block: block.clone(),
+                proof,
+            }))
+        })
+        .ok_or_else(|| "block production failed".to_owned())
+}
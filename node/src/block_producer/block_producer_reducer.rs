@@ -23,7 +23,8 @@ use openmina_core::{
 
 use super::{
     calc_epoch_seed, to_epoch_and_slot, BlockProducerAction, BlockProducerActionWithMetaRef,
-    BlockProducerCurrentState, BlockProducerEnabled, BlockProducerState, BlockWithoutProof,
+    BlockProducerCurrentState, BlockProducerDryRun, BlockProducerEnabled, BlockProducerState,
+    BlockProducerWonSlot, BlockProducerWonSlotDiscardReason, BlockWithoutProof,
 };
 
 impl BlockProducerState {
@@ -112,15 +113,7 @@ impl BlockProducerEnabled {
             }
             BlockProducerAction::WonSlotProduceInit => {
                 if let Some(won_slot) = self.current.won_slot() {
-                    let Some(chain) = best_chain.last().map(|best_tip| {
-                        if best_tip.global_slot() == won_slot.global_slot() {
-                            // We are producing block which replaces current best tip
-                            // instead of extending it.
-                            best_chain[..(best_chain.len() - 1)].to_vec()
-                        } else {
-                            best_chain.to_vec()
-                        }
-                    }) else {
+                    let Some(chain) = chain_to_extend(best_chain, won_slot) else {
                         return;
                     };
 
@@ -213,8 +206,12 @@ impl BlockProducerEnabled {
                 let vrf_truncated_output: ConsensusVrfOutputTruncatedStableV1 =
                     (*won_slot.vrf_output).clone().into();
                 let vrf_hash = won_slot.vrf_output.hash();
-                let block_creator = self.config.pub_key.clone();
-                let coinbase_receiver = self.config.coinbase_receiver().clone();
+                let block_creator = won_slot.producer.clone();
+                let coinbase_receiver = self
+                    .config
+                    .coinbase_receiver_of(&block_creator)
+                    .unwrap_or(&block_creator)
+                    .clone();
                 let proposed_protocol_version_opt = self.config.proposed_protocol_version.clone();
 
                 let ledger_proof_statement = ledger_proof_statement_from_emitted_proof(
@@ -511,6 +508,19 @@ impl BlockProducerEnabled {
                     };
                 }
             }
+            BlockProducerAction::BlockProveError { .. } => {
+                // Dry run returns to the previous state once it is finished.
+                if self.dry_run.is_some() {
+                    return;
+                }
+                if let Some(won_slot) = self.current.won_slot() {
+                    self.current = BlockProducerCurrentState::WonSlotDiscarded {
+                        time: meta.time(),
+                        won_slot: won_slot.clone(),
+                        reason: BlockProducerWonSlotDiscardReason::BlockProveFailed,
+                    };
+                }
+            }
             BlockProducerAction::BlockProduced => {
                 if let BlockProducerCurrentState::BlockProveSuccess {
                     won_slot,
@@ -547,10 +557,53 @@ impl BlockProducerEnabled {
                     };
                 }
             }
+            BlockProducerAction::DryRunInit {
+                rpc_id,
+                won_slot,
+                prove,
+            } => {
+                let Some(chain) = chain_to_extend(best_chain, won_slot) else {
+                    return;
+                };
+                let previous = std::mem::replace(
+                    &mut self.current,
+                    BlockProducerCurrentState::WonSlotProduceInit {
+                        time: meta.time(),
+                        won_slot: won_slot.clone(),
+                        chain,
+                    },
+                );
+                self.dry_run = Some(BlockProducerDryRun {
+                    rpc_id: *rpc_id,
+                    prove: *prove,
+                    started_at: meta.time(),
+                    previous: Box::new(previous),
+                });
+            }
+            BlockProducerAction::DryRunFinish { .. } => {
+                if let Some(dry_run) = self.dry_run.take() {
+                    self.current = *dry_run.previous;
+                }
+            }
         }
     }
 }
 
+/// Chain which the block for the `won_slot` will extend.
+fn chain_to_extend(
+    best_chain: &[AppliedBlock],
+    won_slot: &BlockProducerWonSlot,
+) -> Option<Vec<AppliedBlock>> {
+    let best_tip = best_chain.last()?;
+    if best_tip.global_slot() == won_slot.global_slot() {
+        // We are producing block which replaces current best tip
+        // instead of extending it.
+        Some(best_chain[..(best_chain.len() - 1)].to_vec())
+    } else {
+        Some(best_chain.to_vec())
+    }
+}
+
 fn next_to_staking_epoch_data(
     data: &ConsensusProofOfStakeDataEpochDataNextValueVersionedValueStableV1,
 ) -> ConsensusProofOfStakeDataEpochDataStakingValueVersionedValueStableV1 {
@@ -7,6 +7,8 @@ use mina_p2p_messages::v2::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::stats::block_producer::StagedLedgerDiffCreateLog;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StagedLedgerDiffCreateOutput {
     pub diff: StagedLedgerDiffDiffStableV2,
@@ -17,9 +19,17 @@ pub struct StagedLedgerDiffCreateOutput {
     pub pending_coinbase_update: MinaBasePendingCoinbaseUpdateStableV1,
     pub pending_coinbase_witness: MinaBasePendingCoinbaseWitnessStableV2,
     pub stake_proof_sparse_ledger: MinaBaseSparseLedgerBaseStableV2,
+    pub diff_create_log: StagedLedgerDiffCreateLog,
 }
 
 pub trait BlockProducerService {
     fn provers(&self) -> BlockProver;
-    fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>);
+    /// Creates the block proof. Proofs for a `dry_run` are never
+    /// broadcast, so they aren't recorded as proven for the block's slot.
+    fn prove(
+        &mut self,
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+        dry_run: bool,
+    );
 }
@@ -9,6 +9,7 @@ use openmina_core::{
 use serde::{Deserialize, Serialize};
 
 use crate::account::AccountPublicKey;
+use crate::rpc::RpcId;
 
 use super::{
     vrf_evaluator::BlockProducerVrfEvaluatorState, BlockProducerConfig, BlockProducerWonSlot,
@@ -26,6 +27,20 @@ pub struct BlockProducerEnabled {
     /// Blocks that were injected into transition frontier, but hasn't
     /// become our best tip yet.
     pub injected_blocks: BTreeSet<v2::StateHash>,
+    /// Block being produced on demand, without injecting or broadcasting it.
+    pub dry_run: Option<BlockProducerDryRun>,
+}
+
+/// Block production requested through rpc, for checking the producer setup
+/// ahead of the real won slot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerDryRun {
+    pub rpc_id: RpcId,
+    /// Whether the block proof should be created as well.
+    pub prove: bool,
+    pub started_at: redux::Timestamp,
+    /// State to return to, once the dry run is finished.
+    pub previous: Box<BlockProducerCurrentState>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -140,6 +155,8 @@ pub enum BlockProducerWonSlotDiscardReason {
     BestTipStakingLedgerDifferent,
     BestTipGlobalSlotHigher,
     BestTipSuperior,
+    /// Creating the block proof failed, the slot isn't retried.
+    BlockProveFailed,
 }
 
 impl BlockProducerState {
@@ -149,6 +166,7 @@ impl BlockProducerState {
             vrf_evaluator: BlockProducerVrfEvaluatorState::new(now),
             current: BlockProducerCurrentState::Idle { time: now },
             injected_blocks: Default::default(),
+            dry_run: None,
         }))
     }
 
@@ -176,14 +194,16 @@ impl BlockProducerState {
         self.with(None, |this| Some(&this.config))
     }
 
+    /// Checks if the `producer` is one of our producer keys.
     pub fn is_me(&self, producer: &v2::NonZeroCurvePoint) -> bool {
-        self.with(false, |this| producer == &this.config.pub_key)
+        self.with(false, |this| this.config.has_producer_key(producer))
     }
 
     /// Checks if the block was produced by us recently.
     pub fn is_produced_by_me(&self, block: &ArcBlockWithHash) -> bool {
         self.with(false, |this| {
-            block.producer() == &this.config.pub_key && this.injected_blocks.contains(block.hash())
+            this.config.has_producer_key(block.producer())
+                && this.injected_blocks.contains(block.hash())
         })
     }
 
@@ -191,6 +211,10 @@ impl BlockProducerState {
         self.with(false, |this| this.current.is_producing())
     }
 
+    pub fn is_dry_run(&self) -> bool {
+        self.with(false, |this| this.dry_run.is_some())
+    }
+
     pub fn current_won_slot(&self) -> Option<&BlockProducerWonSlot> {
         self.with(None, |this| this.current.won_slot())
     }
@@ -200,8 +224,11 @@ impl BlockProducerState {
     }
 
     /// Won slot that we are in the middle of producing.
+    ///
+    /// Dry runs are excluded, since their blocks won't be broadcasted.
     pub fn producing_won_slot(&self) -> Option<&BlockProducerWonSlot> {
-        self.current_won_slot().filter(|_| self.is_producing())
+        self.current_won_slot()
+            .filter(|_| self.is_producing() && !self.is_dry_run())
     }
 
     pub fn produced_block(&self) -> Option<&ArcBlockWithHash> {
@@ -223,7 +250,9 @@ impl BlockProducerState {
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         self.vrf_evaluator()?.vrf_delegator_table_inputs()
     }
 
@@ -232,6 +261,26 @@ impl BlockProducerState {
     }
 }
 
+impl BlockProducerEnabled {
+    /// Next won slot to produce a block for, skipping the slot for which
+    /// proving has just failed.
+    pub fn next_won_slot(
+        &self,
+        cur_global_slot: u32,
+        best_tip: &ArcBlockWithHash,
+    ) -> Option<BlockProducerWonSlot> {
+        let cur_global_slot = match &self.current {
+            BlockProducerCurrentState::WonSlotDiscarded {
+                won_slot,
+                reason: BlockProducerWonSlotDiscardReason::BlockProveFailed,
+                ..
+            } => cur_global_slot.max(won_slot.global_slot() + 1),
+            _ => cur_global_slot,
+        };
+        self.vrf_evaluator.next_won_slot(cur_global_slot, best_tip)
+    }
+}
+
 impl BlockProducerCurrentState {
     pub fn won_slot_should_search(&self) -> bool {
         match self {
@@ -304,6 +353,18 @@ impl BlockProducerCurrentState {
         }
     }
 
+    /// Whether we can switch to producing a block on demand. Not allowed
+    /// while producing, or if the won slot we are waiting for is too close.
+    pub fn can_start_dry_run(&self, now: redux::Timestamp) -> bool {
+        let margin = Duration::from_secs(10 * 60).as_nanos() as u64;
+        match self {
+            Self::WonSlot { won_slot, .. } | Self::WonSlotWait { won_slot, .. } => {
+                won_slot.slot_time >= now + margin
+            }
+            _ => !self.is_producing(),
+        }
+    }
+
     pub fn won_slot_should_discard(
         &self,
         best_tip: &ArcBlockWithHash,
@@ -390,3 +451,267 @@ impl Default for BlockProducerCurrentState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ledger::AccountIndex;
+    use mina_p2p_messages::{
+        bigint::BigInt,
+        v2::{EpochSeed, MinaBaseEpochSeedStableV1},
+    };
+    use redux::ActionMeta;
+    use vrf::VrfWonSlot;
+
+    use crate::account::AccountSecretKey;
+    use crate::block_producer::{vrf_evaluator::VrfWonSlotWithHash, BlockProducerAction};
+
+    use super::*;
+
+    const MINUTE: u64 = 60 * 1_000_000_000;
+
+    fn won_slot(global_slot: u32, genesis_timestamp: redux::Timestamp) -> BlockProducerWonSlot {
+        BlockProducerWonSlot::from_vrf_won_slot(&vrf_won_slot(global_slot), genesis_timestamp)
+    }
+
+    fn vrf_won_slot(global_slot: u32) -> VrfWonSlotWithHash {
+        let producer = AccountSecretKey::genesis_producer().public_key();
+        let won_slot = VrfWonSlot {
+            producer: producer.clone(),
+            winner_account: producer,
+            global_slot,
+            account_index: AccountIndex(0),
+            vrf_output: Box::new(
+                vrf::genesis_vrf(EpochSeed::from(MinaBaseEpochSeedStableV1(BigInt::zero())))
+                    .unwrap(),
+            ),
+            value_with_threshold: None,
+        };
+        let staking_ledger_hash = "jxTAZfKKDxoX4vtt68pQCWooXoVLjnfBpusaMwewrcZxsL3uWp6"
+            .parse()
+            .unwrap();
+        VrfWonSlotWithHash::new(won_slot, staking_ledger_hash)
+    }
+
+    /// Block producer waiting for the won slot `slots_ahead` slots after
+    /// the best tip.
+    fn state_waiting(
+        now: redux::Timestamp,
+        best_chain: &[AppliedBlock],
+        slots_ahead: u32,
+    ) -> BlockProducerState {
+        let producer = AccountSecretKey::genesis_producer().public_key();
+        let mut state =
+            BlockProducerState::new(now, Some(BlockProducerConfig::new(producer.into())));
+        let best_tip_slot = best_chain.last().unwrap().global_slot();
+        let won_slot = won_slot(best_tip_slot + slots_ahead, now);
+        state.0.as_mut().unwrap().current = BlockProducerCurrentState::WonSlotWait {
+            time: now,
+            won_slot,
+        };
+        state
+    }
+
+    fn dispatch(
+        state: &mut BlockProducerState,
+        now: redux::Timestamp,
+        best_chain: &[AppliedBlock],
+        action: BlockProducerAction,
+    ) {
+        let meta = ActionMeta::zero_custom(now);
+        state.reducer(meta.with_action(&action), best_chain);
+    }
+
+    fn dry_run_init(best_chain: &[AppliedBlock], rpc_id: RpcId) -> BlockProducerAction {
+        let best_tip = best_chain.last().unwrap();
+        BlockProducerAction::DryRunInit {
+            rpc_id,
+            won_slot: won_slot(best_tip.global_slot() + 1, redux::Timestamp::ZERO),
+            prove: false,
+        }
+    }
+
+    fn waiting_for(state: &BlockProducerState) -> Option<u32> {
+        state.with(None, |this| match &this.current {
+            BlockProducerCurrentState::WonSlotWait { won_slot, .. } => Some(won_slot.global_slot()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn can_start_dry_run_margin() {
+        let now = redux::Timestamp::new(1_000 * MINUTE);
+        let won_slot = won_slot(0, now);
+        let waiting = |slot_time| BlockProducerCurrentState::WonSlotWait {
+            time: now,
+            won_slot: BlockProducerWonSlot {
+                slot_time,
+                ..won_slot.clone()
+            },
+        };
+
+        assert!(waiting(now + 10 * MINUTE).can_start_dry_run(now));
+        assert!(waiting(now + 60 * MINUTE).can_start_dry_run(now));
+        assert!(!waiting(now + (10 * MINUTE - 1)).can_start_dry_run(now));
+        assert!(!waiting(now).can_start_dry_run(now));
+
+        assert!(BlockProducerCurrentState::Idle { time: now }.can_start_dry_run(now));
+        let producing = BlockProducerCurrentState::WonSlotProduceInit {
+            time: now,
+            won_slot,
+            chain: Vec::new(),
+        };
+        assert!(!producing.can_start_dry_run(now));
+    }
+
+    #[test]
+    fn dry_run_finish_restores_current() {
+        let now = redux::Timestamp::new(1_000 * MINUTE);
+        let best_chain = crate::transition_frontier::test_chain(None, 3, 0);
+        let mut state = state_waiting(now, &best_chain, 10);
+        let waiting_slot = waiting_for(&state).unwrap();
+        let rpc_id = RpcId::new_unchecked(0, 1);
+
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            dry_run_init(&best_chain, rpc_id),
+        );
+        assert!(state.is_dry_run());
+        assert!(state.is_producing());
+        // Dry run doesn't count as the real production.
+        assert_eq!(state.producing_won_slot(), None);
+        assert_eq!(
+            state.current_parent_chain().unwrap().len(),
+            best_chain.len()
+        );
+
+        let result = Err("test".to_owned());
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::DryRunFinish { rpc_id, result },
+        );
+        assert!(!state.is_dry_run());
+        assert_eq!(waiting_for(&state), Some(waiting_slot));
+    }
+
+    #[test]
+    fn won_slot_discard_during_dry_run() {
+        let now = redux::Timestamp::new(1_000 * MINUTE);
+        let best_chain = crate::transition_frontier::test_chain(None, 3, 0);
+        let mut state = state_waiting(now, &best_chain, 10);
+        let waiting_slot = waiting_for(&state).unwrap();
+        let rpc_id = RpcId::new_unchecked(0, 1);
+
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            dry_run_init(&best_chain, rpc_id),
+        );
+        let reason = BlockProducerWonSlotDiscardReason::BestTipSuperior;
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::WonSlotDiscard { reason },
+        );
+        // Only the dry run block is discarded.
+        assert!(state.is_dry_run());
+        assert!(state.with(false, |this| matches!(
+            this.current,
+            BlockProducerCurrentState::WonSlotDiscarded { .. }
+        )));
+        assert_ne!(
+            state.current_won_slot().unwrap().global_slot(),
+            waiting_slot
+        );
+
+        let result = Err("won slot discarded".to_owned());
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::DryRunFinish { rpc_id, result },
+        );
+        assert!(!state.is_dry_run());
+        assert_eq!(waiting_for(&state), Some(waiting_slot));
+    }
+
+    #[test]
+    fn block_prove_error_skips_slot() {
+        let now = redux::Timestamp::new(1_000 * MINUTE);
+        let best_chain = crate::transition_frontier::test_chain(None, 3, 0);
+        let best_tip = best_chain.last().unwrap();
+        let mut state = state_waiting(now, &best_chain, 10);
+        let failed_slot = waiting_for(&state).unwrap();
+        let next_slot = failed_slot + 5;
+        let this = state.0.as_mut().unwrap();
+        for slot in [failed_slot, next_slot] {
+            this.vrf_evaluator
+                .won_slots
+                .insert(slot, vrf_won_slot(slot));
+        }
+        let cur_global_slot = best_tip.global_slot();
+        let next_won_slot = |state: &BlockProducerState| {
+            state.with(None, |this| {
+                this.next_won_slot(cur_global_slot, best_tip)
+                    .map(|won_slot| won_slot.global_slot())
+            })
+        };
+        assert_eq!(next_won_slot(&state), Some(failed_slot));
+
+        let error = "refusing to prove block".to_owned();
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::BlockProveError { error },
+        );
+        assert!(state.with(false, |this| matches!(
+            this.current,
+            BlockProducerCurrentState::WonSlotDiscarded {
+                reason: BlockProducerWonSlotDiscardReason::BlockProveFailed,
+                ..
+            }
+        )));
+        assert_eq!(next_won_slot(&state), Some(next_slot));
+    }
+
+    #[test]
+    fn block_prove_error_during_dry_run() {
+        let now = redux::Timestamp::new(1_000 * MINUTE);
+        let best_chain = crate::transition_frontier::test_chain(None, 3, 0);
+        let mut state = state_waiting(now, &best_chain, 10);
+        let waiting_slot = waiting_for(&state).unwrap();
+        let rpc_id = RpcId::new_unchecked(0, 1);
+
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            dry_run_init(&best_chain, rpc_id),
+        );
+        let error = "signer unreachable".to_owned();
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::BlockProveError { error },
+        );
+        // Left for the dry run to finish with the error.
+        assert!(state.is_dry_run());
+        assert!(state.is_producing());
+
+        let result = Err("block proving failed".to_owned());
+        dispatch(
+            &mut state,
+            now,
+            &best_chain,
+            BlockProducerAction::DryRunFinish { rpc_id, result },
+        );
+        assert_eq!(waiting_for(&state), Some(waiting_slot));
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use crate::account::AccountPublicKey;
@@ -36,6 +37,13 @@ pub enum BlockProducerVrfEvaluatorAction {
         vrf_output: VrfEvaluationOutput,
         staking_ledger_hash: LedgerHash,
     },
+    /// Evaluation failed, because the key backend returned an error.
+    #[action_event(level = warn, fields(global_slot, error))]
+    ProcessSlotEvaluationError {
+        global_slot: u32,
+        staking_ledger_hash: LedgerHash,
+        error: String,
+    },
     #[action_event(level = trace)]
     InitializeEvaluator { best_tip: ArcBlockWithHash },
     /// Checking possible Vrf evaluations.
@@ -63,7 +71,7 @@ pub enum BlockProducerVrfEvaluatorAction {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Constructing delegator table.
     #[action_event(level = info)]
@@ -141,6 +149,20 @@ impl redux::EnablingCondition<crate::State> for BlockProducerVrfEvaluatorAction
                     false
                 }
             }),
+            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError {
+                global_slot,
+                staking_ledger_hash,
+                ..
+            } => state.block_producer.with(false, |this| {
+                this.vrf_evaluator.is_slot_requested()
+                    && this
+                        .vrf_evaluator
+                        .current_evaluation()
+                        .is_some_and(|current_evaluation| {
+                            current_evaluation.latest_evaluated_slot + 1 == *global_slot
+                                && current_evaluation.epoch_data.ledger == *staking_ledger_hash
+                        })
+            }),
             BlockProducerVrfEvaluatorAction::InitializeEvaluator { .. } => state
                 .block_producer
                 .with(false, |this| this.vrf_evaluator.is_idle()),
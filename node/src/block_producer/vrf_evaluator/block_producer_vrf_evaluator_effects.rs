@@ -28,6 +28,16 @@ impl BlockProducerVrfEvaluatorAction {
                     }
                 }
             }
+            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError { global_slot, .. } => {
+                if let Some(vrf_evaluator_state) = store.state().block_producer.vrf_evaluator() {
+                    if let Some(pending_evaluation) = vrf_evaluator_state.current_evaluation() {
+                        store.dispatch(BlockProducerVrfEvaluatorAction::CheckEpochBounds {
+                            epoch_number: pending_evaluation.epoch_number,
+                            latest_evaluated_global_slot: global_slot,
+                        });
+                    }
+                }
+            }
             BlockProducerVrfEvaluatorAction::CheckEpochBounds {
                 latest_evaluated_global_slot,
                 epoch_number,
@@ -120,7 +130,11 @@ impl BlockProducerVrfEvaluatorAction {
                         store.dispatch(
                             BlockProducerVrfEvaluatorAction::InitializeEpochEvaluation {
                                 staking_epoch_data: epoch_data,
-                                producer: config.pub_key.clone().into(),
+                                producers: config
+                                    .producer_keys()
+                                    .cloned()
+                                    .map(Into::into)
+                                    .collect(),
                                 best_tip_global_slot,
                                 best_tip_epoch,
                                 best_tip_slot,
@@ -141,13 +155,13 @@ impl BlockProducerVrfEvaluatorAction {
                 store.dispatch(BlockProducerVrfEvaluatorAction::BeginDelegatorTableConstruction);
             }
             BlockProducerVrfEvaluatorAction::BeginDelegatorTableConstruction => {
-                let (staking_ledger_hash, producer) =
+                let (staking_ledger_hash, producers) =
                     match store.state().block_producer.vrf_delegator_table_inputs() {
                         Some((v1, v2)) => (v1.clone(), v2.clone()),
                         None => return,
                     };
                 if store.dispatch(LedgerReadAction::Init {
-                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producer),
+                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producers),
                 }) {
                     // TODO(binier): have pending action.
                 } else {
@@ -1,3 +1,4 @@
+use mina_p2p_messages::v2::LedgerHash;
 use serde::{Deserialize, Serialize};
 
 use super::VrfEvaluationOutputWithHash;
@@ -5,6 +6,13 @@ use super::VrfEvaluationOutputWithHash;
 #[derive(derive_more::From, Serialize, Deserialize, Debug, Clone)]
 pub enum BlockProducerVrfEvaluatorEvent {
     Evaluated(VrfEvaluationOutputWithHash),
+    /// Key backend failed to calculate vrf outputs for the slot.
+    #[from(ignore)]
+    EvaluationFailed {
+        global_slot: u32,
+        staking_ledger_hash: LedgerHash,
+        error: String,
+    },
 }
 
 impl std::fmt::Display for BlockProducerVrfEvaluatorEvent {
@@ -14,6 +22,11 @@ impl std::fmt::Display for BlockProducerVrfEvaluatorEvent {
             Self::Evaluated(vrf_output) => {
                 write!(f, "Evaluated, {}", vrf_output)
             }
+            Self::EvaluationFailed {
+                global_slot, error, ..
+            } => {
+                write!(f, "EvaluationFailed, {global_slot}, {error}")
+            }
         }
     }
 }
@@ -38,6 +38,19 @@ impl BlockProducerVrfEvaluatorState {
                     global_slot: global_slot_evaluated,
                 }
             }
+            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError {
+                global_slot,
+                error,
+                ..
+            } => {
+                self.failed_slots.insert(*global_slot, error.clone());
+                self.set_latest_evaluated_global_slot(global_slot);
+
+                self.status = BlockProducerVrfEvaluatorStatus::SlotEvaluationReceived {
+                    time: meta.time(),
+                    global_slot: *global_slot,
+                }
+            }
             BlockProducerVrfEvaluatorAction::CheckEpochBounds {
                 epoch_number,
                 latest_evaluated_global_slot,
@@ -93,7 +106,7 @@ impl BlockProducerVrfEvaluatorState {
                 best_tip_global_slot,
                 next_epoch_first_slot,
                 staking_epoch_data,
-                producer,
+                producers,
             } => {
                 self.status = BlockProducerVrfEvaluatorStatus::ReadyToEvaluate {
                     time: meta.time(),
@@ -104,7 +117,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 }
             }
             BlockProducerVrfEvaluatorAction::BeginDelegatorTableConstruction => {
@@ -114,7 +127,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     is_current_epoch_evaluated: _,
                     is_next_epoch_evaluated: _,
@@ -130,7 +143,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 }
             }
             BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction {
@@ -142,7 +155,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     staking_epoch_ledger_hash: _,
                 } = &self.status
@@ -161,7 +174,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 }
             }
             BlockProducerVrfEvaluatorAction::BeginEpochEvaluation {
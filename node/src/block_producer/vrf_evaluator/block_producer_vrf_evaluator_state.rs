@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
@@ -15,6 +15,10 @@ pub const SLOTS_PER_EPOCH: u32 = 7140;
 pub struct BlockProducerVrfEvaluatorState {
     pub status: BlockProducerVrfEvaluatorStatus,
     pub won_slots: BTreeMap<u32, VrfWonSlotWithHash>,
+    /// Slots for which the key backend failed to calculate vrf outputs,
+    /// with the error.
+    #[serde(default)]
+    pub failed_slots: BTreeMap<u32, String>,
     pub latest_evaluated_slot: u32,
     pub genesis_timestamp: redux::Timestamp,
     last_evaluated_epoch: Option<u32>,
@@ -27,6 +31,7 @@ impl BlockProducerVrfEvaluatorState {
         Self {
             status: BlockProducerVrfEvaluatorStatus::Idle { time: now },
             won_slots: Default::default(),
+            failed_slots: Default::default(),
             latest_evaluated_slot: Default::default(),
             genesis_timestamp: redux::Timestamp::ZERO,
             last_evaluated_epoch: Default::default(),
@@ -75,6 +80,18 @@ impl BlockProducerVrfEvaluatorState {
             .find(|won_slot| won_slot > best_tip)
     }
 
+    /// Fetches the won slot for the `global_slot`, if it was won by one of our
+    /// producer keys.
+    pub fn won_slot(
+        &self,
+        global_slot: u32,
+        best_tip: &ArcBlockWithHash,
+    ) -> Option<BlockProducerWonSlot> {
+        self.won_slots.get(&global_slot).map(|won_slot| {
+            BlockProducerWonSlot::from_vrf_won_slot(won_slot, best_tip.genesis_timestamp())
+        })
+    }
+
     /// Retrieves the current epoch context.
     ///
     /// Returns:
@@ -351,16 +368,20 @@ impl BlockProducerVrfEvaluatorState {
         let cutoff_slot = self.retention_slot(current_epoch_number);
         self.won_slots
             .retain(|global_slot, _| cutoff_slot < *global_slot);
+        self.failed_slots
+            .retain(|global_slot, _| cutoff_slot < *global_slot);
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         match &self.status {
             BlockProducerVrfEvaluatorStatus::EpochDelegatorTablePending {
                 staking_epoch_ledger_hash,
-                producer,
+                producers,
                 ..
-            } => Some((staking_epoch_ledger_hash, producer)),
+            } => Some((staking_epoch_ledger_hash, producers)),
             _ => None,
         }
     }
@@ -435,7 +456,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Waiting for delegator table building
     EpochDelegatorTablePending {
@@ -447,7 +468,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Delegator table built successfully
     EpochDelegatorTableSuccess {
@@ -459,7 +480,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     InitialSlotSelection {
         time: redux::Timestamp,
@@ -663,6 +684,7 @@ mod test {
                     next_epoch_data: Box::new(DUMMY_NEXT_EPOCH_DATA.to_owned()),
                 },
                 won_slots: BTreeMap::new(),
+                failed_slots: BTreeMap::new(),
                 latest_evaluated_slot: 0,
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: None,
@@ -685,6 +707,7 @@ mod test {
                     next_epoch_data: Box::new(DUMMY_NEXT_EPOCH_DATA.to_owned()),
                 },
                 won_slots: BTreeMap::new(),
+                failed_slots: BTreeMap::new(),
                 latest_evaluated_slot: 7139,
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(0),
@@ -707,6 +730,7 @@ mod test {
                     next_epoch_data: Box::new(DUMMY_NEXT_EPOCH_DATA.to_owned()),
                 },
                 won_slots: BTreeMap::new(),
+                failed_slots: BTreeMap::new(),
                 latest_evaluated_slot: 14279,
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(1),
@@ -729,6 +753,7 @@ mod test {
                     next_epoch_data: Box::new(DUMMY_NEXT_EPOCH_DATA.to_owned()),
                 },
                 won_slots: BTreeMap::new(),
+                failed_slots: BTreeMap::new(),
                 latest_evaluated_slot: 0,
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: None,
@@ -751,6 +776,7 @@ mod test {
                     next_epoch_data: Box::new(DUMMY_NEXT_EPOCH_DATA.to_owned()),
                 },
                 won_slots: BTreeMap::new(),
+                failed_slots: BTreeMap::new(),
                 latest_evaluated_slot: 21419,
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(2),
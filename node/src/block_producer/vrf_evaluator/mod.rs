@@ -24,7 +24,10 @@ use vrf::{VrfEvaluationOutput, VrfWonSlot};
 
 use crate::account::AccountPublicKey;
 
-pub type DelegatorTable = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
+/// Delegators (including self-delegation) of each of our producer keys,
+/// keyed by the producer.
+pub type DelegatorTable = BTreeMap<AccountPublicKey, ProducerDelegatorTable>;
+pub type ProducerDelegatorTable = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct VrfEvaluatorInput {
@@ -6,11 +6,11 @@ use openmina_core::consensus::ConsensusConstants;
 use serde::{Deserialize, Serialize};
 
 use crate::account::AccountPublicKey;
-pub use crate::block_producer::BlockProducerConfig;
+pub use crate::block_producer::{BlockProducerConfig, BlockProducerKeyConfig};
 pub use crate::ledger::LedgerConfig;
 pub use crate::p2p::P2pConfig;
 pub use crate::snark::SnarkConfig;
-pub use crate::snark_pool::SnarkPoolConfig;
+pub use crate::snark_pool::{SnarkPoolConfig, SnarkerPricing};
 use crate::transition_frontier::genesis::GenesisConfig;
 pub use crate::transition_frontier::TransitionFrontierConfig;
 pub use mina_p2p_messages::v2::MinaBaseProtocolConstantsCheckedValueStableV1 as ProtocolConstants;
@@ -21,6 +21,8 @@ pub use mina_p2p_messages::v2::MinaBaseProtocolConstantsCheckedValueStableV1 as
 pub struct Config {
     pub ledger: LedgerConfig,
     pub snark: SnarkConfig,
+    #[serde(default)]
+    pub snark_pool: SnarkPoolConfig,
     pub p2p: P2pConfig,
     pub transition_frontier: TransitionFrontierConfig,
     pub block_producer: Option<BlockProducerConfig>,
@@ -39,9 +41,13 @@ pub struct GlobalConfig {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SnarkerConfig {
     pub public_key: AccountPublicKey,
+    /// Base fee, charged for a job with a single account update.
     pub fee: CurrencyFeeStableV1,
     pub strategy: SnarkerStrategy,
     pub auto_commit: bool,
+    /// Adjusts the fee for each job, see [`SnarkerPricing`].
+    #[serde(default)]
+    pub pricing: SnarkerPricing,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
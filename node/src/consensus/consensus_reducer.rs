@@ -2,11 +2,13 @@ use openmina_core::{
     block::BlockHash,
     consensus::{is_short_range_fork, long_range_fork_take, short_range_fork_take},
 };
+use p2p::{
+    reputation::{P2pPenaltyReason, P2pReputationAction},
+    P2pNetworkPubsubAction, P2pNetworkPubsubValidationResult,
+};
 use snark::block_verify::{SnarkBlockVerifyAction, SnarkBlockVerifyError};
 
-use crate::{
-    transition_frontier::sync::TransitionFrontierSyncAction, Action, State, WatchedAccountsAction,
-};
+use crate::{transition_frontier::sync::TransitionFrontierSyncAction, Action, State};
 
 use super::{
     ConsensusAction, ConsensusActionWithMetaRef, ConsensusBlockState, ConsensusBlockStatus,
@@ -87,12 +89,44 @@ impl ConsensusState {
                 }
 
                 // Dispatch
-                let hash = hash.clone();
-                let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ConsensusAction::DetectForkRange { hash });
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                pubsub_validate_block(
+                    global_state,
+                    dispatcher,
+                    hash,
+                    P2pNetworkPubsubValidationResult::Accept,
+                );
+                dispatcher.push(ConsensusAction::DetectForkRange { hash: hash.clone() });
             }
-            ConsensusAction::BlockSnarkVerifyError { .. } => {
+            ConsensusAction::BlockSnarkVerifyError { hash, .. } => {
                 // TODO: handle block verification error.
+
+                // Blocks are received as peers' best tips, so penalize
+                // every peer which sent us this block.
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                pubsub_validate_block(
+                    global_state,
+                    dispatcher,
+                    hash,
+                    P2pNetworkPubsubValidationResult::Reject,
+                );
+                let Some(p2p) = global_state.p2p.ready() else {
+                    return;
+                };
+                let senders = p2p
+                    .ready_peers_iter()
+                    .filter(|(_, peer)| {
+                        peer.best_tip
+                            .as_ref()
+                            .map_or(false, |best_tip| &best_tip.hash == hash)
+                    })
+                    .map(|(peer_id, _)| *peer_id);
+                for peer_id in senders {
+                    dispatcher.push(P2pReputationAction::Penalize {
+                        peer_id,
+                        reason: P2pPenaltyReason::InvalidBlock,
+                    });
+                }
             }
             ConsensusAction::DetectForkRange { hash } => {
                 let candidate_hash = hash;
@@ -220,17 +254,8 @@ impl ConsensusState {
 
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                let Some(block) = global_state.consensus.best_tip_block_with_hash() else {
+                if global_state.consensus.best_tip_block_with_hash().is_none() {
                     return;
-                };
-                for pub_key in global_state.watched_accounts.accounts() {
-                    dispatcher.push(WatchedAccountsAction::LedgerInitialStateGetInit {
-                        pub_key: pub_key.clone(),
-                    });
-                    dispatcher.push(WatchedAccountsAction::TransactionsIncludedInBlock {
-                        pub_key,
-                        block: block.clone(),
-                    });
                 }
 
                 transition_frontier_new_best_tip_handler(global_state, dispatcher);
@@ -260,7 +285,31 @@ impl ConsensusState {
     }
 }
 
-fn transition_frontier_new_best_tip_handler(
+/// Reports the result of the block verification for the pubsub messages
+/// which carried the block, so that it is only gossiped further if valid.
+fn pubsub_validate_block(
+    state: &State,
+    dispatcher: &mut redux::Dispatcher<Action, State>,
+    hash: &BlockHash,
+    result: P2pNetworkPubsubValidationResult,
+) {
+    let Some(p2p) = state.p2p.ready() else {
+        return;
+    };
+    for message_id in p2p
+        .network
+        .scheduler
+        .broadcast_state
+        .pending_block_messages(hash)
+    {
+        dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessage {
+            message_id: message_id.clone(),
+            result,
+        });
+    }
+}
+
+pub(crate) fn transition_frontier_new_best_tip_handler(
     state: &State,
     dispatcher: &mut redux::Dispatcher<Action, State>,
 ) {
@@ -285,7 +334,16 @@ fn transition_frontier_new_best_tip_handler(
                 let hashes = iter.rev().skip(1).rev().map(|b| b.hash().clone()).collect();
                 Some((hashes, root_block))
             } else {
-                None
+                // new best tip extends one of the known forks.
+                let mut chain = state.transition_frontier.chain_to(pred_hash)?;
+                let max_len = old_best_tip.constants().k.as_u32() as usize;
+                if chain.len() > max_len {
+                    chain.drain(..chain.len() - max_len);
+                }
+                let mut iter = chain.into_iter();
+                let root_block = iter.next()?.block_with_hash().clone();
+                let hashes = iter.map(|b| b.hash().clone()).collect();
+                Some((hashes, root_block))
             }
         })
     else {
@@ -11,6 +11,8 @@ use openmina_core::consensus::{
     ConsensusLongRangeForkDecisionReason, ConsensusShortRangeForkDecisionReason,
 };
 
+use p2p::P2pNetworkPubsubValidationResult;
+
 use crate::snark::block_verify::SnarkBlockVerifyId;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -160,6 +162,42 @@ impl ConsensusState {
         })
     }
 
+    /// Validation result for pubsub messages carrying the block `hash`,
+    /// which we already received, so it won't be verified again.
+    ///
+    /// `None` if the block is still being verified, the messages will be
+    /// validated once it's done.
+    pub fn known_block_pubsub_validation(
+        &self,
+        hash: &StateHash,
+    ) -> Option<P2pNetworkPubsubValidationResult> {
+        match self.blocks.get(hash) {
+            Some(block) if block.status.is_received() || block.status.is_pending() => None,
+            Some(_) => Some(P2pNetworkPubsubValidationResult::Accept),
+            // genesis or already pruned block
+            None => Some(P2pNetworkPubsubValidationResult::Ignore),
+        }
+    }
+
+    /// Candidates which were resolved, but not taken as the best tip.
+    pub fn fork_candidates(&self) -> impl Iterator<Item = ArcBlockWithHash> + '_ {
+        self.blocks
+            .iter()
+            .filter(|(_, candidate)| match &candidate.status {
+                ConsensusBlockStatus::ShortRangeForkResolve { decision, .. } => {
+                    !decision.use_as_best_tip()
+                }
+                ConsensusBlockStatus::LongRangeForkResolve { decision, .. } => {
+                    !decision.use_as_best_tip()
+                }
+                _ => false,
+            })
+            .map(|(hash, candidate)| BlockWithHash {
+                hash: hash.clone(),
+                block: candidate.block.clone(),
+            })
+    }
+
     pub fn is_candidate_decided_to_use_as_tip(&self, hash: &StateHash) -> bool {
         let Some(candidate) = self.blocks.get(hash) else {
             return false;
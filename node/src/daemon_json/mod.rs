@@ -7,7 +7,7 @@ pub use json_daemon::Daemon;
 pub use json_genesis::Genesis;
 pub use json_ledger::{
     build_ledger_name, Account, AccountConfigError, AccountPermissions, AccountTiming, Ledger,
-    Zkapp,
+    Zkapp, ZkappVerificationKey,
 };
 
 /// This type represents a JSON object loaded from daemon.json
@@ -65,7 +65,7 @@ mod test {
     use openmina_node_account::AccountPublicKey;
     use std::str::FromStr;
 
-    use crate::daemon_json::DaemonJson;
+    use crate::daemon_json::{Account, DaemonJson};
 
     #[test]
     fn test_daemon_json_read() {
@@ -110,4 +110,16 @@ mod test {
         assert_eq!(daemon.slot_tx_end(), None);
         assert_eq!(daemon.slot_chain_end(), None);
     }
+
+    #[test]
+    fn test_daemon_json_account_roundtrip() {
+        let test_file = std::fs::File::open("testing/data/daemon.json").unwrap();
+        let daemon_json: DaemonJson = serde_json::from_reader(test_file).unwrap();
+        let accounts = daemon_json.ledger.unwrap().accounts.unwrap();
+        for account in accounts {
+            let account = account.to_account().unwrap();
+            let exported = Account::try_from(&account).unwrap();
+            assert_eq!(exported.to_account().unwrap(), account);
+        }
+    }
 }
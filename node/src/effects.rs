@@ -7,13 +7,17 @@ use crate::external_snark_worker::external_snark_worker_effects;
 use crate::ledger::ledger_effects;
 use crate::ledger::read::LedgerReadAction;
 use crate::logger::logger_effects;
+use crate::p2p::connection::P2pConnectionService;
 use crate::p2p::node_p2p_effects;
 use crate::rpc::rpc_effects;
 use crate::snark::snark_effects;
 use crate::snark_pool::candidate::SnarkPoolCandidateAction;
 use crate::snark_pool::{snark_pool_effects, SnarkPoolAction};
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
-use crate::transition_frontier::transition_frontier_effects;
+use crate::transition_frontier::{
+    transition_frontier_effects, transition_frontier_fork_apply_next,
+};
+use crate::watched_accounts::{watched_accounts_effects, watched_accounts_ledger_reads_retry};
 use crate::{p2p_ready, Action, ActionWithMeta, ExternalSnarkWorkerAction, Service, Store};
 
 use crate::p2p::channels::rpc::{P2pChannelsRpcAction, P2pRpcRequest};
@@ -43,6 +47,10 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
                 p2p_request_transactions_if_needed(store);
                 p2p_request_snarks_if_needed(store);
             }
+            if let Some(p2p) = store.state.get().p2p.ready() {
+                store.service.address_book_save(&p2p.address_book);
+            }
+            store.service.snark_pool_save(&store.state.get().snark_pool);
 
             store.dispatch(SnarkPoolAction::CheckTimeouts);
             store.dispatch(SnarkPoolAction::P2pSendAll);
@@ -56,6 +64,9 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
             store.dispatch(BlockProducerAction::WonSlotProduceInit);
             store.dispatch(BlockProducerAction::BlockInject);
             store.dispatch(LedgerReadAction::FindTodos);
+            transition_frontier_fork_apply_next(store);
+
+            watched_accounts_ledger_reads_retry(store);
         }
         Action::EventSource(action) => {
             event_source_effects(store, meta.with_action(action));
@@ -92,8 +103,8 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
         Action::Rpc(action) => {
             rpc_effects(store, meta.with_action(action));
         }
-        Action::WatchedAccounts(_) => {
-            // Handled by reducer
+        Action::WatchedAccounts(action) => {
+            watched_accounts_effects(store, meta.with_action(action));
         }
     }
 }
@@ -7,6 +7,7 @@ pub use crate::p2p::{P2pConnectionEvent, P2pEvent};
 pub use crate::rpc::{RpcId, RpcRequest};
 pub use crate::snark::SnarkEvent;
 
+use crate::core::snark::Snark;
 use crate::transition_frontier::genesis::GenesisConfigLoaded;
 
 #[derive(derive_more::From, Serialize, Deserialize, Debug, Clone)]
@@ -19,6 +20,7 @@ pub enum Event {
     BlockProducerEvent(BlockProducerEvent),
 
     GenesisLoad(Result<GenesisConfigLoaded, String>),
+    SnarkPoolRestored(Vec<Snark>),
 }
 
 impl std::fmt::Display for Event {
@@ -35,7 +37,14 @@ impl std::fmt::Display for Event {
                     RpcRequest::ActionStatsGet(query) => write!(f, "ActionStatsGet, {query:?}"),
                     RpcRequest::SyncStatsGet(query) => write!(f, "SyncStatsGet, {query:?}"),
                     RpcRequest::BlockProducerStatsGet => write!(f, "BlockProducerStatsGet"),
+                    RpcRequest::BlockProducerScheduleGet => write!(f, "BlockProducerScheduleGet"),
+                    RpcRequest::BlockProducerDryRun(query) => {
+                        write!(f, "BlockProducerDryRun, {query:?}")
+                    }
                     RpcRequest::PeersGet => write!(f, "PeersGet"),
+                    RpcRequest::P2pBansGet => write!(f, "P2pBansGet"),
+                    RpcRequest::P2pBan(query) => write!(f, "P2pBan, {}", query.peer_id),
+                    RpcRequest::P2pUnban(peer_id) => write!(f, "P2pUnban, {peer_id}"),
                     RpcRequest::MessageProgressGet => write!(f, "MessageProgressGet"),
                     RpcRequest::P2pConnectionOutgoing(opts) => {
                         write!(f, "P2pConnectionOutgoing, {opts}")
@@ -55,6 +64,7 @@ impl std::fmt::Display for Event {
                         write!(f, "SnarkerJobCommit, {job_id}")
                     }
                     RpcRequest::SnarkerJobSpec { job_id } => write!(f, "SnarkerJobSpec, {job_id}"),
+                    RpcRequest::SnarkerJobs => write!(f, "SnarkerJobs"),
                     RpcRequest::SnarkerWorkers => write!(f, "SnarkerWorkers"),
                     RpcRequest::HealthCheck => write!(f, "HealthCheck"),
                     RpcRequest::ReadinessCheck => write!(f, "ReadinessCheck"),
@@ -68,9 +78,22 @@ impl std::fmt::Display for Event {
                     RpcRequest::TransitionFrontierUserCommandsGet => {
                         write!(f, "TransitionFrontierUserCommandsGet")
                     }
+                    RpcRequest::TransitionFrontierTipsGet => write!(f, "TransitionFrontierTipsGet"),
                     RpcRequest::BestChain(..) => write!(f, "BestChain"),
                     RpcRequest::ConsensusConstantsGet => write!(f, "ConsensusConstantsGet"),
                     RpcRequest::TransactionStatusGet(..) => write!(f, "TransactionStatusGet"),
+                    RpcRequest::TransactionSimulate(..) => write!(f, "TransactionSimulate"),
+                    RpcRequest::LedgerAccountProofGet(query) => {
+                        write!(f, "LedgerAccountProofGet, {}", query.public_key)
+                    }
+                    RpcRequest::LedgerExport(query) => write!(f, "LedgerExport, {}", query.ledger),
+                    RpcRequest::WatchedAccountsAdd(public_key) => {
+                        write!(f, "WatchedAccountsAdd, {public_key}")
+                    }
+                    RpcRequest::WatchedAccountsRemove(public_key) => {
+                        write!(f, "WatchedAccountsRemove, {public_key}")
+                    }
+                    RpcRequest::WatchedAccountsGet(_) => write!(f, "WatchedAccountsGet"),
                 }
             }
             Self::ExternalSnarkWorker(event) => {
@@ -97,6 +120,7 @@ impl std::fmt::Display for Event {
                     }
                 }
             }
+            Self::SnarkPoolRestored(snarks) => write!(f, "SnarkPoolRestored, {}", snarks.len()),
         }
     }
 }
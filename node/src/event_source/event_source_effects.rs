@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use p2p::channels::snark::P2pChannelsSnarkAction;
 use p2p::channels::streaming_rpc::P2pChannelsStreamingRpcAction;
 use p2p::channels::transaction::P2pChannelsTransactionAction;
@@ -12,12 +14,14 @@ use crate::ledger::read::LedgerReadAction;
 use crate::ledger::write::LedgerWriteAction;
 use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::channels::rpc::P2pChannelsRpcAction;
+use crate::p2p::channels::signaling_relay::P2pChannelsSignalingRelayAction;
 use crate::p2p::channels::snark_job_commitment::P2pChannelsSnarkJobCommitmentAction;
 use crate::p2p::channels::{ChannelId, P2pChannelsMessageReceivedAction};
 use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::{P2pConnectionErrorResponse, P2pConnectionResponse};
 use crate::p2p::disconnection::{P2pDisconnectionAction, P2pDisconnectionReason};
+use crate::p2p::reputation::P2P_REPUTATION_BAN_DURATION;
 use crate::p2p::P2pChannelEvent;
 #[cfg(feature = "p2p-libp2p")]
 use crate::p2p::{MioEvent, P2pNetworkSchedulerAction};
@@ -25,6 +29,7 @@ use crate::rpc::{RpcAction, RpcRequest};
 use crate::snark::block_verify::SnarkBlockVerifyAction;
 use crate::snark::work_verify::SnarkWorkVerifyAction;
 use crate::snark::SnarkEvent;
+use crate::snark_pool::SnarkPoolAction;
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
 use crate::{BlockProducerAction, ExternalSnarkWorkerAction, Service, Store};
 
@@ -213,6 +218,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                                     peer_id,
                                 });
                             }
+                            ChannelId::SignalingRelay => {
+                                store.dispatch(P2pChannelsSignalingRelayAction::Ready { peer_id });
+                            }
                             ChannelId::Rpc => {
                                 store.dispatch(P2pChannelsRpcAction::Ready { peer_id });
                             }
@@ -297,9 +305,30 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::BlockProducerStatsGet => {
                     store.dispatch(RpcAction::BlockProducerStatsGet { rpc_id });
                 }
+                RpcRequest::BlockProducerScheduleGet => {
+                    store.dispatch(RpcAction::BlockProducerScheduleGet { rpc_id });
+                }
+                RpcRequest::BlockProducerDryRun(query) => {
+                    store.dispatch(RpcAction::BlockProducerDryRunInit { rpc_id, query });
+                }
                 RpcRequest::PeersGet => {
                     store.dispatch(RpcAction::PeersGet { rpc_id });
                 }
+                RpcRequest::P2pBansGet => {
+                    store.dispatch(RpcAction::P2pBansGet { rpc_id });
+                }
+                RpcRequest::P2pBan(query) => {
+                    store.dispatch(RpcAction::P2pBan {
+                        rpc_id,
+                        peer_id: query.peer_id,
+                        duration: query
+                            .duration_secs
+                            .map_or(P2P_REPUTATION_BAN_DURATION, Duration::from_secs),
+                    });
+                }
+                RpcRequest::P2pUnban(peer_id) => {
+                    store.dispatch(RpcAction::P2pUnban { rpc_id, peer_id });
+                }
                 RpcRequest::MessageProgressGet => {
                     store.dispatch(RpcAction::MessageProgressGet { rpc_id });
                 }
@@ -327,6 +356,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::SnarkerJobSpec { job_id } => {
                     store.dispatch(RpcAction::SnarkerJobSpec { rpc_id, job_id });
                 }
+                RpcRequest::SnarkerJobs => {
+                    store.dispatch(RpcAction::SnarkerJobsGet { rpc_id });
+                }
                 RpcRequest::SnarkerWorkers => {
                     store.dispatch(RpcAction::SnarkerWorkersGet { rpc_id });
                 }
@@ -351,12 +383,30 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                         account_query,
                     });
                 }
+                RpcRequest::LedgerAccountProofGet(query) => {
+                    store.dispatch(RpcAction::LedgerAccountProofGetInit { rpc_id, query });
+                }
+                RpcRequest::LedgerExport(query) => {
+                    store.dispatch(RpcAction::LedgerExportInit { rpc_id, query });
+                }
+                RpcRequest::WatchedAccountsAdd(public_key) => {
+                    store.dispatch(RpcAction::WatchedAccountsAdd { rpc_id, public_key });
+                }
+                RpcRequest::WatchedAccountsRemove(public_key) => {
+                    store.dispatch(RpcAction::WatchedAccountsRemove { rpc_id, public_key });
+                }
+                RpcRequest::WatchedAccountsGet(public_key) => {
+                    store.dispatch(RpcAction::WatchedAccountsGet { rpc_id, public_key });
+                }
                 RpcRequest::TransactionInject(commands) => {
                     store.dispatch(RpcAction::TransactionInjectInit { rpc_id, commands });
                 }
                 RpcRequest::TransitionFrontierUserCommandsGet => {
                     store.dispatch(RpcAction::TransitionFrontierUserCommandsGet { rpc_id });
                 }
+                RpcRequest::TransitionFrontierTipsGet => {
+                    store.dispatch(RpcAction::TransitionFrontierTipsGet { rpc_id });
+                }
                 RpcRequest::BestChain(max_length) => {
                     store.dispatch(RpcAction::BestChain { rpc_id, max_length });
                 }
@@ -366,6 +416,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::TransactionStatusGet(tx) => {
                     store.dispatch(RpcAction::TransactionStatusGet { rpc_id, tx });
                 }
+                RpcRequest::TransactionSimulate(query) => {
+                    store.dispatch(RpcAction::TransactionSimulateInit { rpc_id, query });
+                }
             },
             Event::ExternalSnarkWorker(e) => match e {
                 ExternalSnarkWorkerEvent::Started => {
@@ -400,11 +453,34 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                             },
                         );
                     }
+                    BlockProducerVrfEvaluatorEvent::EvaluationFailed {
+                        global_slot,
+                        staking_ledger_hash,
+                        error,
+                    } => {
+                        store.dispatch(
+                            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError {
+                                global_slot,
+                                staking_ledger_hash,
+                                error,
+                            },
+                        );
+                    }
                 },
                 BlockProducerEvent::BlockProve(block_hash, res) => match res {
-                    Err(err) => todo!(
-                        "error while trying to produce block proof for block {block_hash} - {err}"
-                    ),
+                    Err(err)
+                        if store
+                            .state()
+                            .transition_frontier
+                            .genesis
+                            .prove_pending_block_hash()
+                            .map_or(false, |hash| hash == block_hash) =>
+                    {
+                        todo!("error while trying to produce genesis block proof {block_hash} - {err}")
+                    }
+                    Err(error) => {
+                        store.dispatch(BlockProducerAction::BlockProveError { error });
+                    }
                     Ok(proof) => {
                         if store
                             .state()
@@ -427,6 +503,13 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                     store.dispatch(TransitionFrontierGenesisAction::LedgerLoadSuccess { data });
                 }
             },
+            Event::SnarkPoolRestored(snarks) => {
+                // Only snarks for jobs of the current scan state get added.
+                let sender = store.state().p2p.my_id();
+                for snark in snarks {
+                    store.dispatch(SnarkPoolAction::WorkAdd { snark, sender });
+                }
+            }
         },
         EventSourceAction::WaitTimeout => {
             store.dispatch(CheckTimeoutsAction {});
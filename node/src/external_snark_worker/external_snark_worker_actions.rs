@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use mina_p2p_messages::v2::CurrencyFeeStableV1;
 use openmina_core::{snark::SnarkJobId, ActionEvent};
 use redux::{EnablingCondition, Timestamp};
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,8 @@ pub enum ExternalSnarkWorkerAction {
     SubmitWork {
         job_id: SnarkJobId,
         summary: JobSummary,
+        /// Fee the work is proven for, as set by the pricing policy.
+        fee: CurrencyFeeStableV1,
     },
     WorkResult {
         result: SnarkWorkResult,
@@ -102,7 +105,7 @@ impl EnablingCondition<State> for ExternalSnarkWorkerAction {
                 )
             }
             ExternalSnarkWorkerAction::WorkTimeout { now } => {
-                if let ExternalSnarkWorkerState::Working(_, summary) =
+                if let ExternalSnarkWorkerState::Working(_, summary, _) =
                     &state.external_snark_worker.0.state
                 {
                     now.checked_sub(state.external_snark_worker.0.timestamp)
@@ -4,7 +4,7 @@ use crate::{p2p_ready, snark_pool::SnarkPoolAction};
 
 use super::{
     available_job_to_snark_worker_spec, ExternalSnarkWorkerAction,
-    ExternalSnarkWorkerActionWithMeta,
+    ExternalSnarkWorkerActionWithMeta, ExternalSnarkWorkerState,
 };
 
 pub fn external_snark_worker_effects<S: crate::Service>(
@@ -47,7 +47,7 @@ pub fn external_snark_worker_effects<S: crate::Service>(
         ExternalSnarkWorkerAction::Error { .. } => {
             store.dispatch(ExternalSnarkWorkerAction::Kill);
         }
-        ExternalSnarkWorkerAction::SubmitWork { job_id, .. } => {
+        ExternalSnarkWorkerAction::SubmitWork { job_id, fee, .. } => {
             let Some(job) = store.state().snark_pool.get(&job_id) else {
                 return;
             };
@@ -61,7 +61,7 @@ pub fn external_snark_worker_effects<S: crate::Service>(
                     return;
                 }
             };
-            if let Err(err) = store.service().submit(input) {
+            if let Err(err) = store.service().submit(input, fee) {
                 store.dispatch(ExternalSnarkWorkerAction::WorkError { error: err.into() });
             }
         }
@@ -69,9 +69,14 @@ pub fn external_snark_worker_effects<S: crate::Service>(
             let Some(config) = &store.state().config.snarker else {
                 return;
             };
+            let ExternalSnarkWorkerState::WorkReady(_, _, fee) =
+                &store.state().external_snark_worker.0.state
+            else {
+                return;
+            };
             let p2p = p2p_ready!(store.state().p2p, meta.time());
             let snarker = config.public_key.clone().into();
-            let fee = config.fee.clone();
+            let fee = fee.clone();
             let snark = Snark {
                 snarker,
                 fee,
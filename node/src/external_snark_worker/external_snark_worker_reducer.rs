@@ -31,17 +31,26 @@ impl ExternalSnarkWorker {
             ExternalSnarkWorkerAction::Error { error, permanent } => {
                 self.state = ExternalSnarkWorkerState::Error(error.clone(), *permanent);
             }
-            ExternalSnarkWorkerAction::SubmitWork { job_id, summary } => {
-                self.state = ExternalSnarkWorkerState::Working(job_id.clone(), summary.clone());
+            ExternalSnarkWorkerAction::SubmitWork {
+                job_id,
+                summary,
+                fee,
+            } => {
+                self.state =
+                    ExternalSnarkWorkerState::Working(job_id.clone(), summary.clone(), fee.clone());
             }
             ExternalSnarkWorkerAction::WorkResult { result } => {
-                let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
+                let ExternalSnarkWorkerState::Working(job_id, _, fee) = &self.state else {
                     return;
                 };
-                self.state = ExternalSnarkWorkerState::WorkReady(job_id.clone(), result.clone());
+                self.state = ExternalSnarkWorkerState::WorkReady(
+                    job_id.clone(),
+                    result.clone(),
+                    fee.clone(),
+                );
             }
             ExternalSnarkWorkerAction::WorkError { error } => {
-                let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
+                let ExternalSnarkWorkerState::Working(job_id, ..) = &self.state else {
                     return;
                 };
                 self.state = ExternalSnarkWorkerState::WorkError(job_id.clone(), error.clone());
@@ -50,7 +59,7 @@ impl ExternalSnarkWorker {
                 return;
             }
             ExternalSnarkWorkerAction::CancelWork => {
-                let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
+                let ExternalSnarkWorkerState::Working(job_id, ..) = &self.state else {
                     return;
                 };
                 self.state = ExternalSnarkWorkerState::Cancelling(job_id.clone());
@@ -23,8 +23,12 @@ pub trait ExternalSnarkWorkerService {
         fee: CurrencyFeeStableV1,
     ) -> Result<(), ExternalSnarkWorkerError>;
 
-    /// Submits snark work
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError>;
+    /// Submits snark work, to be proven for the given `fee`.
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
+    ) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Cancel current work
     fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError>;
@@ -1,3 +1,4 @@
+use mina_p2p_messages::v2::CurrencyFeeStableV1;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
@@ -20,8 +21,8 @@ pub enum ExternalSnarkWorkerState {
     Starting,
 
     Idle,
-    Working(SnarkWorkId, JobSummary),
-    WorkReady(SnarkWorkId, SnarkWorkResult),
+    Working(SnarkWorkId, JobSummary, CurrencyFeeStableV1),
+    WorkReady(SnarkWorkId, SnarkWorkResult, CurrencyFeeStableV1),
     WorkError(SnarkWorkId, ExternalSnarkWorkerWorkError),
 
     Cancelling(SnarkWorkId),
@@ -57,7 +58,7 @@ impl ExternalSnarkWorkers {
 
     pub fn working_job_id(&self) -> Option<&SnarkWorkId> {
         match &self.0.state {
-            ExternalSnarkWorkerState::Working(job_id, _) => Some(job_id),
+            ExternalSnarkWorkerState::Working(job_id, ..) => Some(job_id),
             _ => None,
         }
     }
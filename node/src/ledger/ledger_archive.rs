@@ -0,0 +1,426 @@
+use std::io::{BufWriter, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use mina_p2p_messages::binprot::{BinProtWrite, Nat0};
+use mina_p2p_messages::list::List;
+use mina_p2p_messages::number::Int64;
+use mina_p2p_messages::rpc_kernel::{NeedsLength, QueryHeader};
+use mina_p2p_messages::v2::{
+    self, CurrencyFeeStableV1, MinaBaseAccountBinableArgStableV2, MinaBaseAccountIdStableV2,
+    MinaBaseTokenIdStableV2, StateHash,
+};
+use openmina_core::block::ArcBlockWithHash;
+use openmina_core::constants::constraint_constants;
+use openmina_core::{thread, NetworkConfig};
+use serde::Serialize;
+
+use crate::p2p::network::rpc::RpcMessage;
+
+/// Name of the archive process RPC which accepts new blocks.
+const SEND_ARCHIVE_DIFF_RPC: &str = "Send_archive_diff";
+/// Async RPC connection gets closed by the archive process if it doesn't
+/// receive anything for 30 seconds.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Where the archived blocks are sent to.
+#[derive(Debug, Clone)]
+pub enum ArchiveTarget {
+    /// Directory of precomputed blocks, named
+    /// `<network>-<height>-<state_hash>.json`, which can be imported with
+    /// `mina-archive-blocks --precomputed`.
+    PrecomputedBlocksDir(PathBuf),
+    /// Address of the archive process RPC server (its `--server-port`).
+    ArchiveProcess(SocketAddr),
+}
+
+/// Block added to the best chain, along with the data from the ledger
+/// which the archive needs, but can't derive from the block itself.
+///
+/// User commands, zkApp commands and their statuses, as well as coinbases,
+/// fee transfers and statuses of these internal commands are all part of
+/// the block's staged ledger diff.
+#[derive(Debug)]
+pub struct ArchivedBlock {
+    pub block: ArcBlockWithHash,
+    /// Accounts accessed by the block's transactions, as they are after
+    /// applying the block, along with their ledger index.
+    pub accounts_accessed: Vec<(u64, MinaBaseAccountBinableArgStableV2)>,
+    /// Accounts created by the block and the account creation fee.
+    pub accounts_created: Vec<(MinaBaseAccountIdStableV2, CurrencyFeeStableV1)>,
+    /// Tokens of the accessed accounts and their owners.
+    pub tokens_used: Vec<(MinaBaseTokenIdStableV2, Option<MinaBaseAccountIdStableV2>)>,
+    /// Receipt chain hashes of fee payers in the parent block's ledger.
+    pub sender_receipt_chains_from_parent_ledger:
+        Vec<(MinaBaseAccountIdStableV2, v2::ReceiptChainHash)>,
+}
+
+/// Sends blocks which become part of the best chain to the archive.
+///
+/// Encoding and io happens in a separate thread, so that the ledger
+/// manager isn't blocked by it.
+pub struct LedgerArchive {
+    sender: mpsc::Sender<ArchivedBlock>,
+    /// Whether all the new blocks of the first update (usually the whole
+    /// best chain) should be archived, not just the best tip.
+    backfill: bool,
+    initialized: bool,
+}
+
+impl LedgerArchive {
+    pub fn new(target: ArchiveTarget, backfill: bool) -> std::io::Result<Self> {
+        if let ArchiveTarget::PrecomputedBlocksDir(dir) = &target {
+            std::fs::create_dir_all(dir)?;
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("ledger-archive".into())
+            .spawn(move || match target {
+                ArchiveTarget::PrecomputedBlocksDir(dir) => precomputed_writer_loop(&dir, receiver),
+                ArchiveTarget::ArchiveProcess(addr) => archive_process_loop(addr, receiver),
+            })?;
+
+        Ok(Self {
+            sender,
+            backfill,
+            initialized: false,
+        })
+    }
+
+    /// Number of the `new_blocks` of the best chain update which shouldn't
+    /// be archived. Without backfill, only the best tip of the first
+    /// update is archived.
+    pub(super) fn skip_count(&mut self, new_blocks: usize) -> usize {
+        let skip = match self.initialized || self.backfill {
+            true => 0,
+            false => new_blocks.saturating_sub(1),
+        };
+        self.initialized = true;
+        skip
+    }
+
+    pub(super) fn send(&self, block: ArchivedBlock) {
+        if self.sender.send(block).is_err() {
+            openmina_core::log::inner::error!("LedgerArchive::send: archive thread is gone");
+        }
+    }
+}
+
+/// Precomputed block, as produced by the OCaml node with
+/// `--precomputed-blocks-file` or uploaded to the precomputed blocks bucket.
+#[derive(Serialize)]
+struct PrecomputedBlock<'a> {
+    version: u32,
+    data: PrecomputedBlockData<'a>,
+}
+
+#[derive(Serialize)]
+struct PrecomputedBlockData<'a> {
+    scheduled_time: String,
+    protocol_state: &'a v2::MinaStateProtocolStateValueStableV2,
+    protocol_state_proof: &'a v2::MinaBaseProofStableV2,
+    staged_ledger_diff: &'a v2::StagedLedgerDiffDiffStableV2,
+    delta_transition_chain_proof: &'a (StateHash, List<v2::StateBodyHash>),
+    protocol_version: &'a v2::ProtocolVersionStableV2,
+    proposed_protocol_version: &'a Option<v2::ProtocolVersionStableV2>,
+    accounts_accessed: &'a [(u64, MinaBaseAccountBinableArgStableV2)],
+    accounts_created: &'a [(MinaBaseAccountIdStableV2, CurrencyFeeStableV1)],
+    tokens_used: &'a [(MinaBaseTokenIdStableV2, Option<MinaBaseAccountIdStableV2>)],
+}
+
+impl<'a> From<&'a ArchivedBlock> for PrecomputedBlock<'a> {
+    fn from(value: &'a ArchivedBlock) -> Self {
+        let header = value.block.header();
+        Self {
+            version: 3,
+            data: PrecomputedBlockData {
+                scheduled_time: scheduled_time_ms(&value.block).to_string(),
+                protocol_state: &header.protocol_state,
+                protocol_state_proof: &header.protocol_state_proof,
+                staged_ledger_diff: &value.block.body().staged_ledger_diff,
+                delta_transition_chain_proof: &header.delta_block_chain_proof,
+                protocol_version: &header.current_protocol_version,
+                proposed_protocol_version: &header.proposed_protocol_version_opt,
+                accounts_accessed: &value.accounts_accessed,
+                accounts_created: &value.accounts_created,
+                tokens_used: &value.tokens_used,
+            },
+        }
+    }
+}
+
+/// Start of the block's slot, in milliseconds since the unix epoch.
+fn scheduled_time_ms(block: &ArcBlockWithHash) -> u64 {
+    let genesis_timestamp_ms = u64::from(block.genesis_timestamp()) / 1_000_000;
+    let slot_duration_ms = constraint_constants().block_window_duration_ms;
+    genesis_timestamp_ms + u64::from(block.global_slot()) * slot_duration_ms
+}
+
+fn precomputed_writer_loop(dir: &Path, receiver: mpsc::Receiver<ArchivedBlock>) {
+    let network = NetworkConfig::global().name;
+    while let Ok(block) = receiver.recv() {
+        let path = dir.join(format!(
+            "{network}-{}-{}.json",
+            block.block.height(),
+            block.block.hash()
+        ));
+        let result = std::fs::File::create(&path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &PrecomputedBlock::from(&block))?;
+            writer.flush()
+        });
+        if let Err(err) = result {
+            openmina_core::log::inner::error!("LedgerArchive: failed to write {path:?}: {err}");
+        }
+    }
+}
+
+/// Archive diff, as expected by the `Send_archive_diff` RPC.
+///
+/// ```ocaml
+/// type Archive_lib.Diff.t =
+///   | Transition_frontier of
+///       | Breadcrumb_added of
+///           { block : Mina_block.t State_hash.With_state_hashes.t
+///           ; accounts_accessed : (int * Account.t) list
+///           ; accounts_created : (Account_id.t * Currency.Fee.t) list
+///           ; tokens_used : (Token_id.t * Account_id.t option) list
+///           ; sender_receipt_chains_from_parent_ledger :
+///               (Account_id.t * Receipt.Chain_hash.t) list
+///           }
+///       | ...
+///   | ...
+/// ```
+struct BreadcrumbAddedDiff<'a>(&'a ArchivedBlock);
+
+impl<'a> BinProtWrite for BreadcrumbAddedDiff<'a> {
+    fn binprot_write<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        const TRANSITION_FRONTIER_TAG: u8 = 0;
+        const BREADCRUMB_ADDED_TAG: u8 = 0;
+
+        let block = self.0;
+        w.write_all(&[TRANSITION_FRONTIER_TAG, BREADCRUMB_ADDED_TAG])?;
+        // `With_state_hashes`: block, then `state_body_hash` (left for the
+        // archive process to compute) and `state_hash`.
+        block.block.block.as_ref().binprot_write(w)?;
+        Option::<v2::StateBodyHash>::None.binprot_write(w)?;
+        block.block.hash().binprot_write(w)?;
+
+        let accounts_accessed = block
+            .accounts_accessed
+            .iter()
+            .map(|(index, account)| (Int64::from(index), account.clone()))
+            .collect::<Vec<_>>();
+        write_list(w, &accounts_accessed)?;
+        write_list(w, &block.accounts_created)?;
+        write_list(w, &block.tokens_used)?;
+        write_list(w, &block.sender_receipt_chains_from_parent_ledger)
+    }
+}
+
+fn write_list<W: std::io::Write, T: BinProtWrite>(w: &mut W, items: &[T]) -> std::io::Result<()> {
+    // Same encoding as `List`, without having to copy the items into one.
+    Nat0(items.len() as u64).binprot_write(w)?;
+    items.iter().try_for_each(|item| item.binprot_write(w))
+}
+
+/// Connection to the archive process, speaking Async RPC over tcp.
+struct ArchiveProcessConnection {
+    stream: TcpStream,
+    next_query_id: u64,
+}
+
+impl ArchiveProcessConnection {
+    fn connect(addr: SocketAddr) -> std::io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(&RpcMessage::Handshake.into_bytes())?;
+        // Responses carry no information, but need to be read so that the
+        // archive process doesn't get stuck writing them.
+        let mut reader = stream.try_clone()?;
+        thread::Builder::new()
+            .name("ledger-archive-reader".into())
+            .spawn(move || std::io::copy(&mut reader, &mut std::io::sink()))?;
+
+        Ok(Self {
+            stream,
+            next_query_id: 0,
+        })
+    }
+
+    fn send(&mut self, block: &ArchivedBlock) -> std::io::Result<()> {
+        let mut bytes = Vec::new();
+        NeedsLength(BreadcrumbAddedDiff(block)).binprot_write(&mut bytes)?;
+        let message = RpcMessage::Query {
+            header: QueryHeader {
+                tag: SEND_ARCHIVE_DIFF_RPC.into(),
+                version: 0,
+                id: self.next_query_id,
+            },
+            bytes: bytes.into(),
+        };
+        self.next_query_id += 1;
+        self.stream.write_all(&message.into_bytes())
+    }
+
+    fn heartbeat(&mut self) -> std::io::Result<()> {
+        self.stream.write_all(&RpcMessage::Heartbeat.into_bytes())
+    }
+}
+
+fn archive_process_loop(addr: SocketAddr, receiver: mpsc::Receiver<ArchivedBlock>) {
+    let mut connection: Option<ArchiveProcessConnection> = None;
+    loop {
+        let block = match receiver.recv_timeout(HEARTBEAT_INTERVAL) {
+            Ok(block) => block,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(conn) = connection.as_mut() {
+                    if conn.heartbeat().is_err() {
+                        connection = None;
+                    }
+                }
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        };
+
+        // Reconnect once if the previous connection got broken.
+        for _ in 0..2 {
+            let conn = match connection.as_mut() {
+                Some(conn) => conn,
+                None => match ArchiveProcessConnection::connect(addr) {
+                    Ok(conn) => connection.insert(conn),
+                    Err(err) => {
+                        openmina_core::log::inner::error!(
+                            "LedgerArchive: failed to connect to archive process at {addr}: {err}"
+                        );
+                        break;
+                    }
+                },
+            };
+            match conn.send(&block) {
+                Ok(()) => break,
+                Err(err) => {
+                    openmina_core::log::inner::warn!(
+                        "LedgerArchive: failed to send block {} to archive process: {err}",
+                        block.block.hash()
+                    );
+                    connection = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::binprot::BinProtRead;
+
+    use super::*;
+
+    fn archived_block() -> ArchivedBlock {
+        let block = crate::transition_frontier::test_chain(None, 1, 0)[0]
+            .block_with_hash()
+            .clone();
+        let account = ledger::Account::rand();
+        let account_id = MinaBaseAccountIdStableV2::from(account.id());
+        ArchivedBlock {
+            block,
+            accounts_accessed: vec![(3, (&account).into())],
+            accounts_created: vec![(account_id.clone(), CurrencyFeeStableV1(1_000_000.into()))],
+            tokens_used: vec![((&account.token_id).into(), None)],
+            sender_receipt_chains_from_parent_ledger: vec![(
+                account_id,
+                account.receipt_chain_hash.clone().into(),
+            )],
+        }
+    }
+
+    fn read_list<T: BinProtRead>(r: &mut &[u8]) -> Vec<T> {
+        List::<T>::binprot_read(r).unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn breadcrumb_added_diff_encoding() {
+        let block = archived_block();
+        let mut bytes = Vec::new();
+        BreadcrumbAddedDiff(&block)
+            .binprot_write(&mut bytes)
+            .unwrap();
+
+        let mut r = &bytes[..];
+        assert_eq!(u8::binprot_read(&mut r).unwrap(), 0);
+        assert_eq!(u8::binprot_read(&mut r).unwrap(), 0);
+        let decoded = v2::MinaBlockBlockStableV2::binprot_read(&mut r).unwrap();
+        assert_eq!(&decoded, block.block.block.as_ref());
+        assert_eq!(
+            Option::<v2::StateBodyHash>::binprot_read(&mut r).unwrap(),
+            None
+        );
+        assert_eq!(
+            &StateHash::binprot_read(&mut r).unwrap(),
+            block.block.hash()
+        );
+
+        let accounts_accessed: Vec<(Int64, MinaBaseAccountBinableArgStableV2)> = read_list(&mut r);
+        assert_eq!(accounts_accessed.len(), 1);
+        assert_eq!(accounts_accessed[0].0, Int64::from(&3u64));
+        assert_eq!(accounts_accessed[0].1, block.accounts_accessed[0].1);
+        assert_eq!(read_list(&mut r), block.accounts_created);
+        assert_eq!(read_list(&mut r), block.tokens_used);
+        assert_eq!(
+            read_list(&mut r),
+            block.sender_receipt_chains_from_parent_ledger
+        );
+        assert!(r.is_empty(), "{} trailing bytes", r.len());
+    }
+
+    #[test]
+    fn precomputed_block_scheduled_time() {
+        let block = archived_block();
+        let json = serde_json::to_value(PrecomputedBlock::from(&block)).unwrap();
+
+        let scheduled_time_ms = scheduled_time_ms(&block.block);
+        assert_eq!(
+            json["data"]["scheduled_time"],
+            scheduled_time_ms.to_string()
+        );
+
+        // Slot start, not the time the block was produced at.
+        let genesis_timestamp_ms = u64::from(block.block.genesis_timestamp()) / 1_000_000;
+        let slot_duration_ms = constraint_constants().block_window_duration_ms;
+        assert_eq!(
+            (scheduled_time_ms - genesis_timestamp_ms) % slot_duration_ms,
+            0
+        );
+        let timestamp_ms = u64::from(block.block.timestamp()) / 1_000_000;
+        assert!(scheduled_time_ms <= timestamp_ms);
+        assert!(timestamp_ms - scheduled_time_ms < slot_duration_ms);
+    }
+
+    fn test_archive(name: &str, backfill: bool) -> LedgerArchive {
+        let dir = std::env::temp_dir().join(format!("openmina-ledger-archive-{name}"));
+        LedgerArchive::new(ArchiveTarget::PrecomputedBlocksDir(dir), backfill).unwrap()
+    }
+
+    #[test]
+    fn skip_count() {
+        let mut archive = test_archive("skip-count", false);
+        // Only the best tip of the first update.
+        assert_eq!(archive.skip_count(10), 9);
+        assert_eq!(archive.skip_count(3), 0);
+        assert_eq!(archive.skip_count(1), 0);
+
+        let mut archive = test_archive("skip-count-empty", false);
+        assert_eq!(archive.skip_count(0), 0);
+        assert_eq!(archive.skip_count(2), 0);
+    }
+
+    #[test]
+    fn skip_count_backfill() {
+        let mut archive = test_archive("skip-count-backfill", true);
+        assert_eq!(archive.skip_count(10), 0);
+        assert_eq!(archive.skip_count(3), 0);
+    }
+}
@@ -7,7 +7,11 @@ use crate::p2p::channels::rpc::{P2pChannelsRpcAction, P2pRpcId, P2pRpcResponse};
 use crate::p2p::PeerId;
 use crate::transition_frontier::sync::ledger::staged::TransitionFrontierSyncLedgerStagedAction;
 use crate::transition_frontier::sync::TransitionFrontierSyncAction;
-use crate::{BlockProducerAction, RpcAction, Store};
+use crate::transition_frontier::TransitionFrontierAction;
+use crate::watched_accounts::{
+    WatchedAccountBlockState, WatchedAccountsLedgerInitialStateGetError,
+};
+use crate::{BlockProducerAction, RpcAction, Store, WatchedAccountsAction};
 
 use super::read::{
     LedgerReadAction, LedgerReadId, LedgerReadRequest, LedgerReadResponse,
@@ -55,7 +59,8 @@ pub fn ledger_effects<S: LedgerService>(store: &mut Store<S>, action: LedgerActi
 }
 
 fn next_write_request_init<S: redux::Service>(store: &mut Store<S>) {
-    if store.dispatch(BlockProducerAction::StagedLedgerDiffCreateInit) {
+    if store.dispatch(TransitionFrontierAction::RestoreInit) {
+    } else if store.dispatch(BlockProducerAction::StagedLedgerDiffCreateInit) {
     } else if store.dispatch(TransitionFrontierSyncAction::BlocksNextApplyInit) {
     } else if store.dispatch(TransitionFrontierSyncAction::CommitInit) {
     } else if store.dispatch(TransitionFrontierSyncLedgerStagedAction::ReconstructInit) {
@@ -137,11 +142,20 @@ fn propagate_write_response<S: redux::Service>(
                 result,
             },
         ) => match result {
+            // Block is either applied by the sync or as a fork.
             Err(error) => {
-                store.dispatch(TransitionFrontierSyncAction::BlocksNextApplyError { hash, error });
+                store.dispatch(TransitionFrontierSyncAction::BlocksNextApplyError {
+                    hash: hash.clone(),
+                    error: error.clone(),
+                });
+                store.dispatch(TransitionFrontierAction::ForkApplyError { hash, error });
             }
             Ok(result) => {
                 store.dispatch(TransitionFrontierSyncAction::BlocksNextApplySuccess {
+                    hash: hash.clone(),
+                    just_emitted_a_proof: result.just_emitted_a_proof,
+                });
+                store.dispatch(TransitionFrontierAction::ForkApplySuccess {
                     hash,
                     just_emitted_a_proof: result.just_emitted_a_proof,
                 });
@@ -159,6 +173,17 @@ fn propagate_write_response<S: redux::Service>(
                 store.dispatch(TransitionFrontierSyncAction::CommitSuccess { result });
             }
         }
+        (_, LedgerWriteResponse::FrontierRestore { result }) => match result {
+            Err(error) => {
+                store.dispatch(TransitionFrontierAction::RestoreError { error });
+            }
+            Ok(result) => {
+                store.dispatch(TransitionFrontierAction::RestoreSuccess {
+                    best_chain: result.best_chain,
+                    needed_protocol_states: result.needed_protocol_states,
+                });
+            }
+        },
     }
 }
 
@@ -202,6 +227,9 @@ fn next_read_requests_init<S: redux::Service>(store: &mut Store<S>) {
                     P2pRpcRequest::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash) => {
                         build_staged_ledger_parts_request(store.state(), block_hash)?
                     }
+                    P2pRpcRequest::EpochLedger(hash) => {
+                        LedgerReadRequest::GetEpochLedger(hash.clone())
+                    }
                     _ => return None,
                 };
 
@@ -341,6 +369,9 @@ fn find_peers_with_ledger_rpc(
                         .map_or(false, |b| {
                             b.blockchain_state.staged_ledger_hash == data.ledger_hash
                         }),
+                    (LedgerReadRequest::GetEpochLedger(h1), P2pRpcRequest::EpochLedger(h2)) => {
+                        h1 == h2
+                    }
                     _ => false,
                 })
                 .map(|(peer_id, rpc_id, _)| (*peer_id, rpc_id, false));
@@ -378,12 +409,12 @@ fn propagate_read_response<S: redux::Service>(
     };
     match (request, response) {
         (
-            LedgerReadRequest::DelegatorTable(ledger_hash, pub_key),
+            LedgerReadRequest::DelegatorTable(ledger_hash, producers),
             LedgerReadResponse::DelegatorTable(table),
         ) => {
             let expected = store.state().block_producer.vrf_delegator_table_inputs();
-            if !expected.map_or(false, |(expected_hash, producer)| {
-                ledger_hash == expected_hash && pub_key == producer
+            if !expected.map_or(false, |(expected_hash, expected_producers)| {
+                ledger_hash == expected_hash && producers == expected_producers
             }) {
                 eprintln!("delegator table unexpected");
                 return;
@@ -468,6 +499,15 @@ fn propagate_read_response<S: redux::Service>(
                 }
             }
         }
+        (req, LedgerReadResponse::GetEpochLedger(resp)) => {
+            for (peer_id, id, _) in find_peers_with_ledger_rpc(store.state(), req) {
+                store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response: resp.clone().map(P2pRpcResponse::EpochLedger).map(Box::new),
+                });
+            }
+        }
         (
             LedgerReadRequest::ScanStateSummary(ledger_hash),
             LedgerReadResponse::ScanStateSummary(scan_state),
@@ -495,5 +535,69 @@ fn propagate_read_response<S: redux::Service>(
                 account_query,
             });
         }
+        (_, LedgerReadResponse::AccountProofForRpc(rpc_id, response)) => {
+            store.dispatch(RpcAction::LedgerAccountProofGetSuccess { rpc_id, response });
+        }
+        (_, LedgerReadResponse::LedgerExportForRpc(rpc_id, response)) => {
+            store.dispatch(RpcAction::LedgerExportSuccess { rpc_id, response });
+        }
+        (_, LedgerReadResponse::TransactionSimulate(rpc_id, response)) => {
+            store.dispatch(RpcAction::TransactionSimulateSuccess { rpc_id, response });
+        }
+        (
+            LedgerReadRequest::WatchedAccountGet(ledger_hash, pub_key),
+            LedgerReadResponse::WatchedAccountGet(result),
+        ) => {
+            let (ledger_hash, pub_key) = (ledger_hash.clone(), pub_key.clone());
+            propagate_watched_account_response(store, ledger_hash, pub_key, result);
+        }
+        (_, LedgerReadResponse::WatchedAccountGet(..)) => unreachable!(),
+    }
+}
+
+/// Same ledger might be requested for the initial state and for the
+/// blocks, so the response is propagated to all of them.
+fn propagate_watched_account_response<S: redux::Service>(
+    store: &mut Store<S>,
+    ledger_hash: v2::LedgerHash,
+    pub_key: v2::NonZeroCurvePoint,
+    result: Result<Option<Box<v2::MinaBaseAccountBinableArgStableV2>>, String>,
+) {
+    let Some(account) = store.state().watched_accounts.get(&pub_key) else {
+        return;
+    };
+    let is_initial_state_pending = account
+        .initial_state
+        .block()
+        .map_or(false, |block| block.staged_ledger_hash == ledger_hash);
+    let pending_blocks = account
+        .blocks
+        .iter()
+        .filter(|b| matches!(b, WatchedAccountBlockState::LedgerAccountGetPending { .. }))
+        .filter(|b| b.block().staged_ledger_hash == ledger_hash)
+        .map(|b| b.block().hash.clone())
+        .collect::<Vec<_>>();
+
+    if is_initial_state_pending {
+        match &result {
+            Ok(data) => store.dispatch(WatchedAccountsAction::LedgerInitialStateGetSuccess {
+                pub_key: pub_key.clone(),
+                data: data.clone(),
+            }),
+            Err(error) => store.dispatch(WatchedAccountsAction::LedgerInitialStateGetError {
+                pub_key: pub_key.clone(),
+                error: WatchedAccountsLedgerInitialStateGetError::LedgerReadError(error.clone()),
+            }),
+        };
+    }
+    let Ok(ledger_account) = result else {
+        return;
+    };
+    for block_hash in pending_blocks {
+        store.dispatch(WatchedAccountsAction::BlockLedgerQuerySuccess {
+            pub_key: pub_key.clone(),
+            block_hash,
+            ledger_account: ledger_account.clone(),
+        });
     }
 }
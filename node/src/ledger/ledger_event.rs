@@ -41,6 +41,9 @@ impl std::fmt::Display for LedgerEvent {
                     LedgerWriteResponse::Commit { best_tip_hash, .. } => {
                         write!(f, ", {best_tip_hash}")
                     }
+                    LedgerWriteResponse::FrontierRestore { result } => {
+                        write!(f, ", {}", res_kind_str(result))
+                    }
                 }
             }
             Self::Read(id, resp) => {
@@ -0,0 +1,86 @@
+use std::io::{Read, Write};
+
+use mina_p2p_messages::{
+    binprot::{
+        self,
+        macros::{BinProtRead, BinProtWrite},
+        BinProtRead, BinProtWrite,
+    },
+    v2::{LedgerHash, MinaBaseAccountBinableArgStableV2},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::daemon_json::{self, AccountConfigError};
+
+/// Accounts of a ledger, ordered by their index, together with the ledger
+/// hash.
+#[derive(Serialize, Deserialize, BinProtRead, BinProtWrite, Debug, Clone)]
+pub struct LedgerExport {
+    pub ledger_hash: LedgerHash,
+    pub accounts: Vec<MinaBaseAccountBinableArgStableV2>,
+}
+
+impl LedgerExport {
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, binprot::Error> {
+        Self::binprot_read(&mut reader)
+    }
+
+    pub fn store<W: Write>(&self, mut writer: W) -> Result<(), std::io::Error> {
+        self.binprot_write(&mut writer)
+    }
+
+    /// Ledger in the format of the `ledger` section of the daemon.json,
+    /// as produced by `mina ledger export`.
+    pub fn to_daemon_json(&self) -> Result<daemon_json::Ledger, AccountConfigError> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|account| {
+                let account = ledger::Account::try_from(account)
+                    .map_err(|_| AccountConfigError::InvalidBigInt)?;
+                daemon_json::Account::try_from(&account)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(daemon_json::Ledger {
+            num_accounts: Some(accounts.len()),
+            accounts: Some(accounts),
+            balances: None,
+            hash: Some(self.ledger_hash.to_string()),
+            s3_data_hash: None,
+            name: None,
+            add_genesis_winner: Some(false),
+        })
+    }
+
+    /// Reverse of [`LedgerExport::to_daemon_json`]. The ledger hash must
+    /// be present, it isn't computed here.
+    pub fn from_daemon_json(ledger: &daemon_json::Ledger) -> Result<Self, LedgerExportError> {
+        let ledger_hash = ledger
+            .hash
+            .as_deref()
+            .ok_or(LedgerExportError::NoLedgerHash)?
+            .parse()
+            .map_err(|_| LedgerExportError::InvalidLedgerHash)?;
+        let accounts = ledger
+            .accounts
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|account| Ok((&account.to_account()?).into()))
+            .collect::<Result<_, AccountConfigError>>()?;
+        Ok(Self {
+            ledger_hash,
+            accounts,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerExportError {
+    #[error("ledger hash is missing")]
+    NoLedgerHash,
+    #[error("invalid ledger hash")]
+    InvalidLedgerHash,
+    #[error("account error: {0}")]
+    Account(#[from] AccountConfigError),
+}
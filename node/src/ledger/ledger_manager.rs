@@ -1,7 +1,11 @@
 use std::collections::BTreeMap;
 
 use ledger::staged_ledger::staged_ledger::StagedLedger;
-use mina_p2p_messages::v2::{self, LedgerHash, MinaBaseAccountBinableArgStableV2};
+use mina_p2p_messages::v2::{
+    self, LedgerHash, MinaBaseAccountBinableArgStableV2, MinaStateProtocolStateValueStableV2,
+    StateHash,
+};
+use openmina_core::block::AppliedBlock;
 use openmina_core::channels::mpsc;
 use openmina_core::thread;
 
@@ -61,6 +65,15 @@ pub(super) enum LedgerRequest {
         staged_ledger_hash: LedgerHash,
         result: Result<StagedLedger, String>,
     },
+    FrontierPersist {
+        root: AppliedBlock,
+        new_blocks: Vec<AppliedBlock>,
+        needed_protocol_states: Option<BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>>,
+    },
+    FrontierArchive {
+        parent: Option<AppliedBlock>,
+        new_blocks: Vec<AppliedBlock>,
+    },
 }
 
 #[derive(Debug)]
@@ -179,20 +192,32 @@ impl LedgerRequest {
                         result,
                     }
                 }
+                LedgerWriteRequest::FrontierRestore {
+                    genesis_ledger_hash,
+                } => {
+                    let result = ledger_ctx.frontier_restore(&genesis_ledger_hash);
+                    LedgerWriteResponse::FrontierRestore { result }
+                }
             }),
             Self::Read(id, request) => LedgerResponse::Read(
                 id,
                 match request {
-                    LedgerReadRequest::DelegatorTable(ledger_hash, producer) => {
+                    LedgerReadRequest::DelegatorTable(ledger_hash, producers) => {
                         let res = ledger_ctx
                             .producers_with_delegates(&ledger_hash, |pub_key| {
-                                AccountPublicKey::from(pub_key.clone()) == producer
+                                producers.contains(&AccountPublicKey::from(pub_key.clone()))
                             })
-                            .and_then(|list| list.into_iter().next())
-                            .map(|(_, table)| {
-                                table
-                                    .into_iter()
-                                    .map(|(index, pub_key, balance)| (index, (pub_key, balance)))
+                            .map(|list| {
+                                list.into_iter()
+                                    .map(|(producer, table)| {
+                                        let table = table
+                                            .into_iter()
+                                            .map(|(index, pub_key, balance)| {
+                                                (index, (pub_key, balance))
+                                            })
+                                            .collect();
+                                        (producer, table)
+                                    })
                                     .collect()
                             });
 
@@ -217,6 +242,10 @@ impl LedgerRequest {
                         );
                         LedgerReadResponse::GetStagedLedgerAuxAndPendingCoinbases(res)
                     }
+                    LedgerReadRequest::GetEpochLedger(ledger_hash) => {
+                        let res = ledger_ctx.get_epoch_ledger(&ledger_hash);
+                        LedgerReadResponse::GetEpochLedger(res)
+                    }
                     LedgerReadRequest::ScanStateSummary(ledger_hash) => {
                         let res = ledger_ctx.scan_state_summary(&ledger_hash);
                         LedgerReadResponse::ScanStateSummary(res)
@@ -241,6 +270,22 @@ impl LedgerRequest {
 
                         LedgerReadResponse::AccountsForRpc(rpc_id, res, account_query)
                     }
+                    LedgerReadRequest::AccountProofForRpc(rpc_id, ledger_hash, account_id) => {
+                        let res = ledger_ctx.account_proof(&ledger_hash, &account_id);
+                        LedgerReadResponse::AccountProofForRpc(rpc_id, res)
+                    }
+                    LedgerReadRequest::LedgerExportForRpc(rpc_id, ledger_hash) => {
+                        let res = ledger_ctx.ledger_export(&ledger_hash);
+                        LedgerReadResponse::LedgerExportForRpc(rpc_id, res)
+                    }
+                    LedgerReadRequest::WatchedAccountGet(ledger_hash, pub_key) => {
+                        let res = ledger_ctx.watched_account_get(&ledger_hash, &pub_key);
+                        LedgerReadResponse::WatchedAccountGet(res)
+                    }
+                    LedgerReadRequest::TransactionSimulate(rpc_id, req) => {
+                        let res = ledger_ctx.transaction_simulate(&req);
+                        LedgerReadResponse::TransactionSimulate(rpc_id, res)
+                    }
                 },
             ),
             LedgerRequest::AccountsSet {
@@ -326,6 +371,18 @@ impl LedgerRequest {
                 let res = ledger_ctx.get_accounts(ledger_hash, account_ids);
                 LedgerResponse::AccountsGet(Ok(res))
             }
+            LedgerRequest::FrontierPersist {
+                root,
+                new_blocks,
+                needed_protocol_states,
+            } => {
+                ledger_ctx.frontier_persist(root, new_blocks, needed_protocol_states);
+                LedgerResponse::Success
+            }
+            LedgerRequest::FrontierArchive { parent, new_blocks } => {
+                ledger_ctx.frontier_archive(parent, new_blocks);
+                LedgerResponse::Success
+            }
         }
     }
 }
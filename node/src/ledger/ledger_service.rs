@@ -1,23 +1,31 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use super::{
+    ledger_archive::{ArchivedBlock, LedgerArchive},
     ledger_manager::{LedgerManager, LedgerRequest},
-    write::BlockApplyResult,
+    ledger_storage::{
+        FrontierUpdate, LedgerStorage, PersistedFrontier, PersistedSnarkedLedger,
+        PersistedStagedLedger,
+    },
+    write::{BlockApplyResult, FrontierRestoreResult},
 };
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use ledger::{
     scan_state::{
-        currency::Slot,
+        currency::{Fee, Magnitude, Slot},
         scan_state::{AvailableJobMessage, JobValueBase, JobValueMerge, JobValueWithIndex, Pass},
         transaction_logic::{
+            apply_transaction_first_pass, apply_transaction_second_pass,
             local_state::LocalState,
             protocol_state::{protocol_state_view, ProtocolStateView},
             transaction_partially_applied::TransactionPartiallyApplied,
-            valid, Transaction,
+            valid,
+            zkapp_command::AccessedOrNot,
+            Transaction, TransactionStatus, UserCommand, WithStatus,
         },
     },
     sparse_ledger::SparseLedger,
@@ -52,11 +60,15 @@ use openmina_core::block::ArcBlockWithHash;
 use crate::block_producer::StagedLedgerDiffCreateOutput;
 use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
 use crate::rpc::{
-    RpcScanStateSummaryBlockTransaction, RpcScanStateSummaryScanStateJob,
-    RpcScanStateSummaryScanStateJobKind, RpcSnarkPoolJobSnarkWorkDone,
+    RpcLedgerAccountProof, RpcScanStateSummaryBlockTransaction, RpcScanStateSummaryScanStateJob,
+    RpcScanStateSummaryScanStateJobKind, RpcSnarkPoolJobSnarkWorkDone, RpcTransactionSimulation,
+    RpcTransactionSimulationAccount, RpcTransactionSimulationFailure,
 };
+use crate::stats::block_producer::{StagedLedgerDiffCreateLog, StagedLedgerDiffCreateTransaction};
 use crate::transition_frontier::sync::{
-    ledger::staged::StagedLedgerAuxAndPendingCoinbasesValid,
+    ledger::staged::{
+        StagedLedgerAuxAndPendingCoinbasesValid, StagedLedgerAuxAndPendingCoinbasesValidated,
+    },
     TransitionFrontierRootSnarkedLedgerUpdates,
 };
 use crate::{account::AccountPublicKey, transition_frontier::genesis::empty_pending_coinbase_hash};
@@ -65,10 +77,10 @@ use super::write::CommitResult;
 
 use super::{
     ledger_empty_hash_at_depth, read::LedgerReadResponse, write::LedgerWriteResponse,
-    LedgerAddress, LedgerEvent, LEDGER_DEPTH,
+    LedgerAddress, LedgerEvent, LedgerExport, LEDGER_DEPTH,
 };
 use super::{
-    read::{LedgerReadId, LedgerReadRequest},
+    read::{LedgerReadId, LedgerReadRequest, LedgerReadTransactionSimulate},
     write::LedgerWriteRequest,
 };
 
@@ -80,6 +92,30 @@ fn error_to_string(e: InvalidBigInt) -> String {
     format!("{:?}", e)
 }
 
+/// Creates an empty snarked ledger, stored in a new directory under
+/// `on_disk_dir` if set, in memory otherwise. The directory is removed
+/// once the ledger is dropped.
+fn snarked_ledger_create(on_disk_dir: Option<&Path>, hash: &LedgerHash) -> Mask {
+    let Some(on_disk_dir) = on_disk_dir else {
+        return Mask::create(LEDGER_DEPTH);
+    };
+    let directory = snarked_ledger_dir(on_disk_dir, hash);
+    match Database::create_on_disk_temporary(LEDGER_DEPTH as u8, &directory) {
+        Ok(db) => Mask::new_root(db),
+        Err(err) => {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "LedgerService::snarked_ledger_create",
+                summary = format!("failed to create ledger in {directory:?}, keeping it in memory"),
+                error = err.to_string());
+            Mask::create(LEDGER_DEPTH)
+        }
+    }
+}
+
+fn snarked_ledger_dir(on_disk_dir: &Path, hash: &LedgerHash) -> PathBuf {
+    on_disk_dir.join(format!("{hash}-{}", ledger::next_uuid()))
+}
+
 /// Indexing `StagedLedger` both by their "merkle root hash" and their "staged ledger hash"
 #[derive(Default)]
 struct StagedLedgersStorage {
@@ -173,6 +209,16 @@ pub struct LedgerCtx {
     additional_snarked_ledgers: BTreeMap<LedgerHash, Mask>,
     staged_ledgers: StagedLedgersStorage,
     sync: LedgerSyncState,
+    /// On-disk storage of the transition frontier, `None` if persistence is disabled.
+    storage: Option<LedgerStorage>,
+    /// Sink for the blocks added to the best chain, `None` if archiving is disabled.
+    archive: Option<LedgerArchive>,
+    /// Directory in which snarked ledgers are stored, `None` if they are
+    /// kept in memory.
+    on_disk_ledgers_dir: Option<PathBuf>,
+    /// Sparse ledgers served by the `get_epoch_ledger` rpc, so that they
+    /// aren't rebuilt for every request.
+    epoch_ledgers_cache: BTreeMap<LedgerHash, Arc<v2::MinaBaseSparseLedgerBaseStableV2>>,
     event_sender:
         Option<openmina_core::channels::mpsc::UnboundedSender<crate::event_source::Event>>,
 }
@@ -227,6 +273,54 @@ impl LedgerCtx {
         self.event_sender = Some(event_sender);
     }
 
+    pub fn set_storage(&mut self, storage: LedgerStorage) {
+        self.storage = Some(storage);
+    }
+
+    pub fn set_archive(&mut self, archive: LedgerArchive) {
+        self.archive = Some(archive);
+    }
+
+    /// Store snarked ledgers on disk in `dir`, instead of keeping them in
+    /// memory. Ledgers left there by the previous run are removed.
+    pub fn set_on_disk_ledgers_dir(&mut self, dir: PathBuf) {
+        // Ledgers are removed when dropped, anything there is left by a
+        // crashed run.
+        let _ = std::fs::remove_dir_all(&dir);
+        self.on_disk_ledgers_dir = Some(dir);
+    }
+
+    /// Copies the in-memory `mask` to a new ledger stored on disk, if
+    /// snarked ledgers are stored on disk.
+    fn snarked_ledger_move_to_disk(&self, mask: Mask, hash: &LedgerHash) -> Mask {
+        let Some(on_disk_dir) = self.on_disk_ledgers_dir.as_deref() else {
+            return mask;
+        };
+        let directory = snarked_ledger_dir(on_disk_dir, hash);
+        let on_disk =
+            Database::create_on_disk_temporary(LEDGER_DEPTH as u8, &directory).and_then(|db| {
+                let mut on_disk = Mask::new_root(db.clone());
+                mask.iter(|account| {
+                    on_disk
+                        .get_or_create_account(account.id(), account.clone())
+                        .expect("copied accounts fit into the ledger of the same depth");
+                });
+                // Io errors of the copy are only reported by the flush.
+                db.flush()?;
+                Ok(on_disk)
+            });
+        match on_disk {
+            Ok(on_disk) => on_disk,
+            Err(err) => {
+                openmina_core::error!(openmina_core::log::system_time();
+                    kind = "LedgerService::snarked_ledger_move_to_disk",
+                    summary = format!("failed to copy ledger to {directory:?}, keeping it in memory"),
+                    error = err.to_string());
+                mask
+            }
+        }
+    }
+
     pub(super) fn send_event(&self, event: LedgerEvent) {
         if let Some(tx) = self.event_sender.as_ref() {
             let _ = tx.send(event.into());
@@ -243,6 +337,7 @@ impl LedgerCtx {
 
     pub fn insert_genesis_ledger(&mut self, mut mask: Mask) {
         let merkle_root_hash = merkle_root(&mut mask);
+        let mut mask = self.snarked_ledger_move_to_disk(mask, &merkle_root_hash);
         let staged_ledger =
             StagedLedger::create_exn(constraint_constants().clone(), mask.copy()).unwrap();
         self.snarked_ledgers.insert(merkle_root_hash.clone(), mask);
@@ -594,7 +689,10 @@ impl LedgerCtx {
     {
         let snarked_ledger = self
             .sync
-            .snarked_ledger_mut(snarked_ledger_hash.clone())?
+            .snarked_ledger_mut(
+                snarked_ledger_hash.clone(),
+                self.on_disk_ledgers_dir.as_deref(),
+            )?
             .copy();
 
         thread::Builder::new()
@@ -619,7 +717,10 @@ impl LedgerCtx {
     ) -> Result<(v2::LedgerHash, Result<(), String>), InvalidBigInt> {
         let snarked_ledger = self
             .sync
-            .snarked_ledger_mut(snarked_ledger_hash.clone())?
+            .snarked_ledger_mut(
+                snarked_ledger_hash.clone(),
+                self.on_disk_ledgers_dir.as_deref(),
+            )?
             .copy();
         let (staged_ledger_hash, result) =
             staged_ledger_reconstruct(snarked_ledger, snarked_ledger_hash, parts)?;
@@ -845,6 +946,468 @@ impl LedgerCtx {
         }
     }
 
+    /// Writes the changes of the transition frontier to the on-disk storage
+    /// (if enabled), so that it can be restored with
+    /// [`Self::frontier_restore`] after restart.
+    ///
+    /// Only `new_blocks`, which weren't in the best chain at the last call,
+    /// are written, together with snapshots of their staged ledgers.
+    /// `needed_protocol_states` are only needed if the `root` changed.
+    pub fn frontier_persist(
+        &mut self,
+        root: AppliedBlock,
+        new_blocks: Vec<AppliedBlock>,
+        needed_protocol_states: Option<BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>>,
+    ) {
+        let Some(storage) = self.storage.as_ref() else {
+            return;
+        };
+        let Some(best_tip) = new_blocks.last().cloned() else {
+            return;
+        };
+        if best_tip.is_genesis() {
+            return;
+        }
+        let root_changed = storage.root() != Some(root.hash());
+        // Blocks restored on startup are already persisted.
+        let new_blocks = new_blocks
+            .into_iter()
+            .filter(|block| !storage.contains_block(block.hash()))
+            .collect::<Vec<_>>();
+
+        // Blocks of the best chain are within two epochs, so epoch ledgers
+        // of the root and the best tip are all that's needed. Genesis
+        // ledger is loaded from the genesis config anyway.
+        let needed_snarked_ledgers = [&root, &best_tip]
+            .into_iter()
+            .flat_map(|b| [b.staking_epoch_ledger_hash(), b.next_epoch_ledger_hash()])
+            .chain([root.snarked_ledger_hash()])
+            .filter(|hash| *hash != root.genesis_ledger_hash())
+            .collect::<BTreeSet<_>>();
+        let mut snarked_ledgers = BTreeSet::new();
+        let mut new_snarked_ledgers = Vec::new();
+        for hash in needed_snarked_ledgers {
+            if storage.contains_snarked_ledger(hash) {
+                snarked_ledgers.insert(hash.clone());
+                continue;
+            }
+            if let Some(persisted) = self.snarked_ledger_to_persist(storage, hash) {
+                new_snarked_ledgers.push((hash.clone(), persisted));
+                snarked_ledgers.insert(hash.clone());
+            }
+        }
+
+        let root_staged_ledger_parts = if root_changed {
+            // Root scan state only refers to the root and the blocks before it.
+            let protocol_states = needed_protocol_states
+                .unwrap_or_default()
+                .into_iter()
+                .chain([(root.hash().clone(), root.header().protocol_state.clone())])
+                .collect();
+            let parts = self.staged_ledger_aux_and_pending_coinbase(
+                root.staged_ledger_hashes(),
+                protocol_states,
+            );
+            if parts.is_none() {
+                openmina_core::error!(openmina_core::log::system_time();
+                    kind = "LedgerService::frontier_persist",
+                    summary = format!("missing root staged ledger parts for {}", root.hash()));
+            }
+            parts
+        } else {
+            None
+        };
+
+        let new_blocks = new_blocks
+            .into_iter()
+            .map(|block| {
+                let staged_ledger = self.staged_ledger_snapshot(&block);
+                if let Err(err) = &staged_ledger {
+                    openmina_core::warn!(openmina_core::log::system_time();
+                        kind = "LedgerService::frontier_persist",
+                        summary = format!("no staged ledger snapshot for {}", block.hash()),
+                        error = err);
+                }
+                (block, staged_ledger.ok())
+            })
+            .collect();
+
+        let update = FrontierUpdate {
+            new_snarked_ledgers,
+            snarked_ledgers,
+            root_staged_ledger_parts,
+            root: root.hash().clone(),
+            new_blocks,
+        };
+        if let Some(storage) = self.storage.as_mut() {
+            storage.update(update);
+        }
+    }
+
+    /// Snapshot of the staged ledger after applying the `block`: its scan
+    /// state, pending coinbases and the accounts changed by the block.
+    fn staged_ledger_snapshot(
+        &mut self,
+        block: &AppliedBlock,
+    ) -> Result<PersistedStagedLedger, String> {
+        let accessed = block_accessed_accounts(block)?;
+        let staged_ledger = self
+            .staged_ledger_mut(block.staged_ledger_hashes())
+            .ok_or_else(|| format!("staged ledger {} not found", block.merkle_root_hash()))?;
+        let mask = staged_ledger.ledger();
+        let accounts = accessed
+            .into_iter()
+            .filter_map(|id| {
+                let index = mask.index_of_account(id)?;
+                let account = mask.get_at_index(index)?;
+                Some((index.0, (&*account).into()))
+            })
+            .collect();
+        // Required for the conversion of the pending coinbases.
+        staged_ledger.pending_coinbase_collection_merkle_root();
+        Ok(PersistedStagedLedger {
+            scan_state: staged_ledger.scan_state().into(),
+            pending_coinbase: staged_ledger.pending_coinbase_collection().into(),
+            accounts,
+        })
+    }
+
+    /// Restores the staged ledger of the `block` from its snapshot, on top
+    /// of the staged ledger of the `pred_block`.
+    fn staged_ledger_restore(
+        &mut self,
+        block: &AppliedBlock,
+        pred_block: &AppliedBlock,
+        snapshot: PersistedStagedLedger,
+    ) -> Result<(), String> {
+        let PersistedStagedLedger {
+            scan_state,
+            pending_coinbase,
+            accounts,
+        } = snapshot;
+        let mut mask = self
+            .staged_ledger_mut(pred_block.staged_ledger_hashes())
+            .ok_or_else(|| format!("parent staged ledger {} missing", pred_block.hash()))?
+            .ledger()
+            .make_child();
+        for (index, account) in accounts {
+            let account: Account = (&account).try_into().map_err(error_to_string)?;
+            mask.set_at_index(ledger::AccountIndex(index), Box::new(account))
+                .map_err(|_| format!("failed to set account at index {index}"))?;
+        }
+        let mut staged_ledger = StagedLedger::of_parts_unchecked(
+            constraint_constants(),
+            mask,
+            (&scan_state).try_into().map_err(error_to_string)?,
+            (&pending_coinbase).try_into().map_err(error_to_string)?,
+        );
+        let ledger_hashes = MinaBaseStagedLedgerHashStableV1::from(&staged_ledger.hash());
+        if &ledger_hashes != block.staged_ledger_hashes() {
+            return Err(format!(
+                "staged ledger hash mismatch. found: {ledger_hashes:?}, expected: {:?}",
+                block.staged_ledger_hashes()
+            ));
+        }
+        self.sync
+            .staged_ledgers
+            .insert(Arc::new(ledger_hashes), staged_ledger);
+        Ok(())
+    }
+
+    /// Sends the blocks which were added to the best chain to the archive
+    /// (if enabled). `parent` is the block preceding the first of the
+    /// `new_blocks`, `None` if they start at the root.
+    pub fn frontier_archive(
+        &mut self,
+        parent: Option<AppliedBlock>,
+        new_blocks: Vec<AppliedBlock>,
+    ) {
+        let Some(archive) = self.archive.as_mut() else {
+            return;
+        };
+        let skip = archive.skip_count(new_blocks.len());
+
+        let parents = std::iter::once(parent.as_ref()).chain(new_blocks.iter().map(Some));
+        for (parent, block) in parents.zip(&new_blocks).skip(skip) {
+            if block.is_genesis() {
+                continue;
+            }
+            let Some(parent) = parent else {
+                // Root of the frontier, its parent ledger is gone.
+                openmina_core::warn!(openmina_core::log::system_time();
+                    kind = "LedgerService::frontier_archive",
+                    summary = format!("parent of block {} unknown, not archiving it", block.hash()));
+                continue;
+            };
+            match self.archived_block(block, parent) {
+                Ok(archived) => {
+                    if let Some(archive) = self.archive.as_ref() {
+                        archive.send(archived);
+                    }
+                }
+                Err(err) => {
+                    openmina_core::error!(openmina_core::log::system_time();
+                        kind = "LedgerService::frontier_archive",
+                        summary = format!("failed to archive block {}", block.hash()),
+                        error = err);
+                }
+            }
+        }
+    }
+
+    /// Collects the ledger data the archive needs for the `block`.
+    fn archived_block(
+        &self,
+        block: &AppliedBlock,
+        parent: &AppliedBlock,
+    ) -> Result<ArchivedBlock, String> {
+        let (mask, _) = self
+            .mask(block.merkle_root_hash())
+            .ok_or_else(|| format!("ledger {} not found", block.merkle_root_hash()))?;
+        let (parent_mask, _) = self
+            .mask(parent.merkle_root_hash())
+            .ok_or_else(|| format!("ledger {} not found", parent.merkle_root_hash()))?;
+
+        let diff: Diff = (&block.body().staged_ledger_diff)
+            .try_into()
+            .map_err(error_to_string)?;
+        let fee_payers = diff
+            .commands()
+            .into_iter()
+            .map(|cmd| cmd.data.fee_payer())
+            .collect::<BTreeSet<_>>();
+        let accessed = block_accessed_accounts(block)?;
+
+        let accounts_accessed = accessed
+            .iter()
+            .filter_map(|id| {
+                let index = mask.index_of_account(id.clone())?;
+                let account = mask.get_at_index(index)?;
+                Some((index.0, (&*account).into()))
+            })
+            .collect();
+
+        let account_creation_fee = Fee::from_u64(constraint_constants().account_creation_fee);
+        let accounts_created = accessed
+            .iter()
+            .filter(|id| parent_mask.location_of_account(id).is_none())
+            .map(|id| (id.clone().into(), (&account_creation_fee).into()))
+            .collect();
+
+        let tokens_used = accessed
+            .iter()
+            .map(|id| id.token_id.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|token_id| {
+                let owner = mask.token_owner(token_id.clone()).map(Into::into);
+                ((&token_id).into(), owner)
+            })
+            .collect();
+
+        let sender_receipt_chains_from_parent_ledger = fee_payers
+            .into_iter()
+            .filter_map(|id| {
+                let addr = parent_mask.location_of_account(&id)?;
+                let account = parent_mask.get(addr)?;
+                Some((id.into(), account.receipt_chain_hash.into()))
+            })
+            .collect();
+
+        Ok(ArchivedBlock {
+            block: block.block_with_hash().clone(),
+            accounts_accessed,
+            accounts_created,
+            tokens_used,
+            sender_receipt_chains_from_parent_ledger,
+        })
+    }
+
+    /// Snarked ledger in the form it's persisted in. Only the accounts
+    /// changed since a persisted ledger are written, unless there is none
+    /// to base the ledger on.
+    fn snarked_ledger_to_persist(
+        &self,
+        storage: &LedgerStorage,
+        hash: &LedgerHash,
+    ) -> Option<PersistedSnarkedLedger> {
+        let (mut mask, _) = self.mask(hash)?;
+        let diff = storage
+            .snarked_ledger_base(|base| self.mask(base).is_some())
+            .and_then(|base| {
+                let (mut base_mask, _) = self.mask(base)?;
+                let accounts = snarked_ledger_changed_accounts(&mut base_mask, &mut mask)?;
+                Some(PersistedSnarkedLedger {
+                    base: Some(base.clone()),
+                    accounts,
+                })
+            });
+        Some(diff.unwrap_or_else(|| PersistedSnarkedLedger {
+            base: None,
+            accounts: mask.fold(Vec::new(), |mut accounts, account| {
+                accounts.push((accounts.len() as u64, account.into()));
+                accounts
+            }),
+        }))
+    }
+
+    /// Restores the snarked ledgers needed by the persisted transition
+    /// frontier, by applying the chain of persisted parts of each one.
+    fn snarked_ledgers_restore(&mut self, persisted: &PersistedFrontier) -> Result<(), String> {
+        for hash in &persisted.snarked_ledgers {
+            let chain = persisted.snarked_ledger_chain(hash)?;
+            let mut mask = snarked_ledger_create(self.on_disk_ledgers_dir.as_deref(), hash);
+            for (index, account) in chain.into_iter().flat_map(|part| &part.accounts) {
+                let account: Account = account.try_into().map_err(error_to_string)?;
+                // Accounts of the ledger are only ever changed or appended.
+                match (*index).cmp(&(mask.num_accounts() as u64)) {
+                    std::cmp::Ordering::Less => mask
+                        .set_at_index(ledger::AccountIndex(*index), Box::new(account))
+                        .map_err(|_| format!("failed to set account at index {index}"))?,
+                    std::cmp::Ordering::Equal => {
+                        mask.get_or_create_account(account.id(), account)
+                            .map_err(|err| format!("{err:?}"))?;
+                    }
+                    std::cmp::Ordering::Greater => {
+                        return Err(format!(
+                            "persisted snarked ledger {hash} misses accounts before index {index}"
+                        ));
+                    }
+                }
+            }
+            let calculated = merkle_root(&mut mask);
+            if &calculated != hash {
+                return Err(format!(
+                    "persisted snarked ledger hash mismatch. expected: {hash}, found: {calculated}"
+                ));
+            }
+            self.snarked_ledgers.insert(hash.clone(), mask);
+        }
+        Ok(())
+    }
+
+    /// Restores the transition frontier persisted by the previous run of
+    /// the node. Returns an empty chain if nothing was persisted or if it
+    /// was persisted for a different genesis ledger.
+    pub fn frontier_restore(
+        &mut self,
+        genesis_ledger_hash: &LedgerHash,
+    ) -> Result<FrontierRestoreResult, String> {
+        let Some(storage) = self.storage.as_ref() else {
+            return Ok(Default::default());
+        };
+        let Some(persisted) = storage.load()? else {
+            return Ok(Default::default());
+        };
+        let best_chain = &persisted.best_chain;
+        let (Some(root), Some(best_tip)) = (best_chain.first().cloned(), best_chain.last()) else {
+            return Ok(Default::default());
+        };
+        if root.genesis_ledger_hash() != genesis_ledger_hash {
+            openmina_core::warn!(openmina_core::log::system_time();
+                kind = "LedgerService::frontier_restore",
+                summary = "persisted frontier belongs to a different genesis ledger, ignoring it",
+                genesis_ledger_hash = root.genesis_ledger_hash().to_string());
+            return Ok(Default::default());
+        }
+        openmina_core::info!(openmina_core::log::system_time();
+            kind = "LedgerService::frontier_restore",
+            summary = format!("restoring {} blocks, {}..{}", best_chain.len(), root.height(), best_tip.height()),
+            root = root.hash().to_string(),
+            best_tip = best_tip.hash().to_string());
+
+        self.snarked_ledgers_restore(&persisted)?;
+        let PersistedFrontier {
+            root_staged_ledger_parts,
+            best_chain,
+            ..
+        } = persisted;
+
+        let needed_protocol_states = root_staged_ledger_parts
+            .needed_blocks
+            .iter()
+            .map(|state| Ok((state.try_hash()?, state.clone())))
+            .collect::<Result<BTreeMap<_, _>, InvalidBigInt>>()
+            .map_err(error_to_string)?;
+
+        // Genesis staged ledger is already inserted when loading genesis.
+        if self
+            .staged_ledger_mut(root.staged_ledger_hashes())
+            .is_none()
+        {
+            let validated = StagedLedgerAuxAndPendingCoinbasesValidated::validate(
+                &root_staged_ledger_parts,
+                root.staged_ledger_hashes(),
+            );
+            let StagedLedgerAuxAndPendingCoinbasesValidated::Valid(parts) = validated else {
+                return Err("persisted root staged ledger parts are invalid".to_owned());
+            };
+            let snarked_ledger = self
+                .snarked_ledgers
+                .get(root.snarked_ledger_hash())
+                .ok_or_else(|| {
+                    format!(
+                        "missing root snarked ledger: {}",
+                        root.snarked_ledger_hash()
+                    )
+                })?
+                .copy();
+            let (_, result) = staged_ledger_reconstruct(
+                snarked_ledger,
+                root.snarked_ledger_hash().clone(),
+                Some(parts),
+            )
+            .map_err(error_to_string)?;
+            self.staged_ledgers
+                .insert(Arc::new(root.staged_ledger_hashes().clone()), result?);
+        }
+
+        let mut restored_chain: Vec<AppliedBlock> = Vec::with_capacity(best_chain.len());
+        let mut applied = 0;
+        for (block, snapshot) in best_chain {
+            let Some(pred_block) = restored_chain.last().cloned() else {
+                restored_chain.push(block);
+                continue;
+            };
+            // Blocks are applied again only if their snapshot is missing or
+            // doesn't match the block.
+            let restored = match snapshot
+                .map(|snapshot| self.staged_ledger_restore(&block, &pred_block, snapshot))
+            {
+                Some(Ok(())) => true,
+                Some(Err(err)) => {
+                    openmina_core::warn!(openmina_core::log::system_time();
+                        kind = "LedgerService::frontier_restore",
+                        summary = format!("failed to restore staged ledger of {}", block.hash()),
+                        error = err);
+                    false
+                }
+                None => false,
+            };
+            if !restored {
+                let result = self.block_apply(block.block.clone(), pred_block)?;
+                applied += 1;
+                restored_chain.push(AppliedBlock {
+                    block: block.block,
+                    just_emitted_a_proof: result.just_emitted_a_proof,
+                });
+                continue;
+            }
+            restored_chain.push(block);
+        }
+        let restored = self.sync.staged_ledgers.take();
+        self.staged_ledgers.extend(restored);
+
+        openmina_core::info!(openmina_core::log::system_time();
+            kind = "LedgerService::frontier_restore",
+            summary = format!("restored {} blocks, applied {applied} of them", restored_chain.len()));
+
+        Ok(FrontierRestoreResult {
+            best_chain: restored_chain,
+            needed_protocol_states,
+        })
+    }
+
     pub fn get_num_accounts(
         &mut self,
         ledger_hash: v2::LedgerHash,
@@ -926,6 +1489,147 @@ impl LedgerCtx {
             .collect::<Vec<_>>()
     }
 
+    /// Account with the Merkle path proving it against the `ledger_hash`.
+    pub fn account_proof(
+        &self,
+        ledger_hash: &LedgerHash,
+        account_id: &AccountId,
+    ) -> Result<RpcLedgerAccountProof, String> {
+        let (mut mask, _) = self
+            .mask(ledger_hash)
+            .ok_or_else(|| format!("ledger not found: {ledger_hash}"))?;
+        let index = mask
+            .index_of_account(account_id.clone())
+            .ok_or_else(|| "account not found".to_owned())?;
+        let account = mask
+            .get_at_index(index)
+            .ok_or_else(|| "account not found".to_owned())?;
+        let merkle_path = mask.merkle_path_at_index(index);
+
+        Ok(RpcLedgerAccountProof {
+            ledger_hash: ledger_hash.clone(),
+            index: index.0,
+            account: *account,
+            merkle_path: merkle_path.iter().map(Into::into).collect(),
+        })
+    }
+
+    /// All accounts of the ledger, ordered by their index.
+    pub fn ledger_export(&self, ledger_hash: &LedgerHash) -> Result<LedgerExport, String> {
+        let (mask, _) = self
+            .mask(ledger_hash)
+            .ok_or_else(|| format!("ledger not found: {ledger_hash}"))?;
+        Ok(LedgerExport {
+            ledger_hash: ledger_hash.clone(),
+            accounts: mask.fold(Vec::new(), |mut accounts, account| {
+                accounts.push(account.into());
+                accounts
+            }),
+        })
+    }
+
+    /// Account with the default token, converted to the wire type used by
+    /// the watched accounts.
+    pub fn watched_account_get(
+        &self,
+        ledger_hash: &LedgerHash,
+        pub_key: &v2::NonZeroCurvePoint,
+    ) -> Result<Option<Box<v2::MinaBaseAccountBinableArgStableV2>>, String> {
+        let (mask, _) = self
+            .mask(ledger_hash)
+            .ok_or_else(|| format!("ledger not found: {ledger_hash}"))?;
+        let public_key = CompressedPubKey::try_from(pub_key).map_err(error_to_string)?;
+        let account_id = AccountId::new(public_key, ledger::TokenId::default());
+        Ok(mask
+            .location_of_account(&account_id)
+            .and_then(|addr| mask.get(addr))
+            .map(|account| Box::new((&*account).into())))
+    }
+
+    /// Applies the user command on a throwaway child mask of the ledger, so
+    /// that the ledger itself is left untouched.
+    pub fn transaction_simulate(
+        &self,
+        req: &LedgerReadTransactionSimulate,
+    ) -> Result<RpcTransactionSimulation, String> {
+        let (ledger, _) = self
+            .mask(&req.ledger_hash)
+            .ok_or_else(|| format!("ledger not found: {}", req.ledger_hash))?;
+        let command = UserCommand::try_from(&req.query.command).map_err(error_to_string)?;
+        if req.query.verify {
+            user_command_verify(&ledger, &command)?;
+        }
+
+        let mut account_ids = Vec::new();
+        for id in command.accounts_referenced() {
+            if !account_ids.contains(&id) {
+                account_ids.push(id);
+            }
+        }
+        let get_account = |ledger: &Mask, id: &AccountId| {
+            ledger
+                .location_of_account(id)
+                .and_then(|addr| ledger.get(addr))
+        };
+        let accounts_before = account_ids
+            .iter()
+            .map(|id| get_account(&ledger, id))
+            .collect::<Vec<_>>();
+
+        let mut mask = ledger.make_child();
+        let txn_state_view = protocol_state_view(&req.protocol_state).map_err(error_to_string)?;
+        let global_slot = Slot::from_u32(req.global_slot);
+        let transaction = Transaction::Command(command.clone());
+        let partially_applied = apply_transaction_first_pass(
+            constraint_constants(),
+            global_slot,
+            &txn_state_view,
+            &mut mask,
+            &transaction,
+        )?;
+        let applied =
+            apply_transaction_second_pass(constraint_constants(), &mut mask, partially_applied)?;
+
+        let failures = match applied.transaction_status() {
+            TransactionStatus::Applied => Vec::new(),
+            TransactionStatus::Failed(failures) => failures
+                .iter()
+                .enumerate()
+                .filter(|(_, failures)| !failures.is_empty())
+                .map(|(index, failures)| RpcTransactionSimulationFailure {
+                    index,
+                    failures: failures.clone(),
+                })
+                .collect(),
+        };
+        let new_accounts = applied.new_accounts();
+        let accounts = account_ids
+            .iter()
+            .zip(accounts_before)
+            .filter_map(|(id, before)| {
+                let after = get_account(&mask, id)?;
+                Some(RpcTransactionSimulationAccount {
+                    public_key: after.public_key.clone().into(),
+                    token_id: after.token_id.clone().into(),
+                    created: new_accounts.contains(id),
+                    balance_before: before.as_ref().map(|a| a.balance),
+                    balance_after: after.balance,
+                    nonce_before: before.as_ref().map(|a| a.nonce),
+                    nonce_after: after.nonce,
+                })
+            })
+            .collect();
+
+        Ok(RpcTransactionSimulation {
+            best_tip: req.best_tip.clone(),
+            global_slot: req.global_slot,
+            fee: command.fee(),
+            applied: applied.transaction_status().is_applied(),
+            failures,
+            accounts,
+        })
+    }
+
     pub fn staged_ledger_aux_and_pending_coinbase(
         &mut self,
         ledger_hash: &MinaBaseStagedLedgerHashStableV1,
@@ -985,9 +1689,12 @@ impl LedgerCtx {
         let protocol_state_view =
             protocol_state_view(&pred_block.header().protocol_state).map_err(error_to_string)?;
 
-        // TODO(binier): include `invalid_txns` in output.
-        let (pre_diff, _invalid_txns) = staged_ledger
-            .create_diff(
+        let transactions = transactions_by_fee
+            .iter()
+            .map(StagedLedgerDiffCreateTransaction::new)
+            .collect();
+        let (pre_diff, invalid_txns, diff_create_log) = staged_ledger
+            .create_diff_with_log(
                 constraint_constants(),
                 (&global_slot_since_genesis).into(),
                 Some(true),
@@ -1005,6 +1712,12 @@ impl LedgerCtx {
                 supercharge_coinbase,
             )
             .map_err(|err| format!("{err:?}"))?;
+        let diff_create_log = StagedLedgerDiffCreateLog::new(
+            transactions,
+            &pre_diff,
+            &invalid_txns,
+            &diff_create_log,
+        );
 
         // TODO(binier): maybe here, check if block reward is above threshold.
         // https://github.com/minaprotocol/mina/blob/b3d418a8c0ae4370738886c2b26f0ec7bdb49303/src/lib/block_producer/block_producer.ml#L222
@@ -1056,6 +1769,7 @@ impl LedgerCtx {
             stake_proof_sparse_ledger: self
                 .stake_proof_sparse_ledger(staking_ledger_hash, &producer, &delegator)
                 .map_err(error_to_string)?,
+            diff_create_log,
         })
     }
 
@@ -1076,6 +1790,31 @@ impl LedgerCtx {
         Ok((&sparse_ledger).into())
     }
 
+    /// Sparse ledger containing all the accounts of the snarked (epoch)
+    /// ledger, as served by the `get_epoch_ledger` rpc.
+    ///
+    /// Building it is expensive, so it's cached for as long as the snarked
+    /// ledger is kept.
+    pub fn get_epoch_ledger(
+        &mut self,
+        ledger_hash: &LedgerHash,
+    ) -> Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>> {
+        let mask = self.snarked_ledgers.get(ledger_hash)?;
+        if let Some(sparse_ledger) = self.epoch_ledgers_cache.get(ledger_hash) {
+            return Some(sparse_ledger.clone());
+        }
+        let account_ids = mask.accounts().into_iter().collect::<Vec<_>>();
+        let sparse_ledger = SparseLedger::of_ledger_subset_exn(mask.clone(), &account_ids);
+        let sparse_ledger = Arc::new((&sparse_ledger).into());
+
+        let snarked_ledgers = &self.snarked_ledgers;
+        self.epoch_ledgers_cache
+            .retain(|hash, _| snarked_ledgers.contains_key(hash));
+        self.epoch_ledgers_cache
+            .insert(ledger_hash.clone(), Arc::clone(&sparse_ledger));
+        Some(sparse_ledger)
+    }
+
     pub fn scan_state_summary(
         &self,
         staged_ledger_hash: &MinaBaseStagedLedgerHashStableV1,
@@ -1231,11 +1970,15 @@ impl LedgerSyncState {
     }
 
     /// Returns a [Mask] instance for the snarked ledger with [hash]. If it doesn't
-    /// exist a new instance is created.
-    fn snarked_ledger_mut(&mut self, hash: LedgerHash) -> Result<&mut Mask, InvalidBigInt> {
+    /// exist a new instance is created, on disk in `on_disk_dir` if set.
+    fn snarked_ledger_mut(
+        &mut self,
+        hash: LedgerHash,
+        on_disk_dir: Option<&Path>,
+    ) -> Result<&mut Mask, InvalidBigInt> {
         let hash_fp = hash.to_field()?;
         Ok(self.snarked_ledgers.entry(hash.clone()).or_insert_with(|| {
-            let mut ledger = Mask::create(LEDGER_DEPTH);
+            let mut ledger = snarked_ledger_create(on_disk_dir, &hash);
             ledger.set_cached_hash_unchecked(&LedgerAddress::root(), hash_fp);
             ledger
         }))
@@ -1249,6 +1992,30 @@ impl LedgerSyncState {
     }
 }
 
+/// Checks signatures and zkApp proofs of the command. Verification keys
+/// are taken from the `ledger` and from the command itself.
+fn user_command_verify(ledger: &Mask, command: &UserCommand) -> Result<(), String> {
+    let account_ids = command.accounts_referenced().into_iter().collect();
+    let vks = UserCommand::load_vks_from_ledger(account_ids, ledger)
+        .into_iter()
+        .chain(command.extract_vks())
+        .collect::<Vec<_>>();
+    let command = command.to_verifiable(&TransactionStatus::Applied, |vk_hash, account_id| {
+        vks.iter()
+            .find(|(id, vk)| id == account_id && vk.hash() == vk_hash)
+            .map(|(_, vk)| vk.clone())
+            .ok_or_else(|| format!("verification key not found for {account_id:?}"))
+    })?;
+    match Verifier
+        .verify_commands(vec![WithStatus::applied(command)], None)
+        .pop()
+    {
+        Some(Ok(_)) => Ok(()),
+        Some(Err(err)) => Err(err.to_string()),
+        None => Err("command wasn't verified".to_owned()),
+    }
+}
+
 fn staged_ledger_reconstruct(
     snarked_ledger: Mask,
     snarked_ledger_hash: LedgerHash,
@@ -1301,6 +2068,61 @@ fn staged_ledger_reconstruct(
     Ok((staged_ledger_hash, result))
 }
 
+/// Accounts of the `ledger` which differ from the ones in `base`, found by
+/// comparing the hashes of the subtrees. `None` if the `ledger` isn't
+/// `base` with accounts changed or appended.
+fn snarked_ledger_changed_accounts(
+    base: &mut Mask,
+    ledger: &mut Mask,
+) -> Option<Vec<(u64, v2::MinaBaseAccountBinableArgStableV2)>> {
+    if ledger.num_accounts() < base.num_accounts() {
+        return None;
+    }
+    let mut accounts = Vec::new();
+    let mut stack = vec![LedgerAddress::root()];
+    while let Some(addr) = stack.pop() {
+        if base.get_inner_hash_at_addr(addr.clone()).ok()?
+            == ledger.get_inner_hash_at_addr(addr.clone()).ok()?
+        {
+            continue;
+        }
+        if addr.length() < LEDGER_DEPTH {
+            // Right child first, so that accounts end up ordered by index.
+            stack.push(addr.child_right());
+            stack.push(addr.child_left());
+            continue;
+        }
+        let account = ledger.get(addr.clone())?;
+        accounts.push((addr.to_index().0, (&*account).into()));
+    }
+    Some(accounts)
+}
+
+/// Accounts accessed by the transactions of the `block`, which are the
+/// only ones the block can change.
+fn block_accessed_accounts(block: &ArcBlockWithHash) -> Result<BTreeSet<AccountId>, String> {
+    let consensus_state = block.consensus_state();
+    let diff: Diff = (&block.body().staged_ledger_diff)
+        .try_into()
+        .map_err(error_to_string)?;
+    let transactions = diff
+        .get_transactions(
+            constraint_constants(),
+            (&consensus_state.coinbase_receiver)
+                .try_into()
+                .map_err(error_to_string)?,
+            consensus_state.supercharge_coinbase,
+        )
+        .map_err(|err| format!("{err:?}"))?;
+
+    Ok(transactions
+        .iter()
+        .flat_map(|tx| tx.data.account_access_statuses(&tx.status))
+        .filter(|(_, status)| *status == AccessedOrNot::Accessed)
+        .map(|(id, _)| id)
+        .collect())
+}
+
 pub trait LedgerService: redux::Service {
     fn ledger_manager(&self) -> &LedgerManager;
     fn force_sync_calls(&self) -> bool {
@@ -1324,6 +2146,28 @@ pub trait LedgerService: redux::Service {
             self.ledger_manager().call(request);
         }
     }
+
+    /// Archive the blocks added to the best chain, if the archive is
+    /// enabled. See [`LedgerCtx::frontier_archive`].
+    fn frontier_archive(&mut self, parent: Option<AppliedBlock>, new_blocks: Vec<AppliedBlock>) {
+        self.ledger_manager()
+            .call(LedgerRequest::FrontierArchive { parent, new_blocks });
+    }
+
+    /// Persist the changes of the transition frontier, if the ledger
+    /// storage is enabled. See [`LedgerCtx::frontier_persist`].
+    fn frontier_persist(
+        &mut self,
+        root: AppliedBlock,
+        new_blocks: Vec<AppliedBlock>,
+        needed_protocol_states: Option<BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>>,
+    ) {
+        self.ledger_manager().call(LedgerRequest::FrontierPersist {
+            root,
+            new_blocks,
+            needed_protocol_states,
+        });
+    }
 }
 
 /// Save reconstruction to file, when it fails.
@@ -1449,7 +2293,10 @@ fn dump_application_to_file(
 mod tests {
     use mina_p2p_messages::v2::MinaBaseLedgerHash0StableV1;
 
+    use ledger::scan_state::transaction_logic;
+
     use crate::ledger::hash_node_at_depth;
+    use crate::rpc::{RpcLedgerAccountProofQuery, RpcTransactionSimulateQuery};
 
     use super::*;
 
@@ -1476,4 +2323,399 @@ mod tests {
             assert_eq!(hash.to_string(), expected_hash);
         });
     }
+
+    #[test]
+    fn snarked_ledgers_on_disk() {
+        let dir = std::env::temp_dir().join(format!("snarked-ledgers-{}", ledger::next_uuid()));
+        let mut genesis = Mask::create(LEDGER_DEPTH);
+        for _ in 0..10 {
+            let account = Account::rand();
+            genesis
+                .get_or_create_account(account.id(), account)
+                .unwrap();
+        }
+        let hash = merkle_root(&mut genesis);
+
+        let mut ctx = LedgerCtx::default();
+        ctx.set_on_disk_ledgers_dir(dir.clone());
+        ctx.insert_genesis_ledger(genesis);
+
+        let mask = ctx.snarked_ledgers.get_mut(&hash).unwrap();
+        assert_eq!(merkle_root(mask), hash);
+        assert_eq!(mask.num_accounts(), 10);
+        assert!(std::fs::read_dir(&dir).unwrap().next().is_some());
+
+        // Ledgers are removed from disk once dropped.
+        drop(ctx);
+        assert!(std::fs::read_dir(&dir).unwrap().next().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn snarked_ledgers_restored_from_diffs() {
+        let dir = std::env::temp_dir().join(format!("ledger-storage-{}", ledger::next_uuid()));
+        let chain = crate::transition_frontier::test_chain(None, 2, 0);
+        let add_accounts = |mask: &mut Mask, n: usize| {
+            for _ in 0..n {
+                let account = Account::rand();
+                mask.get_or_create_account(account.id(), account).unwrap();
+            }
+        };
+        // Next epoch ledger changes an account of the staking ledger and
+        // adds new ones, the root snarked ledger changes one more.
+        let mut staking = Mask::create(LEDGER_DEPTH);
+        add_accounts(&mut staking, 10);
+        let mut next = staking.copy();
+        let mut account = next.get_at_index(ledger::AccountIndex(3)).unwrap();
+        account.nonce = account.nonce.incr();
+        next.set_at_index(ledger::AccountIndex(3), account).unwrap();
+        add_accounts(&mut next, 2);
+        let mut snarked = next.copy();
+        let mut account = snarked.get_at_index(ledger::AccountIndex(11)).unwrap();
+        account.nonce = account.nonce.incr();
+        snarked
+            .set_at_index(ledger::AccountIndex(11), account)
+            .unwrap();
+        let hashes = [&mut staking, &mut next, &mut snarked].map(merkle_root);
+
+        let mut ctx = LedgerCtx::default();
+        for (hash, mask) in hashes.iter().zip([staking, next, snarked]) {
+            ctx.snarked_ledgers.insert(hash.clone(), mask);
+        }
+        let mut storage = LedgerStorage::open(&dir).unwrap();
+        let mut persisted = Vec::new();
+        for hash in &hashes {
+            let part = ctx.snarked_ledger_to_persist(&storage, hash).unwrap();
+            persisted.push(part.clone());
+            storage.update(FrontierUpdate {
+                new_snarked_ledgers: vec![(hash.clone(), part)],
+                snarked_ledgers: hashes.iter().cloned().collect(),
+                root_staged_ledger_parts: Some(
+                    super::super::ledger_storage::test_root_staged_ledger_parts(),
+                ),
+                root: chain[0].hash().clone(),
+                new_blocks: chain.iter().map(|b| (b.clone(), None)).collect(),
+            });
+        }
+        let indexes = |part: &PersistedSnarkedLedger| {
+            let indexes = part.accounts.iter().map(|(i, _)| *i);
+            (part.base.clone(), indexes.collect::<Vec<_>>())
+        };
+        assert_eq!(indexes(&persisted[0]), (None, (0..10).collect()));
+        assert_eq!(
+            indexes(&persisted[1]),
+            (Some(hashes[0].clone()), vec![3, 10, 11])
+        );
+        assert_eq!(indexes(&persisted[2]), (Some(hashes[1].clone()), vec![11]));
+        drop(storage);
+
+        // Restart.
+        let storage = LedgerStorage::open(&dir).unwrap();
+        let persisted = storage.load().unwrap().unwrap();
+        let mut ctx = LedgerCtx::default();
+        ctx.snarked_ledgers_restore(&persisted).unwrap();
+        for hash in &hashes {
+            let (mut mask, _) = ctx.mask(hash).unwrap();
+            assert_eq!(&merkle_root(&mut mask), hash);
+        }
+        assert_eq!(ctx.snarked_ledgers[&hashes[2]].num_accounts(), 12);
+        drop(storage);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn epoch_ledger_cached() {
+        let ledger = |accounts: usize| {
+            let mut mask = Mask::create(LEDGER_DEPTH);
+            for _ in 0..accounts {
+                let account = Account::rand();
+                mask.get_or_create_account(account.id(), account).unwrap();
+            }
+            (merkle_root(&mut mask), mask)
+        };
+        let (hash, genesis) = ledger(10);
+        let (other_hash, other) = ledger(3);
+
+        let mut ctx = LedgerCtx::default();
+        ctx.insert_genesis_ledger(genesis);
+
+        let sparse_ledger = ctx.get_epoch_ledger(&hash).unwrap();
+        assert_eq!(sparse_ledger.indexes.len(), 10);
+        let cached = ctx.get_epoch_ledger(&hash).unwrap();
+        assert!(Arc::ptr_eq(&sparse_ledger, &cached));
+
+        // Dropped from the cache along with the snarked ledger.
+        ctx.snarked_ledgers.remove(&hash);
+        assert!(ctx.get_epoch_ledger(&hash).is_none());
+        ctx.snarked_ledgers.insert(other_hash.clone(), other);
+        assert_eq!(ctx.get_epoch_ledger(&other_hash).unwrap().indexes.len(), 3);
+        assert!(!ctx.epoch_ledgers_cache.contains_key(&hash));
+    }
+
+    fn simulate_payment(
+        accounts: &[(&CompressedPubKey, u64)],
+        sender: &CompressedPubKey,
+        receiver: &CompressedPubKey,
+        amount: u64,
+    ) -> RpcTransactionSimulation {
+        use ledger::scan_state::{
+            currency::{Amount, Balance, Nonce},
+            transaction_logic::signed_command::{
+                Body, PaymentPayload, SignedCommand, SignedCommandPayload,
+            },
+            transaction_logic::Memo,
+        };
+
+        let mut genesis = Mask::create(LEDGER_DEPTH);
+        for (public_key, balance) in accounts {
+            let id = AccountId::new((*public_key).clone(), ledger::TokenId::default());
+            let account = Account::create_with(id.clone(), Balance::from_u64(*balance));
+            genesis.get_or_create_account(id, account).unwrap();
+        }
+        let ledger_hash = merkle_root(&mut genesis);
+        let mut ctx = LedgerCtx::default();
+        ctx.insert_genesis_ledger(genesis);
+
+        let payload = SignedCommandPayload::create(
+            Fee::from_u64(10_000_000),
+            sender.clone(),
+            Nonce::zero(),
+            None,
+            Memo::empty(),
+            Body::Payment(PaymentPayload {
+                receiver_pk: receiver.clone(),
+                amount: Amount::from_u64(amount),
+            }),
+        );
+        let command = UserCommand::SignedCommand(Box::new(SignedCommand {
+            payload,
+            signer: sender.clone(),
+            signature: mina_signer::Signature::dummy(),
+        }));
+        let block = crate::transition_frontier::test_chain(None, 1, 0).remove(0);
+        let req = LedgerReadTransactionSimulate {
+            best_tip: block.hash().clone(),
+            ledger_hash,
+            protocol_state: block.header().protocol_state.clone(),
+            global_slot: block.global_slot_since_genesis(),
+            query: RpcTransactionSimulateQuery {
+                command: (&command).into(),
+                verify: false,
+            },
+        };
+        let simulation = ctx.transaction_simulate(&req).unwrap();
+
+        // Ledger itself is left untouched.
+        let (mask, _) = ctx.mask(&req.ledger_hash).unwrap();
+        let sender_id = AccountId::new(sender.clone(), ledger::TokenId::default());
+        let sender_account = mask
+            .location_of_account(&sender_id)
+            .and_then(|addr| mask.get(addr))
+            .unwrap();
+        assert_eq!(sender_account.nonce, Nonce::zero());
+
+        simulation
+    }
+
+    #[test]
+    fn transaction_simulate_payment() {
+        let sender = Account::rand().public_key;
+        let receiver = Account::rand().public_key;
+        let accounts = [(&sender, 10_000_000_000), (&receiver, 0)];
+        let simulation = simulate_payment(&accounts, &sender, &receiver, 1_000_000_000);
+
+        assert!(simulation.applied);
+        assert!(simulation.failures.is_empty());
+        assert_eq!(simulation.fee.as_u64(), 10_000_000);
+        let [sender, receiver] = &simulation.accounts[..] else {
+            panic!("expected sender and receiver: {:?}", simulation.accounts);
+        };
+        assert!(!sender.created);
+        assert_eq!(sender.balance_before.unwrap().as_u64(), 10_000_000_000);
+        assert_eq!(sender.balance_after.as_u64(), 8_990_000_000);
+        assert_eq!(sender.nonce_before.unwrap().as_u32(), 0);
+        assert_eq!(sender.nonce_after.as_u32(), 1);
+        assert!(!receiver.created);
+        assert_eq!(receiver.balance_before.unwrap().as_u64(), 0);
+        assert_eq!(receiver.balance_after.as_u64(), 1_000_000_000);
+    }
+
+    #[test]
+    fn transaction_simulate_insufficient_balance() {
+        let sender = Account::rand().public_key;
+        let receiver = Account::rand().public_key;
+        let accounts = [(&sender, 1_000_000_000), (&receiver, 0)];
+        let simulation = simulate_payment(&accounts, &sender, &receiver, 5_000_000_000);
+
+        assert!(!simulation.applied);
+        let [failure] = &simulation.failures[..] else {
+            panic!("expected a single failure: {:?}", simulation.failures);
+        };
+        assert_eq!(
+            failure.failures,
+            vec![transaction_logic::TransactionFailure::SourceInsufficientBalance]
+        );
+        // Fee is still charged and the nonce incremented.
+        let [sender, receiver] = &simulation.accounts[..] else {
+            panic!("expected sender and receiver: {:?}", simulation.accounts);
+        };
+        assert_eq!(sender.balance_after.as_u64(), 990_000_000);
+        assert_eq!(sender.nonce_after.as_u32(), 1);
+        assert_eq!(receiver.balance_after.as_u64(), 0);
+    }
+
+    #[test]
+    fn transaction_simulate_account_creation() {
+        let sender = Account::rand().public_key;
+        let receiver = Account::rand().public_key;
+        let accounts = [(&sender, 10_000_000_000)];
+        let simulation = simulate_payment(&accounts, &sender, &receiver, 2_000_000_000);
+
+        assert!(simulation.applied);
+        let [_, receiver] = &simulation.accounts[..] else {
+            panic!("expected sender and receiver: {:?}", simulation.accounts);
+        };
+        assert!(receiver.created);
+        assert_eq!(receiver.balance_before, None);
+        assert_eq!(receiver.nonce_before, None);
+        let account_creation_fee = constraint_constants().account_creation_fee;
+        assert_eq!(
+            receiver.balance_after.as_u64(),
+            2_000_000_000 - account_creation_fee
+        );
+    }
+
+    fn ledger_with_accounts(accounts: &[Account]) -> (LedgerHash, Mask) {
+        let mut mask = Mask::create(LEDGER_DEPTH);
+        for account in accounts {
+            mask.get_or_create_account(account.id(), account.clone())
+                .unwrap();
+        }
+        (merkle_root(&mut mask), mask)
+    }
+
+    /// Checks the proof with `verify_account_merkle_proof` the way a client
+    /// of the rpc would, from the serialized proof.
+    fn assert_account_proof_verifies(proof: &RpcLedgerAccountProof, expected: &Account) {
+        use crate::rpc::RpcMerklePathItem;
+        use ledger::{AccountIndex, MerklePath};
+
+        let proof: RpcLedgerAccountProof =
+            serde_json::from_value(serde_json::to_value(proof).unwrap()).unwrap();
+        assert_eq!(&proof.account, expected);
+
+        let root = proof.ledger_hash.to_field().unwrap();
+        let merkle_path = proof
+            .merkle_path
+            .iter()
+            .map(|item| match item {
+                RpcMerklePathItem::Left(hash) => MerklePath::Left(hash.to_field().unwrap()),
+                RpcMerklePathItem::Right(hash) => MerklePath::Right(hash.to_field().unwrap()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(merkle_path.len(), LEDGER_DEPTH);
+        let index = AccountIndex(proof.index);
+        assert!(ledger::verify_account_merkle_proof(
+            &proof.account,
+            index,
+            &merkle_path,
+            root
+        ));
+        assert!(proof.verify());
+
+        // Proof doesn't hold for other positions or ledgers.
+        assert!(!ledger::verify_account_merkle_proof(
+            &proof.account,
+            AccountIndex(proof.index ^ 1),
+            &merkle_path,
+            root
+        ));
+        let other_root = ledger_with_accounts(&[Account::rand()])
+            .0
+            .to_field()
+            .unwrap();
+        assert!(!ledger::verify_account_merkle_proof(
+            &proof.account,
+            index,
+            &merkle_path,
+            other_root
+        ));
+    }
+
+    #[test]
+    fn account_proof_of_snarked_ledger() {
+        let accounts = (0..5).map(|_| Account::rand()).collect::<Vec<_>>();
+        let (hash, genesis) = ledger_with_accounts(&accounts);
+        let mut ctx = LedgerCtx::default();
+        ctx.insert_genesis_ledger(genesis);
+
+        for account in &accounts {
+            let query = RpcLedgerAccountProofQuery {
+                public_key: account.public_key.clone().into(),
+                token_id: Some(account.token_id.clone().into()),
+                snarked_ledger_hash: Some(hash.clone()),
+            };
+            // Explicitly requested ledger is used regardless of the best tip.
+            let best_tip = crate::transition_frontier::test_chain(None, 1, 0).remove(0);
+            let ledger_hash = query.ledger_hash(Some(&best_tip.block)).unwrap();
+            assert_eq!(ledger_hash, hash);
+
+            let proof = ctx
+                .account_proof(&ledger_hash, &query.account_id().unwrap())
+                .unwrap();
+            assert_eq!(proof.ledger_hash, hash);
+            assert_account_proof_verifies(&proof, account);
+        }
+
+        let query = RpcLedgerAccountProofQuery {
+            public_key: Account::rand().public_key.into(),
+            token_id: None,
+            snarked_ledger_hash: Some(hash.clone()),
+        };
+        assert!(ctx
+            .account_proof(&hash, &query.account_id().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn account_proof_of_best_tip_ledger() {
+        let accounts = (0..5).map(|_| Account::rand()).collect::<Vec<_>>();
+        let (_, genesis) = ledger_with_accounts(&accounts[..3]);
+        let (staged_hash, staged) = ledger_with_accounts(&accounts);
+        let mut ctx = LedgerCtx::default();
+        ctx.insert_genesis_ledger(genesis);
+        ctx.staged_ledger_reconstruct_result_store(
+            StagedLedger::create_exn(constraint_constants().clone(), staged).unwrap(),
+        );
+
+        let best_tip = {
+            let applied = crate::transition_frontier::test_chain(None, 1, 0).remove(0);
+            let mut block = (*applied.block.block).clone();
+            block
+                .header
+                .protocol_state
+                .body
+                .blockchain_state
+                .staged_ledger_hash
+                .non_snark
+                .ledger_hash = staged_hash.clone();
+            ArcBlockWithHash::try_new(Arc::new(block)).unwrap()
+        };
+
+        let account = &accounts[4];
+        let query = RpcLedgerAccountProofQuery {
+            public_key: account.public_key.clone().into(),
+            token_id: None,
+            snarked_ledger_hash: None,
+        };
+        assert_eq!(query.ledger_hash(None), None);
+        let ledger_hash = query.ledger_hash(Some(&best_tip)).unwrap();
+        assert_eq!(ledger_hash, staged_hash);
+
+        let proof = ctx
+            .account_proof(&ledger_hash, &query.account_id().unwrap())
+            .unwrap();
+        assert_eq!(proof.index, 4);
+        assert_account_proof_verifies(&proof, account);
+    }
 }
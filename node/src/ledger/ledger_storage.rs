@@ -0,0 +1,697 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+
+use ledger::ondisk::{self, Batch};
+use mina_p2p_messages::binprot::{
+    self,
+    macros::{BinProtRead, BinProtWrite},
+    BinProtRead, BinProtWrite,
+};
+use mina_p2p_messages::v2::{self, LedgerHash, MinaBaseAccountBinableArgStableV2, StateHash};
+use openmina_core::block::{AppliedBlock, BlockWithHash};
+use openmina_core::thread;
+
+use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
+
+const SNARKED_LEDGERS_KEY: &[u8] = b"snarked_ledgers";
+const BEST_CHAIN_KEY: &[u8] = b"best_chain";
+const ROOT_STAGED_LEDGER_PARTS_KEY: &[u8] = b"root_staged_ledger_parts";
+
+/// Amount of overwritten or removed data after which the database file
+/// gets compacted.
+const GC_THRESHOLD_BYTES: u64 = 512 * 1024 * 1024;
+/// Maximum number of diffs on top of a whole snarked ledger. Keeps the
+/// restore time and the number of ledgers kept only as a base in check.
+const MAX_SNARKED_LEDGER_DIFFS: usize = 16;
+
+fn snarked_ledger_key(hash: &LedgerHash) -> Box<[u8]> {
+    format!("snarked_ledger/{hash}").into_bytes().into()
+}
+
+fn block_key(hash: &StateHash) -> Box<[u8]> {
+    format!("block/{hash}").into_bytes().into()
+}
+
+fn staged_ledger_key(hash: &StateHash) -> Box<[u8]> {
+    format!("staged_ledger/{hash}").into_bytes().into()
+}
+
+fn encode<T: BinProtWrite>(value: &T) -> std::io::Result<Box<[u8]>> {
+    let mut buf = Vec::new();
+    value.binprot_write(&mut buf)?;
+    Ok(buf.into())
+}
+
+fn decode<T: BinProtRead>(bytes: &[u8]) -> Result<T, binprot::Error> {
+    T::binprot_read(&mut &*bytes)
+}
+
+#[derive(BinProtRead, BinProtWrite)]
+struct PersistedBlock {
+    block: v2::MinaBlockBlockStableV2,
+    just_emitted_a_proof: bool,
+}
+
+/// Snarked ledger as it's persisted, either whole or as the accounts which
+/// changed since its `base` ledger, so that a moving root snarked ledger
+/// doesn't have to be written whole each time.
+#[derive(BinProtRead, BinProtWrite, Debug, Clone)]
+pub struct PersistedSnarkedLedger {
+    /// Persisted ledger which the `accounts` are applied to, `None` if
+    /// they are the whole ledger.
+    pub base: Option<LedgerHash>,
+    /// Accounts with their indices, ordered by the index.
+    pub accounts: Vec<(u64, MinaBaseAccountBinableArgStableV2)>,
+}
+
+/// Entry of the persisted list of snarked ledgers.
+#[derive(BinProtRead, BinProtWrite, Debug, Clone)]
+struct StoredSnarkedLedger {
+    hash: LedgerHash,
+    base: Option<LedgerHash>,
+    /// Whether the ledger is needed by the transition frontier, or kept
+    /// only as a base of other ledgers.
+    needed: bool,
+}
+
+/// Snapshot of the staged ledger after applying a block, so that it can be
+/// restored without applying the block again.
+#[derive(BinProtRead, BinProtWrite, Debug, Clone)]
+pub struct PersistedStagedLedger {
+    pub scan_state: v2::TransactionSnarkScanStateStableV2,
+    pub pending_coinbase: v2::MinaBasePendingCoinbaseStableV2,
+    /// Accounts changed by the block, with their indices, compared to the
+    /// staged ledger of its predecessor.
+    pub accounts: Vec<(u64, MinaBaseAccountBinableArgStableV2)>,
+}
+
+/// Transition frontier as it was persisted by the previous run of the node.
+pub struct PersistedFrontier {
+    /// Snarked ledgers needed by the transition frontier (root snarked
+    /// ledger, staking and next epoch ledgers).
+    pub snarked_ledgers: Vec<LedgerHash>,
+    /// All persisted snarked ledgers, including the ones kept only as a
+    /// base of others.
+    pub snarked_ledger_parts: BTreeMap<LedgerHash, PersistedSnarkedLedger>,
+    /// Pieces required to reconstruct root staged ledger from the root
+    /// snarked ledger.
+    pub root_staged_ledger_parts: Arc<StagedLedgerAuxAndPendingCoinbases>,
+    /// Best chain, from root to best tip, with snapshots of staged ledgers
+    /// of the blocks after the root (if they were taken).
+    pub best_chain: Vec<(AppliedBlock, Option<PersistedStagedLedger>)>,
+}
+
+impl PersistedFrontier {
+    /// Parts of the snarked ledger, starting with the whole base ledger,
+    /// followed by the diffs leading to the ledger.
+    pub fn snarked_ledger_chain(
+        &self,
+        hash: &LedgerHash,
+    ) -> Result<Vec<&PersistedSnarkedLedger>, String> {
+        let mut chain = Vec::new();
+        let mut next = Some(hash);
+        while let Some(hash) = next {
+            if chain.len() > MAX_SNARKED_LEDGER_DIFFS {
+                return Err(format!("snarked ledger {hash} has too many bases"));
+            }
+            let part = self
+                .snarked_ledger_parts
+                .get(hash)
+                .ok_or_else(|| format!("missing snarked ledger {hash}"))?;
+            chain.push(part);
+            next = part.base.as_ref();
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+}
+
+/// Changes to the persisted transition frontier.
+pub struct FrontierUpdate {
+    /// Snarked ledgers which aren't persisted yet.
+    pub new_snarked_ledgers: Vec<(LedgerHash, PersistedSnarkedLedger)>,
+    /// All snarked ledgers needed by the new transition frontier. Ones
+    /// which aren't in this set, or a base of one in it, are removed from
+    /// the storage.
+    pub snarked_ledgers: BTreeSet<LedgerHash>,
+    /// `None` if root didn't change since the last update.
+    pub root_staged_ledger_parts: Option<Arc<StagedLedgerAuxAndPendingCoinbases>>,
+    /// Hash of the root block of the new best chain.
+    pub root: StateHash,
+    /// Blocks of the new best chain which weren't in the best chain at the
+    /// last update, with snapshots of their staged ledgers.
+    pub new_blocks: Vec<(AppliedBlock, Option<PersistedStagedLedger>)>,
+}
+
+/// Write which is encoded and performed in the storage thread, so that
+/// the ledger manager isn't blocked by the disk io.
+struct PendingWrite {
+    snarked_ledgers: Vec<(LedgerHash, PersistedSnarkedLedger)>,
+    snarked_ledger_list: Vec<StoredSnarkedLedger>,
+    root_staged_ledger_parts: Option<Arc<StagedLedgerAuxAndPendingCoinbases>>,
+    blocks: Vec<(AppliedBlock, Option<PersistedStagedLedger>)>,
+    best_chain: Vec<StateHash>,
+    remove: Vec<Box<[u8]>>,
+}
+
+/// On-disk storage of the transition frontier (root snarked ledger,
+/// staking and next epoch ledgers, root staged ledger scan state and the
+/// best chain with snapshots of the staged ledgers) inside the node's work
+/// dir.
+///
+/// Blocks are written once, when they become part of the best chain, and
+/// removed when they leave it. Snarked ledgers are written once too, as
+/// the accounts changed since a previously written ledger, which is kept
+/// for as long as it's the base of a needed ledger.
+///
+/// Backed by [`ledger::ondisk::Database`].
+pub struct LedgerStorage {
+    db: Arc<Mutex<ondisk::Database>>,
+    writer: mpsc::Sender<PendingWrite>,
+    writer_thread: Option<thread::JoinHandle<()>>,
+    /// Persisted snarked ledgers, in the order they were written.
+    snarked_ledgers: Vec<StoredSnarkedLedger>,
+    best_chain: Vec<StateHash>,
+}
+
+impl LedgerStorage {
+    pub fn open(directory: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut db = ondisk::Database::create(directory)?;
+        // Get rid of data left over from the previous runs, if there is
+        // enough of it.
+        if db.garbage_bytes()? > GC_THRESHOLD_BYTES {
+            db.gc()?;
+        }
+
+        let read_list = |db: &mut ondisk::Database, key: &[u8]| -> std::io::Result<_> {
+            let Some(bytes) = db.get(key)? else {
+                return Ok(Vec::new());
+            };
+            decode(&bytes).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+            })
+        };
+        let snarked_ledgers: Vec<StoredSnarkedLedger> = read_list(&mut db, SNARKED_LEDGERS_KEY)?;
+        let best_chain: Vec<StateHash> = read_list(&mut db, BEST_CHAIN_KEY)?;
+
+        let db = Arc::new(Mutex::new(db));
+        let (writer, receiver) = mpsc::channel();
+        let writer_db = db.clone();
+        let writer_thread = thread::Builder::new()
+            .name("ledger-storage".into())
+            .spawn(move || storage_writer_loop(writer_db, receiver))?;
+
+        Ok(Self {
+            db,
+            writer,
+            writer_thread: Some(writer_thread),
+            snarked_ledgers,
+            best_chain,
+        })
+    }
+
+    pub fn contains_snarked_ledger(&self, hash: &LedgerHash) -> bool {
+        self.stored_snarked_ledger(hash).is_some()
+    }
+
+    fn stored_snarked_ledger(&self, hash: &LedgerHash) -> Option<&StoredSnarkedLedger> {
+        self.snarked_ledgers.iter().find(|l| &l.hash == hash)
+    }
+
+    /// Number of diffs between the snarked ledger and the whole ledger
+    /// it's based on.
+    fn snarked_ledger_diffs(&self, hash: &LedgerHash) -> usize {
+        std::iter::successors(self.stored_snarked_ledger(hash), |l| {
+            self.stored_snarked_ledger(l.base.as_ref()?)
+        })
+        .count()
+        .saturating_sub(1)
+    }
+
+    /// Persisted snarked ledger, which a new ledger can be stored as a
+    /// diff of. The most recently written one, as the root snarked ledger
+    /// only moves forward, among the ones which are `available`.
+    pub fn snarked_ledger_base(
+        &self,
+        available: impl Fn(&LedgerHash) -> bool,
+    ) -> Option<&LedgerHash> {
+        self.snarked_ledgers
+            .iter()
+            .rev()
+            .map(|l| &l.hash)
+            .find(|hash| {
+                self.snarked_ledger_diffs(hash) < MAX_SNARKED_LEDGER_DIFFS && available(hash)
+            })
+    }
+
+    pub fn contains_block(&self, hash: &StateHash) -> bool {
+        self.best_chain.contains(hash)
+    }
+
+    /// Hash of the persisted root block.
+    pub fn root(&self) -> Option<&StateHash> {
+        self.best_chain.first()
+    }
+
+    /// Loads the persisted transition frontier, `None` if nothing was
+    /// persisted yet.
+    pub fn load(&self) -> Result<Option<PersistedFrontier>, String> {
+        if self.best_chain.is_empty() {
+            return Ok(None);
+        }
+        let mut db = self.db.lock().map_err(|_| "storage lock poisoned")?;
+        let mut get = |key: &[u8]| {
+            db.get(key)
+                .map_err(|err| format!("failed to read {:?}: {err}", String::from_utf8_lossy(key)))?
+                .ok_or_else(|| format!("missing {:?}", String::from_utf8_lossy(key)))
+        };
+
+        let snarked_ledgers = self
+            .snarked_ledgers
+            .iter()
+            .filter(|l| l.needed)
+            .map(|l| l.hash.clone())
+            .collect();
+        let snarked_ledger_parts = self
+            .snarked_ledgers
+            .iter()
+            .map(|StoredSnarkedLedger { hash, .. }| {
+                let part = decode(&get(&snarked_ledger_key(hash))?)
+                    .map_err(|err| format!("failed to decode snarked ledger {hash}: {err}"))?;
+                Ok((hash.clone(), part))
+            })
+            .collect::<Result<BTreeMap<_, _>, String>>()?;
+
+        let root_staged_ledger_parts = decode(&get(ROOT_STAGED_LEDGER_PARTS_KEY)?)
+            .map(Arc::new)
+            .map_err(|err| format!("failed to decode root staged ledger parts: {err}"))?;
+
+        let best_chain = self
+            .best_chain
+            .iter()
+            .map(|hash| {
+                let PersistedBlock {
+                    block,
+                    just_emitted_a_proof,
+                } = decode(&get(&block_key(hash))?)
+                    .map_err(|err| format!("failed to decode block {hash}: {err}"))?;
+                let block = BlockWithHash::try_new(Arc::new(block))
+                    .map_err(|err| format!("invalid block {hash}: {err:?}"))?;
+                // Missing or broken snapshot only means that the block
+                // needs to be applied again.
+                let staged_ledger = get(&staged_ledger_key(hash))
+                    .ok()
+                    .and_then(|bytes| decode(&bytes).ok());
+                let block = AppliedBlock {
+                    block,
+                    just_emitted_a_proof,
+                };
+                Ok((block, staged_ledger))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Some(PersistedFrontier {
+            snarked_ledgers,
+            snarked_ledger_parts,
+            root_staged_ledger_parts,
+            best_chain,
+        }))
+    }
+
+    /// Best chain after applying the update to the persisted one.
+    ///
+    /// New blocks replace the blocks after their predecessor, then the
+    /// blocks before the new root are dropped. Empty if the update doesn't
+    /// connect to the persisted best chain.
+    fn updated_best_chain(
+        &self,
+        root: &StateHash,
+        new_blocks: &[(AppliedBlock, Option<PersistedStagedLedger>)],
+    ) -> Vec<StateHash> {
+        let mut best_chain = self.best_chain.clone();
+        if let Some((first, _)) = new_blocks.first() {
+            let pred_index = best_chain.iter().position(|hash| hash == first.pred_hash());
+            best_chain.truncate(pred_index.map_or(0, |i| i + 1));
+            best_chain.extend(new_blocks.iter().map(|(b, _)| b.hash().clone()));
+        }
+        match best_chain.iter().position(|hash| hash == root) {
+            Some(root_index) => {
+                best_chain.drain(..root_index);
+                best_chain
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Persisted snarked ledgers after the update. New ledgers are added
+    /// and the ones, which are neither needed nor a base of a needed one,
+    /// are dropped.
+    fn updated_snarked_ledgers(
+        &self,
+        new_snarked_ledgers: &[(LedgerHash, PersistedSnarkedLedger)],
+        needed: &BTreeSet<LedgerHash>,
+    ) -> Vec<StoredSnarkedLedger> {
+        let mut ledgers = self.snarked_ledgers.clone();
+        for (hash, part) in new_snarked_ledgers {
+            if !ledgers.iter().any(|l| &l.hash == hash) {
+                ledgers.push(StoredSnarkedLedger {
+                    hash: hash.clone(),
+                    base: part.base.clone(),
+                    needed: false,
+                });
+            }
+        }
+        let bases = ledgers
+            .iter()
+            .map(|l| (&l.hash, l.base.as_ref()))
+            .collect::<BTreeMap<_, _>>();
+        let mut kept = BTreeSet::new();
+        for hash in needed {
+            let mut next = bases.contains_key(hash).then_some(hash);
+            while let Some(hash) = next.filter(|hash| kept.insert(*hash)) {
+                next = bases.get(hash).copied().flatten();
+            }
+        }
+        let kept = kept.into_iter().cloned().collect::<BTreeSet<_>>();
+
+        ledgers.retain(|l| kept.contains(&l.hash));
+        for l in &mut ledgers {
+            l.needed = needed.contains(&l.hash);
+        }
+        ledgers
+    }
+
+    /// Persists the changes in the storage thread.
+    pub fn update(&mut self, update: FrontierUpdate) {
+        let FrontierUpdate {
+            new_snarked_ledgers,
+            snarked_ledgers,
+            root_staged_ledger_parts,
+            root,
+            new_blocks,
+        } = update;
+
+        let new_best_chain = self.updated_best_chain(&root, &new_blocks);
+        if new_best_chain.is_empty() {
+            // Writing would remove every persisted block, while leaving the
+            // root staged ledger parts behind.
+            openmina_core::log::inner::error!(
+                "LedgerStorage::update: new blocks don't connect to the persisted best chain, root: {root}"
+            );
+            return;
+        }
+        let kept_blocks: BTreeSet<&StateHash> = new_best_chain.iter().collect();
+        let old_blocks: BTreeSet<&StateHash> = self.best_chain.iter().collect();
+
+        let new_snarked_ledger_list =
+            self.updated_snarked_ledgers(&new_snarked_ledgers, &snarked_ledgers);
+        let mut remove: Vec<Box<[u8]>> = self
+            .snarked_ledgers
+            .iter()
+            .filter(|old| !new_snarked_ledger_list.iter().any(|l| l.hash == old.hash))
+            .map(|l| snarked_ledger_key(&l.hash))
+            .collect();
+        for hash in old_blocks
+            .iter()
+            .filter(|hash| !kept_blocks.contains(*hash))
+        {
+            remove.push(block_key(hash));
+            remove.push(staged_ledger_key(hash));
+        }
+        let blocks = new_blocks
+            .into_iter()
+            .filter(|(b, _)| kept_blocks.contains(b.hash()) && !old_blocks.contains(b.hash()))
+            .collect();
+
+        let write = PendingWrite {
+            snarked_ledgers: new_snarked_ledgers,
+            snarked_ledger_list: new_snarked_ledger_list.clone(),
+            root_staged_ledger_parts,
+            blocks,
+            best_chain: new_best_chain.clone(),
+            remove,
+        };
+
+        self.snarked_ledgers = new_snarked_ledger_list;
+        self.best_chain = new_best_chain;
+
+        if self.writer.send(write).is_err() {
+            openmina_core::log::inner::error!("LedgerStorage::update: storage thread is gone");
+        }
+    }
+}
+
+impl Drop for LedgerStorage {
+    /// Waits for the pending writes to finish.
+    fn drop(&mut self) {
+        let (closed, _) = mpsc::channel();
+        drop(std::mem::replace(&mut self.writer, closed));
+        if let Some(writer_thread) = self.writer_thread.take() {
+            let _ = writer_thread.join();
+        }
+    }
+}
+
+fn storage_writer_loop(db: Arc<Mutex<ondisk::Database>>, receiver: mpsc::Receiver<PendingWrite>) {
+    while let Ok(write) = receiver.recv() {
+        let mut batch = Batch::new();
+        let mut set = |key: Box<[u8]>, value: std::io::Result<Box<[u8]>>| match value {
+            Ok(value) => batch.set(key, value),
+            Err(err) => openmina_core::log::inner::error!(
+                "LedgerStorage: failed to encode {:?}: {err}",
+                String::from_utf8_lossy(&key)
+            ),
+        };
+
+        for (hash, part) in &write.snarked_ledgers {
+            set(snarked_ledger_key(hash), encode(part));
+        }
+        if let Some(parts) = &write.root_staged_ledger_parts {
+            set(ROOT_STAGED_LEDGER_PARTS_KEY.into(), encode(&**parts));
+        }
+        for (block, staged_ledger) in &write.blocks {
+            let value = PersistedBlock {
+                block: (**block.block()).clone(),
+                just_emitted_a_proof: block.just_emitted_a_proof,
+            };
+            set(block_key(block.hash()), encode(&value));
+            if let Some(staged_ledger) = staged_ledger {
+                set(staged_ledger_key(block.hash()), encode(staged_ledger));
+            }
+        }
+        set(
+            SNARKED_LEDGERS_KEY.into(),
+            encode(&write.snarked_ledger_list),
+        );
+        set(BEST_CHAIN_KEY.into(), encode(&write.best_chain));
+        for key in write.remove {
+            batch.remove(key);
+        }
+
+        let Ok(mut db) = db.lock() else {
+            return;
+        };
+        if let Err(err) = db.run_batch(&mut batch) {
+            openmina_core::log::inner::error!("LedgerStorage: failed to write: {err}");
+            continue;
+        }
+        match db.garbage_bytes() {
+            Ok(garbage) if garbage > GC_THRESHOLD_BYTES => {
+                if let Err(err) = db.gc() {
+                    openmina_core::log::inner::error!("LedgerStorage: gc failed: {err}");
+                }
+            }
+            Ok(_) => {}
+            Err(err) => openmina_core::log::inner::error!(
+                "LedgerStorage: failed to estimate garbage: {err}"
+            ),
+        }
+    }
+}
+
+/// Root staged ledger parts of an empty staged ledger.
+#[cfg(test)]
+pub(crate) fn test_root_staged_ledger_parts() -> Arc<StagedLedgerAuxAndPendingCoinbases> {
+    use ledger::staged_ledger::staged_ledger::StagedLedger;
+    use ledger::Mask;
+    use mina_p2p_messages::list::List;
+    use openmina_core::constants::constraint_constants;
+
+    let mut staged_ledger = StagedLedger::create_exn(
+        constraint_constants().clone(),
+        Mask::create(crate::ledger::LEDGER_DEPTH),
+    )
+    .unwrap();
+    staged_ledger.pending_coinbase_collection_merkle_root();
+    Arc::new(StagedLedgerAuxAndPendingCoinbases {
+        scan_state: staged_ledger.scan_state().into(),
+        staged_ledger_hash: LedgerHash::zero(),
+        pending_coinbase: staged_ledger.pending_coinbase_collection().into(),
+        needed_blocks: List::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_hasher::Fp;
+
+    use super::*;
+    use crate::transition_frontier::test_chain;
+
+    fn update(
+        root: &AppliedBlock,
+        new_blocks: &[AppliedBlock],
+        root_staged_ledger_parts: Option<Arc<StagedLedgerAuxAndPendingCoinbases>>,
+    ) -> FrontierUpdate {
+        FrontierUpdate {
+            new_snarked_ledgers: Vec::new(),
+            snarked_ledgers: Default::default(),
+            root_staged_ledger_parts,
+            root: root.hash().clone(),
+            new_blocks: new_blocks.iter().map(|b| (b.clone(), None)).collect(),
+        }
+    }
+
+    fn hashes<'a>(blocks: impl IntoIterator<Item = &'a AppliedBlock>) -> Vec<StateHash> {
+        blocks.into_iter().map(|b| b.hash().clone()).collect()
+    }
+
+    #[test]
+    fn persist_only_the_change() {
+        let dir = std::env::temp_dir().join(format!("ledger-storage-{}", ledger::next_uuid()));
+        let chain = test_chain(None, 4, 0);
+        let fork = test_chain(Some(&chain[2]), 2, 1);
+
+        let mut storage = LedgerStorage::open(&dir).unwrap();
+        assert!(storage.load().unwrap().is_none());
+        storage.update(update(
+            &chain[0],
+            &chain,
+            Some(test_root_staged_ledger_parts()),
+        ));
+        assert_eq!(storage.best_chain, hashes(&chain));
+
+        // Switch to a fork, while the root moves.
+        storage.update(update(&chain[1], &fork, None));
+        let expected = hashes([&chain[1], &chain[2], &fork[0], &fork[1]]);
+        assert_eq!(storage.best_chain, expected);
+        assert!(!storage.contains_block(chain[0].hash()));
+        assert!(!storage.contains_block(chain[3].hash()));
+
+        // Blocks which are already persisted are skipped.
+        storage.update(update(&chain[1], &[], None));
+        assert_eq!(storage.best_chain, expected);
+
+        // Update with a root which isn't in the persisted chain is skipped.
+        assert!(storage.updated_best_chain(chain[0].hash(), &[]).is_empty());
+        storage.update(update(&chain[0], &[], None));
+        assert_eq!(storage.best_chain, expected);
+
+        drop(storage);
+        let storage = LedgerStorage::open(&dir).unwrap();
+        assert_eq!(storage.best_chain, expected);
+        let persisted = storage.load().unwrap().unwrap();
+        let best_chain = persisted.best_chain.iter().map(|(b, _)| b);
+        assert_eq!(hashes(best_chain), expected);
+        assert!(persisted
+            .best_chain
+            .iter()
+            .all(|(_, staged_ledger)| staged_ledger.is_none()));
+        drop(storage);
+
+        let mut db = ondisk::Database::create(&dir).unwrap();
+        for hash in [chain[0].hash(), chain[3].hash()] {
+            assert!(!db.contains_key(&block_key(hash)).unwrap());
+        }
+        assert!(db.get(&block_key(fork[1].hash())).unwrap().is_some());
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn skip_update_which_does_not_connect() {
+        let dir = std::env::temp_dir().join(format!("ledger-storage-{}", ledger::next_uuid()));
+        let chain = test_chain(None, 3, 0);
+        let other = test_chain(None, 2, 1);
+
+        let mut storage = LedgerStorage::open(&dir).unwrap();
+        storage.update(update(
+            &chain[0],
+            &chain,
+            Some(test_root_staged_ledger_parts()),
+        ));
+        storage.update(update(&other[0], &other[1..], None));
+        assert_eq!(storage.best_chain, hashes(&chain));
+
+        drop(storage);
+        let storage = LedgerStorage::open(&dir).unwrap();
+        let persisted = storage.load().unwrap().unwrap();
+        let best_chain = persisted.best_chain.iter().map(|(b, _)| b);
+        assert_eq!(hashes(best_chain), hashes(&chain));
+        assert!(other.iter().all(|b| !storage.contains_block(b.hash())));
+        drop(storage);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keep_bases_of_needed_snarked_ledgers() {
+        let dir = std::env::temp_dir().join(format!("ledger-storage-{}", ledger::next_uuid()));
+        let chain = test_chain(None, 2, 0);
+        let [a, b, c, d] = [1u64, 2, 3, 4].map(|n| LedgerHash::from_fp(Fp::from(n)));
+        let part = |base: Option<&LedgerHash>| PersistedSnarkedLedger {
+            base: base.cloned(),
+            accounts: Vec::new(),
+        };
+
+        let mut storage = LedgerStorage::open(&dir).unwrap();
+        storage.update(FrontierUpdate {
+            new_snarked_ledgers: vec![
+                (a.clone(), part(None)),
+                (b.clone(), part(Some(&a))),
+                (c.clone(), part(Some(&b))),
+            ],
+            snarked_ledgers: [a.clone(), c.clone()].into(),
+            ..update(&chain[0], &chain, Some(test_root_staged_ledger_parts()))
+        });
+        // `b` is kept as a base of `c`, but isn't restored itself.
+        assert!([&a, &b, &c]
+            .iter()
+            .all(|h| storage.contains_snarked_ledger(h)));
+        assert_eq!(storage.snarked_ledger_diffs(&c), 2);
+        assert_eq!(storage.snarked_ledger_base(|_| true), Some(&c));
+        assert_eq!(storage.snarked_ledger_base(|h| h != &c), Some(&b));
+
+        drop(storage);
+        let mut storage = LedgerStorage::open(&dir).unwrap();
+        let persisted = storage.load().unwrap().unwrap();
+        assert_eq!(persisted.snarked_ledgers, vec![a.clone(), c.clone()]);
+        let chain_of = |hash| {
+            let chain = persisted.snarked_ledger_chain(hash).unwrap();
+            chain
+                .into_iter()
+                .map(|p| p.base.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(chain_of(&c), vec![None, Some(a.clone()), Some(b.clone())]);
+        assert_eq!(chain_of(&a), vec![None]);
+
+        // Ledgers which aren't needed, nor a base of a needed one, are removed.
+        storage.update(FrontierUpdate {
+            new_snarked_ledgers: vec![(d.clone(), part(Some(&b)))],
+            snarked_ledgers: [d.clone()].into(),
+            ..update(&chain[0], &[], None)
+        });
+        assert!(!storage.contains_snarked_ledger(&c));
+        assert!([&a, &b, &d]
+            .iter()
+            .all(|h| storage.contains_snarked_ledger(h)));
+        drop(storage);
+
+        let mut db = ondisk::Database::create(&dir).unwrap();
+        assert!(!db.contains_key(&snarked_ledger_key(&c)).unwrap());
+        assert!(db.contains_key(&snarked_ledger_key(&b)).unwrap());
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -21,6 +21,15 @@ pub use ledger_effects::*;
 
 mod ledger_service;
 pub use ledger_service::*;
+
+mod ledger_storage;
+pub use ledger_storage::*;
+
+mod ledger_archive;
+pub use ledger_archive::*;
+
+mod ledger_export;
+pub use ledger_export::*;
 pub mod ledger_manager;
 
 pub use ledger::AccountIndex as LedgerAccountIndex;
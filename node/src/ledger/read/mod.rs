@@ -8,7 +8,7 @@ use openmina_core::requests::RpcId;
 
 mod ledger_read_reducer;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
@@ -18,7 +18,10 @@ use crate::account::AccountPublicKey;
 use crate::block_producer::vrf_evaluator::DelegatorTable;
 use crate::ledger::LedgerAddress;
 use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
-use crate::rpc::{AccountQuery, RpcScanStateSummaryScanStateJob};
+use crate::rpc::{
+    AccountQuery, RpcLedgerAccountProofGetResponse, RpcLedgerExportResponse,
+    RpcScanStateSummaryScanStateJob, RpcTransactionSimulateQuery, RpcTransactionSimulateResponse,
+};
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Copy)]
 pub enum LedgerReadKind {
@@ -28,23 +31,34 @@ pub enum LedgerReadKind {
     GetChildHashesAtAddr,
     GetChildAccountsAtAddr,
     GetStagedLedgerAuxAndPendingCoinbases,
+    GetEpochLedger,
     ScanStateSummary,
     AccountsForRpc,
+    AccountProofForRpc,
+    LedgerExportForRpc,
+    TransactionSimulate,
+    WatchedAccountGet,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum LedgerReadRequest {
-    /// Delegator table requested by vrf state machine.
-    DelegatorTable(v2::LedgerHash, AccountPublicKey),
+    /// Delegator table for our producer keys, requested by vrf state machine.
+    DelegatorTable(v2::LedgerHash, BTreeSet<AccountPublicKey>),
     // p2p rpcs
     GetNumAccounts(v2::LedgerHash),
     GetAccounts(v2::LedgerHash, Vec<AccountId>, Option<RpcId>),
     GetChildHashesAtAddr(v2::LedgerHash, LedgerAddress),
     GetChildAccountsAtAddr(v2::LedgerHash, LedgerAddress),
     GetStagedLedgerAuxAndPendingCoinbases(LedgerReadStagedLedgerAuxAndPendingCoinbases),
+    GetEpochLedger(v2::LedgerHash),
     // rpcs
     ScanStateSummary(v2::MinaBaseStagedLedgerHashStableV1),
     AccountsForRpc(RpcId, v2::LedgerHash, AccountQuery),
+    AccountProofForRpc(RpcId, v2::LedgerHash, AccountId),
+    LedgerExportForRpc(RpcId, v2::LedgerHash),
+    TransactionSimulate(RpcId, Box<LedgerReadTransactionSimulate>),
+    /// Account with the default token, requested by watched accounts.
+    WatchedAccountGet(v2::LedgerHash, v2::NonZeroCurvePoint),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -57,9 +71,15 @@ pub enum LedgerReadResponse {
     GetChildHashesAtAddr(Option<(v2::LedgerHash, v2::LedgerHash)>),
     GetChildAccountsAtAddr(Option<Vec<v2::MinaBaseAccountBinableArgStableV2>>),
     GetStagedLedgerAuxAndPendingCoinbases(Option<Arc<StagedLedgerAuxAndPendingCoinbases>>),
+    GetEpochLedger(Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>>),
     // rpcs
     ScanStateSummary(Result<Vec<Vec<RpcScanStateSummaryScanStateJob>>, String>),
     AccountsForRpc(RpcId, Vec<Account>, AccountQuery),
+    AccountProofForRpc(RpcId, RpcLedgerAccountProofGetResponse),
+    LedgerExportForRpc(RpcId, RpcLedgerExportResponse),
+    TransactionSimulate(RpcId, RpcTransactionSimulateResponse),
+    /// `Ok(None)` if the account doesn't exist in the ledger.
+    WatchedAccountGet(Result<Option<Box<v2::MinaBaseAccountBinableArgStableV2>>, String>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -68,6 +88,16 @@ pub struct LedgerReadStagedLedgerAuxAndPendingCoinbases {
     pub protocol_states: BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>,
 }
 
+/// User command to be applied on a throwaway mask of the best tip ledger.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct LedgerReadTransactionSimulate {
+    pub best_tip: v2::StateHash,
+    pub ledger_hash: v2::LedgerHash,
+    pub protocol_state: v2::MinaStateProtocolStateValueStableV2,
+    pub global_slot: u32,
+    pub query: RpcTransactionSimulateQuery,
+}
+
 impl LedgerReadRequest {
     pub fn kind(&self) -> LedgerReadKind {
         match self {
@@ -79,8 +109,13 @@ impl LedgerReadRequest {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
+            Self::AccountProofForRpc(..) => LedgerReadKind::AccountProofForRpc,
+            Self::LedgerExportForRpc(..) => LedgerReadKind::LedgerExportForRpc,
+            Self::TransactionSimulate(..) => LedgerReadKind::TransactionSimulate,
+            Self::WatchedAccountGet(..) => LedgerReadKind::WatchedAccountGet,
         }
     }
 
@@ -96,9 +131,17 @@ impl LedgerReadRequest {
             }
             Self::GetChildHashesAtAddr(..) => 1,
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => 100,
+            Self::GetEpochLedger(..) => 100,
             Self::ScanStateSummary(..) => 100,
             // TODO(adonagy): not sure
             Self::AccountsForRpc(..) => 10,
+            Self::AccountProofForRpc(..) => 1,
+            // Reads the whole ledger.
+            Self::LedgerExportForRpc(..) => 100,
+            // zkApp proof verification is expensive.
+            Self::TransactionSimulate(_, req) if req.query.verify => 100,
+            Self::TransactionSimulate(..) => 10,
+            Self::WatchedAccountGet(..) => 1,
         };
         cost.max(1)
     }
@@ -115,8 +158,13 @@ impl LedgerReadResponse {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
+            Self::AccountProofForRpc(..) => LedgerReadKind::AccountProofForRpc,
+            Self::LedgerExportForRpc(..) => LedgerReadKind::LedgerExportForRpc,
+            Self::TransactionSimulate(..) => LedgerReadKind::TransactionSimulate,
+            Self::WatchedAccountGet(..) => LedgerReadKind::WatchedAccountGet,
         }
     }
 }
@@ -28,6 +28,7 @@ pub enum LedgerWriteKind {
     StagedLedgerDiffCreate,
     BlockApply,
     Commit,
+    FrontierRestore,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -58,6 +59,8 @@ pub enum LedgerWriteRequest {
         new_root: AppliedBlock,
         new_best_tip: AppliedBlock,
     },
+    /// Restore transition frontier persisted by the previous run of the node.
+    FrontierRestore { genesis_ledger_hash: v2::LedgerHash },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -79,6 +82,9 @@ pub enum LedgerWriteResponse {
         best_tip_hash: v2::StateHash,
         result: CommitResult,
     },
+    FrontierRestore {
+        result: Result<FrontierRestoreResult, String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -92,6 +98,15 @@ pub struct CommitResult {
     pub needed_protocol_states: BTreeSet<v2::StateHash>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FrontierRestoreResult {
+    /// Restored best chain, from root to best tip. Empty if there was
+    /// nothing to restore.
+    pub best_chain: Vec<AppliedBlock>,
+    /// Required protocol states for root block.
+    pub needed_protocol_states: BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>,
+}
+
 impl LedgerWriteRequest {
     pub fn kind(&self) -> LedgerWriteKind {
         match self {
@@ -99,6 +114,7 @@ impl LedgerWriteRequest {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierRestore { .. } => LedgerWriteKind::FrontierRestore,
         }
     }
 }
@@ -110,6 +126,7 @@ impl LedgerWriteResponse {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierRestore { .. } => LedgerWriteKind::FrontierRestore,
         }
     }
 }
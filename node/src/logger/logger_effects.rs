@@ -78,6 +78,7 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                 P2pChannelsAction::Transaction(action) => action.action_event(&context),
                 P2pChannelsAction::Snark(action) => action.action_event(&context),
                 P2pChannelsAction::SnarkJobCommitment(action) => action.action_event(&context),
+                P2pChannelsAction::SignalingRelay(action) => action.action_event(&context),
                 P2pChannelsAction::Rpc(action) => action.action_event(&context),
                 P2pChannelsAction::StreamingRpc(action) => action.action_event(&context),
             },
@@ -89,9 +90,11 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                     action.action_event(&context)
                 }
                 P2pChannelsEffectfulAction::Snark(action) => action.action_event(&context),
+                P2pChannelsEffectfulAction::SignalingRelay(action) => action.action_event(&context),
                 P2pChannelsEffectfulAction::Transaction(action) => action.action_event(&context),
             },
             P2pAction::Peer(action) => action.action_event(&context),
+            P2pAction::Reputation(action) => action.action_event(&context),
             P2pAction::Network(action) => match action {
                 P2pNetworkAction::Scheduler(action) => match action {
                     // MioErrors in scheduler are logged using debug instead of warn, to prevent spam
@@ -2,6 +2,7 @@ pub use ::p2p::channels::*;
 
 pub mod best_tip;
 pub mod rpc;
+pub mod signaling_relay;
 pub mod snark;
 pub mod snark_job_commitment;
 pub mod streaming_rpc;
@@ -1,7 +1,9 @@
 pub use ::p2p::*;
 use p2p::channels::{
     best_tip_effectful::P2pChannelsBestTipEffectfulAction,
-    rpc_effectful::P2pChannelsRpcEffectfulAction, snark_effectful::P2pChannelsSnarkEffectfulAction,
+    rpc_effectful::P2pChannelsRpcEffectfulAction,
+    signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction,
+    snark_effectful::P2pChannelsSnarkEffectfulAction,
     snark_job_commitment_effectful::P2pChannelsSnarkJobCommitmentEffectfulAction,
     streaming_rpc_effectful::P2pChannelsStreamingRpcEffectfulAction,
     transaction_effectful::P2pChannelsTransactionEffectfulAction,
@@ -12,6 +14,7 @@ pub mod connection;
 pub mod disconnection;
 pub mod network;
 pub mod peer;
+pub mod reputation;
 
 mod p2p_effects;
 pub use p2p_effects::*;
@@ -89,6 +92,8 @@ impl_into_global_action!(connection::incoming::P2pConnectionIncomingAction);
 
 impl_into_global_action!(disconnection::P2pDisconnectionAction);
 
+impl_into_global_action!(reputation::P2pReputationAction);
+
 impl_into_global_action!(network::P2pNetworkSchedulerAction);
 impl_into_global_action!(network::kad::P2pNetworkKademliaAction);
 impl_into_global_action!(network::pubsub::P2pNetworkPubsubAction);
@@ -127,5 +132,6 @@ impl_into_global_action!(P2pChannelsTransactionEffectfulAction);
 impl_into_global_action!(P2pChannelsSnarkJobCommitmentEffectfulAction);
 impl_into_global_action!(P2pChannelsRpcEffectfulAction);
 impl_into_global_action!(P2pChannelsSnarkEffectfulAction);
+impl_into_global_action!(P2pChannelsSignalingRelayEffectfulAction);
 
 impl p2p::P2pActionTrait<crate::State> for crate::Action {}
@@ -1,14 +1,24 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
 use ark_ff::fields::arithmetic::InvalidBigInt;
-use mina_p2p_messages::v2::{MinaLedgerSyncLedgerAnswerStableV2, StateHash};
+use mina_p2p_messages::core::InetAddrV1;
+use mina_p2p_messages::number::Number;
+use mina_p2p_messages::rpc::NodeStatusV2;
+use mina_p2p_messages::v2::{self, MinaLedgerSyncLedgerAnswerStableV2, StateHash};
 use openmina_core::block::BlockWithHash;
 use openmina_core::bug_condition;
+use openmina_core::consensus::consensus_take;
 use p2p::channels::streaming_rpc::{
     P2pChannelsStreamingRpcAction, P2pStreamingRpcRequest, P2pStreamingRpcResponseFull,
 };
 use p2p::channels::transaction::P2pChannelsTransactionAction;
 use p2p::channels::P2pChannelsEffectfulAction;
+use p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
 use p2p::connection::P2pConnectionEffectfulAction;
-use p2p::P2pInitializeAction;
+use p2p::webrtc::SignalingMethod;
+use p2p::{P2pInitializeAction, P2pNetworkPubsubAction, P2pState};
+use redux::Timestamp;
 
 use crate::consensus::ConsensusAction;
 use crate::rpc::RpcAction;
@@ -20,7 +30,10 @@ use crate::transition_frontier::sync::ledger::snarked::{
 use crate::transition_frontier::sync::ledger::staged::{
     PeerStagedLedgerPartsFetchError, TransitionFrontierSyncLedgerStagedAction,
 };
-use crate::transition_frontier::sync::{PeerBlockFetchError, TransitionFrontierSyncAction};
+use crate::transition_frontier::sync::{
+    PeerBlockFetchError, SyncPhase, TransitionFrontierSyncAction, TransitionFrontierSyncState,
+};
+use crate::transition_frontier::TransitionFrontierState;
 use crate::watched_accounts::{
     WatchedAccountLedgerInitialState, WatchedAccountsAction,
     WatchedAccountsLedgerInitialStateGetError,
@@ -37,6 +50,7 @@ use super::connection::outgoing::P2pConnectionOutgoingAction;
 use super::connection::{P2pConnectionAction, P2pConnectionResponse};
 use super::disconnection::{P2pDisconnectionAction, P2pDisconnectionReason};
 use super::peer::P2pPeerAction;
+use super::reputation::P2P_REPUTATION_BAN_THRESHOLD;
 use super::{P2pAction, P2pActionWithMeta};
 
 pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithMeta) {
@@ -217,13 +231,14 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                     }
                     P2pChannelsTransactionAction::Libp2pReceived {
                         peer_id: _,
-                        transaction,
+                        transactions,
                         nonce: _,
+                        message_id,
                     } => {
                         store.dispatch(TransactionPoolAction::StartVerify {
-                            // TODO: Take multiple transactions here
-                            commands: [*transaction].into_iter().collect(),
+                            commands: transactions.into_iter().collect(),
                             from_rpc: None,
+                            from_pubsub: Some(message_id),
                         });
                     }
                     _ => {}
@@ -256,6 +271,9 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                     });
                 }
             }
+            P2pChannelsAction::SignalingRelay(_) => {
+                // handled by reducer
+            }
             P2pChannelsAction::Rpc(action) => {
                 match action {
                     P2pChannelsRpcAction::Ready { peer_id } => {
@@ -455,6 +473,13 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                                 });
                             }
                             Some(P2pRpcResponse::InitialPeers(_)) => {}
+                            Some(
+                                P2pRpcResponse::TransitionKnowledge(_)
+                                | P2pRpcResponse::TransitionChainProof(..)
+                                | P2pRpcResponse::Ancestry(_)
+                                | P2pRpcResponse::NodeStatus(_)
+                                | P2pRpcResponse::EpochLedger(_),
+                            ) => {}
                         }
                         store.dispatch(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
                         store.dispatch(
@@ -469,27 +494,10 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                     } => {
                         match *request {
                             P2pRpcRequest::BestTipWithProof => {
-                                let best_chain = &store.state().transition_frontier.best_chain;
-                                let response = None.or_else(|| {
-                                    let best_tip = best_chain.last()?;
-                                    let mut chain_iter = best_chain.iter();
-                                    let root_block = chain_iter.next()?;
-                                    // TODO(binier): cache body hashes
-                                    let Ok(body_hashes) = chain_iter
-                                        .map(|b| b.header().protocol_state.body.try_hash())
-                                        .collect::<Result<_, _>>()
-                                    else {
-                                        openmina_core::error!(meta.time(); "P2pRpcRequest::BestTipWithProof: invalid protocol state");
-                                        return None;
-                                    };
-
-                                    Some(BestTipWithProof {
-                                        best_tip: best_tip.block().clone(),
-                                        proof: (body_hashes, root_block.block().clone()),
-                                    })
-                                });
-                                let response =
-                                    response.map(P2pRpcResponse::BestTipWithProof).map(Box::new);
+                                let tf = &store.state().transition_frontier;
+                                let response = best_tip_with_proof(tf, meta.time())
+                                    .map(P2pRpcResponse::BestTipWithProof)
+                                    .map(Box::new);
                                 store.dispatch(P2pChannelsRpcAction::ResponseSend {
                                     peer_id,
                                     id,
@@ -539,6 +547,18 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                                     .peers
                                     .iter()
                                     .filter_map(|(_, v)| v.dial_opts.clone())
+                                    .filter(|opts| {
+                                        // relayed peers are advertised with us as the relay.
+                                        !matches!(
+                                            opts,
+                                            P2pConnectionOutgoingInitOpts::WebRTC {
+                                                signaling: SignalingMethod::P2p { .. },
+                                                ..
+                                            }
+                                        )
+                                    })
+                                    .chain(p2p.relayed_dial_opts())
+                                    .filter(|opts| opts.peer_id() != &peer_id)
                                     .collect();
                                 let response = Some(Box::new(P2pRpcResponse::InitialPeers(peers)));
 
@@ -548,6 +568,99 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                                     response,
                                 });
                             }
+                            P2pRpcRequest::TransitionKnowledge => {
+                                let best_chain = &store.state().transition_frontier.best_chain;
+                                let hashes = best_chain.iter().map(|b| b.hash().clone()).collect();
+                                let response =
+                                    Some(Box::new(P2pRpcResponse::TransitionKnowledge(hashes)));
+
+                                store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                                    peer_id,
+                                    id,
+                                    response,
+                                });
+                            }
+                            P2pRpcRequest::TransitionChainProof(hash) => {
+                                let tf = &store.state().transition_frontier;
+                                let response = match tf.chain_proof(&hash) {
+                                    None => None,
+                                    Some(Ok((root_block, body_hashes))) => {
+                                        Some(Box::new(P2pRpcResponse::TransitionChainProof(
+                                            root_block.hash().clone(),
+                                            body_hashes,
+                                        )))
+                                    }
+                                    Some(Err(_)) => {
+                                        openmina_core::error!(meta.time(); "P2pRpcRequest::TransitionChainProof: invalid protocol state");
+                                        None
+                                    }
+                                };
+
+                                store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                                    peer_id,
+                                    id,
+                                    response,
+                                });
+                            }
+                            P2pRpcRequest::Ancestry(hash, consensus_state) => {
+                                let tf = &store.state().transition_frontier;
+                                // only answer if our best tip isn't worse than
+                                // the one the peer has seen.
+                                let response = tf
+                                    .best_tip()
+                                    .filter(|best_tip| {
+                                        !consensus_take(
+                                            best_tip.consensus_state(),
+                                            &consensus_state,
+                                            best_tip.hash(),
+                                            &hash,
+                                        )
+                                    })
+                                    .and_then(|_| best_tip_with_proof(tf, meta.time()))
+                                    .map(P2pRpcResponse::Ancestry)
+                                    .map(Box::new);
+
+                                store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                                    peer_id,
+                                    id,
+                                    response,
+                                });
+                            }
+                            P2pRpcRequest::NodeStatus => {
+                                let state = store.state();
+                                let p2p = p2p_ready!(state.p2p, meta.time());
+                                let response = node_status(state, p2p, meta.time())
+                                    .map(Arc::new)
+                                    .map(P2pRpcResponse::NodeStatus)
+                                    .map(Box::new);
+
+                                store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                                    peer_id,
+                                    id,
+                                    response,
+                                });
+                            }
+                            P2pRpcRequest::EpochLedger(hash) => {
+                                // only staking and next epoch ledgers of the
+                                // best tip are served.
+                                let is_epoch_ledger = store
+                                    .state()
+                                    .transition_frontier
+                                    .best_tip()
+                                    .map_or(false, |b| {
+                                        b.staking_epoch_ledger_hash() == &hash
+                                            || b.next_epoch_ledger_hash() == &hash
+                                    });
+                                if !is_epoch_ledger {
+                                    store.dispatch(P2pChannelsRpcAction::ResponseSend {
+                                        peer_id,
+                                        id,
+                                        response: None,
+                                    });
+                                }
+                                // otherwise async ledger request will be
+                                // triggered by `LedgerReadAction::FindTodos`.
+                            }
                         }
                     }
                     P2pChannelsRpcAction::Init { .. } => {}
@@ -642,6 +755,7 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
             P2pChannelsEffectfulAction::Snark(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::Rpc(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::SnarkJobCommitment(action) => action.effects(&meta, store),
+            P2pChannelsEffectfulAction::SignalingRelay(action) => action.effects(&meta, store),
         },
         P2pAction::Peer(action) => match action {
             P2pPeerAction::Discovered { .. }
@@ -650,17 +764,44 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                 // handled by reducer
             }
             P2pPeerAction::BestTipUpdate { best_tip, .. } => {
-                store.dispatch(ConsensusAction::BlockReceived {
+                let hash = best_tip.hash.clone();
+                if !store.dispatch(ConsensusAction::BlockReceived {
                     hash: best_tip.hash,
                     block: best_tip.block,
                     chain_proof: None,
-                });
+                }) {
+                    // Known blocks aren't verified again, so validate the
+                    // pubsub messages carrying them here.
+                    if let Some(result) =
+                        store.state().consensus.known_block_pubsub_validation(&hash)
+                    {
+                        let message_ids = store
+                            .state()
+                            .p2p
+                            .ready()
+                            .into_iter()
+                            .flat_map(|p2p| {
+                                p2p.network
+                                    .scheduler
+                                    .broadcast_state
+                                    .pending_block_messages(&hash)
+                            })
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        for message_id in message_ids {
+                            store.dispatch(P2pNetworkPubsubAction::ValidateIncomingMessage {
+                                message_id,
+                                result,
+                            });
+                        }
+                    }
+                }
                 store.dispatch(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
                 store.dispatch(TransitionFrontierSyncLedgerStagedAction::PartsPeerFetchInit);
                 store.dispatch(TransitionFrontierSyncAction::BlocksPeersQuery);
             }
         },
-        P2pAction::Identify(_) => {
+        P2pAction::Reputation(_) | P2pAction::Identify(_) => {
             // handled by reducer
         }
         P2pAction::Network(_action) => {
@@ -673,3 +814,82 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
         },
     }
 }
+
+fn best_tip_with_proof(tf: &TransitionFrontierState, time: Timestamp) -> Option<BestTipWithProof> {
+    let best_tip = tf.best_tip()?;
+    let (root_block, body_hashes) = match tf.chain_proof(best_tip.hash())? {
+        Ok(proof) => proof,
+        Err(_) => {
+            openmina_core::error!(time; "best_tip_with_proof: invalid protocol state");
+            return None;
+        }
+    };
+    Some(BestTipWithProof {
+        best_tip: best_tip.block.clone(),
+        proof: (body_hashes, root_block.block().clone()),
+    })
+}
+
+/// Status of the node in the format of the `get_node_status` rpc.
+fn node_status(state: &crate::State, p2p: &P2pState, now: Timestamp) -> Option<NodeStatusV2> {
+    let tf = &state.transition_frontier;
+    let best_tip = tf.best_tip()?;
+    let sync_status = match &tf.sync {
+        TransitionFrontierSyncState::Idle => v2::SyncStatusTStableV1::Listening,
+        sync => match sync.sync_phase() {
+            SyncPhase::Bootstrap => v2::SyncStatusTStableV1::Bootstrap,
+            SyncPhase::Catchup => v2::SyncStatusTStableV1::Catchup,
+            SyncPhase::Synced => v2::SyncStatusTStableV1::Synced,
+        },
+    };
+    let uptime_minutes = Some(state.started_at())
+        .filter(|started_at| *started_at != Timestamp::ZERO)
+        .and_then(|started_at| now.checked_sub(started_at))
+        .map_or(0, |uptime| uptime.as_secs() / 60);
+
+    Some(NodeStatusV2 {
+        // we don't know our external ip address.
+        node_ip_addr: InetAddrV1::from(IpAddr::V4(Ipv4Addr::UNSPECIFIED)).into(),
+        node_peer_id: p2p.my_id().try_into().ok()?,
+        sync_status,
+        peers: p2p
+            .ready_peers_iter()
+            .filter_map(|(peer_id, _)| (*peer_id).try_into().ok())
+            .collect(),
+        block_producers: state
+            .block_producer
+            .config()
+            .map(|config| config.pub_key.clone())
+            .into_iter()
+            .collect(),
+        protocol_state_hash: best_tip.hash().clone(),
+        ban_statuses: p2p
+            .reputation
+            .bans(now)
+            .filter_map(|(peer_id, ban)| {
+                let score = p2p.reputation.score(peer_id, now);
+                // trust in the OCaml node is in range [-1, 1].
+                let trust = (score as f64 / -(P2P_REPUTATION_BAN_THRESHOLD as f64)).max(-1.0);
+                let banned_until_secs = u64::from(ban.banned_until) as f64 / 1_000_000_000.0;
+                let status = v2::TrustSystemPeerStatusStableV1 {
+                    trust: Number(trust),
+                    banned: v2::TrustSystemBannedStatusStableV1::BannedUntil(Number(
+                        banned_until_secs,
+                    )),
+                };
+                Some(((*peer_id).try_into().ok()?, status))
+            })
+            .collect(),
+        k_block_hashes_and_timestamps: tf
+            .best_chain
+            .iter()
+            .map(|b| {
+                let timestamp_ms = u64::from(b.timestamp()) / 1_000_000;
+                (b.hash().clone(), timestamp_ms.to_string().as_str().into())
+            })
+            .collect(),
+        git_commit: state.config.build.git.commit_hash.as_str().into(),
+        uptime_minutes: uptime_minutes as i32,
+        block_height_opt: Some(best_tip.height() as i32),
+    })
+}
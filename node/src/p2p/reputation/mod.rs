@@ -0,0 +1,3 @@
+pub use ::p2p::reputation::*;
+
+mod p2p_reputation_actions;
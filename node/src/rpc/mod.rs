@@ -7,14 +7,15 @@ use ledger::scan_state::currency::{Amount, Balance, Fee, Nonce, Slot};
 use ledger::scan_state::transaction_logic::signed_command::SignedCommandPayload;
 use ledger::scan_state::transaction_logic::{self, signed_command, valid, Memo};
 use ledger::transaction_pool::{diff, ValidCommandWithHash};
-use ledger::Account;
+use ledger::{Account, AccountId, AccountIndex, MerklePath};
 use mina_p2p_messages::bigint::BigInt;
 use mina_p2p_messages::v2::{
+    LedgerHash, MinaBaseAccountBinableArgStableV2, MinaBaseProofStableV2,
     MinaBaseSignedCommandPayloadBodyStableV2, MinaBaseTransactionStatusStableV2,
     MinaBaseUserCommandStableV2, MinaTransactionTransactionStableV2,
-    SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse, StateHash, TransactionHash,
+    SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse, StateHash, TokenIdKeyHash, TransactionHash,
 };
-use openmina_core::block::AppliedBlock;
+use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
 use openmina_core::consensus::ConsensusConstants;
 use openmina_node_account::AccountPublicKey;
 use p2p::bootstrap::P2pNetworkKadBootstrapStats;
@@ -42,16 +43,25 @@ use openmina_core::snark::SnarkJobId;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::block_producer::BlockWithoutProof;
 use crate::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkSpecError,
 };
+use crate::ledger::LedgerExport;
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
+use crate::p2p::reputation::P2pBanReason;
 use crate::p2p::PeerId;
-use crate::snark_pool::{JobCommitment, JobSummary};
+use crate::snark_pool::{JobCommitment, JobSummary, SnarkJobPricing, SnarkerPricing};
 use crate::stats::actions::{ActionStatsForBlock, ActionStatsSnapshot};
-use crate::stats::block_producer::{BlockProductionAttempt, BlockProductionAttemptWonSlot};
-use crate::stats::sync::SyncStatsSnapshot;
+use crate::stats::block_producer::{
+    BlockProductionAttempt, BlockProductionAttemptWonSlot, BlockProductionStatus,
+    BlockProofDurations,
+};
+use crate::stats::sync::{SyncStatsSnapshot, SyncStatus};
+use crate::watched_accounts::{
+    WatchedAccountBlockState, WatchedAccountLedgerInitialState, WatchedAccountState,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcRequest {
@@ -60,8 +70,13 @@ pub enum RpcRequest {
     ActionStatsGet(ActionStatsQuery),
     SyncStatsGet(SyncStatsQuery),
     BlockProducerStatsGet,
+    BlockProducerScheduleGet,
+    BlockProducerDryRun(RpcBlockProducerDryRunQuery),
     MessageProgressGet,
     PeersGet,
+    P2pBansGet,
+    P2pBan(RpcP2pBanQuery),
+    P2pUnban(PeerId),
     P2pConnectionOutgoing(P2pConnectionOutgoingInitOpts),
     P2pConnectionIncoming(P2pConnectionIncomingInitOpts),
     ScanStateSummaryGet(RpcScanStateSummaryGetQuery),
@@ -70,6 +85,7 @@ pub enum RpcRequest {
     SnarkerConfig,
     SnarkerJobCommit { job_id: SnarkJobId },
     SnarkerJobSpec { job_id: SnarkJobId },
+    SnarkerJobs,
     SnarkerWorkers,
     HealthCheck,
     ReadinessCheck,
@@ -77,15 +93,39 @@ pub enum RpcRequest {
     DiscoveryBoostrapStats,
     TransactionPoolGet,
     LedgerAccountsGet(AccountQuery),
+    LedgerAccountProofGet(RpcLedgerAccountProofQuery),
+    LedgerExport(RpcLedgerExportQuery),
+    WatchedAccountsAdd(AccountPublicKey),
+    WatchedAccountsRemove(AccountPublicKey),
+    WatchedAccountsGet(Option<AccountPublicKey>),
     TransactionInject(Vec<MinaBaseUserCommandStableV2>),
     TransitionFrontierUserCommandsGet,
+    TransitionFrontierTipsGet,
     BestChain(MaxLength),
     ConsensusConstantsGet,
     TransactionStatusGet(MinaBaseUserCommandStableV2),
+    TransactionSimulate(RpcTransactionSimulateQuery),
 }
 
 pub type MaxLength = u32;
 
+/// Event pushed to the rpc subscribers (e.g. graphql subscriptions), as
+/// opposed to being requested by them.
+#[derive(Serialize, Debug, Clone)]
+pub enum RpcSubscriptionEvent {
+    /// Best tip changed.
+    NewBestTip(AppliedBlock),
+    /// Best tip changed to a block, which isn't a descendant of the previous
+    /// best tip.
+    ChainReorganization { best_tip: StateHash },
+    /// Latest sync status. Published whenever the sync stats get updated, so
+    /// it might be the same as the previous one.
+    SyncStatus(SyncStatus),
+    /// Transactions of the watched account were included in the best tip,
+    /// or its state in the ledger got fetched.
+    WatchedAccountChanged(Box<RpcWatchedAccountChange>),
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcInjectPayment {
     fee: u64,
@@ -107,7 +147,6 @@ impl TryFrom<RpcInjectPayment> for MinaBaseUserCommandStableV2 {
             rx: value.signature_field.try_into()?,
             s: value.signature_scalar.try_into()?,
         };
-        println!("Signature: {signature}");
         let sc = signed_command::SignedCommand {
             payload: SignedCommandPayload::create(
                 Fee::from_u64(value.fee),
@@ -140,6 +179,17 @@ pub struct SyncStatsQuery {
     pub limit: Option<usize>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcBlockProducerDryRunQuery {
+    /// Won slot for which the block is produced. Defaults to our next won slot.
+    pub global_slot: Option<u32>,
+    /// Also create the block proof, which takes considerably longer.
+    /// Needs the producer key in the node process, remote signers don't
+    /// prove dry run blocks.
+    #[serde(default)]
+    pub prove: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcScanStateSummaryGetQuery {
     ForBestTip,
@@ -171,6 +221,24 @@ pub struct RpcPeerInfo {
     pub connection_status: PeerConnectionStatus,
     pub address: Option<String>,
     pub time: u64,
+    /// Outgoing data waiting for the peer to accept it, in bytes.
+    pub pending_outgoing_bytes: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pBanQuery {
+    pub peer_id: PeerId,
+    /// Ban duration in seconds, default one is used if missing.
+    pub duration_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pBan {
+    pub peer_id: PeerId,
+    pub reason: P2pBanReason,
+    pub score: i32,
+    pub banned_at: Timestamp,
+    pub banned_until: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -279,9 +347,25 @@ pub struct RpcSnarkPoolJobSnarkWorkDone {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "kind")]
 pub enum RpcSnarkerJobCommitResponse {
-    Ok,
+    Ok {
+        fee: CurrencyFeeStableV1,
+    },
     JobNotFound,
     JobTaken,
+    /// Job is claimed by another snarker, see [`SnarkJobPricing::ClaimedAtLowerFee`].
+    JobClaimedAtLowerFee {
+        target_fee: u64,
+        competing_fee: u64,
+    },
+    /// Job is claimed by another snarker, see [`SnarkJobPricing::ClaimedNoTakeover`].
+    JobClaimedNoTakeover {
+        competing_fee: u64,
+    },
+    /// Work for the job would be rejected, see [`SnarkJobPricing::FeeEvicted`].
+    JobFeeEvicted {
+        fee: u64,
+        evicted_fee: u64,
+    },
     SnarkerBusy,
 }
 
@@ -331,7 +415,12 @@ pub type RpcStatusGetResponse = Option<RpcNodeStatus>;
 pub type RpcActionStatsGetResponse = Option<ActionStatsResponse>;
 pub type RpcSyncStatsGetResponse = Option<Vec<SyncStatsSnapshot>>;
 pub type RpcBlockProducerStatsGetResponse = Option<RpcBlockProducerStats>;
+pub type RpcBlockProducerScheduleGetResponse = Option<RpcBlockProducerSchedule>;
+pub type RpcBlockProducerDryRunResponse = Result<RpcBlockProducerDryRun, String>;
 pub type RpcPeersGetResponse = Vec<RpcPeerInfo>;
+pub type RpcP2pBansGetResponse = Vec<RpcP2pBan>;
+pub type RpcP2pBanResponse = Result<(), String>;
+pub type RpcP2pUnbanResponse = Result<(), String>;
 pub type RpcP2pConnectionOutgoingResponse = Result<(), String>;
 pub type RpcScanStateSummaryGetResponse = Result<RpcScanStateSummary, String>;
 pub type RpcSnarkPoolGetResponse = Vec<RpcSnarkPoolJobSummary>;
@@ -340,10 +429,230 @@ pub type RpcSnarkerConfigGetResponse = Option<RpcSnarkerConfig>;
 pub type RpcTransactionPoolResponse = Vec<ValidCommandWithHash>;
 pub type RpcLedgerSlimAccountsResponse = Vec<AccountSlim>;
 pub type RpcLedgerAccountsResponse = Vec<Account>;
+pub type RpcLedgerAccountProofGetResponse = Result<RpcLedgerAccountProof, String>;
+pub type RpcLedgerExportResponse = Result<LedgerExport, String>;
+pub type RpcWatchedAccountsAddResponse = Result<(), String>;
+pub type RpcWatchedAccountsRemoveResponse = Result<(), String>;
+pub type RpcWatchedAccountsGetResponse = Vec<RpcWatchedAccount>;
 pub type RpcTransitionFrontierUserCommandsResponse = Vec<MinaBaseUserCommandStableV2>;
+pub type RpcTransitionFrontierTipsGetResponse = Vec<RpcTransitionFrontierTip>;
 pub type RpcBestChainResponse = Vec<AppliedBlock>;
 pub type RpcConsensusConstantsGetResponse = ConsensusConstants;
 pub type RpcTransactionStatusGetResponse = TransactionStatus;
+pub type RpcTransactionSimulateResponse = Result<RpcTransactionSimulation, String>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcLedgerAccountProofQuery {
+    pub public_key: AccountPublicKey,
+    /// Default token if not set.
+    #[serde(default)]
+    pub token_id: Option<TokenIdKeyHash>,
+    /// Snarked ledger to prove the account against. If not set, best tip
+    /// staged ledger is used.
+    #[serde(default)]
+    pub snarked_ledger_hash: Option<LedgerHash>,
+}
+
+impl RpcLedgerAccountProofQuery {
+    /// Ledger to prove the account against, `None` if no snarked ledger
+    /// is requested and there is no best tip yet.
+    pub fn ledger_hash(&self, best_tip: Option<&ArcBlockWithHash>) -> Option<LedgerHash> {
+        self.snarked_ledger_hash
+            .clone()
+            .or_else(|| Some(best_tip?.merkle_root_hash().clone()))
+    }
+
+    pub fn account_id(&self) -> Result<AccountId, String> {
+        let public_key = self
+            .public_key
+            .clone()
+            .try_into()
+            .map_err(|_| "invalid public key".to_owned())?;
+        Ok(AccountId {
+            public_key,
+            token_id: self.token_id.clone().map(Into::into).unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcLedgerExportQuery {
+    pub ledger: RpcLedgerExportKind,
+}
+
+/// Ledger to export, relative to the best tip.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum RpcLedgerExportKind {
+    StakingEpoch,
+    NextEpoch,
+    /// Snarked ledger of the transition frontier root.
+    RootSnarked,
+    BestTipStaged,
+}
+
+/// Account together with the Merkle path, proving that the account is
+/// part of the ledger with the `ledger_hash`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcLedgerAccountProof {
+    pub ledger_hash: LedgerHash,
+    /// Position of the account in the ledger.
+    pub index: u64,
+    pub account: Account,
+    /// Sibling hashes, from the account up to the root.
+    pub merkle_path: Vec<RpcMerklePathItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcMerklePathItem {
+    /// Node on the path is the left child, holds the hash of its right sibling.
+    Left(LedgerHash),
+    /// Node on the path is the right child, holds the hash of its left sibling.
+    Right(LedgerHash),
+}
+
+impl RpcLedgerAccountProof {
+    /// Checks the proof against its `ledger_hash`. It's up to the caller
+    /// to check that the hash itself is trusted, e.g. that it comes from
+    /// a verified block.
+    pub fn verify(&self) -> bool {
+        let Ok(root) = self.ledger_hash.to_field() else {
+            return false;
+        };
+        let merkle_path = self
+            .merkle_path
+            .iter()
+            .map(|item| match item {
+                RpcMerklePathItem::Left(hash) => hash.to_field().map(MerklePath::Left),
+                RpcMerklePathItem::Right(hash) => hash.to_field().map(MerklePath::Right),
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let Ok(merkle_path) = merkle_path else {
+            return false;
+        };
+        ledger::verify_account_merkle_proof(
+            &self.account,
+            AccountIndex(self.index),
+            &merkle_path,
+            root,
+        )
+    }
+}
+
+impl From<&MerklePath> for RpcMerklePathItem {
+    fn from(value: &MerklePath) -> Self {
+        match value {
+            MerklePath::Left(hash) => Self::Left(LedgerHash::from_fp(*hash)),
+            MerklePath::Right(hash) => Self::Right(LedgerHash::from_fp(*hash)),
+        }
+    }
+}
+
+/// Account watched by the node, with transactions affecting it since it
+/// started being watched.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcWatchedAccount {
+    pub public_key: AccountPublicKey,
+    /// Latest known state of the account. `None` if it isn't fetched yet
+    /// or if the account doesn't exist.
+    pub account: Option<MinaBaseAccountBinableArgStableV2>,
+    pub initial_state: WatchedAccountLedgerInitialState,
+    /// Blocks with transactions affecting the account, from oldest to
+    /// newest.
+    pub blocks: Vec<WatchedAccountBlockState>,
+}
+
+impl RpcWatchedAccount {
+    pub fn new(public_key: AccountPublicKey, state: &WatchedAccountState) -> Self {
+        Self {
+            public_key,
+            account: state.latest_ledger_account().cloned(),
+            initial_state: state.initial_state.clone(),
+            blocks: state.blocks.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Change of a watched account, published to the subscribers.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcWatchedAccountChange {
+    pub public_key: AccountPublicKey,
+    /// Latest known state of the account after the change.
+    pub account: Option<MinaBaseAccountBinableArgStableV2>,
+    /// Block with the account's transactions that was added or updated.
+    pub block: Option<WatchedAccountBlockState>,
+    /// Blocks removed from the account because they are no longer part
+    /// of the best chain.
+    pub orphaned_blocks: Vec<StateHash>,
+}
+
+/// Tip of one of the branches in the transition frontier.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcTransitionFrontierTip {
+    pub hash: StateHash,
+    pub height: u32,
+    pub global_slot: u32,
+    /// Height of the block at which this branch forks from the best chain.
+    pub fork_height: u32,
+    pub is_best_tip: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcTransactionSimulateQuery {
+    pub command: MinaBaseUserCommandStableV2,
+    /// Check signatures and zkApp proofs before applying the command.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+/// Outcome of applying the command on top of the best tip ledger. The
+/// ledger itself is left untouched.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcTransactionSimulation {
+    /// Block on top of which the command was applied.
+    pub best_tip: StateHash,
+    /// Global slot since genesis the command was applied at.
+    pub global_slot: u32,
+    pub fee: Fee,
+    pub applied: bool,
+    /// Failures grouped by the account update which caused them. For zkApp
+    /// commands, index 0 is the fee payer and account updates start from 1.
+    pub failures: Vec<RpcTransactionSimulationFailure>,
+    /// Accounts referenced by the command, with their state before and
+    /// after applying it.
+    pub accounts: Vec<RpcTransactionSimulationAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcTransactionSimulationFailure {
+    pub index: usize,
+    pub failures: Vec<transaction_logic::TransactionFailure>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcTransactionSimulationAccount {
+    pub public_key: AccountPublicKey,
+    pub token_id: TokenIdKeyHash,
+    /// Whether the account is created by the command.
+    pub created: bool,
+    /// `None` if the account didn't exist before.
+    pub balance_before: Option<Balance>,
+    pub balance_after: Balance,
+    pub nonce_before: Option<Nonce>,
+    pub nonce_after: Nonce,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, strum_macros::Display)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
@@ -369,10 +678,20 @@ pub struct RpcTransactionInjectedPayment {
     pub nonce: Nonce,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcTransactionInjectedDelegation {
+    pub fee: Fee,
+    pub from: AccountPublicKey,
+    pub to: AccountPublicKey,
+    pub hash: String,
+    pub memo: String,
+    pub nonce: Nonce,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcTransactionInjectedCommand {
     Payment(RpcTransactionInjectedPayment),
-    Delegation,
+    Delegation(RpcTransactionInjectedDelegation),
     Zkapp(valid::UserCommand),
 }
 
@@ -408,8 +727,15 @@ impl From<ValidCommandWithHash> for RpcTransactionInjectedCommand {
                             nonce: signedcmd.nonce(),
                         })
                     }
-                    transaction_logic::signed_command::Body::StakeDelegation(_) => {
-                        todo!("inject stake delegation")
+                    transaction_logic::signed_command::Body::StakeDelegation(ref delegation) => {
+                        Self::Delegation(RpcTransactionInjectedDelegation {
+                            fee: signedcmd.fee(),
+                            from: signedcmd.fee_payer_pk().clone().into(),
+                            to: delegation.receiver_pk().clone().into(),
+                            hash: TransactionHash::from(value.hash.as_ref()).to_string(),
+                            memo: signedcmd.payload.common.memo.to_string(),
+                            nonce: signedcmd.nonce(),
+                        })
                     }
                 }
             }
@@ -427,6 +753,43 @@ pub struct AccountSlim {
     pub nonce: Nonce,
 }
 
+impl RpcBlockProducerKeyStats {
+    pub fn new(
+        pub_key: NonZeroCurvePoint,
+        coinbase_receiver: NonZeroCurvePoint,
+        attempts: &[BlockProductionAttempt],
+        future_won_slots: &[BlockProductionAttemptWonSlot],
+    ) -> Self {
+        let mut stats = Self {
+            future_won_slots: future_won_slots
+                .iter()
+                .filter(|won_slot| won_slot.producer == pub_key)
+                .count(),
+            pub_key,
+            coinbase_receiver,
+            produced: 0,
+            canonical: 0,
+            orphaned: 0,
+            discarded: 0,
+        };
+        for attempt in attempts
+            .iter()
+            .filter(|attempt| attempt.won_slot.producer == stats.pub_key)
+        {
+            if attempt.block.is_some() {
+                stats.produced += 1;
+            }
+            match attempt.status {
+                BlockProductionStatus::Canonical { .. } => stats.canonical += 1,
+                BlockProductionStatus::Orphaned { .. } => stats.orphaned += 1,
+                BlockProductionStatus::Discarded { .. } => stats.discarded += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
 impl From<Account> for AccountSlim {
     fn from(value: Account) -> Self {
         Self {
@@ -486,12 +849,69 @@ pub struct RpcBlockProducerStats {
     pub epoch_end: Option<u32>,
     pub attempts: Vec<BlockProductionAttempt>,
     pub future_won_slots: Vec<BlockProductionAttemptWonSlot>,
+    /// Stats for each of the producer keys run by this node.
+    #[serde(default)]
+    pub producers: Vec<RpcBlockProducerKeyStats>,
+    /// Block proof creation times since the start.
+    #[serde(default)]
+    pub proof_durations: BlockProofDurations,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerKeyStats {
+    pub pub_key: NonZeroCurvePoint,
+    pub coinbase_receiver: NonZeroCurvePoint,
+    pub future_won_slots: usize,
+    pub produced: usize,
+    pub canonical: usize,
+    pub orphaned: usize,
+    pub discarded: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerSchedule {
+    pub current_time: redux::Timestamp,
+    pub current_global_slot: Option<u32>,
+    /// Won slots in the current and the next epoch.
+    pub epochs: Vec<RpcBlockProducerScheduleEpoch>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerScheduleEpoch {
+    pub epoch: u32,
+    pub start_slot: u32,
+    pub end_slot: u32,
+    /// Whether the vrf evaluation for the epoch is finished. If it isn't,
+    /// `won_slots` might be incomplete.
+    pub evaluated: bool,
+    pub won_slots: Vec<BlockProductionAttemptWonSlot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerDryRun {
+    pub won_slot: BlockProductionAttemptWonSlot,
+    pub started_at: redux::Timestamp,
+    pub finished_at: redux::Timestamp,
+    pub block_hash: StateHash,
+    pub block: BlockWithoutProof,
+    /// `None` unless proving was requested.
+    pub proof: Option<Box<MinaBaseProofStableV2>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcSnarkerConfig {
     public_key: NonZeroCurvePoint,
     fee: CurrencyFeeStableV1,
+    pricing: SnarkerPricing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkerJob {
+    pub id: SnarkJobId,
+    pub order: usize,
+    pub summary: JobSummary,
+    /// Decision of the snarker's pricing policy about the job.
+    pub pricing: SnarkJobPricing,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -510,6 +930,7 @@ pub enum RpcSnarkWorkerStatus {
     Working {
         job_id: SnarkJobId,
         summary: JobSummary,
+        fee: CurrencyFeeStableV1,
     },
     WorkReady {
         job_id: SnarkJobId,
@@ -531,6 +952,7 @@ pub enum RpcSnarkWorkerStatus {
     Killing,
 }
 
+pub type RpcSnarkerJobsResponse = Vec<RpcSnarkerJob>;
 pub type RpcSnarkerWorkersResponse = Vec<RpcSnarkWorker>;
 
 impl From<&MinaTransactionTransactionStableV2> for RpcScanStateSummaryBlockTransactionKind {
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use ledger::transaction_pool::{diff, ValidCommandWithHash};
 use ledger::Account;
 use mina_p2p_messages::v2::MinaBaseUserCommandStableV2;
@@ -12,10 +14,13 @@ use crate::external_snark_worker::SnarkWorkId;
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::{P2pConnectionOutgoingError, P2pConnectionOutgoingInitOpts};
 use crate::p2p::connection::P2pConnectionResponse;
+use crate::p2p::PeerId;
 
 use super::{
-    ActionStatsQuery, RpcId, RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob,
-    SyncStatsQuery,
+    ActionStatsQuery, RpcBlockProducerDryRun, RpcBlockProducerDryRunQuery, RpcId,
+    RpcLedgerAccountProofGetResponse, RpcLedgerAccountProofQuery, RpcLedgerExportQuery,
+    RpcLedgerExportResponse, RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob,
+    RpcTransactionSimulateQuery, RpcTransactionSimulateResponse, SyncStatsQuery,
 };
 
 pub type RpcActionWithMeta = redux::ActionWithMeta<RpcAction>;
@@ -43,6 +48,23 @@ pub enum RpcAction {
     BlockProducerStatsGet {
         rpc_id: RpcId,
     },
+    BlockProducerScheduleGet {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info)]
+    BlockProducerDryRunInit {
+        rpc_id: RpcId,
+        query: RpcBlockProducerDryRunQuery,
+    },
+    BlockProducerDryRunSuccess {
+        rpc_id: RpcId,
+        output: Box<RpcBlockProducerDryRun>,
+    },
+    #[action_event(level = warn, fields(error))]
+    BlockProducerDryRunError {
+        rpc_id: RpcId,
+        error: String,
+    },
 
     MessageProgressGet {
         rpc_id: RpcId,
@@ -51,6 +73,20 @@ pub enum RpcAction {
     PeersGet {
         rpc_id: RpcId,
     },
+    P2pBansGet {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info)]
+    P2pBan {
+        rpc_id: RpcId,
+        peer_id: PeerId,
+        duration: Duration,
+    },
+    #[action_event(level = info)]
+    P2pUnban {
+        rpc_id: RpcId,
+        peer_id: PeerId,
+    },
 
     P2pConnectionOutgoingInit {
         rpc_id: RpcId,
@@ -121,6 +157,9 @@ pub enum RpcAction {
         rpc_id: RpcId,
         job_id: SnarkJobId,
     },
+    SnarkerJobsGet {
+        rpc_id: RpcId,
+    },
 
     SnarkerWorkersGet {
         rpc_id: RpcId,
@@ -159,6 +198,32 @@ pub enum RpcAction {
         account_query: AccountQuery,
     },
     #[action_event(level = info)]
+    LedgerAccountProofGetInit {
+        rpc_id: RpcId,
+        query: RpcLedgerAccountProofQuery,
+    },
+    LedgerAccountProofGetPending {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info)]
+    LedgerAccountProofGetSuccess {
+        rpc_id: RpcId,
+        response: RpcLedgerAccountProofGetResponse,
+    },
+    #[action_event(level = info)]
+    LedgerExportInit {
+        rpc_id: RpcId,
+        query: RpcLedgerExportQuery,
+    },
+    LedgerExportPending {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info)]
+    LedgerExportSuccess {
+        rpc_id: RpcId,
+        response: RpcLedgerExportResponse,
+    },
+    #[action_event(level = info)]
     TransactionInjectInit {
         rpc_id: RpcId,
         commands: Vec<MinaBaseUserCommandStableV2>,
@@ -186,6 +251,9 @@ pub enum RpcAction {
     TransitionFrontierUserCommandsGet {
         rpc_id: RpcId,
     },
+    TransitionFrontierTipsGet {
+        rpc_id: RpcId,
+    },
 
     BestChain {
         rpc_id: RpcId,
@@ -200,6 +268,35 @@ pub enum RpcAction {
         tx: MinaBaseUserCommandStableV2,
     },
 
+    #[action_event(level = info)]
+    WatchedAccountsAdd {
+        rpc_id: RpcId,
+        public_key: AccountPublicKey,
+    },
+    #[action_event(level = info)]
+    WatchedAccountsRemove {
+        rpc_id: RpcId,
+        public_key: AccountPublicKey,
+    },
+    WatchedAccountsGet {
+        rpc_id: RpcId,
+        public_key: Option<AccountPublicKey>,
+    },
+
+    #[action_event(level = info)]
+    TransactionSimulateInit {
+        rpc_id: RpcId,
+        query: RpcTransactionSimulateQuery,
+    },
+    TransactionSimulatePending {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info)]
+    TransactionSimulateSuccess {
+        rpc_id: RpcId,
+        response: RpcTransactionSimulateResponse,
+    },
+
     Finish {
         rpc_id: RpcId,
     },
@@ -220,8 +317,15 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ActionStatsGet { .. } => true,
             RpcAction::SyncStatsGet { .. } => true,
             RpcAction::BlockProducerStatsGet { .. } => true,
+            RpcAction::BlockProducerScheduleGet { .. } => true,
+            RpcAction::BlockProducerDryRunInit { .. } => true,
+            RpcAction::BlockProducerDryRunSuccess { .. } => true,
+            RpcAction::BlockProducerDryRunError { .. } => true,
             RpcAction::MessageProgressGet { .. } => true,
             RpcAction::PeersGet { .. } => true,
+            RpcAction::P2pBansGet { .. } => true,
+            RpcAction::P2pBan { .. } => true,
+            RpcAction::P2pUnban { .. } => true,
             RpcAction::P2pConnectionOutgoingInit { rpc_id, .. } => {
                 !state.rpc.requests.contains_key(rpc_id)
             }
@@ -284,6 +388,7 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::SnarkerConfigGet { .. } => true,
             RpcAction::SnarkerJobCommit { .. } => true,
             RpcAction::SnarkerJobSpec { .. } => true,
+            RpcAction::SnarkerJobsGet { .. } => true,
             RpcAction::SnarkerWorkersGet { .. } => true,
             RpcAction::HealthCheck { .. } => true,
             RpcAction::ReadinessCheck { .. } => true,
@@ -293,6 +398,17 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ConsensusConstantsGet { .. } => true,
             RpcAction::BestChain { .. } => state.transition_frontier.best_tip().is_some(),
             RpcAction::TransactionStatusGet { .. } => true,
+            RpcAction::TransactionSimulateInit { .. } => true,
+            RpcAction::TransactionSimulatePending { rpc_id } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_init()),
+            RpcAction::TransactionSimulateSuccess { rpc_id, .. } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_pending()),
             RpcAction::LedgerAccountsGetInit { .. } => {
                 state.transition_frontier.best_tip().is_some()
             }
@@ -306,6 +422,28 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
                 .requests
                 .get(rpc_id)
                 .map_or(false, |v| v.status.is_pending()),
+            RpcAction::LedgerAccountProofGetInit { .. } => true,
+            RpcAction::LedgerAccountProofGetPending { rpc_id } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_init()),
+            RpcAction::LedgerAccountProofGetSuccess { rpc_id, .. } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_pending()),
+            RpcAction::LedgerExportInit { .. } => true,
+            RpcAction::LedgerExportPending { rpc_id } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_init()),
+            RpcAction::LedgerExportSuccess { rpc_id, .. } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .map_or(false, |v| v.status.is_pending()),
 
             RpcAction::TransactionInjectInit { .. } => true,
             RpcAction::TransactionInjectPending { rpc_id } => state
@@ -329,6 +467,10 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
                 .get(rpc_id)
                 .map_or(false, |v| v.status.is_pending()),
             RpcAction::TransitionFrontierUserCommandsGet { .. } => true,
+            RpcAction::TransitionFrontierTipsGet { .. } => true,
+            RpcAction::WatchedAccountsAdd { .. } => true,
+            RpcAction::WatchedAccountsRemove { .. } => true,
+            RpcAction::WatchedAccountsGet { .. } => true,
             RpcAction::Finish { rpc_id } => state
                 .rpc
                 .requests
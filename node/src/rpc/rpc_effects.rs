@@ -4,36 +4,42 @@ use std::time::Duration;
 use ledger::scan_state::currency::{Balance, Magnitude};
 use ledger::Account;
 use mina_p2p_messages::rpc_kernel::QueryHeader;
-use mina_p2p_messages::v2::{MinaBaseTransactionStatusStableV2, TransactionHash};
+use mina_p2p_messages::v2::{
+    MinaBaseTransactionStatusStableV2, NonZeroCurvePoint, TransactionHash,
+};
 use mina_signer::CompressedPubKey;
 use openmina_core::block::ArcBlockWithHash;
 use openmina_core::bug_condition;
 
-use crate::block_producer::BlockProducerWonSlot;
+use crate::block_producer::{BlockProducerAction, BlockProducerWonSlot};
 use crate::external_snark_worker::available_job_to_snark_worker_spec;
-use crate::ledger::read::{LedgerReadAction, LedgerReadRequest};
+use crate::ledger::read::{LedgerReadAction, LedgerReadRequest, LedgerReadTransactionSimulate};
 use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::P2pConnectionResponse;
+use crate::p2p::reputation::{P2pBanReason, P2pReputationAction};
 use crate::rpc::{
-    AccountSlim, PeerConnectionStatus, RpcPeerInfo, RpcTransactionInjectResponse,
+    AccountSlim, PeerConnectionStatus, RpcP2pBan, RpcPeerInfo, RpcTransactionInjectResponse,
     RpcTransactionInjectSuccess, TransactionStatus,
 };
-use crate::snark_pool::SnarkPoolAction;
+use crate::snark_pool::{SnarkJobPricing, SnarkPoolAction};
 use crate::transition_frontier::sync::ledger::TransitionFrontierSyncLedgerState;
 use crate::transition_frontier::sync::TransitionFrontierSyncState;
-use crate::{p2p_ready, Service, Store, TransactionPoolAction};
+use crate::{p2p_ready, Service, Store, TransactionPoolAction, WatchedAccountsAction};
 
 use super::{
     ActionStatsQuery, ActionStatsResponse, CurrentMessageProgress, MessagesStats, RpcAction,
-    RpcActionWithMeta, RpcBlockProducerStats, RpcMessageProgressResponse, RpcNodeStatus,
-    RpcNodeStatusTransactionPool, RpcNodeStatusTransitionFrontier,
-    RpcNodeStatusTransitionFrontierBlockSummary, RpcNodeStatusTransitionFrontierSync, RpcRequest,
-    RpcRequestExtraData, RpcScanStateSummary, RpcScanStateSummaryBlock,
-    RpcScanStateSummaryBlockTransaction, RpcScanStateSummaryBlockTransactionKind,
-    RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob, RpcSnarkPoolJobFull,
-    RpcSnarkPoolJobSnarkWork, RpcSnarkPoolJobSummary, RpcSnarkerJobCommitResponse,
-    RpcSnarkerJobSpecResponse, RpcTransactionInjectFailure, RpcTransactionInjectRejected,
+    RpcActionWithMeta, RpcBlockProducerKeyStats, RpcBlockProducerSchedule,
+    RpcBlockProducerScheduleEpoch, RpcBlockProducerStats, RpcLedgerExportKind,
+    RpcMessageProgressResponse, RpcNodeStatus, RpcNodeStatusTransactionPool,
+    RpcNodeStatusTransitionFrontier, RpcNodeStatusTransitionFrontierBlockSummary,
+    RpcNodeStatusTransitionFrontierSync, RpcRequest, RpcRequestExtraData, RpcScanStateSummary,
+    RpcScanStateSummaryBlock, RpcScanStateSummaryBlockTransaction,
+    RpcScanStateSummaryBlockTransactionKind, RpcScanStateSummaryGetQuery,
+    RpcScanStateSummaryScanStateJob, RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork,
+    RpcSnarkPoolJobSummary, RpcSnarkerJob, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
+    RpcTransactionInjectFailure, RpcTransactionInjectRejected, RpcTransitionFrontierTip,
+    RpcWatchedAccount,
 };
 
 macro_rules! respond_or_log {
@@ -129,6 +135,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
 
                 let stats = store.service.stats()?;
                 let attempts = stats.block_producer().collect_attempts();
+                let proof_durations = stats.block_producer().proof_durations().clone();
                 let future_slot = attempts.last().map_or(0, |v| v.won_slot.global_slot + 1);
 
                 let cur_global_slot = state.cur_global_slot();
@@ -136,26 +143,151 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 let epoch_start =
                     cur_global_slot.map(|slot| (slot / slots_per_epoch) * slots_per_epoch);
 
+                let future_won_slots = won_slots
+                    .range(future_slot..)
+                    .map(|(_, won_slot)| {
+                        let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
+                            won_slot,
+                            best_tip.genesis_timestamp(),
+                        );
+                        (&won_slot).into()
+                    })
+                    .collect::<Vec<_>>();
+
+                let config = state.block_producer.config()?;
+                let producers = config
+                    .producer_keys()
+                    .map(|pub_key| {
+                        let coinbase_receiver =
+                            config.coinbase_receiver_of(pub_key).unwrap_or(pub_key);
+                        RpcBlockProducerKeyStats::new(
+                            pub_key.clone(),
+                            coinbase_receiver.clone(),
+                            &attempts,
+                            &future_won_slots,
+                        )
+                    })
+                    .collect();
+
                 Some(RpcBlockProducerStats {
                     current_time: meta.time(),
                     current_global_slot: cur_global_slot,
                     epoch_start,
                     epoch_end: epoch_start.map(|slot| slot + slots_per_epoch),
                     attempts,
-                    future_won_slots: won_slots
-                        .range(future_slot..)
-                        .map(|(_, won_slot)| {
-                            let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
-                                won_slot,
-                                best_tip.genesis_timestamp(),
-                            );
-                            (&won_slot).into()
-                        })
-                        .collect(),
+                    future_won_slots,
+                    producers,
+                    proof_durations,
                 })
             });
             let _ = store.service.respond_block_producer_stats_get(rpc_id, resp);
         }
+        RpcAction::BlockProducerScheduleGet { rpc_id } => {
+            let resp = None.or_else(|| {
+                let state = store.state.get();
+                let best_tip = state.transition_frontier.best_tip()?;
+                let vrf_evaluator = state.block_producer.vrf_evaluator()?;
+
+                let cur_global_slot = state.cur_global_slot();
+                let slots_per_epoch = best_tip.constants().slots_per_epoch.as_u32();
+                let cur_epoch =
+                    cur_global_slot.unwrap_or_else(|| best_tip.global_slot()) / slots_per_epoch;
+
+                let epochs = [cur_epoch, cur_epoch + 1]
+                    .into_iter()
+                    .map(|epoch| {
+                        let start_slot = epoch * slots_per_epoch;
+                        let end_slot = start_slot + slots_per_epoch;
+                        let won_slots = vrf_evaluator
+                            .won_slots
+                            .range(start_slot..end_slot)
+                            .map(|(_, won_slot)| {
+                                let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
+                                    won_slot,
+                                    best_tip.genesis_timestamp(),
+                                );
+                                (&won_slot).into()
+                            })
+                            .collect();
+                        RpcBlockProducerScheduleEpoch {
+                            epoch,
+                            start_slot,
+                            end_slot,
+                            evaluated: vrf_evaluator.is_epoch_evaluated(epoch),
+                            won_slots,
+                        }
+                    })
+                    .collect();
+
+                Some(RpcBlockProducerSchedule {
+                    current_time: meta.time(),
+                    current_global_slot: cur_global_slot,
+                    epochs,
+                })
+            });
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_block_producer_schedule_get(rpc_id, resp),
+                meta.time()
+            );
+        }
+        RpcAction::BlockProducerDryRunInit { rpc_id, query } => {
+            let won_slot = None
+                .or_else(|| {
+                    let state = store.state.get();
+                    let Some(vrf_evaluator) = state.block_producer.vrf_evaluator() else {
+                        return Some(Err("block producer is not enabled".to_owned()));
+                    };
+                    let best_tip = state.transition_frontier.best_tip()?;
+                    let won_slot = match query.global_slot {
+                        Some(slot) => vrf_evaluator
+                            .won_slot(slot, best_tip)
+                            .filter(|won_slot| won_slot > best_tip)
+                            .ok_or_else(|| {
+                                format!("slot {slot} is not a future slot won by our producers")
+                            }),
+                        None => vrf_evaluator
+                            .next_won_slot(state.cur_global_slot()?, best_tip)
+                            .ok_or_else(|| "no future won slots".to_owned()),
+                    };
+                    Some(won_slot)
+                })
+                .unwrap_or_else(|| Err("node is not synced yet".to_owned()));
+
+            match won_slot {
+                Err(error) => {
+                    store.dispatch(RpcAction::BlockProducerDryRunError { rpc_id, error });
+                }
+                Ok(won_slot) => {
+                    if !store.dispatch(BlockProducerAction::DryRunInit {
+                        rpc_id,
+                        won_slot,
+                        prove: query.prove,
+                    }) {
+                        let error =
+                            "block producer is busy producing a block or about to".to_owned();
+                        store.dispatch(RpcAction::BlockProducerDryRunError { rpc_id, error });
+                    }
+                }
+            }
+        }
+        RpcAction::BlockProducerDryRunSuccess { rpc_id, output } => {
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_block_producer_dry_run(rpc_id, Ok(*output)),
+                meta.time()
+            );
+        }
+        RpcAction::BlockProducerDryRunError { rpc_id, error } => {
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_block_producer_dry_run(rpc_id, Err(error)),
+                meta.time()
+            );
+        }
         RpcAction::MessageProgressGet { rpc_id } => {
             // TODO: move to stats
             let p2p = p2p_ready!(store.state().p2p, meta.time());
@@ -243,6 +375,54 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 meta.time()
             );
         }
+        RpcAction::P2pBansGet { rpc_id } => {
+            let p2p = p2p_ready!(store.state().p2p, meta.time());
+            let bans = p2p
+                .reputation
+                .bans(meta.time())
+                .map(|(peer_id, ban)| RpcP2pBan {
+                    peer_id: *peer_id,
+                    reason: ban.reason.clone(),
+                    score: p2p.reputation.score(peer_id, meta.time()),
+                    banned_at: ban.banned_at,
+                    banned_until: ban.banned_until,
+                })
+                .collect();
+            respond_or_log!(
+                store.service().respond_p2p_bans_get(rpc_id, bans),
+                meta.time()
+            );
+        }
+        RpcAction::P2pBan {
+            rpc_id,
+            peer_id,
+            duration,
+        } => {
+            let response = if store.dispatch(P2pReputationAction::Ban {
+                peer_id,
+                reason: P2pBanReason::Manual,
+                duration,
+            }) {
+                Ok(())
+            } else {
+                Err(format!("cannot ban peer {peer_id}"))
+            };
+            respond_or_log!(
+                store.service().respond_p2p_ban(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::P2pUnban { rpc_id, peer_id } => {
+            let response = if store.dispatch(P2pReputationAction::Unban { peer_id }) {
+                Ok(())
+            } else {
+                Err(format!("peer {peer_id} is not banned"))
+            };
+            respond_or_log!(
+                store.service().respond_p2p_unban(rpc_id, response),
+                meta.time()
+            );
+        }
         RpcAction::P2pConnectionOutgoingInit { rpc_id, opts } => {
             store.dispatch(P2pConnectionOutgoingAction::Init {
                 opts,
@@ -264,7 +444,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
         }
         RpcAction::P2pConnectionIncomingInit { rpc_id, opts } => {
             let p2p = p2p_ready!(store.state().p2p, meta.time());
-            match p2p.incoming_accept(opts.peer_id, &opts.offer) {
+            match p2p.incoming_accept(opts.peer_id, &opts.offer, meta.time()) {
                 Ok(_) => {
                     store.dispatch(P2pConnectionIncomingAction::Init {
                         opts,
@@ -494,17 +674,64 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                     .map(|config| super::RpcSnarkerConfig {
                         public_key: config.public_key.as_ref().clone(),
                         fee: config.fee.clone(),
+                        pricing: config.pricing.clone(),
                     });
             let _ = store.service().respond_snarker_config_get(rpc_id, config);
         }
         RpcAction::SnarkerJobCommit { rpc_id, job_id } => {
-            if !store.state().snark_pool.should_create_commitment(&job_id) {
-                let _ = store
-                    .service()
-                    .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::JobNotFound);
-                // TODO(binier): differentiate between job not found and job already taken.
+            let state = store.state.get();
+            let pricing = state
+                .config
+                .snarker
+                .as_ref()
+                .zip(state.snark_pool.get(&job_id))
+                .map(|(config, job)| state.snark_pool.job_pricing(job, config));
+            let fee = match pricing {
+                None => {
+                    let _ = store.service().respond_snarker_job_commit(
+                        rpc_id,
+                        RpcSnarkerJobCommitResponse::JobNotFound,
+                    );
+                    return;
+                }
+                Some(SnarkJobPricing::Ours) => {
+                    let _ = store
+                        .service()
+                        .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::JobTaken);
+                    return;
+                }
+                Some(SnarkJobPricing::ClaimedAtLowerFee {
+                    target_fee,
+                    competing_fee,
+                }) => {
+                    let _ = store.service().respond_snarker_job_commit(
+                        rpc_id,
+                        RpcSnarkerJobCommitResponse::JobClaimedAtLowerFee {
+                            target_fee,
+                            competing_fee,
+                        },
+                    );
+                    return;
+                }
+                Some(SnarkJobPricing::ClaimedNoTakeover { competing_fee }) => {
+                    let _ = store.service().respond_snarker_job_commit(
+                        rpc_id,
+                        RpcSnarkerJobCommitResponse::JobClaimedNoTakeover { competing_fee },
+                    );
+                    return;
+                }
+                Some(SnarkJobPricing::FeeEvicted { fee, evicted_fee }) => {
+                    let _ = store.service().respond_snarker_job_commit(
+                        rpc_id,
+                        RpcSnarkerJobCommitResponse::JobFeeEvicted { fee, evicted_fee },
+                    );
+                    return;
+                }
+                Some(pricing) => pricing.fee(),
+            };
+            let Some(fee) = fee else {
                 return;
-            }
+            };
             if !store.state().external_snark_worker.has_idle() {
                 let _ = store
                     .service()
@@ -513,7 +740,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             }
             if store
                 .service()
-                .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::Ok)
+                .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::Ok { fee })
                 .is_err()
             {
                 return;
@@ -540,7 +767,18 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 return;
             };
             let public_key = config.public_key.clone().into();
-            let fee = config.fee.clone();
+            // Prove for the fee we committed (or would commit) to the job with.
+            let fee = store
+                .state()
+                .snark_pool
+                .job_pricing(job, config)
+                .fee()
+                .or_else(|| {
+                    job.commitment_msg()
+                        .filter(|c| &c.snarker == config.public_key.as_ref())
+                        .map(|c| c.fee.clone())
+                })
+                .unwrap_or_else(|| config.fee.clone());
             let input = match input {
                 Ok(instances) => RpcSnarkerJobSpecResponse::Ok(
                     mina_p2p_messages::v2::SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse(Some((
@@ -557,6 +795,26 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             // TODO: handle potential errors
             let _ = store.service().respond_snarker_job_spec(rpc_id, input);
         }
+        RpcAction::SnarkerJobsGet { rpc_id } => {
+            let state = store.state.get();
+            let jobs = state
+                .config
+                .snarker
+                .as_ref()
+                .map_or_else(Vec::new, |config| {
+                    state
+                        .snark_pool
+                        .jobs_iter()
+                        .map(|job| RpcSnarkerJob {
+                            id: job.id.clone(),
+                            order: job.order,
+                            summary: job.summary(),
+                            pricing: state.snark_pool.job_pricing(job, config),
+                        })
+                        .collect()
+                });
+            let _ = store.service().respond_snarker_jobs(rpc_id, jobs);
+        }
         RpcAction::SnarkerWorkersGet { rpc_id } => {
             let the_only = store.state().external_snark_worker.0.clone();
 
@@ -694,6 +952,83 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             }
         }
         RpcAction::LedgerAccountsGetPending { .. } => {}
+        RpcAction::LedgerAccountProofGetInit { rpc_id, query } => {
+            let ledger_hash = query.ledger_hash(store.state().transition_frontier.best_tip());
+            let account_id = query.account_id();
+
+            store.dispatch(RpcAction::LedgerAccountProofGetPending { rpc_id });
+            let (ledger_hash, account_id) = match (ledger_hash, account_id) {
+                (_, Err(error)) => {
+                    let response = Err(error);
+                    store.dispatch(RpcAction::LedgerAccountProofGetSuccess { rpc_id, response });
+                    return;
+                }
+                (None, _) => {
+                    let response = Err("node is not synced yet".to_owned());
+                    store.dispatch(RpcAction::LedgerAccountProofGetSuccess { rpc_id, response });
+                    return;
+                }
+                (Some(ledger_hash), Ok(account_id)) => (ledger_hash, account_id),
+            };
+            if !store.dispatch(LedgerReadAction::Init {
+                request: LedgerReadRequest::AccountProofForRpc(rpc_id, ledger_hash, account_id),
+            }) {
+                let response = Err("ledger is busy, try again later".to_owned());
+                store.dispatch(RpcAction::LedgerAccountProofGetSuccess { rpc_id, response });
+            }
+        }
+        RpcAction::LedgerAccountProofGetPending { .. } => {}
+        RpcAction::LedgerExportInit { rpc_id, query } => {
+            let state = store.state();
+            let ledger_hash = match query.ledger {
+                RpcLedgerExportKind::StakingEpoch => state
+                    .transition_frontier
+                    .best_tip()
+                    .map(|best_tip| best_tip.staking_epoch_ledger_hash().clone()),
+                RpcLedgerExportKind::NextEpoch => state
+                    .transition_frontier
+                    .best_tip()
+                    .map(|best_tip| best_tip.next_epoch_ledger_hash().clone()),
+                RpcLedgerExportKind::RootSnarked => state
+                    .transition_frontier
+                    .root()
+                    .map(|root| root.snarked_ledger_hash().clone()),
+                RpcLedgerExportKind::BestTipStaged => state
+                    .transition_frontier
+                    .best_tip()
+                    .map(|best_tip| best_tip.merkle_root_hash().clone()),
+            };
+
+            store.dispatch(RpcAction::LedgerExportPending { rpc_id });
+            let Some(ledger_hash) = ledger_hash else {
+                let response = Err("node is not synced yet".to_owned());
+                store.dispatch(RpcAction::LedgerExportSuccess { rpc_id, response });
+                return;
+            };
+            if !store.dispatch(LedgerReadAction::Init {
+                request: LedgerReadRequest::LedgerExportForRpc(rpc_id, ledger_hash),
+            }) {
+                let response = Err("ledger is busy, try again later".to_owned());
+                store.dispatch(RpcAction::LedgerExportSuccess { rpc_id, response });
+            }
+        }
+        RpcAction::LedgerExportPending { .. } => {}
+        RpcAction::LedgerExportSuccess { rpc_id, response } => {
+            respond_or_log!(
+                store.service().respond_ledger_export(rpc_id, response),
+                meta.time()
+            );
+            store.dispatch(RpcAction::Finish { rpc_id });
+        }
+        RpcAction::LedgerAccountProofGetSuccess { rpc_id, response } => {
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_ledger_account_proof_get(rpc_id, response),
+                meta.time()
+            );
+            store.dispatch(RpcAction::Finish { rpc_id });
+        }
         RpcAction::LedgerAccountsGetSuccess {
             rpc_id,
             accounts,
@@ -766,6 +1101,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             store.dispatch(TransactionPoolAction::StartVerify {
                 commands: commands.into_iter().collect(),
                 from_rpc: Some(rpc_id),
+                from_pubsub: None,
             });
         }
         RpcAction::TransactionInjectPending { .. } => {}
@@ -820,6 +1156,86 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 meta.time()
             )
         }
+        RpcAction::WatchedAccountsAdd { rpc_id, public_key } => {
+            let response = if store.dispatch(WatchedAccountsAction::Add {
+                pub_key: public_key.clone().into(),
+            }) {
+                Ok(())
+            } else {
+                Err(format!("account {public_key} is already watched"))
+            };
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_watched_accounts_add(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::WatchedAccountsRemove { rpc_id, public_key } => {
+            let response = if store.dispatch(WatchedAccountsAction::Remove {
+                pub_key: public_key.clone().into(),
+            }) {
+                Ok(())
+            } else {
+                Err(format!("account {public_key} is not watched"))
+            };
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_watched_accounts_remove(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::WatchedAccountsGet { rpc_id, public_key } => {
+            let pub_key = public_key.map(NonZeroCurvePoint::from);
+            let response = store
+                .state()
+                .watched_accounts
+                .iter()
+                .filter(|(key, _)| pub_key.as_ref().map_or(true, |pub_key| pub_key == *key))
+                .map(|(key, account)| RpcWatchedAccount::new(key.clone().into(), account))
+                .collect();
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_watched_accounts_get(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::TransitionFrontierTipsGet { rpc_id } => {
+            let transition_frontier = &store.state().transition_frontier;
+            let best_tip_hash = transition_frontier.best_tip().map(|b| b.hash());
+            let tips = transition_frontier
+                .tips()
+                .into_iter()
+                .map(|tip| {
+                    let fork_height = transition_frontier
+                        .chain_to(tip.hash())
+                        .and_then(|chain| {
+                            chain
+                                .into_iter()
+                                .rev()
+                                .find(|b| transition_frontier.forks.get(b.hash()).is_none())
+                                .map(|b| b.height())
+                        })
+                        .unwrap_or_else(|| tip.height());
+                    RpcTransitionFrontierTip {
+                        hash: tip.hash().clone(),
+                        height: tip.height(),
+                        global_slot: tip.global_slot(),
+                        fork_height,
+                        is_best_tip: Some(tip.hash()) == best_tip_hash,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_transition_frontier_tips(rpc_id, tips),
+                meta.time()
+            )
+        }
         RpcAction::BestChain { rpc_id, max_length } => {
             let best_chain = store
                 .state()
@@ -896,6 +1312,41 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 )
             }
         }
+        RpcAction::TransactionSimulateInit { rpc_id, query } => {
+            let state = store.state.get();
+            let request = state.transition_frontier.best_tip().and_then(|best_tip| {
+                Some(LedgerReadTransactionSimulate {
+                    best_tip: best_tip.hash().clone(),
+                    ledger_hash: best_tip.merkle_root_hash().clone(),
+                    protocol_state: best_tip.header().protocol_state.clone(),
+                    global_slot: state.cur_global_slot_since_genesis()?,
+                    query,
+                })
+            });
+
+            store.dispatch(RpcAction::TransactionSimulatePending { rpc_id });
+            let Some(request) = request else {
+                let response = Err("node is not synced yet".to_owned());
+                store.dispatch(RpcAction::TransactionSimulateSuccess { rpc_id, response });
+                return;
+            };
+            if !store.dispatch(LedgerReadAction::Init {
+                request: LedgerReadRequest::TransactionSimulate(rpc_id, Box::new(request)),
+            }) {
+                let response = Err("ledger is busy, try again later".to_owned());
+                store.dispatch(RpcAction::TransactionSimulateSuccess { rpc_id, response });
+            }
+        }
+        RpcAction::TransactionSimulatePending { .. } => {}
+        RpcAction::TransactionSimulateSuccess { rpc_id, response } => {
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_transaction_simulate(rpc_id, response),
+                meta.time()
+            );
+            store.dispatch(RpcAction::Finish { rpc_id });
+        }
         RpcAction::Finish { .. } => {}
     }
 }
@@ -934,6 +1385,12 @@ fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
                     best_tip_global_slot: best_tip.map(|bt| bt.global_slot_since_genesis()),
                     best_tip_timestamp: best_tip.map(|bt| bt.timestamp().into()),
                     time,
+                    pending_outgoing_bytes: p2p
+                        .network
+                        .scheduler
+                        .find_peer(peer_id)
+                        .and_then(|(_, connection)| connection.yamux_state())
+                        .map_or(0, |yamux| yamux.pending_outgoing_bytes()),
                 }
             })
             .collect()
@@ -18,10 +18,14 @@ impl From<ExternalSnarkWorkerState> for RpcSnarkWorkerStatus {
             ExternalSnarkWorkerState::None => RpcSnarkWorkerStatus::None,
             ExternalSnarkWorkerState::Starting => RpcSnarkWorkerStatus::Starting,
             ExternalSnarkWorkerState::Idle => RpcSnarkWorkerStatus::Idle,
-            ExternalSnarkWorkerState::Working(job_id, summary) => {
-                RpcSnarkWorkerStatus::Working { job_id, summary }
+            ExternalSnarkWorkerState::Working(job_id, summary, fee) => {
+                RpcSnarkWorkerStatus::Working {
+                    job_id,
+                    summary,
+                    fee,
+                }
             }
-            ExternalSnarkWorkerState::WorkReady(job_id, _) => {
+            ExternalSnarkWorkerState::WorkReady(job_id, ..) => {
                 RpcSnarkWorkerStatus::WorkReady { job_id }
             }
             ExternalSnarkWorkerState::WorkError(job_id, error) => {
@@ -12,8 +12,15 @@ impl RpcState {
             RpcAction::ActionStatsGet { .. } => {}
             RpcAction::SyncStatsGet { .. } => {}
             RpcAction::BlockProducerStatsGet { .. } => {}
+            RpcAction::BlockProducerScheduleGet { .. } => {}
+            RpcAction::BlockProducerDryRunInit { .. } => {}
+            RpcAction::BlockProducerDryRunSuccess { .. } => {}
+            RpcAction::BlockProducerDryRunError { .. } => {}
             RpcAction::MessageProgressGet { .. } => {}
             RpcAction::PeersGet { .. } => {}
+            RpcAction::P2pBansGet { .. } => {}
+            RpcAction::P2pBan { .. } => {}
+            RpcAction::P2pUnban { .. } => {}
             RpcAction::P2pConnectionOutgoingInit { rpc_id, opts } => {
                 let rpc_state = RpcRequestState {
                     req: RpcRequest::P2pConnectionOutgoing(opts.clone()),
@@ -100,6 +107,7 @@ impl RpcState {
             RpcAction::SnarkerConfigGet { .. } => {}
             RpcAction::SnarkerJobCommit { .. } => {}
             RpcAction::SnarkerJobSpec { .. } => {}
+            RpcAction::SnarkerJobsGet { .. } => {}
             RpcAction::SnarkerWorkersGet { .. } => {}
             RpcAction::HealthCheck { .. } => {}
             RpcAction::ReadinessCheck { .. } => {}
@@ -132,6 +140,58 @@ impl RpcState {
                 };
                 rpc.status = RpcRequestStatus::Success { time: meta.time() };
             }
+            RpcAction::LedgerAccountProofGetInit { rpc_id, query } => {
+                let rpc_state = RpcRequestState {
+                    req: RpcRequest::LedgerAccountProofGet(query.clone()),
+                    status: RpcRequestStatus::Init { time: meta.time() },
+                    data: Default::default(),
+                };
+                self.requests.insert(*rpc_id, rpc_state);
+            }
+            RpcAction::LedgerAccountProofGetPending { rpc_id } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = RpcRequestStatus::Pending { time: meta.time() };
+            }
+            RpcAction::LedgerAccountProofGetSuccess { rpc_id, response } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = match response {
+                    Ok(_) => RpcRequestStatus::Success { time: meta.time() },
+                    Err(error) => RpcRequestStatus::Error {
+                        time: meta.time(),
+                        error: error.clone(),
+                    },
+                };
+            }
+            RpcAction::LedgerExportInit { rpc_id, query } => {
+                let rpc_state = RpcRequestState {
+                    req: RpcRequest::LedgerExport(query.clone()),
+                    status: RpcRequestStatus::Init { time: meta.time() },
+                    data: Default::default(),
+                };
+                self.requests.insert(*rpc_id, rpc_state);
+            }
+            RpcAction::LedgerExportPending { rpc_id } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = RpcRequestStatus::Pending { time: meta.time() };
+            }
+            RpcAction::LedgerExportSuccess { rpc_id, response } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = match response {
+                    Ok(_) => RpcRequestStatus::Success { time: meta.time() },
+                    Err(error) => RpcRequestStatus::Error {
+                        time: meta.time(),
+                        error: error.clone(),
+                    },
+                };
+            }
             RpcAction::TransactionInjectInit { rpc_id, commands } => {
                 let rpc_state = RpcRequestState {
                     req: RpcRequest::TransactionInject(commands.clone()),
@@ -168,9 +228,39 @@ impl RpcState {
                 };
             }
             RpcAction::TransitionFrontierUserCommandsGet { .. } => {}
+            RpcAction::TransitionFrontierTipsGet { .. } => {}
             RpcAction::BestChain { .. } => {}
             RpcAction::ConsensusConstantsGet { .. } => {}
             RpcAction::TransactionStatusGet { .. } => {}
+            RpcAction::WatchedAccountsAdd { .. } => {}
+            RpcAction::WatchedAccountsRemove { .. } => {}
+            RpcAction::WatchedAccountsGet { .. } => {}
+            RpcAction::TransactionSimulateInit { rpc_id, query } => {
+                let rpc_state = RpcRequestState {
+                    req: RpcRequest::TransactionSimulate(query.clone()),
+                    status: RpcRequestStatus::Init { time: meta.time() },
+                    data: Default::default(),
+                };
+                self.requests.insert(*rpc_id, rpc_state);
+            }
+            RpcAction::TransactionSimulatePending { rpc_id } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = RpcRequestStatus::Pending { time: meta.time() };
+            }
+            RpcAction::TransactionSimulateSuccess { rpc_id, response } => {
+                let Some(rpc) = self.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = match response {
+                    Ok(_) => RpcRequestStatus::Success { time: meta.time() },
+                    Err(error) => RpcRequestStatus::Error {
+                        time: meta.time(),
+                        error: error.clone(),
+                    },
+                };
+            }
         }
     }
 }
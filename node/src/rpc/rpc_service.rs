@@ -6,15 +6,19 @@ use crate::p2p::connection::P2pConnectionResponse;
 use crate::State;
 
 use super::{
-    RpcActionStatsGetResponse, RpcBestChainResponse, RpcBlockProducerStatsGetResponse,
+    RpcActionStatsGetResponse, RpcBestChainResponse, RpcBlockProducerDryRunResponse,
+    RpcBlockProducerScheduleGetResponse, RpcBlockProducerStatsGetResponse,
     RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse,
-    RpcId, RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse,
-    RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse, RpcReadinessCheckResponse,
-    RpcScanStateSummaryGetResponse, RpcSnarkPoolGetResponse, RpcSnarkPoolJobGetResponse,
-    RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse, RpcSnarkerWorkersResponse,
-    RpcStatusGetResponse, RpcSyncStatsGetResponse, RpcTransactionInjectResponse,
-    RpcTransactionPoolResponse, RpcTransactionStatusGetResponse,
-    RpcTransitionFrontierUserCommandsResponse,
+    RpcId, RpcLedgerAccountsResponse, RpcLedgerExportResponse, RpcLedgerSlimAccountsResponse,
+    RpcMessageProgressResponse, RpcP2pBanResponse, RpcP2pBansGetResponse,
+    RpcP2pConnectionOutgoingResponse, RpcP2pUnbanResponse, RpcPeersGetResponse,
+    RpcReadinessCheckResponse, RpcScanStateSummaryGetResponse, RpcSnarkPoolGetResponse,
+    RpcSnarkPoolJobGetResponse, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
+    RpcSnarkerWorkersResponse, RpcStatusGetResponse, RpcSubscriptionEvent, RpcSyncStatsGetResponse,
+    RpcTransactionInjectResponse, RpcTransactionPoolResponse, RpcTransactionSimulateResponse,
+    RpcTransactionStatusGetResponse, RpcTransitionFrontierTipsGetResponse,
+    RpcTransitionFrontierUserCommandsResponse, RpcWatchedAccountsAddResponse,
+    RpcWatchedAccountsGetResponse, RpcWatchedAccountsRemoveResponse,
 };
 
 #[derive(Error, Serialize, Deserialize, Debug, Clone)]
@@ -67,6 +71,16 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcBlockProducerStatsGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_block_producer_schedule_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcBlockProducerScheduleGetResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_block_producer_dry_run(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcBlockProducerDryRunResponse,
+    ) -> Result<(), RespondError>;
     fn respond_message_progress_stats_get(
         &mut self,
         rpc_id: RpcId,
@@ -77,6 +91,21 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcPeersGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_p2p_bans_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pBansGetResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_p2p_ban(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pBanResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_p2p_unban(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pUnbanResponse,
+    ) -> Result<(), RespondError>;
     fn respond_p2p_connection_outgoing(
         &mut self,
         rpc_id: RpcId,
@@ -122,6 +151,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcSnarkerJobSpecResponse,
     ) -> Result<(), RespondError>;
+    fn respond_snarker_jobs(
+        &mut self,
+        rpc_id: RpcId,
+        response: super::RpcSnarkerJobsResponse,
+    ) -> Result<(), RespondError>;
     fn respond_snarker_workers(
         &mut self,
         rpc_id: RpcId,
@@ -162,6 +196,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcLedgerAccountsResponse,
     ) -> Result<(), RespondError>;
+    fn respond_ledger_account_proof_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcLedgerAccountProofGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_transaction_inject(
         &mut self,
         rpc_id: RpcId,
@@ -172,6 +211,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcTransitionFrontierUserCommandsResponse,
     ) -> Result<(), RespondError>;
+    fn respond_transition_frontier_tips(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcTransitionFrontierTipsGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_best_chain(
         &mut self,
         rpc_id: RpcId,
@@ -187,4 +231,31 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcTransactionStatusGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_ledger_export(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcLedgerExportResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_transaction_simulate(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcTransactionSimulateResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_watched_accounts_add(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcWatchedAccountsAddResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_watched_accounts_remove(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcWatchedAccountsRemoveResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_watched_accounts_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcWatchedAccountsGetResponse,
+    ) -> Result<(), RespondError>;
+    /// Publishes the event to the rpc subscribers.
+    fn publish_subscription_event(&mut self, event: RpcSubscriptionEvent);
 }
@@ -59,6 +59,9 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolCandidateAction {
         match self {
             SnarkPoolCandidateAction::InfoReceived { peer_id, info } => {
                 state.snark_pool.contains(&info.job_id)
+                    && !state
+                        .snark_pool
+                        .is_fee_evicted(&info.job_id, info.fee.0.as_u64())
                     && state
                         .snark_pool
                         .candidates
@@ -92,6 +95,9 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolCandidateAction {
             SnarkPoolCandidateAction::WorkReceived { peer_id, work } => {
                 let job_id = work.job_id();
                 state.snark_pool.contains(&job_id)
+                    && !state
+                        .snark_pool
+                        .is_fee_evicted(&job_id, work.fee.0.as_u64())
                     && state
                         .snark_pool
                         .candidates
@@ -1,11 +1,11 @@
 use std::collections::BTreeMap;
 
 use crate::{p2p_ready, SnarkPoolAction};
-use openmina_core::snark::Snark;
+use openmina_core::snark::{Snark, SnarkJobId};
 use p2p::{
     channels::rpc::{P2pChannelsRpcAction, P2pRpcId, P2pRpcRequest},
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
-    PeerId,
+    P2pNetworkPubsubAction, P2pNetworkPubsubValidationResult, PeerId,
 };
 use snark::{work_verify::SnarkWorkVerifyAction, work_verify_effectful::SnarkWorkVerifyId};
 
@@ -142,11 +142,23 @@ impl SnarkPoolCandidatesState {
                 state.verify_pending(meta.time(), peer_id, *verify_id, job_ids);
             }
             SnarkPoolCandidateAction::WorkVerifyError { peer_id, verify_id } => {
+                let job_ids = state
+                    .jobs_from_peer_iter(*peer_id)
+                    .filter(|(_, job_state)| job_state.pending_verify_id() == Some(*verify_id))
+                    .map(|(job_id, _)| job_id.clone())
+                    .collect::<Vec<_>>();
                 state.verify_result(meta.time(), peer_id, *verify_id, Err(()));
 
                 // TODO(binier): blacklist peer
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
                 let peer_id = *peer_id;
+                pubsub_validate_snarks(
+                    global_state,
+                    dispatcher,
+                    &peer_id,
+                    &job_ids,
+                    P2pNetworkPubsubValidationResult::Reject,
+                );
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
                     reason: P2pDisconnectionReason::SnarkPoolVerifyError,
@@ -160,7 +172,15 @@ impl SnarkPoolCandidatesState {
                 state.verify_result(meta.time(), peer_id, *verify_id, Ok(()));
 
                 // Dispatch
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let job_ids = batch.iter().map(Snark::job_id).collect::<Vec<_>>();
+                pubsub_validate_snarks(
+                    global_state,
+                    dispatcher,
+                    peer_id,
+                    &job_ids,
+                    P2pNetworkPubsubValidationResult::Accept,
+                );
 
                 for snark in batch {
                     dispatcher.push(SnarkPoolAction::WorkAdd {
@@ -175,3 +195,26 @@ impl SnarkPoolCandidatesState {
         }
     }
 }
+
+/// Reports the result of the snark verification for the pubsub messages
+/// which carried the snarks, so that they are only gossiped further if valid.
+fn pubsub_validate_snarks(
+    state: &crate::State,
+    dispatcher: &mut redux::Dispatcher<crate::Action, crate::State>,
+    peer_id: &PeerId,
+    job_ids: &[SnarkJobId],
+    result: P2pNetworkPubsubValidationResult,
+) {
+    let Some(p2p) = state.p2p.ready() else {
+        return;
+    };
+    let pubsub = &p2p.network.scheduler.broadcast_state;
+    for job_id in job_ids {
+        for message_id in pubsub.pending_snark_messages(peer_id, job_id) {
+            dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessage {
+                message_id: message_id.clone(),
+                result,
+            });
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use openmina_core::snark::{Snark, SnarkInfo, SnarkJobId};
 use redux::Timestamp;
@@ -294,6 +295,62 @@ impl SnarkPoolCandidatesState {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.by_peer.values().map(|jobs| jobs.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_peer.values().all(|jobs| jobs.is_empty())
+    }
+
+    fn remove(&mut self, peer_id: &PeerId, job_id: &SnarkJobId) {
+        if let Some(jobs) = self.by_peer.get_mut(peer_id) {
+            jobs.remove(job_id);
+            if jobs.is_empty() {
+                self.by_peer.remove(peer_id);
+            }
+        }
+        if let Some(peers) = self.by_job_id.get_mut(job_id) {
+            peers.remove(peer_id);
+            if peers.is_empty() {
+                self.by_job_id.remove(job_id);
+            }
+        }
+    }
+
+    /// Removes candidates which didn't change for longer than `timeout`
+    /// and then, if there are still more than `max_len` of them, ones
+    /// with the highest fee. Candidates which are being fetched or
+    /// verified are left alone.
+    pub fn evict(&mut self, now: Timestamp, timeout: Duration, max_len: usize) {
+        let is_stale = |state: &SnarkPoolCandidateState| {
+            now.checked_sub(state.time())
+                .map_or(false, |passed| passed >= timeout)
+        };
+        let mut evictable = self
+            .by_peer
+            .iter()
+            .flat_map(|(peer_id, jobs)| jobs.iter().map(move |(job_id, s)| (peer_id, job_id, s)))
+            .filter(|(_, _, state)| !state.is_pending())
+            .map(|(peer_id, job_id, state)| {
+                (is_stale(state), state.fee(), *peer_id, job_id.clone())
+            })
+            .collect::<Vec<_>>();
+        // Stale ones first, then the ones with the highest fee.
+        evictable.sort_by(|(stale1, fee1, ..), (stale2, fee2, ..)| {
+            stale2.cmp(stale1).then(fee2.cmp(fee1))
+        });
+
+        let mut len = self.len();
+        for (is_stale, _, peer_id, job_id) in evictable {
+            if !is_stale && len <= max_len {
+                break;
+            }
+            self.remove(&peer_id, &job_id);
+            len -= 1;
+        }
+    }
+
     pub fn retain<F1, F2>(&mut self, mut predicate: F1)
     where
         F1: FnMut(&SnarkJobId) -> F2,
@@ -334,6 +391,25 @@ impl SnarkPoolCandidateState {
         }
     }
 
+    pub fn time(&self) -> Timestamp {
+        match self {
+            Self::InfoReceived { time, .. }
+            | Self::WorkFetchPending { time, .. }
+            | Self::WorkReceived { time, .. }
+            | Self::WorkVerifyPending { time, .. }
+            | Self::WorkVerifyError { time, .. }
+            | Self::WorkVerifySuccess { time, .. } => *time,
+        }
+    }
+
+    /// Whether the work is being fetched or verified.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            Self::WorkFetchPending { .. } | Self::WorkVerifyPending { .. }
+        )
+    }
+
     pub fn work(&self) -> Option<&Snark> {
         match self {
             Self::InfoReceived { .. } => None,
@@ -365,3 +441,54 @@ impl<'a> From<&'a SnarkPoolCandidateState> for openmina_core::snark::SnarkCmp<'a
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use mina_p2p_messages::v2::CurrencyFeeStableV1;
+
+    use super::*;
+    use crate::snark_pool::test_snark;
+
+    fn peer(i: u8) -> PeerId {
+        PeerId::from_bytes([i; 32])
+    }
+
+    fn time(secs: u64) -> Timestamp {
+        Timestamp::new(Duration::from_secs(secs).as_nanos() as u64)
+    }
+
+    #[test]
+    fn evict() {
+        let job_id = SnarkJobId::from_str("jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc->jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc").unwrap();
+        let prover = test_snark(0).snarker;
+        let info = |fee: u64| SnarkInfo {
+            job_id: job_id.clone(),
+            fee: CurrencyFeeStableV1(fee.into()),
+            prover: prover.clone(),
+        };
+
+        let mut candidates = SnarkPoolCandidatesState::new();
+        candidates.info_received(time(0), peer(1), info(5));
+        candidates.info_received(time(95), peer(2), info(30));
+        candidates.info_received(time(95), peer(3), info(10));
+        candidates.info_received(time(0), peer(4), info(50));
+        candidates.work_fetch_pending(time(0), &peer(4), &job_id, 1);
+
+        // Nothing is stale and the limit isn't reached.
+        let mut unchanged = candidates.clone();
+        unchanged.evict(time(5), Duration::from_secs(10), 4);
+        assert_eq!(unchanged.len(), 4);
+
+        // Stale candidate goes first, then the one with the highest fee.
+        // Pending fetch is left alone, even though it's stale.
+        candidates.evict(time(100), Duration::from_secs(10), 2);
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates.get(peer(1), &job_id).is_none());
+        assert!(candidates.get(peer(2), &job_id).is_none());
+        assert!(candidates.get(peer(3), &job_id).is_some());
+        assert!(candidates.get(peer(4), &job_id).is_some());
+        assert_eq!(candidates.peer_work_count(&peer(2)), 0);
+    }
+}
@@ -3,6 +3,9 @@ pub mod candidate;
 mod snark_pool_config;
 pub use snark_pool_config::*;
 
+mod snark_pool_pricing;
+pub use snark_pool_pricing::*;
+
 mod snark_pool_state;
 pub use snark_pool_state::*;
 
@@ -16,3 +19,29 @@ pub use snark_pool_effects::*;
 
 mod snark_pool_service;
 pub use snark_pool_service::*;
+
+mod snark_pool_storage;
+pub use snark_pool_storage::*;
+
+/// Snark from the recorded gossip message, with the `fee` replaced.
+#[cfg(test)]
+pub(crate) fn test_snark(fee: u64) -> openmina_core::snark::Snark {
+    use mina_p2p_messages::{
+        binprot::BinProtRead,
+        gossip::GossipNetMessageV2,
+        v2::{CurrencyFeeStableV1, NetworkPoolSnarkPoolDiffVersionedStableV2},
+    };
+
+    let mut bytes: &[u8] =
+        include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin");
+    let GossipNetMessageV2::SnarkPoolDiff {
+        message: NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work),
+        ..
+    } = GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+    else {
+        panic!("expected a snark pool diff with a solved work");
+    };
+    let mut snark = openmina_core::snark::Snark::from(work.1);
+    snark.fee = CurrencyFeeStableV1(fee.into());
+    snark
+}
@@ -57,7 +57,9 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
                 .map_or(false, |v| v.auto_commit),
             SnarkPoolAction::CommitmentCreateMany { .. } => state.config.snarker.is_some(),
             SnarkPoolAction::CommitmentCreate { job_id } => {
-                state.config.snarker.is_some() && state.snark_pool.should_create_commitment(job_id)
+                state.config.snarker.as_ref().map_or(false, |config| {
+                    state.snark_pool.should_create_commitment(job_id, config)
+                })
             }
             SnarkPoolAction::CommitmentAdd { commitment, .. } => state
                 .snark_pool
@@ -67,13 +69,17 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
                     None => true,
                 }),
             SnarkPoolAction::WorkAdd { snark, .. } => {
-                state
+                let job_id = snark.job_id();
+                !state
                     .snark_pool
-                    .get(&snark.job_id())
-                    .map_or(false, |s| match s.snark.as_ref() {
-                        Some(cur) => snark > &cur.work,
-                        None => true,
-                    })
+                    .is_fee_evicted(&job_id, snark.fee.0.as_u64())
+                    && state
+                        .snark_pool
+                        .get(&job_id)
+                        .map_or(false, |s| match s.snark.as_ref() {
+                            Some(cur) => snark > &cur.work,
+                            None => true,
+                        })
             }
             SnarkPoolAction::P2pSend { peer_id } => state
                 .p2p
@@ -125,6 +131,8 @@ pub enum SnarkPoolEffectfulAction {
         count: usize,
         on_result: redux::Callback<Vec<SnarkJobId>>,
     },
+    /// Load snarks persisted by the previous run of the node.
+    SnarkPoolRestore,
 }
 
 pub type SnarkPoolEffectfulActionWithMeta = redux::ActionWithMeta<SnarkPoolEffectfulAction>;
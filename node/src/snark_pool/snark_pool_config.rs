@@ -1,4 +1,59 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct SnarkPoolConfig {}
+#[serde(default)]
+pub struct SnarkPoolConfig {
+    /// Maximum number of completed snarks kept in the pool. Once
+    /// exceeded, snarks with the highest fee are evicted first.
+    pub max_snarks: usize,
+    /// Maximum number of snark candidates received from peers and not
+    /// yet added to the pool. Once exceeded, candidates with the highest
+    /// fee are evicted first.
+    pub max_candidates: usize,
+    /// Candidates which didn't make any progress for this long are
+    /// considered stale and removed.
+    pub candidate_timeout: Duration,
+}
+
+impl Default for SnarkPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_snarks: 4096,
+            max_candidates: 8192,
+            candidate_timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Policy for setting the fee of a particular snark job, on top of the
+/// snarker's base fee.
+///
+/// All amounts are in nanomina. Default policy charges the base fee for
+/// every job and never competes for jobs claimed by other snarkers.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SnarkerPricing {
+    /// Added to the base fee for each account update of a transaction
+    /// job beyond the first one.
+    #[serde(default)]
+    pub account_update_fee: u64,
+    /// Fee for merge jobs. Base fee is used if not set.
+    #[serde(default)]
+    pub merge_fee: Option<u64>,
+    /// Jobs with `order` lower than this are the oldest ones, which
+    /// block producers need to buy first.
+    #[serde(default)]
+    pub priority_jobs: usize,
+    /// Premium (in percent) added to the fee of the priority jobs.
+    #[serde(default)]
+    pub priority_premium_percent: u64,
+    /// How much to bid below competing work when taking over a job that
+    /// is already claimed by another snarker at a higher fee. If zero,
+    /// claimed jobs are always skipped.
+    #[serde(default)]
+    pub undercut: u64,
+    /// Upper bound for the fee of any job.
+    #[serde(default)]
+    pub max_fee: Option<u64>,
+}
@@ -17,5 +17,8 @@ pub fn snark_pool_effects<S: Service>(
             let job_ids = store.service.random_choose(choices.iter(), count);
             store.dispatch_callback(on_result, job_ids);
         }
+        SnarkPoolEffectfulAction::SnarkPoolRestore => {
+            store.service.snark_pool_restore();
+        }
     }
 }
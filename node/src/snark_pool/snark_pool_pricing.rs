@@ -0,0 +1,218 @@
+use mina_p2p_messages::v2::{
+    CurrencyFeeStableV1, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SnarkerConfig;
+
+use super::{JobState, JobSummary, SnarkerPricing};
+
+/// Decision of the snarker's pricing policy about a particular job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum SnarkJobPricing {
+    /// Job should be committed to with the `fee`.
+    Commit {
+        fee: u64,
+        /// Fee we would charge for the job without any competition.
+        target_fee: u64,
+        /// Lowest fee of the work from other snarkers, if the job is
+        /// already claimed and we are taking it over.
+        competing_fee: Option<u64>,
+    },
+    /// We already committed to or completed the job.
+    Ours,
+    /// Job is claimed by another snarker and taking it over would mean
+    /// going below the `target_fee`.
+    ClaimedAtLowerFee { target_fee: u64, competing_fee: u64 },
+    /// Job is claimed by another snarker, at any fee, and taking over
+    /// claimed jobs is disabled (`undercut` is 0).
+    ClaimedNoTakeover { competing_fee: u64 },
+    /// Snark for the job with `evicted_fee` was evicted from the pool,
+    /// so the work with the `fee` we would charge would be rejected, see
+    /// [`super::SnarkPoolState::is_fee_evicted`].
+    FeeEvicted { fee: u64, evicted_fee: u64 },
+}
+
+impl SnarkJobPricing {
+    pub fn is_commit(&self) -> bool {
+        matches!(self, Self::Commit { .. })
+    }
+
+    pub fn fee(&self) -> Option<CurrencyFeeStableV1> {
+        match self {
+            Self::Commit { fee, .. } => Some(CurrencyFeeStableV1(
+                UnsignedExtendedUInt64Int64ForVersionTagsStableV1((*fee).into()),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl SnarkerPricing {
+    /// Fee for the job when there is no competing work for it.
+    pub fn target_fee(&self, base_fee: u64, summary: &JobSummary, order: usize) -> u64 {
+        let fee = match summary {
+            JobSummary::Tx(account_updates) => base_fee.saturating_add(
+                self.account_update_fee
+                    .saturating_mul(account_updates.saturating_sub(1) as u64),
+            ),
+            JobSummary::Merge(_) => self.merge_fee.unwrap_or(base_fee),
+        };
+        let fee = if order < self.priority_jobs {
+            fee.saturating_add(fee.saturating_mul(self.priority_premium_percent) / 100)
+        } else {
+            fee
+        };
+        self.max_fee.map_or(fee, |max| fee.min(max))
+    }
+
+    /// Decision about a job for which we would charge `target_fee`,
+    /// given the lowest fee it is already claimed at, if any.
+    pub fn pricing(&self, target_fee: u64, competing_fee: Option<u64>) -> SnarkJobPricing {
+        let Some(competing_fee) = competing_fee else {
+            return SnarkJobPricing::Commit {
+                fee: target_fee,
+                target_fee,
+                competing_fee: None,
+            };
+        };
+        if self.undercut == 0 {
+            return SnarkJobPricing::ClaimedNoTakeover { competing_fee };
+        }
+        match self.takeover_fee(target_fee, competing_fee) {
+            Some(fee) => SnarkJobPricing::Commit {
+                fee,
+                target_fee,
+                competing_fee: Some(competing_fee),
+            },
+            None => SnarkJobPricing::ClaimedAtLowerFee {
+                target_fee,
+                competing_fee,
+            },
+        }
+    }
+
+    /// Fee to take over a job claimed at `competing_fee`, if it is
+    /// not below `target_fee`.
+    pub fn takeover_fee(&self, target_fee: u64, competing_fee: u64) -> Option<u64> {
+        if self.undercut == 0 {
+            return None;
+        }
+        let fee = competing_fee.checked_sub(self.undercut)?;
+        let fee = self.max_fee.map_or(fee, |max| fee.min(max));
+        Some(fee).filter(|fee| *fee >= target_fee)
+    }
+}
+
+impl JobState {
+    pub fn pricing(&self, config: &SnarkerConfig) -> SnarkJobPricing {
+        let our_key = config.public_key.as_ref();
+        let commitment = self.commitment_msg();
+        let snark = self.snark.as_ref().map(|snark| &snark.work);
+        if commitment.map_or(false, |c| &c.snarker == our_key)
+            || snark.map_or(false, |s| &s.snarker == our_key)
+        {
+            return SnarkJobPricing::Ours;
+        }
+
+        let pricing = &config.pricing;
+        let target_fee = pricing.target_fee(config.fee.as_u64(), &self.summary(), self.order);
+        let competing_fee = commitment
+            .map(|c| c.fee.as_u64())
+            .into_iter()
+            .chain(snark.map(|s| s.fee.as_u64()))
+            .min();
+
+        pricing.pricing(target_fee, competing_fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_fee() {
+        let pricing = SnarkerPricing {
+            account_update_fee: 10,
+            merge_fee: Some(50),
+            priority_jobs: 2,
+            priority_premium_percent: 20,
+            undercut: 0,
+            max_fee: Some(200),
+        };
+
+        assert_eq!(pricing.target_fee(100, &JobSummary::Tx(1), 5), 100);
+        assert_eq!(pricing.target_fee(100, &JobSummary::Tx(4), 5), 130);
+        assert_eq!(pricing.target_fee(100, &JobSummary::Tx(4), 1), 156);
+        assert_eq!(pricing.target_fee(100, &JobSummary::Tx(50), 5), 200);
+        assert_eq!(pricing.target_fee(100, &JobSummary::Merge(2), 5), 50);
+        assert_eq!(pricing.target_fee(100, &JobSummary::Merge(2), 0), 60);
+
+        let default = SnarkerPricing::default();
+        assert_eq!(default.target_fee(100, &JobSummary::Tx(4), 0), 100);
+        assert_eq!(default.target_fee(100, &JobSummary::Merge(2), 0), 100);
+    }
+
+    #[test]
+    fn takeover_fee() {
+        let pricing = SnarkerPricing {
+            undercut: 5,
+            max_fee: Some(150),
+            ..Default::default()
+        };
+
+        assert_eq!(pricing.takeover_fee(100, 120), Some(115));
+        assert_eq!(pricing.takeover_fee(100, 105), Some(100));
+        assert_eq!(pricing.takeover_fee(100, 104), None);
+        assert_eq!(pricing.takeover_fee(100, 3), None);
+        assert_eq!(pricing.takeover_fee(100, 300), Some(150));
+        assert_eq!(SnarkerPricing::default().takeover_fee(100, 300), None);
+    }
+
+    #[test]
+    fn pricing() {
+        let pricing = SnarkerPricing {
+            undercut: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            pricing.pricing(100, None),
+            SnarkJobPricing::Commit {
+                fee: 100,
+                target_fee: 100,
+                competing_fee: None,
+            }
+        );
+        assert_eq!(
+            pricing.pricing(100, Some(120)),
+            SnarkJobPricing::Commit {
+                fee: 115,
+                target_fee: 100,
+                competing_fee: Some(120),
+            }
+        );
+        assert_eq!(
+            pricing.pricing(100, Some(104)),
+            SnarkJobPricing::ClaimedAtLowerFee {
+                target_fee: 100,
+                competing_fee: 104,
+            }
+        );
+
+        // Without undercut, claimed jobs aren't taken over, whatever
+        // their fee.
+        let pricing = SnarkerPricing::default();
+        assert!(pricing.pricing(100, None).is_commit());
+        assert_eq!(
+            pricing.pricing(100, Some(300)),
+            SnarkJobPricing::ClaimedNoTakeover { competing_fee: 300 }
+        );
+        assert_eq!(
+            pricing.pricing(100, Some(50)),
+            SnarkJobPricing::ClaimedNoTakeover { competing_fee: 50 }
+        );
+    }
+}
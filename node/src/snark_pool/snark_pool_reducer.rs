@@ -70,9 +70,14 @@ impl SnarkPoolState {
                 }
 
                 state.candidates_prune();
+                state.evict_snarks();
+                let request_restore = !std::mem::replace(&mut state.restore_requested, true);
 
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                if request_restore {
+                    dispatcher.push(SnarkPoolEffectfulAction::SnarkPoolRestore);
+                }
                 if let Some(job_id) = global_state.external_snark_worker.working_job_id() {
                     if !global_state.snark_pool.contains(job_id) {
                         // job is no longer needed.
@@ -90,11 +95,14 @@ impl SnarkPoolState {
                 let available_workers = global_state.external_snark_worker.available();
 
                 if available_workers > 0 {
-                    let jobs = global_state
-                        .snark_pool
-                        .available_jobs_with_highest_priority(available_workers);
                     match snarker_config.strategy {
                         SnarkerStrategy::Sequential => {
+                            let jobs = global_state
+                                .snark_pool
+                                .jobs_to_commit_with_highest_priority(
+                                    snarker_config,
+                                    available_workers,
+                                );
                             let job_ids = jobs
                                 .into_iter()
                                 .map(|job| job.id.clone())
@@ -103,7 +111,7 @@ impl SnarkPoolState {
                             dispatcher.push(SnarkPoolAction::CommitmentCreateMany { job_ids });
                         }
                         SnarkerStrategy::Random => {
-                            let jobs = global_state.snark_pool.available_jobs_iter();
+                            let jobs = global_state.snark_pool.jobs_to_commit_iter(snarker_config);
                             let choices = jobs.map(|job| job.id.clone()).collect();
 
                             dispatcher.push(SnarkPoolEffectfulAction::SnarkPoolJobsRandomChoose {
@@ -128,25 +136,29 @@ impl SnarkPoolState {
             SnarkPoolAction::CommitmentCreate { job_id } => {
                 let job_id = job_id.clone();
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                let Some(summary) = global_state.snark_pool.job_summary(&job_id) else {
+                let Some(config) = global_state.config.snarker.as_ref() else {
+                    return;
+                };
+                let Some(job) = global_state.snark_pool.get(&job_id) else {
+                    return;
+                };
+                let Some(fee) = global_state.snark_pool.job_pricing(job, config).fee() else {
                     return;
                 };
 
                 if global_state.external_snark_worker.is_idle() {
                     dispatcher.push(ExternalSnarkWorkerAction::SubmitWork {
                         job_id: job_id.clone(),
-                        summary,
+                        summary: job.summary(),
+                        fee: fee.clone(),
                     });
 
                     let timestamp_ms = meta.time_as_nanos() / 1_000_000;
-                    let Some(config) = global_state.config.snarker.as_ref() else {
-                        return;
-                    };
                     dispatcher.push(SnarkPoolAction::CommitmentAdd {
                         commitment: SnarkJobCommitment::new(
                             timestamp_ms,
                             job_id,
-                            config.fee.clone(),
+                            fee,
                             config.public_key.clone().into(),
                         ),
                         sender: global_state.p2p.my_id(),
@@ -190,6 +202,7 @@ impl SnarkPoolState {
                 });
                 state.insert(job);
                 state.candidates.remove_inferior_snarks(snark);
+                state.evict_snarks();
 
                 // Dispatch
                 let snark = snark.clone();
@@ -260,6 +273,7 @@ impl SnarkPoolState {
             }
             SnarkPoolAction::CheckTimeouts => {
                 state.last_check_timeouts = meta.time();
+                state.evict_candidates(meta.time());
 
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
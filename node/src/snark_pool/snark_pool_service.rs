@@ -1,9 +1,18 @@
 use crate::core::snark::SnarkJobId;
 
+use super::SnarkPoolState;
+
 pub trait SnarkPoolService: redux::Service {
     fn random_choose<'a>(
         &mut self,
         iter: impl Iterator<Item = &'a SnarkJobId>,
         n: usize,
     ) -> Vec<SnarkJobId>;
+
+    /// Persist completed snarks of the pool, if the storage is configured.
+    fn snark_pool_save(&mut self, pool: &SnarkPoolState);
+
+    /// Load snarks persisted by the previous run, if the storage is
+    /// configured, and send them back with [`crate::event_source::Event::SnarkPoolRestored`].
+    fn snark_pool_restore(&mut self);
 }
@@ -6,10 +6,11 @@ use openmina_core::snark::{Snark, SnarkInfo, SnarkJobCommitment, SnarkJobId};
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::config::SnarkerConfig;
 use crate::p2p::PeerId;
 
 use super::candidate::SnarkPoolCandidatesState;
-use super::SnarkPoolConfig;
+use super::{SnarkJobPricing, SnarkPoolConfig};
 
 #[derive(Clone)]
 pub struct SnarkPoolState {
@@ -18,6 +19,14 @@ pub struct SnarkPoolState {
     list: BTreeMap<u64, JobState>,
     by_ledger_hash_index: BTreeMap<SnarkJobId, u64>,
     pub candidates: SnarkPoolCandidatesState,
+    /// Lowest fee of the snarks evicted for each job. Snarks for the job
+    /// with the same or higher fee would be evicted again, so they are
+    /// rejected.
+    evicted: BTreeMap<SnarkJobId, u64>,
+    /// Whether snarks persisted by the previous run were requested. They
+    /// are requested with the first [`super::SnarkPoolAction::JobsUpdate`],
+    /// so that there are jobs to add them to.
+    pub(super) restore_requested: bool,
     pub(super) last_check_timeouts: Timestamp,
 }
 
@@ -55,22 +64,28 @@ pub enum JobSummary {
 
 impl Default for SnarkPoolState {
     fn default() -> Self {
-        Self::new()
+        Self::new(SnarkPoolConfig::default())
     }
 }
 
 impl SnarkPoolState {
-    pub fn new() -> Self {
+    pub fn new(config: SnarkPoolConfig) -> Self {
         Self {
-            config: SnarkPoolConfig {},
+            config,
             counter: 0,
             list: Default::default(),
             by_ledger_hash_index: Default::default(),
             candidates: SnarkPoolCandidatesState::new(),
+            evicted: Default::default(),
+            restore_requested: false,
             last_check_timeouts: Timestamp::ZERO,
         }
     }
 
+    pub fn config(&self) -> &SnarkPoolConfig {
+        &self.config
+    }
+
     pub fn last_index(&self) -> u64 {
         self.list.last_key_value().map_or(0, |(k, _)| *k)
     }
@@ -140,8 +155,25 @@ impl SnarkPoolState {
         self.list.range(range).map(|(k, v)| (*k, v))
     }
 
-    pub fn should_create_commitment(&self, job_id: &SnarkJobId) -> bool {
-        self.get(job_id).map_or(false, |s| s.is_available())
+    pub fn should_create_commitment(&self, job_id: &SnarkJobId, config: &SnarkerConfig) -> bool {
+        self.get(job_id)
+            .map_or(false, |job| self.job_pricing(job, config).is_commit())
+    }
+
+    /// Pricing decision about the `job`, which doesn't commit to the job
+    /// if the work with the priced fee would be rejected as evicted.
+    pub fn job_pricing(&self, job: &JobState, config: &SnarkerConfig) -> SnarkJobPricing {
+        let pricing = job.pricing(config);
+        let evicted_fee = self.evicted.get(&job.id).copied();
+        match (&pricing, evicted_fee) {
+            (SnarkJobPricing::Commit { fee, .. }, Some(evicted_fee)) if *fee >= evicted_fee => {
+                SnarkJobPricing::FeeEvicted {
+                    fee: *fee,
+                    evicted_fee,
+                }
+            }
+            _ => pricing,
+        }
     }
 
     pub fn is_commitment_timed_out(&self, id: &SnarkJobId, time_now: Timestamp) -> bool {
@@ -200,6 +232,26 @@ impl SnarkPoolState {
             })
     }
 
+    /// Jobs which the snarker's pricing policy decides to commit to.
+    pub fn jobs_to_commit_iter<'a>(
+        &'a self,
+        config: &'a SnarkerConfig,
+    ) -> impl Iterator<Item = &'a JobState> {
+        self.jobs_iter()
+            .filter(move |job| self.job_pricing(job, config).is_commit())
+    }
+
+    pub fn jobs_to_commit_with_highest_priority(
+        &self,
+        config: &SnarkerConfig,
+        n: usize,
+    ) -> Vec<&JobState> {
+        let mut jobs = self.jobs_to_commit_iter(config).collect::<Vec<_>>();
+        jobs.sort_by_key(|job| job.order);
+        jobs.truncate(n);
+        jobs
+    }
+
     pub fn completed_snarks_iter(&self) -> impl '_ + Iterator<Item = &'_ Snark> {
         self.list
             .iter()
@@ -207,15 +259,71 @@ impl SnarkPoolState {
             .map(|snark| &snark.work)
     }
 
-    pub(super) fn job_summary(&self, id: &SnarkJobId) -> Option<JobSummary> {
-        self.get(id).map(|job| job.summary())
+    /// Drops completed snarks over [`SnarkPoolConfig::max_snarks`],
+    /// starting with the ones with the highest fee. Among snarks with the
+    /// same fee, the ones for the newest jobs go first.
+    ///
+    /// Jobs stay in the pool, so that they can be done again if needed,
+    /// but only for a lower fee, see [`Self::is_fee_evicted`].
+    pub fn evict_snarks(&mut self) {
+        let by_job_id = &self.by_ledger_hash_index;
+        self.evicted
+            .retain(|job_id, _| by_job_id.contains_key(job_id));
+
+        let mut snarks = self
+            .list
+            .iter()
+            .filter_map(|(index, job)| {
+                let snark = job.snark.as_ref()?;
+                Some((snark.work.fee.as_u64(), job.order, *index))
+            })
+            .collect::<Vec<_>>();
+        let Some(excess) = snarks.len().checked_sub(self.config.max_snarks) else {
+            return;
+        };
+        snarks.sort_by(|(fee1, order1, _), (fee2, order2, _)| {
+            fee2.cmp(fee1).then(order2.cmp(order1))
+        });
+        for (fee, _, index) in snarks.into_iter().take(excess) {
+            let Some(job) = self.list.get_mut(&index) else {
+                continue;
+            };
+            job.snark = None;
+            self.evicted
+                .entry(job.id.clone())
+                .and_modify(|evicted_fee| *evicted_fee = (*evicted_fee).min(fee))
+                .or_insert(fee);
+        }
+    }
+
+    /// Whether a snark with the `fee` for the job would be evicted
+    /// again, because a snark for the job with the same or lower fee was
+    /// already evicted.
+    pub fn is_fee_evicted(&self, job_id: &SnarkJobId, fee: u64) -> bool {
+        self.evicted
+            .get(job_id)
+            .map_or(false, |evicted_fee| fee >= *evicted_fee)
+    }
+
+    /// Removes stale candidates and the ones over
+    /// [`SnarkPoolConfig::max_candidates`].
+    pub fn evict_candidates(&mut self, now: Timestamp) {
+        let SnarkPoolConfig {
+            max_candidates,
+            candidate_timeout,
+            ..
+        } = self.config;
+        self.candidates
+            .evict(now, candidate_timeout, max_candidates);
     }
 
     pub fn candidates_prune(&mut self) {
         self.candidates.retain(|id| {
             let job = Self::get_by_job_id(&self.by_ledger_hash_index, &self.list, id);
+            let evicted_fee = self.evicted.get(id).copied();
             move |candidate| match job {
                 None => false,
+                Some(_) if evicted_fee.map_or(false, |fee| candidate.fee() >= fee) => false,
                 Some(job) => match job.snark.as_ref() {
                     None => true,
                     Some(snark) => &snark.work < candidate,
@@ -302,6 +410,10 @@ mod ser {
         counter: u64,
         list: BTreeMap<u64, JobState>,
         candidates: SnarkPoolCandidatesState,
+        #[serde(default)]
+        evicted: BTreeMap<SnarkJobId, u64>,
+        #[serde(default)]
+        restore_requested: bool,
         last_check_timeouts: Timestamp,
     }
 
@@ -310,11 +422,13 @@ mod ser {
         where
             S: serde::Serializer,
         {
-            let mut s = serializer.serialize_struct("SnarkPool", 5)?;
+            let mut s = serializer.serialize_struct("SnarkPool", 7)?;
             s.serialize_field("config", &self.config)?;
             s.serialize_field("counter", &self.counter)?;
             s.serialize_field("list", &self.list)?;
             s.serialize_field("candidates", &self.candidates)?;
+            s.serialize_field("evicted", &self.evicted)?;
+            s.serialize_field("restore_requested", &self.restore_requested)?;
             s.serialize_field("last_check_timeouts", &self.last_check_timeouts)?;
             s.end()
         }
@@ -332,8 +446,156 @@ mod ser {
                 list: v.list,
                 by_ledger_hash_index,
                 candidates: v.candidates,
+                evicted: v.evicted,
+                restore_requested: v.restore_requested,
                 last_check_timeouts: v.last_check_timeouts,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use mina_p2p_messages::v2::{
+        MinaBaseSokMessageStableV1, TransactionSnarkScanStateLedgerProofWithSokMessageStableV2,
+        TransactionSnarkWorkTStableV2Proofs,
+    };
+
+    use super::*;
+    use crate::snark_pool::test_snark;
+
+    const JOB_A: &str = "jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc->jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc";
+    const JOB_B: &str = "jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc->jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc";
+    const JOB_C: &str = "jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc:jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N->jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N";
+
+    fn job_id(id: &str) -> SnarkJobId {
+        SnarkJobId::from_str(id).unwrap()
+    }
+
+    fn completed_job(id: &str, order: usize, fee: u64) -> JobState {
+        let work = test_snark(fee);
+        let proof = match &*work.proofs {
+            TransactionSnarkWorkTStableV2Proofs::One(proof)
+            | TransactionSnarkWorkTStableV2Proofs::Two((proof, _)) => proof.clone(),
+        };
+        let proof = TransactionSnarkScanStateLedgerProofWithSokMessageStableV2(
+            proof,
+            MinaBaseSokMessageStableV1 {
+                fee: work.fee.clone(),
+                prover: work.snarker.clone(),
+            },
+        );
+        JobState {
+            time: Timestamp::ZERO,
+            id: job_id(id),
+            job: OneOrTwo::One(AvailableJobMessage::Merge {
+                left: proof.clone(),
+                right: proof,
+            }),
+            commitment: None,
+            snark: Some(SnarkWork {
+                work,
+                received_t: Timestamp::ZERO,
+                sender: PeerId::from_bytes([0; 32]),
+            }),
+            order,
+        }
+    }
+
+    fn pool_with_jobs(max_snarks: usize) -> SnarkPoolState {
+        let mut pool = SnarkPoolState::new(SnarkPoolConfig {
+            max_snarks,
+            ..Default::default()
+        });
+        pool.insert(completed_job(JOB_A, 0, 10));
+        pool.insert(completed_job(JOB_B, 1, 20));
+        pool.insert(completed_job(JOB_C, 2, 20));
+        pool
+    }
+
+    fn has_snark(pool: &SnarkPoolState, id: &str) -> bool {
+        pool.get(&job_id(id)).unwrap().snark.is_some()
+    }
+
+    #[test]
+    fn evict_snarks() {
+        let mut pool = pool_with_jobs(3);
+        pool.evict_snarks();
+        assert_eq!(pool.completed_snarks_iter().count(), 3);
+        assert!(!pool.is_fee_evicted(&job_id(JOB_C), 20));
+
+        // The highest fee goes first, the newest job among equal fees.
+        let mut pool = pool_with_jobs(1);
+        pool.evict_snarks();
+        assert!(has_snark(&pool, JOB_A));
+        assert!(!has_snark(&pool, JOB_B));
+        assert!(!has_snark(&pool, JOB_C));
+        assert_eq!(pool.jobs_iter().count(), 3);
+
+        assert!(pool.is_fee_evicted(&job_id(JOB_B), 20));
+        assert!(pool.is_fee_evicted(&job_id(JOB_B), 21));
+        assert!(!pool.is_fee_evicted(&job_id(JOB_B), 19));
+        assert!(!pool.is_fee_evicted(&job_id(JOB_A), 100));
+    }
+
+    #[test]
+    fn dont_commit_at_evicted_fee() {
+        let mut pool = pool_with_jobs(1);
+        pool.evict_snarks();
+
+        let config = |fee: u64| SnarkerConfig {
+            public_key: crate::account::AccountSecretKey::rand().public_key(),
+            fee: mina_p2p_messages::v2::CurrencyFeeStableV1(fee.into()),
+            strategy: crate::config::SnarkerStrategy::Sequential,
+            auto_commit: true,
+            pricing: Default::default(),
+        };
+        let job = pool.get(&job_id(JOB_B)).unwrap();
+        assert!(job.pricing(&config(20)).is_commit());
+        assert_eq!(
+            pool.job_pricing(job, &config(20)),
+            SnarkJobPricing::FeeEvicted {
+                fee: 20,
+                evicted_fee: 20
+            }
+        );
+        assert!(!pool.should_create_commitment(&job_id(JOB_B), &config(20)));
+        assert_eq!(pool.jobs_to_commit_iter(&config(20)).count(), 0);
+
+        // Work for a lower fee is accepted.
+        assert!(pool.should_create_commitment(&job_id(JOB_B), &config(19)));
+        assert_eq!(pool.jobs_to_commit_iter(&config(19)).count(), 2);
+    }
+
+    #[test]
+    fn evicted_fee_tombstone() {
+        let mut pool = pool_with_jobs(1);
+        pool.evict_snarks();
+
+        // Candidates which would be evicted again are dropped.
+        let prover = test_snark(0).snarker;
+        let candidate = |fee: u64| SnarkInfo {
+            job_id: job_id(JOB_C),
+            fee: mina_p2p_messages::v2::CurrencyFeeStableV1(fee.into()),
+            prover: prover.clone(),
+        };
+        pool.candidates
+            .info_received(Timestamp::ZERO, PeerId::from_bytes([1; 32]), candidate(20));
+        pool.candidates
+            .info_received(Timestamp::ZERO, PeerId::from_bytes([2; 32]), candidate(19));
+        pool.candidates_prune();
+        assert_eq!(pool.candidates.len(), 1);
+        assert!(pool
+            .candidates
+            .get(PeerId::from_bytes([2; 32]), &job_id(JOB_C))
+            .is_some());
+
+        // Tombstone is kept only while the job is in the pool.
+        pool.remove(&job_id(JOB_B));
+        pool.evict_snarks();
+        assert!(!pool.is_fee_evicted(&job_id(JOB_B), 20));
+        assert!(pool.is_fee_evicted(&job_id(JOB_C), 20));
+    }
+}
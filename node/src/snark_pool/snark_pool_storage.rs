@@ -0,0 +1,84 @@
+use std::{io, path::PathBuf, time::Duration};
+
+use mina_p2p_messages::binprot::{BinProtRead, BinProtWrite};
+use openmina_core::{file_writer::PeriodicFileWriter, snark::Snark};
+
+use super::SnarkPoolState;
+
+/// How often the snark pool is written to the disk.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Completed snarks of the pool persisted as a binprot encoded file, so
+/// that the work isn't lost after the restart.
+pub struct SnarkPoolStorage {
+    writer: PeriodicFileWriter<Vec<Snark>>,
+}
+
+impl SnarkPoolStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            writer: PeriodicFileWriter::new(
+                path,
+                "snark-pool",
+                SAVE_INTERVAL,
+                |snarks: &Vec<Snark>| {
+                    let mut bytes = Vec::new();
+                    snarks.binprot_write(&mut bytes)?;
+                    Ok(bytes)
+                },
+            ),
+        }
+    }
+
+    /// Loads snarks saved by the previous run.
+    ///
+    /// The file is written only by this node, so it's trusted and snarks
+    /// aren't re-verified. They are still checked against the current
+    /// scan state jobs before getting into the pool.
+    pub fn load(&self) -> io::Result<Vec<Snark>> {
+        let Some(bytes) = self.writer.read()? else {
+            return Ok(Vec::new());
+        };
+        Vec::<Snark>::binprot_read(&mut bytes.as_slice())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// Writes completed snarks of the pool in the background thread,
+    /// unless they were written less than [`SAVE_INTERVAL`] ago.
+    pub fn save_if_due(&mut self, pool: &SnarkPoolState) {
+        self.writer
+            .write_if_due(|| pool.completed_snarks_iter().cloned().collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snark_pool::test_snark;
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("snark-pool-storage-{}", std::process::id()));
+        let path = dir.join("snark_pool.bin");
+        let storage = SnarkPoolStorage::new(&path);
+        assert!(storage.load().unwrap().is_empty());
+
+        let snarks = vec![test_snark(10), test_snark(20)];
+        storage.writer.write(&snarks).unwrap();
+        let loaded = storage.load().unwrap();
+        assert_eq!(loaded.len(), snarks.len());
+        for (loaded, snark) in loaded.iter().zip(&snarks) {
+            assert_eq!(loaded.job_id(), snark.job_id());
+            assert_eq!(loaded.fee, snark.fee);
+            assert_eq!(loaded.snarker, snark.snarker);
+        }
+
+        std::fs::write(&path, b"garbage").unwrap();
+        assert_eq!(
+            storage.load().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -48,6 +48,10 @@ pub struct State {
 
     pub watched_accounts: WatchedAccountsState,
 
+    /// Time when the node was started, [`Timestamp::ZERO`] if it's unknown
+    /// (state serialized before it was tracked).
+    #[serde(default = "unknown_started_at")]
+    started_at: Timestamp,
     // TODO(binier): include action kind in `last_action`.
     last_action: ActionMeta,
     applied_actions_count: u64,
@@ -193,12 +197,16 @@ impl p2p::P2pStateTrait for State {}
 
 pub type Substate<'a, S> = openmina_core::Substate<'a, crate::Action, State, S>;
 
+fn unknown_started_at() -> Timestamp {
+    Timestamp::ZERO
+}
+
 impl State {
     pub fn new(config: Config, constants: &ConsensusConstants, now: Timestamp) -> Self {
         Self {
             p2p: P2p::Pending(config.p2p),
             ledger: LedgerState::new(config.ledger),
-            snark_pool: SnarkPoolState::new(),
+            snark_pool: SnarkPoolState::new(config.snark_pool),
             snark: SnarkState::new(config.snark),
             consensus: ConsensusState::new(),
             transition_frontier: TransitionFrontierState::new(config.transition_frontier),
@@ -210,6 +218,7 @@ impl State {
             watched_accounts: WatchedAccountsState::new(),
 
             config: config.global,
+            started_at: now,
             last_action: ActionMeta::zero_custom(now),
             applied_actions_count: 0,
         }
@@ -219,6 +228,10 @@ impl State {
         &self.last_action
     }
 
+    pub fn started_at(&self) -> Timestamp {
+        self.started_at
+    }
+
     /// Latest time observed by the state machine.
     ///
     /// Only updated when action is dispatched and reducer is executed.
@@ -8,7 +8,7 @@ mod stats_sync;
 pub mod sync {
     pub use super::stats_sync::*;
 }
-use sync::{SyncStats, SyncStatsSnapshot, SyncingLedger};
+use sync::{SyncStats, SyncStatsSnapshot, SyncStatus, SyncingLedger};
 
 mod stats_block_producer;
 pub mod block_producer {
@@ -110,6 +110,10 @@ impl Stats {
         self.sync_stats.collect_stats(limit)
     }
 
+    pub fn sync_status(&self) -> Option<SyncStatus> {
+        self.sync_stats.status()
+    }
+
     pub fn get_sync_time(&self) -> Option<Timestamp> {
         self.sync_stats
             .collect_stats(Some(1))
@@ -1,8 +1,20 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::Duration;
 
-use ledger::AccountIndex;
+use ledger::{
+    scan_state::{
+        currency::Fee,
+        transaction_logic::{valid, GenericCommand},
+    },
+    staged_ledger::{
+        diff::{with_valid_signatures_and_proofs, AtMostTwo},
+        diff_creation_log::{summary::Resources, DiffCreationLog, Discarded, Partition, Reason},
+    },
+    transaction_pool::transaction_hash::command_hash,
+    AccountIndex,
+};
 use mina_p2p_messages::v2;
-use openmina_core::block::AppliedBlock;
+use openmina_core::{block::AppliedBlock, snark::SnarkJobId};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -11,10 +23,51 @@ use crate::{
 };
 
 const MAX_HISTORY: usize = 2048;
+/// Only this many latest attempts keep the detailed staged ledger diff
+/// creation log, older ones keep just the counts.
+const MAX_DETAILED_DIFF_CREATE_LOGS: usize = 16;
+
+/// Upper bounds of the block proof creation time histogram buckets.
+pub const BLOCK_PROOF_DURATION_BUCKETS: [Duration; 9] = [
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(20),
+    Duration::from_secs(30),
+    Duration::from_secs(45),
+    Duration::from_secs(60),
+    Duration::from_secs(90),
+    Duration::from_secs(120),
+    Duration::from_secs(180),
+];
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct BlockProducerStats {
     pub(super) attempts: VecDeque<BlockProductionAttempt>,
+    #[serde(default)]
+    pub(super) proof_durations: BlockProofDurations,
+}
+
+/// Histogram of the block proof creation times since the start. Unlike
+/// `attempts`, it isn't limited to the latest attempts.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct BlockProofDurations {
+    /// Number of proofs, which took at most the corresponding bound of
+    /// `BLOCK_PROOF_DURATION_BUCKETS`. Cumulative, like Prometheus buckets.
+    pub buckets: [u64; BLOCK_PROOF_DURATION_BUCKETS.len()],
+    pub count: u64,
+    pub sum: Duration,
+}
+
+impl BlockProofDurations {
+    pub fn add(&mut self, duration: Duration) {
+        BLOCK_PROOF_DURATION_BUCKETS
+            .iter()
+            .zip(&mut self.buckets)
+            .filter(|(bound, _)| duration <= **bound)
+            .for_each(|(_, count)| *count += 1);
+        self.count += 1;
+        self.sum += duration;
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -24,6 +77,9 @@ pub struct BlockProductionAttempt {
     pub times: BlockProductionTimes,
     #[serde(flatten)]
     pub status: BlockProductionStatus,
+    /// Explains what went into the staged ledger diff and what was left out.
+    #[serde(default)]
+    pub staged_ledger_diff_create_log: Option<StagedLedgerDiffCreateLog>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -31,6 +87,7 @@ pub struct BlockProductionAttemptWonSlot {
     pub slot_time: redux::Timestamp,
     pub global_slot: u32,
     pub epoch: u32,
+    pub producer: v2::NonZeroCurvePoint,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
     pub value_with_threshold: Option<(f64, f64)>,
 }
@@ -90,6 +147,98 @@ pub struct ProducedBlockTransactions {
     pub zkapps: u16,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreateLog {
+    pub counts: StagedLedgerDiffCreateCounts,
+    /// Transactions from the pool, in the order they were given to
+    /// the staged ledger. Empty if details were stripped.
+    pub transactions: Vec<StagedLedgerDiffCreateTransaction>,
+    /// Completed snark work, either bought or discarded. Empty if details
+    /// were stripped.
+    pub completed_works: Vec<StagedLedgerDiffCreateCompletedWork>,
+    pub partitions: Vec<StagedLedgerDiffCreatePartition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StagedLedgerDiffCreateCounts {
+    pub transactions_included: usize,
+    pub transactions_invalid: usize,
+    pub transactions_discarded: usize,
+    pub transactions_skipped: usize,
+    pub completed_works_bought: usize,
+    pub completed_works_discarded: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreateTransaction {
+    pub hash: v2::TransactionHash,
+    pub fee: u64,
+    #[serde(flatten)]
+    pub status: StagedLedgerDiffCreateTransactionStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "status")]
+pub enum StagedLedgerDiffCreateTransactionStatus {
+    Included,
+    /// Failed to apply on top of the parent staged ledger.
+    Invalid {
+        error: String,
+    },
+    Discarded {
+        reason: Reason,
+    },
+    /// Not looked at, the scan state had no free space left for it.
+    Skipped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreateCompletedWork {
+    pub job_id: SnarkJobId,
+    pub fee: u64,
+    pub prover: v2::NonZeroCurvePoint,
+    #[serde(flatten)]
+    pub status: StagedLedgerDiffCreateCompletedWorkStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "status")]
+pub enum StagedLedgerDiffCreateCompletedWorkStatus {
+    Bought,
+    Discarded { reason: Reason },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreatePartition {
+    pub partition: Partition,
+    pub available_slots: u64,
+    pub required_work_count: u64,
+    pub start: StagedLedgerDiffCreateResources,
+    pub end: StagedLedgerDiffCreateResources,
+    pub discarded_commands_insufficient_work: u64,
+    pub discarded_commands_insufficient_space: u64,
+    pub discarded_completed_works_insufficient_fees: u64,
+    pub discarded_completed_works_extra_work: u64,
+    /// Resources left after each step of the diff creation.
+    pub detail: Vec<StagedLedgerDiffCreateDetailLine>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreateResources {
+    pub commands_count: u64,
+    pub commands_fees: u64,
+    pub completed_works_count: u64,
+    pub completed_works_fees: u64,
+    pub coinbase_work_fees: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StagedLedgerDiffCreateDetailLine {
+    pub reason: Reason,
+    #[serde(flatten)]
+    pub resources: StagedLedgerDiffCreateResources,
+}
+
 impl BlockProducerStats {
     fn latest_attempt_block_hash_matches(&self, hash: &BlockHash) -> bool {
         self.attempts
@@ -102,6 +251,10 @@ impl BlockProducerStats {
         self.attempts.iter().cloned().collect()
     }
 
+    pub fn proof_durations(&self) -> &BlockProofDurations {
+        &self.proof_durations
+    }
+
     pub fn new_best_chain(&mut self, time: redux::Timestamp, chain: &[AppliedBlock]) {
         let (best_tip, chain) = chain.split_last().unwrap();
         let root_block = chain.first().unwrap_or(best_tip);
@@ -190,6 +343,7 @@ impl BlockProducerStats {
                 discarded: None,
             },
             status: BlockProductionStatus::Scheduled,
+            staged_ledger_diff_create_log: None,
         });
     }
 
@@ -207,18 +361,33 @@ impl BlockProducerStats {
         );
     }
 
-    pub fn staged_ledger_diff_create_end(&mut self, time: redux::Timestamp) {
+    pub fn staged_ledger_diff_create_end(
+        &mut self,
+        time: redux::Timestamp,
+        log: &StagedLedgerDiffCreateLog,
+    ) {
         self.update(
             "staged_ledger_diff_create_end",
             move |attempt| match attempt.status {
                 BlockProductionStatus::StagedLedgerDiffCreatePending => {
                     attempt.status = BlockProductionStatus::StagedLedgerDiffCreateSuccess;
                     attempt.times.staged_ledger_diff_create_end = Some(time);
+                    attempt.staged_ledger_diff_create_log = Some(log.clone());
                     true
                 }
                 _ => false,
             },
         );
+
+        if let Some(log) = self
+            .attempts
+            .iter_mut()
+            .rev()
+            .filter_map(|attempt| attempt.staged_ledger_diff_create_log.as_mut())
+            .nth(MAX_DETAILED_DIFF_CREATE_LOGS)
+        {
+            log.strip_details();
+        }
     }
 
     pub fn produced(
@@ -250,14 +419,22 @@ impl BlockProducerStats {
     }
 
     pub fn proof_create_end(&mut self, time: redux::Timestamp) {
-        self.update("proof_create_end", move |attempt| match attempt.status {
+        let mut duration = None;
+        self.update("proof_create_end", |attempt| match attempt.status {
             BlockProductionStatus::ProofCreatePending => {
                 attempt.status = BlockProductionStatus::ProofCreateSuccess;
                 attempt.times.proof_create_end = Some(time);
+                duration = attempt
+                    .times
+                    .proof_create_start
+                    .and_then(|start| time.checked_sub(start));
                 true
             }
             _ => false,
         });
+        if let Some(duration) = duration {
+            self.proof_durations.add(duration);
+        }
     }
 
     pub fn block_apply_start(&mut self, time: redux::Timestamp, hash: &BlockHash) {
@@ -327,6 +504,7 @@ impl From<&BlockProducerWonSlot> for BlockProductionAttemptWonSlot {
             slot_time: won_slot.slot_time,
             global_slot: won_slot.global_slot(),
             epoch: won_slot.epoch(),
+            producer: won_slot.producer.clone(),
             delegator: won_slot.delegator.clone(),
             value_with_threshold: won_slot.value_with_threshold,
         }
@@ -373,3 +551,391 @@ impl From<&BlockWithoutProof> for ProducedBlockTransactions {
             })
     }
 }
+
+impl StagedLedgerDiffCreateTransaction {
+    /// Transaction given to the staged ledger, not yet looked at.
+    pub fn new(cmd: &valid::UserCommand) -> Self {
+        Self {
+            hash: transaction_hash(cmd),
+            fee: cmd.fee().as_u64(),
+            status: StagedLedgerDiffCreateTransactionStatus::Skipped,
+        }
+    }
+}
+
+impl StagedLedgerDiffCreateLog {
+    /// `transactions` must be created with [`StagedLedgerDiffCreateTransaction::new`]
+    /// before the transactions are given to the staged ledger, so that
+    /// they don't need to be cloned.
+    pub fn new(
+        mut transactions: Vec<StagedLedgerDiffCreateTransaction>,
+        diff: &with_valid_signatures_and_proofs::Diff,
+        invalid_txns: &[(valid::UserCommand, String)],
+        log: &[DiffCreationLog],
+    ) -> Self {
+        let included = diff
+            .commands()
+            .iter()
+            .map(|cmd| transaction_hash(&cmd.data))
+            .collect::<BTreeSet<_>>();
+        let invalid = invalid_txns
+            .iter()
+            .map(|(cmd, error)| (transaction_hash(cmd), error))
+            .collect::<BTreeMap<_, _>>();
+
+        // Commands discarded from the first partition may still end up in
+        // the second one, so the last reason wins, unless it was included.
+        let mut discarded_commands = BTreeMap::new();
+        let mut discarded_works = BTreeMap::new();
+        for line in log.iter().flat_map(|log| log.detail.lines()) {
+            match &line.discarded {
+                None => {}
+                Some(Discarded::Command { hash }) => {
+                    discarded_commands.insert(hash.clone(), line.reason);
+                }
+                Some(Discarded::CompletedWork {
+                    job_id,
+                    fee,
+                    prover,
+                }) => {
+                    discarded_works.insert(job_id.clone(), (*fee, prover, line.reason));
+                }
+            }
+        }
+
+        for tx in &mut transactions {
+            tx.status = if included.contains(&tx.hash) {
+                StagedLedgerDiffCreateTransactionStatus::Included
+            } else if let Some(error) = invalid.get(&tx.hash) {
+                StagedLedgerDiffCreateTransactionStatus::Invalid {
+                    error: error.to_string(),
+                }
+            } else if let Some(reason) = discarded_commands.get(&tx.hash) {
+                StagedLedgerDiffCreateTransactionStatus::Discarded { reason: *reason }
+            } else {
+                StagedLedgerDiffCreateTransactionStatus::Skipped
+            };
+        }
+
+        let (first, second) = &diff.diff;
+        let bought = first
+            .completed_works
+            .iter()
+            .chain(second.iter().flat_map(|second| &second.completed_works))
+            .map(|work| {
+                let job_id = SnarkJobId::from(&work.statement());
+                discarded_works.remove(&job_id);
+                StagedLedgerDiffCreateCompletedWork {
+                    job_id,
+                    fee: work.fee.as_u64(),
+                    prover: (&work.prover).into(),
+                    status: StagedLedgerDiffCreateCompletedWorkStatus::Bought,
+                }
+            })
+            .collect::<Vec<_>>();
+        let completed_works = bought
+            .into_iter()
+            .chain(
+                discarded_works
+                    .into_iter()
+                    .map(
+                        |(job_id, (fee, prover, reason))| StagedLedgerDiffCreateCompletedWork {
+                            job_id,
+                            fee: fee.as_u64(),
+                            prover: prover.into(),
+                            status: StagedLedgerDiffCreateCompletedWorkStatus::Discarded { reason },
+                        },
+                    ),
+            )
+            .collect::<Vec<_>>();
+
+        let partitions = log
+            .iter()
+            .map(|log| {
+                let summary = &log.summary;
+                StagedLedgerDiffCreatePartition {
+                    partition: summary.partition.clone(),
+                    available_slots: summary.available_slots,
+                    required_work_count: summary.required_work_count,
+                    start: (&summary.start_resources).into(),
+                    end: (&summary.end_resources).into(),
+                    discarded_commands_insufficient_work: summary
+                        .discarded_commands
+                        .insufficient_work,
+                    discarded_commands_insufficient_space: summary
+                        .discarded_commands
+                        .insufficient_space,
+                    discarded_completed_works_insufficient_fees: summary
+                        .discarded_completed_work
+                        .insufficient_fees,
+                    discarded_completed_works_extra_work: summary
+                        .discarded_completed_work
+                        .extra_work,
+                    detail: log
+                        .detail
+                        .lines()
+                        .iter()
+                        .map(|line| StagedLedgerDiffCreateDetailLine {
+                            reason: line.reason,
+                            resources: StagedLedgerDiffCreateResources {
+                                commands_count: line.commands.0,
+                                commands_fees: line.commands.1.as_u64(),
+                                completed_works_count: line.completed_work.0,
+                                completed_works_fees: line.completed_work.1.as_u64(),
+                                coinbase_work_fees: coinbase_work_fees(&line.coinbase),
+                            },
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let mut counts = StagedLedgerDiffCreateCounts::default();
+        for tx in &transactions {
+            match tx.status {
+                StagedLedgerDiffCreateTransactionStatus::Included => {
+                    counts.transactions_included += 1
+                }
+                StagedLedgerDiffCreateTransactionStatus::Invalid { .. } => {
+                    counts.transactions_invalid += 1
+                }
+                StagedLedgerDiffCreateTransactionStatus::Discarded { .. } => {
+                    counts.transactions_discarded += 1
+                }
+                StagedLedgerDiffCreateTransactionStatus::Skipped => {
+                    counts.transactions_skipped += 1
+                }
+            }
+        }
+        for work in &completed_works {
+            match work.status {
+                StagedLedgerDiffCreateCompletedWorkStatus::Bought => {
+                    counts.completed_works_bought += 1
+                }
+                StagedLedgerDiffCreateCompletedWorkStatus::Discarded { .. } => {
+                    counts.completed_works_discarded += 1
+                }
+            }
+        }
+
+        Self {
+            counts,
+            transactions,
+            completed_works,
+            partitions,
+        }
+    }
+
+    /// Drops per transaction and per work entries, keeping just the
+    /// counts and partition summaries.
+    pub fn strip_details(&mut self) {
+        self.transactions = Vec::new();
+        self.completed_works = Vec::new();
+        for partition in &mut self.partitions {
+            partition.detail = Vec::new();
+        }
+    }
+}
+
+fn transaction_hash(cmd: &valid::UserCommand) -> v2::TransactionHash {
+    v2::TransactionHash::from(command_hash(cmd).as_ref())
+}
+
+impl From<&Resources> for StagedLedgerDiffCreateResources {
+    fn from(resources: &Resources) -> Self {
+        Self {
+            commands_count: resources.commands.0,
+            commands_fees: resources.commands.1.as_u64(),
+            completed_works_count: resources.completed_work.0,
+            completed_works_fees: resources.completed_work.1.as_u64(),
+            coinbase_work_fees: coinbase_work_fees(&resources.coinbase_work_fees),
+        }
+    }
+}
+
+fn coinbase_work_fees(fees: &AtMostTwo<Fee>) -> Vec<u64> {
+    match fees {
+        AtMostTwo::Zero | AtMostTwo::One(None) | AtMostTwo::Two(None) => vec![],
+        AtMostTwo::One(Some(fee)) | AtMostTwo::Two(Some((fee, None))) => vec![fee.as_u64()],
+        AtMostTwo::Two(Some((fee1, Some(fee2)))) => vec![fee1.as_u64(), fee2.as_u64()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ledger::scan_state::{
+        currency::{Amount, Nonce},
+        scan_state::transaction_snark::{work, OneOrTwo},
+        transaction_logic::{
+            signed_command::{self, SignedCommand, SignedCommandPayload},
+            Memo, TransactionStatus, WithStatus,
+        },
+    };
+    use ledger::staged_ledger::diff::PreDiffTwo;
+    use mina_signer::{CompressedPubKey, Signature};
+
+    use super::*;
+    use crate::snark_pool::test_snark;
+
+    fn payment(nonce: u32, fee: u64) -> valid::UserCommand {
+        let pk = CompressedPubKey::from_address(
+            "B62qkEfRowNNxqpA4KZX5FsWu3EDa15SYyxkjC3KvxqKVPbpQZyLofw",
+        )
+        .unwrap();
+        valid::UserCommand::SignedCommand(Box::new(SignedCommand {
+            payload: SignedCommandPayload::create(
+                Fee::from_u64(fee),
+                pk.clone(),
+                Nonce::from_u32(nonce),
+                None,
+                Memo::empty(),
+                signed_command::Body::Payment(signed_command::PaymentPayload {
+                    receiver_pk: pk.clone(),
+                    amount: Amount::from_u64(1),
+                }),
+            ),
+            signer: pk,
+            signature: Signature::dummy(),
+        }))
+    }
+
+    /// Two works for different jobs.
+    fn completed_works() -> (work::Checked, work::Checked) {
+        let first = work::Checked::try_from(&test_snark(10)).unwrap();
+        let other_proofs = match &first.proofs {
+            OneOrTwo::One(proof) => OneOrTwo::Two((proof.clone(), proof.clone())),
+            OneOrTwo::Two((proof, _)) => OneOrTwo::One(proof.clone()),
+        };
+        let other = work::Checked {
+            fee: Fee::from_u64(20),
+            proofs: other_proofs,
+            prover: first.prover.clone(),
+        };
+        (first, other)
+    }
+
+    fn new_log() -> StagedLedgerDiffCreateLog {
+        let txs = [
+            payment(0, 30),
+            payment(1, 20),
+            payment(2, 10),
+            payment(3, 5),
+        ];
+        let (bought, discarded) = completed_works();
+
+        let mut log = DiffCreationLog::init(
+            &[bought.clone(), discarded.clone()],
+            &[txs[0].clone(), txs[2].clone()],
+            &AtMostTwo::Zero,
+            Partition::First,
+            8,
+            1,
+        );
+        log.discard_command(Reason::NoSpace, &txs[2]);
+        log.discard_completed_work(Reason::ExtraWork, &discarded);
+        log.end_log(&[bought.clone()], &[txs[0].clone()], &AtMostTwo::Zero);
+
+        let diff = with_valid_signatures_and_proofs::Diff {
+            diff: (
+                PreDiffTwo {
+                    completed_works: vec![bought],
+                    commands: vec![WithStatus {
+                        data: txs[0].clone(),
+                        status: TransactionStatus::Applied,
+                    }],
+                    coinbase: AtMostTwo::Zero,
+                    internal_command_statuses: vec![],
+                },
+                None,
+            ),
+        };
+        let invalid_txns = [(txs[1].clone(), "invalid nonce".to_owned())];
+
+        StagedLedgerDiffCreateLog::new(
+            txs.iter()
+                .map(StagedLedgerDiffCreateTransaction::new)
+                .collect(),
+            &diff,
+            &invalid_txns,
+            &[log],
+        )
+    }
+
+    #[test]
+    fn staged_ledger_diff_create_log() {
+        let log = new_log();
+
+        let statuses = log
+            .transactions
+            .iter()
+            .map(|tx| (tx.fee, &tx.status))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            statuses[..],
+            [
+                (30, StagedLedgerDiffCreateTransactionStatus::Included),
+                (20, StagedLedgerDiffCreateTransactionStatus::Invalid { .. }),
+                (
+                    10,
+                    StagedLedgerDiffCreateTransactionStatus::Discarded {
+                        reason: Reason::NoSpace
+                    }
+                ),
+                (5, StagedLedgerDiffCreateTransactionStatus::Skipped),
+            ]
+        ));
+
+        let works = log
+            .completed_works
+            .iter()
+            .map(|work| (work.fee, &work.status))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            works[..],
+            [
+                (10, StagedLedgerDiffCreateCompletedWorkStatus::Bought),
+                (
+                    20,
+                    StagedLedgerDiffCreateCompletedWorkStatus::Discarded {
+                        reason: Reason::ExtraWork
+                    }
+                ),
+            ]
+        ));
+
+        assert_eq!(
+            log.counts,
+            StagedLedgerDiffCreateCounts {
+                transactions_included: 1,
+                transactions_invalid: 1,
+                transactions_discarded: 1,
+                transactions_skipped: 1,
+                completed_works_bought: 1,
+                completed_works_discarded: 1,
+            }
+        );
+
+        let [partition] = &log.partitions[..] else {
+            panic!("expected one partition");
+        };
+        assert_eq!(partition.discarded_commands_insufficient_space, 1);
+        assert_eq!(partition.discarded_completed_works_extra_work, 1);
+        assert_eq!(partition.start.commands_count, 2);
+        assert_eq!(partition.start.completed_works_fees, 30);
+        assert_eq!(partition.end.commands_count, 1);
+        assert_eq!(partition.detail.len(), 4);
+    }
+
+    #[test]
+    fn staged_ledger_diff_create_log_strip_details() {
+        let mut log = new_log();
+        let counts = log.counts.clone();
+        log.strip_details();
+
+        assert!(log.transactions.is_empty());
+        assert!(log.completed_works.is_empty());
+        assert_eq!(log.partitions.len(), 1);
+        assert!(log.partitions[0].detail.is_empty());
+        assert_eq!(log.counts, counts);
+    }
+}
@@ -32,6 +32,13 @@ pub enum SyncKind {
     Catchup,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStatus {
+    Bootstrap,
+    Catchup,
+    Synced,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum LedgerResyncKind {
     FetchStagedLedgerError(String),
@@ -53,6 +60,16 @@ pub struct SyncLedgers {
     pub root: Option<SyncLedger>,
 }
 
+impl SyncStatsSnapshot {
+    pub fn status(&self) -> SyncStatus {
+        match (&self.kind, self.synced) {
+            (_, Some(_)) => SyncStatus::Synced,
+            (SyncKind::Bootstrap, None) => SyncStatus::Bootstrap,
+            (SyncKind::Catchup, None) => SyncStatus::Catchup,
+        }
+    }
+}
+
 impl SyncLedgers {
     /// Figure out if a resync is required, and if so, for what reason.
     fn resync_kind(
@@ -345,6 +362,11 @@ impl SyncStats {
         self
     }
 
+    /// Status of the latest sync, `None` if the node never started syncing.
+    pub fn status(&self) -> Option<SyncStatus> {
+        self.snapshots.back().map(SyncStatsSnapshot::status)
+    }
+
     pub fn collect_stats(&self, limit: Option<usize>) -> Vec<SyncStatsSnapshot> {
         let limit = limit.unwrap_or(usize::MAX);
         self.snapshots.iter().rev().take(limit).cloned().collect()
@@ -13,7 +13,10 @@ use mina_p2p_messages::v2;
 use openmina_core::{
     bug_condition, consensus::ConsensusConstants, constants::constraint_constants, requests::RpcId,
 };
-use p2p::channels::transaction::P2pChannelsTransactionAction;
+use p2p::{
+    channels::transaction::P2pChannelsTransactionAction, P2pNetworkPubsubAction,
+    P2pNetworkPubsubValidationResult,
+};
 use redux::callback;
 use snark::{user_command_verify::SnarkUserCommandVerifyId, TransactionVerifier, VerifierSRS};
 use std::{
@@ -135,13 +138,24 @@ impl TransactionPoolState {
         let substate = state.get_substate_mut().unwrap();
 
         match action {
-            TransactionPoolAction::StartVerify { commands, from_rpc } => {
+            TransactionPoolAction::StartVerify {
+                commands,
+                from_rpc,
+                from_pubsub,
+            } => {
                 let Ok(commands) = commands
                     .iter()
                     .map(UserCommand::try_from)
                     .collect::<Result<Vec<_>, _>>()
                 else {
                     // ignore all commands if one is invalid
+                    if let Some(message_id) = from_pubsub.clone() {
+                        let dispatcher = state.into_dispatcher();
+                        dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessage {
+                            message_id,
+                            result: P2pNetworkPubsubValidationResult::Reject,
+                        });
+                    }
                     return;
                 };
 
@@ -168,8 +182,11 @@ impl TransactionPoolState {
                 pending_id,
                 from_rpc,
             } => {
-                let TransactionPoolAction::StartVerify { commands, .. } =
-                    substate.pending_actions.remove(pending_id).unwrap()
+                let TransactionPoolAction::StartVerify {
+                    commands,
+                    from_pubsub,
+                    ..
+                } = substate.pending_actions.remove(pending_id).unwrap()
                 else {
                     panic!()
                 };
@@ -184,16 +201,34 @@ impl TransactionPoolState {
                 };
                 let diff = diff::Diff { list: commands };
 
-                match substate.pool.verify(diff, accounts) {
+                let result = substate.pool.verify(diff, accounts);
+                let best_tip_hash = substate.best_tip_hash.clone();
+
+                let dispatcher = state.into_dispatcher();
+                if let Some(message_id) = from_pubsub {
+                    let result = match &result {
+                        Ok(_) => P2pNetworkPubsubValidationResult::Accept,
+                        Err(TransactionPoolErrors::BatchedErrors(_)) => {
+                            P2pNetworkPubsubValidationResult::Reject
+                        }
+                        // not the sender's fault
+                        Err(_) => P2pNetworkPubsubValidationResult::Ignore,
+                    };
+                    dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessage {
+                        message_id,
+                        result,
+                    });
+                }
+
+                match result {
                     Ok(valids) => {
                         let valids = valids
                             .into_iter()
                             .map(transaction_hash::hash_command)
                             .collect::<Vec<_>>();
-                        let best_tip_hash = substate.best_tip_hash.clone().unwrap();
+                        let best_tip_hash = best_tip_hash.unwrap();
                         let diff = DiffVerified { list: valids };
 
-                        let dispatcher = state.into_dispatcher();
                         dispatcher.push(TransactionPoolAction::ApplyVerifiedDiff {
                             best_tip_hash,
                             diff,
@@ -202,8 +237,7 @@ impl TransactionPoolState {
                         });
                     }
                     Err(e) => {
-                        let dispatch_errors = |errors: Vec<String>| {
-                            let dispatcher = state.into_dispatcher();
+                        let mut dispatch_errors = |errors: Vec<String>| {
                             dispatcher.push(TransactionPoolAction::VerifyError {
                                 errors: errors.clone(),
                             });
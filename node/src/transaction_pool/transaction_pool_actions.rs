@@ -12,6 +12,7 @@ use mina_p2p_messages::{
     v2::{self, LedgerHash},
 };
 use openmina_core::{requests::RpcId, ActionEvent};
+use p2p::P2pNetworkPubsubMessageId;
 use redux::Callback;
 use serde::{Deserialize, Serialize};
 
@@ -25,6 +26,9 @@ pub enum TransactionPoolAction {
     StartVerify {
         commands: List<v2::MinaBaseUserCommandStableV2>,
         from_rpc: Option<RpcId>,
+        /// Pubsub message which carried the commands, it needs to be
+        /// validated before being forwarded to other peers.
+        from_pubsub: Option<P2pNetworkPubsubMessageId>,
     },
     StartVerifyWithAccounts {
         accounts: BTreeMap<AccountId, Account>,
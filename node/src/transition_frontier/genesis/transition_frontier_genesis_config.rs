@@ -6,7 +6,7 @@ use std::{
     str::FromStr,
 };
 
-use crate::{account::AccountSecretKey, daemon_json::EpochData};
+use crate::{account::AccountSecretKey, daemon_json::EpochData, ledger::LedgerExport};
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use ledger::{
     proofs::caching::{ensure_path_exists, openmina_cache_path},
@@ -640,9 +640,37 @@ impl PrebuiltGenesisConfig {
         masks.push(staking_ledger_mask);
         Ok((masks, load_result))
     }
+
+    /// Genesis config with the exported ledgers, e.g. for starting a test
+    /// network from the state of a real one. Epoch ledgers default to the
+    /// genesis ledger. Epoch seeds are zero.
+    pub fn from_ledger_exports(
+        constants: ProtocolConstants,
+        genesis_ledger: LedgerExport,
+        staking_epoch_ledger: Option<LedgerExport>,
+        next_epoch_ledger: Option<LedgerExport>,
+    ) -> Result<Self, GenesisConfigError> {
+        let genesis_ledger = PrebuiltGenesisEpochData::try_from(genesis_ledger)?;
+        let staking_epoch_data = match staking_epoch_ledger {
+            Some(ledger) => ledger.try_into()?,
+            None => genesis_ledger.clone(),
+        };
+        let next_epoch_data = match next_epoch_ledger {
+            Some(ledger) => ledger.try_into()?,
+            None => genesis_ledger.clone(),
+        };
+        Ok(Self {
+            constants,
+            accounts: genesis_ledger.accounts,
+            ledger_hash: genesis_ledger.ledger_hash,
+            hashes: genesis_ledger.hashes,
+            staking_epoch_data,
+            next_epoch_data,
+        })
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, BinProtRead, BinProtWrite)]
+#[derive(Debug, Clone, Serialize, Deserialize, BinProtRead, BinProtWrite)]
 pub struct PrebuiltGenesisEpochData {
     accounts: Vec<MinaBaseAccountBinableArgStableV2>,
     ledger_hash: LedgerHash,
@@ -688,6 +716,42 @@ impl TryFrom<DaemonJson> for PrebuiltGenesisConfig {
     }
 }
 
+impl TryFrom<LedgerExport> for PrebuiltGenesisEpochData {
+    type Error = GenesisConfigError;
+
+    fn try_from(value: LedgerExport) -> Result<Self, Self::Error> {
+        let LedgerExport {
+            ledger_hash: expected_ledger_hash,
+            accounts,
+        } = value;
+
+        let (mut mask, _total_currency) = GenesisConfig::build_ledger_from_accounts(
+            accounts.iter().map(ledger::Account::try_from),
+        )?;
+        let ledger_hash = ledger_hash(&mut mask);
+
+        if ledger_hash != expected_ledger_hash {
+            return Err(Self::Error::LedgerHashMismatch {
+                expected: expected_ledger_hash,
+                computed: ledger_hash,
+            });
+        }
+
+        let hashes = mask
+            .get_raw_inner_hashes()
+            .into_iter()
+            .map(|(idx, hash)| (idx, v2::LedgerHash::from_fp(hash)))
+            .collect();
+
+        Ok(Self {
+            accounts,
+            ledger_hash,
+            hashes,
+            seed: v2::EpochSeed::zero(),
+        })
+    }
+}
+
 impl TryFrom<EpochData> for PrebuiltGenesisEpochData {
     type Error = GenesisConfigError;
 
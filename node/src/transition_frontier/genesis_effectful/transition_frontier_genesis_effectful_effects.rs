@@ -14,7 +14,9 @@ impl TransitionFrontierGenesisEffectfulAction {
                 store.service.load_genesis(config.clone());
             }
             TransitionFrontierGenesisEffectfulAction::ProveInit { block_hash, input } => {
-                store.service.prove(block_hash.clone(), input.clone());
+                store
+                    .service
+                    .prove(block_hash.clone(), input.clone(), false);
             }
         }
     }
@@ -15,3 +15,40 @@ mod transition_frontier_reducer;
 
 mod transition_frontier_effects;
 pub use transition_frontier_effects::*;
+
+/// `len` blocks extending `pred`, built from the recorded block. Blocks of
+/// a different `branch` get different hashes.
+#[cfg(test)]
+pub(crate) fn test_chain(
+    pred: Option<&openmina_core::block::AppliedBlock>,
+    len: u32,
+    branch: u32,
+) -> Vec<openmina_core::block::AppliedBlock> {
+    use std::sync::Arc;
+
+    use mina_p2p_messages::{binprot::BinProtRead, gossip::GossipNetMessageV2};
+    use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
+
+    let mut bytes: &[u8] =
+        include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/new_state.bin");
+    let GossipNetMessageV2::NewState(block) = GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+    else {
+        panic!("expected a new state message");
+    };
+    let mut chain: Vec<AppliedBlock> = Vec::new();
+    let start = pred.map_or(1, |b| b.height() + 1);
+    for height in start..start + len {
+        let mut block = block.clone();
+        if let Some(pred) = chain.last().or(pred) {
+            block.header.protocol_state.previous_state_hash = pred.hash().clone();
+        }
+        let consensus_state = &mut block.header.protocol_state.body.consensus_state;
+        consensus_state.blockchain_length = height.into();
+        consensus_state.epoch_count = branch.into();
+        chain.push(AppliedBlock {
+            block: ArcBlockWithHash::try_new(Arc::new(block)).unwrap(),
+            just_emitted_a_proof: false,
+        });
+    }
+    chain
+}
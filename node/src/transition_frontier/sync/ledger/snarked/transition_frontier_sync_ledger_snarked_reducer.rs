@@ -4,6 +4,7 @@ use mina_p2p_messages::v2::MinaLedgerSyncLedgerQueryStableV1;
 use p2p::{
     channels::rpc::{P2pChannelsRpcAction, P2pRpcId, P2pRpcRequest},
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    reputation::{P2pPenaltyReason, P2pReputationAction},
     PeerId,
 };
 
@@ -551,10 +552,13 @@ impl TransitionFrontierSyncLedgerSnarkedState {
                         .push(TransitionFrontierSyncLedgerSnarkedAction::MerkleTreeSyncSuccess);
                 }
             }
-            TransitionFrontierSyncLedgerSnarkedAction::ChildHashesRejected { .. } => {
+            TransitionFrontierSyncLedgerSnarkedAction::ChildHashesRejected { sender, .. } => {
                 // TODO(tizoc): should this be reflected in the state somehow?
-                // TODO(tizoc): we do nothing here, but the peer must be punished somehow
                 let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pReputationAction::Penalize {
+                    peer_id: *sender,
+                    reason: P2pPenaltyReason::InvalidLedgerAnswer,
+                });
                 dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
             }
             TransitionFrontierSyncLedgerSnarkedAction::ChildAccountsReceived { .. } => {}
@@ -586,10 +590,13 @@ impl TransitionFrontierSyncLedgerSnarkedState {
                         .push(TransitionFrontierSyncLedgerSnarkedAction::MerkleTreeSyncSuccess);
                 }
             }
-            TransitionFrontierSyncLedgerSnarkedAction::ChildAccountsRejected { .. } => {
+            TransitionFrontierSyncLedgerSnarkedAction::ChildAccountsRejected { sender, .. } => {
                 // TODO(tizoc): should this be reflected in the state somehow?
-                // TODO(tizoc): we do nothing here, but the peer must be punished somehow
                 let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pReputationAction::Penalize {
+                    peer_id: *sender,
+                    reason: P2pPenaltyReason::InvalidLedgerAnswer,
+                });
                 dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
             }
             TransitionFrontierSyncLedgerSnarkedAction::Success => {
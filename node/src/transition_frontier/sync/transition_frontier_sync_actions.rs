@@ -121,7 +121,8 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
     fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
         match self {
             TransitionFrontierSyncAction::Init { best_tip, .. } => {
-                !state.transition_frontier.sync.is_pending()
+                !state.transition_frontier.restore.is_pending()
+                    && !state.transition_frontier.sync.is_pending()
                     && !state.transition_frontier.sync.is_synced()
                     && state
                         .transition_frontier
@@ -139,7 +140,8 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
                 ..
             } => {
                 let blacklist = &state.transition_frontier.blacklist;
-                (state.transition_frontier.sync.is_pending() || state.transition_frontier.sync.is_synced())
+                !state.transition_frontier.restore.is_pending()
+                && (state.transition_frontier.sync.is_pending() || state.transition_frontier.sync.is_synced())
                     && !matches!(&state.transition_frontier.sync, TransitionFrontierSyncState::CommitPending { .. } | TransitionFrontierSyncState::CommitSuccess { .. })
                 && state
                     .transition_frontier
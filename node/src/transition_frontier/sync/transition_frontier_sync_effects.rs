@@ -70,6 +70,8 @@ impl TransitionFrontierSyncAction {
                 store.dispatch(TransitionFrontierSyncAction::BlocksPeersQuery);
                 // if we already have a block ready to be applied.
                 store.dispatch(TransitionFrontierSyncAction::BlocksNextApplyInit);
+                // if new best tip is a known fork, all blocks are already applied.
+                store.dispatch(TransitionFrontierSyncAction::BlocksSuccess);
 
                 // TODO(binier): cleanup ledgers
             }
@@ -317,8 +319,12 @@ impl TransitionFrontierSyncAction {
                 let Some(new_best_tip) = chain.last() else {
                     return;
                 };
+                // Staged ledgers of the forks which stay in the transition
+                // frontier need to be kept as well.
+                let forks = transition_frontier.forks_for_new_best_chain(chain);
                 let ledgers_to_keep = chain
                     .iter()
+                    .chain(forks.values())
                     .flat_map(|b| {
                         [
                             b.snarked_ledger_hash(),
@@ -18,6 +18,7 @@ impl TransitionFrontierSyncState {
         mut state_context: crate::Substate<Self>,
         action: TransitionFrontierSyncActionWithMetaRef<'_>,
         best_chain: &[AppliedBlock],
+        forks: &BTreeMap<StateHash, AppliedBlock>,
     ) {
         let Ok(state) = state_context.get_substate_mut() else {
             // TODO: log or propagate
@@ -131,8 +132,11 @@ impl TransitionFrontierSyncState {
                     needed_protocol_states,
                     ..
                 } => {
-                    let mut applied_blocks: BTreeMap<_, _> =
-                        best_chain.iter().map(|b| (b.hash(), b)).collect();
+                    let mut applied_blocks: BTreeMap<_, _> = best_chain
+                        .iter()
+                        .chain(forks.values())
+                        .map(|b| (b.hash(), b))
+                        .collect();
 
                     let old_chain = VecDeque::from(std::mem::take(chain));
                     let old_root = old_chain.front().and_then(|b| b.block()).unwrap().clone();
@@ -216,8 +220,11 @@ impl TransitionFrontierSyncState {
                 Self::CommitPending { .. } => {}
                 Self::CommitSuccess { .. } => {}
                 Self::Synced { time, .. } => {
-                    let applied_blocks: BTreeMap<_, _> =
-                        best_chain.iter().map(|b| (b.hash(), b)).collect();
+                    let applied_blocks: BTreeMap<_, _> = best_chain
+                        .iter()
+                        .chain(forks.values())
+                        .map(|b| (b.hash(), b))
+                        .collect();
 
                     let old_best_tip = best_chain.last().unwrap();
                     let old_root = best_chain.first().unwrap();
@@ -434,8 +441,11 @@ impl TransitionFrontierSyncState {
                 root_snarked_ledger_updates
                     .extend_with_needed(&root_block, root_block_updates.iter().rev().take(1));
 
-                let mut applied_blocks: BTreeMap<_, _> =
-                    best_chain.iter().map(|b| (b.hash(), b)).collect();
+                let mut applied_blocks: BTreeMap<_, _> = best_chain
+                    .iter()
+                    .chain(forks.values())
+                    .map(|b| (b.hash(), b))
+                    .collect();
 
                 let k = best_tip.constants().k.as_u32() as usize;
                 let mut chain = Vec::with_capacity(k + root_block_updates.len());
@@ -1,13 +1,15 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-use mina_p2p_messages::v2::StateHash;
-use openmina_core::block::ArcBlockWithHash;
+use mina_p2p_messages::v2::{MinaStateProtocolStateValueStableV2, StateHash};
+use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
 use super::genesis::TransitionFrontierGenesisAction;
 use super::genesis_effectful::TransitionFrontierGenesisEffectfulAction;
 use super::sync::{SyncError, TransitionFrontierSyncAction, TransitionFrontierSyncState};
+use super::TransitionFrontierRestoreState;
+use crate::ledger::write::LedgerWriteState;
 
 pub type TransitionFrontierActionWithMeta = redux::ActionWithMeta<TransitionFrontierAction>;
 pub type TransitionFrontierActionWithMetaRef<'a> =
@@ -26,6 +28,23 @@ pub enum TransitionFrontierAction {
     #[action_event(level = info)]
     GenesisInject,
 
+    /// Restore transition frontier persisted by the previous run of the node.
+    ///
+    /// Sync and genesis injection wait until restoring is finished.
+    RestoreInit,
+    RestorePending,
+    #[action_event(level = info, fields(restored_blocks = best_chain.len()))]
+    RestoreSuccess {
+        /// Restored best chain, empty if there was nothing to restore.
+        best_chain: Vec<AppliedBlock>,
+        /// Required protocol states for root block.
+        needed_protocol_states: BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
+    },
+    #[action_event(level = warn, fields(error))]
+    RestoreError {
+        error: String,
+    },
+
     Sync(TransitionFrontierSyncAction),
     /// Transition frontier synced.
     Synced {
@@ -36,6 +55,25 @@ pub enum TransitionFrontierAction {
         best_tip: ArcBlockWithHash,
         error: SyncError,
     },
+
+    /// Apply candidate block which wasn't taken as the best tip, but
+    /// extends one of the applied blocks, so that it's kept as a fork.
+    ForkApplyInit {
+        block: ArcBlockWithHash,
+    },
+    ForkApplyPending {
+        block: ArcBlockWithHash,
+    },
+    #[action_event(level = info, fields(display(hash)))]
+    ForkApplySuccess {
+        hash: StateHash,
+        just_emitted_a_proof: bool,
+    },
+    #[action_event(level = warn, fields(display(hash), error))]
+    ForkApplyError {
+        hash: StateHash,
+        error: String,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
@@ -44,7 +82,9 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
             TransitionFrontierAction::Genesis(a) => a.is_enabled(state, time),
             TransitionFrontierAction::GenesisEffect(a) => a.is_enabled(state, time),
             TransitionFrontierAction::GenesisInject => {
-                if state.transition_frontier.best_tip().is_some() {
+                if state.transition_frontier.best_tip().is_some()
+                    || state.transition_frontier.restore.is_pending()
+                {
                     return false;
                 }
                 let genesis_state = &state.transition_frontier.genesis;
@@ -54,6 +94,30 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
                     genesis_state.block_with_dummy_proof().is_some()
                 }
             }
+            TransitionFrontierAction::RestoreInit => {
+                let transition_frontier = &state.transition_frontier;
+                matches!(
+                    transition_frontier.restore,
+                    TransitionFrontierRestoreState::Idle
+                ) && transition_frontier
+                    .genesis
+                    .block_with_dummy_proof()
+                    .is_some()
+                    && transition_frontier
+                        .best_tip()
+                        .map_or(true, |tip| tip.is_genesis())
+                    && !transition_frontier.sync.is_pending()
+            }
+            TransitionFrontierAction::RestorePending => {
+                matches!(
+                    state.transition_frontier.restore,
+                    TransitionFrontierRestoreState::Idle
+                )
+            }
+            TransitionFrontierAction::RestoreSuccess { .. }
+            | TransitionFrontierAction::RestoreError { .. } => {
+                state.transition_frontier.restore.is_pending()
+            }
             TransitionFrontierAction::Sync(a) => a.is_enabled(state, time),
             TransitionFrontierAction::Synced { .. } => matches!(
                 state.transition_frontier.sync,
@@ -69,6 +133,32 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
                             .map_or(false, |s| s.is_apply_error()),
                     }
             }
+            TransitionFrontierAction::ForkApplyInit { block } => {
+                let transition_frontier = &state.transition_frontier;
+                // Ledger writes are shared with the sync and the block
+                // producer, forks are applied only when they are idle.
+                transition_frontier.sync.is_synced()
+                    && transition_frontier.fork_apply_pending.is_none()
+                    && !state.block_producer.is_producing()
+                    && matches!(
+                        &state.ledger.write,
+                        LedgerWriteState::Idle { .. } | LedgerWriteState::Success { .. }
+                    )
+                    && !transition_frontier.blacklist.contains_key(block.hash())
+                    && transition_frontier.applied_block(block.hash()).is_none()
+                    && transition_frontier
+                        .applied_block(block.pred_hash())
+                        .is_some()
+            }
+            TransitionFrontierAction::ForkApplyPending { .. } => {
+                state.transition_frontier.fork_apply_pending.is_none()
+            }
+            TransitionFrontierAction::ForkApplySuccess { hash, .. }
+            | TransitionFrontierAction::ForkApplyError { hash, .. } => state
+                .transition_frontier
+                .fork_apply_pending
+                .as_ref()
+                .map_or(false, |block| block.hash() == hash),
         }
     }
 }
@@ -2,11 +2,14 @@ use redux::Timestamp;
 
 use crate::block_producer::BlockProducerAction;
 use crate::consensus::ConsensusAction;
+use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
 use crate::ledger::LEDGER_DEPTH;
 use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
+use crate::rpc::RpcSubscriptionEvent;
 use crate::snark_pool::{SnarkPoolAction, SnarkWork};
 use crate::stats::sync::SyncingLedger;
-use crate::{Store, TransactionPoolAction};
+use crate::watched_accounts::{account_best_chain_diff, WatchedAccountBlockInfo};
+use crate::{Store, TransactionPoolAction, WatchedAccountsAction};
 
 use super::genesis::TransitionFrontierGenesisAction;
 use super::sync::ledger::snarked::{
@@ -19,7 +22,10 @@ use super::sync::ledger::{
     transition_frontier_sync_ledger_staged_success_effects, TransitionFrontierSyncLedgerAction,
 };
 use super::sync::{TransitionFrontierSyncAction, TransitionFrontierSyncState};
-use super::{TransitionFrontierAction, TransitionFrontierActionWithMeta, TransitionFrontierState};
+use super::{
+    TransitionFrontierAction, TransitionFrontierActionWithMeta, TransitionFrontierRestoreState,
+    TransitionFrontierState,
+};
 
 // TODO(refactor): all service accesses are for stats, how should that be handled?
 
@@ -34,8 +40,13 @@ pub fn transition_frontier_effects<S: crate::Service>(
             // TODO(refactor): this should be handled by a callback and removed from here
             // whenever any of these is going to happen, genesisinject must happen first
             match &a {
-                TransitionFrontierGenesisAction::Produce
-                | TransitionFrontierGenesisAction::ProveSuccess { .. } => {
+                TransitionFrontierGenesisAction::Produce => {
+                    // restore must be initiated before genesis gets injected,
+                    // so that we don't start syncing from scratch.
+                    store.dispatch(TransitionFrontierAction::RestoreInit);
+                    store.dispatch(TransitionFrontierAction::GenesisInject);
+                }
+                TransitionFrontierGenesisAction::ProveSuccess { .. } => {
                     store.dispatch(TransitionFrontierAction::GenesisInject);
                 }
                 _ => {}
@@ -47,6 +58,37 @@ pub fn transition_frontier_effects<S: crate::Service>(
         TransitionFrontierAction::GenesisInject => {
             synced_effects(&meta, store);
         }
+        TransitionFrontierAction::RestoreInit => {
+            let Some(genesis) = store
+                .state()
+                .transition_frontier
+                .genesis
+                .block_with_dummy_proof()
+            else {
+                return;
+            };
+            store.dispatch(LedgerWriteAction::Init {
+                request: LedgerWriteRequest::FrontierRestore {
+                    genesis_ledger_hash: genesis.genesis_ledger_hash().clone(),
+                },
+                on_init: redux::callback!(
+                    on_frontier_restore_init(_request: LedgerWriteRequest) -> crate::Action {
+                        TransitionFrontierAction::RestorePending
+                    }
+                ),
+            });
+        }
+        TransitionFrontierAction::RestorePending => {}
+        TransitionFrontierAction::RestoreSuccess { .. } => {
+            if let TransitionFrontierRestoreState::Success {
+                best_tip_height: Some(_),
+                ..
+            } = &store.state().transition_frontier.restore
+            {
+                synced_effects(&meta, store);
+            }
+        }
+        TransitionFrontierAction::RestoreError { .. } => {}
         TransitionFrontierAction::Sync(a) => {
             match a {
                 TransitionFrontierSyncAction::Init {
@@ -62,6 +104,7 @@ pub fn transition_frontier_effects<S: crate::Service>(
                             stats.syncing_blocks_init(chain);
                         }
                     }
+                    publish_sync_status(store);
                 }
                 TransitionFrontierSyncAction::BestTipUpdate {
                     ref best_tip,
@@ -90,6 +133,7 @@ pub fn transition_frontier_effects<S: crate::Service>(
                             stats.syncing_blocks_init(chain);
                         }
                     }
+                    publish_sync_status(store);
                 }
                 TransitionFrontierSyncAction::LedgerStakingPending => {
                     if let Some(stats) = store.service.stats() {
@@ -266,30 +310,109 @@ pub fn transition_frontier_effects<S: crate::Service>(
         TransitionFrontierAction::SyncFailed { .. } => {
             // TODO(SEC): disconnect/blacklist peers that caused this.
         }
+        TransitionFrontierAction::ForkApplyInit { block } => {
+            let Some(pred_block) = store
+                .state()
+                .transition_frontier
+                .applied_block(block.pred_hash())
+                .cloned()
+            else {
+                return;
+            };
+            store.dispatch(LedgerWriteAction::Init {
+                request: LedgerWriteRequest::BlockApply { block, pred_block },
+                on_init: redux::callback!(
+                    on_fork_apply_init(request: LedgerWriteRequest) -> crate::Action {
+                        let LedgerWriteRequest::BlockApply {
+                            block,
+                            pred_block: _,
+                        } = request
+                        else {
+                            unreachable!()
+                        };
+                        TransitionFrontierAction::ForkApplyPending { block }
+                    }
+                ),
+            });
+        }
+        TransitionFrontierAction::ForkApplyPending { .. } => {}
+        TransitionFrontierAction::ForkApplySuccess { .. } => {
+            transition_frontier_fork_apply_next(store);
+        }
+        TransitionFrontierAction::ForkApplyError { .. } => {
+            transition_frontier_fork_apply_next(store);
+        }
     }
 }
 
+/// Start applying the next candidate block which wasn't taken as the
+/// best tip, but can be kept as a fork.
+pub fn transition_frontier_fork_apply_next<S: crate::Service>(store: &mut Store<S>) {
+    let state = store.state();
+    let transition_frontier = &state.transition_frontier;
+    let Some(block) = state.consensus.fork_candidates().find(|block| {
+        transition_frontier.applied_block(block.hash()).is_none()
+            && !transition_frontier.blacklist.contains_key(block.hash())
+            && transition_frontier
+                .applied_block(block.pred_hash())
+                .is_some()
+    }) else {
+        return;
+    };
+    store.dispatch(TransitionFrontierAction::ForkApplyInit { block });
+}
+
 fn synced_effects<S: crate::Service>(
     meta: &redux::ActionMeta,
     store: &mut redux::Store<crate::State, S, crate::Action>,
 ) {
+    let transition_frontier = &store.state.get().transition_frontier;
     let TransitionFrontierState {
         best_chain,
         chain_diff,
+        best_chain_update,
         ..
-    } = &store.state.get().transition_frontier;
+    } = transition_frontier;
 
-    let Some(best_tip) = best_chain.last() else {
+    let (Some(root), Some(best_tip)) = (best_chain.first(), best_chain.last()) else {
         return;
     };
     if let Some(stats) = store.service.stats() {
         stats.new_best_chain(meta.time(), best_chain);
     }
+    // Protocol states are only needed to persist the new root.
+    let needed_protocol_states = best_chain_update
+        .root_changed
+        .then(|| transition_frontier.needed_protocol_states.clone());
+    let new_blocks = transition_frontier.best_chain_new_blocks();
+    store
+        .service
+        .frontier_persist(root.clone(), new_blocks.to_vec(), needed_protocol_states);
+    let parent = best_chain
+        .len()
+        .checked_sub(new_blocks.len() + 1)
+        .map(|i| best_chain[i].clone());
+    store.service.frontier_archive(parent, new_blocks.to_vec());
 
     let chain_diff = chain_diff.clone();
 
     // publish new best tip.
     let best_tip = best_tip.clone();
+    if chain_diff
+        .as_ref()
+        .map_or(false, |diff| diff.reorg_best_tip)
+    {
+        store
+            .service
+            .publish_subscription_event(RpcSubscriptionEvent::ChainReorganization {
+                best_tip: best_tip.hash().clone(),
+            });
+    }
+    store
+        .service
+        .publish_subscription_event(RpcSubscriptionEvent::NewBestTip(best_tip.clone()));
+    publish_sync_status(store);
+
     for peer_id in store.state().p2p.ready_peers() {
         store.dispatch(P2pChannelsBestTipAction::ResponseSend {
             peer_id,
@@ -311,6 +434,51 @@ fn synced_effects<S: crate::Service>(
             diff,
         });
     }
+
+    // Watched accounts are read from the staged ledger, which is only
+    // available once the block is applied. All blocks added to the best
+    // chain since it was last looked at are checked for the account's
+    // transactions, and blocks dropped by a reorg are removed.
+    let state = store.state();
+    let best_chain = &state.transition_frontier.best_chain;
+    let watched_accounts_updates = state
+        .watched_accounts
+        .iter()
+        .map(|(pub_key, account)| {
+            let (orphaned_blocks, new_blocks) = account_best_chain_diff(account, best_chain);
+            let new_blocks = new_blocks
+                .iter()
+                .map(|b| b.block.clone())
+                .collect::<Vec<_>>();
+            (pub_key.clone(), orphaned_blocks, new_blocks)
+        })
+        .collect::<Vec<_>>();
+    for (pub_key, orphaned_blocks, new_blocks) in watched_accounts_updates {
+        store.dispatch(WatchedAccountsAction::LedgerInitialStateGetInit {
+            pub_key: pub_key.clone(),
+        });
+        for block in new_blocks {
+            store.dispatch(WatchedAccountsAction::TransactionsIncludedInBlock {
+                pub_key: pub_key.clone(),
+                block,
+            });
+        }
+        store.dispatch(WatchedAccountsAction::BestChainUpdate {
+            pub_key,
+            orphaned_blocks,
+            best_tip: WatchedAccountBlockInfo::new(&best_tip.block),
+        });
+    }
+}
+
+fn publish_sync_status<S: crate::Service>(
+    store: &mut redux::Store<crate::State, S, crate::Action>,
+) {
+    if let Some(status) = store.service.stats().and_then(|stats| stats.sync_status()) {
+        store
+            .service
+            .publish_subscription_event(RpcSubscriptionEvent::SyncStatus(status));
+    }
 }
 
 // Handling of the actions related to the synchronization of a target ledger
@@ -1,8 +1,11 @@
 use openmina_core::block::AppliedBlock;
 
+use crate::consensus::transition_frontier_new_best_tip_handler;
+
 use super::sync::{SyncError, TransitionFrontierSyncState};
 use super::{
-    TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierState,
+    TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierRestoreState,
+    TransitionFrontierState,
 };
 
 impl TransitionFrontierState {
@@ -37,17 +40,58 @@ impl TransitionFrontierState {
                 };
                 let new_chain = vec![genesis];
                 state.chain_diff = state.maybe_make_chain_diff(&new_chain);
+                state.best_chain_update = state.best_chain_update_for(&new_chain);
                 state.best_chain = new_chain;
                 if !state.sync.is_pending() {
                     state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
                 }
             }
+            TransitionFrontierAction::RestoreInit => {}
+            TransitionFrontierAction::RestorePending => {
+                state.restore = TransitionFrontierRestoreState::Pending { time: meta.time() };
+            }
+            TransitionFrontierAction::RestoreSuccess {
+                best_chain,
+                needed_protocol_states,
+            } => {
+                let restored_tip = best_chain.last();
+                let is_better = restored_tip.map_or(false, |restored_tip| {
+                    state
+                        .best_tip()
+                        .map_or(true, |tip| restored_tip.height() > tip.height())
+                });
+                let apply = is_better && !state.sync.is_pending();
+                state.restore = TransitionFrontierRestoreState::Success {
+                    time: meta.time(),
+                    best_tip_height: restored_tip.filter(|_| apply).map(|b| b.height()),
+                };
+                if apply {
+                    state.needed_protocol_states = needed_protocol_states.clone();
+                    state.forks = state.forks_for_new_best_chain(best_chain);
+                    state.chain_diff = state.maybe_make_chain_diff(best_chain);
+                    state.best_chain_update = state.best_chain_update_for(best_chain);
+                    state.best_chain = best_chain.clone();
+                    state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
+                }
+
+                Self::restore_finished(state_context);
+            }
+            TransitionFrontierAction::RestoreError { error } => {
+                state.restore = TransitionFrontierRestoreState::Error {
+                    time: meta.time(),
+                    error: error.clone(),
+                };
+
+                Self::restore_finished(state_context);
+            }
             TransitionFrontierAction::Sync(a) => {
                 let best_chain = state.best_chain.clone();
+                let forks = state.forks.clone();
                 super::sync::TransitionFrontierSyncState::reducer(
                     openmina_core::Substate::from_compatible_substate(state_context),
                     meta.with_action(a),
                     &best_chain,
+                    &forks,
                 );
             }
             TransitionFrontierAction::Synced {
@@ -89,7 +133,9 @@ impl TransitionFrontierState {
                     let tip = new_chain.last().unwrap();
                     *height + tip.constants().k.as_u32() > tip.height()
                 });
+                state.forks = state.forks_for_new_best_chain(&new_chain);
                 state.chain_diff = state.maybe_make_chain_diff(&new_chain);
+                state.best_chain_update = state.best_chain_update_for(&new_chain);
                 state.best_chain = new_chain;
                 state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
             }
@@ -101,6 +147,40 @@ impl TransitionFrontierState {
                 }
                 state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
             }
+            TransitionFrontierAction::ForkApplyInit { .. } => {}
+            TransitionFrontierAction::ForkApplyPending { block } => {
+                state.fork_apply_pending = Some(block.clone());
+            }
+            TransitionFrontierAction::ForkApplySuccess {
+                just_emitted_a_proof,
+                ..
+            } => {
+                let Some(block) = state.fork_apply_pending.take() else {
+                    return;
+                };
+                // Root might have moved past the fork while it was applied.
+                if state.applied_block(block.pred_hash()).is_none() {
+                    return;
+                }
+                let block = AppliedBlock {
+                    block,
+                    just_emitted_a_proof: *just_emitted_a_proof,
+                };
+                state.forks.insert(block.hash().clone(), block);
+            }
+            TransitionFrontierAction::ForkApplyError { .. } => {
+                if let Some(block) = state.fork_apply_pending.take() {
+                    state.blacklist.insert(block.hash().clone(), block.height());
+                }
+            }
         }
     }
+
+    /// Once restoring is done, genesis block is injected if nothing was
+    /// restored, and sync is resumed towards the current consensus best tip.
+    fn restore_finished(state_context: crate::Substate<Self>) {
+        let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+        dispatcher.push(TransitionFrontierAction::GenesisInject);
+        transition_frontier_new_best_tip_handler(global_state, dispatcher);
+    }
 }
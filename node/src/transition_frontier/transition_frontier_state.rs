@@ -1,11 +1,14 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use ark_ff::fields::arithmetic::InvalidBigInt;
 use ledger::transaction_pool::diff::BestTipDiff;
+use mina_p2p_messages::list::List;
 use mina_p2p_messages::v2::{
-    MinaStateProtocolStateBodyValueStableV2, MinaStateProtocolStateValueStableV2, StateHash,
-    TransactionHash,
+    MinaBaseStateBodyHashStableV1, MinaStateProtocolStateBodyValueStableV2,
+    MinaStateProtocolStateValueStableV2, StateHash, TransactionHash,
 };
 use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
+use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
 use super::genesis::TransitionFrontierGenesisState;
@@ -19,17 +22,71 @@ pub struct TransitionFrontierState {
     pub genesis: TransitionFrontierGenesisState,
     /// Current best known chain, from root of the transition frontier to best tip
     pub best_chain: Vec<AppliedBlock>,
+    /// Applied blocks which descend from the root of the transition frontier,
+    /// but aren't part of the `best_chain`. Together with the `best_chain`,
+    /// they form the full breadcrumb tree of the transition frontier.
+    ///
+    /// Staged ledgers for these blocks are kept by the ledger service, so
+    /// best tip can switch to one of these branches without re-fetching
+    /// and re-applying the blocks.
+    #[serde(default)]
+    pub forks: BTreeMap<StateHash, AppliedBlock>,
+    /// Candidate block which wasn't taken as the best tip, being applied
+    /// so that it can be kept in `forks`.
+    #[serde(default)]
+    pub fork_apply_pending: Option<ArcBlockWithHash>,
     /// Needed protocol states for applying transactions in the root
     /// scan state that we don't have in the `best_chain` list.
     pub needed_protocol_states: BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
     /// Transition frontier synchronization state
     pub sync: TransitionFrontierSyncState,
+    /// Restoring of the transition frontier persisted by the previous run.
+    #[serde(default)]
+    pub restore: TransitionFrontierRestoreState,
 
     /// Blocks which had valid proof but failed block application or
     /// other validations after it reached transition frontier.
     pub blacklist: BTreeMap<StateHash, u32>,
     /// The diff of `Self::best_chain` with the previous one
     pub chain_diff: Option<BestTipDiff>,
+    /// Blocks added to `Self::best_chain` by its last update.
+    #[serde(default)]
+    pub best_chain_update: TransitionFrontierBestChainUpdate,
+}
+
+/// How the last update changed the best chain, so that only the change
+/// needs to be persisted and archived.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct TransitionFrontierBestChainUpdate {
+    /// Whether the root of the best chain changed.
+    pub root_changed: bool,
+    /// Number of blocks at the end of the best chain, which weren't part
+    /// of the previous best chain.
+    pub new_blocks: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub enum TransitionFrontierRestoreState {
+    #[default]
+    Idle,
+    Pending {
+        time: Timestamp,
+    },
+    Success {
+        time: Timestamp,
+        /// Height of the restored best tip, `None` if nothing was restored.
+        best_tip_height: Option<u32>,
+    },
+    Error {
+        time: Timestamp,
+        error: String,
+    },
+}
+
+impl TransitionFrontierRestoreState {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. })
+    }
 }
 
 impl TransitionFrontierState {
@@ -38,10 +95,14 @@ impl TransitionFrontierState {
             config,
             genesis: TransitionFrontierGenesisState::Idle,
             best_chain: Vec::with_capacity(290),
+            forks: Default::default(),
+            fork_apply_pending: None,
             needed_protocol_states: Default::default(),
             sync: TransitionFrontierSyncState::Idle,
+            restore: TransitionFrontierRestoreState::Idle,
             blacklist: Default::default(),
             chain_diff: None,
+            best_chain_update: Default::default(),
         }
     }
 
@@ -61,6 +122,123 @@ impl TransitionFrontierState {
         self.best_chain.first()
     }
 
+    /// Looks up applied block, either in the best chain or in forks.
+    pub fn applied_block(&self, hash: &StateHash) -> Option<&AppliedBlock> {
+        self.forks
+            .get(hash)
+            .or_else(|| self.best_chain.iter().rev().find(|b| b.hash() == hash))
+    }
+
+    /// Iterates over all applied blocks in the transition frontier.
+    pub fn applied_blocks_iter(&self) -> impl Iterator<Item = &AppliedBlock> {
+        self.best_chain.iter().chain(self.forks.values())
+    }
+
+    /// Tips of all known branches of the transition frontier, including
+    /// the best tip.
+    pub fn tips(&self) -> Vec<&AppliedBlock> {
+        let preds = self
+            .applied_blocks_iter()
+            .map(|b| b.pred_hash())
+            .collect::<BTreeSet<_>>();
+        self.applied_blocks_iter()
+            .filter(|b| !preds.contains(b.hash()))
+            .collect()
+    }
+
+    /// Blocks added to the best chain by its last update.
+    pub fn best_chain_new_blocks(&self) -> &[AppliedBlock] {
+        let new_blocks = self.best_chain_update.new_blocks.min(self.best_chain.len());
+        &self.best_chain[self.best_chain.len() - new_blocks..]
+    }
+
+    /// How the best chain changes, if it's replaced with the `new_chain`.
+    pub fn best_chain_update_for(
+        &self,
+        new_chain: &[AppliedBlock],
+    ) -> TransitionFrontierBestChainUpdate {
+        let old_chain = self
+            .best_chain
+            .iter()
+            .map(|b| b.hash())
+            .collect::<BTreeSet<_>>();
+        TransitionFrontierBestChainUpdate {
+            root_changed: self.root().map(|b| b.hash()) != new_chain.first().map(|b| b.hash()),
+            new_blocks: new_chain
+                .iter()
+                .rev()
+                .take_while(|b| !old_chain.contains(b.hash()))
+                .count(),
+        }
+    }
+
+    /// Chain of applied blocks from the root to the block with `hash`.
+    pub fn chain_to(&self, hash: &StateHash) -> Option<Vec<&AppliedBlock>> {
+        let root = self.root()?;
+        let mut chain = vec![self.applied_block(hash)?];
+        loop {
+            let last = chain.last()?;
+            if last.hash() == root.hash() {
+                break;
+            }
+            let pred = self.applied_block(last.pred_hash())?;
+            chain.push(pred);
+        }
+        chain.reverse();
+        Some(chain)
+    }
+
+    /// Proof that the block with `hash` descends from the root: the root
+    /// block and state body hashes of the blocks after the root, up to
+    /// and including the block with `hash`.
+    pub fn chain_proof(
+        &self,
+        hash: &StateHash,
+    ) -> Option<Result<(&AppliedBlock, List<MinaBaseStateBodyHashStableV1>), InvalidBigInt>> {
+        let chain = self.chain_to(hash)?;
+        let (root, blocks) = chain.split_first()?;
+        // TODO(binier): cache body hashes
+        let body_hashes = blocks
+            .iter()
+            .map(|b| b.header().protocol_state.body.try_hash())
+            .collect::<Result<_, _>>();
+        Some(body_hashes.map(|body_hashes| (*root, body_hashes)))
+    }
+
+    /// Forks which need to be kept once `new_chain` becomes the best chain.
+    ///
+    /// Blocks of the current best chain which aren't part of the `new_chain`
+    /// become forks. Forks which don't descend from the `new_chain` (after
+    /// root transition) are pruned.
+    pub fn forks_for_new_best_chain(
+        &self,
+        new_chain: &[AppliedBlock],
+    ) -> BTreeMap<StateHash, AppliedBlock> {
+        let new_chain_hashes = new_chain.iter().map(|b| b.hash()).collect::<BTreeSet<_>>();
+        let candidates = self
+            .applied_blocks_iter()
+            .filter(|b| !new_chain_hashes.contains(b.hash()))
+            .map(|b| (b.hash(), b))
+            .collect::<BTreeMap<_, _>>();
+
+        candidates
+            .values()
+            .filter(|block| {
+                let mut block: &AppliedBlock = block;
+                loop {
+                    if new_chain_hashes.contains(block.pred_hash()) {
+                        return true;
+                    }
+                    match candidates.get(block.pred_hash()) {
+                        Some(pred) => block = *pred,
+                        None => return false,
+                    }
+                }
+            })
+            .map(|block| (block.hash().clone(), (*block).clone()))
+            .collect()
+    }
+
     /// FIXME
     /// Note(adonagy): This can be expensive, keep a map with all the tx hashis in the best chain
     pub fn contains_transaction(&self, hash: &TransactionHash) -> bool {
@@ -120,11 +298,14 @@ impl TransitionFrontierState {
                 .find(|(_index, block)| *block == new_root),
         };
 
-        let (diff_old_chain, diff_new_chain) = match new_chain_start_at {
+        // Whether blocks of the old chain were dropped, so the new best
+        // tip is not a descendant of the old one.
+        let (diff_old_chain, diff_new_chain, reorg_best_tip) = match new_chain_start_at {
             None => {
                 // The new chain has a root not present in the old chain,
-                // so the diff is the 2 wholes chains
-                (old_chain, new_chain)
+                // so the diff is the 2 wholes chains. That happens when
+                // (re)syncing, which isn't a reorg.
+                (old_chain, new_chain, false)
             }
             Some((new_chain_start_at, _)) => {
                 // `new_chain_start_at` is the index of `new_root` in `old_chain`
@@ -145,7 +326,7 @@ impl TransitionFrontierState {
                 let diff_old_chain = &old_chain_advanced[diff_start_at..];
                 let diff_new_chain = &new_chain[diff_start_at..];
 
-                (diff_old_chain, diff_new_chain)
+                (diff_old_chain, diff_new_chain, !diff_old_chain.is_empty())
             }
         };
 
@@ -168,14 +349,145 @@ impl TransitionFrontierState {
         let removed_commands = collect(diff_old_chain);
         let new_commands = collect(diff_new_chain);
 
-        if removed_commands.is_empty() && new_commands.is_empty() {
+        if removed_commands.is_empty() && new_commands.is_empty() && !reorg_best_tip {
             return None;
         }
 
         Some(BestTipDiff {
             new_commands,
             removed_commands,
-            reorg_best_tip: false, // TODO: Unused for now
+            reorg_best_tip,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEVNET_CONFIG;
+    use crate::transition_frontier::test_chain;
+
+    fn frontier(best_chain: Vec<AppliedBlock>, forks: &[AppliedBlock]) -> TransitionFrontierState {
+        let mut state =
+            TransitionFrontierState::new(TransitionFrontierConfig::new(DEVNET_CONFIG.clone()));
+        state.best_chain = best_chain;
+        state.forks = forks
+            .iter()
+            .map(|b| (b.hash().clone(), b.clone()))
+            .collect();
+        state
+    }
+
+    fn hashes<'a>(blocks: impl IntoIterator<Item = &'a AppliedBlock>) -> BTreeSet<StateHash> {
+        blocks.into_iter().map(|b| b.hash().clone()).collect()
+    }
+
+    #[test]
+    fn tips_of_all_branches() {
+        let best_chain = test_chain(None, 5, 0);
+        let fork_a = test_chain(Some(&best_chain[1]), 2, 1);
+        let fork_b = test_chain(Some(&best_chain[3]), 1, 2);
+        let forks = [fork_a.clone(), fork_b.clone()].concat();
+        let state = frontier(best_chain.clone(), &forks);
+
+        assert_eq!(
+            hashes(state.tips()),
+            hashes([&best_chain[4], &fork_a[1], &fork_b[0]])
+        );
+        assert_eq!(
+            hashes(frontier(best_chain.clone(), &[]).tips()),
+            hashes([&best_chain[4]])
+        );
+    }
+
+    #[test]
+    fn chain_to_fork() {
+        let best_chain = test_chain(None, 5, 0);
+        let fork = test_chain(Some(&best_chain[1]), 2, 1);
+        let state = frontier(best_chain.clone(), &fork);
+
+        let chain = state.chain_to(fork[1].hash()).unwrap();
+        let expected = [&best_chain[0], &best_chain[1], &fork[0], &fork[1]];
+        assert_eq!(
+            chain.iter().map(|b| b.hash()).collect::<Vec<_>>(),
+            expected.iter().map(|b| b.hash()).collect::<Vec<_>>()
+        );
+
+        let chain = state.chain_to(best_chain[2].hash()).unwrap();
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[0].hash(), best_chain[0].hash());
+
+        // unknown block
+        let unknown = test_chain(Some(&best_chain[4]), 1, 0);
+        assert!(state.chain_to(unknown[0].hash()).is_none());
+
+        // fork whose pred was pruned
+        let detached = test_chain(Some(&fork[0]), 1, 3);
+        let state = frontier(best_chain, &detached);
+        assert!(state.chain_to(detached[0].hash()).is_none());
+    }
+
+    #[test]
+    fn forks_for_new_best_chain_switch_to_fork() {
+        let best_chain = test_chain(None, 5, 0);
+        let fork = test_chain(Some(&best_chain[1]), 4, 1);
+        let state = frontier(best_chain.clone(), &fork);
+
+        let new_chain = [&best_chain[..2], &fork[..]].concat();
+        let forks = state.forks_for_new_best_chain(&new_chain);
+        // Old best chain blocks after the fork point become a fork.
+        assert_eq!(hashes(forks.values()), hashes(&best_chain[2..]));
+    }
+
+    #[test]
+    fn forks_for_new_best_chain_prunes_after_root_transition() {
+        let best_chain = test_chain(None, 5, 0);
+        let kept = test_chain(Some(&best_chain[2]), 2, 1);
+        let pruned = test_chain(Some(&best_chain[0]), 2, 2);
+        let forks = [kept.clone(), pruned].concat();
+        let state = frontier(best_chain.clone(), &forks);
+
+        // Root moved to `best_chain[1]` and the chain was extended.
+        let extension = test_chain(Some(&best_chain[4]), 1, 0);
+        let new_chain = [&best_chain[1..], &extension[..]].concat();
+        let forks = state.forks_for_new_best_chain(&new_chain);
+        assert_eq!(hashes(forks.values()), hashes(&kept));
+    }
+
+    #[test]
+    fn forks_for_new_best_chain_keeps_applied_non_best_forks() {
+        let best_chain = test_chain(None, 3, 0);
+        let fork = test_chain(Some(&best_chain[2]), 1, 1);
+        let state = frontier(best_chain.clone(), &fork);
+
+        let extension = test_chain(Some(&best_chain[2]), 1, 0);
+        let new_chain = [&best_chain[..], &extension[..]].concat();
+        let forks = state.forks_for_new_best_chain(&new_chain);
+        assert_eq!(hashes(forks.values()), hashes(&fork));
+    }
+
+    #[test]
+    fn best_chain_update() {
+        let best_chain = test_chain(None, 5, 0);
+        let state = frontier(best_chain.clone(), &[]);
+
+        // Extended by a block, root moved.
+        let extension = test_chain(Some(&best_chain[4]), 1, 0);
+        let new_chain = [&best_chain[1..], &extension[..]].concat();
+        let update = state.best_chain_update_for(&new_chain);
+        assert!(update.root_changed);
+        assert_eq!(update.new_blocks, 1);
+
+        // Switched to a fork, root kept.
+        let fork = test_chain(Some(&best_chain[2]), 3, 1);
+        let new_chain = [&best_chain[..3], &fork[..]].concat();
+        let update = state.best_chain_update_for(&new_chain);
+        assert!(!update.root_changed);
+        assert_eq!(update.new_blocks, 3);
+
+        let mut state = state;
+        state.best_chain = new_chain;
+        state.best_chain_update = update;
+        assert_eq!(hashes(state.best_chain_new_blocks()), hashes(&fork));
+    }
+}
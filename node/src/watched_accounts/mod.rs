@@ -6,11 +6,15 @@ pub use watched_accounts_actions::*;
 
 mod watched_accounts_reducer;
 
+mod watched_accounts_effects;
+pub use watched_accounts_effects::*;
+
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use mina_p2p_messages::v2::{
     NonZeroCurvePoint, NonZeroCurvePointUncompressedStableV1, StagedLedgerDiffDiffDiffStableV2,
-    StagedLedgerDiffDiffPreDiffWithAtMostTwoCoinbaseStableV2B,
+    StagedLedgerDiffDiffPreDiffWithAtMostTwoCoinbaseStableV2B, StateHash,
 };
+use openmina_core::block::AppliedBlock;
 
 pub fn is_transaction_affecting_account(
     pub_key: &NonZeroCurvePoint,
@@ -49,3 +53,162 @@ pub fn account_relevant_transactions_in_diff_iter<'a>(
             status: tx.status.clone(),
         })
 }
+
+/// Compares blocks known for the watched account with the new
+/// `best_chain`. Returns hashes of the account's blocks which are no
+/// longer part of it, and blocks of it which weren't looked at for the
+/// account's transactions yet.
+///
+/// After a reorg the previous best tip isn't part of the new chain, so
+/// new blocks are looked for after the newest account's block which
+/// still is.
+pub fn account_best_chain_diff<'a>(
+    account: &WatchedAccountState,
+    best_chain: &'a [AppliedBlock],
+) -> (Vec<StateHash>, &'a [AppliedBlock]) {
+    let Some(root) = best_chain.first() else {
+        return (Vec::new(), &[]);
+    };
+    let is_in_best_chain = |block: &WatchedAccountBlockInfo| {
+        block
+            .level
+            .checked_sub(root.height())
+            .and_then(|i| best_chain.get(i as usize))
+            .map_or(false, |b| b.hash() == &block.hash)
+    };
+
+    let orphaned_blocks = account
+        .blocks
+        .iter()
+        .map(|b| b.block())
+        .filter(|b| b.level >= root.height() && !is_in_best_chain(b))
+        .map(|b| b.hash.clone())
+        .collect();
+
+    let WatchedAccountLedgerInitialState::Success { block, .. } = &account.initial_state else {
+        return (orphaned_blocks, &[]);
+    };
+    let last_seen_level = account
+        .best_tip
+        .iter()
+        .chain(account.blocks.iter().map(|b| b.block()))
+        .filter(|b| is_in_best_chain(b))
+        .map(|b| b.level)
+        .fold(block.level, u32::max);
+    let start = last_seen_level
+        .saturating_add(1)
+        .saturating_sub(root.height());
+    let new_blocks = best_chain.get(start as usize..).unwrap_or(&[]);
+
+    (orphaned_blocks, new_blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transition_frontier::test_chain;
+
+    fn block_info(block: &AppliedBlock) -> WatchedAccountBlockInfo {
+        WatchedAccountBlockInfo::new(&block.block)
+    }
+
+    fn account_block(block: &AppliedBlock) -> WatchedAccountBlockState {
+        WatchedAccountBlockState::TransactionsInBlockBody {
+            block: block_info(block),
+            transactions: Vec::new(),
+        }
+    }
+
+    fn account(initial: &AppliedBlock) -> WatchedAccountState {
+        WatchedAccountState {
+            initial_state: WatchedAccountLedgerInitialState::Success {
+                time: redux::Timestamp::ZERO,
+                block: block_info(initial),
+                data: None,
+            },
+            blocks: Default::default(),
+            best_tip: None,
+        }
+    }
+
+    fn heights(blocks: &[AppliedBlock]) -> Vec<u32> {
+        blocks.iter().map(|b| b.height()).collect()
+    }
+
+    #[test]
+    fn best_chain_diff_new_blocks() {
+        let chain = test_chain(None, 20, 0).split_off(9);
+        let mut account = account(&chain[2]);
+
+        let (orphaned, new_blocks) = account_best_chain_diff(&account, &chain);
+        assert!(orphaned.is_empty());
+        assert_eq!(heights(new_blocks), (13..=20).collect::<Vec<_>>());
+
+        account.best_tip = Some(block_info(&chain[8]));
+        let (_, new_blocks) = account_best_chain_diff(&account, &chain);
+        assert_eq!(heights(new_blocks), vec![19, 20]);
+
+        account.best_tip = Some(block_info(&chain[10]));
+        let (_, new_blocks) = account_best_chain_diff(&account, &chain);
+        assert!(new_blocks.is_empty());
+    }
+
+    #[test]
+    fn best_chain_diff_not_initialized() {
+        let chain = test_chain(None, 20, 0).split_off(9);
+        let mut account = account(&chain[2]);
+        account.initial_state = WatchedAccountLedgerInitialState::Idle {
+            time: redux::Timestamp::ZERO,
+        };
+
+        let (orphaned, new_blocks) = account_best_chain_diff(&account, &chain);
+        assert!(orphaned.is_empty());
+        assert!(new_blocks.is_empty());
+    }
+
+    #[test]
+    fn best_chain_diff_reorg() {
+        let old_chain = test_chain(None, 20, 0).split_off(9);
+        let fork = test_chain(Some(&old_chain[5]), 6, 1);
+        let new_chain = old_chain[..6]
+            .iter()
+            .chain(&fork)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut account = account(&old_chain[2]);
+        account.block_push(account_block(&old_chain[4]));
+        account.block_push(account_block(&old_chain[7]));
+        account.best_tip = Some(block_info(&old_chain[10]));
+
+        let (orphaned, new_blocks) = account_best_chain_diff(&account, &new_chain);
+        assert_eq!(orphaned, vec![old_chain[7].hash().clone()]);
+        // Looked for after the newest account's block still in the chain.
+        assert_eq!(heights(new_blocks), (15..=21).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn best_chain_diff_ignores_blocks_below_root() {
+        let chain = test_chain(None, 20, 0).split_off(9);
+        let other_chain = test_chain(None, 9, 1).split_off(4);
+        let mut account = account(&other_chain[0]);
+        account.block_push(account_block(&other_chain[2]));
+
+        let (orphaned, new_blocks) = account_best_chain_diff(&account, &chain);
+        assert!(orphaned.is_empty());
+        assert_eq!(heights(new_blocks), (10..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn block_push_drops_oldest() {
+        let chain = test_chain(None, 3, 0);
+        let mut account = account(&chain[0]);
+        for _ in 0..WATCHED_ACCOUNT_MAX_BLOCKS {
+            account.block_push(account_block(&chain[1]));
+        }
+        account.block_push(account_block(&chain[2]));
+
+        assert_eq!(account.blocks.len(), WATCHED_ACCOUNT_MAX_BLOCKS);
+        assert_eq!(account.blocks.back().unwrap().block().level, 3);
+    }
+}
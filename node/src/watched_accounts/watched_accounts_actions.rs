@@ -1,12 +1,8 @@
-use std::sync::Arc;
-
-use mina_p2p_messages::v2::{
-    MinaBaseAccountBinableArgStableV2, MinaBlockBlockStableV2, NonZeroCurvePoint, StateHash,
-};
-use openmina_core::block::BlockWithHash;
+use mina_p2p_messages::v2::{MinaBaseAccountBinableArgStableV2, NonZeroCurvePoint, StateHash};
+use openmina_core::block::ArcBlockWithHash;
 use serde::{Deserialize, Serialize};
 
-use crate::p2p::PeerId;
+use crate::ledger::read::LedgerReadRequest;
 
 use super::{
     WatchedAccountBlockInfo, WatchedAccountBlockState, WatchedAccountLedgerInitialState,
@@ -21,13 +17,15 @@ pub enum WatchedAccountsAction {
     Add {
         pub_key: NonZeroCurvePoint,
     },
+    Remove {
+        pub_key: NonZeroCurvePoint,
+    },
     LedgerInitialStateGetInit {
         pub_key: NonZeroCurvePoint,
     },
     LedgerInitialStateGetPending {
         pub_key: NonZeroCurvePoint,
         block: WatchedAccountBlockInfo,
-        peer_id: PeerId,
     },
     LedgerInitialStateGetError {
         pub_key: NonZeroCurvePoint,
@@ -42,7 +40,15 @@ pub enum WatchedAccountsAction {
     },
     TransactionsIncludedInBlock {
         pub_key: NonZeroCurvePoint,
-        block: BlockWithHash<Arc<MinaBlockBlockStableV2>>,
+        block: ArcBlockWithHash,
+    },
+    /// Best chain was looked at up to `best_tip` for the account's
+    /// transactions.
+    BestChainUpdate {
+        pub_key: NonZeroCurvePoint,
+        /// Account's blocks which are no longer part of the best chain.
+        orphaned_blocks: Vec<StateHash>,
+        best_tip: WatchedAccountBlockInfo,
     },
     BlockLedgerQueryInit {
         pub_key: NonZeroCurvePoint,
@@ -51,28 +57,26 @@ pub enum WatchedAccountsAction {
     BlockLedgerQueryPending {
         pub_key: NonZeroCurvePoint,
         block_hash: StateHash,
-        peer_id: PeerId,
     },
     BlockLedgerQuerySuccess {
         pub_key: NonZeroCurvePoint,
         block_hash: StateHash,
-        ledger_account: Box<MinaBaseAccountBinableArgStableV2>,
+        ledger_account: Option<Box<MinaBaseAccountBinableArgStableV2>>,
     },
 }
 
 fn should_request_ledger_initial_state(state: &crate::State, pub_key: &NonZeroCurvePoint) -> bool {
+    let Some(best_tip) = state.transition_frontier.best_tip() else {
+        return false;
+    };
     state
         .watched_accounts
         .get(pub_key)
-        .filter(|_| state.consensus.best_tip.is_some())
         .map_or(false, |a| match &a.initial_state {
             WatchedAccountLedgerInitialState::Idle { .. } => true,
             WatchedAccountLedgerInitialState::Error { .. } => true,
             WatchedAccountLedgerInitialState::Pending { block, .. } => {
-                let Some(best_tip) = state.consensus.best_tip() else {
-                    return false;
-                };
-                &block.hash != best_tip.hash
+                &block.hash != best_tip.hash()
             }
             // TODO(binier)
             WatchedAccountLedgerInitialState::Success { .. } => false,
@@ -83,10 +87,22 @@ fn should_request_ledger_initial_state(state: &crate::State, pub_key: &NonZeroCu
         })
 }
 
+/// Whether the account is being read from the block's staged ledger.
+fn is_ledger_read_pending(
+    state: &crate::State,
+    block: &WatchedAccountBlockInfo,
+    pub_key: &NonZeroCurvePoint,
+) -> bool {
+    let request =
+        LedgerReadRequest::WatchedAccountGet(block.staged_ledger_hash.clone(), pub_key.clone());
+    state.ledger.read.has_same_request(&request)
+}
+
 impl redux::EnablingCondition<crate::State> for WatchedAccountsAction {
     fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
         match self {
             WatchedAccountsAction::Add { pub_key } => state.watched_accounts.get(pub_key).is_none(),
+            WatchedAccountsAction::Remove { pub_key } => state.watched_accounts.contains(pub_key),
             WatchedAccountsAction::LedgerInitialStateGetInit { pub_key } => {
                 should_request_ledger_initial_state(state, pub_key)
             }
@@ -108,6 +124,9 @@ impl redux::EnablingCondition<crate::State> for WatchedAccountsAction {
                     WatchedAccountLedgerInitialState::Error { time: t, .. } => {
                         time.checked_sub(*t).map_or(false, |d| d.as_secs() >= 3)
                     }
+                    WatchedAccountLedgerInitialState::Pending { block, .. } => {
+                        !is_ledger_read_pending(state, block, pub_key)
+                    }
                     _ => false,
                 }),
             WatchedAccountsAction::LedgerInitialStateGetSuccess { pub_key, .. } => {
@@ -120,12 +139,12 @@ impl redux::EnablingCondition<crate::State> for WatchedAccountsAction {
             }
             WatchedAccountsAction::TransactionsIncludedInBlock { pub_key, block } => {
                 let diff = &block.body().staged_ledger_diff.diff;
-                state
-                    .watched_accounts
-                    .get(pub_key)
-                    .map_or(false, |v| v.initial_state.is_success())
-                    && super::account_relevant_transactions_in_diff_iter(pub_key, diff)
-                        .any(|_| true)
+                state.watched_accounts.get(pub_key).map_or(false, |v| {
+                    v.initial_state.is_success() && v.block_find_by_hash(&block.hash).is_none()
+                }) && super::account_relevant_transactions_in_diff_iter(pub_key, diff).any(|_| true)
+            }
+            WatchedAccountsAction::BestChainUpdate { pub_key, .. } => {
+                state.watched_accounts.contains(pub_key)
             }
             WatchedAccountsAction::BlockLedgerQueryInit {
                 pub_key,
@@ -134,11 +153,15 @@ impl redux::EnablingCondition<crate::State> for WatchedAccountsAction {
                 let Some(acc) = state.watched_accounts.get(pub_key) else {
                     return false;
                 };
+                // Query is retried if it didn't get to the ledger read state.
                 acc.block_find_by_hash(block_hash)
-                    .filter(|b| {
-                        matches!(b, WatchedAccountBlockState::TransactionsInBlockBody { .. })
+                    .map_or(false, |b| match b {
+                        WatchedAccountBlockState::TransactionsInBlockBody { .. } => true,
+                        WatchedAccountBlockState::LedgerAccountGetPending { block, .. } => {
+                            !is_ledger_read_pending(state, block, pub_key)
+                        }
+                        WatchedAccountBlockState::LedgerAccountGetSuccess { .. } => false,
                     })
-                    .is_some()
             }
             WatchedAccountsAction::BlockLedgerQueryPending {
                 pub_key,
@@ -149,23 +172,11 @@ impl redux::EnablingCondition<crate::State> for WatchedAccountsAction {
                     return false;
                 };
 
-                let should_req_for_block = acc
-                    .block_find_by_hash(block_hash)
+                acc.block_find_by_hash(block_hash)
                     .filter(|b| {
                         matches!(b, WatchedAccountBlockState::TransactionsInBlockBody { .. })
                     })
-                    .is_some();
-
-                // TODO(binier)
-                let p2p_rpc_is_pending = false;
-                // let p2p_rpc_is_pending = None
-                //     .or_else(|| {
-                //         let peer = state.p2p.get_ready_peer(&self.peer_id)?;
-                //         peer.rpc.outgoing.get(self.p2p_rpc_id)
-                //     })
-                //     .map_or(false, |v| v.is_init() || v.is_pending());
-
-                should_req_for_block && p2p_rpc_is_pending
+                    .is_some()
             }
             WatchedAccountsAction::BlockLedgerQuerySuccess {
                 pub_key,
@@ -0,0 +1,80 @@
+use crate::rpc::{RpcSubscriptionEvent, RpcWatchedAccountChange};
+use crate::{Service, Store};
+
+use super::{WatchedAccountBlockState, WatchedAccountsAction, WatchedAccountsActionWithMeta};
+
+/// Publishes the change of the watched account to the rpc subscribers
+/// whenever its transactions or its state in the ledger change.
+pub fn watched_accounts_effects<S: Service>(
+    store: &mut Store<S>,
+    action: WatchedAccountsActionWithMeta,
+) {
+    let (action, _meta) = action.split();
+
+    let (pub_key, block_hash, orphaned_blocks) = match action {
+        WatchedAccountsAction::LedgerInitialStateGetSuccess { pub_key, .. } => {
+            (pub_key, None, Vec::new())
+        }
+        WatchedAccountsAction::TransactionsIncludedInBlock { pub_key, block } => {
+            (pub_key, Some(block.hash), Vec::new())
+        }
+        WatchedAccountsAction::BlockLedgerQuerySuccess {
+            pub_key,
+            block_hash,
+            ..
+        } => (pub_key, Some(block_hash), Vec::new()),
+        WatchedAccountsAction::BestChainUpdate {
+            pub_key,
+            orphaned_blocks,
+            ..
+        } if !orphaned_blocks.is_empty() => (pub_key, None, orphaned_blocks),
+        _ => return,
+    };
+    let Some(account) = store.state().watched_accounts.get(&pub_key) else {
+        return;
+    };
+    let change = RpcWatchedAccountChange {
+        account: account.latest_ledger_account().cloned(),
+        block: block_hash
+            .and_then(|hash| account.block_find_by_hash(&hash))
+            .cloned(),
+        orphaned_blocks,
+        public_key: pub_key.into(),
+    };
+    store
+        .service
+        .publish_subscription_event(RpcSubscriptionEvent::WatchedAccountChanged(Box::new(
+            change,
+        )));
+}
+
+/// Retries ledger reads of the watched accounts which were lost, e.g.
+/// because the ledger read limit was reached when they were requested.
+pub fn watched_accounts_ledger_reads_retry<S: Service>(store: &mut Store<S>) {
+    let accounts = store
+        .state()
+        .watched_accounts
+        .iter()
+        .map(|(pub_key, account)| {
+            let pending_blocks = account
+                .blocks
+                .iter()
+                .filter(|b| matches!(b, WatchedAccountBlockState::LedgerAccountGetPending { .. }))
+                .map(|b| b.block().hash.clone())
+                .collect::<Vec<_>>();
+            (pub_key.clone(), pending_blocks)
+        })
+        .collect::<Vec<_>>();
+
+    for (pub_key, pending_blocks) in accounts {
+        store.dispatch(WatchedAccountsAction::LedgerInitialStateGetRetry {
+            pub_key: pub_key.clone(),
+        });
+        for block_hash in pending_blocks {
+            store.dispatch(WatchedAccountsAction::BlockLedgerQueryInit {
+                pub_key: pub_key.clone(),
+                block_hash,
+            });
+        }
+    }
+}
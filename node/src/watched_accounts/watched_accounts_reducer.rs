@@ -1,3 +1,5 @@
+use crate::ledger::read::{LedgerReadAction, LedgerReadRequest};
+
 use super::{
     account_relevant_transactions_in_diff_iter, WatchedAccountBlockInfo, WatchedAccountBlockState,
     WatchedAccountLedgerInitialState, WatchedAccountState, WatchedAccountsAction,
@@ -22,6 +24,7 @@ impl WatchedAccountsState {
                     WatchedAccountState {
                         initial_state: WatchedAccountLedgerInitialState::Idle { time: meta.time() },
                         blocks: Default::default(),
+                        best_tip: None,
                     },
                 );
 
@@ -30,56 +33,28 @@ impl WatchedAccountsState {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(WatchedAccountsAction::LedgerInitialStateGetInit { pub_key });
             }
-            WatchedAccountsAction::LedgerInitialStateGetInit { pub_key: _ }
-            | WatchedAccountsAction::LedgerInitialStateGetRetry { pub_key: _ } => {
-                // TODO(binier)
-                // let Some((peer_id, p2p_rpc_id)) = store.state().p2p.get_free_peer_id_for_rpc() else { return };
-                // let block = {
-                //     let Some(block) = store.state().consensus.best_tip() else { return };
-                //     WatchedAccountBlockInfo {
-                //         level: block.height() as u32,
-                //         hash: block.hash.clone(),
-                //         pred_hash: block.header.protocol_state.previous_state_hash.clone(),
-                //         staged_ledger_hash: block
-                //             .header
-                //             .protocol_state
-                //             .body
-                //             .blockchain_state
-                //             .staged_ledger_hash
-                //             .non_snark
-                //             .ledger_hash
-                //             .clone(),
-                //     }
-                // };
-
-                // let token_id = MinaBaseAccountIdDigestStableV1(BigInt::one());
+            WatchedAccountsAction::Remove { pub_key } => {
+                state.remove(pub_key);
+            }
+            WatchedAccountsAction::LedgerInitialStateGetInit { pub_key }
+            | WatchedAccountsAction::LedgerInitialStateGetRetry { pub_key } => {
+                let pub_key = pub_key.clone();
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let Some(best_tip) = global_state.transition_frontier.best_tip() else {
+                    return;
+                };
+                let block = WatchedAccountBlockInfo::new(best_tip);
 
-                // dispatcher.push(P2pRpcOutgoingInitAction {
-                //     peer_id: peer_id.clone(),
-                //     rpc_id: p2p_rpc_id,
-                //     request: P2pRpcRequest::LedgerQuery((
-                //         block.staged_ledger_hash.0.clone(),
-                //         MinaLedgerSyncLedgerQueryStableV1::WhatAccountWithPath(
-                //             pub_key.clone(),
-                //             token_id.into(),
-                //         ),
-                //     )),
-                //     requestor: P2pRpcRequestor::WatchedAccount(
-                //         P2pRpcRequestorWatchedAccount::LedgerInitialGet(pub_key.clone()),
-                //     ),
-                // });
-                // dispatcher.push(WatchedAccountsLedgerInitialStateGetPendingAction {
-                //     pub_key,
-                //     block,
-                //     peer_id,
-                //     p2p_rpc_id,
-                // });
+                dispatcher.push(LedgerReadAction::Init {
+                    request: LedgerReadRequest::WatchedAccountGet(
+                        block.staged_ledger_hash.clone(),
+                        pub_key.clone(),
+                    ),
+                });
+                dispatcher
+                    .push(WatchedAccountsAction::LedgerInitialStateGetPending { pub_key, block });
             }
-            WatchedAccountsAction::LedgerInitialStateGetPending {
-                pub_key,
-                block,
-                peer_id,
-            } => {
+            WatchedAccountsAction::LedgerInitialStateGetPending { pub_key, block } => {
                 let Some(account) = state.get_mut(pub_key) else {
                     return;
                 };
@@ -88,21 +63,15 @@ impl WatchedAccountsState {
                 account.initial_state = WatchedAccountLedgerInitialState::Pending {
                     time: meta.time(),
                     block: block.clone(),
-                    peer_id: *peer_id,
                 };
             }
             WatchedAccountsAction::LedgerInitialStateGetError { pub_key, error } => {
                 let Some(account) = state.get_mut(pub_key) else {
                     return;
                 };
-                let peer_id = match &account.initial_state {
-                    WatchedAccountLedgerInitialState::Pending { peer_id, .. } => *peer_id,
-                    _ => return,
-                };
                 account.initial_state = WatchedAccountLedgerInitialState::Error {
                     time: meta.time(),
                     error: error.clone(),
-                    peer_id,
                 };
             }
             WatchedAccountsAction::LedgerInitialStateGetSuccess { pub_key, data } => {
@@ -128,39 +97,10 @@ impl WatchedAccountsState {
                 let Some(account) = state.get_mut(pub_key) else {
                     return;
                 };
-                account
-                    .blocks
-                    .push_back(WatchedAccountBlockState::TransactionsInBlockBody {
-                        block: WatchedAccountBlockInfo {
-                            level: block
-                                .block
-                                .header
-                                .protocol_state
-                                .body
-                                .consensus_state
-                                .blockchain_length
-                                .0
-                                 .0,
-                            hash: block.hash.clone(),
-                            pred_hash: block
-                                .block
-                                .header
-                                .protocol_state
-                                .previous_state_hash
-                                .clone(),
-                            staged_ledger_hash: block
-                                .block
-                                .header
-                                .protocol_state
-                                .body
-                                .blockchain_state
-                                .staged_ledger_hash
-                                .non_snark
-                                .ledger_hash
-                                .clone(),
-                        },
-                        transactions,
-                    });
+                account.block_push(WatchedAccountBlockState::TransactionsInBlockBody {
+                    block: WatchedAccountBlockInfo::new(block),
+                    transactions,
+                });
 
                 let pub_key = pub_key.clone();
                 let block_hash = block.hash.clone();
@@ -170,39 +110,41 @@ impl WatchedAccountsState {
                     block_hash,
                 });
             }
-            WatchedAccountsAction::BlockLedgerQueryInit { .. } => {
-                // TODO(binier)
-                // let Some((peer_id, p2p_rpc_id)) = store.state().p2p.get_free_peer_id_for_rpc() else { return };
-                // let ledger_hash = {
-                //     let Some(acc) = store.state().watched_accounts.get(&action.pub_key) else { return };
-                //     let Some(block) = acc.block_find_by_hash(&action.block_hash) else { return };
-                //     block.block().staged_ledger_hash.0.clone()
-                // };
-                // let token_id = MinaBaseAccountIdDigestStableV1(BigInt::one());
+            WatchedAccountsAction::BestChainUpdate {
+                pub_key,
+                orphaned_blocks,
+                best_tip,
+            } => {
+                let Some(account) = state.get_mut(pub_key) else {
+                    return;
+                };
+                account
+                    .blocks
+                    .retain(|b| !orphaned_blocks.contains(&b.block().hash));
+                account.best_tip = Some(best_tip.clone());
+            }
+            WatchedAccountsAction::BlockLedgerQueryInit {
+                pub_key,
+                block_hash,
+            } => {
+                let Some(ledger_hash) = state
+                    .get(pub_key)
+                    .and_then(|account| account.block_find_by_hash(block_hash))
+                    .map(|block| block.block().staged_ledger_hash.clone())
+                else {
+                    return;
+                };
 
-                // store.dispatch(P2pRpcOutgoingInitAction {
-                //     peer_id: peer_id.clone(),
-                //     rpc_id: p2p_rpc_id,
-                //     request: P2pRpcRequest::LedgerQuery((
-                //         ledger_hash,
-                //         MinaLedgerSyncLedgerQueryStableV1::WhatAccountWithPath(
-                //             action.pub_key.clone(),
-                //             token_id.into(),
-                //         ),
-                //     )),
-                //     requestor: P2pRpcRequestor::WatchedAccount(
-                //         P2pRpcRequestorWatchedAccount::BlockLedgerGet(
-                //             action.pub_key.clone(),
-                //             action.block_hash.clone(),
-                //         ),
-                //     ),
-                // });
-                // store.dispatch(WatchedAccountsBlockLedgerQueryPendingAction {
-                //     pub_key: action.pub_key,
-                //     block_hash: action.block_hash,
-                //     peer_id,
-                //     p2p_rpc_id,
-                // });
+                let pub_key = pub_key.clone();
+                let block_hash = block_hash.clone();
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(LedgerReadAction::Init {
+                    request: LedgerReadRequest::WatchedAccountGet(ledger_hash, pub_key.clone()),
+                });
+                dispatcher.push(WatchedAccountsAction::BlockLedgerQueryPending {
+                    pub_key,
+                    block_hash,
+                });
             }
             WatchedAccountsAction::BlockLedgerQueryPending {
                 pub_key,
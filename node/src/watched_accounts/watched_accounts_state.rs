@@ -4,9 +4,12 @@ use mina_p2p_messages::v2::{
     LedgerHash, MinaBaseAccountBinableArgStableV2, MinaBaseTransactionStatusStableV2,
     MinaBaseUserCommandStableV2, NonZeroCurvePoint, StateHash, TransactionHash,
 };
+use openmina_core::block::ArcBlockWithHash;
 use serde::{Deserialize, Serialize};
 
-use crate::p2p::PeerId;
+/// Maximum number of blocks with transactions kept for each watched
+/// account. Oldest blocks are dropped first.
+pub const WATCHED_ACCOUNT_MAX_BLOCKS: usize = 1024;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WatchedAccountBlockInfo {
@@ -16,6 +19,17 @@ pub struct WatchedAccountBlockInfo {
     pub staged_ledger_hash: LedgerHash,
 }
 
+impl WatchedAccountBlockInfo {
+    pub fn new(block: &ArcBlockWithHash) -> Self {
+        Self {
+            level: block.height(),
+            hash: block.hash().clone(),
+            pred_hash: block.pred_hash().clone(),
+            staged_ledger_hash: block.merkle_root_hash().clone(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Transaction {
     pub hash: Option<TransactionHash>,
@@ -25,9 +39,8 @@ pub struct Transaction {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum WatchedAccountsLedgerInitialStateGetError {
-    PeerDisconnected,
-    // TransportError(P2pRpcOutgoingError),
-    P2pRpcError(mina_p2p_messages::core::Info),
+    /// Reading the account from the best tip staged ledger failed.
+    LedgerReadError(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -39,12 +52,10 @@ pub enum WatchedAccountLedgerInitialState {
     Pending {
         time: redux::Timestamp,
         block: WatchedAccountBlockInfo,
-        peer_id: PeerId,
     },
     Error {
         time: redux::Timestamp,
         error: WatchedAccountsLedgerInitialStateGetError,
-        peer_id: PeerId,
     },
     Success {
         time: redux::Timestamp,
@@ -93,7 +104,9 @@ pub enum WatchedAccountBlockState {
         block: WatchedAccountBlockInfo,
         /// Transactions included in the block ordered by nonce from low to high.
         transactions: Vec<Transaction>,
-        ledger_account: Box<MinaBaseAccountBinableArgStableV2>,
+        /// `None` if the account doesn't exist in the ledger, e.g. when the
+        /// transaction creating it has failed.
+        ledger_account: Option<Box<MinaBaseAccountBinableArgStableV2>>,
     },
 }
 
@@ -118,7 +131,9 @@ impl WatchedAccountBlockState {
         match self {
             Self::TransactionsInBlockBody { .. } => None,
             Self::LedgerAccountGetPending { .. } => None,
-            Self::LedgerAccountGetSuccess { ledger_account, .. } => Some(ledger_account),
+            Self::LedgerAccountGetSuccess { ledger_account, .. } => {
+                ledger_account.as_ref().map(AsRef::as_ref)
+            }
         }
     }
 }
@@ -127,8 +142,13 @@ impl WatchedAccountBlockState {
 pub struct WatchedAccountState {
     pub initial_state: WatchedAccountLedgerInitialState,
 
-    /// Blocks in which account updates has happened.
+    /// Blocks in which account updates has happened, at most
+    /// [`WATCHED_ACCOUNT_MAX_BLOCKS`] of them.
     pub blocks: VecDeque<WatchedAccountBlockState>,
+    /// Latest best tip up to which the best chain was looked at for the
+    /// account's transactions.
+    #[serde(default)]
+    pub best_tip: Option<WatchedAccountBlockInfo>,
     // /// Pending transactions which haven't been included in any blocks.
     // pub pending_transactions: BTreeMap<txhash, tx>,
 }
@@ -138,6 +158,24 @@ impl WatchedAccountState {
         self.blocks.iter().rev().find(|b| &b.block().hash == hash)
     }
 
+    /// Account data in the latest block for which it was fetched from
+    /// the ledger, or the initial state if there is no such block.
+    pub fn latest_ledger_account(&self) -> Option<&MinaBaseAccountBinableArgStableV2> {
+        self.blocks
+            .iter()
+            .rev()
+            .find(|b| matches!(b, WatchedAccountBlockState::LedgerAccountGetSuccess { .. }))
+            .map_or_else(|| self.initial_state.data(), |b| b.ledger_account())
+    }
+
+    /// Adds the block, dropping the oldest one if there are too many.
+    pub fn block_push(&mut self, block: WatchedAccountBlockState) {
+        if self.blocks.len() >= WATCHED_ACCOUNT_MAX_BLOCKS {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(block);
+    }
+
     pub fn block_find_by_hash_mut(
         &mut self,
         hash: &StateHash,
@@ -175,6 +213,10 @@ impl WatchedAccountsState {
         self.list.insert(key, value);
     }
 
+    pub fn remove(&mut self, key: &NonZeroCurvePoint) -> Option<WatchedAccountState> {
+        self.list.remove(key)
+    }
+
     pub fn iter(
         &self,
     ) -> impl '_ + Iterator<Item = (&'_ NonZeroCurvePoint, &'_ WatchedAccountState)> {
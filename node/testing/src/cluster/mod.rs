@@ -270,6 +270,7 @@ impl Cluster {
                 work_verifier_index: self.work_verifier_index.clone(),
                 work_verifier_srs: self.verifier_srs.clone(),
             },
+            snark_pool: Default::default(),
             global: GlobalConfig {
                 build: BuildEnv::get().into(),
                 snarker: testing_config.snark_worker,
@@ -282,7 +283,7 @@ impl Cluster {
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
                 ask_initial_peers_interval: testing_config.ask_initial_peers_interval,
-                enabled_channels: ChannelId::iter_all().collect(),
+                enabled_channels: ChannelId::iter_default().collect(),
                 peer_discovery: true,
                 timeouts: testing_config.timeouts,
                 limits: P2pLimits::default().with_max_peers(Some(testing_config.max_peers)),
@@ -292,7 +293,9 @@ impl Cluster {
                         .checked_sub(redux::Timestamp::ZERO)
                         .unwrap_or_default(),
                     ..Default::default()
-                },
+                }
+                .into(),
+                address_book: Vec::new(),
             },
             transition_frontier: TransitionFrontierConfig::new(testing_config.genesis),
             block_producer: block_producer_config,
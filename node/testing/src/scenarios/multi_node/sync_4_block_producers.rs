@@ -34,6 +34,7 @@ impl MultiNodeSync4BlockProducers {
                         pub_key: sec_key.public_key().into(),
                         custom_coinbase_receiver: None,
                         proposed_protocol_version: None,
+                        additional_keys: Vec::new(),
                     },
                     sec_key,
                 }),
@@ -45,6 +45,7 @@ impl MultiNodeVrfGetCorrectLedgers {
                     pub_key: sec_key.public_key().into(),
                     custom_coinbase_receiver: None,
                     proposed_protocol_version: None,
+                    additional_keys: Vec::new(),
                 },
                 sec_key,
             }),
@@ -67,6 +67,7 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
                     pub_key: sec_key.public_key().into(),
                     custom_coinbase_receiver: None,
                     proposed_protocol_version: None,
+                    additional_keys: Vec::new(),
                 },
                 sec_key: sec_key.clone(),
             }),
@@ -81,6 +82,7 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                pricing: Default::default(),
             }),
             ..rust_config
         });
@@ -140,7 +142,8 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
             let (_, balance) = pending_evaluation
                 .epoch_data
                 .delegator_table
-                .get(&AccountIndex(1))
+                .values()
+                .find_map(|table| table.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("Initial balance: {balance}");
             *balance
@@ -236,7 +239,8 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
             let (_, balance) = pending_evaluation
                 .epoch_data
                 .delegator_table
-                .get(&AccountIndex(1))
+                .values()
+                .find_map(|table| table.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("New balance: {balance}");
             *balance
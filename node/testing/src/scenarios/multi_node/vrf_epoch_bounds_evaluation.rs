@@ -53,6 +53,7 @@ impl MultiNodeVrfEpochBoundsEvaluation {
                     pub_key: sec_key.public_key().into(),
                     custom_coinbase_receiver: None,
                     proposed_protocol_version: None,
+                    additional_keys: Vec::new(),
                 },
                 sec_key: sec_key.clone(),
             }),
@@ -67,6 +68,7 @@ impl MultiNodeVrfEpochBoundsEvaluation {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                pricing: Default::default(),
             }),
             ..rust_config
         });
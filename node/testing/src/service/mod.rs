@@ -35,7 +35,7 @@ use node::snark::user_command_verify::SnarkUserCommandVerifyId;
 use node::snark::user_command_verify_effectful::SnarkUserCommandVerifyService;
 use node::snark::work_verify::{SnarkWorkVerifyId, SnarkWorkVerifyService};
 use node::snark::{BlockVerifier, SnarkEvent, TransactionVerifier, VerifierSRS};
-use node::snark_pool::SnarkPoolService;
+use node::snark_pool::{SnarkPoolService, SnarkPoolState};
 use node::stats::Stats;
 use node::transition_frontier::genesis::GenesisConfig;
 use node::{
@@ -132,7 +132,7 @@ pub struct NodeTestingService {
     //pending_events: PendingRequests<PendingEventIdType, Event>,
     dyn_effects: Option<DynEffects>,
 
-    snarker_sok_digest: Option<ByteString>,
+    snarker_sok_message: Option<SokMessage>,
     /// Once dropped, it will cause all threads associated to shutdown.
     _shutdown: mpsc::Receiver<()>,
 }
@@ -148,7 +148,7 @@ impl NodeTestingService {
             monotonic_time: Instant::now(),
             pending_events: PendingEvents::new(),
             dyn_effects: None,
-            snarker_sok_digest: None,
+            snarker_sok_message: None,
             _shutdown,
         }
     }
@@ -200,8 +200,8 @@ impl NodeTestingService {
         self.dyn_effects.take()
     }
 
-    pub fn set_snarker_sok_digest(&mut self, digest: ByteString) {
-        self.snarker_sok_digest = Some(digest);
+    pub fn set_snarker_sok_message(&mut self, message: SokMessage) {
+        self.snarker_sok_message = Some(message);
     }
 
     pub fn pending_events(&mut self, poll: bool) -> impl Iterator<Item = (PendingEventId, &Event)> {
@@ -446,6 +446,14 @@ impl SnarkPoolService for NodeTestingService {
     ) -> Vec<SnarkJobId> {
         self.real.random_choose(iter, n)
     }
+
+    fn snark_pool_save(&mut self, pool: &SnarkPoolState) {
+        self.real.snark_pool_save(pool)
+    }
+
+    fn snark_pool_restore(&mut self) {
+        self.real.snark_pool_restore()
+    }
 }
 
 impl BlockProducerVrfEvaluatorService for NodeTestingService {
@@ -464,12 +472,22 @@ impl BlockProducerService for NodeTestingService {
         self.real.provers()
     }
 
-    fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>) {
+    fn prove(
+        &mut self,
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+        _dry_run: bool,
+    ) {
         fn dummy_proof_event(block_hash: StateHash) -> Event {
             let dummy_proof = (*ledger::dummy::dummy_blockchain_proof()).clone();
             BlockProducerEvent::BlockProve(block_hash, Ok(dummy_proof.into())).into()
         }
-        let keypair = self.real.block_producer().unwrap().keypair();
+        let keypair = self
+            .real
+            .block_producer()
+            .unwrap()
+            .keypair_for_prove(&input)
+            .expect("block creator must be one of our producer keys");
 
         match self.proof_kind() {
             ProofKind::Dummy => {
@@ -540,7 +558,7 @@ impl ExternalSnarkWorkerService for NodeTestingService {
                 node::external_snark_worker::ExternalSnarkWorkerError::Error(format!("{:?}", e))
             })?,
         );
-        self.set_snarker_sok_digest((&sok_message.digest()).into());
+        self.set_snarker_sok_message(sok_message);
         let _ = self
             .real
             .event_sender()
@@ -552,8 +570,13 @@ impl ExternalSnarkWorkerService for NodeTestingService {
     fn submit(
         &mut self,
         spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        let sok_digest = self.snarker_sok_digest.clone().unwrap();
+        let sok_message = SokMessage {
+            fee: (&fee).into(),
+            ..self.snarker_sok_message.clone().unwrap()
+        };
+        let sok_digest: ByteString = (&sok_message.digest()).into();
         let make_dummy_proof = |spec| {
             let statement = match spec {
                 SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Transition(v, _) => v.0,
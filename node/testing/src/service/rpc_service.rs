@@ -19,6 +19,14 @@ impl RpcService for super::NodeTestingService {
         respond_block_producer_stats_get,
         node::rpc::RpcBlockProducerStatsGetResponse
     );
+    to_real!(
+        respond_block_producer_schedule_get,
+        node::rpc::RpcBlockProducerScheduleGetResponse
+    );
+    to_real!(
+        respond_block_producer_dry_run,
+        node::rpc::RpcBlockProducerDryRunResponse
+    );
 
     to_real!(
         respond_action_stats_get,
@@ -29,6 +37,9 @@ impl RpcService for super::NodeTestingService {
         RpcMessageProgressResponse
     );
     to_real!(respond_peers_get, node::rpc::RpcPeersGetResponse,);
+    to_real!(respond_p2p_bans_get, node::rpc::RpcP2pBansGetResponse,);
+    to_real!(respond_p2p_ban, node::rpc::RpcP2pBanResponse,);
+    to_real!(respond_p2p_unban, node::rpc::RpcP2pUnbanResponse,);
     to_real!(
         respond_p2p_connection_outgoing,
         node::rpc::RpcP2pConnectionOutgoingResponse,
@@ -56,6 +67,7 @@ impl RpcService for super::NodeTestingService {
         respond_snarker_job_spec,
         node::rpc::RpcSnarkerJobSpecResponse,
     );
+    to_real!(respond_snarker_jobs, node::rpc::RpcSnarkerJobsResponse,);
     to_real!(
         respond_snarker_workers,
         node::rpc::RpcSnarkerWorkersResponse,
@@ -89,6 +101,10 @@ impl RpcService for super::NodeTestingService {
         respond_ledger_accounts,
         node::rpc::RpcLedgerAccountsResponse
     );
+    to_real!(
+        respond_ledger_account_proof_get,
+        node::rpc::RpcLedgerAccountProofGetResponse,
+    );
     to_real!(
         respond_transaction_inject,
         node::rpc::RpcTransactionInjectResponse
@@ -97,6 +113,10 @@ impl RpcService for super::NodeTestingService {
         respond_transition_frontier_commands,
         node::rpc::RpcTransitionFrontierUserCommandsResponse,
     );
+    to_real!(
+        respond_transition_frontier_tips,
+        node::rpc::RpcTransitionFrontierTipsGetResponse,
+    );
     to_real!(respond_best_chain, node::rpc::RpcBestChainResponse,);
     to_real!(
         respond_consensus_constants,
@@ -106,4 +126,25 @@ impl RpcService for super::NodeTestingService {
         respond_transaction_status,
         node::rpc::RpcTransactionStatusGetResponse,
     );
+    to_real!(respond_ledger_export, node::rpc::RpcLedgerExportResponse,);
+    to_real!(
+        respond_transaction_simulate,
+        node::rpc::RpcTransactionSimulateResponse,
+    );
+    to_real!(
+        respond_watched_accounts_add,
+        node::rpc::RpcWatchedAccountsAddResponse,
+    );
+    to_real!(
+        respond_watched_accounts_remove,
+        node::rpc::RpcWatchedAccountsRemoveResponse,
+    );
+    to_real!(
+        respond_watched_accounts_get,
+        node::rpc::RpcWatchedAccountsGetResponse,
+    );
+
+    fn publish_subscription_event(&mut self, event: node::rpc::RpcSubscriptionEvent) {
+        self.real.publish_subscription_event(event)
+    }
 }
@@ -153,6 +153,7 @@ impl Simulator {
                     )),
                     strategy: SnarkerStrategy::Sequential,
                     auto_commit: true,
+                    pricing: Default::default(),
                 }),
                 ..node_config.clone()
             };
@@ -192,6 +193,7 @@ impl Simulator {
                         pub_key: sec_key.public_key().into(),
                         custom_coinbase_receiver: None,
                         proposed_protocol_version: None,
+                        additional_keys: Vec::new(),
                     },
                     sec_key,
                 }),
@@ -11,8 +11,8 @@ use node::{
     },
     snark::{get_srs, BlockVerifier, TransactionVerifier, VerifierSRS},
     transition_frontier::genesis::GenesisConfig,
-    BlockProducerConfig, GlobalConfig, LedgerConfig, P2pConfig, SnarkConfig, SnarkerConfig,
-    SnarkerStrategy, TransitionFrontierConfig,
+    BlockProducerConfig, BlockProducerKeyConfig, GlobalConfig, LedgerConfig, P2pConfig,
+    SnarkConfig, SnarkerConfig, SnarkerStrategy, TransitionFrontierConfig,
 };
 use openmina_node_common::{p2p::TaskSpawner, NodeServiceCommonBuilder};
 use rand::Rng;
@@ -118,11 +118,7 @@ impl NodeBuilder {
 
     /// Set up block producer.
     pub fn block_producer(&mut self, provers: BlockProver, key: AccountSecretKey) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
         self.service.block_producer_init(provers, key);
         self
@@ -142,6 +138,25 @@ impl NodeBuilder {
         Ok(self)
     }
 
+    /// Produce blocks with an additional key, on top of the one set up
+    /// with `block_producer`. Coinbase rewards for blocks produced with
+    /// this key go to `custom_coinbase_receiver`, if provided.
+    pub fn additional_block_producer(
+        &mut self,
+        key: AccountSecretKey,
+        custom_coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let bp = self.block_producer.as_mut().ok_or_else(|| {
+            anyhow::anyhow!("can't add block producer key when block producer is not initialized.")
+        })?;
+        bp.add_producer_key(BlockProducerKeyConfig {
+            pub_key: key.public_key().into(),
+            custom_coinbase_receiver,
+        });
+        self.service.block_producer_add_key(key);
+        Ok(self)
+    }
+
     pub fn custom_block_producer_config(
         &mut self,
         config: BlockProducerConfig,
@@ -165,6 +180,7 @@ impl NodeBuilder {
             )),
             strategy,
             auto_commit: true,
+            pricing: Default::default(),
         };
         self.snarker = Some(config);
         self
@@ -231,16 +247,18 @@ impl NodeBuilder {
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
                 ask_initial_peers_interval: Duration::from_secs(3600),
-                enabled_channels: ChannelId::iter_all().collect(),
+                enabled_channels: ChannelId::iter_default().collect(),
                 peer_discovery: !self.p2p_no_discovery,
                 meshsub: P2pMeshsubConfig {
                     initial_time: initial_time
                         .checked_sub(redux::Timestamp::ZERO)
                         .unwrap_or_default(),
                     ..Default::default()
-                },
+                }
+                .into(),
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                address_book: Vec::new(),
             },
             ledger: LedgerConfig {},
             snark: SnarkConfig {
@@ -249,6 +267,7 @@ impl NodeBuilder {
                 work_verifier_index,
                 work_verifier_srs: srs,
             },
+            snark_pool: Default::default(),
             transition_frontier,
             block_producer: self.block_producer,
             tx_pool: ledger::transaction_pool::Config {
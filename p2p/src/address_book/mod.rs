@@ -0,0 +1,2 @@
+mod p2p_address_book_state;
+pub use p2p_address_book_state::*;
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    connection::outgoing::P2pConnectionOutgoingInitOpts, disconnection::P2pDisconnectionReason,
+    PeerId,
+};
+
+/// Maximum number of entries in the address book. Once exceeded, entries
+/// with the lowest quality are dropped.
+pub const P2P_ADDRESS_BOOK_MAX_ENTRIES: usize = 1000;
+/// Weight of the latest sample in the moving average of the RTT.
+const RTT_SMOOTHING: f64 = 0.2;
+/// RTT assumed for peers which we haven't measured yet.
+const DEFAULT_RTT: Duration = Duration::from_secs(1);
+/// Peer which wasn't seen for longer than this is less likely to be online.
+const STALE_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Quality of an entry without any connection attempts, which was never
+/// seen and whose RTT is [`DEFAULT_RTT`].
+const UNKNOWN_PEER_QUALITY: f64 = 0.5 * 0.5 * 0.5;
+
+/// Peers which we have been connected to, along with the observed quality
+/// of the connections. Persisted in the node's work dir, so that the node
+/// can be seeded from it after restart.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pAddressBook {
+    entries: BTreeMap<PeerId, P2pAddressBookEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pAddressBookEntry {
+    pub dial_opts: P2pConnectionOutgoingInitOpts,
+    /// Last time the connection with the peer was established.
+    pub last_seen: Option<Timestamp>,
+    pub successful_connections: u32,
+    pub failed_connections: u32,
+    /// Failed connection attempts since the last successful one.
+    pub consecutive_failures: u32,
+    /// Moving average of the round trip time of small RPC requests.
+    pub rtt: Option<Duration>,
+    pub last_disconnect_reason: Option<String>,
+}
+
+impl P2pAddressBookEntry {
+    pub fn new(dial_opts: P2pConnectionOutgoingInitOpts) -> Self {
+        Self {
+            dial_opts,
+            last_seen: None,
+            successful_connections: 0,
+            failed_connections: 0,
+            consecutive_failures: 0,
+            rtt: None,
+            last_disconnect_reason: None,
+        }
+    }
+
+    pub fn peer_id(&self) -> &PeerId {
+        self.dial_opts.peer_id()
+    }
+
+    /// Quality of the peer in range `(0, 1]`, higher is better. Combines
+    /// the ratio of successful connection attempts, recent failures, RTT
+    /// and how long ago the peer was seen.
+    pub fn quality(&self, now: Timestamp) -> f64 {
+        let attempts = self.successful_connections as f64 + self.failed_connections as f64;
+        let success_ratio = (self.successful_connections as f64 + 1.0) / (attempts + 2.0);
+        let recent_failures = 1.0 / (1.0 + self.consecutive_failures as f64);
+        let rtt = 1.0 / (1.0 + self.rtt.unwrap_or(DEFAULT_RTT).as_secs_f64());
+        let recency = match self.last_seen.and_then(|t| now.checked_sub(t)) {
+            Some(elapsed) if elapsed < STALE_AFTER => 1.0,
+            _ => 0.5,
+        };
+        success_ratio * recent_failures * rtt * recency
+    }
+}
+
+impl P2pAddressBook {
+    pub fn new(entries: impl IntoIterator<Item = P2pAddressBookEntry>) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|entry| (*entry.peer_id(), entry))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&P2pAddressBookEntry> {
+        self.entries.get(peer_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &P2pAddressBookEntry> {
+        self.entries.values()
+    }
+
+    /// Quality of the peer, see [`P2pAddressBookEntry::quality`]. Peers
+    /// not in the address book get the quality of a fresh entry.
+    pub fn quality(&self, peer_id: &PeerId, now: Timestamp) -> f64 {
+        self.entries
+            .get(peer_id)
+            .map_or(UNKNOWN_PEER_QUALITY, |entry| entry.quality(now))
+    }
+
+    /// Records successfully established connection with the peer, adding
+    /// it to the address book.
+    pub fn connected(&mut self, dial_opts: P2pConnectionOutgoingInitOpts, now: Timestamp) {
+        let entry = self
+            .entries
+            .entry(*dial_opts.peer_id())
+            .or_insert_with(|| P2pAddressBookEntry::new(dial_opts.clone()));
+        entry.dial_opts = dial_opts;
+        entry.last_seen = Some(now);
+        entry.successful_connections = entry.successful_connections.saturating_add(1);
+        entry.consecutive_failures = 0;
+
+        self.shrink(now);
+    }
+
+    /// Records failed connection attempt, adding the peer to the address
+    /// book if it isn't there yet. Such peers have low quality, so they
+    /// are the first to be dropped once the address book is full.
+    pub fn connection_failed(&mut self, dial_opts: P2pConnectionOutgoingInitOpts, now: Timestamp) {
+        let entry = self
+            .entries
+            .entry(*dial_opts.peer_id())
+            .or_insert_with(|| P2pAddressBookEntry::new(dial_opts));
+        entry.failed_connections = entry.failed_connections.saturating_add(1);
+        entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+
+        self.shrink(now);
+    }
+
+    pub fn disconnected(&mut self, peer_id: &PeerId, reason: &P2pDisconnectionReason) {
+        if let Some(entry) = self.entries.get_mut(peer_id) {
+            entry.last_disconnect_reason = Some(reason.to_string());
+        }
+    }
+
+    pub fn rtt_measured(&mut self, peer_id: &PeerId, rtt: Duration) {
+        if let Some(entry) = self.entries.get_mut(peer_id) {
+            entry.rtt = Some(match entry.rtt {
+                None => rtt,
+                Some(avg) => avg.mul_f64(1.0 - RTT_SMOOTHING) + rtt.mul_f64(RTT_SMOOTHING),
+            });
+        }
+    }
+
+    /// Drops the worst entries so that the address book doesn't exceed
+    /// [`P2P_ADDRESS_BOOK_MAX_ENTRIES`].
+    fn shrink(&mut self, now: Timestamp) {
+        if self.entries.len() <= P2P_ADDRESS_BOOK_MAX_ENTRIES {
+            return;
+        }
+        let mut by_quality = self
+            .entries
+            .iter()
+            .map(|(peer_id, entry)| (entry.quality(now), *peer_id))
+            .collect::<Vec<_>>();
+        by_quality.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        let excess = self.entries.len() - P2P_ADDRESS_BOOK_MAX_ENTRIES;
+        for (_, peer_id) in by_quality.into_iter().take(excess) {
+            self.entries.remove(&peer_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::webrtc::SignalingMethod;
+
+    use super::*;
+
+    fn at(secs: u64) -> Timestamp {
+        Timestamp::new(secs * 1_000_000_000)
+    }
+
+    fn dial_opts(i: u16) -> P2pConnectionOutgoingInitOpts {
+        let mut bytes = [0; 32];
+        bytes[..2].copy_from_slice(&i.to_be_bytes());
+        P2pConnectionOutgoingInitOpts::WebRTC {
+            peer_id: PeerId::from_bytes(bytes),
+            signaling: SignalingMethod::P2p {
+                relay_peer_id: PeerId::from_bytes([0xff; 32]),
+            },
+        }
+    }
+
+    #[test]
+    fn quality() {
+        let now = at(1_000_000);
+        let mut entry = P2pAddressBookEntry::new(dial_opts(1));
+        assert_eq!(entry.quality(now), UNKNOWN_PEER_QUALITY);
+
+        entry.last_seen = Some(now);
+        entry.successful_connections = 3;
+        entry.rtt = Some(Duration::ZERO);
+        let good = entry.quality(now);
+        assert!(good > UNKNOWN_PEER_QUALITY && good <= 1.0);
+
+        // Not seen for a long time.
+        assert!(entry.quality(now + STALE_AFTER.as_nanos() as u64) < good);
+
+        let mut slow = entry.clone();
+        slow.rtt = Some(Duration::from_secs(2));
+        assert!(slow.quality(now) < good);
+
+        let mut failing = entry.clone();
+        failing.failed_connections = 2;
+        failing.consecutive_failures = 2;
+        assert!(failing.quality(now) < good);
+        // Successful connection resets the recent failures.
+        failing.consecutive_failures = 0;
+        assert!(failing.quality(now) < good);
+        assert!(failing.quality(now) > UNKNOWN_PEER_QUALITY);
+    }
+
+    #[test]
+    fn connected_and_failed() {
+        let mut book = P2pAddressBook::default();
+        let peer_id = *dial_opts(1).peer_id();
+        assert_eq!(book.quality(&peer_id, at(0)), UNKNOWN_PEER_QUALITY);
+
+        // Peer which we never connected to is tracked as well.
+        book.connection_failed(dial_opts(1), at(0));
+        let entry = book.get(&peer_id).unwrap();
+        assert_eq!(entry.failed_connections, 1);
+        assert_eq!(entry.consecutive_failures, 1);
+        assert_eq!(entry.last_seen, None);
+        assert!(book.quality(&peer_id, at(0)) < UNKNOWN_PEER_QUALITY);
+
+        book.connected(dial_opts(1), at(10));
+        let entry = book.get(&peer_id).unwrap();
+        assert_eq!(entry.successful_connections, 1);
+        assert_eq!(entry.failed_connections, 1);
+        assert_eq!(entry.consecutive_failures, 0);
+        assert_eq!(entry.last_seen, Some(at(10)));
+    }
+
+    #[test]
+    fn rtt_measured() {
+        let mut book = P2pAddressBook::default();
+        let peer_id = *dial_opts(1).peer_id();
+        // Unknown peers are ignored.
+        book.rtt_measured(&peer_id, Duration::from_millis(100));
+        assert!(book.get(&peer_id).is_none());
+
+        book.connected(dial_opts(1), at(0));
+        book.rtt_measured(&peer_id, Duration::from_millis(100));
+        assert_eq!(
+            book.get(&peer_id).unwrap().rtt,
+            Some(Duration::from_millis(100))
+        );
+
+        book.rtt_measured(&peer_id, Duration::from_millis(600));
+        // Moving average: 0.8 * 100ms + 0.2 * 600ms.
+        let rtt = book.get(&peer_id).unwrap().rtt.unwrap();
+        assert!((rtt.as_secs_f64() - 0.2).abs() < 1e-6, "{rtt:?}");
+    }
+
+    #[test]
+    fn shrink() {
+        let now = at(0);
+        let mut book = P2pAddressBook::default();
+        for i in 0..P2P_ADDRESS_BOOK_MAX_ENTRIES as u16 {
+            book.connected(dial_opts(i), now);
+        }
+        assert_eq!(book.len(), P2P_ADDRESS_BOOK_MAX_ENTRIES);
+
+        // Failing peer has the lowest quality and is dropped first.
+        let failing = P2P_ADDRESS_BOOK_MAX_ENTRIES as u16;
+        book.connection_failed(dial_opts(failing), now);
+        assert_eq!(book.len(), P2P_ADDRESS_BOOK_MAX_ENTRIES);
+        assert!(book.get(dial_opts(failing).peer_id()).is_none());
+
+        book.connection_failed(dial_opts(0), now);
+        book.connected(dial_opts(failing + 1), now);
+        assert_eq!(book.len(), P2P_ADDRESS_BOOK_MAX_ENTRIES);
+        assert!(book.get(dial_opts(0).peer_id()).is_none());
+        assert!(book.get(dial_opts(failing + 1).peer_id()).is_some());
+    }
+}
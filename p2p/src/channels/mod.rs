@@ -2,6 +2,8 @@ pub mod best_tip;
 pub mod best_tip_effectful;
 pub mod rpc;
 pub mod rpc_effectful;
+pub mod signaling_relay;
+pub mod signaling_relay_effectful;
 pub mod snark;
 pub mod snark_effectful;
 pub mod snark_job_commitment;
@@ -30,6 +32,7 @@ use strum_macros::EnumIter;
 
 use self::best_tip::BestTipPropagationChannelMsg;
 use self::rpc::RpcChannelMsg;
+use self::signaling_relay::SignalingRelayChannelMsg;
 use self::snark::SnarkPropagationChannelMsg;
 use self::snark_job_commitment::SnarkJobCommitmentPropagationChannelMsg;
 use self::streaming_rpc::StreamingRpcChannelMsg;
@@ -42,6 +45,7 @@ pub enum ChannelId {
     TransactionPropagation = 3,
     SnarkPropagation = 4,
     SnarkJobCommitmentPropagation = 5,
+    SignalingRelay = 6,
     Rpc = 100,
     StreamingRpc = 101,
 }
@@ -63,6 +67,7 @@ impl ChannelId {
             Self::TransactionPropagation => "transaction/propagation",
             Self::SnarkPropagation => "snark/propagation",
             Self::SnarkJobCommitmentPropagation => "snark_job_commitment/propagation",
+            Self::SignalingRelay => "signaling/relay",
             Self::Rpc => "rpc",
             Self::StreamingRpc => "rpc/streaming",
         }
@@ -74,11 +79,19 @@ impl ChannelId {
             Self::TransactionPropagation => true,
             Self::SnarkPropagation => true,
             Self::SnarkJobCommitmentPropagation => false,
+            Self::SignalingRelay => false,
             Self::Rpc => true,
             Self::StreamingRpc => false,
         }
     }
 
+    /// Channels which peers running older versions don't know about.
+    /// They have to be explicitly enabled, otherwise opening them would
+    /// fail the connection with such peers.
+    pub fn is_opt_in(self) -> bool {
+        matches!(self, Self::SignalingRelay)
+    }
+
     pub fn max_msg_size(self) -> usize {
         match self {
             // TODO(binier): reduce this value once we change message for best tip
@@ -87,6 +100,7 @@ impl ChannelId {
             Self::TransactionPropagation => 1024,         // 1KB - just transaction info.
             Self::SnarkPropagation => 1024,               // 1KB - just snark info.
             Self::SnarkJobCommitmentPropagation => 2 * 1024, // 2KB,
+            Self::SignalingRelay => 16 * 1024,            // 16KB - offer/answer with sdp.
             Self::Rpc => 256 * 1024 * 1024,               // 256MB,
             Self::StreamingRpc => 16 * 1024 * 1024,       // 16MB,
         }
@@ -96,6 +110,11 @@ impl ChannelId {
         <Self as strum::IntoEnumIterator>::iter()
     }
 
+    /// Channels enabled unless configured otherwise.
+    pub fn iter_default() -> impl Iterator<Item = ChannelId> {
+        Self::iter_all().filter(|chan| !chan.is_opt_in())
+    }
+
     pub fn for_libp2p() -> impl Iterator<Item = ChannelId> {
         Self::iter_all().filter(|chan| chan.supported_by_libp2p())
     }
@@ -126,6 +145,7 @@ pub enum ChannelMsg {
     TransactionPropagation(TransactionPropagationChannelMsg),
     SnarkPropagation(SnarkPropagationChannelMsg),
     SnarkJobCommitmentPropagation(SnarkJobCommitmentPropagationChannelMsg),
+    SignalingRelay(SignalingRelayChannelMsg),
     Rpc(RpcChannelMsg),
     StreamingRpc(StreamingRpcChannelMsg),
 }
@@ -137,6 +157,7 @@ impl ChannelMsg {
             Self::TransactionPropagation(_) => ChannelId::TransactionPropagation,
             Self::SnarkPropagation(_) => ChannelId::SnarkPropagation,
             Self::SnarkJobCommitmentPropagation(_) => ChannelId::SnarkJobCommitmentPropagation,
+            Self::SignalingRelay(_) => ChannelId::SignalingRelay,
             Self::Rpc(_) => ChannelId::Rpc,
             Self::StreamingRpc(_) => ChannelId::StreamingRpc,
         }
@@ -151,6 +172,7 @@ impl ChannelMsg {
             Self::TransactionPropagation(v) => v.binprot_write(w),
             Self::SnarkPropagation(v) => v.binprot_write(w),
             Self::SnarkJobCommitmentPropagation(v) => v.binprot_write(w),
+            Self::SignalingRelay(v) => v.binprot_write(w),
             Self::Rpc(v) => v.binprot_write(w),
             Self::StreamingRpc(v) => v.binprot_write(w),
         }
@@ -174,6 +196,9 @@ impl ChannelMsg {
             ChannelId::SnarkJobCommitmentPropagation => {
                 SnarkJobCommitmentPropagationChannelMsg::binprot_read(r).map(|v| v.into())
             }
+            ChannelId::SignalingRelay => {
+                SignalingRelayChannelMsg::binprot_read(r).map(|v| v.into())
+            }
             ChannelId::Rpc => RpcChannelMsg::binprot_read(r).map(|v| v.into()),
             ChannelId::StreamingRpc => StreamingRpcChannelMsg::binprot_read(r).map(|v| v.into()),
         }
@@ -208,6 +233,10 @@ impl crate::P2pState {
                         snark_job_commitment::P2pChannelsSnarkJobCommitmentAction::Init { peer_id },
                     );
                 }
+                ChannelId::SignalingRelay => {
+                    dispatcher
+                        .push(signaling_relay::P2pChannelsSignalingRelayAction::Init { peer_id });
+                }
                 ChannelId::Rpc => {
                     dispatcher.push(rpc::P2pChannelsRpcAction::Init { peer_id });
                 }
@@ -6,6 +6,8 @@ use crate::{P2pState, PeerId};
 use super::{
     best_tip::P2pChannelsBestTipAction, best_tip_effectful::P2pChannelsBestTipEffectfulAction,
     rpc::P2pChannelsRpcAction, rpc_effectful::P2pChannelsRpcEffectfulAction,
+    signaling_relay::P2pChannelsSignalingRelayAction,
+    signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction,
     snark::P2pChannelsSnarkAction, snark_effectful::P2pChannelsSnarkEffectfulAction,
     snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
     snark_job_commitment_effectful::P2pChannelsSnarkJobCommitmentEffectfulAction,
@@ -22,6 +24,7 @@ pub enum P2pChannelsAction {
     Transaction(P2pChannelsTransactionAction),
     Snark(P2pChannelsSnarkAction),
     SnarkJobCommitment(P2pChannelsSnarkJobCommitmentAction),
+    SignalingRelay(P2pChannelsSignalingRelayAction),
     Rpc(P2pChannelsRpcAction),
     StreamingRpc(P2pChannelsStreamingRpcAction),
 }
@@ -32,6 +35,7 @@ pub enum P2pChannelsEffectfulAction {
     Rpc(P2pChannelsRpcEffectfulAction),
     Snark(P2pChannelsSnarkEffectfulAction),
     SnarkJobCommitment(P2pChannelsSnarkJobCommitmentEffectfulAction),
+    SignalingRelay(P2pChannelsSignalingRelayEffectfulAction),
     StreamingRpc(P2pChannelsStreamingRpcEffectfulAction),
     Transaction(P2pChannelsTransactionEffectfulAction),
 }
@@ -44,6 +48,7 @@ impl P2pChannelsAction {
             Self::Transaction(v) => v.peer_id(),
             Self::Snark(v) => v.peer_id(),
             Self::SnarkJobCommitment(v) => Some(v.peer_id()),
+            Self::SignalingRelay(v) => Some(v.peer_id()),
             Self::Rpc(v) => Some(v.peer_id()),
             Self::StreamingRpc(v) => Some(v.peer_id()),
         }
@@ -58,6 +63,7 @@ impl redux::EnablingCondition<crate::P2pState> for P2pChannelsAction {
             P2pChannelsAction::BestTip(a) => a.is_enabled(state, time),
             P2pChannelsAction::Snark(a) => a.is_enabled(state, time),
             P2pChannelsAction::SnarkJobCommitment(a) => a.is_enabled(state, time),
+            P2pChannelsAction::SignalingRelay(a) => a.is_enabled(state, time),
             P2pChannelsAction::Rpc(a) => a.is_enabled(state, time),
             P2pChannelsAction::StreamingRpc(a) => a.is_enabled(state, time),
         }
@@ -71,6 +77,7 @@ impl redux::EnablingCondition<crate::P2pState> for P2pChannelsEffectfulAction {
             P2pChannelsEffectfulAction::Transaction(a) => a.is_enabled(state, time),
             P2pChannelsEffectfulAction::StreamingRpc(a) => a.is_enabled(state, time),
             P2pChannelsEffectfulAction::SnarkJobCommitment(a) => a.is_enabled(state, time),
+            P2pChannelsEffectfulAction::SignalingRelay(a) => a.is_enabled(state, time),
             P2pChannelsEffectfulAction::Rpc(a) => a.is_enabled(state, time),
             P2pChannelsEffectfulAction::Snark(a) => a.is_enabled(state, time),
         }
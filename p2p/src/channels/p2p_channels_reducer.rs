@@ -1,6 +1,9 @@
 use super::{
     best_tip::{BestTipPropagationChannelMsg, P2pChannelsBestTipAction, P2pChannelsBestTipState},
     rpc::{P2pChannelsRpcAction, P2pChannelsRpcState, RpcChannelMsg},
+    signaling_relay::{
+        P2pChannelsSignalingRelayAction, P2pChannelsSignalingRelayState, SignalingRelayChannelMsg,
+    },
     snark::{P2pChannelsSnarkAction, P2pChannelsSnarkState, SnarkPropagationChannelMsg},
     snark_job_commitment::{
         P2pChannelsSnarkJobCommitmentAction, P2pChannelsSnarkJobCommitmentState,
@@ -49,6 +52,9 @@ impl P2pChannelsState {
             P2pChannelsAction::SnarkJobCommitment(action) => {
                 P2pChannelsSnarkJobCommitmentState::reducer(state_context, meta.with_action(action))
             }
+            P2pChannelsAction::SignalingRelay(action) => {
+                P2pChannelsSignalingRelayState::reducer(state_context, meta.with_action(action))
+            }
             P2pChannelsAction::Rpc(action) => {
                 P2pChannelsRpcState::reducer(state_context, meta.with_action(action))
             }
@@ -149,6 +155,29 @@ impl P2pChannelsState {
                     .into(),
                 ),
             },
+            ChannelMsg::SignalingRelay(msg) => match msg {
+                SignalingRelayChannelMsg::Offer(offer) => is_enabled(
+                    P2pChannelsSignalingRelayAction::OfferReceived { peer_id, offer }.into(),
+                ),
+                SignalingRelayChannelMsg::Answer {
+                    offerer,
+                    answerer,
+                    response,
+                } => {
+                    // Answer may arrive after the offer timed out or the
+                    // offerer disconnected, so stale answers are just ignored.
+                    is_enabled(
+                        P2pChannelsSignalingRelayAction::AnswerReceived {
+                            peer_id,
+                            offerer,
+                            answerer,
+                            response,
+                        }
+                        .into(),
+                    );
+                    true
+                }
+            },
             ChannelMsg::Rpc(msg) => match msg {
                 RpcChannelMsg::Request(id, request) => is_enabled(
                     P2pChannelsRpcAction::RequestReceived {
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use super::{
     best_tip::P2pChannelsBestTipState,
     rpc::{P2pChannelsRpcState, P2pRpcId},
+    signaling_relay::P2pChannelsSignalingRelayState,
     snark::P2pChannelsSnarkState,
     snark_job_commitment::P2pChannelsSnarkJobCommitmentState,
     streaming_rpc::P2pChannelsStreamingRpcState,
@@ -18,6 +19,7 @@ pub struct P2pChannelsState {
     pub transaction: P2pChannelsTransactionState,
     pub snark: P2pChannelsSnarkState,
     pub snark_job_commitment: P2pChannelsSnarkJobCommitmentState,
+    pub signaling_relay: P2pChannelsSignalingRelayState,
     pub rpc: P2pChannelsRpcState,
     pub streaming_rpc: P2pChannelsStreamingRpcState,
 
@@ -37,6 +39,10 @@ impl P2pChannelsState {
                 false => P2pChannelsSnarkJobCommitmentState::Disabled,
                 true => P2pChannelsSnarkJobCommitmentState::Enabled,
             },
+            signaling_relay: match enabled_channels.contains(&ChannelId::SignalingRelay) {
+                false => P2pChannelsSignalingRelayState::Disabled,
+                true => P2pChannelsSignalingRelayState::Enabled,
+            },
             transaction: match enabled_channels.contains(&ChannelId::TransactionPropagation) {
                 false => P2pChannelsTransactionState::Disabled,
                 true => P2pChannelsTransactionState::Enabled,
@@ -62,6 +68,14 @@ impl P2pChannelsState {
         self.next_local_rpc_id
     }
 
+    /// Allocates the id for a query which isn't sent by the rpc channel,
+    /// so that it doesn't collide with ids of the channel's requests.
+    pub(crate) fn take_next_local_rpc_id(&mut self) -> P2pRpcId {
+        let id = self.next_local_rpc_id;
+        self.next_local_rpc_id += 1;
+        id
+    }
+
     pub fn rpc_remote_last_responded(&self) -> redux::Timestamp {
         std::cmp::max(
             self.rpc.remote_last_responded(),
@@ -77,6 +91,7 @@ impl P2pChannelsState {
             ChannelId::TransactionPropagation => self.transaction.is_ready(),
             ChannelId::SnarkPropagation => self.snark.is_ready(),
             ChannelId::SnarkJobCommitmentPropagation => self.snark_job_commitment.is_ready(),
+            ChannelId::SignalingRelay => self.signaling_relay.is_ready(),
             ChannelId::Rpc => self.rpc.is_ready(),
             ChannelId::StreamingRpc => self.rpc.is_ready(),
         }
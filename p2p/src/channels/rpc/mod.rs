@@ -11,12 +11,14 @@ use std::{sync::Arc, time::Duration};
 use binprot_derive::{BinProtRead, BinProtWrite};
 use mina_p2p_messages::{
     list::List,
+    rpc::NodeStatusV2,
     rpc_kernel::QueryID,
     v2::{
-        LedgerHash, MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
-        MinaBaseStateBodyHashStableV1, MinaLedgerSyncLedgerAnswerStableV2,
-        MinaLedgerSyncLedgerQueryStableV1, MinaStateProtocolStateValueStableV2, StateHash,
-        TransactionSnarkScanStateStableV2,
+        ConsensusProofOfStakeDataConsensusStateValueStableV2, LedgerHash,
+        MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
+        MinaBaseSparseLedgerBaseStableV2, MinaBaseStateBodyHashStableV1,
+        MinaLedgerSyncLedgerAnswerStableV2, MinaLedgerSyncLedgerQueryStableV1,
+        MinaStateProtocolStateValueStableV2, StateHash, TransactionSnarkScanStateStableV2,
     },
 };
 use openmina_core::{
@@ -52,6 +54,11 @@ pub enum P2pRpcKind {
     Block,
     Snark,
     InitialPeers,
+    TransitionKnowledge,
+    TransitionChainProof,
+    Ancestry,
+    NodeStatus,
+    EpochLedger,
 }
 
 impl P2pRpcKind {
@@ -65,6 +72,11 @@ impl P2pRpcKind {
             Self::Block => config.block,
             Self::Snark => config.snark,
             Self::InitialPeers => config.initial_peers,
+            Self::TransitionKnowledge => config.transition_knowledge,
+            Self::TransitionChainProof => config.transition_chain_proof,
+            Self::Ancestry => config.ancestry,
+            Self::NodeStatus => config.node_status,
+            Self::EpochLedger => config.epoch_ledger,
         }
     }
 
@@ -76,6 +88,11 @@ impl P2pRpcKind {
             Self::Block => true,
             Self::Snark => false,
             Self::InitialPeers => true,
+            Self::TransitionKnowledge => true,
+            Self::TransitionChainProof => true,
+            Self::Ancestry => true,
+            Self::NodeStatus => true,
+            Self::EpochLedger => true,
         }
     }
 }
@@ -88,6 +105,16 @@ pub enum P2pRpcRequest {
     Block(StateHash),
     Snark(SnarkJobId),
     InitialPeers,
+    TransitionKnowledge,
+    TransitionChainProof(StateHash),
+    /// Best tip with proof, if it's not worse than the block with the
+    /// given hash and consensus state.
+    Ancestry(
+        StateHash,
+        Box<ConsensusProofOfStakeDataConsensusStateValueStableV2>,
+    ),
+    NodeStatus,
+    EpochLedger(LedgerHash),
 }
 
 impl P2pRpcRequest {
@@ -101,6 +128,11 @@ impl P2pRpcRequest {
             Self::Block(_) => P2pRpcKind::Block,
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::InitialPeers => P2pRpcKind::InitialPeers,
+            Self::TransitionKnowledge => P2pRpcKind::TransitionKnowledge,
+            Self::TransitionChainProof(_) => P2pRpcKind::TransitionChainProof,
+            Self::Ancestry(..) => P2pRpcKind::Ancestry,
+            Self::NodeStatus => P2pRpcKind::NodeStatus,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
         }
     }
 }
@@ -147,13 +179,18 @@ impl std::fmt::Display for P2pRpcRequest {
                 write!(f, "ledger: {ledger_hash}")
             }
             Self::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash)
-            | Self::Block(block_hash) => {
+            | Self::Block(block_hash)
+            | Self::TransitionChainProof(block_hash)
+            | Self::Ancestry(block_hash, _) => {
                 write!(f, ", {block_hash}")
             }
             Self::Snark(job_id) => {
                 write!(f, ", {job_id}")
             }
-            Self::InitialPeers => Ok(()),
+            Self::EpochLedger(ledger_hash) => {
+                write!(f, ", {ledger_hash}")
+            }
+            Self::InitialPeers | Self::TransitionKnowledge | Self::NodeStatus => Ok(()),
         }
     }
 }
@@ -181,6 +218,13 @@ pub enum P2pRpcResponse {
     Block(ArcBlock),
     Snark(Snark),
     InitialPeers(List<P2pConnectionOutgoingInitOpts>),
+    TransitionKnowledge(List<StateHash>),
+    /// Root hash of the transition frontier and state body hashes of the
+    /// blocks after the root, up to and including the requested block.
+    TransitionChainProof(StateHash, List<MinaBaseStateBodyHashStableV1>),
+    Ancestry(BestTipWithProof),
+    NodeStatus(Arc<NodeStatusV2>),
+    EpochLedger(Arc<MinaBaseSparseLedgerBaseStableV2>),
 }
 
 impl P2pRpcResponse {
@@ -194,6 +238,11 @@ impl P2pRpcResponse {
             Self::Block(_) => P2pRpcKind::Block,
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::InitialPeers(_) => P2pRpcKind::InitialPeers,
+            Self::TransitionKnowledge(_) => P2pRpcKind::TransitionKnowledge,
+            Self::TransitionChainProof(..) => P2pRpcKind::TransitionChainProof,
+            Self::Ancestry(_) => P2pRpcKind::Ancestry,
+            Self::NodeStatus(_) => P2pRpcKind::NodeStatus,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
         }
     }
 }
@@ -293,6 +342,67 @@ mod libp2p {
                     .collect();
                 let r = RpcResult(Ok(NeedsLength(r)));
 
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::TransitionKnowledge(hashes) => {
+                type Method = rpc::GetTransitionKnowledgeV1ForV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r = hashes.into_iter().map(|hash| hash.0.clone()).collect();
+                let r = RpcResult(Ok(NeedsLength(r)));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::TransitionChainProof(root_hash, body_hashes) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let body_hashes = body_hashes.into_iter().map(|hash| hash.0).collect();
+                let r = RpcResult(Ok(NeedsLength(Some((root_hash.0.clone(), body_hashes)))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::Ancestry(r) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let BestTipWithProof {
+                    best_tip,
+                    proof: (middle, block),
+                } = r;
+
+                let middle = middle.into_iter().map(|hash| hash.0).collect();
+                let r = RpcResult(Ok(NeedsLength(Some(rpc::ProofCarryingDataWithHashV1 {
+                    data: best_tip.as_ref().clone(),
+                    proof: (middle, block.as_ref().clone()),
+                }))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::NodeStatus(status) => {
+                type Method = rpc::GetNodeStatusV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r = RpcResult(Ok(NeedsLength(RpcResult(Ok(status.as_ref().clone())))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::EpochLedger(ledger) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r = RpcResult(Ok(NeedsLength(RpcResult(Ok(ledger.as_ref().clone())))));
+
                 let mut v = vec![];
                 <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
                 Some((ResponseHeader { id: id as _ }, v.into()))
@@ -395,6 +505,92 @@ mod libp2p {
                     v.into(),
                 ))
             }
+            P2pRpcRequest::TransitionKnowledge => {
+                type Method = rpc::GetTransitionKnowledgeV1ForV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::TransitionChainProof(hash) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::Ancestry(hash, consensus_state) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(
+                    &NeedsLength(rpc::WithHashV1 {
+                        data: *consensus_state,
+                        hash: hash.0.clone(),
+                    }),
+                    &mut v,
+                )
+                .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::NodeStatus => {
+                type Method = rpc::GetNodeStatusV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::EpochLedger(hash) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
         }
     }
 }
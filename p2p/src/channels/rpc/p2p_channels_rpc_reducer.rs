@@ -9,8 +9,9 @@ use crate::{
 };
 
 use super::{
-    P2pChannelsRpcAction, P2pChannelsRpcState, P2pRpcLocalState, P2pRpcRemotePendingRequestState,
-    P2pRpcRemoteState, P2pRpcResponse, MAX_P2P_RPC_REMOTE_CONCURRENT_REQUESTS,
+    P2pChannelsRpcAction, P2pChannelsRpcState, P2pRpcKind, P2pRpcLocalState,
+    P2pRpcRemotePendingRequestState, P2pRpcRemoteState, P2pRpcResponse,
+    MAX_P2P_RPC_REMOTE_CONCURRENT_REQUESTS,
 };
 
 impl P2pChannelsRpcState {
@@ -117,18 +118,32 @@ impl P2pChannelsRpcState {
                     );
                     return Ok(());
                 };
-                let P2pRpcLocalState::Requested { id, request, .. } = local else {
+                let P2pRpcLocalState::Requested { id, request, time } = local else {
                     bug_condition!(
                         "Invalid state for `P2pChannelsRpcAction::ResponseReceived`, state: {:?}",
                         rpc_state
                     );
                     return Ok(());
                 };
+                // Only responses to small requests reflect the round trip
+                // time, rather than the bandwidth or peer's load.
+                let rtt = matches!(
+                    request.kind(),
+                    P2pRpcKind::LedgerQuery
+                        | P2pRpcKind::InitialPeers
+                        | P2pRpcKind::NodeStatus
+                        | P2pRpcKind::TransitionKnowledge
+                )
+                .then(|| meta.time().checked_sub(*time))
+                .flatten();
                 *local = P2pRpcLocalState::Responded {
                     time: meta.time(),
                     id: *id,
                     request: std::mem::take(request),
                 };
+                if let Some(rtt) = rtt {
+                    p2p_state.address_book.rtt_measured(&peer_id, rtt);
+                }
 
                 let dispatcher = state_context.into_dispatcher();
                 if let Some(P2pRpcResponse::BestTipWithProof(resp)) = response.as_deref() {
@@ -0,0 +1,55 @@
+mod p2p_channels_signaling_relay_state;
+pub use p2p_channels_signaling_relay_state::*;
+
+mod p2p_channels_signaling_relay_actions;
+pub use p2p_channels_signaling_relay_actions::*;
+
+mod p2p_channels_signaling_relay_reducer;
+
+use binprot_derive::{BinProtRead, BinProtWrite};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    connection::{outgoing::P2pConnectionOutgoingInitOpts, P2pConnectionResponse},
+    webrtc, P2pState, PeerId,
+};
+
+#[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
+pub enum SignalingRelayChannelMsg {
+    /// Offer for the `offer.target_peer_id`. If we are the target, the
+    /// offer was relayed to us, otherwise we are asked to relay it.
+    Offer(Box<webrtc::Offer>),
+    /// Response of the `answerer` to the offer of the `offerer`.
+    Answer {
+        offerer: PeerId,
+        answerer: PeerId,
+        response: Box<P2pConnectionResponse>,
+    },
+}
+
+impl P2pState {
+    /// Dial options of the ready peers, which can't be dialed directly
+    /// (e.g. browsers), but which can be reached by relaying the signaling
+    /// through us. Advertised to other peers during discovery.
+    pub fn relayed_dial_opts(&self) -> impl '_ + Iterator<Item = P2pConnectionOutgoingInitOpts> {
+        let relay_peer_id = self.my_id();
+        self.peers
+            .iter()
+            .filter(|(_, peer)| match &peer.dial_opts {
+                None => !peer.is_libp2p,
+                Some(P2pConnectionOutgoingInitOpts::WebRTC { signaling, .. }) => {
+                    matches!(signaling, webrtc::SignalingMethod::P2p { .. })
+                }
+                Some(_) => false,
+            })
+            .filter(|(_, peer)| {
+                peer.status
+                    .as_ready()
+                    .map_or(false, |p| p.channels.signaling_relay.is_ready())
+            })
+            .map(move |(peer_id, _)| P2pConnectionOutgoingInitOpts::WebRTC {
+                peer_id: *peer_id,
+                signaling: webrtc::SignalingMethod::P2p { relay_peer_id },
+            })
+    }
+}
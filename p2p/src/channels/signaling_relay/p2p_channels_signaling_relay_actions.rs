@@ -0,0 +1,291 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    channels::P2pChannelsAction,
+    connection::{
+        outgoing::{P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingState},
+        P2pConnectionResponse, P2pConnectionState,
+    },
+    webrtc, P2pPeerStatus, P2pState, PeerId,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ActionEvent)]
+#[action_event(fields(display(peer_id), display(offerer), display(answerer)))]
+pub enum P2pChannelsSignalingRelayAction {
+    /// Initialize signaling relay channel.
+    Init {
+        peer_id: PeerId,
+    },
+    Pending {
+        peer_id: PeerId,
+    },
+    /// Signaling relay channel is ready.
+    Ready {
+        peer_id: PeerId,
+    },
+    /// Send the offer to the relay, or if we are the relay, to the target
+    /// of the offer.
+    OfferSend {
+        peer_id: PeerId,
+        offer: Box<webrtc::Offer>,
+    },
+    /// Offer is received, either to be relayed or relayed to us.
+    OfferReceived {
+        peer_id: PeerId,
+        offer: Box<webrtc::Offer>,
+    },
+    /// Send the response to the relayed offer, either to the relay or, if
+    /// we are the relay, to the offerer.
+    AnswerSend {
+        peer_id: PeerId,
+        offerer: PeerId,
+        answerer: PeerId,
+        response: Box<P2pConnectionResponse>,
+    },
+    /// Response to the relayed offer is received, either to be relayed or
+    /// relayed to us.
+    AnswerReceived {
+        peer_id: PeerId,
+        offerer: PeerId,
+        answerer: PeerId,
+        response: Box<P2pConnectionResponse>,
+    },
+}
+
+impl P2pChannelsSignalingRelayAction {
+    pub fn peer_id(&self) -> &PeerId {
+        match self {
+            Self::Init { peer_id }
+            | Self::Pending { peer_id }
+            | Self::Ready { peer_id }
+            | Self::OfferSend { peer_id, .. }
+            | Self::OfferReceived { peer_id, .. }
+            | Self::AnswerSend { peer_id, .. }
+            | Self::AnswerReceived { peer_id, .. } => peer_id,
+        }
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pChannelsSignalingRelayAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        use super::P2pChannelsSignalingRelayState as S;
+
+        let channel_state = |peer_id: &PeerId| {
+            state
+                .get_ready_peer(peer_id)
+                .map(|p| &p.channels.signaling_relay)
+        };
+        match self {
+            P2pChannelsSignalingRelayAction::Init { peer_id } => {
+                matches!(channel_state(peer_id), Some(S::Enabled))
+            }
+            P2pChannelsSignalingRelayAction::Pending { peer_id } => {
+                matches!(channel_state(peer_id), Some(S::Init { .. }))
+            }
+            P2pChannelsSignalingRelayAction::Ready { peer_id } => {
+                matches!(channel_state(peer_id), Some(S::Pending { .. }))
+            }
+            P2pChannelsSignalingRelayAction::OfferSend { peer_id, .. }
+            | P2pChannelsSignalingRelayAction::AnswerSend { peer_id, .. } => {
+                channel_state(peer_id).map_or(false, |s| s.is_ready())
+            }
+            P2pChannelsSignalingRelayAction::OfferReceived { peer_id, offer } => {
+                // Peer may only ask us to relay its own offers.
+                channel_state(peer_id).map_or(false, |s| s.is_ready())
+                    && offer.target_peer_id != *peer_id
+                    && (offer.target_peer_id == state.my_id()
+                        || offer.identity_pub_key.peer_id() == *peer_id)
+            }
+            P2pChannelsSignalingRelayAction::AnswerReceived {
+                peer_id,
+                offerer,
+                answerer,
+                ..
+            } => {
+                if !channel_state(peer_id).map_or(false, |s| s.is_ready()) {
+                    return false;
+                }
+                if *offerer == state.my_id() {
+                    // Answer must come from the relay we sent the offer to.
+                    state.peers.get(answerer).map_or(false, |p| {
+                        matches!(
+                            &p.status,
+                            P2pPeerStatus::Connecting(P2pConnectionState::Outgoing(
+                                P2pConnectionOutgoingState::AnswerRecvPending {
+                                    opts: P2pConnectionOutgoingInitOpts::WebRTC {
+                                        signaling: webrtc::SignalingMethod::P2p { relay_peer_id },
+                                        ..
+                                    },
+                                    ..
+                                }
+                            )) if relay_peer_id == peer_id
+                        )
+                    })
+                } else {
+                    // Answer must come from the target of the offer we relayed.
+                    answerer == peer_id
+                        && channel_state(offerer).map_or(false, |s| s.is_relaying_to(answerer))
+                }
+            }
+        }
+    }
+}
+
+impl From<P2pChannelsSignalingRelayAction> for crate::P2pAction {
+    fn from(action: P2pChannelsSignalingRelayAction) -> Self {
+        Self::Channels(P2pChannelsAction::SignalingRelay(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use openmina_core::DEVNET_CHAIN_ID;
+    use redux::EnablingCondition;
+
+    use super::*;
+    use crate::{
+        channels::{signaling_relay::P2pChannelsSignalingRelayState, ChannelId},
+        connection::RejectionReason,
+        identity::SecretKey,
+        webrtc::Host,
+        P2pConfig, P2pPeerState, P2pPeerStatusReady,
+    };
+
+    fn now() -> redux::Timestamp {
+        redux::Timestamp::new(1_000_000_000)
+    }
+
+    fn key(i: u8) -> SecretKey {
+        SecretKey::from_bytes([i; 32])
+    }
+
+    fn peer(i: u8) -> PeerId {
+        key(i).public_key().peer_id()
+    }
+
+    /// Our node is `peer(0)`, connected to the given peers with the
+    /// signaling relay channel ready.
+    fn state_with_ready_peers(peers: &[u8]) -> P2pState {
+        let enabled_channels = ChannelId::iter_all().collect();
+        let config = P2pConfig {
+            libp2p_port: None,
+            listen_port: None,
+            identity_pub_key: key(0).public_key(),
+            initial_peers: Vec::new(),
+            ask_initial_peers_interval: Duration::from_secs(3600),
+            enabled_channels,
+            timeouts: Default::default(),
+            limits: Default::default(),
+            peer_discovery: false,
+            meshsub: Default::default(),
+            address_book: Vec::new(),
+        };
+        let mut state = P2pState::new(config, &DEVNET_CHAIN_ID);
+        for i in peers {
+            let mut ready = P2pPeerStatusReady::new(false, now(), &state.config.enabled_channels);
+            ready.channels.signaling_relay = P2pChannelsSignalingRelayState::Ready {
+                time: now(),
+                relayed_offers: Default::default(),
+            };
+            state.peers.insert(
+                peer(*i),
+                P2pPeerState {
+                    is_libp2p: false,
+                    dial_opts: None,
+                    status: P2pPeerStatus::Ready(ready),
+                    identify: None,
+                },
+            );
+        }
+        state
+    }
+
+    fn channel_mut(state: &mut P2pState, i: u8) -> &mut P2pChannelsSignalingRelayState {
+        &mut state
+            .get_ready_peer_mut(&peer(i))
+            .unwrap()
+            .channels
+            .signaling_relay
+    }
+
+    fn offer(from: u8, to: u8) -> Box<webrtc::Offer> {
+        Box::new(webrtc::Offer {
+            sdp: String::new(),
+            identity_pub_key: key(from).public_key(),
+            target_peer_id: peer(to),
+            host: Host::Ipv4([127, 0, 0, 1].into()),
+            listen_port: None,
+        })
+    }
+
+    fn rejected() -> Box<P2pConnectionResponse> {
+        Box::new(P2pConnectionResponse::Rejected(
+            RejectionReason::PeerCapacityFull,
+        ))
+    }
+
+    #[test]
+    fn init_only_if_enabled() {
+        let mut state = state_with_ready_peers(&[1]);
+        let init = P2pChannelsSignalingRelayAction::Init { peer_id: peer(1) };
+        assert!(!init.is_enabled(&state, now()));
+
+        *channel_mut(&mut state, 1) = P2pChannelsSignalingRelayState::Disabled;
+        assert!(!init.is_enabled(&state, now()));
+
+        *channel_mut(&mut state, 1) = P2pChannelsSignalingRelayState::Enabled;
+        assert!(init.is_enabled(&state, now()));
+
+        let unknown = P2pChannelsSignalingRelayAction::Init { peer_id: peer(2) };
+        assert!(!unknown.is_enabled(&state, now()));
+    }
+
+    #[test]
+    fn offer_received() {
+        let mut state = state_with_ready_peers(&[1, 2]);
+        let received = |peer_id, offer| P2pChannelsSignalingRelayAction::OfferReceived {
+            peer_id: peer(peer_id),
+            offer,
+        };
+
+        // Peer asks us to relay its own offer.
+        assert!(received(1, offer(1, 2)).is_enabled(&state, now()));
+        // Offer of another peer relayed to us.
+        assert!(received(1, offer(3, 0)).is_enabled(&state, now()));
+        // Peer may not relay offers of others, except to us.
+        assert!(!received(1, offer(3, 2)).is_enabled(&state, now()));
+        // Nor send offers to itself.
+        assert!(!received(1, offer(1, 1)).is_enabled(&state, now()));
+
+        *channel_mut(&mut state, 1) = P2pChannelsSignalingRelayState::Pending { time: now() };
+        assert!(!received(1, offer(1, 2)).is_enabled(&state, now()));
+    }
+
+    #[test]
+    fn answer_received_by_relay() {
+        let mut state = state_with_ready_peers(&[1, 2, 3]);
+        let received = |peer_id, answerer| P2pChannelsSignalingRelayAction::AnswerReceived {
+            peer_id: peer(peer_id),
+            offerer: peer(1),
+            answerer: peer(answerer),
+            response: rejected(),
+        };
+
+        // We didn't relay any offer of the offerer.
+        assert!(!received(2, 2).is_enabled(&state, now()));
+
+        channel_mut(&mut state, 1)
+            .offer_relay(peer(2), true, now())
+            .unwrap();
+        assert!(received(2, 2).is_enabled(&state, now()));
+        // Answer must come from the target of the relayed offer.
+        assert!(!received(3, 3).is_enabled(&state, now()));
+        assert!(!received(3, 2).is_enabled(&state, now()));
+
+        channel_mut(&mut state, 1).answer_relayed(&peer(2));
+        assert!(!received(2, 2).is_enabled(&state, now()));
+    }
+}
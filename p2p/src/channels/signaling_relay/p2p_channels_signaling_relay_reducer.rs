@@ -0,0 +1,187 @@
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+
+use crate::{
+    channels::signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction,
+    connection::{
+        incoming::{
+            IncomingSignalingMethod, P2pConnectionIncomingAction, P2pConnectionIncomingInitOpts,
+        },
+        outgoing::P2pConnectionOutgoingAction,
+        P2pConnectionErrorResponse, P2pConnectionResponse,
+    },
+    P2pState,
+};
+
+use super::{P2pChannelsSignalingRelayAction, P2pChannelsSignalingRelayState};
+
+impl P2pChannelsSignalingRelayState {
+    /// Substate is accessed
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<&P2pChannelsSignalingRelayAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+        let peer_id = *action.peer_id();
+        let my_id = p2p_state.my_id();
+
+        match action {
+            P2pChannelsSignalingRelayAction::Init { .. } => {
+                let state = Self::channel_state_mut(p2p_state, action)?;
+                *state = Self::Init { time: meta.time() };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingRelayEffectfulAction::Init { peer_id });
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::Pending { .. } => {
+                let state = Self::channel_state_mut(p2p_state, action)?;
+                *state = Self::Pending { time: meta.time() };
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::Ready { .. } => {
+                let state = Self::channel_state_mut(p2p_state, action)?;
+                *state = Self::Ready {
+                    time: meta.time(),
+                    relayed_offers: Default::default(),
+                };
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::OfferSend { offer, .. } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingRelayEffectfulAction::OfferSend {
+                    peer_id,
+                    offer: offer.clone(),
+                });
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::OfferReceived { offer, .. } => {
+                if offer.target_peer_id == my_id {
+                    let offerer = offer.identity_pub_key.peer_id();
+                    let accepted = p2p_state.incoming_accept(offerer, offer, meta.time());
+                    let dispatcher = state_context.into_dispatcher();
+                    match accepted {
+                        Ok(()) => {
+                            let opts = P2pConnectionIncomingInitOpts {
+                                peer_id: offerer,
+                                signaling: IncomingSignalingMethod::P2p {
+                                    relay_peer_id: peer_id,
+                                },
+                                offer: offer.clone(),
+                            };
+                            dispatcher
+                                .push(P2pConnectionIncomingAction::Init { opts, rpc_id: None });
+                        }
+                        Err(reason) => {
+                            dispatcher.push(P2pChannelsSignalingRelayAction::AnswerSend {
+                                peer_id,
+                                offerer,
+                                answerer: my_id,
+                                response: Box::new(P2pConnectionResponse::Rejected(reason)),
+                            });
+                        }
+                    }
+                    return Ok(());
+                }
+
+                let target = offer.target_peer_id;
+                let is_target_ready = p2p_state
+                    .get_ready_peer(&target)
+                    .map_or(false, |p| p.channels.signaling_relay.is_ready());
+                let state = Self::channel_state_mut(p2p_state, action)?;
+                if let Err(reason) = state.offer_relay(target, is_target_ready, meta.time()) {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pChannelsSignalingRelayAction::AnswerSend {
+                        peer_id,
+                        offerer: peer_id,
+                        answerer: target,
+                        response: Box::new(P2pConnectionResponse::Rejected(reason)),
+                    });
+                    return Ok(());
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingRelayAction::OfferSend {
+                    peer_id: target,
+                    offer: offer.clone(),
+                });
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::AnswerSend {
+                offerer,
+                answerer,
+                response,
+                ..
+            } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingRelayEffectfulAction::AnswerSend {
+                    peer_id,
+                    offerer: *offerer,
+                    answerer: *answerer,
+                    response: response.clone(),
+                });
+                Ok(())
+            }
+            P2pChannelsSignalingRelayAction::AnswerReceived {
+                offerer,
+                answerer,
+                response,
+                ..
+            } => {
+                if *offerer == my_id {
+                    let dispatcher = state_context.into_dispatcher();
+                    let peer_id = *answerer;
+                    match response.as_ref() {
+                        P2pConnectionResponse::Accepted(answer) => {
+                            dispatcher.push(P2pConnectionOutgoingAction::AnswerRecvSuccess {
+                                peer_id,
+                                answer: answer.clone(),
+                            });
+                        }
+                        P2pConnectionResponse::Rejected(reason) => {
+                            dispatcher.push(P2pConnectionOutgoingAction::AnswerRecvError {
+                                peer_id,
+                                error: P2pConnectionErrorResponse::Rejected(*reason),
+                            });
+                        }
+                        P2pConnectionResponse::InternalError => {
+                            dispatcher.push(P2pConnectionOutgoingAction::AnswerRecvError {
+                                peer_id,
+                                error: P2pConnectionErrorResponse::InternalError,
+                            });
+                        }
+                    }
+                    return Ok(());
+                }
+
+                if let Some(p) = p2p_state.get_ready_peer_mut(offerer) {
+                    p.channels.signaling_relay.answer_relayed(answerer);
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingRelayAction::AnswerSend {
+                    peer_id: *offerer,
+                    offerer: *offerer,
+                    answerer: *answerer,
+                    response: response.clone(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn channel_state_mut<'a>(
+        p2p_state: &'a mut P2pState,
+        action: &P2pChannelsSignalingRelayAction,
+    ) -> Result<&'a mut Self, String> {
+        p2p_state
+            .get_ready_peer_mut(action.peer_id())
+            .map(|p| &mut p.channels.signaling_relay)
+            .ok_or_else(|| format!("Peer state not found for: {action:?}"))
+    }
+}
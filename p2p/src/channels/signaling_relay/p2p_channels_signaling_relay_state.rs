@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{connection::RejectionReason, PeerId};
+
+/// Maximum number of offers from a single peer, which we relay at the same
+/// time.
+pub const MAX_RELAYED_OFFERS_PER_PEER: usize = 8;
+/// Relayed offer which wasn't answered within this time is forgotten.
+pub const RELAYED_OFFER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum P2pChannelsSignalingRelayState {
+    Disabled,
+    Enabled,
+    Init {
+        time: redux::Timestamp,
+    },
+    Pending {
+        time: redux::Timestamp,
+    },
+    Ready {
+        time: redux::Timestamp,
+        /// Offers of this peer which we relayed and are waiting for the
+        /// answer, by the target peer id.
+        relayed_offers: BTreeMap<PeerId, redux::Timestamp>,
+    },
+}
+
+impl P2pChannelsSignalingRelayState {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready { .. })
+    }
+
+    /// Whether we relayed the offer of this peer to the `target` and are
+    /// waiting for the answer.
+    pub fn is_relaying_to(&self, target: &PeerId) -> bool {
+        match self {
+            Self::Ready { relayed_offers, .. } => relayed_offers.contains_key(target),
+            _ => false,
+        }
+    }
+
+    /// Whether we can relay another offer of this peer.
+    pub fn can_relay(&self, now: redux::Timestamp) -> bool {
+        match self {
+            Self::Ready { relayed_offers, .. } => {
+                relayed_offers
+                    .values()
+                    .filter(|t| !is_timed_out(**t, now))
+                    .count()
+                    < MAX_RELAYED_OFFERS_PER_PEER
+            }
+            _ => false,
+        }
+    }
+
+    /// Remember that we relay the offer of this peer to the `target`, or
+    /// return the reason why it can't be relayed.
+    pub fn offer_relay(
+        &mut self,
+        target: PeerId,
+        is_target_ready: bool,
+        now: redux::Timestamp,
+    ) -> Result<(), RejectionReason> {
+        if !is_target_ready {
+            return Err(RejectionReason::RelayTargetNotConnected);
+        }
+        if !self.can_relay(now) {
+            return Err(RejectionReason::PeerCapacityFull);
+        }
+        if let Self::Ready { relayed_offers, .. } = self {
+            relayed_offers.retain(|_, relayed_at| !is_timed_out(*relayed_at, now));
+            relayed_offers.insert(target, now);
+        }
+        Ok(())
+    }
+
+    /// Forget the relayed offer of this peer, once the `answerer` responded.
+    pub fn answer_relayed(&mut self, answerer: &PeerId) {
+        if let Self::Ready { relayed_offers, .. } = self {
+            relayed_offers.remove(answerer);
+        }
+    }
+}
+
+fn is_timed_out(relayed_at: redux::Timestamp, now: redux::Timestamp) -> bool {
+    now.checked_sub(relayed_at)
+        .map_or(false, |elapsed| elapsed >= RELAYED_OFFER_TIMEOUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> redux::Timestamp {
+        redux::Timestamp::new(secs * 1_000_000_000)
+    }
+
+    fn ready() -> P2pChannelsSignalingRelayState {
+        P2pChannelsSignalingRelayState::Ready {
+            time: at(0),
+            relayed_offers: Default::default(),
+        }
+    }
+
+    fn target(i: u8) -> PeerId {
+        PeerId::from_bytes([i; 32])
+    }
+
+    #[test]
+    fn offer_relay() {
+        let mut state = ready();
+        assert_eq!(
+            state.offer_relay(target(1), false, at(0)),
+            Err(RejectionReason::RelayTargetNotConnected)
+        );
+        assert!(!state.is_relaying_to(&target(1)));
+
+        assert_eq!(state.offer_relay(target(1), true, at(0)), Ok(()));
+        assert!(state.is_relaying_to(&target(1)));
+
+        state.answer_relayed(&target(1));
+        assert!(!state.is_relaying_to(&target(1)));
+    }
+
+    #[test]
+    fn offer_relay_not_ready() {
+        let mut state = P2pChannelsSignalingRelayState::Pending { time: at(0) };
+        assert_eq!(
+            state.offer_relay(target(1), true, at(0)),
+            Err(RejectionReason::PeerCapacityFull)
+        );
+    }
+
+    #[test]
+    fn offer_relay_capacity() {
+        let mut state = ready();
+        for i in 0..MAX_RELAYED_OFFERS_PER_PEER as u8 {
+            assert_eq!(state.offer_relay(target(i), true, at(0)), Ok(()));
+        }
+        assert!(!state.can_relay(at(0)));
+        let extra = target(MAX_RELAYED_OFFERS_PER_PEER as u8);
+        assert_eq!(
+            state.offer_relay(extra, true, at(0)),
+            Err(RejectionReason::PeerCapacityFull)
+        );
+
+        // Answered offer frees up the slot.
+        state.answer_relayed(&target(0));
+        assert_eq!(state.offer_relay(extra, true, at(0)), Ok(()));
+    }
+
+    #[test]
+    fn offer_relay_timeout() {
+        let mut state = ready();
+        for i in 0..MAX_RELAYED_OFFERS_PER_PEER as u8 {
+            assert_eq!(state.offer_relay(target(i), true, at(0)), Ok(()));
+        }
+        let timeout = RELAYED_OFFER_TIMEOUT.as_secs();
+        assert!(!state.can_relay(at(timeout - 1)));
+        assert!(state.can_relay(at(timeout)));
+
+        let extra = target(MAX_RELAYED_OFFERS_PER_PEER as u8);
+        assert_eq!(state.offer_relay(extra, true, at(timeout)), Ok(()));
+        // Timed out offers are forgotten.
+        assert!(!state.is_relaying_to(&target(0)));
+        assert!(state.is_relaying_to(&extra));
+    }
+}
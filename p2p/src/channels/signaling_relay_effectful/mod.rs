@@ -0,0 +1,4 @@
+mod p2p_channels_signaling_relay_effectful_actions;
+pub use p2p_channels_signaling_relay_effectful_actions::P2pChannelsSignalingRelayEffectfulAction;
+
+mod p2p_channels_signaling_relay_effectful_effects;
@@ -0,0 +1,37 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    channels::P2pChannelsEffectfulAction, connection::P2pConnectionResponse, webrtc, P2pState,
+    PeerId,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ActionEvent)]
+#[action_event(fields(display(peer_id), display(offerer), display(answerer)))]
+pub enum P2pChannelsSignalingRelayEffectfulAction {
+    Init {
+        peer_id: PeerId,
+    },
+    OfferSend {
+        peer_id: PeerId,
+        offer: Box<webrtc::Offer>,
+    },
+    AnswerSend {
+        peer_id: PeerId,
+        offerer: PeerId,
+        answerer: PeerId,
+        response: Box<P2pConnectionResponse>,
+    },
+}
+
+impl redux::EnablingCondition<P2pState> for P2pChannelsSignalingRelayEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
+
+impl From<P2pChannelsSignalingRelayEffectfulAction> for crate::P2pAction {
+    fn from(action: P2pChannelsSignalingRelayEffectfulAction) -> crate::P2pAction {
+        crate::P2pAction::ChannelsEffectful(P2pChannelsEffectfulAction::SignalingRelay(action))
+    }
+}
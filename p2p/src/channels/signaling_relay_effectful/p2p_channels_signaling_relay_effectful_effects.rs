@@ -0,0 +1,44 @@
+use super::P2pChannelsSignalingRelayEffectfulAction;
+use crate::channels::{
+    signaling_relay::{P2pChannelsSignalingRelayAction, SignalingRelayChannelMsg},
+    ChannelId, MsgId, P2pChannelsService,
+};
+use redux::ActionMeta;
+
+impl P2pChannelsSignalingRelayEffectfulAction {
+    pub fn effects<Store, S>(self, _meta: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pChannelsService,
+    {
+        match self {
+            P2pChannelsSignalingRelayEffectfulAction::Init { peer_id } => {
+                store
+                    .service()
+                    .channel_open(peer_id, ChannelId::SignalingRelay);
+                store.dispatch(P2pChannelsSignalingRelayAction::Pending { peer_id });
+            }
+            P2pChannelsSignalingRelayEffectfulAction::OfferSend { peer_id, offer } => {
+                let msg = SignalingRelayChannelMsg::Offer(offer);
+                store
+                    .service()
+                    .channel_send(peer_id, MsgId::first(), msg.into());
+            }
+            P2pChannelsSignalingRelayEffectfulAction::AnswerSend {
+                peer_id,
+                offerer,
+                answerer,
+                response,
+            } => {
+                let msg = SignalingRelayChannelMsg::Answer {
+                    offerer,
+                    answerer,
+                    response,
+                };
+                store
+                    .service()
+                    .channel_send(peer_id, MsgId::first(), msg.into());
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use openmina_core::transaction::Transaction;
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
-use crate::{channels::P2pChannelsAction, P2pState, PeerId};
+use crate::{channels::P2pChannelsAction, P2pNetworkPubsubMessageId, P2pState, PeerId};
 
 use super::{P2pChannelsTransactionState, TransactionInfo, TransactionPropagationState};
 
@@ -43,10 +43,13 @@ pub enum P2pChannelsTransactionAction {
         first_index: u64,
         last_index: u64,
     },
+    /// Transactions of the pool diff received over pubsub. The message
+    /// is forwarded to other peers once the transactions are verified.
     Libp2pReceived {
         peer_id: PeerId,
-        transaction: Box<Transaction>,
+        transactions: Vec<Transaction>,
         nonce: u32,
+        message_id: P2pNetworkPubsubMessageId,
     },
     Libp2pBroadcast {
         transaction: Box<Transaction>,
@@ -22,6 +22,11 @@ pub struct P2pConnectionIncomingInitOpts {
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 pub enum IncomingSignalingMethod {
     Http,
+    /// Offer was relayed to us by the `relay_peer_id` over the signaling
+    /// relay channel and the answer must be sent back the same way.
+    P2p {
+        relay_peer_id: PeerId,
+    },
 }
 
 impl P2pState {
@@ -29,6 +34,7 @@ impl P2pState {
         &self,
         peer_id: PeerId,
         offer: &webrtc::Offer,
+        now: redux::Timestamp,
     ) -> Result<(), RejectionReason> {
         if peer_id != offer.identity_pub_key.peer_id() {
             return Err(RejectionReason::PeerIdAndPublicKeyMismatch);
@@ -44,6 +50,10 @@ impl P2pState {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.reputation.is_banned(&peer_id, now) {
+            return Err(RejectionReason::Banned);
+        }
+
         if self.is_peer_connected_or_connecting(&peer_id) {
             // Both nodes trying to connect to each other at the same time.
             // Choose connection arbitrarily based on peer id.
@@ -60,11 +70,19 @@ impl P2pState {
         Ok(())
     }
 
-    pub fn libp2p_incoming_accept(&self, peer_id: PeerId) -> Result<(), RejectionReason> {
+    pub fn libp2p_incoming_accept(
+        &self,
+        peer_id: PeerId,
+        now: redux::Timestamp,
+    ) -> Result<(), RejectionReason> {
         if peer_id == self.my_id() {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.reputation.is_banned(&peer_id, now) {
+            return Err(RejectionReason::Banned);
+        }
+
         if self.already_has_max_ready_peers() {
             return Err(RejectionReason::PeerCapacityFull);
         }
@@ -5,10 +5,11 @@ use openmina_core::{bug_condition, debug, warn, Substate};
 use redux::{ActionWithMeta, Dispatcher, Timestamp};
 
 use crate::{
+    channels::signaling_relay::P2pChannelsSignalingRelayAction,
     connection::{
         incoming::P2pConnectionIncomingError,
         incoming_effectful::P2pConnectionIncomingEffectfulAction,
-        outgoing::P2pConnectionOutgoingInitOpts, P2pConnectionState,
+        outgoing::P2pConnectionOutgoingInitOpts, P2pConnectionResponse, P2pConnectionState,
     },
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     webrtc::{HttpSignalingInfo, SignalingMethod},
@@ -44,15 +45,23 @@ impl P2pConnectionIncomingState {
                     .entry(peer_id)
                     .or_insert_with(|| P2pPeerState {
                         is_libp2p: false,
-                        dial_opts: opts.offer.listen_port.map(|listen_port| {
-                            let signaling = match opts.signaling {
-                                IncomingSignalingMethod::Http => {
-                                    SignalingMethod::Http(HttpSignalingInfo {
-                                        host: opts.offer.host.clone(),
-                                        port: listen_port,
-                                    })
-                                }
-                            };
+                        dial_opts: match (&opts.signaling, opts.offer.listen_port) {
+                            (_, Some(listen_port)) => {
+                                Some(SignalingMethod::Http(HttpSignalingInfo {
+                                    host: opts.offer.host.clone(),
+                                    port: listen_port,
+                                }))
+                            }
+                            // Peer without signaling server can only be
+                            // reached through the relay.
+                            (IncomingSignalingMethod::P2p { relay_peer_id }, None) => {
+                                Some(SignalingMethod::P2p {
+                                    relay_peer_id: *relay_peer_id,
+                                })
+                            }
+                            (IncomingSignalingMethod::Http, None) => None,
+                        }
+                        .map(|signaling| {
                             P2pConnectionOutgoingInitOpts::WebRTC { peer_id, signaling }
                         }),
                         status: P2pPeerStatus::Connecting(P2pConnectionState::incoming_init(opts)),
@@ -144,6 +153,7 @@ impl P2pConnectionIncomingState {
                 let state = p2p_state
                     .incoming_peer_connection_mut(peer_id)
                     .ok_or_else(|| format!("Invalid state for: {:?}", action))?;
+                let mut relay_peer_id = None;
                 if let Self::AnswerSdpCreateSuccess {
                     signaling,
                     offer,
@@ -151,6 +161,9 @@ impl P2pConnectionIncomingState {
                     ..
                 } = state
                 {
+                    if let IncomingSignalingMethod::P2p { relay_peer_id: id } = signaling {
+                        relay_peer_id = Some(*id);
+                    }
                     *state = Self::AnswerReady {
                         time: meta.time(),
                         signaling: signaling.clone(),
@@ -164,12 +177,21 @@ impl P2pConnectionIncomingState {
                         state
                     );
                 }
-                state_context.into_dispatcher().push(
-                    P2pConnectionIncomingEffectfulAction::AnswerSend {
-                        peer_id: *peer_id,
-                        answer: answer.clone(),
-                    },
-                );
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pConnectionIncomingEffectfulAction::AnswerSend {
+                    peer_id: *peer_id,
+                    answer: answer.clone(),
+                });
+                if let Some(relay_peer_id) = relay_peer_id {
+                    dispatcher.push(P2pChannelsSignalingRelayAction::AnswerSend {
+                        peer_id: relay_peer_id,
+                        offerer: *peer_id,
+                        answerer: my_id,
+                        response: Box::new(P2pConnectionResponse::Accepted(answer.clone())),
+                    });
+                    dispatcher
+                        .push(P2pConnectionIncomingAction::AnswerSendSuccess { peer_id: *peer_id });
+                }
                 Ok(())
             }
             P2pConnectionIncomingAction::AnswerSendSuccess { .. } => {
@@ -420,7 +442,7 @@ impl P2pConnectionIncomingState {
             .as_connecting()
             .and_then(|connecting| connecting.as_incoming())
         {
-            if let Err(reason) = p2p_state.libp2p_incoming_accept(peer_id) {
+            if let Err(reason) = p2p_state.libp2p_incoming_accept(peer_id, time) {
                 warn!(time; node_id = display(my_id), summary = "rejecting incoming connection", peer_id = display(peer_id), reason = display(&reason));
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
@@ -15,11 +15,23 @@ mod p2p_connection_reducer;
 mod p2p_connection_service;
 pub use p2p_connection_service::*;
 
+use binprot_derive::{BinProtRead, BinProtWrite};
 use serde::{Deserialize, Serialize};
 
 use crate::webrtc;
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy, thiserror::Error)]
+#[derive(
+    BinProtWrite,
+    BinProtRead,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Debug,
+    Clone,
+    Copy,
+    thiserror::Error,
+)]
 pub enum RejectionReason {
     #[error("peer_id does not match peer's public key")]
     PeerIdAndPublicKeyMismatch,
@@ -31,6 +43,10 @@ pub enum RejectionReason {
     AlreadyConnected,
     #[error("self connection detected")]
     ConnectingToSelf,
+    #[error("peer is banned")]
+    Banned,
+    #[error("relay is not connected to the target peer")]
+    RelayTargetNotConnected,
 }
 
 impl RejectionReason {
@@ -41,6 +57,8 @@ impl RejectionReason {
             Self::PeerCapacityFull => false,
             Self::AlreadyConnected => true,
             Self::ConnectingToSelf => false,
+            Self::Banned => false,
+            Self::RelayTargetNotConnected => false,
         }
     }
 }
@@ -53,7 +71,7 @@ pub enum P2pConnectionErrorResponse {
     InternalError,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
 pub enum P2pConnectionResponse {
     Accepted(Box<webrtc::Answer>),
     Rejected(RejectionReason),
@@ -226,6 +226,8 @@ impl P2pConnectionOutgoingInitOpts {
                         (*peer_id).to_string().into_bytes().into(),
                     ),
                 }),
+                // OCaml nodes can't relay the signaling.
+                SignalingMethod::P2p { .. } => None,
             },
         }
     }
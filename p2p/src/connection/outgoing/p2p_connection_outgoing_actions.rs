@@ -92,10 +92,11 @@ pub enum P2pConnectionOutgoingAction {
 impl redux::EnablingCondition<P2pState> for P2pConnectionOutgoingAction {
     fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
         match self {
-            P2pConnectionOutgoingAction::RandomInit =>  !state.already_has_min_peers() && state.disconnected_peers().next().is_some(),
+            P2pConnectionOutgoingAction::RandomInit =>  !state.already_has_min_peers() && state.disconnected_peers(time).next().is_some(),
             P2pConnectionOutgoingAction::Init { opts, .. } => {
                 !state.already_has_min_peers() &&
                 &state.my_id() != opts.peer_id() &&
+                !state.reputation.is_banned(opts.peer_id(), time) &&
                 state
                     .peers
                     .get(opts.peer_id())
@@ -103,6 +104,7 @@ impl redux::EnablingCondition<P2pState> for P2pConnectionOutgoingAction {
             }
             P2pConnectionOutgoingAction::Reconnect { opts, .. } => {
                 !state.already_has_min_peers()
+                    && !state.reputation.is_banned(opts.peer_id(), time)
                     && state.peers.get(opts.peer_id()).map_or(false, |peer| {
                         peer.can_reconnect(time, &state.config.timeouts)
                     })
@@ -416,6 +416,15 @@ impl P2pConnectionOutgoingState {
                     error: error.clone(),
                     rpc_id,
                 };
+                if let Some(dial_opts) = p2p_state
+                    .peers
+                    .get(peer_id)
+                    .and_then(|peer| peer.dial_opts.clone())
+                {
+                    p2p_state
+                        .address_book
+                        .connection_failed(dial_opts, meta.time());
+                }
 
                 #[cfg(feature = "p2p-libp2p")]
                 {
@@ -2,11 +2,12 @@ use openmina_core::bug_condition;
 use redux::ActionMeta;
 
 use crate::{
+    channels::signaling_relay::P2pChannelsSignalingRelayAction,
     connection::{
         outgoing::{
             P2pConnectionOutgoingAction, P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingState,
         },
-        P2pConnectionService, P2pConnectionState,
+        P2pConnectionErrorResponse, P2pConnectionService, P2pConnectionState, RejectionReason,
     },
     webrtc, P2pPeerStatus,
 };
@@ -14,15 +15,19 @@ use crate::{
 use super::P2pConnectionOutgoingEffectfulAction;
 
 impl P2pConnectionOutgoingEffectfulAction {
-    pub fn effects<Store, S>(self, _meta: &ActionMeta, store: &mut Store)
+    pub fn effects<Store, S>(self, meta: &ActionMeta, store: &mut Store)
     where
         Store: crate::P2pStore<S>,
         Store::Service: P2pConnectionService,
     {
         match self {
             P2pConnectionOutgoingEffectfulAction::RandomInit => {
-                let peers = store.state().disconnected_peers().collect::<Vec<_>>();
-                let picked_peer = store.service().random_pick(&peers);
+                // Pick randomly from the better half of the peers, so that we
+                // prefer peers with good connection history, while not
+                // everyone connects to the same few peers.
+                let peers = store.state().disconnected_peers_by_quality(meta.time());
+                let best_peers = &peers[..peers.len().div_ceil(2)];
+                let picked_peer = store.service().random_pick(best_peers);
                 if let Some(picked_peer) = picked_peer {
                     store.dispatch(P2pConnectionOutgoingAction::Reconnect {
                         opts: picked_peer,
@@ -59,9 +64,25 @@ impl P2pConnectionOutgoingEffectfulAction {
                             return;
                         };
                         service.http_signaling_request(url, *offer);
+                        store.dispatch(P2pConnectionOutgoingAction::OfferSendSuccess { peer_id });
+                    }
+                    webrtc::SignalingMethod::P2p { relay_peer_id } => {
+                        let relay_peer_id = *relay_peer_id;
+                        let is_sent = store.dispatch(P2pChannelsSignalingRelayAction::OfferSend {
+                            peer_id: relay_peer_id,
+                            offer,
+                        });
+                        store.dispatch(P2pConnectionOutgoingAction::OfferSendSuccess { peer_id });
+                        if !is_sent {
+                            store.dispatch(P2pConnectionOutgoingAction::AnswerRecvError {
+                                peer_id,
+                                error: P2pConnectionErrorResponse::Rejected(
+                                    RejectionReason::RelayTargetNotConnected,
+                                ),
+                            });
+                        }
                     }
                 }
-                store.dispatch(P2pConnectionOutgoingAction::OfferSendSuccess { peer_id });
             }
             P2pConnectionOutgoingEffectfulAction::AnswerSet { peer_id, answer } => {
                 store.service().set_answer(peer_id, *answer);
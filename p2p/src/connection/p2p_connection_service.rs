@@ -1,4 +1,4 @@
-use crate::{webrtc, PeerId};
+use crate::{address_book::P2pAddressBook, webrtc, PeerId};
 
 use super::outgoing::P2pConnectionOutgoingInitOpts;
 
@@ -19,4 +19,8 @@ pub trait P2pConnectionService: redux::Service {
     fn set_answer(&mut self, peer_id: PeerId, answer: webrtc::Answer);
 
     fn http_signaling_request(&mut self, url: String, offer: webrtc::Offer);
+
+    /// Persists the address book. Implementation may skip the write if
+    /// the previous one happened recently.
+    fn address_book_save(&mut self, address_book: &P2pAddressBook);
 }
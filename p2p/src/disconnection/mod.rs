@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     channels::{rpc::P2pRpcKind, streaming_rpc::P2pStreamingRpcKind, ChannelId},
     connection::RejectionReason,
+    reputation::P2pPenaltyReason,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, thiserror::Error)]
@@ -39,4 +40,30 @@ pub enum P2pDisconnectionReason {
     Timeout,
     #[error("rpc protocol not supported")]
     Unsupported,
+    #[error("peer is banned")]
+    Banned,
+}
+
+impl P2pDisconnectionReason {
+    /// Penalty to the peer's reputation, if the disconnection was caused by
+    /// peer's misbehavior.
+    pub fn penalty(&self) -> Option<P2pPenaltyReason> {
+        match self {
+            Self::P2pChannelMsgUnexpected(_) => Some(P2pPenaltyReason::ProtocolViolation),
+            Self::TransitionFrontierRpcTimeout(_)
+            | Self::TransitionFrontierStreamingRpcTimeout(_) => Some(P2pPenaltyReason::RpcTimeout),
+            Self::TransitionFrontierSyncLedgerSnarkedNumAccountsRejected => {
+                Some(P2pPenaltyReason::InvalidLedgerAnswer)
+            }
+            Self::SnarkPoolVerifyError => Some(P2pPenaltyReason::SnarkVerifyFailed),
+            Self::P2pChannelSendFailed(_)
+            | Self::P2pChannelReceiveFailed(_)
+            | Self::P2pChannelClosed(_)
+            | Self::Libp2pIncomingRejected(_)
+            | Self::DuplicateConnection
+            | Self::Timeout
+            | Self::Unsupported
+            | Self::Banned => None,
+        }
+    }
 }
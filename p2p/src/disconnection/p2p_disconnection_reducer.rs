@@ -2,8 +2,8 @@ use openmina_core::{bug_condition, Substate};
 use redux::ActionWithMeta;
 
 use crate::{
-    disconnection_effectful::P2pDisconnectionEffectfulAction, P2pNetworkSchedulerAction,
-    P2pPeerAction, P2pPeerStatus, P2pState,
+    disconnection_effectful::P2pDisconnectionEffectfulAction, reputation::P2pReputationAction,
+    P2pNetworkSchedulerAction, P2pPeerAction, P2pPeerStatus, P2pState,
 };
 
 use super::{P2pDisconnectedState, P2pDisconnectionAction};
@@ -22,6 +22,8 @@ impl P2pDisconnectedState {
 
         match action {
             P2pDisconnectionAction::Init { peer_id, reason } => {
+                p2p_state.address_book.disconnected(peer_id, reason);
+
                 #[cfg(feature = "p2p-libp2p")]
                 if p2p_state.is_libp2p_peer(peer_id) {
                     if let Some((&addr, _)) = p2p_state
@@ -43,12 +45,24 @@ impl P2pDisconnectedState {
                             reason: reason.clone(),
                         });
                         dispatcher.push(P2pDisconnectionAction::Finish { peer_id: *peer_id });
+                        if let Some(reason) = reason.penalty() {
+                            dispatcher.push(P2pReputationAction::Penalize {
+                                peer_id: *peer_id,
+                                reason,
+                            });
+                        }
                     }
                     return Ok(());
                 }
 
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(P2pDisconnectionEffectfulAction::Init { peer_id: *peer_id });
+                if let Some(reason) = reason.penalty() {
+                    dispatcher.push(P2pReputationAction::Penalize {
+                        peer_id: *peer_id,
+                        reason,
+                    });
+                }
                 Ok(())
             }
             #[cfg(not(feature = "p2p-libp2p"))]
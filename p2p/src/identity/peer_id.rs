@@ -144,6 +144,15 @@ impl TryFrom<PeerId> for libp2p_identity::PeerId {
     }
 }
 
+impl TryFrom<PeerId> for mina_p2p_messages::v2::NetworkPeerPeerIdStableV1 {
+    type Error = DecodingError;
+
+    fn try_from(value: PeerId) -> Result<Self, Self::Error> {
+        let peer_id = libp2p_identity::PeerId::try_from(value)?;
+        Ok(Self(peer_id.to_string().into_bytes().into()))
+    }
+}
+
 impl Serialize for PeerId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
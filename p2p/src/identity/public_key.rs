@@ -1,5 +1,6 @@
 use std::{fmt, str::FromStr};
 
+use binprot::{BinProtRead, BinProtWrite};
 use ed25519_dalek::VerifyingKey as Ed25519PublicKey;
 use serde::{Deserialize, Serialize};
 
@@ -98,3 +99,20 @@ impl<'de> serde::Deserialize<'de> for PublicKey {
         }
     }
 }
+
+/// Encoded the same way as [`PeerId`], since they share the bytes.
+impl BinProtWrite for PublicKey {
+    fn binprot_write<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        self.peer_id().binprot_write(w)
+    }
+}
+
+impl BinProtRead for PublicKey {
+    fn binprot_read<R: std::io::Read + ?Sized>(r: &mut R) -> Result<Self, binprot::Error>
+    where
+        Self: Sized,
+    {
+        let bytes = PeerId::binprot_read(r)?.to_bytes();
+        Self::from_bytes(bytes).map_err(|err| binprot::Error::CustomError(err.into()))
+    }
+}
@@ -1,13 +1,17 @@
 ///#![feature(trivial_bounds)]
+pub mod address_book;
 pub mod channels;
 pub mod connection;
 pub mod disconnection;
 pub mod disconnection_effectful;
 pub mod identity;
+pub mod reputation;
 use bootstrap::P2pNetworkKadBootstrapState;
 use channels::{
     best_tip::P2pChannelsBestTipAction, best_tip_effectful::P2pChannelsBestTipEffectfulAction,
     rpc::P2pChannelsRpcAction, rpc_effectful::P2pChannelsRpcEffectfulAction,
+    signaling_relay::P2pChannelsSignalingRelayAction,
+    signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction,
     snark::P2pChannelsSnarkAction, snark_effectful::P2pChannelsSnarkEffectfulAction,
     snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
     snark_job_commitment_effectful::P2pChannelsSnarkJobCommitmentEffectfulAction,
@@ -30,6 +34,7 @@ use network::identify::{
     P2pNetworkIdentifyStreamAction,
 };
 use openmina_core::SubstateAccess;
+use reputation::P2pReputationAction;
 
 pub mod webrtc;
 
@@ -131,9 +136,11 @@ pub trait P2pActionTrait<State>:
     + From<P2pNetworkRpcAction>
     + From<P2pChannelsRpcAction>
     + From<P2pDisconnectionAction>
+    + From<P2pReputationAction>
     + From<P2pNetworkSchedulerEffectfulAction>
     + From<P2pChannelsBestTipAction>
     + From<P2pChannelsSnarkJobCommitmentAction>
+    + From<P2pChannelsSignalingRelayAction>
     + From<P2pChannelsStreamingRpcAction>
     + From<P2pConnectionIncomingEffectfulAction>
     + From<P2pConnectionOutgoingEffectfulAction>
@@ -142,6 +149,7 @@ pub trait P2pActionTrait<State>:
     + From<P2pChannelsTransactionEffectfulAction>
     + From<P2pChannelsStreamingRpcEffectfulAction>
     + From<P2pChannelsSnarkJobCommitmentEffectfulAction>
+    + From<P2pChannelsSignalingRelayEffectfulAction>
     + From<P2pChannelsRpcEffectfulAction>
     + From<P2pChannelsSnarkEffectfulAction>
 {
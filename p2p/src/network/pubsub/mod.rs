@@ -7,7 +7,9 @@ pub use self::p2p_network_pubsub_actions::P2pNetworkPubsubAction;
 
 mod p2p_network_pubsub_state;
 pub use self::p2p_network_pubsub_state::{
-    P2pNetworkPubsubClientState, P2pNetworkPubsubClientTopicState, P2pNetworkPubsubState,
+    P2pNetworkPubsubClientState, P2pNetworkPubsubClientTopicState, P2pNetworkPubsubMessageContent,
+    P2pNetworkPubsubMessageId, P2pNetworkPubsubPeerScore, P2pNetworkPubsubPendingMessage,
+    P2pNetworkPubsubState, P2pNetworkPubsubValidationResult,
 };
 
 #[cfg(feature = "p2p-libp2p")]
@@ -1,4 +1,4 @@
-use super::pb;
+use super::{pb, P2pNetworkPubsubMessageId, P2pNetworkPubsubValidationResult};
 use crate::{token::BroadcastAlgorithm, ConnectionAddr, Data, P2pState, PeerId, StreamId};
 use mina_p2p_messages::gossip::GossipNetMessageV2;
 use openmina_core::ActionEvent;
@@ -25,6 +25,18 @@ pub enum P2pNetworkPubsubAction {
         message: pb::Message,
         seen_limit: usize,
     },
+    /// Node's verdict on the received message. Accepted message gets
+    /// forwarded to the mesh, rejected one penalizes the peer it came from.
+    #[action_event(level = debug, fields(display(message_id), debug(result)))]
+    ValidateIncomingMessage {
+        message_id: P2pNetworkPubsubMessageId,
+        result: P2pNetworkPubsubValidationResult,
+    },
+    /// Periodic mesh maintenance: prunes peers with negative score, grafts
+    /// or prunes peers to keep the mesh degree within the bounds, gossips
+    /// ids of recent messages to peers outside of the mesh and decays
+    /// the scores.
+    Heartbeat,
     Graft {
         peer_id: PeerId,
         topic_id: String,
@@ -72,7 +84,18 @@ impl From<P2pNetworkPubsubAction> for crate::P2pAction {
 }
 
 impl redux::EnablingCondition<P2pState> for P2pNetworkPubsubAction {
-    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
-        true
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        let pubsub = &state.network.scheduler.broadcast_state;
+        match self {
+            P2pNetworkPubsubAction::ValidateIncomingMessage { message_id, .. } => {
+                pubsub.pending_validation.contains_key(message_id)
+            }
+            P2pNetworkPubsubAction::Heartbeat => pubsub.last_heartbeat.map_or(true, |last| {
+                time.checked_sub(last).map_or(false, |elapsed| {
+                    elapsed >= state.config.meshsub.heartbeat_interval
+                })
+            }),
+            _ => true,
+        }
     }
 }
@@ -1,35 +1,46 @@
-use std::{collections::btree_map::Entry, sync::Arc};
+use std::{
+    collections::{btree_map::Entry, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
 
 use binprot::BinProtRead;
 use mina_p2p_messages::{gossip, v2};
 use openmina_core::{block::BlockWithHash, bug_condition, fuzz_maybe, fuzzed_maybe, Substate};
-use redux::Dispatcher;
+use redux::{Dispatcher, Timestamp};
 
 use crate::{
     channels::{snark::P2pChannelsSnarkAction, transaction::P2pChannelsTransactionAction},
     peer::P2pPeerAction,
-    Data, P2pConfig, P2pNetworkYamuxAction, PeerId,
+    Data, P2pConfig, P2pMeshsubConfig, P2pNetworkYamuxAction, PeerId,
 };
 
 use super::{
-    p2p_network_pubsub_state::P2pNetworkPubsubClientMeshAddingState,
+    p2p_network_pubsub_state::{compute_message_id, P2pNetworkPubsubClientMeshAddingState},
     pb::{self, Message},
     P2pNetworkPubsubAction, P2pNetworkPubsubClientState, P2pNetworkPubsubClientTopicState,
-    P2pNetworkPubsubEffectfulAction, P2pNetworkPubsubState, TOPIC,
+    P2pNetworkPubsubEffectfulAction, P2pNetworkPubsubMessageContent, P2pNetworkPubsubMessageId,
+    P2pNetworkPubsubPendingMessage, P2pNetworkPubsubState, P2pNetworkPubsubValidationResult, TOPIC,
 };
 
+/// Maximum number of message ids we gossip or request from a peer at once.
+const MAX_IHAVE_LENGTH: usize = 5000;
+
 impl P2pNetworkPubsubState {
     pub fn reducer<Action, State>(
         mut state_context: Substate<Action, State, Self>,
         action: redux::ActionWithMeta<&P2pNetworkPubsubAction>,
+        meshsub: &P2pMeshsubConfig,
     ) -> Result<(), String>
     where
         State: crate::P2pStateTrait,
         Action: crate::P2pActionTrait<State>,
     {
         let pubsub_state = state_context.get_substate_mut()?;
+        let (action, meta) = action.split();
 
-        match action.action() {
+        match action {
             P2pNetworkPubsubAction::NewStream {
                 incoming: true,
                 peer_id,
@@ -60,7 +71,8 @@ impl P2pNetworkPubsubState {
 
                 pubsub_state
                     .topics
-                    .insert(super::TOPIC.to_owned(), Default::default());
+                    .entry(super::TOPIC.to_owned())
+                    .or_default();
 
                 Ok(())
             }
@@ -90,7 +102,6 @@ impl P2pNetworkPubsubState {
                 state.addr = *addr;
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
-                let config: &P2pConfig = state.substate()?;
                 let state: &P2pNetworkPubsubState = state.substate()?;
 
                 let Some(map) = state.topics.get(TOPIC) else {
@@ -109,7 +120,7 @@ impl P2pNetworkPubsubState {
                     peer_id: *peer_id,
                 });
                 let mesh_size = map.values().filter(|s| s.on_mesh()).count();
-                if mesh_size < config.meshsub.outbound_degree_desired {
+                if mesh_size < meshsub.outbound_degree_desired {
                     dispatcher.push(P2pNetworkPubsubAction::Graft {
                         peer_id: *peer_id,
                         topic_id: TOPIC.to_owned(),
@@ -124,135 +135,150 @@ impl P2pNetworkPubsubState {
                 seen_limit,
                 ..
             } => {
-                pubsub_state.reduce_incoming_data(peer_id, data)?;
+                pubsub_state.reduce_incoming_data(peer_id, data, meshsub, meta.time())?;
 
-                let dispatcher: &mut Dispatcher<Action, State> = state_context.into_dispatcher();
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 dispatcher.push(P2pNetworkPubsubEffectfulAction::IncomingData {
                     peer_id: *peer_id,
                     seen_limit: *seen_limit,
                 });
 
-                Ok(())
+                // send out responses to the control messages
+                broadcast(dispatcher, state)
             }
             P2pNetworkPubsubAction::IncomingMessage {
                 peer_id,
                 message,
                 seen_limit,
             } => {
-                pubsub_state.reduce_incoming_message(peer_id, message, *seen_limit)?;
+                let Some(message_id) = pubsub_state.reduce_incoming_message(
+                    peer_id,
+                    message,
+                    *seen_limit,
+                    meta.time(),
+                ) else {
+                    return Ok(());
+                };
 
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
                 let state: &Self = global_state.substate()?;
-                let config: &P2pConfig = global_state.substate()?;
                 let peer_id = *peer_id;
+                let Some(pending) = state.pending_validation.get(&message_id) else {
+                    return Ok(());
+                };
 
-                let incoming_block = state.incoming_block.as_ref().cloned();
-                let incoming_transactions = state.incoming_transactions.clone();
-                let incoming_snarks = state.incoming_snarks.clone();
-                let topics = state.topics.clone();
-
-                for (topic_id, map) in topics {
-                    let mesh_size = map.values().filter(|s| s.on_mesh()).count();
-                    let could_accept = mesh_size < config.meshsub.outbound_degree_high;
-
-                    if !could_accept {
-                        if let Some(topic_state) = map.get(&peer_id) {
-                            if topic_state.on_mesh() {
-                                dispatcher.push(P2pNetworkPubsubAction::Prune { peer_id, topic_id })
-                            }
+                // Message is forwarded only after the node validates it,
+                // see `P2pNetworkPubsubAction::ValidateIncomingMessage`.
+                match &pending.content {
+                    P2pNetworkPubsubMessageContent::Block(hash) => {
+                        if let Some((_, block)) = state.incoming_block.as_ref() {
+                            let best_tip = BlockWithHash {
+                                hash: hash.clone(),
+                                block: Arc::new(block.clone()),
+                            };
+                            dispatcher.push(P2pPeerAction::BestTipUpdate { peer_id, best_tip });
+                        } else {
+                            dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessage {
+                                message_id,
+                                result: P2pNetworkPubsubValidationResult::Ignore,
+                            });
+                        }
+                    }
+                    P2pNetworkPubsubMessageContent::Transactions => {
+                        let nonce = state
+                            .incoming_transactions
+                            .first()
+                            .map_or(0, |(_, nonce)| *nonce);
+                        dispatcher.push(P2pChannelsTransactionAction::Libp2pReceived {
+                            peer_id,
+                            transactions: state
+                                .incoming_transactions
+                                .iter()
+                                .map(|(tx, _)| tx.clone())
+                                .collect(),
+                            nonce,
+                            message_id,
+                        });
+                    }
+                    P2pNetworkPubsubMessageContent::Snark(_) => {
+                        for (snark, nonce) in state.incoming_snarks.iter().cloned() {
+                            dispatcher.push(P2pChannelsSnarkAction::Libp2pReceived {
+                                peer_id,
+                                snark: Box::new(snark),
+                                nonce,
+                            });
                         }
                     }
-                }
-
-                broadcast(dispatcher, global_state)?;
-                if let Some((_, block)) = incoming_block {
-                    let best_tip = BlockWithHash::try_new(Arc::new(block))?;
-                    dispatcher.push(P2pPeerAction::BestTipUpdate { peer_id, best_tip });
-                }
-                for (transaction, nonce) in incoming_transactions {
-                    dispatcher.push(P2pChannelsTransactionAction::Libp2pReceived {
-                        peer_id,
-                        transaction: Box::new(transaction),
-                        nonce,
-                    });
-                }
-                for (snark, nonce) in incoming_snarks {
-                    dispatcher.push(P2pChannelsSnarkAction::Libp2pReceived {
-                        peer_id,
-                        snark: Box::new(snark),
-                        nonce,
-                    });
                 }
                 Ok(())
             }
-            // we want to add peer to our mesh
-            P2pNetworkPubsubAction::Graft { peer_id, topic_id } => {
-                let Some(state) = pubsub_state
-                    .topics
-                    .get_mut(topic_id)
-                    .and_then(|m| m.get_mut(peer_id))
-                else {
+            P2pNetworkPubsubAction::ValidateIncomingMessage { message_id, result } => {
+                let Some(pending) = pubsub_state.pending_validation.remove(message_id) else {
+                    bug_condition!("Pending message not found for action: {action:?}");
                     return Ok(());
                 };
 
-                state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
+                match result {
+                    P2pNetworkPubsubValidationResult::Accept => {
+                        pubsub_state
+                            .scores
+                            .entry(pending.peer_id)
+                            .or_default()
+                            .first_message_delivered(&meshsub.score);
+                        pubsub_state.mcache.put(pending.message.clone());
+
+                        let topic = pubsub_state.topics.get(&pending.message.topic);
+                        pubsub_state
+                            .clients
+                            .iter_mut()
+                            // don't send back to who sent this
+                            .filter(|(c, _)| **c != pending.peer_id)
+                            .filter(|(c, _)| {
+                                topic
+                                    .and_then(|topic| topic.get(c))
+                                    .map_or(false, |s| s.on_mesh())
+                            })
+                            .for_each(|(_, state)| {
+                                state.message.publish.push(pending.message.clone())
+                            });
+
+                        let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                        broadcast(dispatcher, state)
+                    }
+                    P2pNetworkPubsubValidationResult::Reject => {
+                        pubsub_state
+                            .scores
+                            .entry(pending.peer_id)
+                            .or_default()
+                            .invalid_message_deliveries += 1.0;
+                        Ok(())
+                    }
+                    P2pNetworkPubsubValidationResult::Ignore => Ok(()),
+                }
+            }
+            P2pNetworkPubsubAction::Heartbeat => {
+                pubsub_state.heartbeat(meshsub, meta.time());
 
-                let dispatcher = state_context.into_dispatcher();
-                let msg = pb::Rpc {
-                    subscriptions: vec![],
-                    publish: vec![],
-                    control: Some(pb::ControlMessage {
-                        ihave: vec![],
-                        iwant: vec![],
-                        graft: vec![pb::ControlGraft {
-                            topic_id: Some(topic_id.clone()),
-                        }],
-                        prune: vec![],
-                    }),
-                };
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                broadcast(dispatcher, state)
+            }
+            // we want to add peer to our mesh
+            P2pNetworkPubsubAction::Graft { peer_id, topic_id } => {
+                if !pubsub_state.graft(peer_id, topic_id, meta.time()) {
+                    return Ok(());
+                }
 
-                dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage {
-                    msg,
-                    peer_id: *peer_id,
-                });
-                Ok(())
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                broadcast(dispatcher, state)
             }
             P2pNetworkPubsubAction::Prune { peer_id, topic_id } => {
-                let Some(state) = pubsub_state
-                    .topics
-                    .get_mut(topic_id)
-                    .and_then(|m| m.get_mut(peer_id))
-                else {
+                if !pubsub_state.prune(peer_id, topic_id, meshsub.prune_backoff, meta.time()) {
                     bug_condition!("State not found for action: {action:?}");
                     return Ok(());
-                };
-
-                state.mesh = P2pNetworkPubsubClientMeshAddingState::WeRefused;
-
-                let dispatcher = state_context.into_dispatcher();
-                let msg = pb::Rpc {
-                    subscriptions: vec![],
-                    publish: vec![],
-                    control: Some(pb::ControlMessage {
-                        ihave: vec![],
-                        iwant: vec![],
-                        graft: vec![],
-                        prune: vec![pb::ControlPrune {
-                            topic_id: Some(topic_id.clone()),
-                            peers: vec![pb::PeerInfo {
-                                peer_id: None,
-                                signed_peer_record: None,
-                            }],
-                            backoff: None,
-                        }],
-                    }),
-                };
+                }
 
-                dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage {
-                    msg,
-                    peer_id: *peer_id,
-                });
-                Ok(())
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                broadcast(dispatcher, state)
             }
             P2pNetworkPubsubAction::OutgoingMessage { peer_id, msg } => {
                 if let Some(v) = pubsub_state.clients.get_mut(peer_id) {
@@ -339,9 +365,25 @@ impl P2pNetworkPubsubState {
             P2pNetworkPubsubAction::BroadcastSigned { signature } => {
                 if let Some(mut message) = pubsub_state.to_sign.pop_front() {
                     message.signature = Some(signature.clone().0.to_vec());
+                    if let Some(message_id) = pubsub_state.mcache.put(message.clone()) {
+                        pubsub_state.seen.push_back(message_id);
+                        if pubsub_state.seen.len() > meshsub.mcache_len {
+                            pubsub_state.seen.pop_front();
+                        }
+                    }
+
+                    // Own messages are published to all peers with good
+                    // enough score, not just the mesh.
+                    let scores = &pubsub_state.scores;
                     pubsub_state
                         .clients
                         .iter_mut()
+                        .filter(|(peer_id, _)| {
+                            scores
+                                .get(peer_id)
+                                .map_or(0.0, |score| score.value(&meshsub.score, meta.time()))
+                                >= meshsub.score.publish_threshold
+                        })
                         .for_each(|(_, state)| state.message.publish.push(message.clone()));
                 }
 
@@ -372,89 +414,103 @@ impl P2pNetworkPubsubState {
         }
     }
 
+    /// Decodes the message and puts it into the `pending_validation`.
+    /// Returns its id, unless it is a duplicate or can't be decoded.
     fn reduce_incoming_message(
         &mut self,
         peer_id: &PeerId,
         message: &Message,
         seen_limit: usize,
-    ) -> Result<(), String> {
+        now: Timestamp,
+    ) -> Option<P2pNetworkPubsubMessageId> {
         self.incoming_transactions.clear();
         self.incoming_snarks.clear();
 
         let Some(state) = self.clients.get_mut(peer_id) else {
             bug_condition!("State not found for action P2pNetworkPubsubAction::IncomingMessage");
-            return Ok(());
+            return None;
         };
         state.incoming_messages.clear();
 
-        let message_id = self.mcache.put(message.clone());
-
-        let topic = self.topics.entry(message.topic.clone()).or_default();
+        let Some(message_id) = compute_message_id(message) else {
+            self.scores
+                .entry(*peer_id)
+                .or_default()
+                .invalid_message_deliveries += 1.0;
+            return None;
+        };
+        self.iwant_promises.remove(&message_id);
 
-        if let Some(signature) = &message.signature {
-            // skip recently seen message
-            if !self.seen.contains(signature) {
-                self.seen.push_back(signature.clone());
-                // keep only last `n` to avoid memory leak
-                if self.seen.len() > seen_limit {
-                    self.seen.pop_front();
-                }
-            } else {
-                return Ok(());
-            }
+        // skip recently seen message
+        if self.seen.contains(&message_id) {
+            return None;
+        }
+        self.seen.push_back(message_id.clone());
+        // keep only last `n` to avoid memory leak
+        if self.seen.len() > seen_limit {
+            self.seen.pop_front();
         }
 
-        self.clients
-            .iter_mut()
-            .filter(|(c, _)| {
-                // don't send back to who sent this
-                **c != *peer_id
-            })
-            .for_each(|(c, state)| {
-                let Some(topic_state) = topic.get(c) else {
-                    return;
-                };
-                if topic_state.on_mesh() {
-                    state.message.publish.push(message.clone())
-                } else {
-                    let ctr = state.message.control.get_or_insert_with(Default::default);
-                    ctr.ihave.push(pb::ControlIHave {
-                        topic_id: Some(message.topic.clone()),
-                        message_ids: message_id.clone().into_iter().collect(),
-                    })
-                }
-            });
-
-        if let Some(data) = &message.data {
-            if data.len() > 8 {
-                let mut slice = &data[8..];
-                match gossip::GossipNetMessageV2::binprot_read(&mut slice) {
-                    Ok(gossip::GossipNetMessageV2::NewState(block)) => {
-                        self.incoming_block = Some((*peer_id, block));
-                    }
-                    Ok(gossip::GossipNetMessageV2::TransactionPoolDiff { message, nonce }) => {
-                        let nonce = nonce.as_u32();
-                        let txs = message.0.into_iter().map(|tx| (tx, nonce));
-                        self.incoming_transactions.extend(txs);
-                    }
-                    Ok(gossip::GossipNetMessageV2::SnarkPoolDiff { message, nonce }) => {
-                        if let v2::NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work) =
-                            message
-                        {
-                            self.incoming_snarks.push((work.1.into(), nonce.as_u32()));
-                        }
-                    }
-                    Err(err) => {
-                        return Err(err.to_string());
+        let decoded = message
+            .data
+            .as_ref()
+            .filter(|data| data.len() > 8)
+            .and_then(|data| gossip::GossipNetMessageV2::binprot_read(&mut &data[8..]).ok());
+        let content = match decoded {
+            Some(gossip::GossipNetMessageV2::NewState(block)) => {
+                block.try_hash().ok().map(|hash| {
+                    self.incoming_block = Some((*peer_id, block));
+                    P2pNetworkPubsubMessageContent::Block(hash)
+                })
+            }
+            Some(gossip::GossipNetMessageV2::TransactionPoolDiff { message, nonce }) => {
+                let nonce = nonce.as_u32();
+                let txs = message.0.into_iter().map(|tx| (tx, nonce));
+                self.incoming_transactions.extend(txs);
+                Some(P2pNetworkPubsubMessageContent::Transactions)
+            }
+            Some(gossip::GossipNetMessageV2::SnarkPoolDiff { message, nonce }) => {
+                match message {
+                    v2::NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work) => {
+                        let snark: openmina_core::snark::Snark = work.1.into();
+                        let job_id = snark.job_id();
+                        self.incoming_snarks.push((snark, nonce.as_u32()));
+                        Some(P2pNetworkPubsubMessageContent::Snark(job_id))
                     }
+                    // nothing to validate or forward
+                    v2::NetworkPoolSnarkPoolDiffVersionedStableV2::Empty => return None,
                 }
             }
-        }
+            None => None,
+        };
+        let Some(content) = content else {
+            self.scores
+                .entry(*peer_id)
+                .or_default()
+                .invalid_message_deliveries += 1.0;
+            return None;
+        };
 
-        Ok(())
+        self.pending_validation.insert(
+            message_id.clone(),
+            P2pNetworkPubsubPendingMessage {
+                peer_id: *peer_id,
+                message: message.clone(),
+                content,
+                received_at: now,
+            },
+        );
+        Some(message_id)
     }
 
-    fn reduce_incoming_data(&mut self, peer_id: &PeerId, data: &Data) -> Result<(), String> {
+    fn reduce_incoming_data(
+        &mut self,
+        peer_id: &PeerId,
+        data: &Data,
+        meshsub: &P2pMeshsubConfig,
+        now: Timestamp,
+    ) -> Result<(), String> {
+        let score = self.score(peer_id, &meshsub.score, now);
         let Some(state) = self.clients.get_mut(peer_id) else {
             // TODO: investigate, cannot reproduce this
             // bug_condition!("State not found for action: P2pNetworkPubsubAction::IncomingData");
@@ -471,12 +527,9 @@ impl P2pNetworkPubsubState {
         match <pb::Rpc as prost::Message>::decode_length_delimited(slice) {
             Ok(v) => {
                 state.buffer.clear();
-                // println!(
-                //     "(pubsub) this <- {peer_id}, {:?}, {:?}, {}",
-                //     v.subscriptions,
-                //     v.control,
-                //     v.publish.len()
-                // );
+                if score < meshsub.score.graylist_threshold {
+                    return Ok(());
+                }
 
                 subscriptions.extend_from_slice(&v.subscriptions);
                 state.incoming_messages.extend_from_slice(&v.publish);
@@ -509,12 +562,35 @@ impl P2pNetworkPubsubState {
         }
 
         for graft in &control.graft {
-            if let Some(mesh_state) = self
+            let topic_id = graft.topic_id();
+            if topic_id != TOPIC {
+                // we are not subscribed to it
+                continue;
+            }
+            let Some(topic) = self.topics.get(topic_id) else {
+                continue;
+            };
+            let mesh_size = topic.values().filter(|s| s.on_mesh()).count();
+            let Some(mesh_state) = topic.get(peer_id) else {
+                continue;
+            };
+            if mesh_state.on_mesh() {
+                continue;
+            }
+
+            if mesh_state.is_backed_off(now) {
+                // grafting during backoff is a misbehaviour
+                self.scores.entry(*peer_id).or_default().behaviour_penalty += 1.0;
+                self.prune(peer_id, topic_id, meshsub.prune_backoff, now);
+            } else if score < 0.0 || mesh_size >= meshsub.outbound_degree_high {
+                self.prune(peer_id, topic_id, meshsub.prune_backoff, now);
+            } else if let Some(mesh_state) = self
                 .topics
-                .get_mut(graft.topic_id())
+                .get_mut(topic_id)
                 .and_then(|m| m.get_mut(peer_id))
             {
                 mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
+                self.scores.entry(*peer_id).or_default().mesh_since = Some(now);
             }
         }
         for prune in &control.prune {
@@ -523,12 +599,25 @@ impl P2pNetworkPubsubState {
                 .get_mut(prune.topic_id())
                 .and_then(|m| m.get_mut(peer_id))
             {
+                let backoff = prune
+                    .backoff
+                    .map_or(meshsub.prune_backoff, Duration::from_secs);
                 mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::TheyRefused;
+                mesh_state.backoff_until = Some(timestamp_add(now, backoff));
+                if let Some(score) = self.scores.get_mut(peer_id) {
+                    score.mesh_since = None;
+                }
             }
         }
+
+        if score < meshsub.score.gossip_threshold {
+            return Ok(());
+        }
+
         for iwant in &control.iwant {
             for msg_id in &iwant.message_ids {
-                if let Some(msg) = self.mcache.map.get(msg_id) {
+                let msg_id = P2pNetworkPubsubMessageId(msg_id.clone());
+                if let Some(msg) = self.mcache.map.get(&msg_id) {
                     if let Some(client) = self.clients.get_mut(peer_id) {
                         client.message.publish.push(msg.clone());
                     }
@@ -536,20 +625,238 @@ impl P2pNetworkPubsubState {
             }
         }
 
-        for ihave in &control.ihave {
-            let message_ids = ihave
-                .message_ids
-                .iter()
-                .filter(|msg_id| !self.mcache.map.contains_key(*msg_id))
-                .cloned()
-                .collect();
+        let message_ids = control
+            .ihave
+            .iter()
+            .flat_map(|ihave| &ihave.message_ids)
+            .map(|msg_id| P2pNetworkPubsubMessageId(msg_id.clone()))
+            .filter(|msg_id| {
+                !self.mcache.contains(msg_id)
+                    && !self.seen.contains(msg_id)
+                    && !self.pending_validation.contains_key(msg_id)
+                    && !self.iwant_promises.contains_key(msg_id)
+            })
+            .take(MAX_IHAVE_LENGTH)
+            .collect::<Vec<_>>();
+        if !message_ids.is_empty() {
             if let Some(client) = self.clients.get_mut(peer_id) {
+                let deadline = timestamp_add(now, meshsub.iwant_followup_time);
+                for msg_id in &message_ids {
+                    self.iwant_promises
+                        .insert(msg_id.clone(), (*peer_id, deadline));
+                }
                 let ctr = client.message.control.get_or_insert_with(Default::default);
-                ctr.iwant.push(pb::ControlIWant { message_ids })
+                ctr.iwant.push(pb::ControlIWant {
+                    message_ids: message_ids.into_iter().map(|id| id.0).collect(),
+                })
             }
         }
         Ok(())
     }
+
+    /// Adds the peer to our mesh and queues GRAFT for it. Returns `false`
+    /// if the peer can't be grafted.
+    fn graft(&mut self, peer_id: &PeerId, topic_id: &str, now: Timestamp) -> bool {
+        let Some(topic_state) = self
+            .topics
+            .get_mut(topic_id)
+            .and_then(|m| m.get_mut(peer_id))
+        else {
+            return false;
+        };
+        if topic_state.on_mesh() || topic_state.is_backed_off(now) {
+            return false;
+        }
+        let Some(client) = self.clients.get_mut(peer_id) else {
+            return false;
+        };
+
+        topic_state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
+        let ctr = client.message.control.get_or_insert_with(Default::default);
+        ctr.graft.push(pb::ControlGraft {
+            topic_id: Some(topic_id.to_owned()),
+        });
+        self.scores.entry(*peer_id).or_default().mesh_since = Some(now);
+        true
+    }
+
+    /// Removes the peer from our mesh, queues PRUNE for it and backs it off.
+    /// Returns `false` if the peer isn't subscribed to the topic.
+    fn prune(
+        &mut self,
+        peer_id: &PeerId,
+        topic_id: &str,
+        backoff: Duration,
+        now: Timestamp,
+    ) -> bool {
+        let Some(topic_state) = self
+            .topics
+            .get_mut(topic_id)
+            .and_then(|m| m.get_mut(peer_id))
+        else {
+            return false;
+        };
+
+        topic_state.mesh = P2pNetworkPubsubClientMeshAddingState::WeRefused;
+        topic_state.backoff_until = Some(timestamp_add(now, backoff));
+        if let Some(client) = self.clients.get_mut(peer_id) {
+            let ctr = client.message.control.get_or_insert_with(Default::default);
+            ctr.prune.push(pb::ControlPrune {
+                topic_id: Some(topic_id.to_owned()),
+                peers: vec![],
+                backoff: Some(backoff.as_secs()),
+            });
+        }
+        if let Some(score) = self.scores.get_mut(peer_id) {
+            score.mesh_since = None;
+        }
+        true
+    }
+
+    fn heartbeat(&mut self, meshsub: &P2pMeshsubConfig, now: Timestamp) {
+        self.last_heartbeat = Some(now);
+
+        // Node didn't validate these in time, drop them.
+        self.pending_validation.retain(|_, pending| {
+            now.checked_sub(pending.received_at)
+                .map_or(true, |elapsed| elapsed < meshsub.validation_timeout)
+        });
+
+        let mut broken_promises = vec![];
+        self.iwant_promises.retain(|_, (peer_id, deadline)| {
+            let keep = now < *deadline;
+            if !keep {
+                broken_promises.push(*peer_id);
+            }
+            keep
+        });
+        for peer_id in broken_promises {
+            self.scores.entry(peer_id).or_default().behaviour_penalty += 1.0;
+        }
+
+        self.maintain_mesh(meshsub, now);
+
+        self.mcache.shift(meshsub.history_length);
+
+        let clients = &self.clients;
+        self.scores.retain(|peer_id, score| {
+            score.decay(&meshsub.score);
+            clients.contains_key(peer_id) || !score.is_neutral()
+        });
+    }
+
+    fn maintain_mesh(&mut self, meshsub: &P2pMeshsubConfig, now: Timestamp) {
+        let Some(topic) = self.topics.get_mut(TOPIC) else {
+            return;
+        };
+
+        // Disconnected peers are no longer in the mesh.
+        for (peer_id, topic_state) in topic.iter_mut() {
+            if topic_state.on_mesh() && !self.clients.contains_key(peer_id) {
+                topic_state.mesh = P2pNetworkPubsubClientMeshAddingState::Initial;
+                if let Some(score) = self.scores.get_mut(peer_id) {
+                    score.mesh_since = None;
+                }
+            }
+        }
+
+        let seed = u64::from(now);
+        let mut peers = topic
+            .iter()
+            .filter(|(peer_id, _)| {
+                self.clients
+                    .get(peer_id)
+                    .map_or(false, |client| client.outgoing_stream_id.is_some())
+            })
+            .map(|(peer_id, topic_state)| {
+                let score = self
+                    .scores
+                    .get(peer_id)
+                    .map_or(0.0, |score| score.value(&meshsub.score, now));
+                (*peer_id, score, topic_state.clone())
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|(peer_id, ..)| shuffle_key(peer_id, seed));
+
+        let mut mesh = peers
+            .iter()
+            .filter(|(_, _, topic_state)| topic_state.on_mesh())
+            .map(|(peer_id, score, _)| (*peer_id, *score))
+            .collect::<Vec<_>>();
+        let mut to_prune = mesh
+            .iter()
+            .filter(|(_, score)| *score < 0.0)
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        mesh.retain(|(_, score)| *score >= 0.0);
+
+        let mut to_graft = vec![];
+        if mesh.len() < meshsub.outbound_degree_low {
+            to_graft.extend(
+                peers
+                    .iter()
+                    .filter(|(_, score, topic_state)| {
+                        *score >= 0.0 && !topic_state.on_mesh() && !topic_state.is_backed_off(now)
+                    })
+                    .map(|(peer_id, ..)| *peer_id)
+                    .take(meshsub.outbound_degree_desired.saturating_sub(mesh.len())),
+            );
+        } else if mesh.len() > meshsub.outbound_degree_high {
+            // keep the best scoring peers
+            mesh.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+            to_prune.extend(
+                mesh.drain(meshsub.outbound_degree_desired..)
+                    .map(|(peer_id, _)| peer_id),
+            );
+        }
+
+        let gossip_to = peers
+            .iter()
+            .filter(|(peer_id, score, topic_state)| {
+                *score >= meshsub.score.gossip_threshold
+                    && !topic_state.on_mesh()
+                    && !to_graft.contains(peer_id)
+            })
+            .map(|(peer_id, ..)| *peer_id)
+            .take(meshsub.outbound_degree_lazy)
+            .collect::<Vec<_>>();
+        let message_ids = self
+            .mcache
+            .gossip_ids(meshsub.history_gossip)
+            .take(MAX_IHAVE_LENGTH)
+            .map(|id| id.0.clone())
+            .collect::<Vec<_>>();
+
+        for peer_id in &to_prune {
+            self.prune(peer_id, TOPIC, meshsub.prune_backoff, now);
+        }
+        for peer_id in &to_graft {
+            self.graft(peer_id, TOPIC, now);
+        }
+        if !message_ids.is_empty() {
+            for peer_id in &gossip_to {
+                if let Some(client) = self.clients.get_mut(peer_id) {
+                    let ctr = client.message.control.get_or_insert_with(Default::default);
+                    ctr.ihave.push(pb::ControlIHave {
+                        topic_id: Some(TOPIC.to_owned()),
+                        message_ids: message_ids.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Order of peers which is deterministic, but changes with the `seed`, so
+/// that the same peers aren't always picked first.
+fn shuffle_key(peer_id: &PeerId, seed: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (peer_id.to_bytes(), seed).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn timestamp_add(time: Timestamp, duration: Duration) -> Timestamp {
+    Timestamp::new(u64::from(time).saturating_add(duration.as_nanos() as u64))
 }
 
 fn message_is_empty(msg: &pb::Rpc) -> bool {
@@ -578,3 +885,114 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{token::BroadcastAlgorithm, ConnectionAddr};
+
+    use super::*;
+
+    fn state_with_peers(
+        count: u8,
+        mesh: P2pNetworkPubsubClientMeshAddingState,
+    ) -> P2pNetworkPubsubState {
+        let mut state = P2pNetworkPubsubState::default();
+        for i in 0..count {
+            let peer_id = PeerId::from_bytes([i; 32]);
+            state.clients.insert(
+                peer_id,
+                P2pNetworkPubsubClientState {
+                    protocol: BroadcastAlgorithm::Meshsub1_1_0,
+                    addr: ConnectionAddr {
+                        sock_addr: "127.0.0.1:8302".parse().unwrap(),
+                        incoming: false,
+                    },
+                    outgoing_stream_id: Some(1),
+                    message: Default::default(),
+                    buffer: vec![],
+                    incoming_messages: vec![],
+                },
+            );
+            state.topics.entry(TOPIC.to_owned()).or_default().insert(
+                peer_id,
+                P2pNetworkPubsubClientTopicState {
+                    mesh,
+                    backoff_until: None,
+                },
+            );
+        }
+        state
+    }
+
+    fn mesh_peers(state: &P2pNetworkPubsubState) -> Vec<PeerId> {
+        state.topics[TOPIC]
+            .iter()
+            .filter(|(_, topic_state)| topic_state.on_mesh())
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_maintain_mesh_grafts_up_to_desired_degree() {
+        let config = P2pMeshsubConfig::default();
+        let mut state = state_with_peers(10, P2pNetworkPubsubClientMeshAddingState::Initial);
+
+        state.maintain_mesh(&config, Timestamp::new(1_000_000_000));
+
+        let mesh = mesh_peers(&state);
+        assert_eq!(mesh.len(), config.outbound_degree_desired);
+        for peer_id in &mesh {
+            let control = state.clients[peer_id].message.control.as_ref().unwrap();
+            assert_eq!(control.graft.len(), 1);
+            assert!(state.scores[peer_id].mesh_since.is_some());
+        }
+    }
+
+    #[test]
+    fn test_maintain_mesh_prunes_down_to_desired_degree() {
+        let config = P2pMeshsubConfig::default();
+        let now = Timestamp::new(1_000_000_000);
+        let mut state = state_with_peers(14, P2pNetworkPubsubClientMeshAddingState::Added);
+
+        state.maintain_mesh(&config, now);
+
+        assert_eq!(mesh_peers(&state).len(), config.outbound_degree_desired);
+        let pruned = state.topics[TOPIC]
+            .values()
+            .filter(|topic_state| {
+                topic_state.mesh == P2pNetworkPubsubClientMeshAddingState::WeRefused
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pruned.len(), 14 - config.outbound_degree_desired);
+        assert!(pruned
+            .iter()
+            .all(|topic_state| topic_state.is_backed_off(now)));
+    }
+
+    #[test]
+    fn test_maintain_mesh_prunes_negative_score_and_disconnected() {
+        let config = P2pMeshsubConfig::default();
+        let now = Timestamp::new(1_000_000_000);
+        let mut state = state_with_peers(6, P2pNetworkPubsubClientMeshAddingState::Added);
+        let misbehaving = PeerId::from_bytes([0; 32]);
+        let disconnected = PeerId::from_bytes([1; 32]);
+        state
+            .scores
+            .entry(misbehaving)
+            .or_default()
+            .behaviour_penalty = 100.0;
+        state.clients.remove(&disconnected);
+
+        state.maintain_mesh(&config, now);
+
+        let mesh = mesh_peers(&state);
+        assert_eq!(mesh.len(), 4);
+        assert!(!mesh.contains(&misbehaving));
+        assert!(!mesh.contains(&disconnected));
+        assert!(state.topics[TOPIC][&misbehaving].is_backed_off(now));
+        assert_eq!(
+            state.topics[TOPIC][&disconnected].mesh,
+            P2pNetworkPubsubClientMeshAddingState::Initial
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use super::pb;
-use crate::{token::BroadcastAlgorithm, ConnectionAddr, PeerId, StreamId};
+use crate::{token::BroadcastAlgorithm, ConnectionAddr, P2pMeshsubScoreConfig, PeerId, StreamId};
 
 use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
 
 use mina_p2p_messages::v2;
-use openmina_core::{snark::Snark, transaction::Transaction};
+use openmina_core::{
+    block::BlockHash,
+    snark::{Snark, SnarkJobId},
+    transaction::Transaction,
+};
+use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
@@ -12,18 +18,64 @@ pub struct P2pNetworkPubsubState {
     pub clients: BTreeMap<PeerId, P2pNetworkPubsubClientState>,
     pub seq: u64,
     pub to_sign: VecDeque<pb::Message>,
-    pub seen: VecDeque<Vec<u8>>,
+    /// Ids of recently received messages, used to drop duplicates.
+    pub seen: VecDeque<P2pNetworkPubsubMessageId>,
     pub mcache: P2pNetworkPubsubMessageCache,
     pub incoming_block: Option<(PeerId, v2::MinaBlockBlockStableV2)>,
     pub incoming_transactions: Vec<(Transaction, u32)>,
     pub incoming_snarks: Vec<(Snark, u32)>,
     pub topics: BTreeMap<String, BTreeMap<PeerId, P2pNetworkPubsubClientTopicState>>,
+    /// Received messages waiting for the node to validate them. Only
+    /// accepted messages are forwarded to other peers.
+    pub pending_validation: BTreeMap<P2pNetworkPubsubMessageId, P2pNetworkPubsubPendingMessage>,
+    /// Message ids we requested with IWANT, along with the peer which
+    /// promised them in IHAVE and the deadline for delivering them.
+    pub iwant_promises: BTreeMap<P2pNetworkPubsubMessageId, (PeerId, Timestamp)>,
+    /// Score counters of peers. Kept after disconnection until they decay,
+    /// so that reconnecting doesn't reset the penalties.
+    pub scores: BTreeMap<PeerId, P2pNetworkPubsubPeerScore>,
+    pub last_heartbeat: Option<Timestamp>,
 }
 
 impl P2pNetworkPubsubState {
     pub fn prune_peer_state(&mut self, peer_id: &PeerId) {
         self.clients.remove(peer_id);
     }
+
+    pub fn score(&self, peer_id: &PeerId, config: &P2pMeshsubScoreConfig, now: Timestamp) -> f64 {
+        self.scores
+            .get(peer_id)
+            .map_or(0.0, |score| score.value(config, now))
+    }
+
+    /// Ids of the pending messages which carry the block with `hash`.
+    pub fn pending_block_messages<'a>(
+        &'a self,
+        hash: &'a BlockHash,
+    ) -> impl 'a + Iterator<Item = &'a P2pNetworkPubsubMessageId> {
+        self.pending_validation
+            .iter()
+            .filter(move |(_, pending)| {
+                matches!(&pending.content, P2pNetworkPubsubMessageContent::Block(h) if h == hash)
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// Ids of the pending messages from `peer_id` which carry snark for
+    /// the `job_id`.
+    pub fn pending_snark_messages<'a>(
+        &'a self,
+        peer_id: &'a PeerId,
+        job_id: &'a SnarkJobId,
+    ) -> impl 'a + Iterator<Item = &'a P2pNetworkPubsubMessageId> {
+        self.pending_validation
+            .iter()
+            .filter(move |(_, pending)| {
+                &pending.peer_id == peer_id
+                    && matches!(&pending.content, P2pNetworkPubsubMessageContent::Snark(id) if id == job_id)
+            })
+            .map(|(id, _)| id)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -36,32 +88,96 @@ pub struct P2pNetworkPubsubClientState {
     pub incoming_messages: Vec<pb::Message>,
 }
 
-// TODO: store blocks, snarks and txs separately
+/// Message id, as computed by Mina: base58 encoded source peer id followed
+/// by the decimal sequence number.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct P2pNetworkPubsubMessageId(pub Vec<u8>);
+
+impl fmt::Display for P2pNetworkPubsubMessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
+/// Outcome of the node's validation of the received message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum P2pNetworkPubsubValidationResult {
+    /// Message is valid, forward it to the mesh.
+    Accept,
+    /// Message is invalid, penalize the peer which sent it.
+    Reject,
+    /// Message is valid but not useful (e.g. stale), drop it.
+    Ignore,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pNetworkPubsubPendingMessage {
+    /// Peer which delivered the message to us.
+    pub peer_id: PeerId,
+    pub message: pb::Message,
+    pub content: P2pNetworkPubsubMessageContent,
+    pub received_at: Timestamp,
+}
+
+/// What the pending message carries, so that the validation result can be
+/// matched with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum P2pNetworkPubsubMessageContent {
+    Block(BlockHash),
+    Transactions,
+    Snark(SnarkJobId),
+}
+
+/// Validated messages of the last few heartbeats, used to answer IWANT
+/// requests and to gossip ids of recent messages in IHAVE.
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct P2pNetworkPubsubMessageCache {
-    pub map: BTreeMap<Vec<u8>, pb::Message>,
-    pub queue: VecDeque<Vec<u8>>,
+    pub map: BTreeMap<P2pNetworkPubsubMessageId, pb::Message>,
+    /// Ids of messages added within each heartbeat, most recent first.
+    pub windows: VecDeque<Vec<P2pNetworkPubsubMessageId>>,
 }
 
 impl P2pNetworkPubsubMessageCache {
-    const CAPACITY: usize = 100;
-
-    pub fn put(&mut self, message: pb::Message) -> Option<Vec<u8>> {
+    pub fn put(&mut self, message: pb::Message) -> Option<P2pNetworkPubsubMessageId> {
         let id = compute_message_id(&message)?;
-        self.map.insert(id.clone(), message);
-        self.queue.push_back(id.clone());
-        if self.queue.len() > Self::CAPACITY {
-            if let Some(id) = self.queue.pop_back() {
-                self.map.remove(&id);
+        if self.map.insert(id.clone(), message).is_none() {
+            if self.windows.is_empty() {
+                self.windows.push_front(Vec::new());
+            }
+            if let Some(window) = self.windows.front_mut() {
+                window.push(id.clone());
             }
         }
         Some(id)
     }
+
+    pub fn contains(&self, id: &P2pNetworkPubsubMessageId) -> bool {
+        self.map.contains_key(id)
+    }
+
+    /// Starts a new window, dropping messages older than `history_length`
+    /// heartbeats.
+    pub fn shift(&mut self, history_length: usize) {
+        self.windows.push_front(Vec::new());
+        while self.windows.len() > history_length.max(1) {
+            for id in self.windows.pop_back().unwrap_or_default() {
+                self.map.remove(&id);
+            }
+        }
+    }
+
+    /// Ids of messages received within the last `history_gossip` heartbeats.
+    pub fn gossip_ids(
+        &self,
+        history_gossip: usize,
+    ) -> impl '_ + Iterator<Item = &P2pNetworkPubsubMessageId> {
+        self.windows.iter().take(history_gossip).flatten()
+    }
 }
 
 // TODO: what if wasm32?
 // How to test it?
-pub fn compute_message_id(message: &pb::Message) -> Option<Vec<u8>> {
+pub fn compute_message_id(message: &pb::Message) -> Option<P2pNetworkPubsubMessageId> {
     let source_bytes = message
         .from
         .as_ref()
@@ -79,12 +195,15 @@ pub fn compute_message_id(message: &pb::Message) -> Option<Vec<u8>> {
         .map(u64::from_be_bytes)
         .unwrap_or_default();
     source_string.push_str(&sequence_number.to_string());
-    Some(source_string.into_bytes())
+    Some(P2pNetworkPubsubMessageId(source_string.into_bytes()))
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct P2pNetworkPubsubClientTopicState {
     pub mesh: P2pNetworkPubsubClientMeshAddingState,
+    /// Peer can't be grafted until this time, after it was pruned by
+    /// either side.
+    pub backoff_until: Option<Timestamp>,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -100,4 +219,187 @@ impl P2pNetworkPubsubClientTopicState {
     pub fn on_mesh(&self) -> bool {
         matches!(&self.mesh, P2pNetworkPubsubClientMeshAddingState::Added)
     }
+
+    pub fn is_backed_off(&self, now: Timestamp) -> bool {
+        self.backoff_until.map_or(false, |until| now < until)
+    }
+}
+
+/// Score counters of a peer, see [`P2pMeshsubScoreConfig`] for how the
+/// score is computed from them.
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct P2pNetworkPubsubPeerScore {
+    /// Since when the peer is in our mesh.
+    pub mesh_since: Option<Timestamp>,
+    pub first_message_deliveries: f64,
+    pub invalid_message_deliveries: f64,
+    pub behaviour_penalty: f64,
+}
+
+impl P2pNetworkPubsubPeerScore {
+    pub fn value(&self, config: &P2pMeshsubScoreConfig, now: Timestamp) -> f64 {
+        let time_in_mesh = self
+            .mesh_since
+            .and_then(|since| now.checked_sub(since))
+            .map_or(0.0, |time| {
+                let quantum = config.time_in_mesh_quantum.as_secs_f64().max(f64::EPSILON);
+                (time.as_secs_f64() / quantum).min(config.time_in_mesh_cap)
+            });
+        let topic_score = config.time_in_mesh_weight * time_in_mesh
+            + config.first_message_deliveries_weight * self.first_message_deliveries
+            + config.invalid_message_deliveries_weight * self.invalid_message_deliveries.powi(2);
+        let excess_penalty = (self.behaviour_penalty - config.behaviour_penalty_threshold).max(0.0);
+
+        config.topic_weight * topic_score + config.behaviour_penalty_weight * excess_penalty.powi(2)
+    }
+
+    pub fn first_message_delivered(&mut self, config: &P2pMeshsubScoreConfig) {
+        self.first_message_deliveries =
+            (self.first_message_deliveries + 1.0).min(config.first_message_deliveries_cap);
+    }
+
+    pub fn decay(&mut self, config: &P2pMeshsubScoreConfig) {
+        let decay = |value: &mut f64, factor: f64| {
+            *value *= factor;
+            if *value < config.decay_to_zero {
+                *value = 0.0;
+            }
+        };
+        decay(
+            &mut self.first_message_deliveries,
+            config.first_message_deliveries_decay,
+        );
+        decay(
+            &mut self.invalid_message_deliveries,
+            config.invalid_message_deliveries_decay,
+        );
+        decay(&mut self.behaviour_penalty, config.behaviour_penalty_decay);
+    }
+
+    /// Whether the counters carry no information anymore.
+    pub fn is_neutral(&self) -> bool {
+        self.mesh_since.is_none()
+            && self.first_message_deliveries == 0.0
+            && self.invalid_message_deliveries == 0.0
+            && self.behaviour_penalty == 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn score_config() -> P2pMeshsubScoreConfig {
+        P2pMeshsubScoreConfig {
+            decay_to_zero: 0.1,
+            topic_weight: 1.0,
+            time_in_mesh_weight: 0.5,
+            time_in_mesh_quantum: Duration::from_secs(1),
+            time_in_mesh_cap: 10.0,
+            first_message_deliveries_weight: 1.0,
+            first_message_deliveries_decay: 0.5,
+            first_message_deliveries_cap: 5.0,
+            invalid_message_deliveries_weight: -2.0,
+            invalid_message_deliveries_decay: 0.5,
+            behaviour_penalty_weight: -1.0,
+            behaviour_penalty_threshold: 1.0,
+            behaviour_penalty_decay: 0.5,
+            ..Default::default()
+        }
+    }
+
+    fn secs(secs: u64) -> Timestamp {
+        Timestamp::new(secs * 1_000_000_000)
+    }
+
+    fn message(seqno: u64) -> pb::Message {
+        pb::Message {
+            seqno: Some(seqno.to_be_bytes().to_vec()),
+            topic: "test".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_peer_score_value() {
+        let config = score_config();
+        let score = P2pNetworkPubsubPeerScore {
+            mesh_since: Some(secs(10)),
+            first_message_deliveries: 3.0,
+            invalid_message_deliveries: 2.0,
+            behaviour_penalty: 3.0,
+        };
+        // 0.5 * 4 + 3 - 2 * 2^2 - (3 - 1)^2
+        assert_eq!(score.value(&config, secs(14)), -7.0);
+        // time in mesh is capped
+        assert_eq!(score.value(&config, secs(110)), -4.0);
+        assert_eq!(
+            P2pNetworkPubsubPeerScore::default().value(&config, secs(14)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_peer_score_first_message_delivered_is_capped() {
+        let config = score_config();
+        let mut score = P2pNetworkPubsubPeerScore::default();
+        for _ in 0..10 {
+            score.first_message_delivered(&config);
+        }
+        assert_eq!(score.first_message_deliveries, 5.0);
+    }
+
+    #[test]
+    fn test_peer_score_decay() {
+        let config = score_config();
+        let mut score = P2pNetworkPubsubPeerScore {
+            mesh_since: None,
+            first_message_deliveries: 3.0,
+            invalid_message_deliveries: 0.4,
+            behaviour_penalty: 1.0,
+        };
+
+        score.decay(&config);
+        assert_eq!(score.first_message_deliveries, 1.5);
+        assert_eq!(score.invalid_message_deliveries, 0.2);
+        assert_eq!(score.behaviour_penalty, 0.5);
+        assert!(!score.is_neutral());
+
+        for _ in 0..5 {
+            score.decay(&config);
+        }
+        // counters are reset once they drop below `decay_to_zero`
+        assert_eq!(score.invalid_message_deliveries, 0.0);
+        assert_eq!(score.behaviour_penalty, 0.0);
+        assert_eq!(score.first_message_deliveries, 0.0);
+        assert!(score.is_neutral());
+    }
+
+    #[test]
+    fn test_message_cache_shift_and_gossip_ids() {
+        let mut mcache = P2pNetworkPubsubMessageCache::default();
+        let id1 = mcache.put(message(1)).unwrap();
+        let id2 = mcache.put(message(2)).unwrap();
+        // duplicates are stored once
+        assert_eq!(mcache.put(message(2)), Some(id2.clone()));
+        assert_eq!(mcache.gossip_ids(1).collect::<Vec<_>>(), vec![&id1, &id2]);
+
+        mcache.shift(3);
+        let id3 = mcache.put(message(3)).unwrap();
+        assert_eq!(mcache.gossip_ids(1).collect::<Vec<_>>(), vec![&id3]);
+        assert_eq!(
+            mcache.gossip_ids(2).collect::<Vec<_>>(),
+            vec![&id3, &id1, &id2]
+        );
+
+        // the window with `id1` and `id2` is dropped
+        mcache.shift(2);
+        assert_eq!(mcache.windows.len(), 2);
+        assert!(!mcache.contains(&id1));
+        assert!(!mcache.contains(&id2));
+        assert!(mcache.contains(&id3));
+        assert_eq!(mcache.gossip_ids(5).collect::<Vec<_>>(), vec![&id3]);
+    }
 }
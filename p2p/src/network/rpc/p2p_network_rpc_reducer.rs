@@ -20,6 +20,7 @@ use crate::{
     },
     connection::outgoing::P2pConnectionOutgoingInitOpts,
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    reputation::P2pReputationAction,
     Data, Limit, P2pLimits, P2pNetworkState, P2pNetworkYamuxAction, PeerId,
 };
 
@@ -360,6 +361,17 @@ impl P2pNetworkRpcState {
                     limits.rpc_get_some_initial_peers(),
                     GetSomeInitialPeersV1ForV2::NAME,
                 ),
+                GetTransitionKnowledgeV1ForV2::NAME => (
+                    limits.rpc_get_transition_knowledge(),
+                    GetTransitionKnowledgeV1ForV2::NAME,
+                ),
+                GetTransitionChainProofV1ForV2::NAME => (
+                    limits.rpc_get_transition_chain_proof(),
+                    GetTransitionChainProofV1ForV2::NAME,
+                ),
+                GetAncestryV2::NAME => (limits.rpc_get_ancestry(), GetAncestryV2::NAME),
+                GetNodeStatusV2::NAME => (limits.rpc_get_node_status(), GetNodeStatusV2::NAME),
+                GetEpochLedgerV2::NAME => (limits.rpc_get_epoch_ledger(), GetEpochLedgerV2::NAME),
                 _ => (Limit::Some(0), b"<unimplemented>"),
             }
         } else {
@@ -441,6 +453,52 @@ where
                 request: Box::new(P2pRpcRequest::InitialPeers),
             });
         }
+        (rpc::GetTransitionKnowledgeV1ForV2::NAME, rpc::GetTransitionKnowledgeV1ForV2::VERSION) => {
+            let () = rpc::GetTransitionKnowledgeV1ForV2::query_payload(&mut bytes)?;
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::TransitionKnowledge),
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let hash = rpc::GetTransitionChainProofV1ForV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash));
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::TransitionChainProof(hash)),
+            });
+        }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let rpc::WithHashV1 { data, hash } = rpc::GetAncestryV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash));
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::Ancestry(hash, Box::new(data))),
+            });
+        }
+        (rpc::GetNodeStatusV2::NAME, rpc::GetNodeStatusV2::VERSION) => {
+            let () = rpc::GetNodeStatusV2::query_payload(&mut bytes)?;
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::NodeStatus),
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            let hash = rpc::GetEpochLedgerV2::query_payload(&mut bytes)?;
+            let hash = v2::LedgerHash::from(v2::MinaBaseLedgerHash0StableV1(hash));
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::EpochLedger(hash)),
+            });
+        }
         (name, version) => return Err(RpcQueryError::Unimplemented(name, version)),
     }
     Ok(())
@@ -556,6 +614,92 @@ where
                 });
             }
         }
+        (rpc::GetTransitionKnowledgeV1ForV2::NAME, rpc::GetTransitionKnowledgeV1ForV2::VERSION) => {
+            let response = rpc::GetTransitionKnowledgeV1ForV2::response_payload(&mut bytes)?;
+            let hashes = response
+                .into_iter()
+                .map(|hash| v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash)))
+                .collect();
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response: Some(Box::new(P2pRpcResponse::TransitionKnowledge(hashes))),
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let response = rpc::GetTransitionChainProofV1ForV2::response_payload(&mut bytes)?
+                .map(|(root_hash, body_hashes)| {
+                    let root_hash =
+                        v2::StateHash::from(v2::DataHashLibStateHashStableV1(root_hash));
+                    let body_hashes = body_hashes
+                        .into_iter()
+                        .map(v2::MinaBaseStateBodyHashStableV1)
+                        .collect();
+                    P2pRpcResponse::TransitionChainProof(root_hash, body_hashes)
+                })
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let response = rpc::GetAncestryV2::response_payload(&mut bytes)?
+                .map(|resp| BestTipWithProof {
+                    best_tip: resp.data.into(),
+                    proof: (
+                        resp.proof
+                            .0
+                            .into_iter()
+                            .map(v2::MinaBaseStateBodyHashStableV1)
+                            .collect(),
+                        resp.proof.1.into(),
+                    ),
+                })
+                .map(P2pRpcResponse::Ancestry)
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::GetNodeStatusV2::NAME, rpc::GetNodeStatusV2::VERSION) => {
+            // error means that the peer failed to collect its status.
+            let response = rpc::GetNodeStatusV2::response_payload(&mut bytes)?
+                .0
+                .ok()
+                .map(|status| Box::new(P2pRpcResponse::NodeStatus(Arc::new(status))));
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            // error means that the peer doesn't have the requested ledger.
+            let response = rpc::GetEpochLedgerV2::response_payload(&mut bytes)?
+                .0
+                .ok()
+                .map(|ledger| Box::new(P2pRpcResponse::EpochLedger(Arc::new(ledger))));
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::BanNotifyV1::NAME, rpc::BanNotifyV1::VERSION) => {
+            // Peer acknowledged the ban, connection can be closed now.
+            dispatcher.push(P2pReputationAction::BanNotifyFinish { peer_id });
+        }
         _ => {}
     }
     Ok(())
@@ -194,6 +194,8 @@ pub enum P2pNetworkConnectionError {
     KademliaOutgoingStreamError(#[from] P2pNetworkKadOutgoingStreamError),
     #[error("peer reset yamux stream")]
     StreamReset(StreamId),
+    #[error("yamux stream {0} outgoing buffer overflow, peer is too slow")]
+    YamuxOverflow(StreamId),
     #[error("pubsub error: {0}")]
     PubSubError(String),
 }
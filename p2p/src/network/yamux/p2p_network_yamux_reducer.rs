@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use openmina_core::{bug_condition, fuzz_maybe, fuzzed_maybe, Substate, SubstateAccess};
 
 use crate::P2pLimits;
@@ -182,6 +184,7 @@ impl P2pNetworkYamuxState {
                 Ok(())
             }
             P2pNetworkYamuxAction::IncomingFrame { addr, frame } => {
+                let mut pending_outgoing = VecDeque::new();
                 if let Some(frame) = yamux_state.incoming.pop_front() {
                     if frame.flags.contains(YamuxFlags::SYN) {
                         yamux_state
@@ -212,11 +215,13 @@ impl P2pNetworkYamuxState {
                             }
                         }
                         YamuxFrameInner::WindowUpdate { difference } => {
-                            yamux_state
+                            let stream = yamux_state
                                 .streams
                                 .entry(frame.stream_id)
-                                .or_insert_with(YamuxStreamState::incoming)
-                                .update_window(false, difference);
+                                .or_insert_with(YamuxStreamState::incoming);
+                            // resend the pending frames, those which still
+                            // don't fit are queued again
+                            pending_outgoing = stream.incoming_window_update(difference);
                         }
                         YamuxFrameInner::Ping { .. } => {}
                         YamuxFrameInner::GoAway(res) => yamux_state.set_res(res),
@@ -225,6 +230,9 @@ impl P2pNetworkYamuxState {
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let addr = *addr;
+                for frame in pending_outgoing {
+                    dispatcher.push(P2pNetworkYamuxAction::OutgoingFrame { addr, frame });
+                }
                 let limits: &P2pLimits = state.substate()?;
                 let max_streams = limits.max_streams();
                 let connection_state =
@@ -329,37 +337,61 @@ impl P2pNetworkYamuxState {
                 let Some(stream) = yamux_state.streams.get_mut(&frame.stream_id) else {
                     return Ok(());
                 };
-                match &frame.inner {
-                    YamuxFrameInner::Data(data) => {
-                        // must not underflow
-                        // the action must not dispatch if it doesn't fit in the window
-                        // TODO: add pending queue, where frames will wait for window increase
-                        stream.window_theirs -= data.len() as u32;
+                let stream_id = frame.stream_id;
+                let mut queued = false;
+                let frame = match frame.inner {
+                    YamuxFrameInner::Data(_) => {
+                        let pending = stream.pending.len();
+                        let frame = stream.outgoing_data(frame.clone());
+                        queued = stream.pending.len() > pending;
+                        frame
                     }
                     YamuxFrameInner::WindowUpdate { difference } => {
-                        stream.update_window(true, *difference);
+                        stream.update_window(true, difference);
+                        Some(frame.clone())
                     }
-                    _ => {}
-                }
+                    _ => Some(frame.clone()),
+                };
 
-                if frame.flags.contains(YamuxFlags::FIN) {
-                    connection_state.streams.remove(&frame.stream_id);
-                    stream.writable = false;
-                } else {
-                    if frame.flags.contains(YamuxFlags::ACK) {
-                        stream.established |= true;
-                    }
-                    if frame.flags.contains(YamuxFlags::SYN) {
-                        stream.syn_sent = true;
+                if let Some(frame) = &frame {
+                    if frame.flags.contains(YamuxFlags::FIN) {
+                        connection_state.streams.remove(&frame.stream_id);
+                        stream.writable = false;
+                    } else {
+                        if frame.flags.contains(YamuxFlags::ACK) {
+                            stream.established |= true;
+                        }
+                        if frame.flags.contains(YamuxFlags::SYN) {
+                            stream.syn_sent = true;
+                        }
                     }
                 }
 
-                let dispatcher = state_context.into_dispatcher();
-                let data = fuzzed_maybe!(
-                    Data::from(frame.clone().into_bytes()),
-                    crate::fuzzer::mutate_yamux_frame
-                );
-                dispatcher.push(P2pNetworkNoiseAction::OutgoingData { addr: *addr, data });
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                if let Some(frame) = frame {
+                    let data = fuzzed_maybe!(
+                        Data::from(frame.into_bytes()),
+                        crate::fuzzer::mutate_yamux_frame
+                    );
+                    dispatcher.push(P2pNetworkNoiseAction::OutgoingData { addr: *addr, data });
+                }
+
+                if queued {
+                    let limits: &P2pLimits = state.substate()?;
+                    let Some(yamux_state) =
+                        <State as SubstateAccess<P2pNetworkSchedulerState>>::substate(state)?
+                            .connection_state(addr)
+                            .and_then(|connection_state| connection_state.yamux_state())
+                    else {
+                        return Ok(());
+                    };
+                    if yamux_state.pending_outgoing_overflow(stream_id, limits) {
+                        dispatcher.push(P2pNetworkSchedulerAction::Error {
+                            addr: *addr,
+                            error: P2pNetworkConnectionError::YamuxOverflow(stream_id),
+                        });
+                    }
+                }
                 Ok(())
             }
             P2pNetworkYamuxAction::PingStream { addr, ping } => {
@@ -425,3 +457,128 @@ impl YamuxStreamState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_frame(data: &[u8], flags: YamuxFlags) -> YamuxFrame {
+        YamuxFrame {
+            flags,
+            stream_id: 1,
+            inner: YamuxFrameInner::Data(data.to_vec().into()),
+        }
+    }
+
+    fn data(frame: &YamuxFrame) -> &[u8] {
+        match &frame.inner {
+            YamuxFrameInner::Data(data) => data,
+            _ => panic!("expected a data frame"),
+        }
+    }
+
+    fn stream_with_window(window: u32) -> YamuxStreamState {
+        YamuxStreamState {
+            window_theirs: window,
+            writable: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_outgoing_data_queued_when_window_used_up() {
+        let mut stream = stream_with_window(10);
+
+        let sent = stream.outgoing_data(data_frame(&[1; 10], YamuxFlags::empty()));
+        assert_eq!(data(&sent.unwrap()), &[1; 10]);
+        assert_eq!(stream.window_theirs, 0);
+
+        assert!(stream
+            .outgoing_data(data_frame(&[2; 5], YamuxFlags::empty()))
+            .is_none());
+        assert_eq!(stream.pending_outgoing_bytes(), 5);
+        assert!(stream.writable);
+    }
+
+    #[test]
+    fn test_outgoing_data_split_at_window_boundary() {
+        let mut stream = stream_with_window(4);
+
+        let frame = data_frame(&[1, 2, 3, 4, 5, 6], YamuxFlags::SYN | YamuxFlags::FIN);
+        let sent = stream.outgoing_data(frame).unwrap();
+        assert_eq!(data(&sent), &[1, 2, 3, 4]);
+        assert_eq!(sent.flags.bits(), YamuxFlags::SYN.bits());
+        assert_eq!(stream.window_theirs, 0);
+
+        // `FIN` stays with the queued rest, but the stream isn't writable anymore
+        let rest = stream.pending.front().unwrap();
+        assert_eq!(data(rest), &[5, 6]);
+        assert_eq!(rest.flags.bits(), YamuxFlags::FIN.bits());
+        assert!(!stream.writable);
+    }
+
+    #[test]
+    fn test_pending_drained_in_order_on_window_update() {
+        let mut stream = stream_with_window(0);
+        for (byte, len) in [(1, 10), (2, 20), (3, 30)] {
+            assert!(stream
+                .outgoing_data(data_frame(&vec![byte; len], YamuxFlags::empty()))
+                .is_none());
+        }
+
+        let pending = stream.incoming_window_update(35);
+        assert_eq!(stream.window_theirs, 35);
+        let sent = pending
+            .into_iter()
+            .filter_map(|frame| stream.outgoing_data(frame))
+            .collect::<Vec<_>>();
+        let sent = sent.iter().map(data).collect::<Vec<_>>();
+        assert_eq!(sent, [&[1; 10][..], &[2; 20], &[3; 5]]);
+        assert_eq!(stream.window_theirs, 0);
+        assert_eq!(data(&stream.pending[0]), &[3; 25]);
+
+        // frames queued after the window update wait behind the rest
+        assert!(stream
+            .outgoing_data(data_frame(&[4], YamuxFlags::empty()))
+            .is_none());
+        let pending = stream.incoming_window_update(100);
+        let sent = pending
+            .into_iter()
+            .filter_map(|frame| stream.outgoing_data(frame))
+            .collect::<Vec<_>>();
+        let sent = sent.iter().map(data).collect::<Vec<_>>();
+        assert_eq!(sent, [&[3; 25][..], &[4]]);
+        assert!(stream.pending.is_empty());
+    }
+
+    #[test]
+    fn test_pending_outgoing_overflow() {
+        let limits = P2pLimits::default()
+            .with_yamux_pending_outgoing_per_stream(Limit::Some(100))
+            .with_yamux_pending_outgoing_per_connection(Limit::Some(150));
+        let mut state = P2pNetworkYamuxState::default();
+        state.streams.insert(1, stream_with_window(0));
+        state.streams.insert(3, stream_with_window(0));
+        let queue = |state: &mut P2pNetworkYamuxState, stream_id, len| {
+            let stream = state.streams.get_mut(&stream_id).unwrap();
+            assert!(stream
+                .outgoing_data(data_frame(&vec![0; len], YamuxFlags::empty()))
+                .is_none());
+        };
+
+        // the message which is being sent isn't limited
+        queue(&mut state, 1, 1000);
+        queue(&mut state, 3, 1000);
+        queue(&mut state, 1, 100);
+        assert!(!state.pending_outgoing_overflow(1, &limits));
+        queue(&mut state, 1, 1);
+        assert!(state.pending_outgoing_overflow(1, &limits));
+
+        // the limit of the connection is reached by both streams
+        state.streams.get_mut(&1).unwrap().pending.pop_back();
+        queue(&mut state, 3, 50);
+        assert!(!state.pending_outgoing_overflow(3, &limits));
+        queue(&mut state, 3, 1);
+        assert!(state.pending_outgoing_overflow(3, &limits));
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::{BTreeMap, VecDeque};
 use serde::{Deserialize, Serialize};
 
 use super::super::*;
+use crate::P2pLimits;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct P2pNetworkYamuxState {
@@ -43,6 +44,32 @@ impl P2pNetworkYamuxState {
 
         windows + headers * SIZE_OF_HEADER
     }
+
+    /// Total size of the outgoing data waiting for window updates in all
+    /// streams of the connection.
+    pub fn pending_outgoing_bytes(&self) -> usize {
+        self.streams
+            .values()
+            .map(YamuxStreamState::pending_outgoing_bytes)
+            .sum()
+    }
+
+    /// Whether the outgoing data queued behind the messages which are being
+    /// sent exceeds the limits, for the stream or for the whole connection.
+    /// A single message can be larger than the window, so it's not counted.
+    pub fn pending_outgoing_overflow(&self, stream_id: StreamId, limits: &P2pLimits) -> bool {
+        let stream_queued = self
+            .streams
+            .get(&stream_id)
+            .map_or(0, YamuxStreamState::queued_outgoing_bytes);
+        let queued = self
+            .streams
+            .values()
+            .map(YamuxStreamState::queued_outgoing_bytes)
+            .sum::<usize>();
+        stream_queued > limits.yamux_pending_outgoing_per_stream()
+            || queued > limits.yamux_pending_outgoing_per_connection()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -54,6 +81,9 @@ pub struct YamuxStreamState {
     pub writable: bool,
     pub window_theirs: u32,
     pub window_ours: u32,
+    /// Outgoing data frames which don't fit into `window_theirs`. They are
+    /// sent in order as the peer increases the window.
+    pub pending: VecDeque<YamuxFrame>,
 }
 
 impl Default for YamuxStreamState {
@@ -66,6 +96,7 @@ impl Default for YamuxStreamState {
             writable: false,
             window_theirs: 256 * 1024,
             window_ours: 256 * 1024,
+            pending: VecDeque::new(),
         }
     }
 }
@@ -77,6 +108,43 @@ impl YamuxStreamState {
             ..Default::default()
         }
     }
+
+    pub fn pending_outgoing_bytes(&self) -> usize {
+        self.pending.iter().map(YamuxFrame::data_len).sum()
+    }
+
+    /// Size of the pending outgoing data behind the first pending frame.
+    pub fn queued_outgoing_bytes(&self) -> usize {
+        self.pending.iter().skip(1).map(YamuxFrame::data_len).sum()
+    }
+
+    /// Takes the outgoing data frame. Returns the part of it which fits into
+    /// the window and can be sent now, the rest is queued until the peer
+    /// increases the window. Frames must be sent in order, so if some are
+    /// already queued, the whole frame is queued behind them.
+    pub fn outgoing_data(&mut self, mut frame: YamuxFrame) -> Option<YamuxFrame> {
+        if frame.flags.contains(YamuxFlags::FIN) {
+            // nothing can be written after the `FIN`, even if it's queued
+            self.writable = false;
+        }
+        let window = self.window_theirs as usize;
+        if !self.pending.is_empty() || (window == 0 && frame.data_len() > 0) {
+            self.pending.push_back(frame);
+            return None;
+        }
+        if let Some(rest) = frame.split_off(window) {
+            self.pending.push_back(rest);
+        }
+        self.window_theirs -= frame.data_len() as u32;
+        Some(frame)
+    }
+
+    /// Applies the window update from the peer and takes the queued frames,
+    /// which are to be passed to [`Self::outgoing_data`] again, in order.
+    pub fn incoming_window_update(&mut self, difference: i32) -> VecDeque<YamuxFrame> {
+        self.update_window(false, difference);
+        std::mem::take(&mut self.pending)
+    }
 }
 
 bitflags::bitflags! {
@@ -139,6 +207,35 @@ pub struct YamuxFrame {
 }
 
 impl YamuxFrame {
+    /// Length of the payload, zero for frames other than data.
+    pub fn data_len(&self) -> usize {
+        match &self.inner {
+            YamuxFrameInner::Data(data) => data.len(),
+            _ => 0,
+        }
+    }
+
+    /// Splits the data frame, leaving the first `at` bytes in `self` and
+    /// returning a frame with the rest of the data. The `FIN` flag is moved
+    /// to the returned frame, other flags stay with the first frame.
+    /// Returns `None` if there is nothing to split off.
+    pub fn split_off(&mut self, at: usize) -> Option<YamuxFrame> {
+        let YamuxFrameInner::Data(data) = &mut self.inner else {
+            return None;
+        };
+        if data.len() <= at {
+            return None;
+        }
+        let rest = YamuxFrame {
+            flags: self.flags & YamuxFlags::FIN,
+            stream_id: self.stream_id,
+            inner: YamuxFrameInner::Data(data[at..].to_vec().into()),
+        };
+        *data = data[..at].to_vec().into();
+        self.flags.remove(YamuxFlags::FIN);
+        Some(rest)
+    }
+
     pub fn into_bytes(self) -> Vec<u8> {
         let data_len = if let YamuxFrameInner::Data(data) = &self.inner {
             data.len()
@@ -222,4 +319,23 @@ mod tests {
         assert_eq!(Kademlia.stream_id(false), 5);
         assert_eq!(Kademlia.stream_id(true), 6);
     }
+
+    #[test]
+    fn yamux_frame_split_off() {
+        use super::{YamuxFlags, YamuxFrame, YamuxFrameInner};
+
+        let mut frame = YamuxFrame {
+            flags: YamuxFlags::SYN | YamuxFlags::FIN,
+            stream_id: 1,
+            inner: YamuxFrameInner::Data(vec![1, 2, 3, 4, 5].into()),
+        };
+        assert!(frame.split_off(5).is_none());
+
+        let rest = frame.split_off(2).unwrap();
+        assert_eq!(frame.data_len(), 2);
+        assert_eq!(frame.flags.bits(), YamuxFlags::SYN.bits());
+        assert_eq!(rest.data_len(), 3);
+        assert_eq!(rest.flags.bits(), YamuxFlags::FIN.bits());
+        assert_eq!(rest.stream_id, 1);
+    }
 }
@@ -12,6 +12,7 @@ use super::disconnection::P2pDisconnectionAction;
 use super::identify::P2pIdentifyAction;
 use super::network::P2pNetworkAction;
 use super::peer::P2pPeerAction;
+use super::reputation::P2pReputationAction;
 use super::P2pState;
 
 pub type P2pActionWithMeta = redux::ActionWithMeta<P2pAction>;
@@ -28,6 +29,7 @@ pub enum P2pAction {
     Channels(P2pChannelsAction),
     ChannelsEffectful(P2pChannelsEffectfulAction),
     Peer(P2pPeerAction),
+    Reputation(P2pReputationAction),
     Network(P2pNetworkAction),
 }
 
@@ -56,6 +58,7 @@ impl redux::EnablingCondition<crate::P2pState> for P2pAction {
             P2pAction::DisconnectionEffectful(a) => a.is_enabled(state, time),
             P2pAction::Channels(a) => a.is_enabled(state, time),
             P2pAction::Peer(a) => a.is_enabled(state, time),
+            P2pAction::Reputation(a) => a.is_enabled(state, time),
             P2pAction::Identify(a) => a.is_enabled(state, time),
             P2pAction::Network(a) => a.is_enabled(state, time),
             P2pAction::ChannelsEffectful(a) => a.is_enabled(state, time),
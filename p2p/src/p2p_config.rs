@@ -1,9 +1,10 @@
-use std::{collections::BTreeSet, time::Duration};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts, identity::PublicKey,
+    address_book::P2pAddressBookEntry, channels::ChannelId,
+    connection::outgoing::P2pConnectionOutgoingInitOpts, identity::PublicKey,
 };
 
 pub const DEVNET_SEEDS: &[&str] = &[
@@ -36,7 +37,13 @@ pub struct P2pConfig {
     /// Use peers discovery.
     pub peer_discovery: bool,
 
-    pub meshsub: P2pMeshsubConfig,
+    /// Shared, so that it can be passed to the pubsub reducer
+    /// cheaply on every action.
+    pub meshsub: Arc<P2pMeshsubConfig>,
+
+    /// Peers from the address book persisted by the previous run.
+    #[serde(default)]
+    pub address_book: Vec<P2pAddressBookEntry>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -47,7 +54,65 @@ pub struct P2pMeshsubConfig {
     pub outbound_degree_desired: usize,
     pub outbound_degree_low: usize,
     pub outbound_degree_high: usize,
+    /// Number of peers outside of the mesh to gossip message ids to on
+    /// each heartbeat.
+    pub outbound_degree_lazy: usize,
     pub mcache_len: usize,
+
+    /// Interval of the mesh maintenance and gossip emission.
+    pub heartbeat_interval: Duration,
+    /// For how long a pruned peer can't be grafted again.
+    pub prune_backoff: Duration,
+    /// Number of heartbeats for which validated messages are kept to answer
+    /// IWANT requests.
+    pub history_length: usize,
+    /// Number of most recent heartbeats whose messages are gossiped in IHAVE.
+    pub history_gossip: usize,
+    /// How long the peer has to deliver the message we requested with IWANT,
+    /// before it gets penalized for the broken promise.
+    pub iwant_followup_time: Duration,
+    /// How long the received message waits for the node to validate it.
+    /// After that it is dropped, as if the node ignored it.
+    pub validation_timeout: Duration,
+
+    pub score: P2pMeshsubScoreConfig,
+}
+
+/// Peer scoring parameters, see [gossipsub v1.1 spec](https://github.com/libp2p/specs/blob/master/pubsub/gossipsub/gossipsub-v1.1.md#peer-scoring).
+///
+/// Counters are decayed on each heartbeat. Only the consensus topic is
+/// scored, so there is a single set of topic parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pMeshsubScoreConfig {
+    /// No gossip is exchanged with peers below this score.
+    pub gossip_threshold: f64,
+    /// Own messages aren't published to peers below this score.
+    pub publish_threshold: f64,
+    /// All RPCs from peers below this score are ignored.
+    pub graylist_threshold: f64,
+    /// Counter is reset to zero once it decays below this value.
+    pub decay_to_zero: f64,
+
+    pub topic_weight: f64,
+    /// P1: reward for the time spent in our mesh.
+    pub time_in_mesh_weight: f64,
+    pub time_in_mesh_quantum: Duration,
+    pub time_in_mesh_cap: f64,
+    /// P2: reward for being the first to deliver a valid message.
+    pub first_message_deliveries_weight: f64,
+    pub first_message_deliveries_decay: f64,
+    pub first_message_deliveries_cap: f64,
+    /// P4: penalty for delivering messages rejected by the node. Applied
+    /// to the square of the counter.
+    pub invalid_message_deliveries_weight: f64,
+    pub invalid_message_deliveries_decay: f64,
+
+    /// P7: penalty for misbehaviour, like grafting during backoff or not
+    /// delivering messages promised in IHAVE. Applied to the square of the
+    /// counter above the threshold.
+    pub behaviour_penalty_weight: f64,
+    pub behaviour_penalty_threshold: f64,
+    pub behaviour_penalty_decay: f64,
 }
 
 impl Default for P2pMeshsubConfig {
@@ -57,7 +122,38 @@ impl Default for P2pMeshsubConfig {
             outbound_degree_desired: 6,
             outbound_degree_low: 4,
             outbound_degree_high: 12,
+            outbound_degree_lazy: 6,
             mcache_len: 256,
+            heartbeat_interval: Duration::from_secs(1),
+            prune_backoff: Duration::from_secs(60),
+            history_length: 5,
+            history_gossip: 3,
+            iwant_followup_time: Duration::from_secs(3),
+            validation_timeout: Duration::from_secs(60),
+            score: P2pMeshsubScoreConfig::default(),
+        }
+    }
+}
+
+impl Default for P2pMeshsubScoreConfig {
+    fn default() -> Self {
+        P2pMeshsubScoreConfig {
+            gossip_threshold: -10.0,
+            publish_threshold: -50.0,
+            graylist_threshold: -80.0,
+            decay_to_zero: 0.01,
+            topic_weight: 1.0,
+            time_in_mesh_weight: 0.01,
+            time_in_mesh_quantum: Duration::from_secs(1),
+            time_in_mesh_cap: 3600.0,
+            first_message_deliveries_weight: 1.0,
+            first_message_deliveries_decay: 0.99,
+            first_message_deliveries_cap: 20.0,
+            invalid_message_deliveries_weight: -10.0,
+            invalid_message_deliveries_decay: 0.99,
+            behaviour_penalty_weight: -10.0,
+            behaviour_penalty_threshold: 0.0,
+            behaviour_penalty_decay: 0.99,
         }
     }
 }
@@ -75,6 +171,11 @@ pub struct P2pTimeouts {
     pub block: Option<Duration>,
     pub snark: Option<Duration>,
     pub initial_peers: Option<Duration>,
+    pub transition_knowledge: Option<Duration>,
+    pub transition_chain_proof: Option<Duration>,
+    pub ancestry: Option<Duration>,
+    pub node_status: Option<Duration>,
+    pub epoch_ledger: Option<Duration>,
     pub kademlia_bootstrap: Option<Duration>,
     pub kademlia_initial_bootstrap: Option<Duration>,
     pub select: Option<Duration>,
@@ -124,6 +225,17 @@ impl Default for P2pTimeouts {
             block: from_env_or("BLOCK_TIMEOUT", Some(Duration::from_secs(5))),
             snark: from_env_or("SNARK_TIMEOUT", Some(Duration::from_secs(5))),
             initial_peers: from_env_or("INITIAL_PEERS_TIMEOUT", Some(Duration::from_secs(5))),
+            transition_knowledge: from_env_or(
+                "TRANSITION_KNOWLEDGE_TIMEOUT",
+                Some(Duration::from_secs(5)),
+            ),
+            transition_chain_proof: from_env_or(
+                "TRANSITION_CHAIN_PROOF_TIMEOUT",
+                Some(Duration::from_secs(5)),
+            ),
+            ancestry: from_env_or("ANCESTRY_TIMEOUT", Some(Duration::from_secs(10))),
+            node_status: from_env_or("NODE_STATUS_TIMEOUT", Some(Duration::from_secs(5))),
+            epoch_ledger: from_env_or("EPOCH_LEDGER_TIMEOUT", Some(Duration::from_secs(120))),
             kademlia_bootstrap: from_env_or(
                 "KADEMLIA_BOOTSTRAP_TIMEOUT",
                 Some(Duration::from_secs(60)),
@@ -146,6 +258,11 @@ impl P2pTimeouts {
             staged_ledger_aux_and_pending_coinbases_at_block: None,
             block: None,
             snark: None,
+            transition_knowledge: None,
+            transition_chain_proof: None,
+            ancestry: None,
+            node_status: None,
+            epoch_ledger: None,
             ..Default::default()
         }
     }
@@ -252,6 +369,8 @@ pub struct P2pLimits {
     max_peers_in_state: Limit<usize>,
     max_streams: Limit<usize>,
     yamux_message_size: Limit<usize>,
+    yamux_pending_outgoing_per_stream: Limit<usize>,
+    yamux_pending_outgoing_per_connection: Limit<usize>,
 
     identify_message: Limit<usize>,
     kademlia_request: Limit<usize>,
@@ -264,6 +383,11 @@ pub struct P2pLimits {
     rpc_get_staged_ledger: Limit<usize>,
     rpc_get_transition_chain: Limit<usize>,
     rpc_get_some_initial_peers: Limit<usize>,
+    rpc_get_transition_knowledge: Limit<usize>,
+    rpc_get_transition_chain_proof: Limit<usize>,
+    rpc_get_ancestry: Limit<usize>,
+    rpc_get_node_status: Limit<usize>,
+    rpc_get_epoch_ledger: Limit<usize>,
 }
 
 macro_rules! limit {
@@ -319,6 +443,18 @@ impl P2pLimits {
         /// Sets the maximum number of streams that a peer is allowed to open simultaneously.
         with_yamux_message_size
     );
+    limit!(
+        /// Maximum size of outgoing data queued in a yamux stream behind the message being sent.
+        yamux_pending_outgoing_per_stream,
+        /// Sets the maximum size of outgoing data buffered in a yamux stream.
+        with_yamux_pending_outgoing_per_stream
+    );
+    limit!(
+        /// Maximum size of outgoing data queued in all yamux streams of a connection.
+        yamux_pending_outgoing_per_connection,
+        /// Sets the maximum size of outgoing data buffered in a yamux connection.
+        with_yamux_pending_outgoing_per_connection
+    );
 
     limit!(
         /// Minimum number of peers.
@@ -371,6 +507,26 @@ impl P2pLimits {
         #[doc = "RPC some_initial_peers"]
         rpc_get_some_initial_peers
     );
+    limit!(
+        #[doc = "RPC get_transition_knowledge"]
+        rpc_get_transition_knowledge
+    );
+    limit!(
+        #[doc = "RPC get_transition_chain_proof"]
+        rpc_get_transition_chain_proof
+    );
+    limit!(
+        #[doc = "RPC get_ancestry"]
+        rpc_get_ancestry
+    );
+    limit!(
+        #[doc = "RPC get_node_status"]
+        rpc_get_node_status
+    );
+    limit!(
+        #[doc = "RPC get_epoch_ledger"]
+        rpc_get_epoch_ledger
+    );
 }
 
 impl Default for P2pLimits {
@@ -381,6 +537,10 @@ impl Default for P2pLimits {
         let max_streams = Limit::Some(10);
         // 256 MiB
         let yamux_message_size = Limit::Some(0x10000000);
+        // 1 MiB, four times the initial window of a stream
+        let yamux_pending_outgoing_per_stream = Limit::Some(0x100000);
+        // 4 MiB
+        let yamux_pending_outgoing_per_connection = Limit::Some(0x400000);
 
         let identify_message = Limit::Some(0x1000);
         let kademlia_request = Limit::Some(50);
@@ -393,6 +553,11 @@ impl Default for P2pLimits {
         let rpc_get_staged_ledger = Limit::Some(400_000_000); // 59286608 as observed, may go higher
         let rpc_get_transition_chain = Limit::Some(3_500_000); // 2979112 as observed
         let rpc_get_some_initial_peers = Limit::Some(32_000); // TODO: calculate
+        let rpc_get_transition_knowledge = Limit::Some(32_000); // 290 hashes, 32 bytes each
+        let rpc_get_transition_chain_proof = Limit::Some(32_000); // 290 hashes, 32 bytes each
+        let rpc_get_ancestry = rpc_get_best_tip;
+        let rpc_get_node_status = Limit::Some(1_000_000); // TODO: calculate
+        let rpc_get_epoch_ledger = yamux_message_size;
 
         Self {
             max_peers,
@@ -400,6 +565,8 @@ impl Default for P2pLimits {
             max_peers_in_state,
             max_streams,
             yamux_message_size,
+            yamux_pending_outgoing_per_stream,
+            yamux_pending_outgoing_per_connection,
 
             identify_message,
             kademlia_request,
@@ -412,6 +579,11 @@ impl Default for P2pLimits {
             rpc_get_staged_ledger,
             rpc_get_transition_chain,
             rpc_get_some_initial_peers,
+            rpc_get_transition_knowledge,
+            rpc_get_transition_chain_proof,
+            rpc_get_ancestry,
+            rpc_get_node_status,
+            rpc_get_epoch_ledger,
         }
     }
 }
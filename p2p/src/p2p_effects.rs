@@ -3,13 +3,17 @@ use redux::{ActionMeta, ActionWithMeta};
 
 use crate::{
     channels::P2pChannelsEffectfulAction,
-    connection::{outgoing::P2pConnectionOutgoingAction, P2pConnectionEffectfulAction},
+    connection::{
+        outgoing::{P2pConnectionOutgoingAction, P2pConnectionOutgoingInitOpts},
+        P2pConnectionEffectfulAction,
+    },
+    reputation::P2pReputationAction,
     P2pAction, P2pStore,
 };
 #[cfg(feature = "p2p-libp2p")]
 use crate::{
-    P2pNetworkKadKey, P2pNetworkKademliaAction, P2pNetworkPnetAction, P2pNetworkSelectAction,
-    PeerId,
+    P2pNetworkKadKey, P2pNetworkKademliaAction, P2pNetworkPnetAction, P2pNetworkPubsubAction,
+    P2pNetworkSelectAction, PeerId,
 };
 
 pub fn p2p_timeout_effects<Store, S>(store: &mut Store, meta: &ActionMeta)
@@ -30,6 +34,17 @@ where
     p2p_select_timeouts(store, meta);
     #[cfg(feature = "p2p-libp2p")]
     p2p_rpc_heartbeats(store, meta);
+    #[cfg(feature = "p2p-libp2p")]
+    store.dispatch(P2pNetworkPubsubAction::Heartbeat);
+
+    let timed_out_ban_notify = store
+        .state()
+        .reputation
+        .ban_notify_timeouts(meta.time())
+        .collect::<Vec<_>>();
+    for peer_id in timed_out_ban_notify {
+        store.dispatch(P2pReputationAction::BanNotifyFinish { peer_id });
+    }
 
     let state = store.state();
     for (peer_id, id, is_streaming) in state.peer_rpc_timeouts(meta.time()) {
@@ -216,9 +231,9 @@ where
     if store.state().already_has_min_peers() {
         return;
     }
-    let timeouts = &store.state().config.timeouts;
-    let reconnect_actions: Vec<_> = store
-        .state()
+    let state = store.state();
+    let timeouts = &state.config.timeouts;
+    let mut reconnect_peers: Vec<_> = state
         .peers
         .iter()
         .filter_map(|(_, p)| {
@@ -228,6 +243,15 @@ where
                 None
             }
         })
+        .collect();
+    // Best peers first, so that they are used before the limits kick in.
+    reconnect_peers.sort_by(|a, b| {
+        let quality =
+            |opts: &P2pConnectionOutgoingInitOpts| state.address_book.quality(opts.peer_id(), now);
+        quality(b).total_cmp(&quality(a))
+    });
+    let reconnect_actions: Vec<_> = reconnect_peers
+        .into_iter()
         .map(|opts| P2pConnectionOutgoingAction::Reconnect { opts, rpc_id: None })
         .collect();
     for action in reconnect_actions {
@@ -291,6 +315,7 @@ where
             P2pChannelsEffectfulAction::Transaction(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::StreamingRpc(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::SnarkJobCommitment(action) => action.effects(&meta, store),
+            P2pChannelsEffectfulAction::SignalingRelay(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::Rpc(action) => action.effects(&meta, store),
             P2pChannelsEffectfulAction::Snark(action) => action.effects(&meta, store),
         },
@@ -302,6 +327,7 @@ where
         | P2pAction::Channels(_)
         | P2pAction::Disconnection(_)
         | P2pAction::Peer(_)
+        | P2pAction::Reputation(_)
         | P2pAction::Identify(_) => {
             // handled by reducer
         }
@@ -119,6 +119,7 @@ impl fmt::Display for P2pChannelEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use crate::channels::best_tip::BestTipPropagationChannelMsg;
         use crate::channels::rpc::RpcChannelMsg;
+        use crate::channels::signaling_relay::SignalingRelayChannelMsg;
         use crate::channels::snark::SnarkPropagationChannelMsg;
         use crate::channels::snark_job_commitment::SnarkJobCommitmentPropagationChannelMsg;
 
@@ -203,6 +204,17 @@ impl fmt::Display for P2pChannelEvent {
                             commitment.job_id
                         ),
                     },
+                    ChannelMsg::SignalingRelay(v) => match v {
+                        SignalingRelayChannelMsg::Offer(offer) => write!(
+                            f,
+                            "Offer, from: {}, to: {}",
+                            offer.identity_pub_key.peer_id(),
+                            offer.target_peer_id
+                        ),
+                        SignalingRelayChannelMsg::Answer {
+                            offerer, answerer, ..
+                        } => write!(f, "Answer, offerer: {offerer}, answerer: {answerer}"),
+                    },
                     ChannelMsg::Rpc(v) => match v {
                         RpcChannelMsg::Request(id, req) => {
                             write!(f, "Request, id: {id}, {req}")
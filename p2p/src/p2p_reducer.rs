@@ -1,7 +1,7 @@
 use crate::{
     channels::P2pChannelsState, connection::P2pConnectionState,
-    disconnection::P2pDisconnectedState, P2pAction, P2pActionWithMetaRef, P2pNetworkState,
-    P2pPeerState, P2pState,
+    disconnection::P2pDisconnectedState, reputation::P2pReputationState, P2pAction,
+    P2pActionWithMetaRef, P2pNetworkState, P2pPeerState, P2pState,
 };
 use openmina_core::{bug_condition, Substate};
 
@@ -35,6 +35,9 @@ impl P2pState {
                 Substate::from_compatible_substate(state_context),
                 meta.with_action(action),
             ),
+            P2pAction::Reputation(action) => {
+                P2pReputationState::reducer(state_context, meta.with_action(action))
+            }
             P2pAction::Channels(action) => {
                 P2pChannelsState::reducer(state_context, meta.with_action(action))
             }
@@ -47,10 +50,12 @@ impl P2pState {
                 #[cfg(feature = "p2p-libp2p")]
                 {
                     let limits = state.config.limits;
+                    let meshsub = state.config.meshsub.clone();
                     P2pNetworkState::reducer(
                         Substate::from_compatible_substate(state_context),
                         meta.with_action(_action),
                         &limits,
+                        &meshsub,
                     )?;
                 }
                 Ok(())
@@ -6,6 +6,7 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use openmina_core::requests::RpcId;
 
+use crate::address_book::P2pAddressBook;
 use crate::bootstrap::P2pNetworkKadBootstrapState;
 use crate::channels::rpc::P2pRpcId;
 use crate::channels::streaming_rpc::P2pStreamingRpcId;
@@ -14,6 +15,7 @@ use crate::connection::incoming::P2pConnectionIncomingState;
 use crate::connection::outgoing::{P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingState};
 use crate::network::identify::{P2pNetworkIdentify, P2pNetworkIdentifyState};
 use crate::network::P2pNetworkState;
+use crate::reputation::P2pReputationState;
 use crate::{
     is_time_passed, Limit, P2pLimits, P2pNetworkKadState, P2pNetworkPubsubState,
     P2pNetworkSchedulerState, P2pTimeouts, PeerId,
@@ -28,6 +30,8 @@ pub struct P2pState {
     pub config: P2pConfig,
     pub network: P2pNetworkState,
     pub peers: BTreeMap<PeerId, P2pPeerState>,
+    pub reputation: P2pReputationState,
+    pub address_book: P2pAddressBook,
 }
 
 impl P2pState {
@@ -56,9 +60,18 @@ impl P2pState {
             );
         }
 
+        let address_book = P2pAddressBook::new(
+            config
+                .address_book
+                .iter()
+                .filter(|entry| entry.peer_id() != &my_id)
+                .cloned(),
+        );
+
         let initial_peers = config
             .initial_peers
             .iter()
+            .chain(address_book.iter().map(|entry| &entry.dial_opts))
             .filter(|peer| peer.peer_id() != &my_id);
 
         let known_peers = if cfg!(feature = "p2p-libp2p") {
@@ -104,6 +117,8 @@ impl P2pState {
             config,
             network,
             peers,
+            reputation: Default::default(),
+            address_book,
         }
     }
 
@@ -133,14 +148,21 @@ impl P2pState {
             .any(|(_, p)| p.status.as_ready().is_some())
     }
 
-    pub fn disconnected_peers(&self) -> impl '_ + Iterator<Item = P2pConnectionOutgoingInitOpts> {
-        self.peers.iter().filter_map(|(_, state)| {
+    /// Disconnected peers which can be dialed, excluding banned ones.
+    pub fn disconnected_peers(
+        &self,
+        now: redux::Timestamp,
+    ) -> impl '_ + Iterator<Item = P2pConnectionOutgoingInitOpts> {
+        self.peers.iter().filter_map(move |(peer_id, state)| {
             if let P2pPeerState {
                 status: P2pPeerStatus::Disconnected { .. },
                 dial_opts: Some(opts),
                 ..
             } = state
             {
+                if self.reputation.is_banned(peer_id, now) {
+                    return None;
+                }
                 Some(opts.clone())
             } else {
                 None
@@ -148,6 +170,22 @@ impl P2pState {
         })
     }
 
+    /// Disconnected peers which can be dialed, ordered from the best to the
+    /// worst quality according to the address book.
+    pub fn disconnected_peers_by_quality(
+        &self,
+        now: redux::Timestamp,
+    ) -> Vec<P2pConnectionOutgoingInitOpts> {
+        let mut peers = self.disconnected_peers(now).collect::<Vec<_>>();
+        peers.sort_by(|a, b| {
+            let quality = |opts: &P2pConnectionOutgoingInitOpts| {
+                self.address_book.quality(opts.peer_id(), now)
+            };
+            quality(b).total_cmp(&quality(a))
+        });
+        peers
+    }
+
     pub fn ready_peers_iter(&self) -> impl Iterator<Item = (&PeerId, &P2pPeerStatusReady)> {
         self.peers
             .iter()
@@ -47,8 +47,12 @@ impl P2pPeerState {
                     meta.time(),
                     &p2p_state.config.enabled_channels,
                 ));
+                let is_libp2p = peer.is_libp2p;
+                if let Some(dial_opts) = peer.dial_opts.clone() {
+                    p2p_state.address_book.connected(dial_opts, meta.time());
+                }
 
-                if !peer.is_libp2p {
+                if !is_libp2p {
                     let (dispatcher, state) = state_context.into_dispatcher_and_state();
                     let state: &P2pState = state.substate()?;
                     state.channels_init(dispatcher, *peer_id);
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use super::{P2pBanReason, P2pPenaltyReason};
+use crate::{P2pState, PeerId};
+
+pub type P2pReputationActionWithMetaRef<'a> = redux::ActionWithMeta<&'a P2pReputationAction>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(display(peer_id), display(reason), debug(duration)), level = info)]
+pub enum P2pReputationAction {
+    /// Lower peer's score because of its misbehavior. Bans the peer if the
+    /// score drops to or below the ban threshold.
+    Penalize {
+        peer_id: PeerId,
+        reason: P2pPenaltyReason,
+    },
+    /// Ban peer for `duration`, notifying it and disconnecting if connected.
+    #[action_event(level = warn)]
+    Ban {
+        peer_id: PeerId,
+        reason: P2pBanReason,
+        duration: Duration,
+    },
+    /// Disconnect banned peer which was sent `ban_notify`, once it
+    /// acknowledged it or the notification timed out.
+    BanNotifyFinish { peer_id: PeerId },
+    /// Lift the ban and reset peer's score.
+    Unban { peer_id: PeerId },
+}
+
+impl redux::EnablingCondition<P2pState> for P2pReputationAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        match self {
+            P2pReputationAction::Penalize { peer_id, .. } => {
+                peer_id != &state.my_id() && !state.reputation.is_banned(peer_id, time)
+            }
+            P2pReputationAction::Ban { peer_id, .. } => peer_id != &state.my_id(),
+            P2pReputationAction::BanNotifyFinish { peer_id } => {
+                state.reputation.is_ban_notify_pending(peer_id)
+            }
+            P2pReputationAction::Unban { peer_id } => state.reputation.get(peer_id).is_some(),
+        }
+    }
+}
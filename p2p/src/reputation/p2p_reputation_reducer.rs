@@ -0,0 +1,150 @@
+#[cfg(feature = "p2p-libp2p")]
+use mina_p2p_messages::{
+    rpc,
+    rpc_kernel::{NeedsLength, QueryHeader, QueryPayload, RpcMethod},
+};
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+#[cfg(feature = "p2p-libp2p")]
+use redux::Timestamp;
+
+#[cfg(feature = "p2p-libp2p")]
+use crate::{channels::rpc::P2pRpcId, Data, P2pNetworkRpcAction};
+use crate::{
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    P2pState,
+};
+
+use super::{
+    P2pBanReason, P2pReputationAction, P2pReputationState, P2P_REPUTATION_BAN_DURATION,
+    P2P_REPUTATION_BAN_THRESHOLD,
+};
+
+impl P2pReputationState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<&P2pReputationAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+
+        match action {
+            P2pReputationAction::Penalize { peer_id, reason } => {
+                let score = p2p_state
+                    .reputation
+                    .penalize(*peer_id, *reason, meta.time());
+
+                if score <= P2P_REPUTATION_BAN_THRESHOLD {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pReputationAction::Ban {
+                        peer_id: *peer_id,
+                        reason: P2pBanReason::ScoreTooLow(*reason),
+                        duration: P2P_REPUTATION_BAN_DURATION,
+                    });
+                }
+                Ok(())
+            }
+            P2pReputationAction::Ban {
+                peer_id,
+                reason,
+                duration,
+            } => {
+                p2p_state
+                    .reputation
+                    .ban(*peer_id, reason.clone(), *duration, meta.time());
+
+                // Peer might already be disconnecting if the penalty was
+                // caused by the disconnection itself. In that case the ban
+                // only prevents it from reconnecting.
+                let Some(peer) = p2p_state
+                    .peers
+                    .get(peer_id)
+                    .filter(|peer| peer.status.is_connected_or_connecting())
+                else {
+                    return Ok(());
+                };
+
+                // Libp2p peer is sent `ban_notify` first and disconnected
+                // once it responds, or after a timeout, so that the
+                // notification gets flushed before the connection is closed.
+                #[cfg(feature = "p2p-libp2p")]
+                if peer.is_libp2p() {
+                    if let Some(ready) = p2p_state.get_ready_peer_mut(peer_id) {
+                        let id = ready.channels.take_next_local_rpc_id();
+                        let banned_until = p2p_state
+                            .reputation
+                            .get(peer_id)
+                            .and_then(|r| r.ban.as_ref())
+                            .map_or(meta.time(), |ban| ban.banned_until);
+                        let (query, data) = ban_notify_into_libp2p(id, banned_until);
+                        p2p_state.reputation.ban_notify_sent(*peer_id, meta.time());
+
+                        let dispatcher = state_context.into_dispatcher();
+                        dispatcher.push(P2pNetworkRpcAction::OutgoingQuery {
+                            peer_id: *peer_id,
+                            query,
+                            data,
+                        });
+                        return Ok(());
+                    }
+                }
+                #[cfg(not(feature = "p2p-libp2p"))]
+                let _ = peer;
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pDisconnectionAction::Init {
+                    peer_id: *peer_id,
+                    reason: P2pDisconnectionReason::Banned,
+                });
+                Ok(())
+            }
+            P2pReputationAction::BanNotifyFinish { peer_id } => {
+                p2p_state.reputation.ban_notify_finish(peer_id);
+
+                let is_connected = p2p_state
+                    .peers
+                    .get(peer_id)
+                    .map_or(false, |peer| peer.status.is_connected_or_connecting());
+                if !is_connected {
+                    return Ok(());
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pDisconnectionAction::Init {
+                    peer_id: *peer_id,
+                    reason: P2pDisconnectionReason::Banned,
+                });
+                Ok(())
+            }
+            P2pReputationAction::Unban { peer_id } => {
+                p2p_state.reputation.unban(peer_id);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "p2p-libp2p")]
+fn ban_notify_into_libp2p(id: P2pRpcId, banned_until: Timestamp) -> (QueryHeader, Data) {
+    use binprot::BinProtWrite;
+
+    type Method = rpc::BanNotifyV1;
+    type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+    let banned_until = u64::from(banned_until) as f64 / 1_000_000_000.0;
+    let mut v = vec![];
+    <Payload as BinProtWrite>::binprot_write(&NeedsLength(banned_until.into()), &mut v)
+        .unwrap_or_default();
+    (
+        QueryHeader {
+            tag: Method::NAME.into(),
+            version: Method::VERSION,
+            id,
+        },
+        v.into(),
+    )
+}
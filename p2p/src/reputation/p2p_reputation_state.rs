@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::PeerId;
+
+/// Peer gets banned once its score drops to or below this value.
+pub const P2P_REPUTATION_BAN_THRESHOLD: i32 = -100;
+/// For how long a peer stays banned after reaching the ban threshold.
+pub const P2P_REPUTATION_BAN_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+/// For how long to wait for the banned peer to acknowledge `ban_notify`
+/// before disconnecting it anyway.
+pub const P2P_REPUTATION_BAN_NOTIFY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Score recovers by one point per this interval, up to neutral (zero).
+const SCORE_RECOVERY_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pReputationState {
+    peers: BTreeMap<PeerId, P2pPeerReputation>,
+    /// Banned peers which were sent `ban_notify` and are waiting to be
+    /// disconnected, with the time the notification was sent.
+    #[serde(default)]
+    ban_notify_pending: BTreeMap<PeerId, Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pPeerReputation {
+    /// Score as of `updated_at`. Use [`P2pPeerReputation::score_at`] to get
+    /// the current score, which accounts for recovery over time.
+    pub score: i32,
+    pub updated_at: Timestamp,
+    pub ban: Option<P2pPeerBan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pPeerBan {
+    pub reason: P2pBanReason,
+    pub banned_at: Timestamp,
+    pub banned_until: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum P2pPenaltyReason {
+    #[error("sent invalid block")]
+    InvalidBlock,
+    #[error("sent snark which failed verification")]
+    SnarkVerifyFailed,
+    #[error("sent invalid ledger answer")]
+    InvalidLedgerAnswer,
+    #[error("rpc timeout")]
+    RpcTimeout,
+    #[error("protocol violation")]
+    ProtocolViolation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum P2pBanReason {
+    #[error("banned manually")]
+    Manual,
+    #[error("score too low, last penalty: {0}")]
+    ScoreTooLow(P2pPenaltyReason),
+}
+
+impl P2pPenaltyReason {
+    /// Amount by which the peer's score is lowered.
+    pub fn penalty(&self) -> i32 {
+        match self {
+            Self::InvalidBlock => 100,
+            Self::SnarkVerifyFailed => 50,
+            Self::InvalidLedgerAnswer => 25,
+            Self::ProtocolViolation => 20,
+            Self::RpcTimeout => 5,
+        }
+    }
+}
+
+impl P2pPeerReputation {
+    fn new(now: Timestamp) -> Self {
+        Self {
+            score: 0,
+            updated_at: now,
+            ban: None,
+        }
+    }
+
+    pub fn score_at(&self, now: Timestamp) -> i32 {
+        let recovered = now
+            .checked_sub(self.updated_at)
+            .map_or(0, |elapsed| {
+                elapsed.as_secs() / SCORE_RECOVERY_INTERVAL.as_secs()
+            })
+            .min(i32::MAX as u64) as i32;
+        self.score.saturating_add(recovered).min(0)
+    }
+
+    pub fn is_banned(&self, now: Timestamp) -> bool {
+        self.ban
+            .as_ref()
+            .map_or(false, |ban| now < ban.banned_until)
+    }
+
+    /// Whether this entry carries no information anymore and can be dropped.
+    fn is_neutral(&self, now: Timestamp) -> bool {
+        !self.is_banned(now) && self.score_at(now) == 0
+    }
+}
+
+impl P2pReputationState {
+    pub fn get(&self, peer_id: &PeerId) -> Option<&P2pPeerReputation> {
+        self.peers.get(peer_id)
+    }
+
+    pub fn score(&self, peer_id: &PeerId, now: Timestamp) -> i32 {
+        self.peers.get(peer_id).map_or(0, |r| r.score_at(now))
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId, now: Timestamp) -> bool {
+        self.peers.get(peer_id).map_or(false, |r| r.is_banned(now))
+    }
+
+    /// Currently active bans.
+    pub fn bans(&self, now: Timestamp) -> impl '_ + Iterator<Item = (&PeerId, &P2pPeerBan)> {
+        self.peers
+            .iter()
+            .filter(move |(_, r)| r.is_banned(now))
+            .filter_map(|(peer_id, r)| Some((peer_id, r.ban.as_ref()?)))
+    }
+
+    pub fn is_ban_notify_pending(&self, peer_id: &PeerId) -> bool {
+        self.ban_notify_pending.contains_key(peer_id)
+    }
+
+    /// Banned peers which didn't acknowledge `ban_notify` in time.
+    pub fn ban_notify_timeouts(&self, now: Timestamp) -> impl '_ + Iterator<Item = PeerId> {
+        self.ban_notify_pending
+            .iter()
+            .filter(move |(_, sent_at)| {
+                now.checked_sub(**sent_at)
+                    .map_or(false, |d| d >= P2P_REPUTATION_BAN_NOTIFY_TIMEOUT)
+            })
+            .map(|(peer_id, _)| *peer_id)
+    }
+
+    /// Lowers the score of the peer, returns the resulting score.
+    pub(super) fn penalize(
+        &mut self,
+        peer_id: PeerId,
+        reason: P2pPenaltyReason,
+        now: Timestamp,
+    ) -> i32 {
+        self.peers.retain(|_, r| !r.is_neutral(now));
+
+        let reputation = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| P2pPeerReputation::new(now));
+        reputation.score = reputation.score_at(now).saturating_sub(reason.penalty());
+        reputation.updated_at = now;
+        reputation.score
+    }
+
+    pub(super) fn ban(
+        &mut self,
+        peer_id: PeerId,
+        reason: P2pBanReason,
+        duration: Duration,
+        now: Timestamp,
+    ) {
+        let banned_until =
+            Timestamp::new(u64::from(now).saturating_add(duration.as_nanos() as u64));
+        let reputation = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| P2pPeerReputation::new(now));
+        reputation.ban = Some(P2pPeerBan {
+            reason,
+            banned_at: now,
+            banned_until,
+        });
+    }
+
+    pub(super) fn unban(&mut self, peer_id: &PeerId) {
+        self.peers.remove(peer_id);
+        self.ban_notify_pending.remove(peer_id);
+    }
+
+    pub(super) fn ban_notify_sent(&mut self, peer_id: PeerId, now: Timestamp) {
+        self.ban_notify_pending.insert(peer_id, now);
+    }
+
+    pub(super) fn ban_notify_finish(&mut self, peer_id: &PeerId) {
+        self.ban_notify_pending.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Timestamp {
+        Timestamp::new(secs * 1_000_000_000)
+    }
+
+    #[test]
+    fn score_recovers_over_time() {
+        let peer_id = PeerId::from_bytes([1; 32]);
+        let mut state = P2pReputationState::default();
+
+        let score = state.penalize(peer_id, P2pPenaltyReason::SnarkVerifyFailed, at(0));
+        assert_eq!(score, -50);
+        assert_eq!(state.score(&peer_id, at(59)), -50);
+        assert_eq!(state.score(&peer_id, at(60)), -49);
+        assert_eq!(state.score(&peer_id, at(60 * 30)), -20);
+        // Doesn't recover above neutral.
+        assert_eq!(state.score(&peer_id, at(60 * 1000)), 0);
+
+        // Penalty applies on top of the recovered score.
+        let score = state.penalize(peer_id, P2pPenaltyReason::RpcTimeout, at(60 * 30));
+        assert_eq!(score, -25);
+    }
+
+    #[test]
+    fn neutral_peers_are_dropped() {
+        let peer_a = PeerId::from_bytes([1; 32]);
+        let peer_b = PeerId::from_bytes([2; 32]);
+        let mut state = P2pReputationState::default();
+
+        state.penalize(peer_a, P2pPenaltyReason::RpcTimeout, at(0));
+        state.penalize(peer_b, P2pPenaltyReason::RpcTimeout, at(60 * 5));
+        assert!(state.get(&peer_a).is_none());
+        assert!(state.get(&peer_b).is_some());
+    }
+
+    #[test]
+    fn ban_threshold() {
+        let peer_id = PeerId::from_bytes([1; 32]);
+        let mut state = P2pReputationState::default();
+
+        let score = state.penalize(peer_id, P2pPenaltyReason::SnarkVerifyFailed, at(0));
+        assert!(score > P2P_REPUTATION_BAN_THRESHOLD);
+        // Recovery in between keeps the peer above the threshold.
+        let score = state.penalize(peer_id, P2pPenaltyReason::SnarkVerifyFailed, at(60));
+        assert_eq!(score, -99);
+        assert!(score > P2P_REPUTATION_BAN_THRESHOLD);
+        let score = state.penalize(peer_id, P2pPenaltyReason::RpcTimeout, at(60));
+        assert!(score <= P2P_REPUTATION_BAN_THRESHOLD);
+
+        // Single invalid block reaches the threshold.
+        let other = PeerId::from_bytes([2; 32]);
+        let score = state.penalize(other, P2pPenaltyReason::InvalidBlock, at(0));
+        assert!(score <= P2P_REPUTATION_BAN_THRESHOLD);
+    }
+
+    #[test]
+    fn ban_expires() {
+        let peer_id = PeerId::from_bytes([1; 32]);
+        let mut state = P2pReputationState::default();
+
+        let duration = Duration::from_secs(60);
+        state.ban(peer_id, P2pBanReason::Manual, duration, at(10));
+        assert!(state.is_banned(&peer_id, at(10)));
+        assert!(state.is_banned(&peer_id, at(69)));
+        assert!(!state.is_banned(&peer_id, at(70)));
+        assert_eq!(state.bans(at(20)).count(), 1);
+        assert_eq!(state.bans(at(70)).count(), 0);
+
+        // Banned peer isn't dropped while the ban is active.
+        state.penalize(
+            PeerId::from_bytes([2; 32]),
+            P2pPenaltyReason::RpcTimeout,
+            at(20),
+        );
+        assert!(state.get(&peer_id).is_some());
+
+        state.unban(&peer_id);
+        assert!(!state.is_banned(&peer_id, at(20)));
+    }
+
+    #[test]
+    fn ban_notify_timeout() {
+        let peer_id = PeerId::from_bytes([1; 32]);
+        let mut state = P2pReputationState::default();
+
+        state.ban(
+            peer_id,
+            P2pBanReason::Manual,
+            Duration::from_secs(60),
+            at(10),
+        );
+        state.ban_notify_sent(peer_id, at(10));
+        assert!(state.is_ban_notify_pending(&peer_id));
+        assert_eq!(state.ban_notify_timeouts(at(11)).count(), 0);
+        assert_eq!(
+            state.ban_notify_timeouts(at(12)).collect::<Vec<_>>(),
+            [peer_id]
+        );
+
+        state.ban_notify_finish(&peer_id);
+        assert!(!state.is_ban_notify_pending(&peer_id));
+        assert_eq!(state.ban_notify_timeouts(at(12)).count(), 0);
+    }
+}
@@ -0,0 +1,42 @@
+use std::{io, path::PathBuf, time::Duration};
+
+use openmina_core::file_writer::PeriodicFileWriter;
+
+use crate::address_book::{P2pAddressBook, P2pAddressBookEntry};
+
+/// How often the address book is written to the disk.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Address book persisted as a JSON file.
+pub struct P2pAddressBookStorage {
+    writer: PeriodicFileWriter<Vec<P2pAddressBookEntry>>,
+}
+
+impl P2pAddressBookStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            writer: PeriodicFileWriter::new(
+                path,
+                "address-book",
+                SAVE_INTERVAL,
+                |entries: &Vec<P2pAddressBookEntry>| Ok(serde_json::to_vec_pretty(entries)?),
+            ),
+        }
+    }
+
+    /// Loads entries saved by the previous run.
+    pub fn load(&self) -> io::Result<Vec<P2pAddressBookEntry>> {
+        let Some(bytes) = self.writer.read()? else {
+            return Ok(Vec::new());
+        };
+        serde_json::from_slice(&bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the address book in the background thread, unless it was
+    /// written less than [`SAVE_INTERVAL`] ago.
+    pub fn save_if_due(&mut self, address_book: &P2pAddressBook) {
+        self.writer
+            .write_if_due(|| address_book.iter().cloned().collect());
+    }
+}
@@ -3,6 +3,8 @@ pub mod mio;
 #[cfg(feature = "p2p-webrtc")]
 pub mod webrtc;
 pub mod webrtc_with_libp2p;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod address_book;
 
 use std::future::Future;
 
@@ -1,4 +1,5 @@
 use crate::{
+    address_book::P2pAddressBook,
     channels::{ChannelId, ChannelMsg, MsgId, P2pChannelsService},
     connection::{outgoing::P2pConnectionOutgoingInitOpts, P2pConnectionService},
     disconnection_effectful::P2pDisconnectionService,
@@ -11,6 +12,8 @@ use super::mio::MioService;
 #[cfg(feature = "p2p-libp2p")]
 use crate::{P2pMioService, P2pNetworkService, P2pNetworkServiceError};
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::address_book::P2pAddressBookStorage;
 use super::{webrtc::P2pServiceWebrtc, TaskSpawner};
 
 pub struct P2pServiceCtx {
@@ -18,6 +21,8 @@ pub struct P2pServiceCtx {
     pub webrtc: super::webrtc::P2pServiceCtx,
     #[cfg(feature = "p2p-libp2p")]
     pub mio: MioService,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub address_book: Option<P2pAddressBookStorage>,
 }
 
 pub trait P2pServiceWebrtcWithLibp2p: P2pServiceWebrtc {
@@ -30,9 +35,16 @@ pub trait P2pServiceWebrtcWithLibp2p: P2pServiceWebrtc {
             #[cfg(feature = "p2p-libp2p")]
             mio: MioService::pending(sec_key.clone().try_into().expect("valid keypair")),
             webrtc: <Self as P2pServiceWebrtc>::init(sec_key, spawner),
+            #[cfg(not(target_arch = "wasm32"))]
+            address_book: None,
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn address_book_storage(&mut self) -> Option<&mut P2pAddressBookStorage> {
+        None
+    }
+
     #[cfg(feature = "p2p-libp2p")]
     fn resolve_name(
         &mut self,
@@ -109,6 +121,15 @@ impl<T: P2pServiceWebrtcWithLibp2p> P2pConnectionService for T {
     fn http_signaling_request(&mut self, url: String, offer: crate::webrtc::Offer) {
         P2pServiceWebrtc::http_signaling_request(self, url, offer)
     }
+
+    fn address_book_save(&mut self, address_book: &P2pAddressBook) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(storage) = P2pServiceWebrtcWithLibp2p::address_book_storage(self) {
+            storage.save_if_due(address_book);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = address_book;
+    }
 }
 
 impl<T: P2pServiceWebrtcWithLibp2p> P2pDisconnectionService for T {
@@ -179,6 +200,8 @@ impl P2pServiceCtx {
                 cmd_sender: mpsc::unbounded_channel().0,
                 peers: Default::default(),
             },
+            #[cfg(not(target_arch = "wasm32"))]
+            address_book: None,
         }
     }
 }
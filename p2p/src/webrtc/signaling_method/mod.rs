@@ -7,10 +7,17 @@ use binprot_derive::{BinProtRead, BinProtWrite};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::PeerId;
+
 #[derive(BinProtWrite, BinProtRead, Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
 pub enum SignalingMethod {
     Http(HttpSignalingInfo),
     Https(HttpSignalingInfo),
+    /// Offer and answer are relayed over the signaling relay channel
+    /// through a peer, which is connected to both sides.
+    P2p {
+        relay_peer_id: PeerId,
+    },
 }
 
 impl SignalingMethod {
@@ -20,7 +27,7 @@ impl SignalingMethod {
         let (http, info) = match self {
             Self::Http(info) => ("http", info),
             Self::Https(info) => ("https", info),
-            // _ => return None,
+            Self::P2p { .. } => return None,
         };
         Some(format!(
             "{http}://{}:{}/mina/webrtc/signal",
@@ -40,6 +47,9 @@ impl fmt::Display for SignalingMethod {
                 write!(f, "/https")?;
                 signaling.fmt(f)
             }
+            Self::P2p { relay_peer_id } => {
+                write!(f, "/p2p/{relay_peer_id}")
+            }
         }
     }
 }
@@ -54,6 +64,8 @@ pub enum SignalingMethodParseError {
     HostParseError(String),
     #[error("host parse error: {0}")]
     PortParseError(String),
+    #[error("relay peer id parse error: {0}")]
+    RelayPeerIdParseError(String),
 }
 
 impl FromStr for SignalingMethod {
@@ -73,6 +85,14 @@ impl FromStr for SignalingMethod {
         match &s[1..method_end_index] {
             "http" => Ok(Self::Http(s[method_end_index..].parse()?)),
             "https" => Ok(Self::Https(s[method_end_index..].parse()?)),
+            "p2p" => Ok(Self::P2p {
+                relay_peer_id: s[method_end_index..]
+                    .trim_matches('/')
+                    .parse::<PeerId>()
+                    .map_err(|err| {
+                        SignalingMethodParseError::RelayPeerIdParseError(err.to_string())
+                    })?,
+            }),
             method => Err(SignalingMethodParseError::UnknownSignalingMethod(
                 method.to_owned(),
             )),
@@ -98,3 +118,16 @@ impl<'de> serde::Deserialize<'de> for SignalingMethod {
         s.parse().map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn p2p_signaling_method_to_string_roundtrip() {
+        let s = "/p2p/2bEgBrPTzL8wov2D4Kz34WVLCxR4uCarsBmHYXWKQA5wvBQzd9H";
+        let method = s.parse::<SignalingMethod>().expect("should be parseable");
+        assert!(matches!(method, SignalingMethod::P2p { .. }));
+        assert_eq!(s, method.to_string());
+    }
+}
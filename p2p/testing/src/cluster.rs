@@ -359,7 +359,8 @@ impl Cluster {
             peer_discovery: config.discovery,
             timeouts: config.timeouts,
             limits: config.limits,
-            meshsub: P2pMeshsubConfig::default(),
+            meshsub: P2pMeshsubConfig::default().into(),
+            address_book: Vec::new(),
         };
 
         Ok((config, secret_key))
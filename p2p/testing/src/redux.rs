@@ -14,6 +14,8 @@ use p2p::{
     channels::{
         best_tip::P2pChannelsBestTipAction, best_tip_effectful::P2pChannelsBestTipEffectfulAction,
         rpc::P2pChannelsRpcAction, rpc_effectful::P2pChannelsRpcEffectfulAction,
+        signaling_relay::P2pChannelsSignalingRelayAction,
+        signaling_relay_effectful::P2pChannelsSignalingRelayEffectfulAction,
         snark::P2pChannelsSnarkAction, snark_effectful::P2pChannelsSnarkEffectfulAction,
         snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
         snark_job_commitment_effectful::P2pChannelsSnarkJobCommitmentEffectfulAction,
@@ -283,11 +285,13 @@ impl_from_p2p!(P2pChannelsSnarkAction);
 impl_from_p2p!(p2p::P2pNetworkRpcAction);
 impl_from_p2p!(P2pChannelsRpcAction);
 impl_from_p2p!(P2pDisconnectionAction);
+impl_from_p2p!(p2p::reputation::P2pReputationAction);
 impl_from_p2p!(p2p::P2pNetworkSchedulerEffectfulAction);
 impl_from_p2p!(p2p::P2pNetworkPnetEffectfulAction);
 impl_from_p2p!(P2pChannelsBestTipAction);
 impl_from_p2p!(P2pChannelsSnarkJobCommitmentAction);
 impl_from_p2p!(P2pChannelsStreamingRpcAction);
+impl_from_p2p!(P2pChannelsSignalingRelayAction);
 impl_from_p2p!(P2pConnectionIncomingEffectfulAction);
 impl_from_p2p!(P2pConnectionOutgoingEffectfulAction);
 impl_from_p2p!(P2pDisconnectionEffectfulAction);
@@ -297,5 +301,6 @@ impl_from_p2p!(P2pChannelsTransactionEffectfulAction);
 impl_from_p2p!(P2pChannelsSnarkJobCommitmentEffectfulAction);
 impl_from_p2p!(P2pChannelsRpcEffectfulAction);
 impl_from_p2p!(P2pChannelsSnarkEffectfulAction);
+impl_from_p2p!(P2pChannelsSignalingRelayEffectfulAction);
 
 impl p2p::P2pActionTrait<State> for Action {}
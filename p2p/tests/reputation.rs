@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use mina_p2p_messages::rpc::BanNotifyV1;
+use mina_p2p_messages::rpc_kernel::RpcMethod;
+use p2p::{
+    disconnection::P2pDisconnectionReason,
+    reputation::{P2pBanReason, P2pReputationAction, P2P_REPUTATION_BAN_NOTIFY_TIMEOUT},
+    PeerId,
+};
+use p2p_testing::{
+    cluster::{Cluster, ClusterBuilder, ClusterEvent, NodeId, TimestampSource},
+    event::RustNodeEvent,
+    futures::StreamExt,
+    libp2p::swarm::SwarmEvent,
+    libp2p_node::{Libp2pBehaviourEvent, Libp2pNodeConfig, Libp2pNodeId},
+    libp2p_rpc_behaviour::{Event as RpcEvent, Received},
+    rust_node::{RustNodeConfig, RustNodeId},
+    stream::ClusterStreamExt,
+    utils::{peer_ids, wait_for_all_nodes_to_listen},
+};
+
+/// Connects a Rust node to a libp2p node and waits for the rpc channel
+/// between them to be ready.
+async fn rust_to_libp2p_rpc_ready() -> anyhow::Result<(Cluster, RustNodeId, Libp2pNodeId, PeerId)> {
+    let mut cluster = ClusterBuilder::default()
+        .ports_with_len(10)
+        .idle_duration(Duration::from_millis(100))
+        .total_duration(Duration::from_secs(30))
+        .start()
+        .await?;
+
+    let rust_node = cluster.add_rust_node(RustNodeConfig::default())?;
+    let libp2p_node = cluster.add_libp2p_node(Libp2pNodeConfig::default())?;
+    let [_, libp2p_peer_id] = peer_ids(
+        &cluster,
+        [NodeId::from(rust_node), NodeId::from(libp2p_node)],
+    );
+
+    let listening = wait_for_all_nodes_to_listen(
+        &mut cluster,
+        [NodeId::from(rust_node), NodeId::from(libp2p_node)],
+        Duration::from_secs(2),
+    )
+    .await;
+    assert!(listening, "nodes should be listening");
+
+    cluster.connect(rust_node, libp2p_node)?;
+
+    let rpc_ready = cluster
+        .stream()
+        .take_during(Duration::from_secs(5))
+        .any(|event| {
+            std::future::ready(matches!(
+                event,
+                ClusterEvent::Rust {
+                    id,
+                    event: RustNodeEvent::RpcChannelReady { peer_id },
+                } if id == rust_node && peer_id == libp2p_peer_id
+            ))
+        })
+        .await;
+    assert!(rpc_ready, "rpc channel should be ready");
+
+    Ok((cluster, rust_node, libp2p_node, libp2p_peer_id))
+}
+
+/// Bans the libp2p peer and runs the cluster until the Rust node disconnects
+/// it, answering `ban_notify` if `respond` is set. Returns how long it took
+/// for the peer to be disconnected.
+async fn ban_and_wait_for_disconnection(respond: bool) -> anyhow::Result<Duration> {
+    let (mut cluster, rust_node, libp2p_node, peer_id) = rust_to_libp2p_rpc_ready().await?;
+
+    let expected_query_id = cluster
+        .rust_node(rust_node)
+        .state()
+        .get_ready_peer(&peer_id)
+        .expect("peer should be ready")
+        .channels
+        .next_local_rpc_id();
+
+    let banned_at = cluster.timestamp();
+    assert!(cluster
+        .rust_node_mut(rust_node)
+        .dispatch_action(P2pReputationAction::Ban {
+            peer_id,
+            reason: P2pBanReason::Manual,
+            duration: Duration::from_secs(60),
+        }));
+
+    let mut notified = false;
+    let mut disconnected = false;
+    while cluster.timestamp() - banned_at < Duration::from_secs(10) {
+        let Some(event) = cluster.next().await else {
+            break;
+        };
+        match event {
+            ClusterEvent::Libp2p {
+                id,
+                event:
+                    SwarmEvent::Behaviour(Libp2pBehaviourEvent::Rpc((
+                        rust_peer_id,
+                        RpcEvent::Stream {
+                            stream_id,
+                            received: Received::Query { header, .. },
+                        },
+                    ))),
+            } if id == libp2p_node && header.tag.as_ref() == BanNotifyV1::NAME => {
+                assert!(!notified, "ban_notify should be sent once");
+                assert_eq!(
+                    header.id, expected_query_id,
+                    "ban_notify should use the next local rpc id"
+                );
+                notified = true;
+                if respond {
+                    cluster
+                        .libp2p_node_mut(libp2p_node)
+                        .swarm_mut()
+                        .behaviour_mut()
+                        .rpc
+                        .respond::<BanNotifyV1>(rust_peer_id, stream_id, header.id, Ok(()))?;
+                }
+            }
+            ClusterEvent::Rust {
+                id,
+                event: RustNodeEvent::PeerDisconnected { peer_id: p, reason },
+            } if id == rust_node && p == peer_id => {
+                assert_eq!(reason, P2pDisconnectionReason::Banned.to_string());
+                disconnected = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    assert!(notified, "banned peer should be sent ban_notify");
+    assert!(disconnected, "banned peer should be disconnected");
+    let state = cluster.rust_node(rust_node).state();
+    assert!(!state.reputation.is_ban_notify_pending(&peer_id));
+
+    Ok(cluster.timestamp() - banned_at)
+}
+
+#[tokio::test]
+async fn ban_disconnects_after_notify_response() -> anyhow::Result<()> {
+    let elapsed = ban_and_wait_for_disconnection(true).await?;
+    assert!(
+        elapsed < P2P_REPUTATION_BAN_NOTIFY_TIMEOUT,
+        "peer should be disconnected once it responds, took {elapsed:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn ban_disconnects_after_notify_timeout() -> anyhow::Result<()> {
+    let elapsed = ban_and_wait_for_disconnection(false).await?;
+    assert!(
+        elapsed >= P2P_REPUTATION_BAN_NOTIFY_TIMEOUT,
+        "peer should be disconnected after the timeout, took {elapsed:?}"
+    );
+    Ok(())
+}
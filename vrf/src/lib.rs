@@ -118,8 +118,9 @@ pub fn genesis_vrf(epoch_seed: EpochSeed) -> VrfResult<VrfOutput> {
     calculate_vrf(&genesis_keypair, epoch_seed, 0, &AccountIndex(0))
 }
 
-/// Calculates the VRF output
-fn calculate_vrf(
+/// Calculates the VRF output. This is the only step of the evaluation
+/// which needs the producer's secret key.
+pub fn calculate_vrf(
     producer_key: &Keypair,
     epoch_seed: EpochSeed,
     global_slot: u32,
@@ -149,12 +150,34 @@ pub fn evaluate_vrf(vrf_input: VrfEvaluationInput) -> VrfResult<VrfEvaluationOut
 
     let vrf_output = calculate_vrf(&producer_key, epoch_seed, global_slot, &delegator_index)?;
 
+    Ok(evaluate_vrf_output(
+        producer_key.public.into(),
+        vrf_output,
+        account_pub_key,
+        global_slot,
+        delegator_index,
+        delegated_stake,
+        total_currency,
+    ))
+}
+
+/// Checks if the already calculated `vrf_output` wins the slot, e.g. when
+/// the output was calculated by an external signer holding the producer key.
+pub fn evaluate_vrf_output(
+    producer: AccountPublicKey,
+    vrf_output: VrfOutput,
+    account_pub_key: AccountPublicKey,
+    global_slot: u32,
+    delegator_index: AccountIndex,
+    delegated_stake: BigInt,
+    total_currency: BigInt,
+) -> VrfEvaluationOutput {
     let value = vrf_output.truncated().into_repr();
     let threshold = Threshold::new(delegated_stake, total_currency);
 
     if threshold.threshold_met(value) {
-        Ok(VrfEvaluationOutput::SlotWon(VrfWonSlot {
-            producer: producer_key.public.into(),
+        VrfEvaluationOutput::SlotWon(VrfWonSlot {
+            producer,
             vrf_output: Box::new(vrf_output),
             winner_account: account_pub_key,
             global_slot,
@@ -165,9 +188,9 @@ pub fn evaluate_vrf(vrf_input: VrfEvaluationInput) -> VrfResult<VrfEvaluationOut
                     threshold.threshold_rational.to_f64()?,
                 ))
             }),
-        }))
+        })
     } else {
-        Ok(VrfEvaluationOutput::SlotLost(global_slot))
+        VrfEvaluationOutput::SlotLost(global_slot)
     }
 }
 